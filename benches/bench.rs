@@ -72,17 +72,23 @@ fn actson_benchmark(c: &mut Criterion) {
         })
     });
 
+    c.bench_function("actson_validate_large", |b| {
+        b.iter(|| {
+            actson::validate(json_large_bytes).unwrap();
+        })
+    });
+
     #[cfg(feature = "serde_json")]
     c.bench_function("actson_serde", |b| {
         b.iter(|| {
-            actson::serde_json::from_slice(json_bytes).unwrap();
+            actson::serde_json::from_slice::<serde_json::Value>(json_bytes).unwrap();
         })
     });
 
     #[cfg(feature = "serde_json")]
     c.bench_function("actson_serde_large", |b| {
         b.iter(|| {
-            actson::serde_json::from_slice(json_large_bytes).unwrap();
+            actson::serde_json::from_slice::<serde_json::Value>(json_large_bytes).unwrap();
         })
     });
 
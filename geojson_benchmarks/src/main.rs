@@ -42,6 +42,7 @@ struct RunArgs {
     input: String,
 }
 
+#[allow(dead_code)]
 #[derive(Serialize)]
 struct BenchmarkFileResult<'a> {
     filename: String,
@@ -29,6 +29,7 @@ pub struct Geometry {
     coordinates: Vec<Vec<Vec<f64>>>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize)]
 #[serde(untagged)]
 pub enum PropertyValue {
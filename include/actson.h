@@ -0,0 +1,60 @@
+/*
+ * C API of the Actson JSON parser. Build the library with the `ffi`
+ * feature enabled (e.g. `cargo rustc --release --features ffi
+ * --crate-type cdylib`). See the documentation of the `ffi` module for
+ * details.
+ */
+#ifndef ACTSON_H
+#define ACTSON_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Events returned by actson_parser_next_event() */
+#define ACTSON_EVENT_NEED_MORE_INPUT 0
+#define ACTSON_EVENT_START_OBJECT 1
+#define ACTSON_EVENT_END_OBJECT 2
+#define ACTSON_EVENT_START_ARRAY 3
+#define ACTSON_EVENT_END_ARRAY 4
+#define ACTSON_EVENT_FIELD_NAME 5
+#define ACTSON_EVENT_VALUE_STRING 6
+#define ACTSON_EVENT_VALUE_INT 7
+#define ACTSON_EVENT_VALUE_FLOAT 8
+#define ACTSON_EVENT_VALUE_TRUE 9
+#define ACTSON_EVENT_VALUE_FALSE 10
+#define ACTSON_EVENT_VALUE_NULL 11
+
+/* The end of the JSON text has been reached */
+#define ACTSON_END (-1)
+
+/* The JSON text is invalid (see actson_parser_error()) */
+#define ACTSON_ERROR (-2)
+
+typedef struct ActsonParser ActsonParser;
+
+ActsonParser *actson_parser_new(void);
+void actson_parser_free(ActsonParser *parser);
+
+size_t actson_parser_feed(ActsonParser *parser, const uint8_t *buf, size_t len);
+bool actson_parser_is_full(ActsonParser *parser);
+void actson_parser_done(ActsonParser *parser);
+
+int actson_parser_next_event(ActsonParser *parser);
+const char *actson_parser_error(ActsonParser *parser);
+
+const uint8_t *actson_parser_current_str(ActsonParser *parser, size_t *len);
+bool actson_parser_current_int(ActsonParser *parser, int64_t *value);
+bool actson_parser_current_float(ActsonParser *parser, double *value);
+void actson_parser_current_span(ActsonParser *parser, size_t *start, size_t *end);
+size_t actson_parser_parsed_bytes(ActsonParser *parser);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ACTSON_H */
@@ -0,0 +1,203 @@
+//! Decode base64-encoded string values incrementally.
+//!
+//! [`Base64Decoder`] accepts base64 text in chunks of arbitrary size and
+//! writes the decoded bytes to an [`io::Write`], so binary data embedded in
+//! a JSON text can be streamed to a file without holding the decoded bytes
+//! in memory. Use
+//! [`JsonParser::current_base64_to()`](crate::JsonParser::current_base64_to())
+//! to decode the current string value. Note that the parser still collects
+//! the complete base64 text of a string before reporting it.
+//!
+//! ```
+//! use std::io::Write;
+//!
+//! use actson::base64::Base64Decoder;
+//!
+//! let mut decoder = Base64Decoder::new(Vec::new());
+//! decoder.write_all(b"SGVsbG8g").unwrap();
+//! decoder.write_all(b"V29y").unwrap();
+//! decoder.write_all(b"bGQ=").unwrap();
+//! assert_eq!(decoder.finish().unwrap(), b"Hello World");
+//! ```
+
+use std::io::{self, Write};
+
+/// Marks bytes that are not part of the base64 alphabet in [`DECODE_TABLE`]
+const INVALID: u8 = 0xFF;
+
+/// Maps ASCII characters of the standard base64 alphabet to their values
+const DECODE_TABLE: [u8; 256] = {
+    let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < alphabet.len() {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// An incremental decoder for base64 text (standard alphabet, with or
+/// without padding) that writes the decoded bytes to an [`io::Write`].
+///
+/// The decoder implements [`io::Write`] itself, so base64 text can be
+/// written to it with [`Write::write_all()`] or [`io::copy()`]. Call
+/// [`Self::finish()`] at the end to decode the last group of characters.
+/// Invalid characters result in an error of kind
+/// [`io::ErrorKind::InvalidData`].
+#[derive(Debug)]
+pub struct Base64Decoder<W> {
+    writer: W,
+
+    /// Characters of the current (incomplete) group of four
+    group: [u8; 4],
+
+    /// The number of characters in [`Self::group`]
+    group_len: usize,
+
+    /// `true` if padding has been seen, which means that no more
+    /// characters except for further padding are allowed
+    padded: bool,
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<W> Base64Decoder<W>
+where
+    W: Write,
+{
+    /// Create a new decoder that writes decoded bytes to the given writer
+    pub fn new(writer: W) -> Self {
+        Base64Decoder {
+            writer,
+            group: [0; 4],
+            group_len: 0,
+            padded: false,
+        }
+    }
+
+    /// Decode the given chunk of base64 text
+    pub fn decode_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let mut out = [0u8; 768];
+        let mut out_len = 0;
+        for &c in chunk {
+            if c == b'=' {
+                // padding is only allowed after two or three characters
+                if !self.padded && self.group_len < 2 {
+                    return Err(invalid_data("unexpected base64 padding"));
+                }
+                if !self.padded {
+                    out_len += self.decode_group(&mut out[out_len..]);
+                    self.padded = true;
+                }
+                continue;
+            }
+            if self.padded {
+                return Err(invalid_data("base64 data after padding"));
+            }
+            let v = DECODE_TABLE[c as usize];
+            if v == INVALID {
+                return Err(invalid_data("invalid base64 character"));
+            }
+            self.group[self.group_len] = v;
+            self.group_len += 1;
+            if self.group_len == 4 {
+                out_len += self.decode_group(&mut out[out_len..]);
+                if out_len + 3 > out.len() {
+                    self.writer.write_all(&out[..out_len])?;
+                    out_len = 0;
+                }
+            }
+        }
+        self.writer.write_all(&out[..out_len])
+    }
+
+    /// Decode the characters in [`Self::group`] to `out` and return the
+    /// number of decoded bytes
+    fn decode_group(&mut self, out: &mut [u8]) -> usize {
+        let g = &self.group;
+        let n = (g[0] as u32) << 18 | (g[1] as u32) << 12 | (g[2] as u32) << 6 | g[3] as u32;
+        let len = self.group_len.saturating_sub(1);
+        out[..len].copy_from_slice(&n.to_be_bytes()[1..1 + len]);
+        self.group = [0; 4];
+        self.group_len = 0;
+        len
+    }
+
+    /// Decode the remaining characters (if the text was not padded), flush
+    /// the writer, and return it
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.group_len == 1 {
+            return Err(invalid_data("incomplete base64 data"));
+        }
+        if self.group_len > 0 {
+            let mut out = [0u8; 3];
+            let len = self.decode_group(&mut out);
+            self.writer.write_all(&out[..len])?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W> Write for Base64Decoder<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.decode_chunk(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Base64Decoder;
+
+    fn decode_in_chunks(text: &[u8], chunk_size: usize) -> std::io::Result<Vec<u8>> {
+        let mut decoder = Base64Decoder::new(Vec::new());
+        for chunk in text.chunks(chunk_size) {
+            decoder.decode_chunk(chunk)?;
+        }
+        decoder.finish()
+    }
+
+    /// Test that base64 text is decoded correctly regardless of how it is
+    /// split into chunks
+    #[test]
+    fn chunks() {
+        let cases: [(&[u8], &[u8]); 7] = [
+            (b"", b""),
+            (b"Zg==", b"f"),
+            (b"Zm8=", b"fo"),
+            (b"Zm9v", b"foo"),
+            (b"Zm9vYg", b"foob"),
+            (b"Zm9vYmE", b"fooba"),
+            (b"+/+/", &[0xFB, 0xFF, 0xBF]),
+        ];
+        for (text, expected) in cases {
+            for chunk_size in 1..=5 {
+                assert_eq!(decode_in_chunks(text, chunk_size).unwrap(), expected);
+            }
+        }
+
+        // more data than fits into the decoder's output buffer
+        let text = b"AAECAwQF".repeat(200);
+        let expected = [0, 1, 2, 3, 4, 5].repeat(200);
+        assert_eq!(decode_in_chunks(&text, 1000).unwrap(), expected);
+    }
+
+    /// Test that invalid base64 text is rejected
+    #[test]
+    fn invalid() {
+        for text in [&b"Z"[..], b"Z===", b"Zg==Zg==", b"Zm9v!", b"Zm 9v", b"=Zm9"] {
+            assert!(decode_in_chunks(text, 2).is_err());
+        }
+    }
+}
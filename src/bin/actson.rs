@@ -0,0 +1,208 @@
+//! Command-line tool for validating, formatting, and converting JSON. All
+//! subcommands read from stdin and write to stdout in a streaming fashion,
+//! so inputs of arbitrary size can be processed with constant memory.
+
+use std::io::{self, BufReader, StdinLock, StdoutLock, Write};
+use std::process::ExitCode;
+
+use actson::emitter::{EmitterError, EmitterOptionsBuilder, Indent, JsonEmitter, NdjsonWriter};
+use actson::feeder::{BufReaderJsonFeeder, FillJsonFeeder, JsonFeeder};
+use actson::options::JsonParserOptionsBuilder;
+use actson::sink::{parse_with_parser, JsonEventSink, ParseWithError};
+use actson::storage::ParserStorage;
+use actson::{JsonEvent, JsonParser};
+use clap::{Parser, Subcommand};
+use thiserror::Error;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that the input is a valid JSON text
+    Validate,
+
+    /// Pretty-print the input
+    Pretty {
+        /// The number of spaces per indentation level
+        #[arg(long, default_value_t = 2)]
+        indent: usize,
+    },
+
+    /// Remove insignificant whitespace from the input
+    Minify,
+
+    /// Convert a top-level array to newline-delimited JSON with one element
+    /// per line
+    ToNdjson,
+
+    /// Convert newline-delimited JSON (or any sequence of top-level values)
+    /// to an array
+    FromNdjson,
+}
+
+/// An error that can happen while processing the input
+#[derive(Error, Debug)]
+enum CliError {
+    /// The input could not be parsed or the output could not be written
+    #[error("{source} (at byte {offset})")]
+    Parse {
+        source: ParseWithError<EmitterError>,
+        offset: usize,
+    },
+
+    /// The input of `to-ndjson` is not an array
+    #[error("expected an array (at byte {0})")]
+    NotAnArray(usize),
+
+    /// The output could not be written
+    #[error(transparent)]
+    Emitter(#[from] EmitterError),
+
+    /// The output could not be written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+type Feeder = BufReaderJsonFeeder<StdinLock<'static>>;
+
+/// Create a parser that reads from stdin
+fn stdin_parser(streaming: bool, validate_only: bool) -> JsonParser<Feeder> {
+    JsonParser::new_with_options(
+        BufReaderJsonFeeder::new(BufReader::new(io::stdin().lock())),
+        JsonParserOptionsBuilder::default()
+            .with_streaming(streaming)
+            .with_validate_only(validate_only)
+            .build(),
+    )
+}
+
+/// Forward all events from the parser to the sink and record the position
+/// in the input if an error happens
+fn run<K>(parser: &mut JsonParser<Feeder>, sink: &mut K) -> Result<(), CliError>
+where
+    K: JsonEventSink<Error = EmitterError>,
+{
+    parse_with_parser(parser, sink).map_err(|source| CliError::Parse {
+        source,
+        offset: parser.parsed_bytes(),
+    })
+}
+
+/// Write the elements of the top-level array as NDJSON records
+fn to_ndjson(
+    parser: &mut JsonParser<Feeder>,
+    writer: &mut NdjsonWriter<StdoutLock<'static>>,
+) -> Result<(), CliError> {
+    let parse_error =
+        |parser: &JsonParser<Feeder>, source: ParseWithError<EmitterError>| CliError::Parse {
+            source,
+            offset: parser.parsed_bytes(),
+        };
+
+    let mut depth = 0usize;
+    loop {
+        let event = match parser.next_event() {
+            Ok(Some(event)) => event,
+            Ok(None) => return Ok(()),
+            Err(e) => return Err(parse_error(parser, e.into())),
+        };
+        let outer = match event {
+            JsonEvent::NeedMoreInput => {
+                if let Err(e) = parser.feeder.fill() {
+                    return Err(parse_error(parser, e.into()));
+                }
+                continue;
+            }
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                depth += 1;
+                depth == 1
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                depth -= 1;
+                depth == 0
+            }
+            _ => depth == 0,
+        };
+        if outer {
+            if !matches!(event, JsonEvent::StartArray | JsonEvent::EndArray) {
+                return Err(CliError::NotAnArray(parser.current_span().start));
+            }
+        } else {
+            writer.on_event(event, parser)?;
+        }
+    }
+}
+
+fn execute(command: Command) -> Result<(), CliError> {
+    let stdout = io::stdout().lock();
+    match command {
+        Command::Validate => {
+            run(&mut stdin_parser(false, true), &mut NoopSink)?;
+        }
+
+        Command::Pretty { indent } => {
+            let options = EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(indent))
+                .build();
+            let mut emitter = JsonEmitter::new_with_options(stdout, options);
+            run(&mut stdin_parser(false, false), &mut emitter)?;
+            emitter.finish()?.write_all(b"\n")?;
+        }
+
+        Command::Minify => {
+            let mut emitter = JsonEmitter::new(stdout);
+            run(&mut stdin_parser(false, false), &mut emitter)?;
+            emitter.finish()?.write_all(b"\n")?;
+        }
+
+        Command::ToNdjson => {
+            let mut writer = NdjsonWriter::new(stdout);
+            to_ndjson(&mut stdin_parser(false, false), &mut writer)?;
+            writer.finish()?.flush()?;
+        }
+
+        Command::FromNdjson => {
+            let mut emitter = JsonEmitter::new(stdout);
+            emitter.start_array()?;
+            run(&mut stdin_parser(true, false), &mut emitter)?;
+            emitter.end_array()?;
+            emitter.finish()?.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// A sink that ignores all events
+struct NoopSink;
+
+impl JsonEventSink for NoopSink {
+    type Error = EmitterError;
+
+    fn on_event<T, S>(
+        &mut self,
+        _event: JsonEvent,
+        _parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        Ok(())
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match execute(cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::io::Write;
+
+use crate::{
+    emitter::DEFAULT_BUFFER_SIZE, feeder::JsonFeeder, sink::JsonEventSink, storage::ParserStorage,
+    JsonEvent, JsonNumber, JsonParser,
+};
+
+use super::{as_f32, BinaryError};
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_TAG: u8 = 6;
+
+const TAG_POSITIVE_BIGNUM: u64 = 2;
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+
+const START_ARRAY: u8 = 0x9f;
+const START_MAP: u8 = 0xbf;
+const BREAK: u8 = 0xff;
+const FALSE: u8 = 0xf4;
+const TRUE: u8 = 0xf5;
+const NULL: u8 = 0xf6;
+const FLOAT32: u8 = 0xfa;
+const FLOAT64: u8 = 0xfb;
+
+/// Writes the events of a parser as CBOR (RFC 8949). See the [module
+/// documentation](super).
+///
+/// Objects and arrays are written with indefinite length, so the output can
+/// be produced with constant memory. Integers are written in their shortest
+/// form and floats as single precision if this does not lose information.
+/// Integers that do not fit into 64 bits are written as bignums (tags 2 and
+/// 3). Floats that exceed the range of [`f64`] are written as infinity.
+///
+/// The output is buffered. Call [`Self::finish()`] when you are done.
+pub struct CborWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+}
+
+impl<W> CborWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer
+    pub fn new(writer: W) -> Self {
+        CborWriter {
+            writer,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub fn finish(mut self) -> Result<W, BinaryError> {
+        self.writer.write_all(&self.buf)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Write the initial byte of a data item and its argument
+    fn head(&mut self, major: u8, n: u64) {
+        let major = major << 5;
+        if n < 24 {
+            self.buf.push(major | n as u8);
+        } else if let Ok(n) = u8::try_from(n) {
+            self.buf.extend_from_slice(&[major | 24, n]);
+        } else if let Ok(n) = u16::try_from(n) {
+            self.buf.push(major | 25);
+            self.buf.extend_from_slice(&n.to_be_bytes());
+        } else if let Ok(n) = u32::try_from(n) {
+            self.buf.push(major | 26);
+            self.buf.extend_from_slice(&n.to_be_bytes());
+        } else {
+            self.buf.push(major | 27);
+            self.buf.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    fn text(&mut self, s: &str) {
+        self.head(MAJOR_TEXT, s.len() as u64);
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    fn float(&mut self, f: f64) {
+        if let Some(g) = as_f32(f) {
+            self.buf.push(FLOAT32);
+            self.buf.extend_from_slice(&g.to_be_bytes());
+        } else {
+            self.buf.push(FLOAT64);
+            self.buf.extend_from_slice(&f.to_be_bytes());
+        }
+    }
+
+    fn number(&mut self, n: JsonNumber<'_>) {
+        match n {
+            JsonNumber::I64(i) if i < 0 => self.head(MAJOR_NEGATIVE, !(i as u64)),
+            JsonNumber::I64(i) => self.head(MAJOR_UNSIGNED, i as u64),
+            JsonNumber::U64(u) => self.head(MAJOR_UNSIGNED, u),
+            JsonNumber::F64(f) => self.float(f),
+            JsonNumber::Big(literal) => match bignum(literal) {
+                Some((true, magnitude)) if magnitude.len() <= 8 => {
+                    let mut n = [0; 8];
+                    n[8 - magnitude.len()..].copy_from_slice(&magnitude);
+                    self.head(MAJOR_NEGATIVE, u64::from_be_bytes(n));
+                }
+                Some((negative, magnitude)) => {
+                    let tag = if negative {
+                        TAG_NEGATIVE_BIGNUM
+                    } else {
+                        TAG_POSITIVE_BIGNUM
+                    };
+                    self.head(MAJOR_TAG, tag);
+                    self.head(MAJOR_BYTES, magnitude.len() as u64);
+                    self.buf.extend_from_slice(&magnitude);
+                }
+                None => self.float(literal.parse().unwrap_or(f64::NAN)),
+            },
+        }
+    }
+}
+
+/// Convert an integer literal to the sign and the big-endian magnitude of a
+/// CBOR bignum (for negative numbers, the magnitude is `-1 - n`). Returns
+/// `None` if the literal is not an integer.
+fn bignum(literal: &str) -> Option<(bool, Vec<u8>)> {
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, literal),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    // little-endian while converting
+    let mut magnitude: Vec<u8> = Vec::new();
+    for d in digits.bytes() {
+        let mut carry = u32::from(d - b'0');
+        for b in magnitude.iter_mut() {
+            let v = u32::from(*b) * 10 + carry;
+            *b = v as u8;
+            carry = v >> 8;
+        }
+        if carry > 0 {
+            magnitude.push(carry as u8);
+        }
+    }
+
+    if negative {
+        // the number is large, so it is not zero and there is no underflow
+        for b in magnitude.iter_mut() {
+            let (v, borrow) = b.overflowing_sub(1);
+            *b = v;
+            if !borrow {
+                break;
+            }
+        }
+    }
+
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    magnitude.reverse();
+    Some((negative, magnitude))
+}
+
+impl<W> JsonEventSink for CborWriter<W>
+where
+    W: Write,
+{
+    type Error = BinaryError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartObject => self.buf.push(START_MAP),
+            JsonEvent::StartArray => self.buf.push(START_ARRAY),
+            JsonEvent::EndObject | JsonEvent::EndArray => self.buf.push(BREAK),
+            JsonEvent::FieldName | JsonEvent::ValueString => self.text(parser.current_str()?),
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => self.number(parser.current_number()?),
+            JsonEvent::ValueTrue => self.buf.push(TRUE),
+            JsonEvent::ValueFalse => self.buf.push(FALSE),
+            JsonEvent::ValueNull => self.buf.push(NULL),
+        }
+
+        if self.buf.len() >= DEFAULT_BUFFER_SIZE {
+            self.writer.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, sink::parse_with_parser,
+        JsonParser,
+    };
+
+    use super::CborWriter;
+
+    fn convert(json: &str) -> Vec<u8> {
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json.as_bytes()),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut writer = CborWriter::new(Vec::new());
+        parse_with_parser(&mut parser, &mut writer).unwrap();
+        writer.finish().unwrap()
+    }
+
+    /// Test the examples from RFC 8949, Appendix A, that can be expressed
+    /// in JSON
+    #[test]
+    fn rfc_examples() {
+        let examples: &[(&str, &[u8])] = &[
+            ("0", &[0x00]),
+            ("23", &[0x17]),
+            ("24", &[0x18, 0x18]),
+            ("1000", &[0x19, 0x03, 0xe8]),
+            ("1000000", &[0x1a, 0x00, 0x0f, 0x42, 0x40]),
+            (
+                "18446744073709551615",
+                &[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (
+                "18446744073709551616",
+                &[
+                    0xc2, 0x49, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                ],
+            ),
+            (
+                "-18446744073709551616",
+                &[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (
+                "-18446744073709551617",
+                &[
+                    0xc3, 0x49, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                ],
+            ),
+            ("-1000", &[0x39, 0x03, 0xe7]),
+            ("1.5", &[0xfa, 0x3f, 0xc0, 0x00, 0x00]),
+            (
+                "1.1",
+                &[0xfb, 0x3f, 0xf1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+            ),
+            ("\"\\u00fc\"", &[0x62, 0xc3, 0xbc]),
+            ("[]", &[0x9f, 0xff]),
+            ("{\"a\": null}", &[0xbf, 0x61, 0x61, 0xf6, 0xff]),
+            ("true false", &[0xf5, 0xf4]),
+        ];
+        for (json, cbor) in examples {
+            assert_eq!(convert(json), *cbor, "{json}");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Convert JSON to binary formats while parsing.
+//!
+//! The writers in this module are [`JsonEventSink`](crate::sink::JsonEventSink)s
+//! that translate events into another format on the fly, so huge documents
+//! can be converted in one pass without building a DOM:
+//!
+//! - [`CborWriter`] writes CBOR (RFC 8949) if the `cbor` feature is enabled
+//! - [`MessagePackWriter`] writes MessagePack if the `msgpack` feature is
+//!   enabled
+//!
+//! ```
+//! # #[cfg(feature = "cbor")]
+//! # {
+//! use actson::binary::CborWriter;
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sink::parse_with;
+//!
+//! let json = br#"{"a": [1, -2, true]}"#;
+//! let mut writer = CborWriter::new(Vec::new());
+//! parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+//! assert_eq!(
+//!     writer.finish().unwrap(),
+//!     [0xbf, 0x61, b'a', 0x9f, 0x01, 0x21, 0xf5, 0xff, 0xff]
+//! );
+//! # }
+//! ```
+//!
+//! Multiple top-level values (see
+//! [`JsonParserOptionsBuilder::with_streaming()`](crate::options::JsonParserOptionsBuilder::with_streaming()))
+//! are written one after the other.
+
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+
+#[cfg(feature = "cbor")]
+pub use cbor::CborWriter;
+#[cfg(feature = "msgpack")]
+pub use msgpack::MessagePackWriter;
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::parser::{InvalidFloatValueError, InvalidStringValueError};
+
+/// An error that can happen when converting JSON to a binary format
+#[derive(Error, Debug)]
+pub enum BinaryError {
+    /// The output could not be written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// A string received from a parser is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+
+    /// A number received from a parser is invalid
+    #[error(transparent)]
+    InvalidNumberValue(#[from] InvalidFloatValueError),
+}
+
+/// Returns the float as an [`f32`] if this does not lose precision
+fn as_f32(f: f64) -> Option<f32> {
+    let g = f as f32;
+    (g as f64 == f || f.is_nan()).then_some(g)
+}
@@ -0,0 +1,299 @@
+use std::io::Write;
+
+use crate::{
+    feeder::JsonFeeder, sink::JsonEventSink, storage::ParserStorage, JsonEvent, JsonNumber,
+    JsonParser,
+};
+
+use super::{as_f32, BinaryError};
+
+const NIL: u8 = 0xc0;
+const FALSE: u8 = 0xc2;
+const TRUE: u8 = 0xc3;
+const FLOAT32: u8 = 0xca;
+const FLOAT64: u8 = 0xcb;
+const UINT8: u8 = 0xcc;
+const INT8: u8 = 0xd0;
+const STR8: u8 = 0xd9;
+const ARRAY16: u8 = 0xdc;
+const MAP16: u8 = 0xde;
+
+/// The length of the placeholder written in place of the header of an
+/// array or map until its number of entries is known
+const PLACEHOLDER_LEN: usize = 5;
+
+/// An object or array that is currently open
+struct Container {
+    /// The position of the placeholder for the header in the buffer
+    start: usize,
+
+    /// The number of entries (elements or members) so far
+    len: usize,
+
+    is_map: bool,
+}
+
+/// Writes the events of a parser as MessagePack. See the [module
+/// documentation](super).
+///
+/// MessagePack requires the number of entries of an array or map before
+/// its contents, so each top-level value is kept in memory until it is
+/// complete. Use [`CborWriter`](super::CborWriter) to convert documents
+/// consisting of a single huge array or object with constant memory.
+///
+/// Integers and headers are written in their shortest form and floats as
+/// single precision if this does not lose information. Numbers that fit
+/// neither into 64-bit integers nor into [`f64`] are written as the nearest
+/// [`f64`].
+///
+/// Call [`Self::finish()`] when you are done.
+pub struct MessagePackWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+    stack: Vec<Container>,
+}
+
+impl<W> MessagePackWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer
+    pub fn new(writer: W) -> Self {
+        MessagePackWriter {
+            writer,
+            buf: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Flush all complete top-level values and return the underlying writer
+    pub fn finish(mut self) -> Result<W, BinaryError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Write a header consisting of a prefix and a big-endian length,
+    /// choosing the shortest form. `fix` is the prefix of the form that
+    /// stores lengths below `fix_max` in the prefix itself. `prefix8` is the
+    /// prefix of the 8-bit form or `None` if there is none. The 16- and
+    /// 32-bit forms follow it (or `prefix16`).
+    fn header(
+        out: &mut Vec<u8>,
+        fix: u8,
+        fix_max: usize,
+        prefix8: Option<u8>,
+        prefix16: u8,
+        n: usize,
+    ) {
+        if n < fix_max {
+            out.push(fix | n as u8);
+        } else if let (Some(p), Ok(n)) = (prefix8, u8::try_from(n)) {
+            out.extend_from_slice(&[p, n]);
+        } else if let Ok(n) = u16::try_from(n) {
+            out.push(prefix16);
+            out.extend_from_slice(&n.to_be_bytes());
+        } else {
+            out.push(prefix16 + 1);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+    }
+
+    fn str(&mut self, s: &str) {
+        Self::header(&mut self.buf, 0xa0, 32, Some(STR8), STR8 + 1, s.len());
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    fn uint(&mut self, u: u64) {
+        if u < 0x80 {
+            self.buf.push(u as u8);
+        } else if let Ok(u) = u8::try_from(u) {
+            self.buf.extend_from_slice(&[UINT8, u]);
+        } else if let Ok(u) = u16::try_from(u) {
+            self.buf.push(UINT8 + 1);
+            self.buf.extend_from_slice(&u.to_be_bytes());
+        } else if let Ok(u) = u32::try_from(u) {
+            self.buf.push(UINT8 + 2);
+            self.buf.extend_from_slice(&u.to_be_bytes());
+        } else {
+            self.buf.push(UINT8 + 3);
+            self.buf.extend_from_slice(&u.to_be_bytes());
+        }
+    }
+
+    fn int(&mut self, i: i64) {
+        if i >= 0 {
+            self.uint(i as u64);
+        } else if i >= -32 {
+            self.buf.push(i as u8);
+        } else if let Ok(i) = i8::try_from(i) {
+            self.buf.extend_from_slice(&[INT8, i as u8]);
+        } else if let Ok(i) = i16::try_from(i) {
+            self.buf.push(INT8 + 1);
+            self.buf.extend_from_slice(&i.to_be_bytes());
+        } else if let Ok(i) = i32::try_from(i) {
+            self.buf.push(INT8 + 2);
+            self.buf.extend_from_slice(&i.to_be_bytes());
+        } else {
+            self.buf.push(INT8 + 3);
+            self.buf.extend_from_slice(&i.to_be_bytes());
+        }
+    }
+
+    fn float(&mut self, f: f64) {
+        if let Some(g) = as_f32(f) {
+            self.buf.push(FLOAT32);
+            self.buf.extend_from_slice(&g.to_be_bytes());
+        } else {
+            self.buf.push(FLOAT64);
+            self.buf.extend_from_slice(&f.to_be_bytes());
+        }
+    }
+
+    /// Start an array or map, whose header is written when it ends
+    fn start(&mut self, is_map: bool) {
+        self.stack.push(Container {
+            start: self.buf.len(),
+            len: 0,
+            is_map,
+        });
+        self.buf.extend_from_slice(&[0; PLACEHOLDER_LEN]);
+    }
+
+    /// Replace the placeholder of the innermost array or map with its header
+    fn end(&mut self) {
+        let Some(c) = self.stack.pop() else {
+            return;
+        };
+        let mut header = Vec::with_capacity(PLACEHOLDER_LEN);
+        if c.is_map {
+            Self::header(&mut header, 0x80, 16, None, MAP16, c.len);
+        } else {
+            Self::header(&mut header, 0x90, 16, None, ARRAY16, c.len);
+        }
+        self.buf.splice(c.start..c.start + PLACEHOLDER_LEN, header);
+    }
+}
+
+impl<W> JsonEventSink for MessagePackWriter<W>
+where
+    W: Write,
+{
+    type Error = BinaryError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if matches!(event, JsonEvent::NeedMoreInput) {
+            return Ok(());
+        }
+
+        // count array elements and map members (by their names)
+        if let Some(c) = self.stack.last_mut() {
+            if matches!(event, JsonEvent::FieldName) || !(c.is_map || is_end(event)) {
+                c.len += 1;
+            }
+        }
+
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartObject => self.start(true),
+            JsonEvent::StartArray => self.start(false),
+            JsonEvent::EndObject | JsonEvent::EndArray => self.end(),
+            JsonEvent::FieldName | JsonEvent::ValueString => self.str(parser.current_str()?),
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => match parser.current_number()? {
+                JsonNumber::I64(i) => self.int(i),
+                JsonNumber::U64(u) => self.uint(u),
+                JsonNumber::F64(f) => self.float(f),
+                JsonNumber::Big(literal) => self.float(literal.parse().unwrap_or(f64::NAN)),
+            },
+            JsonEvent::ValueTrue => self.buf.push(TRUE),
+            JsonEvent::ValueFalse => self.buf.push(FALSE),
+            JsonEvent::ValueNull => self.buf.push(NIL),
+        }
+
+        if self.stack.is_empty() {
+            self.writer.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+}
+
+fn is_end(event: JsonEvent) -> bool {
+    matches!(event, JsonEvent::EndObject | JsonEvent::EndArray)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, sink::parse_with_parser,
+        JsonParser,
+    };
+
+    use super::MessagePackWriter;
+
+    fn convert(json: &str) -> Vec<u8> {
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json.as_bytes()),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut writer = MessagePackWriter::new(Vec::new());
+        parse_with_parser(&mut parser, &mut writer).unwrap();
+        writer.finish().unwrap()
+    }
+
+    /// Test that values are written in their shortest form and that the
+    /// headers of arrays and maps contain the number of entries
+    #[test]
+    fn values() {
+        let examples: &[(&str, &[u8])] = &[
+            ("0", &[0x00]),
+            ("127", &[0x7f]),
+            ("128", &[0xcc, 0x80]),
+            ("65536", &[0xce, 0x00, 0x01, 0x00, 0x00]),
+            (
+                "18446744073709551615",
+                &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            ("-32", &[0xe0]),
+            ("-33", &[0xd0, 0xdf]),
+            ("-129", &[0xd1, 0xff, 0x7f]),
+            ("1.5", &[0xca, 0x3f, 0xc0, 0x00, 0x00]),
+            (
+                "1.1",
+                &[0xcb, 0x3f, 0xf1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+            ),
+            ("\"a\"", &[0xa1, 0x61]),
+            ("true false null", &[0xc3, 0xc2, 0xc0]),
+            ("[]", &[0x90]),
+            (
+                r#"{"a": [1, {}], "b": null}"#,
+                &[0x82, 0xa1, 0x61, 0x92, 0x01, 0x80, 0xa1, 0x62, 0xc0],
+            ),
+        ];
+        for (json, msgpack) in examples {
+            assert_eq!(convert(json), *msgpack, "{json}");
+        }
+
+        let json = format!("[{}]", vec!["1"; 16].join(","));
+        let msgpack = convert(&json);
+        assert_eq!(msgpack[..3], [0xdc, 0x00, 0x10]);
+        assert_eq!(msgpack.len(), 3 + 16);
+
+        let json = format!("\"{}\"", "x".repeat(32));
+        assert_eq!(convert(&json)[..2], [0xd9, 32]);
+    }
+}
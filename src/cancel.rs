@@ -0,0 +1,60 @@
+//! Abort long-running parses from another thread or task.
+//!
+//! Register a [`CancellationToken`] with
+//! [`JsonParser::set_cancellation_token()`](crate::JsonParser::set_cancellation_token())
+//! and call [`CancellationToken::cancel()`] on a clone of it. The parser
+//! checks the token at every call of
+//! [`JsonParser::next_event()`](crate::JsonParser::next_event()) and returns
+//! [`ParserError::Cancelled`](crate::parser::ParserError::Cancelled) once it
+//! has been cancelled. See also
+//! [`JsonParserOptionsBuilder::with_byte_budget()`](crate::options::JsonParserOptionsBuilder::with_byte_budget())
+//! and
+//! [`JsonParserOptionsBuilder::with_time_budget()`](crate::options::JsonParserOptionsBuilder::with_time_budget()).
+//!
+//! ```
+//! use actson::cancel::CancellationToken;
+//! use actson::feeder::PushJsonFeeder;
+//! use actson::parser::ParserError;
+//! use actson::{JsonEvent, JsonParser};
+//!
+//! let token = CancellationToken::new();
+//! let mut parser = JsonParser::new(PushJsonFeeder::new());
+//! parser.set_cancellation_token(token.clone());
+//!
+//! parser.feeder.push_bytes(b"[1, 2");
+//! assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+//!
+//! // e.g. from another thread
+//! token.cancel();
+//!
+//! assert!(matches!(parser.next_event(), Err(ParserError::Cancelled)));
+//! ```
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A token that can be used to cancel a running parser. Clones of a token
+/// share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new token that has not been cancelled yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token and all its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
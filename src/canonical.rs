@@ -0,0 +1,307 @@
+//! Verify that JSON texts are in canonical form.
+//!
+//! [`CanonicalVerifier`] is a [`JsonEventSink`] that checks while parsing
+//! that a JSON text is already in the canonical form defined by
+//! [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) (JSON Canonicalization
+//! Scheme): there is no whitespace, the members of each object are sorted
+//! by their keys, numbers are formatted like in ECMAScript, and strings only
+//! contain the escape sequences that are strictly necessary. This allows
+//! signatures over canonical JSON to be verified without serializing the
+//! document again. Verification stops at the first violation. The output of
+//! a [`JsonEmitter`](crate::emitter::JsonEmitter) with
+//! [`with_canonical()`](crate::emitter::EmitterOptionsBuilder::with_canonical())
+//! always passes.
+//!
+//! ```
+//! use actson::canonical::{verify_canonical, CanonicalError};
+//! use actson::sink::ParseWithError;
+//!
+//! assert!(verify_canonical(br#"{"a":[1,1.5,"\u000b"],"b":null}"#).is_ok());
+//!
+//! assert!(matches!(
+//!     verify_canonical(br#"{"b":1,"a":2}"#),
+//!     Err(ParseWithError::Sink(CanonicalError::UnsortedKeys(7)))
+//! ));
+//! assert!(matches!(
+//!     verify_canonical(br#"[1.0]"#),
+//!     Err(ParseWithError::Sink(CanonicalError::Number(1)))
+//! ));
+//! assert!(matches!(
+//!     verify_canonical(br#"{"a": 1}"#),
+//!     Err(ParseWithError::Sink(CanonicalError::Whitespace(4)))
+//! ));
+//! ```
+
+use thiserror::Error;
+
+use crate::{
+    emitter::{cmp_utf16, write_es_number},
+    escape::{escape_contents, EscapeOptions},
+    feeder::{JsonFeeder, SliceJsonFeeder},
+    parser::InvalidStringValueError,
+    sink::{parse_with_parser, JsonEventSink, ParseWithError},
+    storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+/// A violation of the canonical form found by a [`CanonicalVerifier`]. Each
+/// variant contains the offset of the byte at which the violation was
+/// detected.
+#[derive(Error, Debug)]
+pub enum CanonicalError {
+    /// A string received from the parser is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+
+    /// The JSON text contains whitespace between two tokens. The offset is
+    /// the end of the token before the whitespace.
+    #[error("whitespace after byte {0}")]
+    Whitespace(usize),
+
+    /// A key is not greater than the previous key of the same object (as
+    /// compared by UTF-16 code units)
+    #[error("object key at byte {0} is not in sorted order")]
+    UnsortedKeys(usize),
+
+    /// A number is not formatted like in ECMAScript
+    #[error("number at byte {0} is not in canonical form")]
+    Number(usize),
+
+    /// A string contains an unnecessary escape sequence or a character
+    /// that should have been escaped differently
+    #[error("string at byte {0} is not in canonical form")]
+    String(usize),
+
+    /// The JSON text is followed by whitespace or another value
+    #[error("unexpected data after the JSON text at byte {0}")]
+    TrailingData(usize),
+}
+
+/// An object or array that is currently open
+struct Frame {
+    /// `true` if the container is an object
+    is_object: bool,
+
+    /// The most recent key if the container is an object
+    last_key: Option<String>,
+}
+
+/// Verifies that a JSON text is in canonical form. See the [module
+/// documentation](self).
+///
+/// Call [`Self::finish()`] after parsing to check that there is nothing
+/// after the JSON text.
+#[derive(Default)]
+pub struct CanonicalVerifier {
+    /// The offset right after the most recent event
+    end: usize,
+
+    /// The most recent event
+    last: Option<JsonEvent>,
+
+    /// The containers that are currently open
+    stack: Vec<Frame>,
+
+    /// A buffer for the canonical form of the current string or number
+    buf: Vec<u8>,
+}
+
+impl CanonicalVerifier {
+    /// Create a new verifier
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check that the given parser, which has parsed the JSON text, has not
+    /// consumed anything after it
+    pub fn finish<T, S>(&self, parser: &JsonParser<T, S>) -> Result<(), CanonicalError>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if parser.parsed_bytes() > self.end {
+            return Err(CanonicalError::TrailingData(self.end));
+        }
+        Ok(())
+    }
+
+    /// Check the separator between the previous and the given event
+    fn check_gap(&self, event: JsonEvent, start: usize) -> Result<(), CanonicalError> {
+        let separator = match self.last {
+            None => 0,
+            Some(_) if self.stack.is_empty() => {
+                return Err(CanonicalError::TrailingData(self.end));
+            }
+            Some(JsonEvent::StartObject | JsonEvent::StartArray) => 0,
+            // a colon after a key
+            Some(JsonEvent::FieldName) => 1,
+            // a comma between two members or elements
+            Some(_) if event.is_container_end() => 0,
+            Some(_) => 1,
+        };
+        if start > self.end + separator {
+            return Err(CanonicalError::Whitespace(self.end));
+        }
+        Ok(())
+    }
+
+    /// Check that the raw string `raw` is the canonical representation of
+    /// `value`
+    fn check_string(&mut self, raw: &str, value: &str, start: usize) -> Result<(), CanonicalError> {
+        // without escape sequences, the string is canonical because the
+        // parser rejects unescaped control characters
+        if !raw.contains('\\') {
+            return Ok(());
+        }
+        self.buf.clear();
+        escape_contents(value, EscapeOptions::default(), &mut self.buf);
+        if self.buf != raw.as_bytes() {
+            return Err(CanonicalError::String(start));
+        }
+        Ok(())
+    }
+}
+
+impl JsonEventSink for CanonicalVerifier {
+    type Error = CanonicalError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if event == JsonEvent::NeedMoreInput {
+            return Ok(());
+        }
+
+        let span = parser.current_span();
+        self.check_gap(event, span.start)?;
+
+        match event {
+            JsonEvent::StartObject | JsonEvent::StartArray => self.stack.push(Frame {
+                is_object: event == JsonEvent::StartObject,
+                last_key: None,
+            }),
+
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                self.stack.pop();
+            }
+
+            JsonEvent::FieldName => {
+                let key = parser.current_str()?;
+                self.check_string(parser.current_raw_str()?, key, span.start)?;
+                if let Some(frame) = self.stack.last_mut().filter(|f| f.is_object) {
+                    if let Some(last_key) = &mut frame.last_key {
+                        if cmp_utf16(last_key, key).is_ge() {
+                            return Err(CanonicalError::UnsortedKeys(span.start));
+                        }
+                        last_key.clear();
+                        last_key.push_str(key);
+                    } else {
+                        frame.last_key = Some(key.to_string());
+                    }
+                }
+            }
+
+            JsonEvent::ValueString => {
+                self.check_string(parser.current_raw_str()?, parser.current_str()?, span.start)?;
+            }
+
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let raw = parser.current_raw_str()?;
+                let value = raw.parse::<f64>().unwrap_or(f64::NAN);
+                if !value.is_finite() {
+                    return Err(CanonicalError::Number(span.start));
+                }
+                self.buf.clear();
+                write_es_number(value, &mut self.buf);
+                if self.buf != raw.as_bytes() {
+                    return Err(CanonicalError::Number(span.start));
+                }
+            }
+
+            _ => {}
+        }
+
+        self.end = span.end;
+        self.last = Some(event);
+        Ok(())
+    }
+}
+
+/// Check that the given byte slice contains a single JSON text in
+/// canonical form. See the [module documentation](self).
+pub fn verify_canonical(json: &[u8]) -> Result<(), ParseWithError<CanonicalError>> {
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    let mut verifier = CanonicalVerifier::new();
+    parse_with_parser(&mut parser, &mut verifier)?;
+    verifier.finish(&parser).map_err(ParseWithError::Sink)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        emitter::{EmitterOptionsBuilder, JsonEmitter},
+        feeder::SliceJsonFeeder,
+        sink::{parse_with, ParseWithError},
+    };
+
+    use super::{verify_canonical, CanonicalError};
+
+    /// Test that the output of the canonical emitter passes
+    #[test]
+    fn emitter_output() {
+        let json = r#"{"b": [1.0, 1E3, -0, 1e21, 0.000001, 1e-7, "é\/\u001f\t"],
+            "a": {"z": null, "€": true, "": false, "😀": 1}}"#
+            .as_bytes();
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_canonical(true)
+                .build(),
+        );
+        parse_with(SliceJsonFeeder::new(json), &mut emitter).unwrap();
+        let canonical = emitter.finish().unwrap();
+        verify_canonical(&canonical).unwrap();
+        assert!(verify_canonical(json).is_err());
+    }
+
+    /// Test that violations are detected at the right offsets
+    #[test]
+    fn violations() {
+        let error = |json: &[u8]| match verify_canonical(json) {
+            Err(ParseWithError::Sink(e)) => e,
+            r => panic!("unexpected result: {r:?}"),
+        };
+        assert!(matches!(error(b" 1"), CanonicalError::Whitespace(0)));
+        assert!(matches!(error(b"[1 ]"), CanonicalError::Whitespace(2)));
+        assert!(matches!(
+            error(br#"{"a" :1}"#),
+            CanonicalError::Whitespace(4)
+        ));
+        assert!(matches!(error(b"[1,\n2]"), CanonicalError::Whitespace(2)));
+        assert!(matches!(error(b"{} "), CanonicalError::TrailingData(2)));
+        assert!(matches!(
+            error(br#"{"a":1,"a":2}"#),
+            CanonicalError::UnsortedKeys(7)
+        ));
+        assert!(matches!(error(b"[1e3]"), CanonicalError::Number(1)));
+        assert!(matches!(error(b"[-0]"), CanonicalError::Number(1)));
+        assert!(matches!(error(b"[1e400]"), CanonicalError::Number(1)));
+        assert!(matches!(error(br#"["\u0041"]"#), CanonicalError::String(1)));
+        assert!(matches!(error(br#"["\/"]"#), CanonicalError::String(1)));
+        assert!(matches!(error(br#"["\u000A"]"#), CanonicalError::String(1)));
+        assert!(matches!(
+            error(br#"{"\u0062":1}"#),
+            CanonicalError::String(1)
+        ));
+
+        // keys are compared by UTF-16 code units
+        verify_canonical("{\"\u{e9}\":1,\"\u{1f600}\":2,\"\u{ff21}\":3}".as_bytes()).unwrap();
+        verify_canonical(br#"[[],{},"",0,-1.5,"\n\"\\",true]"#).unwrap();
+    }
+}
@@ -0,0 +1,377 @@
+//! Convert arrays of flat objects to CSV.
+//!
+//! [`CsvWriter`] is a [`JsonEventSink`] that writes each object of a
+//! top-level array as a row of comma-separated values (RFC 4180). Only the
+//! current row is kept in memory, so exports of arbitrary size can be
+//! converted on the fly. Top-level objects are treated as rows, too, which
+//! means that streams of objects (e.g. JSON Lines) can be converted as well.
+//!
+//! The columns are either given in advance or taken from the field names of
+//! the first row. Fields that do not belong to a column are ignored and
+//! missing fields produce empty cells. Strings are written without quotes
+//! unless they contain special characters, numbers exactly as they appear
+//! in the JSON text, and `null` as an empty cell. Nested objects and arrays
+//! are written as compact JSON.
+//!
+//! ```
+//! use actson::csv::CsvWriter;
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sink::parse_with;
+//!
+//! let json = br#"[
+//!     {"name": "Elvis", "born": 1935, "genres": ["rock", "gospel"]},
+//!     {"name": "Aretha, Queen of Soul", "born": 1942}
+//! ]"#;
+//! let mut writer = CsvWriter::new(Vec::new());
+//! parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(writer.finish().unwrap()).unwrap(),
+//!     "name,born,genres\r\n\
+//!      Elvis,1935,\"[\"\"rock\"\",\"\"gospel\"\"]\"\r\n\
+//!      \"Aretha, Queen of Soul\",1942,\r\n"
+//! );
+//! ```
+
+use std::{collections::HashMap, io::Write};
+
+use thiserror::Error;
+
+use crate::{
+    emitter::{EmitterError, JsonEmitter, DEFAULT_BUFFER_SIZE},
+    feeder::JsonFeeder,
+    parser::InvalidStringValueError,
+    sink::JsonEventSink,
+    storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+/// An error that can happen when writing CSV
+#[derive(Error, Debug)]
+pub enum CsvError {
+    /// The output could not be written
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// A string received from the parser is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+
+    /// A nested object or array could not be written
+    #[error(transparent)]
+    Emitter(#[from] EmitterError),
+
+    /// The JSON text contains a value that is neither an object nor an array
+    /// of objects (e.g. an array of numbers)
+    #[error("expected an object or an array of objects")]
+    NotARecord,
+}
+
+/// Writes arrays of flat objects as CSV. See the [module
+/// documentation](self).
+///
+/// The output is buffered. Call [`Self::finish()`] when you are done.
+pub struct CsvWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+
+    /// The byte that separates cells
+    delimiter: u8,
+
+    /// The names of the columns
+    columns: Vec<String>,
+
+    /// The index of each column by its name
+    column_index: HashMap<String, usize>,
+
+    /// `true` if the columns are still being collected from the first row
+    collect_columns: bool,
+
+    /// `true` if the header has been written
+    header_written: bool,
+
+    /// `true` if a top-level array is open
+    in_array: bool,
+
+    /// The cells of the current row or `None` if no row is open
+    row: Option<Vec<Option<String>>>,
+
+    /// The column of the current field or `None` if it is ignored
+    column: Option<usize>,
+
+    /// A nested object or array that is currently being written into a
+    /// cell, together with its nesting depth
+    nested: Option<(JsonEmitter<Vec<u8>>, usize)>,
+}
+
+impl<W> CsvWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer that takes the columns from the first row
+    pub fn new(writer: W) -> Self {
+        CsvWriter {
+            writer,
+            buf: Vec::new(),
+            delimiter: b',',
+            columns: Vec::new(),
+            column_index: HashMap::new(),
+            collect_columns: true,
+            header_written: false,
+            in_array: false,
+            row: None,
+            column: None,
+            nested: None,
+        }
+    }
+
+    /// Use the given columns instead of taking them from the first row
+    pub fn with_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns.clear();
+        self.column_index.clear();
+        for c in columns {
+            self.add_column(c.into());
+        }
+        self.collect_columns = false;
+        self
+    }
+
+    /// Separate cells with the given byte instead of a comma (e.g. `b'\t'`
+    /// or `b';'`)
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Get the names of the columns (which are only complete after the
+    /// first row if they are taken from it)
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Write the header if no rows have been written, flush all buffered
+    /// output, and return the underlying writer
+    pub fn finish(mut self) -> Result<W, CsvError> {
+        if !self.header_written && !self.columns.is_empty() {
+            self.write_header();
+        }
+        self.writer.write_all(&self.buf)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn add_column(&mut self, name: String) -> usize {
+        *self.column_index.entry(name).or_insert_with_key(|name| {
+            self.columns.push(name.clone());
+            self.columns.len() - 1
+        })
+    }
+
+    /// Write a cell, quoting it if necessary
+    fn write_cell(&mut self, cell: &str) {
+        let needs_quotes = cell
+            .bytes()
+            .any(|b| b == self.delimiter || matches!(b, b'"' | b'\r' | b'\n'));
+        if needs_quotes {
+            self.buf.push(b'"');
+            for b in cell.bytes() {
+                if b == b'"' {
+                    self.buf.push(b'"');
+                }
+                self.buf.push(b);
+            }
+            self.buf.push(b'"');
+        } else {
+            self.buf.extend_from_slice(cell.as_bytes());
+        }
+    }
+
+    fn write_header(&mut self) {
+        let columns = std::mem::take(&mut self.columns);
+        for (i, c) in columns.iter().enumerate() {
+            if i > 0 {
+                self.buf.push(self.delimiter);
+            }
+            self.write_cell(c);
+        }
+        self.buf.extend_from_slice(b"\r\n");
+        self.columns = columns;
+        self.header_written = true;
+    }
+
+    fn write_row(&mut self, row: Vec<Option<String>>) {
+        if !self.header_written {
+            self.write_header();
+        }
+        for i in 0..self.columns.len() {
+            if i > 0 {
+                self.buf.push(self.delimiter);
+            }
+            if let Some(Some(cell)) = row.get(i) {
+                self.write_cell(cell);
+            }
+        }
+        self.buf.extend_from_slice(b"\r\n");
+    }
+
+    /// Put a value into the cell of the current field
+    fn set_cell(&mut self, value: String) {
+        if let (Some(row), Some(column)) = (&mut self.row, self.column) {
+            if row.len() <= column {
+                row.resize(column + 1, None);
+            }
+            row[column] = Some(value);
+        }
+    }
+}
+
+impl<W> JsonEventSink for CsvWriter<W>
+where
+    W: Write,
+{
+    type Error = CsvError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if event == JsonEvent::NeedMoreInput {
+            return Ok(());
+        }
+
+        if let Some((emitter, depth)) = &mut self.nested {
+            emitter.on_event(event, parser)?;
+            match event {
+                JsonEvent::StartObject | JsonEvent::StartArray => *depth += 1,
+                JsonEvent::EndObject | JsonEvent::EndArray => *depth -= 1,
+                _ => {}
+            }
+            if *depth == 0 {
+                let (emitter, _) = self.nested.take().unwrap_or_else(|| unreachable!());
+                let json = emitter.finish()?;
+                self.set_cell(String::from_utf8_lossy(&json).into_owned());
+            }
+            return Ok(());
+        }
+
+        if self.row.is_none() {
+            match event {
+                JsonEvent::StartArray if !self.in_array => self.in_array = true,
+                JsonEvent::EndArray if self.in_array => self.in_array = false,
+                JsonEvent::StartObject => {
+                    self.row = Some(Vec::with_capacity(self.columns.len()));
+                    self.column = None;
+                }
+                _ => return Err(CsvError::NotARecord),
+            }
+            return Ok(());
+        }
+
+        match event {
+            JsonEvent::FieldName => {
+                let name = parser.current_str()?;
+                self.column = match self.column_index.get(name) {
+                    Some(&i) => Some(i),
+                    None if self.collect_columns => Some(self.add_column(name.to_string())),
+                    None => None,
+                };
+            }
+            JsonEvent::EndObject => {
+                self.collect_columns = false;
+                if let Some(row) = self.row.take() {
+                    self.write_row(row);
+                }
+                if self.buf.len() >= DEFAULT_BUFFER_SIZE {
+                    self.writer.write_all(&self.buf)?;
+                    self.buf.clear();
+                }
+            }
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                let mut emitter = JsonEmitter::new(Vec::new());
+                emitter.on_event(event, parser)?;
+                self.nested = Some((emitter, 1));
+            }
+            JsonEvent::ValueString => self.set_cell(parser.current_str()?.to_string()),
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                self.set_cell(parser.current_raw_str()?.to_string())
+            }
+            JsonEvent::ValueTrue => self.set_cell("true".to_string()),
+            JsonEvent::ValueFalse => self.set_cell("false".to_string()),
+            JsonEvent::ValueNull => self.set_cell(String::new()),
+            JsonEvent::NeedMoreInput | JsonEvent::EndArray => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        feeder::SliceJsonFeeder,
+        options::JsonParserOptionsBuilder,
+        sink::{parse_with, parse_with_parser, ParseWithError},
+        JsonParser,
+    };
+
+    use super::{CsvError, CsvWriter};
+
+    /// Test that given columns are written in their order, that other
+    /// fields are ignored, and that special characters are quoted
+    #[test]
+    fn columns() {
+        let json = br#"{"b": "x\ny", "c": true, "a": 1.5e3} {"a": "\"q\"", "b": null}"#;
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut writer = CsvWriter::new(Vec::new())
+            .with_columns(["a", "b;c", "b"])
+            .with_delimiter(b';');
+        parse_with_parser(&mut parser, &mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "a;\"b;c\";b\r\n1.5e3;;\"x\ny\"\r\n\"\"\"q\"\"\";;\r\n"
+        );
+    }
+
+    /// Test that an empty array produces no output, that the header is
+    /// written for given columns, and that other values are rejected
+    #[test]
+    fn edge_cases() {
+        let mut writer = CsvWriter::new(Vec::new());
+        parse_with(SliceJsonFeeder::new(b"[]"), &mut writer).unwrap();
+        assert!(writer.finish().unwrap().is_empty());
+
+        let mut writer = CsvWriter::new(Vec::new()).with_columns(["a"]);
+        parse_with(SliceJsonFeeder::new(b"[]"), &mut writer).unwrap();
+        assert_eq!(writer.finish().unwrap(), b"a\r\n");
+
+        let mut writer = CsvWriter::new(Vec::new());
+        assert!(matches!(
+            parse_with(SliceJsonFeeder::new(b"[{}, 1]"), &mut writer),
+            Err(ParseWithError::Sink(CsvError::NotARecord))
+        ));
+        assert!(matches!(
+            parse_with(
+                SliceJsonFeeder::new(b"[[{}]]"),
+                &mut CsvWriter::new(Vec::new())
+            ),
+            Err(ParseWithError::Sink(CsvError::NotARecord))
+        ));
+    }
+}
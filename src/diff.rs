@@ -0,0 +1,1104 @@
+//! Compare two JSON texts structurally.
+//!
+//! [`diff()`] and [`first_difference()`] read two parsers in lockstep and
+//! report where their JSON texts differ. Only the current path is kept in
+//! memory, so huge documents can be compared in constant memory (e.g. the
+//! expected and the actual output of a test or two exports of a database).
+//!
+//! Numbers are compared by value (e.g. `1` equals `1.0`). Object members
+//! are compared in the order in which they appear, so objects with the
+//! same members in a different order are reported as different. If the
+//! names of two members differ, their values are not compared. If two
+//! values have different types, their contents are not compared.
+//!
+//! To only check whether two JSON texts are equal, use [`json_eq()`] or
+//! [`assert_json_stream_eq()`] instead. They ignore the order of object
+//! members, and [`JsonEq`] can compare numbers with a tolerance.
+//!
+//! ```
+//! use actson::diff::{diff, DifferenceKind};
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::JsonParser;
+//!
+//! let left = br#"{"name": "Elvis", "albums": [1956, 1957], "alive": false}"#;
+//! let right = br#"{"name": "Elvis", "albums": [1956.0, 1958, 1960], "alive": null}"#;
+//! let differences = diff(
+//!     &mut JsonParser::new(SliceJsonFeeder::new(left)),
+//!     &mut JsonParser::new(SliceJsonFeeder::new(right)),
+//! )
+//! .unwrap();
+//!
+//! let d = differences
+//!     .iter()
+//!     .map(|d| (d.path.as_str(), &d.kind))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     d,
+//!     [
+//!         ("/albums/1", &DifferenceKind::Value),
+//!         ("/albums/2", &DifferenceKind::OnlyRight),
+//!         ("/alive", &DifferenceKind::Type),
+//!     ]
+//! );
+//! assert_eq!(differences[0].left_offset, Some(35));
+//! assert_eq!(differences[0].right_offset, Some(37));
+//! ```
+
+use thiserror::Error;
+
+use crate::{
+    feeder::FillJsonFeeder,
+    parser::ParserError,
+    reader::ReaderError,
+    storage::ParserStorage,
+    value::{JsonObject, JsonValue},
+    JsonEvent, JsonNumber, JsonParser,
+};
+
+/// An error that can happen while comparing two JSON texts
+#[derive(Error, Debug)]
+pub enum DiffError {
+    /// The left JSON text could not be read
+    #[error("left JSON text: {0}")]
+    Left(#[source] ReaderError),
+
+    /// The right JSON text could not be read
+    #[error("right JSON text: {0}")]
+    Right(#[source] ReaderError),
+}
+
+/// How two JSON texts differ at a given location
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// The values have different types (e.g. an object and a string)
+    #[error("values have different types")]
+    Type,
+
+    /// The values have the same type but are not equal
+    #[error("values are not equal")]
+    Value,
+
+    /// Two object members at the same position have different names
+    #[error("field names `{left}' and `{right}' differ")]
+    FieldName {
+        /// The name in the left JSON text
+        left: String,
+
+        /// The name in the right JSON text
+        right: String,
+    },
+
+    /// The member or element only exists in the left JSON text
+    #[error("value only exists on the left")]
+    OnlyLeft,
+
+    /// The member or element only exists in the right JSON text
+    #[error("value only exists on the right")]
+    OnlyRight,
+}
+
+/// A location at which two JSON texts differ
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{kind} at `{path}'")]
+pub struct Difference {
+    /// The JSON Pointer to the values (e.g. `/items/0/id`). For
+    /// [`DifferenceKind::FieldName`], this is the path of the object.
+    pub path: String,
+
+    /// The byte offset of the value (or field name) in the left JSON text
+    /// or `None` if it only exists in the right one
+    pub left_offset: Option<usize>,
+
+    /// The byte offset of the value (or field name) in the right JSON text
+    /// or `None` if it only exists in the left one
+    pub right_offset: Option<usize>,
+
+    /// How the JSON texts differ
+    pub kind: DifferenceKind,
+}
+
+/// Compare the JSON texts of the two parsers and return all differences in
+/// document order. See the [module documentation](self).
+pub fn diff<A, SA, B, SB>(
+    left: &mut JsonParser<A, SA>,
+    right: &mut JsonParser<B, SB>,
+) -> Result<Vec<Difference>, DiffError>
+where
+    A: FillJsonFeeder,
+    SA: ParserStorage,
+    B: FillJsonFeeder,
+    SB: ParserStorage,
+{
+    let mut differ = Differ::new(left, right, usize::MAX);
+    differ.compare_texts()?;
+    Ok(differ.differences)
+}
+
+/// Compare the JSON texts of the two parsers and return the first
+/// difference or `None` if they are equal. Stops reading as soon as a
+/// difference has been found. See the [module documentation](self).
+pub fn first_difference<A, SA, B, SB>(
+    left: &mut JsonParser<A, SA>,
+    right: &mut JsonParser<B, SB>,
+) -> Result<Option<Difference>, DiffError>
+where
+    A: FillJsonFeeder,
+    SA: ParserStorage,
+    B: FillJsonFeeder,
+    SB: ParserStorage,
+{
+    let mut differ = Differ::new(left, right, 1);
+    differ.compare_texts()?;
+    Ok(differ.differences.pop())
+}
+
+/// The kind of a value as far as type differences are concerned
+#[derive(PartialEq, Eq)]
+enum ValueType {
+    Object,
+    Array,
+    String,
+    Number,
+    Bool,
+    Null,
+}
+
+impl ValueType {
+    fn of(event: JsonEvent) -> Self {
+        match event {
+            JsonEvent::StartObject => ValueType::Object,
+            JsonEvent::StartArray => ValueType::Array,
+            JsonEvent::ValueString => ValueType::String,
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => ValueType::Number,
+            JsonEvent::ValueTrue | JsonEvent::ValueFalse => ValueType::Bool,
+            _ => ValueType::Null,
+        }
+    }
+}
+
+/// Returns `true` if the two numbers have the same value
+fn numbers_equal(a: JsonNumber<'_>, b: JsonNumber<'_>) -> bool {
+    match (a, b) {
+        (JsonNumber::I64(a), JsonNumber::I64(b)) => a == b,
+        (JsonNumber::U64(a), JsonNumber::U64(b)) => a == b,
+        (JsonNumber::F64(a), JsonNumber::F64(b)) => a == b,
+        (JsonNumber::Big(a), JsonNumber::Big(b)) => a == b,
+        (JsonNumber::I64(i), JsonNumber::F64(f)) | (JsonNumber::F64(f), JsonNumber::I64(i)) => {
+            i as f64 == f
+        }
+        (JsonNumber::U64(u), JsonNumber::F64(f)) | (JsonNumber::F64(f), JsonNumber::U64(u)) => {
+            u as f64 == f
+        }
+        _ => false,
+    }
+}
+
+/// Get the next event that is not [`JsonEvent::NeedMoreInput`]
+fn next<T, S>(parser: &mut JsonParser<T, S>) -> Result<Option<JsonEvent>, ReaderError>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    loop {
+        match parser.next_event()? {
+            Some(JsonEvent::NeedMoreInput) => parser.feeder.fill()?,
+            e => return Ok(e),
+        }
+    }
+}
+
+/// Skip the rest of the value that starts with the given event
+fn skip_rest<T, S>(parser: &mut JsonParser<T, S>, event: JsonEvent) -> Result<(), ReaderError>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    if !matches!(event, JsonEvent::StartObject | JsonEvent::StartArray) {
+        return Ok(());
+    }
+    let mut depth = 1usize;
+    while depth > 0 {
+        match next(parser)? {
+            Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+            Some(JsonEvent::EndObject | JsonEvent::EndArray) => depth -= 1,
+            Some(_) => {}
+            None => return Err(ParserError::NoMoreInput.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Reads two parsers in lockstep and collects differences
+struct Differ<'l, 'r, A, SA, B, SB> {
+    left: &'l mut JsonParser<A, SA>,
+    right: &'r mut JsonParser<B, SB>,
+
+    /// The JSON Pointer to the values currently being compared
+    path: String,
+
+    differences: Vec<Difference>,
+
+    /// The number of differences after which comparing stops
+    limit: usize,
+}
+
+impl<'l, 'r, A, SA, B, SB> Differ<'l, 'r, A, SA, B, SB>
+where
+    A: FillJsonFeeder,
+    SA: ParserStorage,
+    B: FillJsonFeeder,
+    SB: ParserStorage,
+{
+    fn new(
+        left: &'l mut JsonParser<A, SA>,
+        right: &'r mut JsonParser<B, SB>,
+        limit: usize,
+    ) -> Self {
+        Differ {
+            left,
+            right,
+            path: String::new(),
+            differences: Vec::new(),
+            limit,
+        }
+    }
+
+    fn next_left(&mut self) -> Result<Option<JsonEvent>, DiffError> {
+        next(self.left).map_err(DiffError::Left)
+    }
+
+    fn next_right(&mut self) -> Result<Option<JsonEvent>, DiffError> {
+        next(self.right).map_err(DiffError::Right)
+    }
+
+    fn left_offset(&self) -> usize {
+        self.left.current_span().start
+    }
+
+    fn right_offset(&self) -> usize {
+        self.right.current_span().start
+    }
+
+    /// Record a difference at the current path. Returns `false` if the
+    /// limit has been reached and comparing should stop.
+    fn report(
+        &mut self,
+        left_offset: Option<usize>,
+        right_offset: Option<usize>,
+        kind: DifferenceKind,
+    ) -> bool {
+        self.differences.push(Difference {
+            path: self.path.clone(),
+            left_offset,
+            right_offset,
+            kind,
+        });
+        self.differences.len() < self.limit
+    }
+
+    /// Compare all top-level values (there may be more than one in
+    /// streaming mode)
+    fn compare_texts(&mut self) -> Result<(), DiffError> {
+        // the parsers must not be called again once they have returned `None`
+        let (mut left_done, mut right_done) = (false, false);
+        loop {
+            let l = if left_done { None } else { self.next_left()? };
+            let r = if right_done { None } else { self.next_right()? };
+            left_done = l.is_none();
+            right_done = r.is_none();
+            let more = match (l, r) {
+                (None, None) => return Ok(()),
+                (Some(l), Some(r)) => self.compare_values(l, r)?,
+                (Some(l), None) => self.only_left(l)?,
+                (None, Some(r)) => self.only_right(r)?,
+            };
+            if !more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Report and skip a value that only exists on the left
+    fn only_left(&mut self, event: JsonEvent) -> Result<bool, DiffError> {
+        let more = self.report(Some(self.left_offset()), None, DifferenceKind::OnlyLeft);
+        skip_rest(self.left, event).map_err(DiffError::Left)?;
+        Ok(more)
+    }
+
+    /// Report and skip a value that only exists on the right
+    fn only_right(&mut self, event: JsonEvent) -> Result<bool, DiffError> {
+        let more = self.report(None, Some(self.right_offset()), DifferenceKind::OnlyRight);
+        skip_rest(self.right, event).map_err(DiffError::Right)?;
+        Ok(more)
+    }
+
+    /// Compare the two values starting with the given events. Returns
+    /// `false` if comparing should stop.
+    fn compare_values(&mut self, l: JsonEvent, r: JsonEvent) -> Result<bool, DiffError> {
+        let offsets = (Some(self.left_offset()), Some(self.right_offset()));
+        if ValueType::of(l) != ValueType::of(r) {
+            let more = self.report(offsets.0, offsets.1, DifferenceKind::Type);
+            if more {
+                skip_rest(self.left, l).map_err(DiffError::Left)?;
+                skip_rest(self.right, r).map_err(DiffError::Right)?;
+            }
+            return Ok(more);
+        }
+
+        let equal = match l {
+            JsonEvent::StartObject => return self.compare_objects(),
+            JsonEvent::StartArray => return self.compare_arrays(),
+            JsonEvent::ValueString => {
+                let a = self
+                    .left
+                    .current_str()
+                    .map_err(|e| DiffError::Left(e.into()))?;
+                let b = self
+                    .right
+                    .current_str()
+                    .map_err(|e| DiffError::Right(e.into()))?;
+                a == b
+            }
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let a = self
+                    .left
+                    .current_number()
+                    .map_err(|e| DiffError::Left(e.into()))?;
+                let b = self
+                    .right
+                    .current_number()
+                    .map_err(|e| DiffError::Right(e.into()))?;
+                numbers_equal(a, b)
+            }
+            _ => l == r,
+        };
+        if equal {
+            Ok(true)
+        } else {
+            Ok(self.report(offsets.0, offsets.1, DifferenceKind::Value))
+        }
+    }
+
+    /// Append a reference token to the current path
+    fn push_path(&mut self, token: &str) -> usize {
+        let len = self.path.len();
+        self.path.push('/');
+        self.path
+            .push_str(&token.replace('~', "~0").replace('/', "~1"));
+        len
+    }
+
+    fn compare_arrays(&mut self) -> Result<bool, DiffError> {
+        let mut i = 0;
+        let (mut l, mut r) = (self.next_left()?, self.next_right()?);
+        loop {
+            let len = self.push_path(&i.to_string());
+            let more = match (l, r) {
+                (Some(JsonEvent::EndArray), Some(JsonEvent::EndArray)) => {
+                    self.path.truncate(len);
+                    return Ok(true);
+                }
+                (Some(JsonEvent::EndArray), Some(re)) => {
+                    let more = self.only_right(re)?;
+                    r = self.next_right()?;
+                    more
+                }
+                (Some(le), Some(JsonEvent::EndArray)) => {
+                    let more = self.only_left(le)?;
+                    l = self.next_left()?;
+                    more
+                }
+                (Some(le), Some(re)) => {
+                    let more = self.compare_values(le, re)?;
+                    l = self.next_left()?;
+                    r = self.next_right()?;
+                    more
+                }
+                // the parsers report an error before the end of the JSON
+                // text if an array has not been closed
+                _ => true,
+            };
+            self.path.truncate(len);
+            if !more {
+                return Ok(false);
+            }
+            i += 1;
+        }
+    }
+
+    fn compare_objects(&mut self) -> Result<bool, DiffError> {
+        let (mut l, mut r) = (self.next_left()?, self.next_right()?);
+        loop {
+            let more = match (l, r) {
+                (Some(JsonEvent::EndObject), Some(JsonEvent::EndObject)) => return Ok(true),
+                (Some(JsonEvent::EndObject), Some(_)) => {
+                    let name = self.field_name_right()?;
+                    let len = self.push_path(&name);
+                    let re = self.next_right()?.unwrap_or(JsonEvent::ValueNull);
+                    let more = self.only_right(re)?;
+                    self.path.truncate(len);
+                    r = self.next_right()?;
+                    more
+                }
+                (Some(_), Some(JsonEvent::EndObject)) => {
+                    let name = self.field_name_left()?;
+                    let len = self.push_path(&name);
+                    let le = self.next_left()?.unwrap_or(JsonEvent::ValueNull);
+                    let more = self.only_left(le)?;
+                    self.path.truncate(len);
+                    l = self.next_left()?;
+                    more
+                }
+                (Some(_), Some(_)) => {
+                    let (a, b) = (self.field_name_left()?, self.field_name_right()?);
+                    let offsets = (Some(self.left_offset()), Some(self.right_offset()));
+                    let le = self.next_left()?.unwrap_or(JsonEvent::ValueNull);
+                    let re = self.next_right()?.unwrap_or(JsonEvent::ValueNull);
+                    let more = if a == b {
+                        let len = self.push_path(&a);
+                        let more = self.compare_values(le, re)?;
+                        self.path.truncate(len);
+                        more
+                    } else {
+                        let kind = DifferenceKind::FieldName { left: a, right: b };
+                        let more = self.report(offsets.0, offsets.1, kind);
+                        if more {
+                            skip_rest(self.left, le).map_err(DiffError::Left)?;
+                            skip_rest(self.right, re).map_err(DiffError::Right)?;
+                        }
+                        more
+                    };
+                    if more {
+                        l = self.next_left()?;
+                        r = self.next_right()?;
+                    }
+                    more
+                }
+                // the parsers report an error before the end of the JSON
+                // text if an object has not been closed
+                _ => return Ok(true),
+            };
+            if !more {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn field_name_left(&self) -> Result<String, DiffError> {
+        self.left
+            .current_str()
+            .map(str::to_string)
+            .map_err(|e| DiffError::Left(e.into()))
+    }
+
+    fn field_name_right(&self) -> Result<String, DiffError> {
+        self.right
+            .current_str()
+            .map(str::to_string)
+            .map_err(|e| DiffError::Right(e.into()))
+    }
+}
+
+/// Compares two JSON texts for equality while streaming them. Unlike
+/// [`diff()`], objects are equal by default if they have the same members
+/// in any order, and numbers can be compared with a tolerance. Comparing
+/// stops at the first mismatch.
+///
+/// As long as the members of two objects appear in the same order, only
+/// the current path is kept in memory. Once the order differs, the rest of
+/// both objects is read into [`JsonValue`]s and compared independently of
+/// the order, so texts written by the same serializer can be compared in
+/// constant memory even if they are huge.
+///
+/// ```
+/// use actson::diff::JsonEq;
+/// use actson::feeder::SliceJsonFeeder;
+///
+/// let left = SliceJsonFeeder::new(br#"{"a": [0.1, 2], "b": null}"#);
+/// let right = SliceJsonFeeder::new(br#"{"b": null, "a": [0.10000001, 2.0]}"#);
+/// assert!(JsonEq::new().with_tolerance(1e-6).check(left, right).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonEq {
+    ordered_objects: bool,
+    tolerance: f64,
+    relative_tolerance: f64,
+}
+
+impl JsonEq {
+    /// Create a comparison that ignores the order of object members and
+    /// compares numbers exactly by value
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require object members to appear in the same order
+    pub fn with_ordered_objects(mut self, ordered_objects: bool) -> Self {
+        self.ordered_objects = ordered_objects;
+        self
+    }
+
+    /// Consider two numbers equal if their absolute difference is at most
+    /// the given value
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Consider two numbers equal if their absolute difference is at most
+    /// the given fraction of the larger absolute value of the two
+    pub fn with_relative_tolerance(mut self, relative_tolerance: f64) -> Self {
+        self.relative_tolerance = relative_tolerance;
+        self
+    }
+
+    /// Compare the JSON texts provided by the two feeders
+    pub fn check<A, B>(&self, left: A, right: B) -> Result<bool, DiffError>
+    where
+        A: FillJsonFeeder,
+        B: FillJsonFeeder,
+    {
+        let mut left = JsonParser::new(left);
+        let mut right = JsonParser::new(right);
+        Ok(self.first_mismatch(&mut left, &mut right)?.is_none())
+    }
+
+    /// Compare the JSON texts of the two parsers and return the JSON
+    /// Pointer of the first location at which they differ or `None` if
+    /// they are equal. If two objects have different members, the pointer
+    /// refers to the object or to the first member that is missing or
+    /// different.
+    pub fn first_mismatch<A, SA, B, SB>(
+        &self,
+        left: &mut JsonParser<A, SA>,
+        right: &mut JsonParser<B, SB>,
+    ) -> Result<Option<String>, DiffError>
+    where
+        A: FillJsonFeeder,
+        SA: ParserStorage,
+        B: FillJsonFeeder,
+        SB: ParserStorage,
+    {
+        let mut walker = EqWalker {
+            left,
+            right,
+            options: self,
+            path: String::new(),
+        };
+        if walker.compare_texts()? {
+            Ok(None)
+        } else {
+            Ok(Some(walker.path))
+        }
+    }
+
+    /// Returns `true` if the two numbers are equal within the tolerances
+    fn numbers_eq(&self, a: JsonNumber<'_>, b: JsonNumber<'_>) -> bool {
+        if numbers_equal(a, b) {
+            return true;
+        }
+        let (a, b) = (number_to_f64(a), number_to_f64(b));
+        let diff = (a - b).abs();
+        diff <= self.tolerance || diff <= self.relative_tolerance * a.abs().max(b.abs())
+    }
+
+    /// Compare two values that have been read into memory. On a mismatch,
+    /// the reference tokens leading to it are appended to `path`.
+    fn values_eq(&self, a: &JsonValue, b: &JsonValue, path: &mut String) -> bool {
+        match (a, b) {
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                // large objects index their members, so looking up each
+                // member of `a` in `b` takes constant time on average
+                a.iter().all(|(k, v)| {
+                    let len = path.len();
+                    push_token(path, k);
+                    let equal = b.get(k).is_some_and(|w| self.values_eq(v, w, path));
+                    if equal {
+                        path.truncate(len);
+                    }
+                    equal
+                })
+            }
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                a.iter().zip(b).enumerate().all(|(i, (v, w))| {
+                    let len = path.len();
+                    push_token(path, &i.to_string());
+                    let equal = self.values_eq(v, w, path);
+                    if equal {
+                        path.truncate(len);
+                    }
+                    equal
+                })
+            }
+            _ => match (value_number(a), value_number(b)) {
+                (Some(a), Some(b)) => self.numbers_eq(a, b),
+                _ => a == b,
+            },
+        }
+    }
+}
+
+/// Compare the JSON texts provided by the two feeders with the default
+/// options of [`JsonEq`] (i.e. ignoring the order of object members)
+pub fn json_eq<A, B>(left: A, right: B) -> Result<bool, DiffError>
+where
+    A: FillJsonFeeder,
+    B: FillJsonFeeder,
+{
+    JsonEq::new().check(left, right)
+}
+
+/// Assert that the JSON texts provided by the two feeders are equal (see
+/// [`json_eq()`]). Panics with the location of the first mismatch if they
+/// are not or if one of them cannot be parsed.
+///
+/// ```
+/// use actson::diff::assert_json_stream_eq;
+/// use actson::feeder::SliceJsonFeeder;
+///
+/// assert_json_stream_eq(
+///     SliceJsonFeeder::new(br#"{"a": 1, "b": [true]}"#),
+///     SliceJsonFeeder::new(br#"{"b": [true], "a": 1.0}"#),
+/// );
+/// ```
+#[track_caller]
+pub fn assert_json_stream_eq<A, B>(left: A, right: B)
+where
+    A: FillJsonFeeder,
+    B: FillJsonFeeder,
+{
+    let mut left = JsonParser::new(left);
+    let mut right = JsonParser::new(right);
+    match JsonEq::new().first_mismatch(&mut left, &mut right) {
+        Ok(None) => {}
+        Ok(Some(path)) => panic!("JSON texts differ at `{path}'"),
+        Err(e) => panic!("JSON texts could not be compared: {e}"),
+    }
+}
+
+/// Convert a number to the nearest [`f64`]
+fn number_to_f64(n: JsonNumber<'_>) -> f64 {
+    match n {
+        JsonNumber::I64(i) => i as f64,
+        JsonNumber::U64(u) => u as f64,
+        JsonNumber::F64(f) => f,
+        JsonNumber::Big(s) => s.parse().unwrap_or(f64::NAN),
+    }
+}
+
+/// Get the number a value contains
+fn value_number(v: &JsonValue) -> Option<JsonNumber<'_>> {
+    match v {
+        JsonValue::Int(i) => Some(JsonNumber::I64(*i)),
+        JsonValue::UInt(u) => Some(JsonNumber::U64(*u)),
+        JsonValue::Float(f) => Some(JsonNumber::F64(*f)),
+        JsonValue::RawNumber(s) => Some(JsonNumber::Big(s)),
+        _ => None,
+    }
+}
+
+/// Append an escaped reference token to a JSON Pointer
+fn push_token(path: &mut String, token: &str) {
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+/// Read the rest of the value that starts with the given event into memory
+fn read_value<T, S>(
+    parser: &mut JsonParser<T, S>,
+    event: JsonEvent,
+) -> Result<JsonValue, ReaderError>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    Ok(match event {
+        JsonEvent::StartObject => {
+            let mut object = JsonObject::new();
+            while let Some(JsonEvent::FieldName) = next(parser)? {
+                let name = parser.current_str()?.to_string();
+                let e = next(parser)?.ok_or(ParserError::NoMoreInput)?;
+                object.insert(name, read_value(parser, e)?);
+            }
+            JsonValue::Object(object)
+        }
+        JsonEvent::StartArray => {
+            let mut array = Vec::new();
+            loop {
+                match next(parser)?.ok_or(ParserError::NoMoreInput)? {
+                    JsonEvent::EndArray => break,
+                    e => array.push(read_value(parser, e)?),
+                }
+            }
+            JsonValue::Array(array)
+        }
+        JsonEvent::ValueString => JsonValue::String(parser.current_str()?.to_string()),
+        JsonEvent::ValueInt | JsonEvent::ValueFloat => match parser.current_number()? {
+            JsonNumber::I64(i) => JsonValue::Int(i),
+            JsonNumber::U64(u) => JsonValue::UInt(u),
+            JsonNumber::F64(f) => JsonValue::Float(f),
+            JsonNumber::Big(s) => JsonValue::RawNumber(s.to_string()),
+        },
+        JsonEvent::ValueTrue => JsonValue::Bool(true),
+        JsonEvent::ValueFalse => JsonValue::Bool(false),
+        _ => JsonValue::Null,
+    })
+}
+
+/// Reads two parsers in lockstep until they differ
+struct EqWalker<'l, 'r, 'o, A, SA, B, SB> {
+    left: &'l mut JsonParser<A, SA>,
+    right: &'r mut JsonParser<B, SB>,
+    options: &'o JsonEq,
+
+    /// The JSON Pointer to the values currently being compared
+    path: String,
+}
+
+impl<A, SA, B, SB> EqWalker<'_, '_, '_, A, SA, B, SB>
+where
+    A: FillJsonFeeder,
+    SA: ParserStorage,
+    B: FillJsonFeeder,
+    SB: ParserStorage,
+{
+    fn next_left(&mut self) -> Result<Option<JsonEvent>, DiffError> {
+        next(self.left).map_err(DiffError::Left)
+    }
+
+    fn next_right(&mut self) -> Result<Option<JsonEvent>, DiffError> {
+        next(self.right).map_err(DiffError::Right)
+    }
+
+    /// Compare all top-level values (there may be more than one in
+    /// streaming mode)
+    fn compare_texts(&mut self) -> Result<bool, DiffError> {
+        loop {
+            match (self.next_left()?, self.next_right()?) {
+                (None, None) => return Ok(true),
+                (Some(l), Some(r)) => {
+                    if !self.compare_values(l, r)? {
+                        return Ok(false);
+                    }
+                }
+                _ => return Ok(false),
+            }
+        }
+    }
+
+    fn compare_values(&mut self, l: JsonEvent, r: JsonEvent) -> Result<bool, DiffError> {
+        if ValueType::of(l) != ValueType::of(r) {
+            return Ok(false);
+        }
+        Ok(match l {
+            JsonEvent::StartObject => return self.compare_objects(),
+            JsonEvent::StartArray => return self.compare_arrays(),
+            JsonEvent::ValueString => {
+                let a = self
+                    .left
+                    .current_str()
+                    .map_err(|e| DiffError::Left(e.into()))?;
+                let b = self
+                    .right
+                    .current_str()
+                    .map_err(|e| DiffError::Right(e.into()))?;
+                a == b
+            }
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let a = self
+                    .left
+                    .current_number()
+                    .map_err(|e| DiffError::Left(e.into()))?;
+                let b = self
+                    .right
+                    .current_number()
+                    .map_err(|e| DiffError::Right(e.into()))?;
+                self.options.numbers_eq(a, b)
+            }
+            _ => l == r,
+        })
+    }
+
+    fn compare_arrays(&mut self) -> Result<bool, DiffError> {
+        let mut i = 0;
+        loop {
+            match (self.next_left()?, self.next_right()?) {
+                (Some(JsonEvent::EndArray), Some(JsonEvent::EndArray)) => return Ok(true),
+                (Some(JsonEvent::EndArray), _) | (_, Some(JsonEvent::EndArray)) => {
+                    return Ok(false)
+                }
+                (Some(l), Some(r)) => {
+                    let len = self.path.len();
+                    push_token(&mut self.path, &i.to_string());
+                    if !self.compare_values(l, r)? {
+                        return Ok(false);
+                    }
+                    self.path.truncate(len);
+                }
+                // the parsers report an error before the end of the JSON
+                // text if an array has not been closed
+                _ => return Ok(true),
+            }
+            i += 1;
+        }
+    }
+
+    fn compare_objects(&mut self) -> Result<bool, DiffError> {
+        loop {
+            match (self.next_left()?, self.next_right()?) {
+                (Some(JsonEvent::EndObject), Some(JsonEvent::EndObject)) => return Ok(true),
+                (Some(JsonEvent::FieldName), Some(JsonEvent::FieldName)) => {
+                    let a = self
+                        .left
+                        .current_str()
+                        .map_err(|e| DiffError::Left(e.into()))?;
+                    let b = self
+                        .right
+                        .current_str()
+                        .map_err(|e| DiffError::Right(e.into()))?;
+                    if a != b {
+                        if self.options.ordered_objects {
+                            return Ok(false);
+                        }
+                        let (a, b) = (a.to_string(), b.to_string());
+                        return self.compare_rest(a, b);
+                    }
+                    let len = self.path.len();
+                    push_token(&mut self.path, a);
+                    let l = self.next_left()?.unwrap_or(JsonEvent::ValueNull);
+                    let r = self.next_right()?.unwrap_or(JsonEvent::ValueNull);
+                    if !self.compare_values(l, r)? {
+                        return Ok(false);
+                    }
+                    self.path.truncate(len);
+                }
+                _ => return Ok(false),
+            }
+        }
+    }
+
+    /// Read the remaining members of two objects whose current field names
+    /// differ and compare them independently of their order
+    fn compare_rest(&mut self, a: String, b: String) -> Result<bool, DiffError> {
+        let left = read_members(self.left, a).map_err(DiffError::Left)?;
+        let right = read_members(self.right, b).map_err(DiffError::Right)?;
+        Ok(self.options.values_eq(
+            &JsonValue::Object(left),
+            &JsonValue::Object(right),
+            &mut self.path,
+        ))
+    }
+}
+
+/// Read the rest of an object whose current field name is `name`
+fn read_members<T, S>(
+    parser: &mut JsonParser<T, S>,
+    name: String,
+) -> Result<JsonObject, ReaderError>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    let mut object = JsonObject::new();
+    let e = next(parser)?.ok_or(ParserError::NoMoreInput)?;
+    object.insert(name, read_value(parser, e)?);
+    while let Some(JsonEvent::FieldName) = next(parser)? {
+        let name = parser.current_str()?.to_string();
+        let e = next(parser)?.ok_or(ParserError::NoMoreInput)?;
+        object.insert(name, read_value(parser, e)?);
+    }
+    Ok(object)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, JsonParser};
+
+    use super::{diff, first_difference, json_eq, DifferenceKind, JsonEq};
+
+    fn differences(left: &str, right: &str) -> Vec<(String, DifferenceKind)> {
+        let options = JsonParserOptionsBuilder::default()
+            .with_streaming(true)
+            .build();
+        diff(
+            &mut JsonParser::new_with_options(SliceJsonFeeder::new(left.as_bytes()), options),
+            &mut JsonParser::new_with_options(SliceJsonFeeder::new(right.as_bytes()), options),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|d| (d.path, d.kind))
+        .collect()
+    }
+
+    /// Test that differences of all kinds are found and that comparing
+    /// continues after them
+    #[test]
+    fn all() {
+        assert_eq!(
+            differences(r#"{"a": [1, "x"]} 2"#, r#"{"a": [1.0, "x"]} 2"#),
+            vec![]
+        );
+        assert_eq!(
+            differences(
+                r#"{"a": {"b": [1]}, "c~": true, "d": 1, "e": 3, "f": 1}"#,
+                r#"{"a": {"b": {}}, "c~": false, "x": 1, "e": 3}"#
+            ),
+            vec![
+                ("/a/b".to_string(), DifferenceKind::Type),
+                ("/c~0".to_string(), DifferenceKind::Value),
+                (
+                    "".to_string(),
+                    DifferenceKind::FieldName {
+                        left: "d".to_string(),
+                        right: "x".to_string()
+                    }
+                ),
+                ("/f".to_string(), DifferenceKind::OnlyLeft),
+            ]
+        );
+        assert_eq!(
+            differences("[1, [2, 3]] 4 5", "[[2], [2]] 4"),
+            vec![
+                ("/0".to_string(), DifferenceKind::Type),
+                ("/1/1".to_string(), DifferenceKind::OnlyLeft),
+                ("".to_string(), DifferenceKind::OnlyLeft),
+            ]
+        );
+    }
+
+    /// Test that comparing stops at the first difference
+    #[test]
+    fn first() {
+        let left = br#"{"a": [1, 2], "b": 3}"#;
+        let right = br#"{"a": [1, 5], "b": 4}"#;
+        let mut l = JsonParser::new(SliceJsonFeeder::new(left));
+        let mut r = JsonParser::new(SliceJsonFeeder::new(right));
+        let d = first_difference(&mut l, &mut r).unwrap().unwrap();
+        assert_eq!(d.path, "/a/1");
+        assert_eq!(d.kind, DifferenceKind::Value);
+        assert_eq!((d.left_offset, d.right_offset), (Some(10), Some(10)));
+        assert!(l.parsed_bytes() < left.len());
+
+        let mut l = JsonParser::new(SliceJsonFeeder::new(left));
+        let mut r = JsonParser::new(SliceJsonFeeder::new(left));
+        assert_eq!(first_difference(&mut l, &mut r).unwrap(), None);
+    }
+
+    /// Test that texts are equal regardless of the order of object members
+    /// and that the first mismatch is located
+    #[test]
+    fn eq() {
+        let eq = |a: &str, b: &str| {
+            json_eq(
+                SliceJsonFeeder::new(a.as_bytes()),
+                SliceJsonFeeder::new(b.as_bytes()),
+            )
+            .unwrap()
+        };
+        assert!(eq(
+            r#"{"a": [1, {"x": 2, "y": 3}], "b": 1}"#,
+            r#"{"a": [1.0, {"y": 3, "x": 2}], "b": 1}"#
+        ));
+        assert!(eq(
+            r#"{"a": 1, "b": {"c": [], "d": "e"}}"#,
+            r#"{"b": {"d": "e", "c": []}, "a": 1}"#
+        ));
+        assert!(!eq(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1, "c": 3}"#));
+        assert!(!eq("[1, 2]", "[2, 1]"));
+
+        let mismatch = |options: JsonEq, a: &str, b: &str| {
+            options
+                .first_mismatch(
+                    &mut JsonParser::new(SliceJsonFeeder::new(a.as_bytes())),
+                    &mut JsonParser::new(SliceJsonFeeder::new(b.as_bytes())),
+                )
+                .unwrap()
+        };
+        let options = JsonEq::new();
+        assert_eq!(
+            mismatch(
+                options,
+                r#"{"a": {"b/c": [1, 2]}}"#,
+                r#"{"a": {"b/c": [1, 3]}}"#
+            ),
+            Some("/a/b~1c/1".to_string())
+        );
+        assert_eq!(
+            mismatch(
+                options,
+                r#"{"x": 0, "a": [1], "b": [2]}"#,
+                r#"{"x": 0, "b": [2], "a": [5]}"#
+            ),
+            Some("/a/0".to_string())
+        );
+        assert_eq!(
+            mismatch(
+                options.with_ordered_objects(true),
+                r#"{"a": 1, "b": 2}"#,
+                r#"{"b": 2, "a": 1}"#
+            ),
+            Some("".to_string())
+        );
+
+        // numbers can be compared with a tolerance
+        assert_eq!(
+            mismatch(options, "[1.5]", "[1.5000001]"),
+            Some("/0".to_string())
+        );
+        assert_eq!(
+            mismatch(options.with_tolerance(1e-3), "[1.5]", "[1.5000001]"),
+            None
+        );
+        assert_eq!(
+            mismatch(options.with_relative_tolerance(0.01), "[1000]", "[1005]"),
+            None
+        );
+        assert_eq!(
+            mismatch(options.with_relative_tolerance(0.01), "[1000]", "[1020]"),
+            Some("/0".to_string())
+        );
+    }
+
+    /// Test that large objects are compared regardless of the order of
+    /// their members without taking quadratic time
+    #[test]
+    fn eq_reordered() {
+        let n = 100_000;
+        let object = |keys: &mut dyn Iterator<Item = usize>| {
+            let members = keys.map(|i| format!("\"k{i}\": [{i}]")).collect::<Vec<_>>();
+            format!("{{{}}}", members.join(", "))
+        };
+        let a = object(&mut (0..n));
+        let b = object(&mut (0..n).rev());
+        assert!(json_eq(
+            SliceJsonFeeder::new(a.as_bytes()),
+            SliceJsonFeeder::new(b.as_bytes())
+        )
+        .unwrap());
+
+        let c = object(&mut (0..n).rev().map(|i| if i == 7 { n } else { i }));
+        let mismatch = JsonEq::new()
+            .first_mismatch(
+                &mut JsonParser::new(SliceJsonFeeder::new(a.as_bytes())),
+                &mut JsonParser::new(SliceJsonFeeder::new(c.as_bytes())),
+            )
+            .unwrap();
+        assert_eq!(mismatch, Some("/k7".to_string()));
+    }
+
+    /// Test that the assertion panics if the texts differ
+    #[test]
+    #[should_panic(expected = "JSON texts differ at `/b'")]
+    fn assert_stream_eq() {
+        super::assert_json_stream_eq(
+            SliceJsonFeeder::new(br#"{"a": 1, "b": 2}"#),
+            SliceJsonFeeder::new(br#"{"b": 3, "a": 1}"#),
+        );
+    }
+}
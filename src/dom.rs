@@ -0,0 +1,505 @@
+//! Parse a JSON text into a compact document for fast repeated access.
+//!
+//! A [`Dom`] stores all values of a JSON text in a flat array of nodes (a
+//! *tape*). Each node only contains the type of the value, its [`Span`] in
+//! the input, and the position of the node after it, so values can be
+//! skipped without looking at their children. Strings and numbers are not
+//! decoded until they are accessed. Compared to building a
+//! `serde_json::Value`, this needs far fewer allocations, and navigating
+//! to a value is still cheap.
+//!
+//! ```
+//! use actson::dom::Dom;
+//!
+//! let json = br#"{"name": "Elvis", "albums": [{"year": 1956}, {"year": 1957}]}"#;
+//! let dom = Dom::parse(json).unwrap();
+//! let root = dom.root();
+//! assert_eq!(root.get("name").unwrap().as_str().unwrap(), "Elvis");
+//! let year = root.pointer("/albums/1/year").unwrap().unwrap();
+//! assert_eq!(year.as_i64(), Some(1957));
+//!
+//! let years = root
+//!     .get("albums")
+//!     .unwrap()
+//!     .elements()
+//!     .filter_map(|a| a.get("year")?.as_i64())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(years, vec![1956, 1957]);
+//! ```
+
+use std::{borrow::Cow, str::from_utf8};
+
+use crate::{
+    feeder::SliceJsonFeeder, parser::NextValueError, unescape::unescape, JsonEvent, JsonNumber,
+    JsonParser, Span,
+};
+
+/// The type of a value in a [`Dom`] or a
+/// [`LazyValue`](crate::lazy::LazyValue)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DomKind {
+    /// An object
+    Object,
+
+    /// An array
+    Array,
+
+    /// A string
+    String,
+
+    /// An integer or a floating point number
+    Number,
+
+    /// The value `true`
+    True,
+
+    /// The value `false`
+    False,
+
+    /// The value `null`
+    Null,
+}
+
+/// A value or a field name on the tape
+#[derive(Clone, Debug)]
+struct Node {
+    kind: DomKind,
+
+    /// `true` if the node is a string that contains escape sequences
+    escaped: bool,
+
+    /// The position of the node in the JSON text
+    span: Span,
+
+    /// The number of elements or members if the node is an array or object
+    len: usize,
+
+    /// The index of the node after this one and all its children
+    next: usize,
+}
+
+/// A JSON text parsed into a tape of nodes. See the [module
+/// documentation](self).
+#[derive(Clone, Debug)]
+pub struct Dom<'a> {
+    json: &'a [u8],
+    nodes: Vec<Node>,
+}
+
+impl<'a> Dom<'a> {
+    /// Parse the given JSON text into a document
+    pub fn parse(json: &'a [u8]) -> Result<Self, NextValueError> {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        let mut nodes: Vec<Node> = Vec::new();
+
+        // the nodes of the objects and arrays that are currently open
+        let mut stack: Vec<usize> = Vec::new();
+
+        while let Some(e) = parser.next_event()? {
+            let kind = match e {
+                JsonEvent::NeedMoreInput => continue,
+                JsonEvent::EndObject | JsonEvent::EndArray => {
+                    if let Some(i) = stack.pop() {
+                        nodes[i].span.end = parser.current_span().end;
+                        nodes[i].next = nodes.len();
+                    }
+                    continue;
+                }
+                JsonEvent::StartObject => DomKind::Object,
+                JsonEvent::StartArray => DomKind::Array,
+                JsonEvent::FieldName | JsonEvent::ValueString => DomKind::String,
+                JsonEvent::ValueInt | JsonEvent::ValueFloat => DomKind::Number,
+                JsonEvent::ValueTrue => DomKind::True,
+                JsonEvent::ValueFalse => DomKind::False,
+                JsonEvent::ValueNull => DomKind::Null,
+            };
+
+            let span = parser.current_span();
+            let mut escaped = false;
+            if kind == DomKind::String {
+                // validate strings once, so they can be accessed without
+                // having to check them again
+                let raw = &json[span.start + 1..span.end - 1];
+                from_utf8(raw).map_err(|e| NextValueError::String(e.into()))?;
+                escaped = memchr::memchr(b'\\', raw).is_some();
+            }
+
+            if e != JsonEvent::FieldName {
+                if let Some(&parent) = stack.last() {
+                    nodes[parent].len += 1;
+                }
+            }
+
+            nodes.push(Node {
+                kind,
+                escaped,
+                span,
+                len: 0,
+                next: nodes.len() + 1,
+            });
+            if matches!(kind, DomKind::Object | DomKind::Array) {
+                stack.push(nodes.len() - 1);
+            }
+        }
+
+        Ok(Dom { json, nodes })
+    }
+
+    /// Get the top-level value
+    pub fn root(&self) -> DomValue<'_, 'a> {
+        DomValue { dom: self, i: 0 }
+    }
+
+    /// Get the raw bytes of the given node with or without the quotes of
+    /// strings
+    fn raw(&self, node: &Node, strip_quotes: bool) -> &'a [u8] {
+        if strip_quotes && node.kind == DomKind::String {
+            &self.json[node.span.start + 1..node.span.end - 1]
+        } else {
+            &self.json[node.span.start..node.span.end]
+        }
+    }
+
+    /// Decode the string at the given node
+    fn str(&self, i: usize) -> Cow<'a, str> {
+        let node = &self.nodes[i];
+        let raw = self.raw(node, true);
+        if node.escaped {
+            let mut out = Vec::with_capacity(raw.len());
+            unescape(raw, &mut out);
+            // the escape sequences have been decoded to valid UTF-8
+            Cow::Owned(String::from_utf8(out).unwrap_or_default())
+        } else {
+            // the string has been validated during parsing
+            Cow::Borrowed(from_utf8(raw).unwrap_or_default())
+        }
+    }
+
+    /// Check if the string at the given node equals `s`
+    fn str_eq(&self, i: usize, s: &str) -> bool {
+        let node = &self.nodes[i];
+        if node.escaped {
+            self.str(i) == s
+        } else {
+            self.raw(node, true) == s.as_bytes()
+        }
+    }
+}
+
+/// A reference to a value in a [`Dom`]
+#[derive(Clone, Copy, Debug)]
+pub struct DomValue<'d, 'a> {
+    dom: &'d Dom<'a>,
+    i: usize,
+}
+
+impl<'d, 'a> DomValue<'d, 'a> {
+    fn node(&self) -> &'d Node {
+        &self.dom.nodes[self.i]
+    }
+
+    /// Get the type of the value
+    pub fn kind(&self) -> DomKind {
+        self.node().kind
+    }
+
+    /// Get the position of the value in the JSON text
+    pub fn span(&self) -> Span {
+        self.node().span
+    }
+
+    /// Get the value exactly as it appears in the JSON text
+    pub fn raw(&self) -> &'a [u8] {
+        self.dom.raw(self.node(), false)
+    }
+
+    /// Get the number of elements of an array or members of an object.
+    /// Returns 0 for all other values.
+    pub fn len(&self) -> usize {
+        self.node().len
+    }
+
+    /// Returns `true` if the value is not an array or object or if it is
+    /// empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the value of the field with the given name if the value is an
+    /// object. If the object contains the field more than once, the first
+    /// occurrence is returned.
+    pub fn get(&self, name: &str) -> Option<DomValue<'d, 'a>> {
+        self.members_raw()
+            .find(|&(k, _)| self.dom.str_eq(k, name))
+            .map(|(_, v)| DomValue {
+                dom: self.dom,
+                i: v,
+            })
+    }
+
+    /// Get the element at the given index if the value is an array
+    pub fn at(&self, index: usize) -> Option<DomValue<'d, 'a>> {
+        self.elements().nth(index)
+    }
+
+    /// Navigate to the value at the given JSON Pointer (e.g. `/a/0/b`)
+    /// relative to this value. Returns `Ok(None)` if there is no such value
+    /// and [`NextValueError::InvalidPointer`] if the pointer is neither
+    /// empty nor starts with `/`.
+    pub fn pointer(&self, pointer: &str) -> Result<Option<DomValue<'d, 'a>>, NextValueError> {
+        if pointer.is_empty() {
+            return Ok(Some(*self));
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(NextValueError::InvalidPointer(pointer.to_string()));
+        };
+        Ok(rest.split('/').try_fold(*self, |v, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match v.kind() {
+                DomKind::Object => v.get(&token),
+                DomKind::Array if token == "0" || !token.starts_with('0') => {
+                    v.at(token.parse().ok()?)
+                }
+                _ => None,
+            }
+        }))
+    }
+
+    /// Iterate over the elements if the value is an array. The iterator is
+    /// empty for all other values.
+    pub fn elements(&self) -> Elements<'d, 'a> {
+        let node = self.node();
+        let (i, end) = if node.kind == DomKind::Array {
+            (self.i + 1, node.next)
+        } else {
+            (0, 0)
+        };
+        Elements {
+            dom: self.dom,
+            i,
+            end,
+        }
+    }
+
+    /// Iterate over the names and values of the members if the value is an
+    /// object. The iterator is empty for all other values.
+    pub fn members(&self) -> Members<'d, 'a> {
+        Members {
+            dom: self.dom,
+            inner: self.members_raw(),
+        }
+    }
+
+    /// Iterate over the node indexes of the names and values of the members
+    fn members_raw(&self) -> RawMembers<'d, 'a> {
+        let node = self.node();
+        let (i, end) = if node.kind == DomKind::Object {
+            (self.i + 1, node.next)
+        } else {
+            (0, 0)
+        };
+        RawMembers {
+            dom: self.dom,
+            i,
+            end,
+        }
+    }
+
+    /// Get the decoded string if the value is a string. The result borrows
+    /// from the JSON text unless the string contains escape sequences.
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        (self.kind() == DomKind::String).then(|| self.dom.str(self.i))
+    }
+
+    /// Get the number in its best-fitting representation if the value is a
+    /// number
+    pub fn as_number(&self) -> Option<JsonNumber<'a>> {
+        if self.kind() != DomKind::Number {
+            return None;
+        }
+        JsonNumber::from_literal(from_utf8(self.raw()).ok()?).ok()
+    }
+
+    /// Get the number if the value is an integer that fits into an [`i64`]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.as_number()? {
+            JsonNumber::I64(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Get the number if the value is a non-negative integer that fits into
+    /// a [`u64`]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.as_number()? {
+            JsonNumber::I64(i) => i.try_into().ok(),
+            JsonNumber::U64(u) => Some(u),
+            _ => None,
+        }
+    }
+
+    /// Get the number as an [`f64`] if the value is a number. Large numbers
+    /// may lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.as_number()? {
+            JsonNumber::I64(i) => Some(i as f64),
+            JsonNumber::U64(u) => Some(u as f64),
+            JsonNumber::F64(f) => Some(f),
+            JsonNumber::Big(s) => s.parse().ok(),
+        }
+    }
+
+    /// Get the value if it is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind() {
+            DomKind::True => Some(true),
+            DomKind::False => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is `null`
+    pub fn is_null(&self) -> bool {
+        self.kind() == DomKind::Null
+    }
+}
+
+/// An iterator over the elements of an array in a [`Dom`]. Create it with
+/// [`DomValue::elements()`].
+pub struct Elements<'d, 'a> {
+    dom: &'d Dom<'a>,
+    i: usize,
+    end: usize,
+}
+
+impl<'d, 'a> Iterator for Elements<'d, 'a> {
+    type Item = DomValue<'d, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i >= self.end {
+            return None;
+        }
+        let v = DomValue {
+            dom: self.dom,
+            i: self.i,
+        };
+        self.i = self.dom.nodes[self.i].next;
+        Some(v)
+    }
+}
+
+/// Iterates over the node indexes of the names and values of an object's
+/// members
+struct RawMembers<'d, 'a> {
+    dom: &'d Dom<'a>,
+    i: usize,
+    end: usize,
+}
+
+impl Iterator for RawMembers<'_, '_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i >= self.end {
+            return None;
+        }
+        let r = (self.i, self.i + 1);
+        self.i = self.dom.nodes[self.i + 1].next;
+        Some(r)
+    }
+}
+
+/// An iterator over the names and values of the members of an object in a
+/// [`Dom`]. Create it with [`DomValue::members()`].
+pub struct Members<'d, 'a> {
+    dom: &'d Dom<'a>,
+    inner: RawMembers<'d, 'a>,
+}
+
+impl<'d, 'a> Iterator for Members<'d, 'a> {
+    type Item = (Cow<'a, str>, DomValue<'d, 'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.inner.next()?;
+        Some((
+            self.dom.str(k),
+            DomValue {
+                dom: self.dom,
+                i: v,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parser::NextValueError, JsonNumber};
+
+    use super::{Dom, DomKind};
+
+    /// Test that values of all types can be navigated to and read
+    #[test]
+    fn navigate() {
+        let json = br#" {"a": [1, -2.5, 18446744073709551616, "x\n\u00e9"],
+            "b": {"c": true, "d": false, "e": null, "f": {}, "g": []},
+            "a\/b": "escaped"} "#;
+        let dom = Dom::parse(json).unwrap();
+        let root = dom.root();
+        assert_eq!(root.kind(), DomKind::Object);
+        assert_eq!(root.len(), 3);
+        assert_eq!(root.raw(), &json[1..json.len() - 1]);
+
+        let a = root.get("a").unwrap();
+        assert_eq!(a.len(), 4);
+        assert_eq!(a.at(0).unwrap().as_i64(), Some(1));
+        assert_eq!(a.at(1).unwrap().as_f64(), Some(-2.5));
+        assert_eq!(
+            a.at(2).unwrap().as_number(),
+            Some(JsonNumber::Big("18446744073709551616"))
+        );
+        assert_eq!(a.at(3).unwrap().as_str().unwrap(), "x\n\u{e9}");
+        assert!(a.at(4).is_none());
+
+        let b = root.get("b").unwrap();
+        let kinds = b.members().map(|(k, v)| (k, v.kind())).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("c".into(), DomKind::True),
+                ("d".into(), DomKind::False),
+                ("e".into(), DomKind::Null),
+                ("f".into(), DomKind::Object),
+                ("g".into(), DomKind::Array),
+            ]
+        );
+        assert_eq!(b.get("c").unwrap().as_bool(), Some(true));
+        assert!(b.get("e").unwrap().is_null());
+        assert!(b.get("f").unwrap().is_empty());
+        assert_eq!(b.get("g").unwrap().elements().count(), 0);
+
+        assert_eq!(root.get("a/b").unwrap().as_str().unwrap(), "escaped");
+        assert_eq!(
+            root.pointer("/a~1b").unwrap().unwrap().as_str().unwrap(),
+            "escaped"
+        );
+        assert_eq!(root.pointer("/a/1").unwrap().unwrap().raw(), b"-2.5");
+        assert!(root.pointer("/a/01").unwrap().is_none());
+        assert!(root.pointer("/b/c/d").unwrap().is_none());
+        assert!(root.get("x").is_none());
+        assert!(a.get("x").is_none());
+
+        assert!(Dom::parse(b"[1, 2").is_err());
+    }
+
+    /// Test that a malformed pointer is reported as an error
+    #[test]
+    fn invalid_pointer() {
+        let dom = Dom::parse(br#"{"a": 1}"#).unwrap();
+        let root = dom.root();
+        assert!(matches!(
+            root.pointer("a"),
+            Err(NextValueError::InvalidPointer(p)) if p == "a"
+        ));
+        assert_eq!(root.pointer("").unwrap().unwrap().raw(), br#"{"a": 1}"#);
+    }
+}
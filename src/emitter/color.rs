@@ -0,0 +1,36 @@
+/// ANSI escape sequences that colorize the output of an emitter (see
+/// [`EmitterOptionsBuilder::with_colors()`](super::EmitterOptionsBuilder::with_colors)).
+/// Each field contains the sequence that is written before a token of the
+/// respective kind. The colors are reset after each token. An empty string
+/// leaves tokens of this kind uncolored.
+///
+/// The default scheme is similar to the one of `jq`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorScheme {
+    /// The color of field names
+    pub key: &'static str,
+
+    /// The color of string values
+    pub string: &'static str,
+
+    /// The color of numbers
+    pub number: &'static str,
+
+    /// The color of `true`, `false`, and `null`
+    pub literal: &'static str,
+
+    /// The color of brackets, braces, commas, and colons
+    pub punctuation: &'static str,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme {
+            key: "\x1b[1;34m",
+            string: "\x1b[32m",
+            number: "\x1b[36m",
+            literal: "\x1b[35m",
+            punctuation: "\x1b[1m",
+        }
+    }
+}
@@ -0,0 +1,845 @@
+use std::{cmp::Ordering, fmt::Display, io::Write};
+
+use num_traits::PrimInt;
+
+use crate::{
+    escape::{escape_contents, escape_str_with, EscapeOptions},
+    feeder::JsonFeeder,
+    storage::ParserStorage,
+    JsonEvent, JsonParser, OwnedJsonEvent,
+};
+
+use super::{EmitterError, EmitterOptions, FloatFormat, Indent, Newline};
+
+/// A member of an object whose members are sorted before they are written
+/// or a member or element of a container that might be kept on one line
+struct Member {
+    key: String,
+
+    /// The member's output (including its key)
+    text: Vec<u8>,
+}
+
+/// An object or array that is currently open
+struct Container {
+    /// `true` if the container is an object
+    is_object: bool,
+
+    /// The number of members or elements written so far
+    len: usize,
+
+    /// The members collected so far if keys are sorted
+    members: Vec<Member>,
+
+    /// The members or elements collected so far if the container only
+    /// contains scalars and might be kept on a single line
+    inline: Option<Vec<Member>>,
+}
+
+/// Formats JSON tokens into a byte buffer and keeps track of where
+/// separators are needed. Shared by the synchronous and asynchronous
+/// emitters, which only differ in how the buffer is written out.
+#[derive(Default)]
+pub(crate) struct Encoder {
+    options: EmitterOptions,
+
+    /// The currently open objects and arrays
+    stack: Vec<Container>,
+
+    /// `true` if a field name has been written and its value is next
+    after_field_name: bool,
+
+    /// The number of top-level values written so far
+    top_level_values: usize,
+
+    /// Comments that will be written before the next member or at the end
+    /// of the current container
+    comments: Vec<String>,
+
+    /// `true` if a string value has been started with
+    /// [`Self::begin_string()`] but not ended yet
+    in_string: bool,
+}
+
+/// The kinds of tokens that can have different colors
+#[derive(Clone, Copy)]
+enum Token {
+    Key,
+    String,
+    Number,
+    Literal,
+    Punctuation,
+}
+
+/// Write the given color code, call the given function to write a token,
+/// and reset the color afterwards if necessary
+fn paint(color: &str, out: &mut Vec<u8>, f: impl FnOnce(&mut Vec<u8>)) {
+    out.extend_from_slice(color.as_bytes());
+    f(out);
+    reset(color, out);
+}
+
+/// Reset the given color if it is not empty
+fn reset(color: &str, out: &mut Vec<u8>) {
+    if !color.is_empty() {
+        out.extend_from_slice(b"\x1b[0m");
+    }
+}
+
+fn write_newline(newline: Newline, out: &mut Vec<u8>) {
+    match newline {
+        Newline::Lf => out.push(b'\n'),
+        Newline::CrLf => out.extend_from_slice(b"\r\n"),
+    }
+}
+
+/// Start a new line and indent it according to the given depth if
+/// pretty-printing is enabled
+fn line_break(indent: Option<Indent>, newline: Newline, depth: usize, out: &mut Vec<u8>) {
+    let Some(indent) = indent else {
+        return;
+    };
+    write_newline(newline, out);
+    match indent {
+        Indent::Spaces(n) => out.resize(out.len() + n * depth, b' '),
+        Indent::Tabs => out.resize(out.len() + depth, b'\t'),
+    }
+}
+
+/// Write the given comment as a line comment if pretty-printing is
+/// enabled and as a block comment otherwise
+fn write_comment(comment: &str, indent: Option<Indent>, out: &mut Vec<u8>) {
+    if indent.is_some() && !comment.contains(['\n', '\r']) {
+        out.extend_from_slice(b"// ");
+        out.extend_from_slice(comment.as_bytes());
+    } else {
+        out.extend_from_slice(b"/* ");
+        out.extend_from_slice(comment.as_bytes());
+        out.extend_from_slice(b" */");
+    }
+}
+
+/// Write the given comments, each followed by a line break (if
+/// pretty-printing is enabled)
+fn write_comments(
+    comments: &[String],
+    indent: Option<Indent>,
+    newline: Newline,
+    depth: usize,
+    out: &mut Vec<u8>,
+) {
+    for c in comments {
+        write_comment(c, indent, out);
+        if depth == 0 {
+            if indent.is_some() {
+                write_newline(newline, out);
+            }
+        } else {
+            line_break(indent, newline, depth, out);
+        }
+    }
+}
+
+/// Returns `true` if the given key can be written without quotes in JSON5
+/// (i.e. if it is an ASCII identifier)
+fn is_identifier(key: &str) -> bool {
+    let mut bytes = key.bytes();
+    bytes
+        .next()
+        .is_some_and(|b| b.is_ascii_alphabetic() || b == b'_' || b == b'$')
+        && bytes.all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'$')
+}
+
+/// Write a number the way ECMAScript's `Number.prototype.toString()` does,
+/// as required by RFC 8785
+pub(crate) fn write_es_number(value: f64, out: &mut Vec<u8>) {
+    if value == 0.0 {
+        out.push(b'0');
+        return;
+    }
+    if value < 0.0 {
+        out.push(b'-');
+    }
+
+    // Rust's scientific notation contains the shortest digits that
+    // round-trip, which is what ECMAScript uses too
+    let sci = format!("{:e}", value.abs());
+    let (mantissa, exp) = sci.split_once('e').expect("scientific notation");
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exp.parse::<i32>().expect("valid exponent") + 1;
+
+    if k <= n && n <= 21 {
+        out.extend_from_slice(digits.as_bytes());
+        out.resize(out.len() + (n - k) as usize, b'0');
+    } else if 0 < n && n <= 21 {
+        out.extend_from_slice(&digits.as_bytes()[..n as usize]);
+        out.push(b'.');
+        out.extend_from_slice(&digits.as_bytes()[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.extend_from_slice(b"0.");
+        out.resize(out.len() + (-n) as usize, b'0');
+        out.extend_from_slice(digits.as_bytes());
+    } else {
+        out.push(digits.as_bytes()[0]);
+        if k > 1 {
+            out.push(b'.');
+            out.extend_from_slice(&digits.as_bytes()[1..]);
+        }
+        // writing to a vector cannot fail
+        let _ = write!(out, "e{}{}", if n > 0 { '+' } else { '-' }, (n - 1).abs());
+    }
+}
+
+/// Compare two keys by their UTF-16 code units as required by RFC 8785
+pub(crate) fn cmp_utf16(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+impl Encoder {
+    pub(crate) fn new(options: EmitterOptions) -> Self {
+        Encoder {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Get the options the encoder has been created with
+    pub(crate) fn options(&self) -> &EmitterOptions {
+        &self.options
+    }
+
+    /// Returns `true` if the members of objects should be sorted
+    fn sorts_keys(&self) -> bool {
+        self.options.sort_keys || self.options.canonical
+    }
+
+    /// Returns the ANSI escape sequence for the given kind of token or an
+    /// empty string if the output is not colored
+    fn color(&self, token: Token) -> &'static str {
+        #[cfg(feature = "color")]
+        if let Some(c) = &self.options.colors {
+            return match token {
+                Token::Key => c.key,
+                Token::String => c.string,
+                Token::Number => c.number,
+                Token::Literal => c.literal,
+                Token::Punctuation => c.punctuation,
+            };
+        }
+        let _ = token;
+        ""
+    }
+
+    /// Returns the characters to escape in strings
+    pub(crate) fn escape_options(&self) -> EscapeOptions {
+        if self.options.canonical {
+            return EscapeOptions::default();
+        }
+        EscapeOptions {
+            ascii: self.options.ensure_ascii,
+            html: self.options.escape_html,
+            slash: self.options.escape_slash,
+        }
+    }
+
+    /// Returns the indentation to use or `None` if the output is compact
+    fn indent(&self) -> Option<Indent> {
+        if self.options.canonical {
+            None
+        } else {
+            self.options.indent
+        }
+    }
+
+    /// Returns `true` if the current container might be kept on one line
+    fn inlining(&self) -> bool {
+        self.stack.last().is_some_and(|c| c.inline.is_some())
+    }
+
+    /// Returns `true` if the current container is a candidate for being
+    /// kept on one line but is already too long
+    fn too_wide(&self) -> bool {
+        let (Some(max), Some(parts)) = (
+            self.options.max_inline_width,
+            self.stack.last().and_then(|c| c.inline.as_ref()),
+        ) else {
+            return false;
+        };
+        let len = parts.iter().map(|p| p.text.len() + 2).sum::<usize>();
+        len > max
+    }
+
+    /// Get the buffer the next token should be written to. If keys are
+    /// sorted, the members of each object are collected in separate buffers
+    /// until the object ends. Members of containers that might be kept on
+    /// one line are collected too.
+    fn target<'a>(&'a mut self, out: &'a mut Vec<u8>) -> &'a mut Vec<u8> {
+        if self
+            .stack
+            .last()
+            .is_some_and(|c| c.inline.as_ref().is_some_and(|p| !p.is_empty()))
+        {
+            let c = self.stack.last_mut().expect("current container");
+            let parts = c.inline.as_mut().expect("inline members");
+            return &mut parts.last_mut().expect("inline member").text;
+        }
+        match self
+            .stack
+            .iter_mut()
+            .rev()
+            .find_map(|c| c.members.last_mut())
+        {
+            Some(m) => &mut m.text,
+            None => out,
+        }
+    }
+
+    /// Write the current container that has been a candidate for being kept
+    /// on one line like any other container, because it contains a nested
+    /// container, a comment, or is too long
+    fn spill(&mut self, out: &mut Vec<u8>) {
+        let indent = self.indent();
+        let newline = self.options.newline;
+        let depth = self.stack.len();
+        let punctuation = self.color(Token::Punctuation);
+        let sorts_keys = self.sorts_keys();
+        let Some(c) = self.stack.last_mut() else {
+            return;
+        };
+        let Some(parts) = c.inline.take() else {
+            return;
+        };
+        let is_object = c.is_object;
+
+        paint(punctuation, self.target(out), |t| {
+            t.push(if is_object { b'{' } else { b'[' })
+        });
+        for (i, mut p) in parts.into_iter().enumerate() {
+            if is_object && sorts_keys {
+                let mut text = Vec::new();
+                line_break(indent, newline, depth, &mut text);
+                text.append(&mut p.text);
+                let c = self.stack.last_mut().expect("current container");
+                c.members.push(Member { key: p.key, text });
+            } else {
+                let t = self.target(out);
+                if i > 0 {
+                    paint(punctuation, t, |t| t.push(b','));
+                }
+                line_break(indent, newline, depth, t);
+                t.append(&mut p.text);
+            }
+        }
+    }
+
+    /// Start a new member of the current container if it might be kept on
+    /// one line. Returns `false` if the member has to be written as usual.
+    fn inline_member(&mut self, key: &str, out: &mut Vec<u8>) -> bool {
+        if !self.inlining() {
+            return false;
+        }
+        if !self.comments.is_empty() || self.too_wide() {
+            self.spill(out);
+            return false;
+        }
+        let c = self.stack.last_mut().expect("current container");
+        c.len += 1;
+        c.inline.as_mut().expect("inline members").push(Member {
+            key: key.to_string(),
+            text: Vec::new(),
+        });
+        true
+    }
+
+    /// Write the separator that is needed before the next member or element
+    fn before_member(&mut self, out: &mut Vec<u8>) {
+        if self.inline_member("", out) {
+            return;
+        }
+        let indent = self.indent();
+        let newline = self.options.newline;
+        let depth = self.stack.len();
+        let punctuation = self.color(Token::Punctuation);
+        let comments = std::mem::take(&mut self.comments);
+        if let Some(c) = self.stack.last_mut() {
+            c.len += 1;
+            let comma = c.len > 1;
+            let t = self.target(out);
+            if comma {
+                paint(punctuation, t, |t| t.push(b','));
+            }
+            line_break(indent, newline, depth, t);
+            write_comments(&comments, indent, newline, depth, t);
+        } else {
+            self.top_level_values += 1;
+            if self.top_level_values > 1 {
+                write_newline(newline, out);
+            }
+            write_comments(&comments, indent, newline, 0, out);
+        }
+    }
+
+    /// Write the separator that is needed before the next value. Returns
+    /// an error if a field name is expected instead.
+    /// Returns `true` if a value may be written at the current position
+    fn expects_value(&self) -> bool {
+        self.after_field_name || !self.stack.last().is_some_and(|c| c.is_object)
+    }
+
+    fn before_value(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        if !self.expects_value() {
+            return Err(EmitterError::UnexpectedToken("value"));
+        }
+        if self.after_field_name {
+            self.after_field_name = false;
+        } else {
+            self.before_member(out);
+        }
+        Ok(())
+    }
+
+    /// Write the start of an object or array
+    fn start_container(&mut self, is_object: bool, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        if self.expects_value() {
+            // only containers with scalars can be kept on one line
+            self.spill(out);
+        }
+        self.before_value(out)?;
+        let inline = self.indent().is_some() && self.options.max_inline_width.is_some();
+        if !inline {
+            let punctuation = self.color(Token::Punctuation);
+            paint(punctuation, self.target(out), |t| {
+                t.push(if is_object { b'{' } else { b'[' })
+            });
+        }
+        self.stack.push(Container {
+            is_object,
+            len: 0,
+            members: Vec::new(),
+            inline: inline.then(Vec::new),
+        });
+        Ok(())
+    }
+
+    /// Close the current container on the same line
+    fn end_inline(&mut self, out: &mut Vec<u8>) {
+        let punctuation = self.color(Token::Punctuation);
+        let sorts_keys = self.sorts_keys();
+        let Some(c) = self.stack.pop() else {
+            return;
+        };
+        let mut parts = c.inline.unwrap_or_default();
+        if c.is_object && sorts_keys {
+            parts.sort_by(|a, b| cmp_utf16(&a.key, &b.key));
+        }
+        let t = self.target(out);
+        paint(punctuation, t, |t| {
+            t.push(if c.is_object { b'{' } else { b'[' })
+        });
+        for (i, p) in parts.iter().enumerate() {
+            if i > 0 {
+                paint(punctuation, t, |t| t.push(b','));
+                t.push(b' ');
+            }
+            t.extend_from_slice(&p.text);
+        }
+        paint(punctuation, t, |t| {
+            t.push(if c.is_object { b'}' } else { b']' })
+        });
+    }
+
+    /// Close the current object or array. Returns an error if the current
+    /// container is not of the given type or if a field's value is missing.
+    fn end_container(&mut self, is_object: bool, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        if self.after_field_name || self.stack.last().map(|c| c.is_object) != Some(is_object) {
+            return Err(EmitterError::UnexpectedToken(if is_object {
+                "end of object"
+            } else {
+                "end of array"
+            }));
+        }
+        if self.inlining() {
+            if self.comments.is_empty() && !self.too_wide() {
+                self.end_inline(out);
+                return Ok(());
+            }
+            self.spill(out);
+        }
+        let indent = self.indent();
+        let newline = self.options.newline;
+        let punctuation = self.color(Token::Punctuation);
+        let trailing_comma = self.options.trailing_commas && indent.is_some();
+        let comments = std::mem::take(&mut self.comments);
+        let Some(mut c) = self.stack.pop() else {
+            return Ok(());
+        };
+        let depth = self.stack.len();
+        let t = self.target(out);
+
+        if !c.members.is_empty() {
+            c.members.sort_by(|a, b| cmp_utf16(&a.key, &b.key));
+            for (i, m) in c.members.iter().enumerate() {
+                if i > 0 {
+                    paint(punctuation, t, |t| t.push(b','));
+                }
+                t.extend_from_slice(&m.text);
+            }
+        }
+
+        if trailing_comma && c.len > 0 {
+            paint(punctuation, t, |t| t.push(b','));
+        }
+        for (i, comment) in comments.iter().enumerate() {
+            if i > 0 || indent.is_some() {
+                line_break(indent, newline, depth + 1, t);
+            }
+            write_comment(comment, indent, t);
+        }
+
+        if c.len > 0 || !comments.is_empty() {
+            line_break(indent, newline, depth, t);
+        }
+        paint(punctuation, t, |t| {
+            t.push(if c.is_object { b'}' } else { b']' })
+        });
+        Ok(())
+    }
+
+    pub(crate) fn start_object(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.start_container(true, out)
+    }
+
+    pub(crate) fn end_object(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.end_container(true, out)
+    }
+
+    pub(crate) fn start_array(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.start_container(false, out)
+    }
+
+    pub(crate) fn end_array(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.end_container(false, out)
+    }
+
+    pub(crate) fn field_name(&mut self, name: &str, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        if self.after_field_name || !self.stack.last().is_some_and(|c| c.is_object) {
+            return Err(EmitterError::UnexpectedToken("field name"));
+        }
+        let separator: &[u8] = if self.indent().is_some() { b": " } else { b":" };
+        let escape = self.escape_options();
+        let key = self.color(Token::Key);
+        let punctuation = self.color(Token::Punctuation);
+        let unquoted = self.options.unquoted_keys && !self.options.canonical && is_identifier(name);
+        let write_key = |t: &mut Vec<u8>| {
+            paint(key, t, |t| {
+                if unquoted {
+                    t.extend_from_slice(name.as_bytes());
+                } else {
+                    escape_str_with(name, escape, t);
+                }
+            });
+            paint(punctuation, t, |t| t.extend_from_slice(separator));
+        };
+
+        if self.inline_member(name, out) {
+            write_key(self.target(out));
+        } else if self.sorts_keys() {
+            let indent = self.indent();
+            let newline = self.options.newline;
+            let depth = self.stack.len();
+            if let Some(c) = self.stack.last_mut() {
+                c.len += 1;
+                let mut text = Vec::new();
+                line_break(indent, newline, depth, &mut text);
+                write_comments(&self.comments, indent, newline, depth, &mut text);
+                self.comments.clear();
+                write_key(&mut text);
+                c.members.push(Member {
+                    key: name.to_string(),
+                    text,
+                });
+            }
+        } else {
+            self.before_member(out);
+            write_key(self.target(out));
+        }
+
+        self.after_field_name = true;
+        Ok(())
+    }
+
+    pub(crate) fn value_str(&mut self, value: &str, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.before_value(out)?;
+        let escape = self.escape_options();
+        let color = self.color(Token::String);
+        paint(color, self.target(out), |t| {
+            escape_str_with(value, escape, t)
+        });
+        Ok(())
+    }
+
+    /// Write the start of a string value whose contents are written with
+    /// [`Self::string_chunk()`]
+    pub(crate) fn begin_string(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        if self.expects_value() {
+            // do not collect strings of unknown length in memory
+            self.spill(out);
+        }
+        self.before_value(out)?;
+        let color = self.color(Token::String);
+        let t = self.target(out);
+        t.extend_from_slice(color.as_bytes());
+        t.push(b'"');
+        self.in_string = true;
+        Ok(())
+    }
+
+    /// Write a part of the contents of the current string value
+    pub(crate) fn string_chunk(&mut self, chunk: &str, out: &mut Vec<u8>) {
+        let escape = self.escape_options();
+        escape_contents(chunk, escape, self.target(out));
+    }
+
+    /// Write the end of the current string value
+    pub(crate) fn end_string(&mut self, out: &mut Vec<u8>) {
+        let color = self.color(Token::String);
+        let t = self.target(out);
+        t.push(b'"');
+        reset(color, t);
+        self.in_string = false;
+    }
+
+    pub(crate) fn value_int<I>(&mut self, value: I, out: &mut Vec<u8>) -> Result<(), EmitterError>
+    where
+        I: PrimInt + Display,
+    {
+        self.before_value(out)?;
+        let canonical = self.options.canonical;
+        let color = self.color(Token::Number);
+        paint(color, self.target(out), |t| {
+            if canonical {
+                // every integer type fits into an f64, possibly with rounding
+                write_es_number(value.to_f64().unwrap_or_default(), t);
+            } else {
+                // writing to a vector cannot fail
+                let _ = write!(t, "{value}");
+            }
+        });
+        Ok(())
+    }
+
+    pub(crate) fn value_float(
+        &mut self,
+        value: f64,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EmitterError> {
+        if !value.is_finite() {
+            return Err(EmitterError::IllegalJsonNumber(value));
+        }
+        self.before_value(out)?;
+        let canonical = self.options.canonical;
+        let float_format = self.options.float_format;
+        let color = self.color(Token::Number);
+        paint(color, self.target(out), |t| match float_format {
+            _ if canonical => write_es_number(value, t),
+            FloatFormat::Shortest => {
+                t.extend_from_slice(ryu::Buffer::new().format_finite(value).as_bytes());
+            }
+            FloatFormat::DecimalPoint => {
+                // `Display` prints the shortest round-trip digits without
+                // an exponent but omits the fraction of integral values
+                let s = value.to_string();
+                t.extend_from_slice(s.as_bytes());
+                if !s.contains('.') {
+                    t.extend_from_slice(b".0");
+                }
+            }
+            FloatFormat::Fixed(precision) => {
+                t.extend_from_slice(format!("{value:.precision$}").as_bytes());
+            }
+        });
+        Ok(())
+    }
+
+    /// Write a number as it appeared in a JSON text
+    pub(crate) fn value_number_str(
+        &mut self,
+        value: &str,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EmitterError> {
+        if self.options.canonical {
+            let f = value.parse::<f64>().unwrap_or(f64::NAN);
+            return self.value_float(f, out);
+        }
+        self.before_value(out)?;
+        let color = self.color(Token::Number);
+        paint(color, self.target(out), |t| {
+            t.extend_from_slice(value.as_bytes())
+        });
+        Ok(())
+    }
+
+    pub(crate) fn value_bool(
+        &mut self,
+        value: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EmitterError> {
+        self.before_value(out)?;
+        let color = self.color(Token::Literal);
+        paint(color, self.target(out), |t| {
+            t.extend_from_slice(if value { b"true" } else { b"false" })
+        });
+        Ok(())
+    }
+
+    /// Write a serialized JSON value verbatim
+    pub(crate) fn write_raw(&mut self, json: &str, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.before_value(out)?;
+        self.target(out).extend_from_slice(json.as_bytes());
+        Ok(())
+    }
+
+    pub(crate) fn value_null(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        self.before_value(out)?;
+        let color = self.color(Token::Literal);
+        paint(color, self.target(out), |t| t.extend_from_slice(b"null"));
+        Ok(())
+    }
+
+    /// Write a comment before the next member or at the end of the current
+    /// container. Returns an error if comments are not enabled or if the
+    /// comment cannot be represented.
+    pub(crate) fn comment(&mut self, comment: &str) -> Result<(), EmitterError> {
+        if !self.options.comments || self.options.canonical {
+            return Err(EmitterError::UnexpectedToken("comment"));
+        }
+        if comment.contains("*/") {
+            return Err(EmitterError::InvalidComment);
+        }
+        self.comments.push(comment.to_string());
+        Ok(())
+    }
+
+    /// Write comments that have not been written yet because no value
+    /// followed them
+    pub(crate) fn finish(&mut self, out: &mut Vec<u8>) {
+        if self.comments.is_empty() || !self.is_top_level() {
+            return;
+        }
+        let indent = self.indent();
+        if self.top_level_values > 0 {
+            write_newline(self.options.newline, out);
+        }
+        for (i, c) in self.comments.iter().enumerate() {
+            if i > 0 {
+                write_newline(self.options.newline, out);
+            }
+            write_comment(c, indent, out);
+        }
+        self.comments.clear();
+    }
+
+    /// Returns `true` if no object, array, or string is open
+    pub(crate) fn is_top_level(&self) -> bool {
+        self.stack.is_empty() && !self.in_string
+    }
+
+    /// Terminate the current line of top-level values with a line break so
+    /// that the next value starts without a separator. Returns an error if
+    /// an object or array is still open.
+    pub(crate) fn end_line(&mut self, out: &mut Vec<u8>) -> Result<(), EmitterError> {
+        if !self.is_top_level() {
+            return Err(EmitterError::UnexpectedToken("end of line"));
+        }
+        if self.top_level_values > 0 {
+            write_newline(self.options.newline, out);
+            self.top_level_values = 0;
+        }
+        Ok(())
+    }
+
+    /// Write the given event of the given parser together with its value.
+    /// Numbers are written as they appear in the JSON text unless the output
+    /// is canonical.
+    pub(crate) fn event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EmitterError>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartObject => self.start_object(out)?,
+            JsonEvent::EndObject => self.end_object(out)?,
+            JsonEvent::StartArray => self.start_array(out)?,
+            JsonEvent::EndArray => self.end_array(out)?,
+            JsonEvent::FieldName => self.field_name(parser.current_str()?, out)?,
+            JsonEvent::ValueString => self.value_str(parser.current_str()?, out)?,
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                self.value_number_str(parser.current_raw_str()?, out)?
+            }
+            JsonEvent::ValueTrue => self.value_bool(true, out)?,
+            JsonEvent::ValueFalse => self.value_bool(false, out)?,
+            JsonEvent::ValueNull => self.value_null(out)?,
+        }
+        Ok(())
+    }
+
+    /// Write the given event together with its value
+    pub(crate) fn owned_event(
+        &mut self,
+        event: &OwnedJsonEvent,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EmitterError> {
+        match event {
+            OwnedJsonEvent::NeedMoreInput => {}
+            OwnedJsonEvent::StartObject => self.start_object(out)?,
+            OwnedJsonEvent::EndObject => self.end_object(out)?,
+            OwnedJsonEvent::StartArray => self.start_array(out)?,
+            OwnedJsonEvent::EndArray => self.end_array(out)?,
+            OwnedJsonEvent::FieldName(name) => self.field_name(name, out)?,
+            OwnedJsonEvent::ValueString(s) => self.value_str(s, out)?,
+            OwnedJsonEvent::ValueInt(i) => self.value_int(*i, out)?,
+            OwnedJsonEvent::ValueUint(u) => self.value_int(*u, out)?,
+            OwnedJsonEvent::ValueFloat(f) => self.value_float(*f, out)?,
+            OwnedJsonEvent::ValueTrue => self.value_bool(true, out)?,
+            OwnedJsonEvent::ValueFalse => self.value_bool(false, out)?,
+            OwnedJsonEvent::ValueNull => self.value_null(out)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::write_es_number;
+
+    fn es(f: f64) -> String {
+        let mut out = Vec::new();
+        write_es_number(f, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Test numbers from the examples in RFC 8785, appendix B
+    #[test]
+    fn es_numbers() {
+        assert_eq!(es(0.0), "0");
+        assert_eq!(es(-0.0), "0");
+        assert_eq!(es(f64::from_bits(0x0000000000000001)), "5e-324");
+        assert_eq!(es(f64::MAX), "1.7976931348623157e+308");
+        assert_eq!(es(9007199254740992.0), "9007199254740992");
+        assert_eq!(es(-9007199254740992.0), "-9007199254740992");
+        assert_eq!(es(295147905179352830000.0), "295147905179352830000");
+        assert_eq!(es(1e21), "1e+21");
+        assert_eq!(es(1e-7), "1e-7");
+        assert_eq!(es(0.000001), "0.000001");
+        assert_eq!(es(333333333.3333333), "333333333.3333333");
+        assert_eq!(es(4.5), "4.5");
+        assert_eq!(es(-1.5e-7), "-1.5e-7");
+    }
+}
@@ -0,0 +1,906 @@
+//! Write JSON texts incrementally.
+//!
+//! [`JsonEmitter`] is the counterpart of the [`JsonParser`]: call a method
+//! for each token and the emitter writes valid JSON to any [`io::Write`],
+//! adding separators and escaping strings as needed. The output is
+//! buffered and written to the underlying writer in chunks (see
+//! [`EmitterOptionsBuilder::with_buffer_size()`]). Call
+//! [`JsonEmitter::finish()`] (or at least [`JsonEmitter::flush()`]) when
+//! you are done.
+//!
+//! ```
+//! use actson::emitter::JsonEmitter;
+//!
+//! let mut emitter = JsonEmitter::new(Vec::new());
+//! emitter.start_object().unwrap();
+//! emitter.field_name("name").unwrap();
+//! emitter.value_str("Elvis \"The King\"").unwrap();
+//! emitter.field_name("albums").unwrap();
+//! emitter.start_array().unwrap();
+//! emitter.value_int(1956).unwrap();
+//! emitter.value_float(19.57).unwrap();
+//! emitter.end_array().unwrap();
+//! emitter.end_object().unwrap();
+//!
+//! let json = emitter.finish().unwrap();
+//! assert_eq!(json, br#"{"name":"Elvis \"The King\"","albums":[1956,19.57]}"#);
+//! ```
+//!
+//! The emitter checks the order of calls and returns
+//! [`EmitterError::UnexpectedToken`] instead of writing invalid JSON (e.g.
+//! for a field name outside of an object, a value without a field name in
+//! an object, or mismatched ends of containers).
+//!
+//! The emitter is a [`JsonEventSink`], so it can re-emit the events of a
+//! parser (e.g. to minify a JSON text or to write the output of the
+//! adapters in the [`transform`](crate::transform) module). Numbers are
+//! written exactly as they appear in the original text. Multiple top-level
+//! values are separated by line breaks.
+//!
+//! By default, the output is compact. Use [`EmitterOptionsBuilder`] to
+//! pretty-print it. [`PrettyPrinter`] is a sink that pretty-prints events
+//! into a string.
+//!
+//! ```
+//! use actson::emitter::JsonEmitter;
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sink::parse_with;
+//!
+//! let json = br#"{ "a" : [ 1.50, true, null ] }"#;
+//! let mut emitter = JsonEmitter::new(Vec::new());
+//! parse_with(SliceJsonFeeder::new(json), &mut emitter).unwrap();
+//! assert_eq!(emitter.finish().unwrap(), br#"{"a":[1.50,true,null]}"#);
+//! ```
+
+#[cfg(feature = "color")]
+mod color;
+mod encoder;
+mod ndjson;
+mod options;
+mod pretty;
+mod string;
+mod truncate;
+
+#[cfg(feature = "color")]
+pub use color::ColorScheme;
+pub(crate) use encoder::{cmp_utf16, write_es_number, Encoder};
+pub use ndjson::{array_to_ndjson, ndjson_to_array, ArrayToNdjsonError, NdjsonWriter};
+pub use options::{
+    EmitterOptions, EmitterOptionsBuilder, FloatFormat, Indent, Newline, DEFAULT_BUFFER_SIZE,
+};
+pub use pretty::PrettyPrinter;
+pub use string::StringWriter;
+pub use truncate::TruncatingWriter;
+
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use num_traits::PrimInt;
+use thiserror::Error;
+
+use crate::{
+    feeder::{FillJsonFeeder, JsonFeeder},
+    parser::InvalidStringValueError,
+    sink::{parse_with, JsonEventSink, ParseWithError},
+    storage::ParserStorage,
+    JsonEvent, JsonParser, OwnedJsonEvent,
+};
+
+/// An error that can happen when emitting JSON
+#[derive(Error, Debug)]
+pub enum EmitterError {
+    /// The output could not be written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// A float cannot be represented in JSON (e.g. NaN or infinity)
+    #[error("not a JSON number: {0}")]
+    IllegalJsonNumber(f64),
+
+    /// A method was called in an order that would produce invalid JSON (e.g.
+    /// a field name outside of an object or two values for the same field).
+    /// Nothing has been written.
+    #[error("unexpected {0}")]
+    UnexpectedToken(&'static str),
+
+    /// A comment contains `*/` and therefore cannot be written
+    #[error("comment must not contain `*/`")]
+    InvalidComment,
+
+    /// A string received from a parser is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+}
+
+/// Writes a JSON text to an [`io::Write`]. See the [module
+/// documentation](self).
+pub struct JsonEmitter<W> {
+    writer: W,
+    encoder: Encoder,
+
+    /// Output that has not been written to [`Self::writer`] yet
+    buf: Vec<u8>,
+}
+
+impl<W> JsonEmitter<W>
+where
+    W: Write,
+{
+    /// Create a new emitter writing compact JSON to the given writer
+    pub fn new(writer: W) -> Self {
+        Self::new_with_options(writer, EmitterOptions::default())
+    }
+
+    /// Create a new emitter writing to the given writer with the given
+    /// options
+    pub fn new_with_options(writer: W, options: EmitterOptions) -> Self {
+        JsonEmitter {
+            writer,
+            encoder: Encoder::new(options),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Write the buffer to the underlying writer if it is full or flush
+    /// it if a top-level value is complete and the options require it
+    fn maybe_flush(&mut self) -> Result<(), EmitterError> {
+        let options = *self.encoder.options();
+        if options.flush_on_document_end() && self.encoder.is_top_level() && !self.buf.is_empty() {
+            return self.flush();
+        }
+        if self.buf.len() >= options.buffer_size() {
+            self.writer.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    /// Write the start of an object
+    pub fn start_object(&mut self) -> Result<(), EmitterError> {
+        self.encoder.start_object(&mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write the end of the current object
+    pub fn end_object(&mut self) -> Result<(), EmitterError> {
+        self.encoder.end_object(&mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write the start of an array
+    pub fn start_array(&mut self) -> Result<(), EmitterError> {
+        self.encoder.start_array(&mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write the end of the current array
+    pub fn end_array(&mut self) -> Result<(), EmitterError> {
+        self.encoder.end_array(&mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write the name of a field in the current object. Write the field's
+    /// value next.
+    pub fn field_name(&mut self, name: &str) -> Result<(), EmitterError> {
+        self.encoder.field_name(name, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write a string value
+    pub fn value_str(&mut self, value: &str) -> Result<(), EmitterError> {
+        self.encoder.value_str(value, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Start a string value whose contents are written in chunks to the
+    /// returned [`StringWriter`], which escapes them on the fly. Call
+    /// [`StringWriter::end_string()`] when you are done.
+    ///
+    /// ```
+    /// use std::io::{self, Write};
+    /// use actson::emitter::JsonEmitter;
+    ///
+    /// let mut emitter = JsonEmitter::new(Vec::new());
+    /// emitter.start_object().unwrap();
+    /// emitter.field_name("log").unwrap();
+    /// let mut s = emitter.begin_string().unwrap();
+    /// io::copy(&mut "line 1\nline 2\n".as_bytes(), &mut s).unwrap();
+    /// write!(s, "{} lines", 2).unwrap();
+    /// s.end_string().unwrap();
+    /// emitter.end_object().unwrap();
+    /// assert_eq!(
+    ///     emitter.finish().unwrap(),
+    ///     br#"{"log":"line 1\nline 2\n2 lines"}"#
+    /// );
+    /// ```
+    pub fn begin_string(&mut self) -> Result<StringWriter<'_, W>, EmitterError> {
+        self.encoder.begin_string(&mut self.buf)?;
+        Ok(StringWriter::new(self))
+    }
+
+    /// Write an integer value
+    pub fn value_int<I>(&mut self, value: I) -> Result<(), EmitterError>
+    where
+        I: PrimInt + Display,
+    {
+        self.encoder.value_int(value, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write a floating point value. Returns
+    /// [`EmitterError::IllegalJsonNumber`] if the value is NaN or infinite.
+    pub fn value_float(&mut self, value: f64) -> Result<(), EmitterError> {
+        self.encoder.value_float(value, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write a boolean value
+    pub fn value_bool(&mut self, value: bool) -> Result<(), EmitterError> {
+        self.encoder.value_bool(value, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write a `null` value
+    pub fn value_null(&mut self) -> Result<(), EmitterError> {
+        self.encoder.value_null(&mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write an already serialized JSON value (e.g. a subtree copied from
+    /// another JSON text) verbatim in place of a value. The fragment is
+    /// neither parsed nor validated, so the caller must make sure it is a
+    /// single valid JSON value. It is also not reformatted, i.e. it is not
+    /// indented in pretty-printed output and keys in it are not sorted.
+    ///
+    /// ```
+    /// use actson::emitter::JsonEmitter;
+    ///
+    /// let mut emitter = JsonEmitter::new(Vec::new());
+    /// emitter.start_object().unwrap();
+    /// emitter.field_name("cached").unwrap();
+    /// emitter.write_raw(r#"{"a": [1, 2]}"#).unwrap();
+    /// emitter.field_name("b").unwrap();
+    /// emitter.value_bool(true).unwrap();
+    /// emitter.end_object().unwrap();
+    /// assert_eq!(
+    ///     emitter.finish().unwrap(),
+    ///     br#"{"cached":{"a": [1, 2]},"b":true}"#
+    /// );
+    /// ```
+    pub fn write_raw(&mut self, json: &str) -> Result<(), EmitterError> {
+        self.encoder.write_raw(json, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Serialize the given value at the current position, so that manually
+    /// written structure can be mixed with values that implement
+    /// [`Serialize`](serde::Serialize). The value is written with the
+    /// options of this emitter.
+    ///
+    /// ```
+    /// use actson::emitter::JsonEmitter;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Meta {
+    ///     version: u32,
+    /// }
+    ///
+    /// let mut emitter = JsonEmitter::new(Vec::new());
+    /// emitter.start_object().unwrap();
+    /// emitter.field_name("meta").unwrap();
+    /// emitter.serialize_value(&Meta { version: 2 }).unwrap();
+    /// emitter.field_name("items").unwrap();
+    /// emitter.serialize_value(&[1, 2]).unwrap();
+    /// emitter.end_object().unwrap();
+    /// assert_eq!(
+    ///     emitter.finish().unwrap(),
+    ///     br#"{"meta":{"version":2},"items":[1,2]}"#
+    /// );
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn serialize_value<T>(&mut self, value: &T) -> Result<(), crate::serde_json::SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    /// Write an array whose elements are taken one by one from the given
+    /// iterator and serialized at the current position. Elements are never
+    /// collected: output is handed to the underlying writer whenever the
+    /// buffer is full (see [`EmitterOptionsBuilder::with_buffer_size()`]),
+    /// so arbitrarily large arrays (e.g. rows from a database cursor) can
+    /// be written with constant memory.
+    ///
+    /// ```
+    /// use actson::emitter::JsonEmitter;
+    ///
+    /// let mut emitter = JsonEmitter::new(Vec::new());
+    /// emitter.array_from_iter((1..=3).map(|i| i * i)).unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), b"[1,4,9]");
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn array_from_iter<I>(&mut self, iter: I) -> Result<(), crate::serde_json::SerializeError>
+    where
+        I: IntoIterator,
+        I::Item: serde::Serialize,
+    {
+        self.start_array()?;
+        for element in iter {
+            self.serialize_value(&element)?;
+        }
+        Ok(self.end_array()?)
+    }
+
+    /// Write an object whose members are taken one by one from the given
+    /// iterator of names and values. Like with [`Self::array_from_iter()`],
+    /// members are not collected, unless keys are sorted (see
+    /// [`EmitterOptionsBuilder::with_sort_keys()`]), which requires the
+    /// whole object to be kept in memory.
+    ///
+    /// ```
+    /// use actson::emitter::JsonEmitter;
+    ///
+    /// let mut emitter = JsonEmitter::new(Vec::new());
+    /// emitter
+    ///     .object_from_iter([("a", vec![1]), ("b", vec![2, 3])])
+    ///     .unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), br#"{"a":[1],"b":[2,3]}"#);
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn object_from_iter<I, K, V>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), crate::serde_json::SerializeError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: serde::Serialize,
+    {
+        self.start_object()?;
+        for (name, value) in iter {
+            self.field_name(name.as_ref())?;
+            self.serialize_value(&value)?;
+        }
+        Ok(self.end_object()?)
+    }
+
+    /// Write a comment before the next member or value, or at the end of
+    /// the current object or array if no member follows. Comments require
+    /// [`EmitterOptionsBuilder::with_comments()`]. Otherwise,
+    /// [`EmitterError::UnexpectedToken`] is returned. In pretty-printed
+    /// output, comments are written as line comments (`// ...`). Otherwise,
+    /// and if they span multiple lines, they are written as block comments
+    /// (`/* ... */`).
+    pub fn comment(&mut self, comment: &str) -> Result<(), EmitterError> {
+        self.encoder.comment(comment)
+    }
+
+    /// Write the given event together with its value.
+    /// [`OwnedJsonEvent::NeedMoreInput`] is ignored.
+    pub fn emit_owned_event(&mut self, event: &OwnedJsonEvent) -> Result<(), EmitterError> {
+        self.encoder.owned_event(event, &mut self.buf)?;
+        self.maybe_flush()
+    }
+
+    /// Write all buffered output to the underlying writer and flush it
+    pub fn flush(&mut self) -> Result<(), EmitterError> {
+        self.writer.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(self.writer.flush()?)
+    }
+
+    /// Get a reference to the underlying writer. Output that has not been
+    /// flushed yet is not visible in it.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns `true` if no object or array is open
+    pub(crate) fn is_top_level(&self) -> bool {
+        self.encoder.is_top_level()
+    }
+
+    /// Terminate the current top-level value with a line break and flush
+    /// the output
+    pub(crate) fn end_line(&mut self) -> Result<(), EmitterError> {
+        self.encoder.end_line(&mut self.buf)?;
+        self.flush()
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub fn finish(mut self) -> Result<W, EmitterError> {
+        self.encoder.finish(&mut self.buf);
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W> JsonEventSink for JsonEmitter<W>
+where
+    W: Write,
+{
+    type Error = EmitterError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        self.encoder.event(event, parser, &mut self.buf)?;
+        self.maybe_flush()
+    }
+}
+
+/// Parse the JSON text provided by the given feeder and write it to the
+/// given writer without insignificant whitespace. Only the current value
+/// is kept in memory, so documents of arbitrary size can be minified.
+/// Returns the writer.
+///
+/// ```
+/// use actson::emitter::minify;
+/// use actson::feeder::SliceJsonFeeder;
+///
+/// let json = b"{\n  \"name\": \"Elvis\",\n  \"albums\": [ 1956, 1957 ]\n}";
+/// let minified = minify(SliceJsonFeeder::new(json), Vec::new()).unwrap();
+/// assert_eq!(minified, br#"{"name":"Elvis","albums":[1956,1957]}"#);
+/// ```
+pub fn minify<T, W>(feeder: T, writer: W) -> Result<W, ParseWithError<EmitterError>>
+where
+    T: FillJsonFeeder,
+    W: Write,
+{
+    transcode(feeder, JsonEmitter::new(writer))
+}
+
+/// Parse the JSON text provided by the given feeder and write it with the
+/// given emitter, which determines the format of the output (e.g. whether
+/// it is pretty-printed or which characters are escaped). Only the current
+/// value is kept in memory. Flushes the emitter and returns its writer.
+///
+/// ```
+/// use actson::emitter::{transcode, EmitterOptionsBuilder, Indent, JsonEmitter};
+/// use actson::feeder::SliceJsonFeeder;
+///
+/// let json = br#"{"name":"Bj\u00f6rn","albums":[]}"#;
+/// let emitter = JsonEmitter::new_with_options(
+///     Vec::new(),
+///     EmitterOptionsBuilder::default()
+///         .with_indent(Indent::Spaces(2))
+///         .build(),
+/// );
+/// let pretty = transcode(SliceJsonFeeder::new(json), emitter).unwrap();
+/// assert_eq!(pretty, "{\n  \"name\": \"Bj\u{f6}rn\",\n  \"albums\": []\n}".as_bytes());
+/// ```
+pub fn transcode<T, W>(
+    feeder: T,
+    emitter: JsonEmitter<W>,
+) -> Result<W, ParseWithError<EmitterError>>
+where
+    T: FillJsonFeeder,
+    W: Write,
+{
+    transcode_with(feeder, emitter)?
+        .finish()
+        .map_err(ParseWithError::Sink)
+}
+
+/// Like [`transcode()`] but forward the events to a sink that wraps an
+/// emitter (e.g. one of the adapters from the [`transform`](crate::transform)
+/// module), so they can be modified before they are written. Returns the
+/// sink. Call [`JsonEmitter::finish()`] on the wrapped emitter to flush the
+/// output.
+///
+/// ```
+/// use actson::emitter::{transcode_with, JsonEmitter};
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::transform::{DropFields, FieldSelector};
+///
+/// let json = br#"{"name": "Elvis", "password": "secret"}"#;
+/// let sink = DropFields::new(
+///     JsonEmitter::new(Vec::new()),
+///     [FieldSelector::name("password")],
+/// );
+/// let sink = transcode_with(SliceJsonFeeder::new(json), sink).unwrap();
+/// let out = sink.into_inner().finish().unwrap();
+/// assert_eq!(out, br#"{"name":"Elvis"}"#);
+/// ```
+pub fn transcode_with<T, K>(feeder: T, mut sink: K) -> Result<K, ParseWithError<K::Error>>
+where
+    T: FillJsonFeeder,
+    K: JsonEventSink,
+{
+    parse_with(feeder, &mut sink)?;
+    Ok(sink)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::SliceJsonFeeder, sink::parse_with, OwnedJsonEvent};
+
+    use super::{EmitterError, EmitterOptionsBuilder, FloatFormat, Indent, JsonEmitter};
+
+    fn emit(events: &[OwnedJsonEvent]) -> String {
+        let mut emitter = JsonEmitter::new(Vec::new());
+        for e in events {
+            emitter.emit_owned_event(e).unwrap();
+        }
+        String::from_utf8(emitter.finish().unwrap()).unwrap()
+    }
+
+    /// Test that separators are written between members, elements, and
+    /// top-level values
+    #[test]
+    fn separators() {
+        use OwnedJsonEvent::*;
+        assert_eq!(
+            emit(&[
+                StartArray,
+                StartObject,
+                EndObject,
+                StartObject,
+                FieldName("a".to_string()),
+                ValueInt(1),
+                FieldName("b".to_string()),
+                StartArray,
+                EndArray,
+                EndObject,
+                ValueNull,
+                EndArray,
+                ValueString("\u{1}".to_string()),
+                ValueFloat(-0.5),
+            ]),
+            "[{},{\"a\":1,\"b\":[]},null]\n\"\\u0001\"\n-0.5"
+        );
+    }
+
+    /// Test that nested values are indented and empty containers are kept
+    /// on one line
+    #[test]
+    fn pretty() {
+        use OwnedJsonEvent::*;
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(4))
+                .build(),
+        );
+        for e in [
+            StartObject,
+            FieldName("a".to_string()),
+            StartArray,
+            ValueTrue,
+            StartObject,
+            EndObject,
+            EndArray,
+            EndObject,
+            ValueNull,
+        ] {
+            emitter.emit_owned_event(&e).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(emitter.finish().unwrap()).unwrap(),
+            "{\n    \"a\": [\n        true,\n        {}\n    ]\n}\nnull"
+        );
+    }
+
+    /// Test that short containers of scalars are kept on one line and that
+    /// long or nested containers are expanded
+    #[test]
+    fn inline_width() {
+        let json = br#"{"b": [1, 2], "a": {"y": true, "x": null}, "c": [[], "long string"]}"#;
+        for (sort_keys, expected) in [
+            (
+                false,
+                "{\n  \"b\": [1, 2],\n  \"a\": {\"y\": true, \"x\": null},\n  \"c\": [\n    [],\n    \"long string\"\n  ]\n}",
+            ),
+            (
+                true,
+                "{\n  \"a\": {\"x\": null, \"y\": true},\n  \"b\": [1, 2],\n  \"c\": [\n    [],\n    \"long string\"\n  ]\n}",
+            ),
+        ] {
+            let mut emitter = JsonEmitter::new_with_options(
+                Vec::new(),
+                EmitterOptionsBuilder::default()
+                    .with_indent(Indent::Spaces(2))
+                    .with_sort_keys(sort_keys)
+                    .with_max_inline_width(Some(24))
+                    .build(),
+            );
+            parse_with(SliceJsonFeeder::new(json), &mut emitter).unwrap();
+            assert_eq!(
+                String::from_utf8(emitter.finish().unwrap()).unwrap(),
+                expected
+            );
+        }
+
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(2))
+                .with_max_inline_width(Some(8))
+                .build(),
+        );
+        parse_with(
+            SliceJsonFeeder::new(br#"[[1, 2], [1, 2, 3]]"#),
+            &mut emitter,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(emitter.finish().unwrap()).unwrap(),
+            "[\n  [1, 2],\n  [\n    1,\n    2,\n    3\n  ]\n]"
+        );
+    }
+
+    /// Test that keys are sorted in objects nested in arrays and that
+    /// indentation is kept
+    #[test]
+    fn sort_keys() {
+        let json = br#"[{"b": [{"y": 1, "x": 2}], "a": {}}, {"\uff61": 0, "\ud83d\ude00": 1}]"#;
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Tabs)
+                .with_sort_keys(true)
+                .build(),
+        );
+        parse_with(SliceJsonFeeder::new(json), &mut emitter).unwrap();
+        assert_eq!(
+            String::from_utf8(emitter.finish().unwrap()).unwrap(),
+            "[\n\t{\n\t\t\"a\": {},\n\t\t\"b\": [\n\t\t\t{\n\t\t\t\t\"x\": 2,\n\t\t\t\t\"y\": 1\n\t\t\t}\n\t\t]\n\t},\n\t{\n\t\t\"\u{1f600}\": 1,\n\t\t\"\u{ff61}\": 0\n\t}\n]"
+        );
+    }
+
+    /// Test that raw fragments are kept together with their keys when keys
+    /// are sorted
+    #[test]
+    fn raw_sorted() {
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_sort_keys(true)
+                .build(),
+        );
+        emitter.start_object().unwrap();
+        emitter.field_name("b").unwrap();
+        emitter.write_raw("[3, 2, 1]").unwrap();
+        emitter.field_name("a").unwrap();
+        emitter.write_raw(r#"{"z":0,"y":1}"#).unwrap();
+        emitter.end_object().unwrap();
+        emitter.write_raw("null").unwrap();
+        assert_eq!(
+            emitter.finish().unwrap(),
+            b"{\"a\":{\"z\":0,\"y\":1},\"b\":[3, 2, 1]}\nnull"
+        );
+    }
+
+    /// Test that calls in an impossible order are rejected without writing
+    /// anything and that the emitter can be used afterwards
+    #[test]
+    fn event_order() {
+        let unexpected = |r| matches!(r, Err(EmitterError::UnexpectedToken(_)));
+
+        let mut emitter = JsonEmitter::new(Vec::new());
+        assert!(unexpected(emitter.field_name("a")));
+        assert!(unexpected(emitter.end_array()));
+        emitter.start_object().unwrap();
+        assert!(unexpected(emitter.value_int(1)));
+        assert!(unexpected(emitter.end_array()));
+        emitter.field_name("a").unwrap();
+        assert!(unexpected(emitter.field_name("b")));
+        assert!(unexpected(emitter.end_object()));
+        emitter.start_array().unwrap();
+        assert!(unexpected(emitter.field_name("c")));
+        assert!(unexpected(emitter.end_object()));
+        emitter.end_array().unwrap();
+        assert!(unexpected(emitter.write_raw("2")));
+        emitter.end_object().unwrap();
+        assert!(unexpected(emitter.end_object()));
+        assert_eq!(emitter.finish().unwrap(), br#"{"a":[]}"#);
+    }
+
+    /// Test that each kind of token gets its own color
+    #[cfg(feature = "color")]
+    #[test]
+    fn colors() {
+        let json = br#"{"b": [true, "x"], "a": null}"#;
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(1))
+                .with_sort_keys(true)
+                .with_colors(Some(super::ColorScheme {
+                    key: "K",
+                    string: "S",
+                    number: "",
+                    literal: "L",
+                    punctuation: "P",
+                }))
+                .build(),
+        );
+        parse_with(SliceJsonFeeder::new(json), &mut emitter).unwrap();
+        let r = "\x1b[0m";
+        assert_eq!(
+            String::from_utf8(emitter.finish().unwrap()).unwrap(),
+            format!(
+                "P{{{r}\n K\"a\"{r}P: {r}Lnull{r}P,{r}\n K\"b\"{r}P: {r}P[{r}\n  Ltrue{r}P,{r}\n  S\"x\"{r}\n P]{r}\nP}}{r}"
+            )
+        );
+    }
+
+    /// Test that comments are written before members and at the end of
+    /// containers, and that they are rejected unless enabled
+    #[test]
+    fn json5() {
+        let write = |options| {
+            let mut emitter = JsonEmitter::new_with_options(Vec::new(), options);
+            emitter.start_array().unwrap();
+            emitter.value_int(1).unwrap();
+            emitter.comment("two").unwrap();
+            emitter.start_object().unwrap();
+            emitter.field_name("$b").unwrap();
+            emitter.value_null().unwrap();
+            emitter.comment("a\nb").unwrap();
+            emitter.field_name("0").unwrap();
+            emitter.value_null().unwrap();
+            emitter.comment("end").unwrap();
+            emitter.end_object().unwrap();
+            emitter.end_array().unwrap();
+            emitter.comment("eof").unwrap();
+            String::from_utf8(emitter.finish().unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            write(EmitterOptionsBuilder::default().with_json5(true).build()),
+            "[1,/* two */{$b:null,/* a\nb */\"0\":null/* end */}]\n/* eof */"
+        );
+        assert_eq!(
+            write(
+                EmitterOptionsBuilder::default()
+                    .with_indent(Indent::Spaces(1))
+                    .with_sort_keys(true)
+                    .with_json5(true)
+                    .build()
+            ),
+            "[\n 1,\n // two\n {\n  $b: null,\n  /* a\nb */\n  \"0\": null,\n  // end\n },\n]\n// eof"
+        );
+
+        let mut emitter = JsonEmitter::new(Vec::new());
+        assert!(matches!(
+            emitter.comment("x"),
+            Err(EmitterError::UnexpectedToken("comment"))
+        ));
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default().with_comments(true).build(),
+        );
+        assert!(matches!(
+            emitter.comment("*/"),
+            Err(EmitterError::InvalidComment)
+        ));
+    }
+
+    /// Test that floats that cannot be represented in JSON are rejected
+    #[test]
+    fn illegal_float() {
+        let mut emitter = JsonEmitter::new(Vec::new());
+        assert!(matches!(
+            emitter.value_float(f64::NAN),
+            Err(EmitterError::IllegalJsonNumber(_))
+        ));
+        assert!(emitter.value_float(1e300).is_ok());
+        assert_eq!(emitter.finish().unwrap(), b"1e300");
+    }
+
+    /// Test the different float formats
+    #[test]
+    fn float_format() {
+        let format = |float_format, values: &[f64]| {
+            let mut emitter = JsonEmitter::new_with_options(
+                Vec::new(),
+                EmitterOptionsBuilder::default()
+                    .with_float_format(float_format)
+                    .build(),
+            );
+            emitter.start_array().unwrap();
+            for &v in values {
+                emitter.value_float(v).unwrap();
+            }
+            emitter.end_array().unwrap();
+            String::from_utf8(emitter.finish().unwrap()).unwrap()
+        };
+
+        let values = [1.0, -0.0, 0.1, 1e21, 1.5e-7];
+        assert_eq!(
+            format(FloatFormat::Shortest, &values),
+            "[1.0,-0.0,0.1,1e21,1.5e-7]"
+        );
+        assert_eq!(
+            format(FloatFormat::DecimalPoint, &values),
+            "[1.0,-0.0,0.1,1000000000000000000000.0,0.00000015]"
+        );
+        assert_eq!(
+            format(FloatFormat::Fixed(2), &values),
+            "[1.00,-0.00,0.10,1000000000000000000000.00,0.00]"
+        );
+        assert_eq!(format(FloatFormat::Fixed(0), &[2.5, 3.5]), "[2,4]");
+    }
+
+    /// Test that large outputs are written in chunks
+    #[test]
+    fn large_output() {
+        let mut emitter = JsonEmitter::new(Vec::new());
+        emitter.start_array().unwrap();
+        for i in 0..10000 {
+            emitter.value_int(i).unwrap();
+        }
+        emitter.end_array().unwrap();
+        let json = emitter.finish().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 10000);
+    }
+
+    /// A writer that counts the bytes written to it
+    #[cfg(feature = "serde_json")]
+    struct CountingWriter(std::rc::Rc<std::cell::Cell<usize>>);
+
+    #[cfg(feature = "serde_json")]
+    impl std::io::Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.set(self.0.get() + buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Test that arrays and objects from iterators are written while the
+    /// iterators are still running
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn from_iter() {
+        let written = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut emitter = JsonEmitter::new(CountingWriter(written.clone()));
+        let w = written.clone();
+        emitter
+            .array_from_iter((0..100000).inspect(|&i| {
+                if i == 50000 {
+                    assert!(w.get() > 0);
+                }
+            }))
+            .unwrap();
+        let w = written.clone();
+        emitter
+            .object_from_iter((0..100000).map(|i| {
+                if i == 50000 {
+                    assert!(w.get() > 600000);
+                }
+                (i.to_string(), [i])
+            }))
+            .unwrap();
+        emitter.finish().unwrap();
+
+        let mut emitter = JsonEmitter::new(Vec::new());
+        emitter.start_object().unwrap();
+        emitter.field_name("rows").unwrap();
+        emitter.array_from_iter([Some("a"), None].iter()).unwrap();
+        emitter.field_name("empty").unwrap();
+        emitter
+            .object_from_iter(std::iter::empty::<(&str, u8)>())
+            .unwrap();
+        emitter.end_object().unwrap();
+        assert_eq!(
+            emitter.finish().unwrap(),
+            br#"{"rows":["a",null],"empty":{}}"#
+        );
+    }
+}
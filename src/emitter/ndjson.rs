@@ -0,0 +1,346 @@
+use std::io::Write;
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{FillJsonFeeder, JsonFeeder},
+    options::JsonParserOptionsBuilder,
+    parser::ParserError,
+    sink::{parse_with, parse_with_parser, JsonEventSink, ParseWithError},
+    storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+use super::{EmitterError, EmitterOptions, JsonEmitter};
+
+/// Writes a sequence of JSON values as newline-delimited JSON (NDJSON, also
+/// known as JSON Lines). Each value is written compactly on its own line,
+/// terminated by a line break, and the output is flushed after each value,
+/// so readers on the other end receive complete records immediately.
+///
+/// The writer is a [`JsonEventSink`]. Together with a parser in streaming
+/// mode, it converts any sequence of top-level values into NDJSON:
+///
+/// ```
+/// use actson::emitter::NdjsonWriter;
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::options::JsonParserOptionsBuilder;
+/// use actson::sink::parse_with_parser;
+/// use actson::JsonParser;
+///
+/// let json = br#"{"a": 1} [2, 3]
+///     "four""#;
+/// let mut parser = JsonParser::new_with_options(
+///     SliceJsonFeeder::new(json),
+///     JsonParserOptionsBuilder::default().with_streaming(true).build(),
+/// );
+/// let mut writer = NdjsonWriter::new(Vec::new());
+/// parse_with_parser(&mut parser, &mut writer).unwrap();
+/// assert_eq!(writer.finish().unwrap(), b"{\"a\":1}\n[2,3]\n\"four\"\n");
+/// ```
+///
+/// Records can also be written with [`Self::write_with()`] or, if the
+/// `serde_json` feature is enabled, serialized with [`Self::write()`].
+pub struct NdjsonWriter<W>
+where
+    W: Write,
+{
+    emitter: JsonEmitter<W>,
+}
+
+impl<W> NdjsonWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer
+    pub fn new(writer: W) -> Self {
+        Self::new_with_options(writer, EmitterOptions::default())
+    }
+
+    /// Create a new writer with the given options. Indentation is ignored
+    /// because each record must fit on one line.
+    pub fn new_with_options(writer: W, mut options: EmitterOptions) -> Self {
+        options.indent = None;
+        NdjsonWriter {
+            emitter: JsonEmitter::new_with_options(writer, options),
+        }
+    }
+
+    /// Write a record using the given function, which must write exactly
+    /// one complete value with the given emitter. The record is then
+    /// terminated and flushed.
+    ///
+    /// ```
+    /// use actson::emitter::NdjsonWriter;
+    ///
+    /// let mut writer = NdjsonWriter::new(Vec::new());
+    /// for i in 0..2 {
+    ///     writer
+    ///         .write_with(|e| {
+    ///             e.start_object()?;
+    ///             e.field_name("id")?;
+    ///             e.value_int(i)?;
+    ///             e.end_object()
+    ///         })
+    ///         .unwrap();
+    /// }
+    /// assert_eq!(writer.finish().unwrap(), b"{\"id\":0}\n{\"id\":1}\n");
+    /// ```
+    pub fn write_with<F, E>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut JsonEmitter<W>) -> Result<(), E>,
+        E: From<EmitterError>,
+    {
+        f(&mut self.emitter)?;
+        Ok(self.emitter.end_line()?)
+    }
+
+    /// Serialize the given value as a record
+    ///
+    /// ```
+    /// use actson::emitter::NdjsonWriter;
+    ///
+    /// let mut writer = NdjsonWriter::new(Vec::new());
+    /// writer.write(&vec![1, 2]).unwrap();
+    /// writer.write(&"three").unwrap();
+    /// assert_eq!(writer.finish().unwrap(), b"[1,2]\n\"three\"\n");
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn write<T>(&mut self, value: &T) -> Result<(), crate::serde_json::SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.write_with(|e| value.serialize(e))
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        self.emitter.get_ref()
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub fn finish(self) -> Result<W, EmitterError> {
+        self.emitter.finish()
+    }
+}
+
+impl<W> JsonEventSink for NdjsonWriter<W>
+where
+    W: Write,
+{
+    type Error = EmitterError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        self.emitter.on_event(event, parser)?;
+        if !matches!(event, JsonEvent::NeedMoreInput | JsonEvent::FieldName)
+            && self.emitter.is_top_level()
+        {
+            self.emitter.end_line()?;
+        }
+        Ok(())
+    }
+}
+
+/// An error that can happen in [`array_to_ndjson()`]
+#[derive(Error, Debug)]
+pub enum ArrayToNdjsonError {
+    /// The output could not be written
+    #[error(transparent)]
+    Emitter(#[from] EmitterError),
+
+    /// The JSON text contains a value that is not an array at the top level
+    /// (at the given offset)
+    #[error("expected an array (at byte {0})")]
+    NotAnArray(usize),
+}
+
+/// A sink that writes the elements of top-level arrays as records
+struct ArrayElements<W>
+where
+    W: Write,
+{
+    writer: NdjsonWriter<W>,
+
+    /// The current nesting depth
+    depth: usize,
+}
+
+impl<W> JsonEventSink for ArrayElements<W>
+where
+    W: Write,
+{
+    type Error = ArrayToNdjsonError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        let outer = match event {
+            JsonEvent::NeedMoreInput => return Ok(()),
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                self.depth += 1;
+                self.depth == 1
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                self.depth -= 1;
+                self.depth == 0
+            }
+            _ => self.depth == 0,
+        };
+        if !outer {
+            self.writer.on_event(event, parser)?;
+        } else if !matches!(event, JsonEvent::StartArray | JsonEvent::EndArray) {
+            return Err(ArrayToNdjsonError::NotAnArray(parser.current_span().start));
+        }
+        Ok(())
+    }
+}
+
+/// Parse the JSON text provided by the given feeder, which must be an
+/// array, and write each of its elements as a compact NDJSON record to the
+/// given writer. Only the current element is kept in memory, so arrays of
+/// arbitrary size can be converted. Flushes the output and returns the
+/// writer.
+///
+/// ```
+/// use actson::emitter::array_to_ndjson;
+/// use actson::feeder::SliceJsonFeeder;
+///
+/// let json = br#"[{"id": 1}, [2, 3], "four"]"#;
+/// let ndjson = array_to_ndjson(SliceJsonFeeder::new(json), Vec::new()).unwrap();
+/// assert_eq!(ndjson, b"{\"id\":1}\n[2,3]\n\"four\"\n");
+/// ```
+pub fn array_to_ndjson<T, W>(feeder: T, writer: W) -> Result<W, ParseWithError<ArrayToNdjsonError>>
+where
+    T: FillJsonFeeder,
+    W: Write,
+{
+    let mut sink = ArrayElements {
+        writer: NdjsonWriter::new(writer),
+        depth: 0,
+    };
+    parse_with(feeder, &mut sink)?;
+    sink.writer
+        .finish()
+        .map_err(|e| ParseWithError::Sink(e.into()))
+}
+
+/// Parse a sequence of JSON values provided by the given feeder (e.g.
+/// NDJSON records) and write them as the elements of a single compact array
+/// to the given writer. Each value is validated by the parser before it is
+/// written. The values may be separated by any whitespace, not only by line
+/// breaks. Only the current value is kept in memory. Flushes the output and
+/// returns the writer.
+///
+/// ```
+/// use actson::emitter::ndjson_to_array;
+/// use actson::feeder::SliceJsonFeeder;
+///
+/// let ndjson = b"{\"id\": 1}\n[2, 3]\n\"four\"\n";
+/// let json = ndjson_to_array(SliceJsonFeeder::new(ndjson), Vec::new()).unwrap();
+/// assert_eq!(json, br#"[{"id":1},[2,3],"four"]"#);
+/// ```
+pub fn ndjson_to_array<T, W>(feeder: T, writer: W) -> Result<W, ParseWithError<EmitterError>>
+where
+    T: FillJsonFeeder,
+    W: Write,
+{
+    let mut parser = JsonParser::new_with_options(
+        feeder,
+        JsonParserOptionsBuilder::default()
+            .with_streaming(true)
+            .build(),
+    );
+    let mut emitter = JsonEmitter::new(writer);
+    emitter.start_array().map_err(ParseWithError::Sink)?;
+    match parse_with_parser(&mut parser, &mut emitter) {
+        // the input does not contain any values
+        Err(ParseWithError::Parser(ParserError::NoMoreInput))
+            if parser.stats().events_emitted == 0 => {}
+        r => r?,
+    }
+    emitter.end_array().map_err(ParseWithError::Sink)?;
+    emitter.finish().map_err(ParseWithError::Sink)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        emitter::{EmitterError, EmitterOptionsBuilder, Indent, Newline},
+        feeder::SliceJsonFeeder,
+        parser::ParserError,
+        sink::ParseWithError,
+    };
+
+    use super::{array_to_ndjson, ndjson_to_array, ArrayToNdjsonError, NdjsonWriter};
+
+    /// Test that records are flushed as soon as they are complete, that
+    /// indentation is ignored, and that incomplete records are rejected
+    #[test]
+    fn records() {
+        let mut writer = NdjsonWriter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(2))
+                .with_newline(Newline::CrLf)
+                .build(),
+        );
+        writer
+            .write_with(|e| {
+                e.start_array()?;
+                e.value_null()?;
+                e.end_array()
+            })
+            .unwrap();
+        assert_eq!(writer.get_ref(), b"[null]\r\n");
+
+        assert!(matches!(
+            writer.write_with(|e| e.start_object()),
+            Err(EmitterError::UnexpectedToken(_))
+        ));
+    }
+
+    /// Test that an empty array produces no output and that values other
+    /// than arrays are rejected
+    #[test]
+    fn split_array() {
+        let ndjson = array_to_ndjson(SliceJsonFeeder::new(b" [ ] "), Vec::new()).unwrap();
+        assert!(ndjson.is_empty());
+
+        assert!(matches!(
+            array_to_ndjson(SliceJsonFeeder::new(br#" {"a": [1]}"#), Vec::new()),
+            Err(ParseWithError::Sink(ArrayToNdjsonError::NotAnArray(1)))
+        ));
+    }
+
+    /// Test that empty input produces an empty array and that invalid
+    /// records are rejected
+    #[test]
+    fn join_records() {
+        let json = ndjson_to_array(SliceJsonFeeder::new(b"\n"), Vec::new()).unwrap();
+        assert_eq!(json, b"[]");
+
+        assert!(matches!(
+            ndjson_to_array(SliceJsonFeeder::new(b"[1]\n{"), Vec::new()),
+            Err(ParseWithError::Parser(ParserError::NoMoreInput))
+        ));
+
+        assert!(matches!(
+            ndjson_to_array(SliceJsonFeeder::new(b"{\"a\": 1}\n{\"a\": }\n"), Vec::new()),
+            Err(ParseWithError::Parser(_))
+        ));
+    }
+}
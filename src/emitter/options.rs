@@ -0,0 +1,546 @@
+/// How to indent nested values (see [`EmitterOptionsBuilder::with_indent()`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+    /// Indent each level with the given number of spaces
+    Spaces(usize),
+
+    /// Indent each level with one tab
+    Tabs,
+}
+
+/// The line break to write between lines of pretty-printed output and
+/// between top-level values
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Newline {
+    /// A line feed (`\n`)
+    #[default]
+    Lf,
+
+    /// A carriage return followed by a line feed (`\r\n`)
+    CrLf,
+}
+
+/// How to format floating-point numbers (see
+/// [`EmitterOptionsBuilder::with_float_format()`])
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest representation that parses back to the same value.
+    /// Integral values get a trailing `.0` and very large or small values
+    /// use scientific notation (e.g. `1.0`, `0.1`, `1e30`).
+    #[default]
+    Shortest,
+
+    /// The shortest digits that parse back to the same value, but always in
+    /// decimal notation with at least one digit after the decimal point
+    /// (e.g. `1.0`, `0.1`, `1000000000000000000000000000000.0`)
+    DecimalPoint,
+
+    /// A fixed number of digits after the decimal point (e.g. `1.000000`
+    /// for a precision of 6). Values may be rounded and not parse back to
+    /// the same value.
+    Fixed(usize),
+}
+
+/// The default number of bytes an emitter collects before it writes them to
+/// the underlying writer (see [`EmitterOptionsBuilder::with_buffer_size()`])
+pub const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Options for [`JsonEmitter`](super::JsonEmitter) and
+/// [`PrettyPrinter`](super::PrettyPrinter). Use [`EmitterOptionsBuilder`] to
+/// create instances of this struct.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmitterOptions {
+    /// The indentation of nested values or `None` if the output should be
+    /// compact
+    pub(super) indent: Option<Indent>,
+
+    /// The line break to use
+    pub(super) newline: Newline,
+
+    /// `true` if the output should be canonical JSON according to RFC 8785
+    pub(super) canonical: bool,
+
+    /// `true` if the members of objects should be sorted by their keys
+    pub(super) sort_keys: bool,
+
+    /// `true` if all non-ASCII characters should be escaped
+    pub(super) ensure_ascii: bool,
+
+    /// `true` if `<`, `>`, `&`, U+2028, and U+2029 should be escaped
+    pub(super) escape_html: bool,
+
+    /// `true` if forward slashes should be escaped
+    pub(super) escape_slash: bool,
+
+    /// How to format floating-point numbers
+    pub(super) float_format: FloatFormat,
+
+    /// `true` if comments may be written
+    pub(super) comments: bool,
+
+    /// `true` if keys that are identifiers should be written without quotes
+    pub(super) unquoted_keys: bool,
+
+    /// `true` if a comma should be written after the last member of each
+    /// object and array in pretty-printed output
+    pub(super) trailing_commas: bool,
+
+    /// The maximum length of objects and arrays of scalars that should be
+    /// kept on a single line in pretty-printed output
+    pub(super) max_inline_width: Option<usize>,
+
+    /// The number of bytes to collect before writing them to the underlying
+    /// writer or `None` if the default should be used
+    pub(super) buffer_size: Option<usize>,
+
+    /// `true` if the output should be flushed after each top-level value
+    pub(super) flush_on_document_end: bool,
+
+    /// The colors of tokens or `None` if the output should not be colored
+    #[cfg(feature = "color")]
+    pub(super) colors: Option<super::ColorScheme>,
+}
+
+impl EmitterOptions {
+    /// Returns the indentation of nested values or `None` if the output is
+    /// compact
+    pub fn indent(&self) -> Option<Indent> {
+        self.indent
+    }
+
+    /// Returns the line break to use
+    pub fn newline(&self) -> Newline {
+        self.newline
+    }
+
+    /// Returns `true` if the output is canonical JSON according to RFC 8785
+    pub fn canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Returns `true` if the members of objects are sorted by their keys
+    pub fn sort_keys(&self) -> bool {
+        self.sort_keys
+    }
+
+    /// Returns `true` if all non-ASCII characters are escaped
+    pub fn ensure_ascii(&self) -> bool {
+        self.ensure_ascii
+    }
+
+    /// Returns `true` if `<`, `>`, `&`, U+2028, and U+2029 are escaped
+    pub fn escape_html(&self) -> bool {
+        self.escape_html
+    }
+
+    /// Returns `true` if forward slashes are escaped
+    pub fn escape_slash(&self) -> bool {
+        self.escape_slash
+    }
+
+    /// Returns how floating-point numbers are formatted
+    pub fn float_format(&self) -> FloatFormat {
+        self.float_format
+    }
+
+    /// Returns `true` if comments may be written
+    pub fn comments(&self) -> bool {
+        self.comments
+    }
+
+    /// Returns `true` if keys that are identifiers are written without quotes
+    pub fn unquoted_keys(&self) -> bool {
+        self.unquoted_keys
+    }
+
+    /// Returns `true` if a comma is written after the last member of each
+    /// object and array in pretty-printed output
+    pub fn trailing_commas(&self) -> bool {
+        self.trailing_commas
+    }
+
+    /// Returns the maximum length of objects and arrays of scalars that are
+    /// kept on a single line in pretty-printed output or `None` if all
+    /// containers are expanded
+    pub fn max_inline_width(&self) -> Option<usize> {
+        self.max_inline_width
+    }
+
+    /// Returns the number of bytes collected before they are written to the
+    /// underlying writer
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Returns `true` if the output is flushed after each top-level value
+    pub fn flush_on_document_end(&self) -> bool {
+        self.flush_on_document_end
+    }
+
+    /// Returns the colors of tokens or `None` if the output is not colored
+    #[cfg(feature = "color")]
+    pub fn colors(&self) -> Option<super::ColorScheme> {
+        self.colors
+    }
+}
+
+/// A builder for [`EmitterOptions`]
+///
+/// ```rust
+/// use actson::emitter::{EmitterOptionsBuilder, Indent, JsonEmitter, Newline};
+///
+/// let mut emitter = JsonEmitter::new_with_options(
+///     Vec::new(),
+///     EmitterOptionsBuilder::default()
+///         .with_indent(Indent::Tabs)
+///         .with_newline(Newline::CrLf)
+///         .build(),
+/// );
+/// emitter.start_array().unwrap();
+/// emitter.value_int(1).unwrap();
+/// emitter.end_array().unwrap();
+/// assert_eq!(emitter.finish().unwrap(), b"[\r\n\t1\r\n]");
+/// ```
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmitterOptionsBuilder {
+    options: EmitterOptions,
+}
+
+impl EmitterOptionsBuilder {
+    /// Pretty-print the output and indent nested values as given. By
+    /// default, the output is compact.
+    pub fn with_indent(mut self, indent: Indent) -> Self {
+        self.options.indent = Some(indent);
+        self
+    }
+
+    /// Set the line break to use. The default is [`Newline::Lf`].
+    pub fn with_newline(mut self, newline: Newline) -> Self {
+        self.options.newline = newline;
+        self
+    }
+
+    /// Sort the members of each object by their keys, so that the output
+    /// does not depend on the order of the keys in the input. The members
+    /// of an object are kept in memory until the object ends. Keys are
+    /// compared by their UTF-16 code units like in canonical JSON (see
+    /// [`Self::with_canonical()`]).
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, Indent, PrettyPrinter};
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::sink::parse_with;
+    ///
+    /// let json = br#"{"b": 1, "a": {"d": 2, "c": 3}}"#;
+    /// let mut prettyprinter = PrettyPrinter::new_with_options(
+    ///     EmitterOptionsBuilder::default()
+    ///         .with_indent(Indent::Spaces(2))
+    ///         .with_sort_keys(true)
+    ///         .build(),
+    /// );
+    /// parse_with(SliceJsonFeeder::new(json), &mut prettyprinter).unwrap();
+    /// assert_eq!(prettyprinter.get_result(), r#"{
+    ///   "a": {
+    ///     "c": 3,
+    ///     "d": 2
+    ///   },
+    ///   "b": 1
+    /// }"#);
+    /// ```
+    pub fn with_sort_keys(mut self, sort_keys: bool) -> Self {
+        self.options.sort_keys = sort_keys;
+        self
+    }
+
+    /// Escape all non-ASCII characters in strings as `\uXXXX` (using
+    /// surrogate pairs for characters outside the Basic Multilingual
+    /// Plane), so that the output only consists of ASCII characters. This
+    /// is ignored if the output is canonical (see
+    /// [`Self::with_canonical()`]).
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default().with_ensure_ascii(true).build(),
+    /// );
+    /// emitter.value_str("Bj\u{f6}rn").unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), br#""Bj\u00f6rn""#);
+    /// ```
+    pub fn with_ensure_ascii(mut self, ensure_ascii: bool) -> Self {
+        self.options.ensure_ascii = ensure_ascii;
+        self
+    }
+
+    /// Escape `<`, `>`, and `&` as well as the line and paragraph separators
+    /// U+2028 and U+2029 in strings, so that the output can safely be
+    /// embedded in an HTML `<script>` tag and parsed by older JavaScript
+    /// engines. This is ignored if the output is canonical (see
+    /// [`Self::with_canonical()`]).
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default().with_escape_html(true).build(),
+    /// );
+    /// emitter.value_str("</script>").unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), br#""\u003c/script\u003e""#);
+    /// ```
+    pub fn with_escape_html(mut self, escape_html: bool) -> Self {
+        self.options.escape_html = escape_html;
+        self
+    }
+
+    /// Escape forward slashes in strings as `\/`. Together with
+    /// [`Self::with_escape_html()`], this makes sure `</` never appears in
+    /// the output. This is ignored if the output is canonical (see
+    /// [`Self::with_canonical()`]).
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default().with_escape_slash(true).build(),
+    /// );
+    /// emitter.value_str("a/b").unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), br#""a\/b""#);
+    /// ```
+    pub fn with_escape_slash(mut self, escape_slash: bool) -> Self {
+        self.options.escape_slash = escape_slash;
+        self
+    }
+
+    /// Specify how floating-point numbers passed to
+    /// [`JsonEmitter::value_float()`](super::JsonEmitter::value_float) are
+    /// formatted. Numbers that are forwarded from a parser are written as
+    /// they appeared in the input. This is ignored if the output is canonical
+    /// (see [`Self::with_canonical()`]).
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, FloatFormat, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default()
+    ///         .with_float_format(FloatFormat::Fixed(3))
+    ///         .build(),
+    /// );
+    /// emitter.start_array().unwrap();
+    /// emitter.value_float(1.0).unwrap();
+    /// emitter.value_float(2.0 / 3.0).unwrap();
+    /// emitter.end_array().unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), b"[1.000,0.667]");
+    /// ```
+    pub fn with_float_format(mut self, float_format: FloatFormat) -> Self {
+        self.options.float_format = float_format;
+        self
+    }
+
+    /// Allow comments to be written with
+    /// [`JsonEmitter::comment()`](super::JsonEmitter::comment). Comments are
+    /// not part of the JSON standard, but they are supported by JSON5 and
+    /// many configuration file parsers. This is ignored if the output is
+    /// canonical (see [`Self::with_canonical()`]).
+    pub fn with_comments(mut self, comments: bool) -> Self {
+        self.options.comments = comments;
+        self
+    }
+
+    /// Write keys that are ASCII identifiers (e.g. `name` or `_id`) without
+    /// quotes as allowed by JSON5. Other keys are still quoted. This is
+    /// ignored if the output is canonical (see [`Self::with_canonical()`]).
+    pub fn with_unquoted_keys(mut self, unquoted_keys: bool) -> Self {
+        self.options.unquoted_keys = unquoted_keys;
+        self
+    }
+
+    /// Write a comma after the last member of each non-empty object and
+    /// array as allowed by JSON5. This only applies to pretty-printed output
+    /// (see [`Self::with_indent()`]), where it makes adding lines to
+    /// human-maintained files easier.
+    pub fn with_trailing_commas(mut self, trailing_commas: bool) -> Self {
+        self.options.trailing_commas = trailing_commas;
+        self
+    }
+
+    /// Keep objects and arrays that only contain scalars on a single line in
+    /// pretty-printed output (see [`Self::with_indent()`]) if they are at
+    /// most `width` bytes long. Longer containers, containers with nested
+    /// objects or arrays, and containers with comments are expanded as
+    /// usual. `None` (the default) expands all containers.
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, Indent, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default()
+    ///         .with_indent(Indent::Spaces(2))
+    ///         .with_max_inline_width(Some(20))
+    ///         .build(),
+    /// );
+    /// emitter.start_object().unwrap();
+    /// emitter.field_name("point").unwrap();
+    /// emitter.start_array().unwrap();
+    /// emitter.value_int(1).unwrap();
+    /// emitter.value_int(2).unwrap();
+    /// emitter.end_array().unwrap();
+    /// emitter.end_object().unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(emitter.finish().unwrap()).unwrap(),
+    ///     "{\n  \"point\": [1, 2]\n}"
+    /// );
+    /// ```
+    pub fn with_max_inline_width(mut self, width: Option<usize>) -> Self {
+        self.options.max_inline_width = width;
+        self
+    }
+
+    /// Enable or disable all JSON5 extensions the emitter supports at once
+    /// (see [`Self::with_comments()`], [`Self::with_unquoted_keys()`], and
+    /// [`Self::with_trailing_commas()`])
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, Indent, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default()
+    ///         .with_indent(Indent::Spaces(2))
+    ///         .with_json5(true)
+    ///         .build(),
+    /// );
+    /// emitter.comment("generated file").unwrap();
+    /// emitter.start_object().unwrap();
+    /// emitter.comment("the port to listen on").unwrap();
+    /// emitter.field_name("port").unwrap();
+    /// emitter.value_int(8080).unwrap();
+    /// emitter.field_name("log-level").unwrap();
+    /// emitter.value_str("info").unwrap();
+    /// emitter.end_object().unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(emitter.finish().unwrap()).unwrap(),
+    ///     r#"// generated file
+    /// {
+    ///   // the port to listen on
+    ///   port: 8080,
+    ///   "log-level": "info",
+    /// }"#
+    /// );
+    /// ```
+    pub fn with_json5(self, json5: bool) -> Self {
+        self.with_comments(json5)
+            .with_unquoted_keys(json5)
+            .with_trailing_commas(json5)
+    }
+
+    /// Set the number of bytes the emitter collects before it writes them to
+    /// the underlying writer (default: [`DEFAULT_BUFFER_SIZE`]). Larger
+    /// buffers mean fewer (possibly expensive) calls to the writer. A size
+    /// of 0 writes each token immediately.
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default().with_buffer_size(0).build(),
+    /// );
+    /// emitter.start_array().unwrap();
+    /// assert_eq!(emitter.get_ref(), b"[");
+    /// ```
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.options.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// Flush the output (including the underlying writer) whenever a
+    /// top-level value is complete, so that consumers receive each
+    /// document without delay, regardless of the buffer size
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default()
+    ///         .with_flush_on_document_end(true)
+    ///         .build(),
+    /// );
+    /// emitter.start_array().unwrap();
+    /// assert_eq!(emitter.get_ref(), b"");
+    /// emitter.end_array().unwrap();
+    /// assert_eq!(emitter.get_ref(), b"[]");
+    /// ```
+    pub fn with_flush_on_document_end(mut self, flush_on_document_end: bool) -> Self {
+        self.options.flush_on_document_end = flush_on_document_end;
+        self
+    }
+
+    /// Colorize the output with ANSI escape sequences for display in a
+    /// terminal. Pass `None` to disable colors (the default). Raw fragments
+    /// written with [`JsonEmitter::write_raw()`](super::JsonEmitter::write_raw)
+    /// are not colored.
+    ///
+    /// ```rust
+    /// use actson::emitter::{ColorScheme, EmitterOptionsBuilder, JsonEmitter};
+    ///
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default()
+    ///         .with_colors(Some(ColorScheme::default()))
+    ///         .build(),
+    /// );
+    /// emitter.start_array().unwrap();
+    /// emitter.value_int(1).unwrap();
+    /// emitter.end_array().unwrap();
+    /// assert_eq!(
+    ///     emitter.finish().unwrap(),
+    ///     b"\x1b[1m[\x1b[0m\x1b[36m1\x1b[0m\x1b[1m]\x1b[0m"
+    /// );
+    /// ```
+    #[cfg(feature = "color")]
+    pub fn with_colors(mut self, colors: Option<super::ColorScheme>) -> Self {
+        self.options.colors = colors;
+        self
+    }
+
+    /// Produce canonical JSON according to
+    /// [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) (JSON
+    /// Canonicalization Scheme), which is suitable for hashing and signing.
+    /// The members of each object are sorted by their keys, numbers are
+    /// formatted like in ECMAScript, and no whitespace is written. The
+    /// indentation is ignored. Only the members of open objects are kept in
+    /// memory. Numbers that cannot be represented as an `f64` result in
+    /// [`EmitterError::IllegalJsonNumber`](super::EmitterError::IllegalJsonNumber).
+    ///
+    /// ```rust
+    /// use actson::emitter::{EmitterOptionsBuilder, JsonEmitter};
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::sink::parse_with;
+    ///
+    /// let json = br#"{"b": [1.0, 1E3, "\u00e9"], "a": {"z": null, "\u20ac": true}}"#;
+    /// let mut emitter = JsonEmitter::new_with_options(
+    ///     Vec::new(),
+    ///     EmitterOptionsBuilder::default().with_canonical(true).build(),
+    /// );
+    /// parse_with(SliceJsonFeeder::new(json), &mut emitter).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(emitter.finish().unwrap()).unwrap(),
+    ///     "{\"a\":{\"z\":null,\"\u{20ac}\":true},\"b\":[1,1000,\"\u{e9}\"]}"
+    /// );
+    /// ```
+    pub fn with_canonical(mut self, canonical: bool) -> Self {
+        self.options.canonical = canonical;
+        self
+    }
+
+    /// Create a new [`EmitterOptions`] object
+    pub fn build(self) -> EmitterOptions {
+        self.options
+    }
+}
@@ -0,0 +1,88 @@
+use std::str::from_utf8;
+
+use crate::{
+    feeder::JsonFeeder, sink::JsonEventSink, storage::ParserStorage, JsonEvent, JsonParser,
+};
+
+use super::{EmitterError, EmitterOptions, EmitterOptionsBuilder, Encoder, Indent};
+
+/// A [`JsonEventSink`] that pretty-prints the events it receives into a
+/// string. By default, nested values are indented with two spaces. Use
+/// [`JsonEmitter`](super::JsonEmitter) with the same options to write the
+/// output to an [`io::Write`](std::io::Write) instead.
+///
+/// ```
+/// use actson::emitter::PrettyPrinter;
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::sink::parse_with;
+///
+/// let json = r#"{"name": "Elvis", "albums": [1956, 1957], "awards": {}}"#.as_bytes();
+/// let mut prettyprinter = PrettyPrinter::new();
+/// parse_with(SliceJsonFeeder::new(json), &mut prettyprinter).unwrap();
+///
+/// assert_eq!(prettyprinter.get_result(), r#"{
+///   "name": "Elvis",
+///   "albums": [
+///     1956,
+///     1957
+///   ],
+///   "awards": {}
+/// }"#);
+/// ```
+pub struct PrettyPrinter {
+    encoder: Encoder,
+    result: Vec<u8>,
+}
+
+impl PrettyPrinter {
+    /// Create a new pretty printer that indents nested values with two
+    /// spaces
+    pub fn new() -> Self {
+        Self::new_with_options(
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(2))
+                .build(),
+        )
+    }
+
+    /// Create a new pretty printer with the given options. If the options do
+    /// not specify an indentation, the output is compact.
+    pub fn new_with_options(options: EmitterOptions) -> Self {
+        PrettyPrinter {
+            encoder: Encoder::new(options),
+            result: Vec::new(),
+        }
+    }
+
+    /// Get the output generated so far
+    pub fn get_result(&self) -> &str {
+        from_utf8(&self.result).expect("output is always valid UTF-8")
+    }
+
+    /// Consume the pretty printer and return its output
+    pub fn into_string(self) -> String {
+        String::from_utf8(self.result).expect("output is always valid UTF-8")
+    }
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonEventSink for PrettyPrinter {
+    type Error = EmitterError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        self.encoder.event(event, parser, &mut self.result)
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    io::{self, Write},
+    str::{from_utf8, Utf8Error},
+};
+
+use crate::parser::InvalidStringValueError;
+
+use super::{EmitterError, JsonEmitter};
+
+/// Writes the contents of a string value in chunks. Bytes written to it
+/// are escaped on the fly, so large strings never have to be kept in memory
+/// as a whole. Create it with [`JsonEmitter::begin_string()`] and close it
+/// with [`Self::end_string()`].
+///
+/// The bytes must be valid UTF-8. Characters may be split across calls to
+/// [`Write::write()`]. Otherwise, writing fails with
+/// [`io::ErrorKind::InvalidData`].
+///
+/// If the writer is dropped without calling [`Self::end_string()`], the
+/// string is closed anyway, but errors can only be reported by
+/// [`Self::end_string()`].
+pub struct StringWriter<'a, W>
+where
+    W: Write,
+{
+    emitter: &'a mut JsonEmitter<W>,
+
+    /// The beginning of a UTF-8 sequence whose remaining bytes have not been
+    /// written yet
+    pending: [u8; 4],
+
+    /// The number of bytes in [`Self::pending`]
+    pending_len: usize,
+
+    /// `true` if the string has been closed
+    closed: bool,
+}
+
+/// Returns the number of bytes in the UTF-8 sequence that starts with the
+/// given byte
+fn utf8_width(first: u8) -> usize {
+    match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
+
+fn invalid_data(e: Utf8Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, InvalidStringValueError::from(e))
+}
+
+impl<'a, W> StringWriter<'a, W>
+where
+    W: Write,
+{
+    pub(super) fn new(emitter: &'a mut JsonEmitter<W>) -> Self {
+        StringWriter {
+            emitter,
+            pending: [0; 4],
+            pending_len: 0,
+            closed: false,
+        }
+    }
+
+    /// Escape the given characters and append them to the string
+    fn chunk(&mut self, s: &str) {
+        self.emitter.encoder.string_chunk(s, &mut self.emitter.buf);
+    }
+
+    /// Write the end of the string. Returns an error if the bytes written
+    /// so far end with an incomplete UTF-8 sequence.
+    pub fn end_string(mut self) -> Result<(), EmitterError> {
+        if self.pending_len > 0 {
+            let e = from_utf8(&self.pending[..self.pending_len]).unwrap_err();
+            return Err(InvalidStringValueError::from(e).into());
+        }
+        self.closed = true;
+        self.emitter.encoder.end_string(&mut self.emitter.buf);
+        self.emitter.maybe_flush()
+    }
+}
+
+impl<W> Write for StringWriter<'_, W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut input = buf;
+
+        // complete the character from the previous call first
+        if self.pending_len > 0 {
+            let width = utf8_width(self.pending[0]);
+            let n = (width - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + n].copy_from_slice(&input[..n]);
+            self.pending_len += n;
+            input = &input[n..];
+            if self.pending_len < width {
+                return Ok(buf.len());
+            }
+            let pending = self.pending;
+            self.pending_len = 0;
+            self.chunk(from_utf8(&pending[..width]).map_err(invalid_data)?);
+        }
+
+        match from_utf8(input) {
+            Ok(s) => self.chunk(s),
+            Err(e) => {
+                let (valid, rest) = input.split_at(e.valid_up_to());
+                self.chunk(from_utf8(valid).expect("valid UTF-8"));
+                if e.error_len().is_some() {
+                    return Err(invalid_data(e));
+                }
+                self.pending[..rest.len()].copy_from_slice(rest);
+                self.pending_len = rest.len();
+            }
+        }
+
+        self.emitter.maybe_flush().map_err(|e| match e {
+            EmitterError::Io(e) => e,
+            e => io::Error::other(e),
+        })?;
+        Ok(buf.len())
+    }
+
+    /// Write all complete characters to the underlying writer and flush it.
+    /// The string is not closed.
+    fn flush(&mut self) -> io::Result<()> {
+        self.emitter.flush().map_err(|e| match e {
+            EmitterError::Io(e) => e,
+            e => io::Error::other(e),
+        })
+    }
+}
+
+impl<W> Drop for StringWriter<'_, W>
+where
+    W: Write,
+{
+    fn drop(&mut self) {
+        if !self.closed {
+            self.emitter.encoder.end_string(&mut self.emitter.buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use crate::emitter::{EmitterError, EmitterOptionsBuilder, JsonEmitter};
+
+    /// Test that characters split across writes are escaped correctly and
+    /// that invalid UTF-8 is rejected
+    #[test]
+    fn split_characters() {
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_ensure_ascii(true)
+                .build(),
+        );
+        emitter.start_array().unwrap();
+        let mut s = emitter.begin_string().unwrap();
+        let text = "a\"\u{e9}\u{20ac}\u{1f600}\n".as_bytes();
+        for b in text.chunks(1) {
+            s.write_all(b).unwrap();
+        }
+        s.end_string().unwrap();
+
+        let mut s = emitter.begin_string().unwrap();
+        assert_eq!(
+            s.write(b"x\xff").unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        drop(s);
+
+        let mut s = emitter.begin_string().unwrap();
+        s.write_all(b"\xe2\x82").unwrap();
+        assert!(matches!(
+            s.end_string(),
+            Err(EmitterError::InvalidStringValue(_))
+        ));
+        emitter.end_array().unwrap();
+
+        assert_eq!(
+            String::from_utf8(emitter.finish().unwrap()).unwrap(),
+            r#"["a\"\u00e9\u20ac\ud83d\ude00\n","x",""]"#
+        );
+    }
+}
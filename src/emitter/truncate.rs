@@ -0,0 +1,467 @@
+use std::{borrow::Cow, io::Write};
+
+use crate::{
+    escape::escape_str_with, feeder::JsonFeeder, sink::JsonEventSink, storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+use super::{EmitterError, EmitterOptions, JsonEmitter};
+
+/// The value that replaces omitted containers, members, elements, and the
+/// end of long strings
+const ELLIPSIS: &str = "...";
+
+/// A container that is currently being written
+struct Level {
+    /// `true` if the container is an object
+    is_object: bool,
+
+    /// The number of members or elements received so far
+    items: usize,
+
+    /// `true` if the remaining members or elements are omitted
+    truncated: bool,
+
+    /// `true` if at least one member or element has been written
+    written: bool,
+}
+
+/// A [`JsonEventSink`] that writes the events it receives like a
+/// [`JsonEmitter`] but leaves out parts of large values, so that huge
+/// payloads can be logged safely. The output is still valid JSON:
+///
+/// * Objects and arrays nested deeper than the maximum depth are replaced
+///   by the string `"..."`.
+/// * Members and elements beyond the maximum number of items per container
+///   are replaced by a single element `"..."` or a single member
+///   `"...": "..."`.
+/// * Strings longer than the maximum length are cut and end with `...`.
+/// * If a byte budget is set, the output ends before the first value that
+///   would exceed it, and all open objects and arrays are closed.
+///
+/// All limits are disabled by default.
+///
+/// ```
+/// use actson::emitter::TruncatingWriter;
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::sink::parse_with;
+///
+/// let json = br#"{"ids": [1, 2, 3, 4], "user": {"name": "Elvis", "address": {}}}"#;
+/// let mut writer = TruncatingWriter::new(Vec::new())
+///     .with_max_depth(2)
+///     .with_max_items(2)
+///     .with_max_string_len(3);
+/// parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+/// assert_eq!(
+///     writer.finish().unwrap(),
+///     br#"{"ids":[1,2,"..."],"user":{"name":"Elv...","address":"..."}}"#
+/// );
+/// ```
+pub struct TruncatingWriter<W>
+where
+    W: Write,
+{
+    emitter: JsonEmitter<W>,
+
+    /// The maximum nesting depth of containers
+    max_depth: Option<usize>,
+
+    /// The maximum number of members or elements per container
+    max_items: Option<usize>,
+
+    /// The maximum number of characters per string
+    max_string_len: Option<usize>,
+
+    /// The maximum number of bytes to write
+    max_bytes: Option<usize>,
+
+    /// The number of bytes written so far plus one for closing each open
+    /// container
+    bytes: usize,
+
+    /// A field name that is only written together with the start of its
+    /// value, so that it can be left out if the value does not fit into the
+    /// byte budget
+    pending_name: Option<String>,
+
+    /// `true` if the byte budget has been exhausted and all further events
+    /// are ignored
+    exhausted: bool,
+
+    /// The number of top-level values written so far
+    top_level_values: usize,
+
+    /// The containers that are currently being written
+    stack: Vec<Level>,
+
+    /// The nesting depth inside a container that is currently being
+    /// skipped or 0 if no container is being skipped
+    skip: usize,
+}
+
+impl<W> TruncatingWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer that writes compact JSON
+    pub fn new(writer: W) -> Self {
+        Self::new_with_options(writer, EmitterOptions::default())
+    }
+
+    /// Create a new writer with the given emitter options
+    pub fn new_with_options(writer: W, options: EmitterOptions) -> Self {
+        TruncatingWriter {
+            emitter: JsonEmitter::new_with_options(writer, options),
+            max_depth: None,
+            max_items: None,
+            max_string_len: None,
+            max_bytes: None,
+            bytes: 0,
+            pending_name: None,
+            exhausted: false,
+            top_level_values: 0,
+            stack: Vec::new(),
+            skip: 0,
+        }
+    }
+
+    /// Replace objects and arrays that are nested deeper than the given
+    /// depth. A depth of 0 replaces all containers, even at the top level.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Leave out all members or elements of a container after the given
+    /// number of items
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Cut strings (but not field names) after the given number of
+    /// characters
+    pub fn with_max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// Stop before the first value that would make the output longer than
+    /// the given number of bytes and close all open objects and arrays, so
+    /// that the output is a valid prefix of the full JSON text. The budget
+    /// includes the closing brackets and braces. It refers to output
+    /// without indentation, colors, and comments, so the actual output is
+    /// longer if the emitter options enable them. If not even the start of
+    /// the first value fits, nothing is written.
+    ///
+    /// ```
+    /// use actson::emitter::TruncatingWriter;
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::sink::parse_with;
+    ///
+    /// let json = br#"{"id": 1, "tags": ["a", "b", "c"], "name": "Elvis"}"#;
+    /// let mut writer = TruncatingWriter::new(Vec::new()).with_max_bytes(25);
+    /// parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+    /// assert!(writer.is_exhausted());
+    /// assert_eq!(writer.finish().unwrap(), br#"{"id":1,"tags":["a","b"]}"#);
+    /// ```
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Returns `true` if the output has ended early because the byte budget
+    /// has been exhausted
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        self.emitter.get_ref()
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub fn finish(self) -> Result<W, EmitterError> {
+        self.emitter.finish()
+    }
+
+    /// Check if the given event starts a new member or element in the
+    /// current container and omit it if the container already has enough
+    /// items. Returns `true` if the event has been handled.
+    fn truncate_item(&mut self, event: JsonEvent) -> Result<bool, EmitterError> {
+        let Some(level) = self.stack.last_mut() else {
+            return Ok(false);
+        };
+        let starts_item = if level.is_object {
+            matches!(event, JsonEvent::FieldName)
+        } else {
+            !event.is_container_end()
+        };
+        if !starts_item {
+            return Ok(false);
+        }
+        level.items += 1;
+        if self.max_items.is_none_or(|m| level.items <= m) {
+            return Ok(false);
+        }
+
+        level.truncated = true;
+        if level.is_object {
+            self.write_name(ELLIPSIS)?;
+        }
+        self.write_value(ELLIPSIS.len() + 2, |e| e.value_str(ELLIPSIS))?;
+        if event.is_container_start() {
+            self.skip = 1;
+        }
+        Ok(true)
+    }
+
+    /// Returns the length of the given string after quoting and escaping
+    fn escaped_len(&self, s: &str) -> usize {
+        let mut escaped = Vec::with_capacity(s.len() + 2);
+        escape_str_with(s, self.emitter.encoder.escape_options(), &mut escaped);
+        escaped.len()
+    }
+
+    /// Write a field name or keep it until its value is written if there
+    /// is a byte budget
+    fn write_name(&mut self, name: &str) -> Result<(), EmitterError> {
+        if self.max_bytes.is_some() {
+            self.pending_name = Some(name.to_string());
+            Ok(())
+        } else {
+            self.emitter.field_name(name)
+        }
+    }
+
+    /// Write a value (or the start of a container) whose compact output has
+    /// the given length (including the end of the container) together with
+    /// its pending field name. If it does not fit into the byte budget,
+    /// close all containers and return `false`.
+    fn write_value(
+        &mut self,
+        len: usize,
+        write: impl FnOnce(&mut JsonEmitter<W>) -> Result<(), EmitterError>,
+    ) -> Result<bool, EmitterError> {
+        if let Some(max_bytes) = self.max_bytes {
+            let separator = match self.stack.last() {
+                Some(level) => level.written,
+                None => self.top_level_values > 0,
+            };
+            let name_len = self
+                .pending_name
+                .as_deref()
+                .map_or(0, |n| self.escaped_len(n) + 1);
+            let bytes = self.bytes + usize::from(separator) + name_len + len;
+            if bytes > max_bytes {
+                self.exhaust()?;
+                return Ok(false);
+            }
+            self.bytes = bytes;
+            if let Some(name) = self.pending_name.take() {
+                self.emitter.field_name(&name)?;
+            }
+        }
+
+        write(&mut self.emitter)?;
+        match self.stack.last_mut() {
+            Some(level) => level.written = true,
+            None => self.top_level_values += 1,
+        }
+        Ok(true)
+    }
+
+    /// Close all open containers and ignore all further events
+    fn exhaust(&mut self) -> Result<(), EmitterError> {
+        self.exhausted = true;
+        self.pending_name = None;
+        while let Some(level) = self.stack.pop() {
+            if level.is_object {
+                self.emitter.end_object()?;
+            } else {
+                self.emitter.end_array()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W> JsonEventSink for TruncatingWriter<W>
+where
+    W: Write,
+{
+    type Error = EmitterError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if self.exhausted || matches!(event, JsonEvent::NeedMoreInput) {
+            return Ok(());
+        }
+
+        let is_start = event.is_container_start();
+        let is_end = event.is_container_end();
+
+        if self.skip > 0 {
+            if is_start {
+                self.skip += 1;
+            } else if is_end {
+                self.skip -= 1;
+            }
+            return Ok(());
+        }
+
+        if self.stack.last().is_some_and(|l| l.truncated) {
+            if is_start {
+                self.skip = 1;
+            } else if is_end {
+                self.stack.pop();
+                self.emitter.on_event(event, parser)?;
+            }
+            return Ok(());
+        }
+
+        if self.truncate_item(event)? {
+            return Ok(());
+        }
+
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                if self.max_depth.is_some_and(|m| self.stack.len() >= m) {
+                    self.skip = 1;
+                    self.write_value(ELLIPSIS.len() + 2, |e| e.value_str(ELLIPSIS))?;
+                } else if self.write_value(2, |e| e.on_event(event, parser))? {
+                    self.stack.push(Level {
+                        is_object: matches!(event, JsonEvent::StartObject),
+                        items: 0,
+                        truncated: false,
+                        written: false,
+                    });
+                }
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                self.stack.pop();
+                self.emitter.on_event(event, parser)?;
+            }
+            JsonEvent::FieldName => self.write_name(parser.current_str()?)?,
+            JsonEvent::ValueString => {
+                let s = parser.current_str()?;
+                let s = match self
+                    .max_string_len
+                    .and_then(|max| s.char_indices().nth(max))
+                {
+                    Some((end, _)) => Cow::Owned(format!("{}{ELLIPSIS}", &s[..end])),
+                    None => Cow::Borrowed(s),
+                };
+                let len = if self.max_bytes.is_some() {
+                    self.escaped_len(&s)
+                } else {
+                    0
+                };
+                self.write_value(len, |e| e.value_str(&s))?;
+            }
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let len = parser.current_raw_str()?.len();
+                self.write_value(len, |e| e.on_event(event, parser))?;
+            }
+            JsonEvent::ValueTrue | JsonEvent::ValueNull => {
+                self.write_value(4, |e| e.on_event(event, parser))?;
+            }
+            JsonEvent::ValueFalse => {
+                self.write_value(5, |e| e.on_event(event, parser))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, JsonParser};
+
+    use super::TruncatingWriter;
+
+    /// Test that omitted containers are skipped completely, even if they
+    /// contain nested containers, and that the limits apply to each
+    /// top-level value
+    #[test]
+    fn skip_nested() {
+        let json = br#"[[1, [2, [3]]], [4, 5, 6], {"a": [7], "b": {}, "c": 8}] "\u00e9\u00e9""#;
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut writer = TruncatingWriter::new(Vec::new())
+            .with_max_depth(2)
+            .with_max_items(2)
+            .with_max_string_len(1);
+        crate::sink::parse_with_parser(&mut parser, &mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "[[1,\"...\"],[4,5,\"...\"],\"...\"]\n\"\u{e9}...\""
+        );
+
+        let json = br#"{"a": {"b": 1}, "c": [], "d": [{"e": 2}]}"#;
+        let mut writer = TruncatingWriter::new(Vec::new()).with_max_items(2);
+        crate::sink::parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+        assert_eq!(
+            writer.finish().unwrap(),
+            br#"{"a":{"b":1},"c":[],"...":"..."}"#
+        );
+    }
+
+    /// Test that the byte budget includes separators, field names, and
+    /// closing brackets, that field names are only written together with
+    /// their values, and that other limits are taken into account
+    #[test]
+    fn max_bytes() {
+        let json = br#"[1, {"abc": [true], "d": "e\"f"}, 2]"#;
+        let full = br#"[1,{"abc":[true],"d":"e\"f"},2]"#;
+        for max_bytes in 0..=full.len() {
+            let mut writer = TruncatingWriter::new(Vec::new()).with_max_bytes(max_bytes);
+            crate::sink::parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+            let exhausted = writer.is_exhausted();
+            let output = writer.finish().unwrap();
+            assert!(output.len() <= max_bytes, "{max_bytes}");
+            assert_eq!(exhausted, max_bytes < full.len(), "{max_bytes}");
+            let expected: &[u8] = match max_bytes {
+                0..=1 => b"",
+                2 => b"[]",
+                3..=5 => b"[1]",
+                6..=13 => br#"[1,{}]"#,
+                14..=17 => br#"[1,{"abc":[]}]"#,
+                18..=28 => br#"[1,{"abc":[true]}]"#,
+                29..=30 => br#"[1,{"abc":[true],"d":"e\"f"}]"#,
+                _ => full,
+            };
+            assert_eq!(output, expected, "{max_bytes}");
+        }
+
+        let json = br#"{"a": ["\u00e9\u00e9", 2, 3]} {"c": 1}"#;
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut writer = TruncatingWriter::new(Vec::new())
+            .with_max_items(1)
+            .with_max_string_len(1)
+            .with_max_bytes(26);
+        crate::sink::parse_with_parser(&mut parser, &mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "{\"a\":[\"\u{e9}...\",\"...\"]}\n{}"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// Specifies which characters should be escaped in addition to quotes,
+/// backslashes, and control characters
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct EscapeOptions {
+    /// Escape all non-ASCII characters
+    pub(crate) ascii: bool,
+
+    /// Escape `<`, `>`, `&`, U+2028, and U+2029
+    pub(crate) html: bool,
+
+    /// Escape `/` as `\/`
+    pub(crate) slash: bool,
+}
+
+/// Append `\uXXXX` for the given UTF-16 code unit to `out`
+fn push_unicode_escape(unit: u16, out: &mut Vec<u8>) {
+    out.extend_from_slice(&[
+        b'\\',
+        b'u',
+        HEX[(unit >> 12) as usize],
+        HEX[((unit >> 8) & 0xf) as usize],
+        HEX[((unit >> 4) & 0xf) as usize],
+        HEX[(unit & 0xf) as usize],
+    ]);
+}
+
+/// Append the given string to `out` as a quoted JSON string. Quotes,
+/// backslashes, and control characters are escaped.
+pub(crate) fn escape_str(s: &str, out: &mut Vec<u8>) {
+    escape_str_with(s, EscapeOptions::default(), out)
+}
+
+/// Like [`escape_str()`] but also escape the characters selected by the
+/// given options
+pub(crate) fn escape_str_with(s: &str, options: EscapeOptions, out: &mut Vec<u8>) {
+    out.push(b'"');
+    escape_contents(s, options, out);
+    out.push(b'"');
+}
+
+/// Append the given string to `out` with all characters selected by the
+/// given options escaped but without quotes
+pub(crate) fn escape_contents(s: &str, options: EscapeOptions, out: &mut Vec<u8>) {
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let escaped: &[u8] = match b {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x08 => b"\\b",
+            0x0c => b"\\f",
+            b'/' if options.slash => b"\\/",
+            0..=0x1f | b'<' | b'>' | b'&' if b < 0x20 || options.html => &[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[(b >> 4) as usize],
+                HEX[(b & 0xf) as usize],
+            ],
+            // U+2028 LINE SEPARATOR and U+2029 PARAGRAPH SEPARATOR are
+            // valid in JSON strings but not in older JavaScript parsers
+            0xe2 if options.html
+                && matches!(bytes.get(i + 1..i + 3), Some([0x80, 0xa8 | 0xa9])) =>
+            {
+                out.extend_from_slice(&bytes[start..i]);
+                let unit = if bytes[i + 2] == 0xa8 { 0x2028 } else { 0x2029 };
+                push_unicode_escape(unit, out);
+                start = i + 3;
+                continue;
+            }
+            0x80.. if options.ascii => {
+                // only handle the first byte of each character
+                if b & 0xc0 == 0x80 {
+                    continue;
+                }
+                out.extend_from_slice(&bytes[start..i]);
+                let c = s[i..].chars().next().expect("valid UTF-8");
+                let mut units = [0u16; 2];
+                for &unit in c.encode_utf16(&mut units).iter() {
+                    push_unicode_escape(unit, out);
+                }
+                start = i + c.len_utf8();
+                continue;
+            }
+            _ => continue,
+        };
+        out.extend_from_slice(&bytes[start..i]);
+        out.extend_from_slice(escaped);
+        start = i + 1;
+    }
+    out.extend_from_slice(&bytes[start..]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{escape_str, escape_str_with, EscapeOptions};
+
+    fn escape_str_owned(s: &str) -> String {
+        let mut out = Vec::new();
+        escape_str(s, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    fn escape_with(s: &str, options: EscapeOptions) -> String {
+        let mut out = Vec::new();
+        escape_str_with(s, options, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Test that special characters are escaped and everything else is kept
+    #[test]
+    fn escape() {
+        assert_eq!(escape_str_owned(""), r#""""#);
+        assert_eq!(escape_str_owned("Bj\u{f6}rn"), "\"Bj\u{f6}rn\"");
+        assert_eq!(escape_str_owned("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+    }
+
+    /// Test that characters that are unsafe inside HTML `<script>` tags
+    /// and forward slashes are escaped if requested
+    #[test]
+    fn html() {
+        let options = EscapeOptions {
+            html: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            escape_with("</script><b>&amp;</b>", options),
+            r#""\u003c/script\u003e\u003cb\u003e\u0026amp;\u003c/b\u003e""#
+        );
+        assert_eq!(
+            escape_with("a\u{2028}b\u{2029}c\u{2027}", options),
+            "\"a\\u2028b\\u2029c\u{2027}\""
+        );
+
+        let options = EscapeOptions {
+            html: true,
+            slash: true,
+            ..Default::default()
+        };
+        assert_eq!(escape_with("</a>", options), r#""\u003c\/a\u003e""#);
+        assert_eq!(escape_str_owned("</a>"), r#""</a>""#);
+    }
+
+    /// Test that non-ASCII characters are escaped if requested, including
+    /// characters outside the Basic Multilingual Plane
+    #[test]
+    fn ascii() {
+        let options = EscapeOptions {
+            ascii: true,
+            ..Default::default()
+        };
+        assert_eq!(escape_with("Bj\u{f6}rn", options), r#""Bj\u00f6rn""#);
+        assert_eq!(
+            escape_with("\u{20ac}\u{1f600}\n", options),
+            r#""\u20ac\ud83d\ude00\n""#
+        );
+    }
+}
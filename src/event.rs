@@ -1,5 +1,6 @@
 /// All possible JSON events returned by [`JsonParser::next_event()`](crate::JsonParser::next_event())
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JsonEvent {
     /// The JSON parser needs more input before the next event can be returned.
     /// Invoke the parser's feeder to give it more input.
@@ -42,3 +43,242 @@ pub enum JsonEvent {
     /// A `null` value.
     ValueNull = 11,
 }
+
+impl JsonEvent {
+    /// Return `Some(true)` for [`JsonEvent::ValueTrue`], `Some(false)` for
+    /// [`JsonEvent::ValueFalse`], and `None` for all other events
+    pub fn as_bool(self) -> Option<bool> {
+        match self {
+            JsonEvent::ValueTrue => Some(true),
+            JsonEvent::ValueFalse => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if this event starts a value, i.e. if it is a scalar
+    /// (see [`Self::is_scalar()`]) or the start of an object or array. Field
+    /// names, the ends of containers, and [`JsonEvent::NeedMoreInput`] are
+    /// not values.
+    ///
+    /// ```
+    /// use actson::JsonEvent;
+    ///
+    /// assert!(JsonEvent::StartArray.is_value());
+    /// assert!(JsonEvent::ValueNull.is_value());
+    /// assert!(!JsonEvent::FieldName.is_value());
+    /// assert!(!JsonEvent::EndObject.is_value());
+    /// ```
+    pub fn is_value(self) -> bool {
+        self.is_scalar() || self.is_container_start()
+    }
+
+    /// Return `true` for events that represent a complete value: strings,
+    /// numbers, booleans, and `null`
+    pub fn is_scalar(self) -> bool {
+        matches!(
+            self,
+            JsonEvent::ValueString
+                | JsonEvent::ValueInt
+                | JsonEvent::ValueFloat
+                | JsonEvent::ValueTrue
+                | JsonEvent::ValueFalse
+                | JsonEvent::ValueNull
+        )
+    }
+
+    /// Return `true` for [`JsonEvent::StartObject`] and
+    /// [`JsonEvent::StartArray`]
+    pub fn is_container_start(self) -> bool {
+        matches!(self, JsonEvent::StartObject | JsonEvent::StartArray)
+    }
+
+    /// Return `true` for [`JsonEvent::EndObject`] and
+    /// [`JsonEvent::EndArray`]
+    pub fn is_container_end(self) -> bool {
+        matches!(self, JsonEvent::EndObject | JsonEvent::EndArray)
+    }
+}
+
+/// A [`JsonEvent`] together with its value. Unlike [`JsonEvent`], which
+/// requires the value to be read from the parser, an owned event does not
+/// borrow the parser and can be stored or sent to another thread. Returned by
+/// [`JsonParser::next_owned_event()`](crate::JsonParser::next_owned_event()).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedJsonEvent {
+    /// See [`JsonEvent::NeedMoreInput`]
+    NeedMoreInput,
+
+    /// See [`JsonEvent::StartObject`]
+    StartObject,
+
+    /// See [`JsonEvent::EndObject`]
+    EndObject,
+
+    /// See [`JsonEvent::StartArray`]
+    StartArray,
+
+    /// See [`JsonEvent::EndArray`]
+    EndArray,
+
+    /// A field name
+    FieldName(String),
+
+    /// A string value
+    ValueString(String),
+
+    /// An integer value
+    ValueInt(i64),
+
+    /// An integer value that does not fit into an [`i64`] but into a
+    /// [`u64`] (reported as [`JsonEvent::ValueInt`] by the parser)
+    ValueUint(u64),
+
+    /// A floating point value
+    ValueFloat(f64),
+
+    /// The boolean value `true`
+    ValueTrue,
+
+    /// The boolean value `false`
+    ValueFalse,
+
+    /// A `null` value
+    ValueNull,
+}
+
+impl OwnedJsonEvent {
+    /// Return the type of this event without its value
+    pub fn event(&self) -> JsonEvent {
+        match self {
+            OwnedJsonEvent::NeedMoreInput => JsonEvent::NeedMoreInput,
+            OwnedJsonEvent::StartObject => JsonEvent::StartObject,
+            OwnedJsonEvent::EndObject => JsonEvent::EndObject,
+            OwnedJsonEvent::StartArray => JsonEvent::StartArray,
+            OwnedJsonEvent::EndArray => JsonEvent::EndArray,
+            OwnedJsonEvent::FieldName(_) => JsonEvent::FieldName,
+            OwnedJsonEvent::ValueString(_) => JsonEvent::ValueString,
+            OwnedJsonEvent::ValueInt(_) | OwnedJsonEvent::ValueUint(_) => JsonEvent::ValueInt,
+            OwnedJsonEvent::ValueFloat(_) => JsonEvent::ValueFloat,
+            OwnedJsonEvent::ValueTrue => JsonEvent::ValueTrue,
+            OwnedJsonEvent::ValueFalse => JsonEvent::ValueFalse,
+            OwnedJsonEvent::ValueNull => JsonEvent::ValueNull,
+        }
+    }
+}
+
+/// A [`JsonEvent`] together with its value borrowed from the parser.
+/// Unlike [`OwnedJsonEvent`], reading a borrowed event does not allocate,
+/// but the event must be dropped before the parser can advance. Returned by
+/// [`JsonParser::advance()`](crate::JsonParser::advance()).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BorrowedJsonEvent<'a> {
+    /// See [`JsonEvent::NeedMoreInput`]
+    NeedMoreInput,
+
+    /// See [`JsonEvent::StartObject`]
+    StartObject,
+
+    /// See [`JsonEvent::EndObject`]
+    EndObject,
+
+    /// See [`JsonEvent::StartArray`]
+    StartArray,
+
+    /// See [`JsonEvent::EndArray`]
+    EndArray,
+
+    /// A field name
+    FieldName(&'a str),
+
+    /// A string value
+    ValueString(&'a str),
+
+    /// An integer value
+    ValueInt(i64),
+
+    /// An integer value that does not fit into an [`i64`] but into a
+    /// [`u64`] (reported as [`JsonEvent::ValueInt`] by the parser)
+    ValueUint(u64),
+
+    /// A floating point value
+    ValueFloat(f64),
+
+    /// The boolean value `true`
+    ValueTrue,
+
+    /// The boolean value `false`
+    ValueFalse,
+
+    /// A `null` value
+    ValueNull,
+}
+
+impl BorrowedJsonEvent<'_> {
+    /// Return the type of this event without its value
+    pub fn event(&self) -> JsonEvent {
+        match self {
+            BorrowedJsonEvent::NeedMoreInput => JsonEvent::NeedMoreInput,
+            BorrowedJsonEvent::StartObject => JsonEvent::StartObject,
+            BorrowedJsonEvent::EndObject => JsonEvent::EndObject,
+            BorrowedJsonEvent::StartArray => JsonEvent::StartArray,
+            BorrowedJsonEvent::EndArray => JsonEvent::EndArray,
+            BorrowedJsonEvent::FieldName(_) => JsonEvent::FieldName,
+            BorrowedJsonEvent::ValueString(_) => JsonEvent::ValueString,
+            BorrowedJsonEvent::ValueInt(_) | BorrowedJsonEvent::ValueUint(_) => JsonEvent::ValueInt,
+            BorrowedJsonEvent::ValueFloat(_) => JsonEvent::ValueFloat,
+            BorrowedJsonEvent::ValueTrue => JsonEvent::ValueTrue,
+            BorrowedJsonEvent::ValueFalse => JsonEvent::ValueFalse,
+            BorrowedJsonEvent::ValueNull => JsonEvent::ValueNull,
+        }
+    }
+
+    /// Copy the value of this event so that it no longer borrows the parser
+    pub fn into_owned(self) -> OwnedJsonEvent {
+        match self {
+            BorrowedJsonEvent::NeedMoreInput => OwnedJsonEvent::NeedMoreInput,
+            BorrowedJsonEvent::StartObject => OwnedJsonEvent::StartObject,
+            BorrowedJsonEvent::EndObject => OwnedJsonEvent::EndObject,
+            BorrowedJsonEvent::StartArray => OwnedJsonEvent::StartArray,
+            BorrowedJsonEvent::EndArray => OwnedJsonEvent::EndArray,
+            BorrowedJsonEvent::FieldName(s) => OwnedJsonEvent::FieldName(s.to_string()),
+            BorrowedJsonEvent::ValueString(s) => OwnedJsonEvent::ValueString(s.to_string()),
+            BorrowedJsonEvent::ValueInt(i) => OwnedJsonEvent::ValueInt(i),
+            BorrowedJsonEvent::ValueUint(u) => OwnedJsonEvent::ValueUint(u),
+            BorrowedJsonEvent::ValueFloat(f) => OwnedJsonEvent::ValueFloat(f),
+            BorrowedJsonEvent::ValueTrue => OwnedJsonEvent::ValueTrue,
+            BorrowedJsonEvent::ValueFalse => OwnedJsonEvent::ValueFalse,
+            BorrowedJsonEvent::ValueNull => OwnedJsonEvent::ValueNull,
+        }
+    }
+}
+
+/// The position of a [`JsonEvent`] in the JSON text, given as a range of
+/// byte offsets. `start` is inclusive and `end` is exclusive.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// The offset of the event's first byte
+    pub start: usize,
+
+    /// The offset right after the event's last byte
+    pub end: usize,
+}
+
+impl Span {
+    /// Create a new span
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Return the number of bytes covered by the span
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Return `true` if the span does not cover any bytes
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
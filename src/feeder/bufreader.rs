@@ -1,6 +1,6 @@
 use std::io::{BufRead, BufReader, Read};
 
-use super::{FillError, JsonFeeder};
+use super::{FillError, FillJsonFeeder, JsonFeeder};
 
 /// A [`JsonFeeder`] that reads from a [`BufReader`].
 pub struct BufReaderJsonFeeder<T> {
@@ -54,4 +54,29 @@ where
             None
         }
     }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.reader.buffer()[self.pos..]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        if n > self.pos {
+            return false;
+        }
+        self.pos -= n;
+        true
+    }
+}
+
+impl<T> FillJsonFeeder for BufReaderJsonFeeder<T>
+where
+    T: Read,
+{
+    fn fill(&mut self) -> Result<(), FillError> {
+        self.fill_buf()
+    }
 }
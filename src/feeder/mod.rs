@@ -1,10 +1,16 @@
 mod bufreader;
 mod push;
 mod slice;
+mod spsc;
+mod string;
+mod tail;
 
 pub use bufreader::BufReaderJsonFeeder;
 pub use push::{PushError, PushJsonFeeder};
 pub use slice::SliceJsonFeeder;
+pub use spsc::{FeederWriter, SpscJsonFeeder};
+pub use string::StrJsonFeeder;
+pub use tail::TailJsonFeeder;
 
 use thiserror::Error;
 
@@ -25,4 +31,61 @@ pub trait JsonFeeder {
 
     /// Decode and return the next character to be parsed
     fn next_input(&mut self) -> Option<u8>;
+
+    /// Return all bytes consumed so far if the feeder keeps its complete
+    /// input in a contiguous buffer. The parser uses this to read strings
+    /// directly from the input instead of copying them into its own buffer.
+    /// The default implementation returns `None`.
+    fn consumed_input(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Like [`consumed_input()`](Self::consumed_input()) but return the
+    /// bytes consumed so far as a string if the feeder's input is known to
+    /// be valid UTF-8. If the last consumed byte is not the end of a
+    /// character, the incomplete character is not included. The string must
+    /// refer to the same memory as the slice returned by
+    /// [`consumed_input()`](Self::consumed_input()). The parser uses this to skip validating strings that
+    /// do not contain escape sequences. The default implementation returns
+    /// `None`.
+    fn consumed_str(&self) -> Option<&str> {
+        None
+    }
+
+    /// Return input bytes that are currently available and that can be
+    /// consumed in bulk with [`consume_input()`](Self::consume_input()). The
+    /// parser uses this to process runs of bytes (e.g. white space or the
+    /// contents of strings) without calling [`next_input()`](Self::next_input())
+    /// for each of them. The slice does not have to contain all available
+    /// bytes. The default implementation returns an empty slice.
+    fn peek_input(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Skip the first `n` bytes of the slice returned by
+    /// [`peek_input()`](Self::peek_input())
+    fn consume_input(&mut self, n: usize) {
+        for _ in 0..n {
+            self.next_input();
+        }
+    }
+
+    /// Step back by `n` bytes, so that they are returned again by
+    /// [`next_input()`](Self::next_input()), and return `true`. If the feeder
+    /// has already discarded some of these bytes, nothing happens and
+    /// `false` is returned. The parser uses this to rewind to a
+    /// [`SavePoint`](crate::parser::SavePoint). The default implementation
+    /// always returns `false` unless `n` is 0.
+    fn rewind_input(&mut self, n: usize) -> bool {
+        n == 0
+    }
+}
+
+/// A [`JsonFeeder`] that can get more input on its own (e.g. by reading
+/// from a file) and that can therefore be used with
+/// [`parse_with()`](crate::sink::parse_with())
+pub trait FillJsonFeeder: JsonFeeder {
+    /// Block until more input is available or the end of the input has
+    /// been reached
+    fn fill(&mut self) -> Result<(), FillError>;
 }
@@ -5,6 +5,9 @@ use thiserror::Error;
 
 use super::JsonFeeder;
 
+/// The default capacity of a [`PushJsonFeeder`] in bytes
+pub(super) const DEFAULT_CAPACITY: usize = 1024;
+
 #[derive(Error, Debug)]
 pub enum PushError {
     #[error("feeder is full")]
@@ -30,8 +33,22 @@ pub struct PushJsonFeeder {
 impl PushJsonFeeder {
     /// Create a new push-based feeder
     pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new push-based feeder that accepts at least the given
+    /// number of bytes before it is full. A larger capacity means fewer
+    /// alternations between pushing and parsing.
+    ///
+    /// ```
+    /// use actson::feeder::PushJsonFeeder;
+    ///
+    /// let mut feeder = PushJsonFeeder::with_capacity(64 * 1024);
+    /// assert_eq!(feeder.push_bytes(&[b' '; 64 * 1024]), 64 * 1024);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
         PushJsonFeeder {
-            input: VecDeque::with_capacity(1024),
+            input: VecDeque::with_capacity(capacity.max(1)),
             done: false,
         }
     }
@@ -53,7 +70,7 @@ impl PushJsonFeeder {
     /// of bytes consumed (which can be 0 if the parser does not accept more
     /// input at the moment).
     pub fn push_bytes(&mut self, buf: &[u8]) -> usize {
-        let n = min(buf.len(), self.input.capacity() - self.input.len());
+        let n = min(buf.len(), self.spare_capacity());
         self.input.extend(buf.iter().take(n));
         n
     }
@@ -66,6 +83,53 @@ impl PushJsonFeeder {
         self.input.len() == self.input.capacity()
     }
 
+    /// Return the maximum number of bytes the feeder can hold
+    pub fn capacity(&self) -> usize {
+        self.input.capacity()
+    }
+
+    /// Return the number of bytes that can currently be pushed before the
+    /// feeder is full
+    ///
+    /// ```
+    /// use actson::feeder::PushJsonFeeder;
+    ///
+    /// let mut feeder = PushJsonFeeder::with_capacity(16);
+    /// let capacity = feeder.capacity();
+    /// feeder.push_bytes(b"[1, 2]");
+    /// assert_eq!(feeder.spare_capacity(), capacity - 6);
+    ///
+    /// // make room for a larger chunk
+    /// feeder.reserve(1024);
+    /// assert!(feeder.spare_capacity() >= 1024);
+    /// ```
+    pub fn spare_capacity(&self) -> usize {
+        self.input.capacity() - self.input.len()
+    }
+
+    /// Increase the capacity so that at least `additional` more bytes can
+    /// be pushed than are currently buffered
+    pub fn reserve(&mut self, additional: usize) {
+        self.input.reserve(additional);
+    }
+
+    /// Release memory beyond what the currently buffered bytes need. The
+    /// feeder still accepts at least one more byte afterwards. See also
+    /// [`Self::shrink_to()`].
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Release memory beyond the given capacity or the number of bytes
+    /// currently buffered plus one, whichever is larger. The feeder accepts
+    /// at least one more byte afterwards, but fewer than before if its
+    /// capacity has been reduced.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.input.shrink_to(min_capacity);
+        // a full feeder would not accept any input anymore
+        self.input.reserve_exact(1);
+    }
+
     /// Call this method to indicate that the end of the JSON text has been
     /// reached and that there is no more input to parse.
     pub fn done(&mut self) {
@@ -91,6 +155,14 @@ impl JsonFeeder for PushJsonFeeder {
     fn next_input(&mut self) -> Option<u8> {
         self.input.pop_front()
     }
+
+    fn peek_input(&self) -> &[u8] {
+        self.input.as_slices().0
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.input.drain(..n);
+    }
 }
 
 #[cfg(test)]
@@ -246,4 +318,38 @@ mod test {
             &mut feeder,
         );
     }
+
+    /// Test that shrinking keeps buffered bytes and that the feeder still
+    /// accepts input afterwards
+    #[test]
+    fn shrink() {
+        let mut feeder = PushJsonFeeder::with_capacity(4096);
+        feeder.push_bytes(b"abc");
+        feeder.shrink_to(0);
+        assert!(feeder.input.capacity() < 4096);
+        assert_eq!(feeder.next_input(), Some(b'a'));
+        assert_eq!(feeder.next_input(), Some(b'b'));
+        assert_eq!(feeder.next_input(), Some(b'c'));
+
+        feeder.shrink_to(0);
+        assert!(!feeder.is_full());
+        feeder.push_byte(b'd').unwrap();
+
+        // a full feeder still accepts input after shrinking
+        let mut feeder = PushJsonFeeder::with_capacity(4096);
+        while !feeder.is_full() {
+            feeder.push_byte(b'x').unwrap();
+        }
+        let len = feeder.input.len();
+        feeder.shrink_to_fit();
+        assert!(!feeder.is_full());
+        assert_eq!(feeder.push_bytes(b"y"), 1);
+        assert_eq!(feeder.input.len(), len + 1);
+
+        let mut feeder = PushJsonFeeder::with_capacity(4096);
+        feeder.push_bytes(b"abc");
+        feeder.shrink_to(64);
+        assert!(feeder.capacity() >= 64);
+        assert!(feeder.capacity() < 4096);
+    }
 }
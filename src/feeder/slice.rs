@@ -1,4 +1,4 @@
-use super::JsonFeeder;
+use super::{FillError, FillJsonFeeder, JsonFeeder};
 
 /// A [`JsonFeeder`] that feeds the [`JsonParser`](crate::JsonParser) from a slice of bytes
 pub struct SliceJsonFeeder<'a> {
@@ -11,6 +11,17 @@ impl<'a> SliceJsonFeeder<'a> {
     pub fn new(slice: &'a [u8]) -> Self {
         SliceJsonFeeder { slice, pos: 0 }
     }
+
+    /// Return the wrapped slice
+    #[cfg(feature = "serde_json")]
+    pub(crate) fn slice(&self) -> &'a [u8] {
+        self.slice
+    }
+
+    /// Return the bytes consumed so far with the lifetime of the wrapped slice
+    pub(crate) fn consumed_slice(&self) -> &'a [u8] {
+        &self.slice[..self.pos]
+    }
 }
 
 impl<'a> JsonFeeder for SliceJsonFeeder<'a> {
@@ -31,6 +42,33 @@ impl<'a> JsonFeeder for SliceJsonFeeder<'a> {
             r
         }
     }
+
+    fn consumed_input(&self) -> Option<&[u8]> {
+        Some(self.consumed_slice())
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.slice[self.pos..]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        if n > self.pos {
+            return false;
+        }
+        self.pos -= n;
+        true
+    }
+}
+
+impl FillJsonFeeder for SliceJsonFeeder<'_> {
+    fn fill(&mut self) -> Result<(), FillError> {
+        // the slice is always complete
+        Ok(())
+    }
 }
 
 #[cfg(test)]
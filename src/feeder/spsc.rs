@@ -0,0 +1,263 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+    Arc,
+};
+
+use super::{push::DEFAULT_CAPACITY, JsonFeeder, PushError};
+
+/// The ring buffer shared between a [`FeederWriter`] and a
+/// [`SpscJsonFeeder`]
+struct Shared {
+    buf: Box<[AtomicU8]>,
+
+    /// The total number of bytes read so far. Only written by the feeder.
+    head: AtomicUsize,
+
+    /// The total number of bytes written so far. Only written by the writer.
+    tail: AtomicUsize,
+
+    done: AtomicBool,
+}
+
+/// A variant of the [`PushJsonFeeder`](super::PushJsonFeeder) that is split
+/// into two halves, so that input can be pushed and parsed on different
+/// threads or tasks without putting the whole feeder behind a mutex. The
+/// [`FeederWriter`] is used by the thread doing I/O, and the
+/// [`SpscJsonFeeder`] is owned by the [`JsonParser`](crate::JsonParser).
+///
+/// Both halves share a fixed-size, lock-free ring buffer for a single
+/// producer and a single consumer. Like with the
+/// [`PushJsonFeeder`](super::PushJsonFeeder), the writer can only push as
+/// many bytes as there is room in the buffer, and the parser returns
+/// [`JsonEvent::NeedMoreInput`](crate::JsonEvent::NeedMoreInput) whenever
+/// the buffer is empty. Waking up the other side is up to the caller.
+///
+/// ```
+/// use std::thread;
+///
+/// use actson::feeder::SpscJsonFeeder;
+/// use actson::{JsonEvent, JsonParser};
+///
+/// let (mut writer, feeder) = SpscJsonFeeder::with_capacity(8);
+///
+/// let producer = thread::spawn(move || {
+///     let mut json: &[u8] = br#"{"name": "Elvis", "born": 1935}"#;
+///     while !json.is_empty() {
+///         let n = writer.push_bytes(json);
+///         json = &json[n..];
+///         thread::yield_now();
+///     }
+///     writer.done();
+/// });
+///
+/// let mut parser = JsonParser::new(feeder);
+/// let mut born = None;
+/// while let Some(e) = parser.next_event().unwrap() {
+///     match e {
+///         JsonEvent::NeedMoreInput => thread::yield_now(),
+///         JsonEvent::ValueInt => born = Some(parser.current_int::<i64>().unwrap()),
+///         _ => {}
+///     }
+/// }
+/// producer.join().unwrap();
+/// assert_eq!(born, Some(1935));
+/// ```
+pub struct SpscJsonFeeder {
+    shared: Arc<Shared>,
+}
+
+/// The half of a [`SpscJsonFeeder`] that provides input. It can be sent to
+/// another thread or task.
+pub struct FeederWriter {
+    shared: Arc<Shared>,
+}
+
+impl SpscJsonFeeder {
+    /// Create a new pair of a writer and a feeder with a default capacity
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (FeederWriter, SpscJsonFeeder) {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new pair of a writer and a feeder that can buffer the given
+    /// number of bytes (at least one)
+    pub fn with_capacity(capacity: usize) -> (FeederWriter, SpscJsonFeeder) {
+        let shared = Arc::new(Shared {
+            buf: (0..capacity.max(1)).map(|_| AtomicU8::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            done: AtomicBool::new(false),
+        });
+        (
+            FeederWriter {
+                shared: Arc::clone(&shared),
+            },
+            SpscJsonFeeder { shared },
+        )
+    }
+
+    /// Return `true` if the [`FeederWriter`] has been dropped. If it has
+    /// been dropped without calling [`FeederWriter::done()`], no more input
+    /// will arrive.
+    pub fn is_writer_dropped(&self) -> bool {
+        Arc::strong_count(&self.shared) == 1
+    }
+}
+
+impl FeederWriter {
+    /// Provide more data to the [`JsonParser`](crate::JsonParser). Should
+    /// only be called if [`is_full()`](Self::is_full()) returns `false`.
+    pub fn push_byte(&mut self, b: u8) -> Result<(), PushError> {
+        if self.push_bytes(&[b]) == 0 {
+            return Err(PushError::Full);
+        }
+        Ok(())
+    }
+
+    /// Provide more data to the [`JsonParser`](crate::JsonParser). Push as
+    /// many bytes as there is room for in the buffer and return their
+    /// number (which can be 0 if the buffer is full).
+    pub fn push_bytes(&mut self, buf: &[u8]) -> usize {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Relaxed);
+        let n = buf.len().min(self.spare_capacity());
+        let capacity = shared.buf.len();
+        for (i, &b) in buf[..n].iter().enumerate() {
+            shared.buf[(tail + i) % capacity].store(b, Ordering::Relaxed);
+        }
+        // publish the bytes written above
+        shared.tail.store(tail + n, Ordering::Release);
+        n
+    }
+
+    /// Checks if the parser accepts more input at the moment
+    pub fn is_full(&self) -> bool {
+        self.spare_capacity() == 0
+    }
+
+    /// Return the maximum number of bytes the buffer can hold
+    pub fn capacity(&self) -> usize {
+        self.shared.buf.len()
+    }
+
+    /// Return the number of bytes that can currently be pushed before the
+    /// buffer is full
+    pub fn spare_capacity(&self) -> usize {
+        let head = self.shared.head.load(Ordering::Acquire);
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        self.capacity() - (tail - head)
+    }
+
+    /// Call this method to indicate that the end of the JSON text has been
+    /// reached and that there is no more input to parse.
+    pub fn done(self) {
+        self.shared.done.store(true, Ordering::Release);
+    }
+}
+
+impl JsonFeeder for SpscJsonFeeder {
+    fn has_input(&self) -> bool {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        self.shared.tail.load(Ordering::Acquire) != head
+    }
+
+    fn is_done(&self) -> bool {
+        // everything pushed before `done()` is visible after this
+        self.shared.done.load(Ordering::Acquire) && !self.has_input()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        if !self.has_input() {
+            return None;
+        }
+        let shared = &*self.shared;
+        let head = shared.head.load(Ordering::Relaxed);
+        let b = shared.buf[head % shared.buf.len()].load(Ordering::Relaxed);
+        // hand the slot back to the writer
+        shared.head.store(head + 1, Ordering::Release);
+        Some(b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use crate::{
+        feeder::{JsonFeeder, PushError},
+        JsonEvent, JsonParser,
+    };
+
+    use super::SpscJsonFeeder;
+
+    /// Test that bytes wrap around the ring buffer and that the writer
+    /// respects its capacity
+    #[test]
+    fn push_and_read() {
+        let (mut writer, mut feeder) = SpscJsonFeeder::with_capacity(4);
+        assert!(!feeder.has_input());
+        assert_eq!(writer.push_bytes(b"abcdef"), 4);
+        assert!(writer.is_full());
+        assert!(matches!(writer.push_byte(b'x'), Err(PushError::Full)));
+
+        assert_eq!(feeder.next_input(), Some(b'a'));
+        assert_eq!(feeder.next_input(), Some(b'b'));
+        assert_eq!(writer.spare_capacity(), 2);
+        assert_eq!(writer.push_bytes(b"ef"), 2);
+
+        let mut read = Vec::new();
+        while let Some(b) = feeder.next_input() {
+            read.push(b);
+        }
+        assert_eq!(read, b"cdef");
+        assert!(!feeder.is_done());
+
+        writer.push_byte(b'g').unwrap();
+        writer.done();
+        assert!(!feeder.is_done());
+        assert!(feeder.is_writer_dropped());
+        assert_eq!(feeder.next_input(), Some(b'g'));
+        assert!(feeder.is_done());
+    }
+
+    /// Test that a JSON text can be pushed on one thread and parsed on
+    /// another one
+    #[test]
+    fn threads() {
+        let json = format!(
+            "[{}]",
+            (0..10000)
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let (mut writer, feeder) = SpscJsonFeeder::with_capacity(7);
+
+        let producer = thread::spawn(move || {
+            let mut json = json.as_bytes();
+            while !json.is_empty() {
+                let n = writer.push_bytes(json);
+                json = &json[n..];
+                if n == 0 {
+                    thread::yield_now();
+                }
+            }
+            writer.done();
+        });
+
+        let mut parser = JsonParser::new(feeder);
+        let mut expected = 0;
+        while let Some(e) = parser.next_event().unwrap() {
+            match e {
+                JsonEvent::NeedMoreInput => thread::yield_now(),
+                JsonEvent::ValueInt => {
+                    assert_eq!(parser.current_int::<i64>().unwrap(), expected);
+                    expected += 1;
+                }
+                _ => {}
+            }
+        }
+        producer.join().unwrap();
+        assert_eq!(expected, 10000);
+    }
+}
@@ -0,0 +1,98 @@
+use super::{FillError, FillJsonFeeder, JsonFeeder, SliceJsonFeeder};
+
+/// A [`JsonFeeder`] that feeds the [`JsonParser`](crate::JsonParser) from a
+/// string slice. Since the input is known to be valid UTF-8, the parser does
+/// not have to validate strings again when they are read (unless they
+/// contain escape sequences). Prefer this over [`SliceJsonFeeder`] if your
+/// input is a [`str`] or a [`String`].
+pub struct StrJsonFeeder<'a> {
+    input: &'a str,
+    inner: SliceJsonFeeder<'a>,
+}
+
+impl<'a> StrJsonFeeder<'a> {
+    /// Create a new feeder that wraps around the given string slice
+    pub fn new(input: &'a str) -> Self {
+        StrJsonFeeder {
+            input,
+            inner: SliceJsonFeeder::new(input.as_bytes()),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for StrJsonFeeder<'a> {
+    fn from(input: &'a str) -> Self {
+        StrJsonFeeder::new(input)
+    }
+}
+
+impl<'a> From<&'a String> for StrJsonFeeder<'a> {
+    fn from(input: &'a String) -> Self {
+        StrJsonFeeder::new(input)
+    }
+}
+
+impl JsonFeeder for StrJsonFeeder<'_> {
+    fn has_input(&self) -> bool {
+        self.inner.has_input()
+    }
+
+    fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        self.inner.next_input()
+    }
+
+    fn consumed_input(&self) -> Option<&[u8]> {
+        self.inner.consumed_input()
+    }
+
+    fn consumed_str(&self) -> Option<&str> {
+        // the consumed bytes may end in the middle of a character
+        let mut end = self.inner.consumed_slice().len();
+        while !self.input.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(&self.input[..end])
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        self.inner.peek_input()
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.inner.consume_input(n)
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        self.inner.rewind_input(n)
+    }
+}
+
+impl FillJsonFeeder for StrJsonFeeder<'_> {
+    fn fill(&mut self) -> Result<(), FillError> {
+        // the string is always complete
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::feeder::JsonFeeder;
+
+    use super::StrJsonFeeder;
+
+    #[test]
+    fn consume_all() {
+        let mut feeder = StrJsonFeeder::new("B\u{f6}");
+        assert_eq!(feeder.consumed_str(), Some(""));
+        assert_eq!(feeder.next_input(), Some(b'B'));
+        assert_eq!(feeder.next_input(), Some(0xc3));
+        assert_eq!(feeder.consumed_str(), Some("B"));
+        assert_eq!(feeder.next_input(), Some(0xb6));
+        assert_eq!(feeder.consumed_str(), Some("B\u{f6}"));
+        assert!(feeder.is_done());
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{ErrorKind, Read},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::cancel::CancellationToken;
+
+use super::{FillError, FillJsonFeeder, JsonFeeder};
+
+/// The default size of the buffer of a [`TailJsonFeeder`]
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// The default time a [`TailJsonFeeder`] waits before it checks again if
+/// more input is available
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A [`JsonFeeder`] that follows a growing file like `tail -f`. When it
+/// reaches the current end of the file, it does not signal the end of the
+/// input but waits until more bytes have been appended. This makes it
+/// possible to continuously parse append-only logs (e.g. NDJSON written by
+/// another process) in streaming mode (see
+/// [`JsonParserOptionsBuilder::with_streaming()`](crate::options::JsonParserOptionsBuilder::with_streaming())).
+///
+/// The feeder checks for new input in regular intervals (see
+/// [`Self::with_poll_interval()`]). It only reports the end of the input if
+/// the token passed to [`Self::with_stop_token()`] has been cancelled or if
+/// no new input has arrived within the time given to
+/// [`Self::with_idle_timeout()`]. In both cases, everything that has been
+/// appended so far is read first. Truncated or rotated files are not
+/// detected.
+///
+/// ```
+/// use std::fs::{self, File, OpenOptions};
+/// use std::io::Write;
+/// use std::time::Duration;
+///
+/// use actson::feeder::TailJsonFeeder;
+/// use actson::options::JsonParserOptionsBuilder;
+/// use actson::{JsonEvent, JsonParser};
+///
+/// let path = std::env::temp_dir().join("actson-tail-doctest.ndjson");
+/// fs::write(&path, "{\"level\": \"info\"}\n").unwrap();
+///
+/// let feeder = TailJsonFeeder::new(File::open(&path).unwrap())
+///     .with_poll_interval(Duration::from_millis(10))
+///     .with_idle_timeout(Some(Duration::from_millis(200)));
+/// let mut parser = JsonParser::new_with_options(
+///     feeder,
+///     JsonParserOptionsBuilder::default().with_streaming(true).build(),
+/// );
+///
+/// let mut records = 0;
+/// while let Some(e) = parser.next_event().unwrap() {
+///     match e {
+///         JsonEvent::NeedMoreInput => parser.feeder.fill_buf().unwrap(),
+///         JsonEvent::EndObject => {
+///             records += 1;
+///             if records == 1 {
+///                 // another process appends to the file
+///                 let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+///                 file.write_all(b"{\"level\": \"warn\"}\n").unwrap();
+///             }
+///         }
+///         _ => {}
+///     }
+/// }
+///
+/// assert_eq!(records, 2);
+/// fs::remove_file(&path).unwrap();
+/// ```
+pub struct TailJsonFeeder<R> {
+    reader: R,
+    buf: Box<[u8]>,
+    len: usize,
+    pos: usize,
+    poll_interval: Duration,
+    idle_timeout: Option<Duration>,
+    stop_token: Option<CancellationToken>,
+    done: bool,
+}
+
+impl<R> TailJsonFeeder<R>
+where
+    R: Read,
+{
+    /// Create a new feeder that follows the given reader (typically a
+    /// [`File`](std::fs::File))
+    pub fn new(reader: R) -> Self {
+        Self::with_capacity(reader, DEFAULT_CAPACITY)
+    }
+
+    /// Create a new feeder that follows the given reader and reads at most
+    /// `capacity` bytes at once
+    pub fn with_capacity(reader: R, capacity: usize) -> Self {
+        TailJsonFeeder {
+            reader,
+            buf: vec![0; capacity.max(1)].into_boxed_slice(),
+            len: 0,
+            pos: 0,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            idle_timeout: None,
+            stop_token: None,
+            done: false,
+        }
+    }
+
+    /// Set the time to wait before checking again if more input is
+    /// available (default: 100 ms)
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set the time after which the end of the input is reported if no new
+    /// input has arrived. `None` (the default) means to wait forever.
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Report the end of the input as soon as the given token has been
+    /// cancelled and everything appended so far has been read
+    pub fn with_stop_token(mut self, stop_token: CancellationToken) -> Self {
+        self.stop_token = Some(stop_token);
+        self
+    }
+
+    /// Fill the feeder's internal buffer. Blocks until more input is
+    /// available or the end of the input has been reached (see
+    /// [`Self::with_stop_token()`] and [`Self::with_idle_timeout()`]).
+    pub fn fill_buf(&mut self) -> Result<(), FillError> {
+        if self.done || self.has_input() {
+            return Ok(());
+        }
+        let start = Instant::now();
+        loop {
+            match self.reader.read(&mut self.buf) {
+                Ok(0) => {}
+                Ok(n) => {
+                    self.len = n;
+                    self.pos = 0;
+                    return Ok(());
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+
+            let stopped = self.stop_token.as_ref().is_some_and(|t| t.is_cancelled());
+            let idle = self.idle_timeout.is_some_and(|t| start.elapsed() >= t);
+            if stopped || idle {
+                self.done = true;
+                return Ok(());
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Consume the feeder and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> JsonFeeder for TailJsonFeeder<R>
+where
+    R: Read,
+{
+    fn has_input(&self) -> bool {
+        self.pos < self.len
+    }
+
+    fn is_done(&self) -> bool {
+        self.done && !self.has_input()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        if self.pos < self.len {
+            let r = Some(self.buf[self.pos]);
+            self.pos += 1;
+            r
+        } else {
+            None
+        }
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.buf[self.pos..self.len]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        if n > self.pos {
+            return false;
+        }
+        self.pos -= n;
+        true
+    }
+}
+
+impl<R> FillJsonFeeder for TailJsonFeeder<R>
+where
+    R: Read,
+{
+    fn fill(&mut self) -> Result<(), FillError> {
+        self.fill_buf()
+    }
+}
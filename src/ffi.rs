@@ -0,0 +1,363 @@
+//! A C API for embedding the incremental parser in C/C++ applications and
+//! other language runtimes.
+//!
+//! Build the crate as a dynamic or static library with the `ffi` feature
+//! enabled (e.g. `cargo rustc --release --features ffi --crate-type cdylib`)
+//! and include `include/actson.h`. A parser is created with
+//! [`actson_parser_new()`] and must be released with
+//! [`actson_parser_free()`]. Input is pushed with [`actson_parser_feed()`]
+//! and [`actson_parser_done()`] the same way as with a
+//! [`PushJsonFeeder`]:
+//!
+//! ```c
+//! ActsonParser *parser = actson_parser_new();
+//! size_t pushed = 0;
+//! int event;
+//! do {
+//!     event = actson_parser_next_event(parser);
+//!     if (event == ACTSON_EVENT_NEED_MORE_INPUT) {
+//!         pushed += actson_parser_feed(parser, json + pushed, len - pushed);
+//!         if (pushed == len) {
+//!             actson_parser_done(parser);
+//!         }
+//!     } else if (event == ACTSON_EVENT_VALUE_STRING) {
+//!         size_t n;
+//!         const uint8_t *s = actson_parser_current_str(parser, &n);
+//!         printf("%.*s\n", (int)n, s);
+//!     }
+//! } while (event >= 0);
+//! if (event == ACTSON_ERROR) {
+//!     fprintf(stderr, "%s\n", actson_parser_error(parser));
+//! }
+//! actson_parser_free(parser);
+//! ```
+//!
+//! [`actson_parser_next_event()`] returns the discriminant of a
+//! [`JsonEvent`](crate::JsonEvent), [`ACTSON_END`] at the end of the JSON text, or
+//! [`ACTSON_ERROR`] if the JSON text is invalid. All pointers returned by
+//! the API are owned by the parser and remain valid until the next call
+//! that takes the parser.
+
+use std::ffi::{c_char, c_int, CString};
+use std::ptr;
+use std::slice;
+
+use crate::feeder::PushJsonFeeder;
+use crate::JsonParser;
+
+/// Returned by [`actson_parser_next_event()`] when the end of the JSON text
+/// has been reached
+pub const ACTSON_END: c_int = -1;
+
+/// Returned by [`actson_parser_next_event()`] when the JSON text is invalid.
+/// Call [`actson_parser_error()`] to get a description of the error.
+pub const ACTSON_ERROR: c_int = -2;
+
+/// An incremental JSON parser with a push-based feeder. Opaque to C.
+pub struct ActsonParser {
+    parser: JsonParser<PushJsonFeeder>,
+
+    /// The description of the most recent error
+    error: Option<CString>,
+}
+
+impl ActsonParser {
+    fn set_error(&mut self, err: impl ToString) {
+        // error messages never contain NUL bytes
+        self.error = CString::new(err.to_string()).ok();
+    }
+}
+
+/// Get a mutable reference to the parser behind the given pointer
+///
+/// # Safety
+///
+/// `parser` must have been returned by [`actson_parser_new()`] and must not
+/// have been freed.
+unsafe fn parser_mut<'a>(parser: *mut ActsonParser) -> &'a mut ActsonParser {
+    debug_assert!(!parser.is_null());
+    &mut *parser
+}
+
+/// Create a new parser. The parser must be released with
+/// [`actson_parser_free()`].
+#[no_mangle]
+pub extern "C" fn actson_parser_new() -> *mut ActsonParser {
+    Box::into_raw(Box::new(ActsonParser {
+        parser: JsonParser::new(PushJsonFeeder::new()),
+        error: None,
+    }))
+}
+
+/// Release a parser. Does nothing if `parser` is `NULL`.
+///
+/// # Safety
+///
+/// `parser` must be `NULL` or must have been returned by
+/// [`actson_parser_new()`] and must not have been freed before.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_free(parser: *mut ActsonParser) {
+    if !parser.is_null() {
+        drop(Box::from_raw(parser));
+    }
+}
+
+/// Push up to `len` bytes from `buf` into the parser. Returns the number of
+/// bytes consumed, which can be less than `len` (or 0) if the parser does
+/// not accept more input at the moment. In this case, call
+/// [`actson_parser_next_event()`] until it returns
+/// [`JsonEvent::NeedMoreInput`](crate::JsonEvent::NeedMoreInput) and then push the remaining bytes.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser and `buf` must point to at least `len`
+/// readable bytes (it may be `NULL` if `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_feed(
+    parser: *mut ActsonParser,
+    buf: *const u8,
+    len: usize,
+) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let buf = slice::from_raw_parts(buf, len);
+    parser_mut(parser).parser.feeder.push_bytes(buf)
+}
+
+/// Returns `true` if the parser does not accept more input at the moment
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_is_full(parser: *mut ActsonParser) -> bool {
+    parser_mut(parser).parser.feeder.is_full()
+}
+
+/// Indicate that the end of the JSON text has been reached and that there
+/// is no more input to push
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_done(parser: *mut ActsonParser) {
+    parser_mut(parser).parser.feeder.done();
+}
+
+/// Get the next event. Returns the discriminant of a [`JsonEvent`](crate::JsonEvent),
+/// [`ACTSON_END`] if the end of the JSON text has been reached, or
+/// [`ACTSON_ERROR`] if the JSON text is invalid.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_next_event(parser: *mut ActsonParser) -> c_int {
+    let p = parser_mut(parser);
+    match p.parser.next_event() {
+        Ok(Some(event)) => event as c_int,
+        Ok(None) => ACTSON_END,
+        Err(e) => {
+            p.set_error(e);
+            ACTSON_ERROR
+        }
+    }
+}
+
+/// Get a NUL-terminated description of the most recent error or `NULL` if
+/// no error has happened
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_error(parser: *mut ActsonParser) -> *const c_char {
+    match &parser_mut(parser).error {
+        Some(e) => e.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Get the value of the current field name or string as UTF-8 bytes. Stores
+/// the number of bytes in `len` and returns a pointer to them. The bytes
+/// are not NUL-terminated. Returns `NULL` if the value is not valid UTF-8.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser and `len` must point to a writable
+/// `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_current_str(
+    parser: *mut ActsonParser,
+    len: *mut usize,
+) -> *const u8 {
+    let p = parser_mut(parser);
+    match p.parser.current_str() {
+        Ok(s) => {
+            *len = s.len();
+            s.as_ptr()
+        }
+        Err(e) => {
+            p.set_error(e);
+            *len = 0;
+            ptr::null()
+        }
+    }
+}
+
+/// Get the value of the current integer and store it in `value`. Returns
+/// `false` if the value does not fit into a 64-bit signed integer.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser and `value` must point to a writable
+/// `int64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_current_int(
+    parser: *mut ActsonParser,
+    value: *mut i64,
+) -> bool {
+    let p = parser_mut(parser);
+    match p.parser.current_int::<i64>() {
+        Ok(i) => {
+            *value = i;
+            true
+        }
+        Err(e) => {
+            p.set_error(e);
+            false
+        }
+    }
+}
+
+/// Get the value of the current number as a floating point number and store
+/// it in `value`. Returns `false` if the value could not be converted.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser and `value` must point to a writable
+/// `double`.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_current_float(
+    parser: *mut ActsonParser,
+    value: *mut f64,
+) -> bool {
+    let p = parser_mut(parser);
+    match p.parser.current_float() {
+        Ok(f) => {
+            *value = f;
+            true
+        }
+        Err(e) => {
+            p.set_error(e);
+            false
+        }
+    }
+}
+
+/// Store the byte offsets of the start (inclusive) and end (exclusive) of
+/// the current event in the JSON text in `start` and `end` (see
+/// [`JsonParser::current_span()`])
+///
+/// # Safety
+///
+/// `parser` must be a valid parser and `start` and `end` must point to
+/// writable `size_t`s.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_current_span(
+    parser: *mut ActsonParser,
+    start: *mut usize,
+    end: *mut usize,
+) {
+    let span = parser_mut(parser).parser.current_span();
+    *start = span.start;
+    *end = span.end;
+}
+
+/// Get the number of bytes the parser has consumed so far
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn actson_parser_parsed_bytes(parser: *mut ActsonParser) -> usize {
+    parser_mut(parser).parser.parsed_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::CStr;
+
+    use super::*;
+    use crate::JsonEvent;
+
+    /// Drive a parser through the C API like a C application would
+    #[test]
+    fn parse() {
+        let json = br#"{"a": [1, 2.5, "x"]} x"#;
+        let mut events = Vec::new();
+        let mut values = Vec::new();
+        unsafe {
+            let parser = actson_parser_new();
+            let mut pushed = 0;
+            let mut event;
+            loop {
+                event = actson_parser_next_event(parser);
+                if event < 0 {
+                    break;
+                }
+                events.push(event);
+                if event == JsonEvent::NeedMoreInput as c_int {
+                    pushed +=
+                        actson_parser_feed(parser, json[pushed..].as_ptr(), json.len() - pushed);
+                    if pushed == json.len() {
+                        actson_parser_done(parser);
+                    }
+                } else if event == JsonEvent::ValueString as c_int {
+                    let mut len = 0;
+                    let s = actson_parser_current_str(parser, &mut len);
+                    values
+                        .push(String::from_utf8_lossy(slice::from_raw_parts(s, len)).into_owned());
+                } else if event == JsonEvent::ValueInt as c_int {
+                    let mut i = 0;
+                    assert!(actson_parser_current_int(parser, &mut i));
+                    values.push(i.to_string());
+                } else if event == JsonEvent::ValueFloat as c_int {
+                    let mut f = 0.0;
+                    assert!(actson_parser_current_float(parser, &mut f));
+                    values.push(f.to_string());
+                    let (mut start, mut end) = (0, 0);
+                    actson_parser_current_span(parser, &mut start, &mut end);
+                    assert_eq!(&json[start..end], b"2.5");
+                }
+            }
+
+            assert_eq!(event, ACTSON_ERROR);
+            let err = CStr::from_ptr(actson_parser_error(parser));
+            assert_eq!(
+                err.to_str().unwrap(),
+                "syntax error: the parsed text is not valid JSON"
+            );
+            actson_parser_free(parser);
+        }
+
+        assert_eq!(
+            events,
+            [
+                JsonEvent::NeedMoreInput,
+                JsonEvent::StartObject,
+                JsonEvent::FieldName,
+                JsonEvent::StartArray,
+                JsonEvent::ValueInt,
+                JsonEvent::ValueFloat,
+                JsonEvent::ValueString,
+                JsonEvent::EndArray,
+                JsonEvent::EndObject,
+            ]
+            .map(|e| e as c_int)
+        );
+        assert_eq!(values, ["1", "2.5", "x"]);
+    }
+}
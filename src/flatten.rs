@@ -0,0 +1,221 @@
+//! Flatten a JSON text into pairs of JSON Pointers and scalar values.
+//!
+//! [`Flattener`] is a [`JsonEventSink`] that calls a function for every
+//! leaf of a JSON text, in document order, with the JSON Pointer to the
+//! leaf and its value. Leaves are strings, numbers, booleans, `null`, and
+//! empty objects and arrays (so that no information is lost). Only the
+//! current path is kept in memory, which makes flattening suitable as a
+//! building block for indexing, diffing, or exporting deeply nested
+//! documents into columns.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::flatten::flatten;
+//! use actson::value::JsonValue;
+//!
+//! let json = br#"{"user": {"name": "Elvis", "albums": [1956, 1957]}, "tags": []}"#;
+//! let pairs = flatten(SliceJsonFeeder::new(json)).unwrap();
+//! assert_eq!(
+//!     pairs,
+//!     vec![
+//!         ("/user/name".to_string(), JsonValue::String("Elvis".to_string())),
+//!         ("/user/albums/0".to_string(), JsonValue::Int(1956)),
+//!         ("/user/albums/1".to_string(), JsonValue::Int(1957)),
+//!         ("/tags".to_string(), JsonValue::Array(vec![])),
+//!     ]
+//! );
+//! ```
+
+use crate::{
+    feeder::{FillJsonFeeder, JsonFeeder},
+    parser::NextValueError,
+    sink::{parse_with, JsonEventSink, ParseWithError},
+    storage::ParserStorage,
+    value::{JsonObject, JsonValue, JsonValueBuilder},
+    JsonEvent, JsonParser,
+};
+
+/// An object or array that is currently open
+struct Frame {
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The index of the next element if the container is an array
+    next_index: usize,
+
+    /// The length of the JSON Pointer to the container
+    path_len: usize,
+
+    /// `true` if the container does not have any members or elements yet
+    empty: bool,
+}
+
+/// A [`JsonEventSink`] that passes the JSON Pointer and the value of each
+/// leaf to a function. See the [module documentation](self).
+pub struct Flattener<F> {
+    callback: F,
+
+    /// The JSON Pointer to the current value
+    path: String,
+
+    /// The containers that are currently open
+    stack: Vec<Frame>,
+
+    /// Converts scalar events to values
+    builder: JsonValueBuilder,
+}
+
+impl<F> Flattener<F>
+where
+    F: FnMut(&str, JsonValue),
+{
+    /// Create a new sink that passes each leaf to the given function
+    pub fn new(callback: F) -> Self {
+        Flattener {
+            callback,
+            path: String::new(),
+            stack: Vec::new(),
+            builder: JsonValueBuilder::new(),
+        }
+    }
+
+    /// Consume the sink and return the function
+    pub fn into_inner(self) -> F {
+        self.callback
+    }
+
+    /// Append the reference token of the next element to the path if the
+    /// current container is an array
+    fn enter_element(&mut self) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.empty = false;
+            if frame.is_array {
+                self.path.truncate(frame.path_len);
+                self.path.push('/');
+                self.path.push_str(&frame.next_index.to_string());
+                frame.next_index += 1;
+            }
+        }
+    }
+}
+
+impl<F> JsonEventSink for Flattener<F>
+where
+    F: FnMut(&str, JsonValue),
+{
+    type Error = NextValueError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => {}
+
+            JsonEvent::FieldName => {
+                if let Some(frame) = self.stack.last() {
+                    self.path.truncate(frame.path_len);
+                    self.path.push('/');
+                    let key = parser.current_str()?;
+                    self.path
+                        .push_str(&key.replace('~', "~0").replace('/', "~1"));
+                }
+            }
+
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                self.enter_element();
+                self.stack.push(Frame {
+                    is_array: event == JsonEvent::StartArray,
+                    next_index: 0,
+                    path_len: self.path.len(),
+                    empty: true,
+                });
+            }
+
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                if let Some(frame) = self.stack.pop() {
+                    self.path.truncate(frame.path_len);
+                    if frame.empty {
+                        let value = if frame.is_array {
+                            JsonValue::Array(Vec::new())
+                        } else {
+                            JsonValue::Object(JsonObject::new())
+                        };
+                        (self.callback)(&self.path, value);
+                    }
+                }
+            }
+
+            _ => {
+                self.enter_element();
+                self.builder.on_event(event, parser)?;
+                if let Some(value) = self.builder.take() {
+                    (self.callback)(&self.path, value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse the JSON text provided by the given feeder and return the JSON
+/// Pointers and values of all its leaves in document order. See the
+/// [module documentation](self).
+pub fn flatten<T>(feeder: T) -> Result<Vec<(String, JsonValue)>, ParseWithError<NextValueError>>
+where
+    T: FillJsonFeeder,
+{
+    let mut pairs = Vec::new();
+    parse_with(
+        feeder,
+        &mut Flattener::new(|path: &str, value| pairs.push((path.to_string(), value))),
+    )?;
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, sink::parse_with_parser,
+        value::JsonValue, JsonParser,
+    };
+
+    use super::Flattener;
+
+    /// Test that keys are escaped, that empty containers and scalars at
+    /// the top level are leaves, and that each top-level value starts at
+    /// the root again
+    #[test]
+    fn leaves() {
+        let json = br#"{"a/b": [[], {"~": {}}, [null]], "c": 1.5} "x" [true]"#;
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut pairs = Vec::new();
+        let mut flattener =
+            Flattener::new(|path: &str, value: JsonValue| pairs.push((path.to_string(), value)));
+        parse_with_parser(&mut parser, &mut flattener).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("/a~1b/0".to_string(), JsonValue::Array(vec![])),
+                (
+                    "/a~1b/1/~0".to_string(),
+                    JsonValue::Object(Default::default())
+                ),
+                ("/a~1b/2/0".to_string(), JsonValue::Null),
+                ("/c".to_string(), JsonValue::Float(1.5)),
+                ("".to_string(), JsonValue::String("x".to_string())),
+                ("/0".to_string(), JsonValue::Bool(true)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Compute a deterministic digest of the structure of a JSON text.
+//!
+//! A [`StructuralHasher`] is a [`JsonEventSink`] that computes a 64-bit
+//! digest of a value in a single pass without building a document or
+//! serializing it again. The digest only depends on the canonical form of
+//! the value as defined by [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785):
+//! white space, the order of object members, escape sequences, and the
+//! notation of numbers (e.g. `1`, `1.0`, and `1e0`) do not matter. This
+//! makes it suitable for change detection and content addressing of large
+//! documents.
+//!
+//! Only the digests of the members of the objects that are currently open
+//! are kept in memory, so the memory usage depends on the size of the
+//! largest object and not on the size of the document. Note that the digest
+//! is not the hash of the serialized canonical form.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::hash::StructuralHasher;
+//! use actson::sink::parse_with;
+//!
+//! let digest = |json: &[u8]| {
+//!     let mut hasher = StructuralHasher::new();
+//!     parse_with(SliceJsonFeeder::new(json), &mut hasher).unwrap();
+//!     hasher.digest().unwrap()
+//! };
+//!
+//! assert_eq!(
+//!     digest(br#"{"name": "Elvis", "albums": [1956, 1957]}"#),
+//!     digest(br#"{"albums":[1.956e3,1957.0],"name":"Elvis"}"#),
+//! );
+//! assert_ne!(
+//!     digest(br#"{"albums": [1956, 1957]}"#),
+//!     digest(br#"{"albums": [1957, 1956]}"#),
+//! );
+//! ```
+
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+use thiserror::Error;
+
+use crate::{
+    emitter::{cmp_utf16, write_es_number},
+    feeder::JsonFeeder,
+    parser::{InvalidFloatValueError, InvalidStringValueError},
+    sink::JsonEventSink,
+    storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+/// A 64-bit FNV-1a [`Hasher`]. Unlike the standard library's default
+/// hasher, its output is guaranteed to stay the same across releases and
+/// platforms, so digests can be stored.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    // integers are written in a fixed byte order, so digests do not depend
+    // on the platform
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// An error that can happen when computing a digest
+#[derive(Error, Debug)]
+pub enum HashError {
+    /// A string in the JSON text is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+
+    /// A number in the JSON text is invalid
+    #[error(transparent)]
+    InvalidFloatValue(#[from] InvalidFloatValueError),
+}
+
+/// Tags that separate the types of values in the digest
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_NUMBER: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_ARRAY: u8 = 5;
+const TAG_OBJECT: u8 = 6;
+
+/// An object or array that is currently open
+enum Frame<H> {
+    /// An array and the hasher its elements' digests are written to
+    Array(H, usize),
+
+    /// An object, the keys and digests of its members, and the most recent
+    /// field name
+    Object(Vec<(String, u64)>, Option<String>),
+}
+
+/// Computes the digest of a JSON value. See the [module
+/// documentation](self).
+///
+/// The hasher can be fed the events of more than one value (e.g. in
+/// streaming mode). [`Self::digest()`] always returns the digest of the
+/// most recent complete value.
+pub struct StructuralHasher<B = BuildHasherDefault<Fnv1aHasher>>
+where
+    B: BuildHasher,
+{
+    build_hasher: B,
+    stack: Vec<Frame<B::Hasher>>,
+    digest: Option<u64>,
+
+    /// A buffer for the canonical form of numbers
+    buf: Vec<u8>,
+}
+
+impl StructuralHasher {
+    /// Create a new hasher that uses [`Fnv1aHasher`]
+    pub fn new() -> Self {
+        Self::with_hasher(BuildHasherDefault::default())
+    }
+}
+
+impl Default for StructuralHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> StructuralHasher<B>
+where
+    B: BuildHasher,
+{
+    /// Create a new hasher that uses hashers built by the given
+    /// [`BuildHasher`]. The digests are only deterministic if the built
+    /// hashers are.
+    pub fn with_hasher(build_hasher: B) -> Self {
+        StructuralHasher {
+            build_hasher,
+            stack: Vec::new(),
+            digest: None,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get the digest of the most recent complete value or `None` if no
+    /// value has been completed yet
+    pub fn digest(&self) -> Option<u64> {
+        self.digest
+    }
+
+    /// Compute the digest of a scalar value from its tag and its canonical
+    /// bytes
+    fn scalar(&self, tag: u8, bytes: &[u8]) -> u64 {
+        let mut hasher = self.build_hasher.build_hasher();
+        hasher.write_u8(tag);
+        hasher.write_usize(bytes.len());
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    /// Add the digest of a complete value to the current container or make
+    /// it the digest of the document
+    fn push(&mut self, digest: u64) {
+        match self.stack.last_mut() {
+            Some(Frame::Array(hasher, len)) => {
+                hasher.write_u64(digest);
+                *len += 1;
+            }
+            Some(Frame::Object(members, key)) => {
+                members.push((key.take().unwrap_or_default(), digest));
+            }
+            None => self.digest = Some(digest),
+        }
+    }
+}
+
+impl<B> JsonEventSink for StructuralHasher<B>
+where
+    B: BuildHasher,
+{
+    type Error = HashError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        let digest = match event {
+            JsonEvent::NeedMoreInput => return Ok(()),
+
+            JsonEvent::StartObject => {
+                self.stack.push(Frame::Object(Vec::new(), None));
+                return Ok(());
+            }
+
+            JsonEvent::StartArray => {
+                let mut hasher = self.build_hasher.build_hasher();
+                hasher.write_u8(TAG_ARRAY);
+                self.stack.push(Frame::Array(hasher, 0));
+                return Ok(());
+            }
+
+            JsonEvent::FieldName => {
+                if let Some(Frame::Object(_, key)) = self.stack.last_mut() {
+                    *key = Some(parser.current_str()?.to_string());
+                }
+                return Ok(());
+            }
+
+            JsonEvent::EndObject => {
+                let Some(Frame::Object(mut members, _)) = self.stack.pop() else {
+                    return Ok(());
+                };
+                members.sort_by(|a, b| cmp_utf16(&a.0, &b.0));
+                let mut hasher = self.build_hasher.build_hasher();
+                hasher.write_u8(TAG_OBJECT);
+                hasher.write_usize(members.len());
+                for (key, digest) in members {
+                    hasher.write_usize(key.len());
+                    hasher.write(key.as_bytes());
+                    hasher.write_u64(digest);
+                }
+                hasher.finish()
+            }
+
+            JsonEvent::EndArray => {
+                let Some(Frame::Array(mut hasher, len)) = self.stack.pop() else {
+                    return Ok(());
+                };
+                hasher.write_usize(len);
+                hasher.finish()
+            }
+
+            JsonEvent::ValueString => self.scalar(TAG_STRING, parser.current_str()?.as_bytes()),
+
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let value = parser.current_float()?;
+                self.buf.clear();
+                if value.is_finite() {
+                    write_es_number(value, &mut self.buf);
+                } else {
+                    // numbers outside the range of an f64 have no canonical
+                    // form, so fall back to the literal
+                    self.buf
+                        .extend_from_slice(parser.current_raw_str()?.as_bytes());
+                }
+                self.scalar(TAG_NUMBER, &self.buf)
+            }
+
+            JsonEvent::ValueTrue => self.scalar(TAG_TRUE, &[]),
+            JsonEvent::ValueFalse => self.scalar(TAG_FALSE, &[]),
+            JsonEvent::ValueNull => self.scalar(TAG_NULL, &[]),
+        };
+        self.push(digest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::hash::{BuildHasherDefault, DefaultHasher};
+
+    use crate::{feeder::SliceJsonFeeder, sink::parse_with};
+
+    use super::StructuralHasher;
+
+    fn digest(json: &str) -> u64 {
+        let mut hasher = StructuralHasher::new();
+        parse_with(SliceJsonFeeder::new(json.as_bytes()), &mut hasher).unwrap();
+        hasher.digest().unwrap()
+    }
+
+    /// Test that the digest only depends on the canonical form
+    #[test]
+    fn canonical() {
+        assert_eq!(
+            digest(r#"{"b": [1, -0, 1e21, "é"], "a": {"y": null, "x": true}}"#),
+            digest(r#"{"a":{"x":true,"y":null},"b":[1.0,0,1000000000000000000000,"é"]}"#)
+        );
+        // keys are sorted by UTF-16 code units like in RFC 8785
+        assert_eq!(
+            digest("{\"\u{ff21}\": 1, \"\u{1f600}\": 2}"),
+            digest("{\"\u{1f600}\": 2, \"\u{ff21}\": 1}")
+        );
+    }
+
+    /// Test that different values have different digests
+    #[test]
+    fn different() {
+        let values = [
+            "null",
+            "false",
+            "true",
+            "0",
+            "1",
+            r#""1""#,
+            r#""""#,
+            "[]",
+            "{}",
+            "[[]]",
+            "[null]",
+            "[1, 2]",
+            "[2, 1]",
+            "[[1], 2]",
+            "[1, [2]]",
+            r#"{"a": 1}"#,
+            r#"{"a": "1"}"#,
+            r#"{"b": 1}"#,
+            r#"{"a": {}}"#,
+            r#"{"a": [], "b": []}"#,
+            r#"{"ab": [], "": []}"#,
+        ];
+        let mut digests = values.iter().map(|v| digest(v)).collect::<Vec<_>>();
+        digests.sort();
+        digests.dedup();
+        assert_eq!(digests.len(), values.len());
+    }
+
+    /// Test that digests are stable and that other hashers can be used
+    #[test]
+    fn hashers() {
+        assert_eq!(digest("[1]"), 0x6947_834c_04fc_4051);
+
+        let mut hasher =
+            StructuralHasher::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+        parse_with(SliceJsonFeeder::new(b"[1, 2]"), &mut hasher).unwrap();
+        assert!(hasher.digest().is_some());
+    }
+}
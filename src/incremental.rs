@@ -0,0 +1,200 @@
+//! Keep the events of a JSON text up to date while it is being edited.
+//!
+//! An [`EventIndex`] holds all events of a JSON text together with their
+//! [`Span`]s. When a range of the text is replaced, [`EventIndex::update()`]
+//! only re-parses the smallest object or array that encloses the edit and
+//! splices the new events into the index. The spans of all events after
+//! the edit are shifted accordingly. This keeps editors and language
+//! servers responsive on large files, where parsing the whole text on every
+//! keystroke would be too slow.
+//!
+//! ```
+//! use actson::incremental::EventIndex;
+//! use actson::{OwnedJsonEvent, Span};
+//!
+//! let old = br#"{"a": [1, 2], "b": true}"#;
+//! let mut index = EventIndex::build(old).unwrap();
+//!
+//! // replace `2` with `20, 30`
+//! let new = br#"{"a": [1, 20, 30], "b": true}"#;
+//! let changed = index.update(new, Span::new(10, 11), 6).unwrap();
+//!
+//! // only the array has been parsed again
+//! assert_eq!(changed, 2..7);
+//! assert_eq!(index.events()[5].event, OwnedJsonEvent::ValueInt(30));
+//! assert_eq!(index.events()[8].span, Span::new(24, 28));
+//! ```
+
+use std::ops::Range;
+
+use crate::{feeder::SliceJsonFeeder, parser::NextValueError, JsonParser, OwnedJsonEvent, Span};
+
+/// An event and its position in the JSON text
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedEvent {
+    /// The event
+    pub event: OwnedJsonEvent,
+
+    /// The position of the event in the JSON text (see
+    /// [`JsonParser::current_span()`])
+    pub span: Span,
+}
+
+/// The events of a JSON text that can be updated incrementally. See the
+/// [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct EventIndex {
+    events: Vec<IndexedEvent>,
+}
+
+impl EventIndex {
+    /// Parse the given JSON text and record all of its events
+    pub fn build(json: &[u8]) -> Result<Self, NextValueError> {
+        Ok(EventIndex {
+            events: parse_events(json, 0)?,
+        })
+    }
+
+    /// Get all events in document order
+    pub fn events(&self) -> &[IndexedEvent] {
+        &self.events
+    }
+
+    /// Update the index after the bytes in `old` (a range of the text the
+    /// index currently describes) have been replaced by `new_len` bytes.
+    /// `json` is the complete text after the edit.
+    ///
+    /// The method re-parses the innermost object or array whose brackets
+    /// enclose the edit. If that does not result in a single valid value
+    /// of the same length, the enclosing containers are tried one after the
+    /// other and, finally, the whole text is parsed again. Returns the
+    /// range of events in [`Self::events()`] that have been replaced. All
+    /// other events are kept, but their spans may have been shifted.
+    ///
+    /// If the edited text is not valid JSON, the parser's error is returned
+    /// and the index is left unchanged.
+    pub fn update(
+        &mut self,
+        json: &[u8],
+        old: Span,
+        new_len: usize,
+    ) -> Result<Range<usize>, NextValueError> {
+        let delta = new_len as isize - old.len() as isize;
+
+        for (first, last) in self.enclosing(old) {
+            let start = self.events[first].span.start;
+            let end = self.events[last].span.end.wrapping_add_signed(delta);
+            let Some(slice) = json.get(start..end) else {
+                continue;
+            };
+            let Ok(events) = parse_events(slice, start) else {
+                continue;
+            };
+            for e in &mut self.events[last + 1..] {
+                e.span.start = e.span.start.wrapping_add_signed(delta);
+                e.span.end = e.span.end.wrapping_add_signed(delta);
+            }
+            let n = events.len();
+            self.events.splice(first..=last, events);
+            return Ok(first..first + n);
+        }
+
+        self.events = parse_events(json, 0)?;
+        Ok(0..self.events.len())
+    }
+
+    /// Return the indexes of the start and end events of all containers
+    /// whose brackets enclose the given span, from the innermost to the
+    /// outermost one
+    fn enclosing(&self, span: Span) -> Vec<(usize, usize)> {
+        // containers are closed from the inside out
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        for (i, e) in self.events.iter().enumerate() {
+            match e.event {
+                OwnedJsonEvent::StartObject | OwnedJsonEvent::StartArray => stack.push(i),
+                OwnedJsonEvent::EndObject | OwnedJsonEvent::EndArray => {
+                    if let Some(first) = stack.pop() {
+                        if self.events[first].span.end <= span.start && e.span.start >= span.end {
+                            result.push((first, i));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
+/// Parse a complete JSON text and record its events with spans shifted by
+/// `offset`
+fn parse_events(json: &[u8], offset: usize) -> Result<Vec<IndexedEvent>, NextValueError> {
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    let mut events = Vec::new();
+    while let Some(event) = parser.next_owned_event()? {
+        if event == OwnedJsonEvent::NeedMoreInput {
+            continue;
+        }
+        let span = parser.current_span();
+        events.push(IndexedEvent {
+            event,
+            span: Span::new(span.start + offset, span.end + offset),
+        });
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Span;
+
+    use super::EventIndex;
+
+    /// Apply an edit to `json` and check that the updated index matches
+    /// an index built from scratch
+    fn edit(json: &str, old: Span, replacement: &str) -> std::ops::Range<usize> {
+        let mut index = EventIndex::build(json.as_bytes()).unwrap();
+        let mut new = json.to_string();
+        new.replace_range(old.start..old.end, replacement);
+        let changed = index
+            .update(new.as_bytes(), old, replacement.len())
+            .unwrap();
+        assert_eq!(
+            index.events(),
+            EventIndex::build(new.as_bytes()).unwrap().events()
+        );
+        changed
+    }
+
+    /// Test that only the innermost enclosing container is parsed again
+    #[test]
+    fn innermost() {
+        let json = r#"{"a": {"b": [1, 2]}, "c": [3], "d": {"e": "x"}}"#;
+        assert_eq!(edit(json, Span::new(16, 17), "42"), 4..8);
+        assert_eq!(edit(json, Span::new(14, 17), ""), 4..7);
+        assert_eq!(edit(json, Span::new(43, 44), "yz"), 14..18);
+        assert_eq!(edit(json, Span::new(7, 10), r#""f""#), 2..9);
+    }
+
+    /// Test that enclosing containers are tried if the edit changes the
+    /// structure and that the whole text is parsed as a last resort
+    #[test]
+    fn fallback() {
+        let json = r#"{"a": [1, 2], "b": [3]}"#;
+
+        // the array is closed early, so it cannot be parsed on its own
+        assert_eq!(edit(json, Span::new(10, 11), "2], \"x\": [5"), 0..15);
+
+        // the edit touches the brackets of the root object
+        assert_eq!(edit(json, Span::new(0, 1), " {"), 0..11);
+
+        // invalid edits are rejected and leave the index unchanged
+        let mut index = EventIndex::build(json.as_bytes()).unwrap();
+        let events = index.events().to_vec();
+        assert!(index
+            .update(br#"{"a": [1, 2}, "b": [3]}"#, Span::new(11, 12), 1)
+            .is_err());
+        assert_eq!(index.events(), events);
+    }
+}
@@ -0,0 +1,286 @@
+//! Build a structural index of a JSON text for random access.
+//!
+//! A [`SemiIndex`] records the location of every value in a JSON text (or
+//! only of those matching a set of [`PathPattern`]s) as a JSON Pointer and a
+//! [`Span`]. Once the index has been built, a value can be read directly
+//! from its span without parsing the text again. This is useful for huge
+//! files that are accessed repeatedly: the index can be built in a single
+//! pass and kept while only the parts of the file that are actually needed
+//! are parsed later.
+//!
+//! ```
+//! use actson::index::SemiIndex;
+//!
+//! let json = br#"{"items": [{"id": 1}, {"id": 2}], "total": 2}"#;
+//! let index = SemiIndex::build(json).unwrap();
+//! let entry = index.get("/items/1").unwrap().unwrap();
+//! assert_eq!(entry.slice(json), br#"{"id": 2}"#);
+//! assert_eq!(index.get("/total").unwrap().unwrap().span.start, 43);
+//! ```
+
+use crate::{
+    feeder::SliceJsonFeeder,
+    parser::NextValueError,
+    path::{Location, PathPattern},
+    JsonEvent, JsonParser, Span,
+};
+
+/// The location of a value in a JSON text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// The JSON Pointer to the value (e.g. `/items/0/id`)
+    pub path: String,
+
+    /// The position of the value in the JSON text. For strings, the span
+    /// includes the quotes. For objects and arrays, it covers everything
+    /// from the opening to the closing bracket.
+    pub span: Span,
+}
+
+impl IndexEntry {
+    /// Get the bytes of the value from the JSON text the index has been
+    /// built from
+    pub fn slice<'a>(&self, json: &'a [u8]) -> &'a [u8] {
+        &json[self.span.start..self.span.end]
+    }
+}
+
+/// The location of the value currently being indexed in its parent
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// An object or array that is currently open
+struct Frame {
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The index of the next element if the container is an array
+    next_index: usize,
+
+    /// The most recent field name if the container is an object
+    key: String,
+
+    /// The index of the container's entry or `None` if it is not recorded
+    entry: Option<usize>,
+}
+
+/// An index of the locations of values in a JSON text. See the [module
+/// documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct SemiIndex {
+    /// The recorded entries in document order
+    entries: Vec<IndexEntry>,
+
+    /// Positions in [`Self::entries`] sorted by path
+    sorted: Vec<usize>,
+}
+
+impl SemiIndex {
+    /// Build an index of all values in the given JSON text
+    pub fn build(json: &[u8]) -> Result<Self, NextValueError> {
+        Self::build_with(json, None)
+    }
+
+    /// Build an index of only those values in the given JSON text that
+    /// match one of the given patterns
+    ///
+    /// ```
+    /// use actson::index::SemiIndex;
+    /// use actson::path::PathPattern;
+    ///
+    /// let json = br#"{"items": [{"id": 1}, {"id": 2}], "total": 2}"#;
+    /// let pattern = "$.items[*].id".parse::<PathPattern>().unwrap();
+    /// let index = SemiIndex::build_matching(json, [pattern]).unwrap();
+    /// let paths = index.entries().iter().map(|e| e.path.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(paths, vec!["/items/0/id", "/items/1/id"]);
+    /// ```
+    pub fn build_matching(
+        json: &[u8],
+        patterns: impl IntoIterator<Item = PathPattern>,
+    ) -> Result<Self, NextValueError> {
+        Self::build_with(json, Some(patterns.into_iter().collect()))
+    }
+
+    fn build_with(json: &[u8], patterns: Option<Vec<PathPattern>>) -> Result<Self, NextValueError> {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        let mut entries: Vec<IndexEntry> = Vec::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut steps: Vec<Step> = Vec::new();
+        let mut path = String::new();
+
+        while let Some(e) = parser.next_event()? {
+            match e {
+                JsonEvent::NeedMoreInput => {}
+
+                JsonEvent::FieldName => {
+                    if let Some(frame) = stack.last_mut() {
+                        frame.key.clear();
+                        frame.key.push_str(parser.current_str()?);
+                    }
+                }
+
+                JsonEvent::EndObject | JsonEvent::EndArray => {
+                    let frame = stack.pop();
+                    if let Some(i) = frame.and_then(|f| f.entry) {
+                        entries[i].span.end = parser.current_span().end;
+                    }
+                    pop_step(&mut steps, &mut path);
+                }
+
+                _ => {
+                    // determine the location of the value in its parent
+                    if let Some(frame) = stack.last_mut() {
+                        let step = if frame.is_array {
+                            frame.next_index += 1;
+                            Step::Index(frame.next_index - 1)
+                        } else {
+                            Step::Key(frame.key.clone())
+                        };
+                        push_step(step, &mut steps, &mut path);
+                    }
+
+                    let record = patterns.as_ref().is_none_or(|patterns| {
+                        patterns.iter().any(|p| {
+                            p.matches_path(steps.iter().map(|s| match s {
+                                Step::Key(k) => Location::Key(Some(k)),
+                                Step::Index(i) => Location::Index(*i),
+                            }))
+                        })
+                    });
+                    let entry = record.then(|| {
+                        entries.push(IndexEntry {
+                            path: path.clone(),
+                            span: parser.current_span(),
+                        });
+                        entries.len() - 1
+                    });
+
+                    if e.is_container_start() {
+                        stack.push(Frame {
+                            is_array: e == JsonEvent::StartArray,
+                            next_index: 0,
+                            key: String::new(),
+                            entry,
+                        });
+                    } else if !stack.is_empty() {
+                        pop_step(&mut steps, &mut path);
+                    }
+                }
+            }
+        }
+
+        let mut sorted = (0..entries.len()).collect::<Vec<_>>();
+        sorted.sort_by(|&a, &b| entries[a].path.cmp(&entries[b].path));
+
+        Ok(SemiIndex { entries, sorted })
+    }
+
+    /// Get all recorded entries in document order
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Look up the entry of the value at the given JSON Pointer. If an
+    /// object contains the same field more than once, the first occurrence
+    /// is returned. Returns `Ok(None)` if no value has been recorded at the
+    /// pointer and [`NextValueError::InvalidPointer`] if the pointer is
+    /// neither empty nor starts with `/`.
+    pub fn get(&self, pointer: &str) -> Result<Option<&IndexEntry>, NextValueError> {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(NextValueError::InvalidPointer(pointer.to_string()));
+        }
+        let i = self
+            .sorted
+            .partition_point(|&i| self.entries[i].path.as_str() < pointer);
+        Ok(self
+            .sorted
+            .get(i)
+            .map(|&i| &self.entries[i])
+            .filter(|e| e.path == pointer))
+    }
+
+    /// Returns the number of recorded entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no entries have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Enter a value and append its reference token to the JSON Pointer
+fn push_step(step: Step, steps: &mut Vec<Step>, path: &mut String) {
+    path.push('/');
+    match &step {
+        Step::Key(k) => path.push_str(&k.replace('~', "~0").replace('/', "~1")),
+        Step::Index(i) => path.push_str(&i.to_string()),
+    }
+    steps.push(step);
+}
+
+/// Leave the current value and remove its reference token from the JSON
+/// Pointer
+fn pop_step(steps: &mut Vec<Step>, path: &mut String) {
+    if steps.pop().is_some() {
+        let end = path.rfind('/').unwrap_or(0);
+        path.truncate(end);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parser::NextValueError, path::PathPattern, Span};
+
+    use super::SemiIndex;
+
+    /// Test that all values are indexed with escaped paths and correct
+    /// spans and that they can be looked up
+    #[test]
+    fn build() {
+        let json = br#"{"a/b": [1, "x", {}], "~c": {"d": null}} "#;
+        let index = SemiIndex::build(json).unwrap();
+        let entries = index
+            .entries()
+            .iter()
+            .map(|e| (e.path.as_str(), e.slice(json)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                ("", &br#"{"a/b": [1, "x", {}], "~c": {"d": null}}"#[..]),
+                ("/a~1b", br#"[1, "x", {}]"#),
+                ("/a~1b/0", b"1"),
+                ("/a~1b/1", br#""x""#),
+                ("/a~1b/2", b"{}"),
+                ("/~0c", br#"{"d": null}"#),
+                ("/~0c/d", b"null"),
+            ]
+        );
+        assert_eq!(
+            index.get("/~0c/d").unwrap().unwrap().span,
+            Span::new(34, 38)
+        );
+        assert_eq!(index.get("/a~1b/3").unwrap(), None);
+
+        let index =
+            SemiIndex::build_matching(json, ["/*/*".parse::<PathPattern>().unwrap()]).unwrap();
+        assert_eq!(index.len(), 4);
+        assert!(SemiIndex::build(b"[1, ").is_err());
+    }
+
+    /// Test that a malformed pointer is reported as an error
+    #[test]
+    fn invalid_pointer() {
+        let json = br#"{"a": 1}"#;
+        let index = SemiIndex::build(json).unwrap();
+        assert!(matches!(
+            index.get("a"),
+            Err(NextValueError::InvalidPointer(p)) if p == "a"
+        ));
+        assert_eq!(index.get("").unwrap().unwrap().slice(json), json);
+    }
+}
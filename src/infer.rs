@@ -0,0 +1,324 @@
+//! Infer the structure of JSON texts.
+//!
+//! [`SchemaInferrer`] is a [`JsonEventSink`] that records which types occur
+//! at each position of a JSON text, which fields objects have and how often,
+//! and what the elements of arrays look like. All elements of an array and
+//! all top-level values in streaming mode (e.g. the records of an NDJSON
+//! file) are merged into one [`InferredSchema`], so only the structure and
+//! not the data is kept in memory. This is useful for exploring
+//! undocumented data feeds. The result can be converted to a JSON Schema,
+//! which can then be used to validate further data with a
+//! [`Schema`](crate::schema::Schema).
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::infer::SchemaInferrer;
+//! use actson::options::JsonParserOptionsBuilder;
+//! use actson::schema::Schema;
+//! use actson::sink::parse_with_parser;
+//! use actson::JsonParser;
+//!
+//! let ndjson = br#"
+//!     {"id": 1, "tags": ["a"]}
+//!     {"id": 2, "tags": [], "note": null}
+//! "#;
+//! let mut parser = JsonParser::new_with_options(
+//!     SliceJsonFeeder::new(ndjson),
+//!     JsonParserOptionsBuilder::default().with_streaming(true).build(),
+//! );
+//! let mut inferrer = SchemaInferrer::new();
+//! parse_with_parser(&mut parser, &mut inferrer).unwrap();
+//!
+//! let inferred = inferrer.into_schema();
+//! assert_eq!(inferred.types.objects, 2);
+//! assert_eq!(inferred.required().collect::<Vec<_>>(), vec!["id", "tags"]);
+//! assert_eq!(inferred.field("note").unwrap().count, 1);
+//!
+//! let schema = Schema::from_value(&inferred.to_json_schema()).unwrap();
+//! assert!(schema.validate(br#"{"id": 3, "tags": ["b", "c"]}"#).unwrap().is_empty());
+//! assert!(!schema.validate(br#"{"id": "4", "tags": []}"#).unwrap().is_empty());
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    feeder::JsonFeeder,
+    parser::InvalidStringValueError,
+    sink::JsonEventSink,
+    stats::TypeCounts,
+    storage::ParserStorage,
+    value::{JsonObject, JsonValue},
+    JsonEvent, JsonParser,
+};
+
+/// The structure inferred for the values at one position of a JSON text.
+/// See the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InferredSchema {
+    /// The number of values of each type observed at this position
+    pub types: TypeCounts,
+
+    /// The fields of the objects observed at this position in the order in
+    /// which they first appeared
+    pub fields: Vec<InferredField>,
+
+    /// The structure of the elements of the arrays observed at this
+    /// position or `None` if there were no elements
+    pub items: Option<Box<InferredSchema>>,
+
+    /// The index of each field in [`Self::fields`] by its name
+    field_index: HashMap<String, usize>,
+}
+
+/// A field of the objects at a position of a JSON text
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InferredField {
+    /// The name of the field
+    pub name: String,
+
+    /// The number of objects that have this field
+    pub count: usize,
+
+    /// The structure of the field's values
+    pub schema: InferredSchema,
+}
+
+impl InferredSchema {
+    /// Get the field with the given name
+    pub fn field(&self, name: &str) -> Option<&InferredField> {
+        self.field_index.get(name).map(|&i| &self.fields[i])
+    }
+
+    /// Get the names of the fields that all objects at this position have
+    pub fn required(&self) -> impl Iterator<Item = &str> {
+        self.fields
+            .iter()
+            .filter(|f| f.count >= self.types.objects)
+            .map(|f| f.name.as_str())
+    }
+
+    /// Convert the structure to a JSON Schema with the keywords `type`,
+    /// `properties`, `required`, and `items`. Numbers are of type `integer`
+    /// if no floats have been observed. A position without any values
+    /// produces the empty schema, which allows all values.
+    pub fn to_json_schema(&self) -> JsonValue {
+        let mut schema = JsonObject::new();
+
+        let t = &self.types;
+        let types = [
+            (t.objects, "object"),
+            (t.arrays, "array"),
+            (t.strings, "string"),
+            (t.integers * usize::from(t.floats == 0), "integer"),
+            (t.floats, "number"),
+            (t.booleans, "boolean"),
+            (t.nulls, "null"),
+        ]
+        .into_iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(_, name)| JsonValue::String(name.to_string()))
+        .collect::<Vec<_>>();
+        match types.len() {
+            0 => {}
+            1 => {
+                schema.insert("type", types.into_iter().next().unwrap_or_default());
+            }
+            _ => {
+                schema.insert("type", JsonValue::Array(types));
+            }
+        }
+
+        if t.objects > 0 {
+            let properties = self
+                .fields
+                .iter()
+                .map(|f| (f.name.clone(), f.schema.to_json_schema()))
+                .collect::<JsonObject>();
+            let required = self
+                .required()
+                .map(|name| JsonValue::String(name.to_string()))
+                .collect::<Vec<_>>();
+            schema.insert("properties", JsonValue::Object(properties));
+            schema.insert("required", JsonValue::Array(required));
+        }
+        if let Some(items) = &self.items {
+            schema.insert("items", items.to_json_schema());
+        }
+
+        JsonValue::Object(schema)
+    }
+
+    /// Get the field with the given name and create it if necessary
+    fn field_mut(&mut self, name: &str) -> usize {
+        if let Some(&i) = self.field_index.get(name) {
+            return i;
+        }
+        self.fields.push(InferredField {
+            name: name.to_string(),
+            ..Default::default()
+        });
+        self.field_index
+            .insert(name.to_string(), self.fields.len() - 1);
+        self.fields.len() - 1
+    }
+}
+
+/// An object or array that is currently open
+struct Frame {
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The index of the current field if the container is an object
+    field: usize,
+}
+
+/// Infers an [`InferredSchema`] from the events of a parser. See the
+/// [module documentation](self).
+#[derive(Default)]
+pub struct SchemaInferrer {
+    schema: InferredSchema,
+    stack: Vec<Frame>,
+}
+
+impl SchemaInferrer {
+    /// Create a new inferrer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the structure inferred so far
+    pub fn schema(&self) -> &InferredSchema {
+        &self.schema
+    }
+
+    /// Consume the inferrer and return the inferred structure
+    pub fn into_schema(self) -> InferredSchema {
+        self.schema
+    }
+
+    /// Get the structure at the position described by the given containers
+    fn node<'a>(schema: &'a mut InferredSchema, frames: &[Frame]) -> &'a mut InferredSchema {
+        let mut node = schema;
+        for frame in frames {
+            node = if frame.is_array {
+                node.items.get_or_insert_with(Default::default)
+            } else {
+                &mut node.fields[frame.field].schema
+            };
+        }
+        node
+    }
+}
+
+impl JsonEventSink for SchemaInferrer {
+    type Error = InvalidStringValueError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => return Ok(()),
+
+            JsonEvent::FieldName => {
+                let n = self.stack.len().saturating_sub(1);
+                let object = Self::node(&mut self.schema, &self.stack[..n]);
+                let field = object.field_mut(parser.current_str()?);
+                object.fields[field].count += 1;
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.field = field;
+                }
+                return Ok(());
+            }
+
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                self.stack.pop();
+                return Ok(());
+            }
+
+            _ => {}
+        }
+
+        let types = &mut Self::node(&mut self.schema, &self.stack).types;
+        match event {
+            JsonEvent::StartObject => types.objects += 1,
+            JsonEvent::StartArray => types.arrays += 1,
+            JsonEvent::ValueString => types.strings += 1,
+            JsonEvent::ValueInt => types.integers += 1,
+            JsonEvent::ValueFloat => types.floats += 1,
+            JsonEvent::ValueTrue | JsonEvent::ValueFalse => types.booleans += 1,
+            JsonEvent::ValueNull => types.nulls += 1,
+            _ => {}
+        }
+        if matches!(event, JsonEvent::StartObject | JsonEvent::StartArray) {
+            self.stack.push(Frame {
+                is_array: event == JsonEvent::StartArray,
+                field: 0,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::SliceJsonFeeder, sink::parse_with, value::JsonValue};
+
+    use super::SchemaInferrer;
+
+    /// Test that elements of arrays are merged, that optional fields and
+    /// mixed types are detected, and that the JSON Schema reflects them
+    #[test]
+    fn infer() {
+        let json = br#"[
+            {"a": 1, "b": [{"c": "x"}, {"c": 2.5, "d": true}]},
+            {"a": null, "b": []},
+            [],
+            {"a": 2}
+        ]"#;
+        let mut inferrer = SchemaInferrer::new();
+        parse_with(SliceJsonFeeder::new(json), &mut inferrer).unwrap();
+        let schema = inferrer.into_schema();
+
+        assert_eq!(schema.types.arrays, 1);
+        let items = schema.items.as_deref().unwrap();
+        assert_eq!(items.types.objects, 3);
+        assert_eq!(items.types.arrays, 1);
+        assert_eq!(items.required().collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(items.field("b").unwrap().count, 2);
+        let b_items = items.field("b").unwrap().schema.items.as_deref().unwrap();
+        assert_eq!(b_items.field("c").unwrap().schema.types.total(), 2);
+        assert_eq!(b_items.field("d").unwrap().count, 1);
+
+        let expected = JsonValue::parse(
+            br#"{
+                "type": "array",
+                "items": {
+                    "type": ["object", "array"],
+                    "properties": {
+                        "a": {"type": ["integer", "null"]},
+                        "b": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "c": {"type": ["string", "number"]},
+                                    "d": {"type": "boolean"}
+                                },
+                                "required": ["c"]
+                            }
+                        }
+                    },
+                    "required": ["a"]
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(schema.to_json_schema(), expected);
+    }
+}
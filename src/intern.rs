@@ -0,0 +1,88 @@
+//! Share repeated strings such as object keys instead of allocating a new
+//! string for every occurrence.
+//!
+//! Pass a [`StringInterner`] to
+//! [`JsonParser::current_str_interned()`](crate::JsonParser::current_str_interned()).
+//! Strings are looked up by their raw (still escaped) contents, so repeated
+//! keys are neither unescaped nor validated again.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::intern::StringInterner;
+//! use actson::{JsonEvent, JsonParser};
+//!
+//! let json = r#"[{"name": "Elvis"}, {"name": "Max"}]"#.as_bytes();
+//! let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+//! let mut interner = StringInterner::new();
+//!
+//! let mut keys = Vec::new();
+//! while let Some(e) = parser.next_event().unwrap() {
+//!     if e == JsonEvent::FieldName {
+//!         keys.push(parser.current_str_interned(&mut interner).unwrap());
+//!     }
+//! }
+//!
+//! assert_eq!(&*keys[0], "name");
+//! assert!(Arc::ptr_eq(&keys[0], &keys[1]));
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+/// A cache of shared strings keyed by their raw contents in the JSON text
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    strings: HashMap<Box<[u8]>, Arc<str>>,
+    max_len: Option<usize>,
+}
+
+impl StringInterner {
+    /// Create a new, empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new, empty interner that holds at most `max_len` strings.
+    /// Once it is full, new strings are still returned but not cached
+    /// anymore. Use this to bound memory usage if the JSON text may contain
+    /// an arbitrary number of distinct keys.
+    pub fn with_max_len(max_len: usize) -> Self {
+        StringInterner {
+            strings: HashMap::new(),
+            max_len: Some(max_len),
+        }
+    }
+
+    /// Returns the number of cached strings
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if the interner does not contain any strings
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Remove all cached strings
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+
+    /// Look up the string with the given raw contents or create it with
+    /// `decode` and cache it
+    pub(crate) fn get_or_insert<E>(
+        &mut self,
+        raw: &[u8],
+        decode: impl FnOnce() -> Result<Arc<str>, E>,
+    ) -> Result<Arc<str>, E> {
+        if let Some(s) = self.strings.get(raw) {
+            return Ok(Arc::clone(s));
+        }
+        let s = decode()?;
+        if self.max_len.is_none_or(|m| self.strings.len() < m) {
+            self.strings.insert(raw.into(), Arc::clone(&s));
+        }
+        Ok(s)
+    }
+}
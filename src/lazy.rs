@@ -0,0 +1,395 @@
+//! Navigate a JSON text without parsing the parts that are not needed.
+//!
+//! A [`LazyValue`] is a reference to a value in a slice of bytes. Nothing is
+//! parsed when it is created. Only when a field, element, or the value
+//! itself is accessed, the value is parsed far enough to find what was
+//! requested. Children are returned as new lazy values that point into the
+//! same slice. This makes it cheap to read a few fields of a large document,
+//! but every access parses the value again. If a document is accessed
+//! repeatedly, parse it into a [`Dom`](crate::dom::Dom) instead.
+//!
+//! Since values are only parsed when they are accessed, syntax errors are
+//! not reported until the invalid part of the text is reached.
+//!
+//! ```
+//! use actson::lazy::LazyValue;
+//!
+//! let json = br#"{"id": 42, "payload": {"huge": [1, 2, 3]}, "name": "Elvis"}"#;
+//! let value = LazyValue::new(json);
+//! let name = value.get("name").unwrap().unwrap();
+//! assert_eq!(name.as_str().unwrap(), "Elvis");
+//! let payload = value.get("payload").unwrap().unwrap();
+//! assert_eq!(payload.raw(), br#"{"huge": [1, 2, 3]}"#);
+//! ```
+
+use std::{borrow::Cow, str::from_utf8};
+
+use crate::{
+    dom::DomKind,
+    feeder::SliceJsonFeeder,
+    parser::{InvalidFloatValueError, InvalidStringValueError, NextValueError},
+    JsonEvent, JsonNumber, JsonParser, Span,
+};
+
+/// A reference to a value in a JSON text that is parsed on demand. See the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LazyValue<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> LazyValue<'a> {
+    /// Create a lazy value from the given JSON text. Leading and trailing
+    /// whitespace is ignored. The text is not parsed yet.
+    pub fn new(json: &'a [u8]) -> Self {
+        LazyValue {
+            raw: json.trim_ascii(),
+        }
+    }
+
+    /// Get the value exactly as it appears in the JSON text
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Get the type of the value judging by its first byte. Returns `None`
+    /// if the value is empty or does not start like a JSON value.
+    pub fn kind(&self) -> Option<DomKind> {
+        match self.raw.first()? {
+            b'{' => Some(DomKind::Object),
+            b'[' => Some(DomKind::Array),
+            b'"' => Some(DomKind::String),
+            b'-' | b'0'..=b'9' => Some(DomKind::Number),
+            b't' => Some(DomKind::True),
+            b'f' => Some(DomKind::False),
+            b'n' => Some(DomKind::Null),
+            _ => None,
+        }
+    }
+
+    /// Create a parser for the value and read its first event
+    fn parse(
+        &self,
+        expected: &'static str,
+    ) -> Result<(JsonParser<SliceJsonFeeder<'a>>, JsonEvent), NextValueError> {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(self.raw));
+        match parser.next_event()? {
+            Some(e) if e != JsonEvent::NeedMoreInput => Ok((parser, e)),
+            found => Err(NextValueError::UnexpectedEvent { expected, found }),
+        }
+    }
+
+    /// Get the value of the field with the given name if the value is an
+    /// object. Returns `Ok(None)` if the object does not contain the field.
+    /// If the object contains the field more than once, the first
+    /// occurrence is returned. Only the members up to the field are parsed.
+    pub fn get(&self, name: &str) -> Result<Option<LazyValue<'a>>, NextValueError> {
+        for m in self.members()? {
+            let (k, v) = m?;
+            if k == name {
+                return Ok(Some(v));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Get the element at the given index if the value is an array. Returns
+    /// `Ok(None)` if the array is shorter. Only the elements up to the
+    /// index are parsed.
+    pub fn at(&self, index: usize) -> Result<Option<LazyValue<'a>>, NextValueError> {
+        self.elements()?.nth(index).transpose()
+    }
+
+    /// Navigate to the value at the given JSON Pointer (e.g. `/a/0/b`)
+    /// relative to this value. Returns `Ok(None)` if there is no such value
+    /// and [`NextValueError::InvalidPointer`] if the pointer is neither
+    /// empty nor starts with `/`.
+    pub fn pointer(&self, pointer: &str) -> Result<Option<LazyValue<'a>>, NextValueError> {
+        if pointer.is_empty() {
+            return Ok(Some(*self));
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(NextValueError::InvalidPointer(pointer.to_string()));
+        };
+        let mut v = *self;
+        for token in rest.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            let child = match v.kind() {
+                Some(DomKind::Object) => v.get(&token)?,
+                Some(DomKind::Array) if token == "0" || !token.starts_with('0') => {
+                    match token.parse() {
+                        Ok(i) => v.at(i)?,
+                        Err(_) => None,
+                    }
+                }
+                _ => None,
+            };
+            match child {
+                Some(c) => v = c,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(v))
+    }
+
+    /// Iterate over the elements if the value is an array. Fails if the
+    /// value is not an array.
+    pub fn elements(&self) -> Result<Elements<'a>, NextValueError> {
+        match self.parse("array")? {
+            (parser, JsonEvent::StartArray) => Ok(Elements {
+                raw: self.raw,
+                parser,
+                done: false,
+            }),
+            (_, found) => Err(NextValueError::UnexpectedEvent {
+                expected: "array",
+                found: Some(found),
+            }),
+        }
+    }
+
+    /// Iterate over the names and values of the members if the value is an
+    /// object. Fails if the value is not an object.
+    pub fn members(&self) -> Result<Members<'a>, NextValueError> {
+        match self.parse("object")? {
+            (parser, JsonEvent::StartObject) => Ok(Members {
+                raw: self.raw,
+                parser,
+                done: false,
+            }),
+            (_, found) => Err(NextValueError::UnexpectedEvent {
+                expected: "object",
+                found: Some(found),
+            }),
+        }
+    }
+
+    /// Decode the value if it is a string. The result borrows from the JSON
+    /// text unless the string contains escape sequences.
+    pub fn as_str(&self) -> Result<Cow<'a, str>, NextValueError> {
+        match self.parse("string")? {
+            (parser, JsonEvent::ValueString) => Ok(parser.current_str_cow()?),
+            (_, found) => Err(NextValueError::UnexpectedEvent {
+                expected: "string",
+                found: Some(found),
+            }),
+        }
+    }
+
+    /// Get the number in its best-fitting representation if the value is a
+    /// number
+    pub fn as_number(&self) -> Result<JsonNumber<'a>, NextValueError> {
+        match self.parse("number")? {
+            (_, JsonEvent::ValueInt | JsonEvent::ValueFloat) => {
+                let literal = from_utf8(self.raw).map_err(InvalidStringValueError::from)?;
+                Ok(JsonNumber::from_literal(literal).map_err(InvalidFloatValueError::from)?)
+            }
+            (_, found) => Err(NextValueError::UnexpectedEvent {
+                expected: "number",
+                found: Some(found),
+            }),
+        }
+    }
+
+    /// Get the value if it is a boolean
+    pub fn as_bool(&self) -> Result<bool, NextValueError> {
+        match self.parse("boolean")? {
+            (_, JsonEvent::ValueTrue) => Ok(true),
+            (_, JsonEvent::ValueFalse) => Ok(false),
+            (_, found) => Err(NextValueError::UnexpectedEvent {
+                expected: "boolean",
+                found: Some(found),
+            }),
+        }
+    }
+
+    /// Returns `true` if the value is `null`
+    pub fn is_null(&self) -> bool {
+        self.raw == b"null"
+    }
+
+    /// Deserialize the value into an instance of type `T`
+    ///
+    /// *Heads up:* The `serde_json` feature has to be enabled for this.
+    #[cfg(feature = "serde_json")]
+    pub fn deserialize<T>(&self) -> Result<T, crate::serde_json::DeserializeError>
+    where
+        T: serde::Deserialize<'a>,
+    {
+        crate::serde_json::from_slice(self.raw)
+    }
+}
+
+/// Read the next value of an array or object from the given parser and
+/// return its span. Returns `None` at the end of the array or object.
+fn next_value_span(
+    parser: &mut JsonParser<SliceJsonFeeder<'_>>,
+) -> Result<Option<Span>, NextValueError> {
+    let start = match parser.next_event()? {
+        Some(JsonEvent::EndObject | JsonEvent::EndArray) => return Ok(None),
+        Some(JsonEvent::StartObject | JsonEvent::StartArray) => parser.current_span().start,
+        Some(e) if e != JsonEvent::FieldName && e != JsonEvent::NeedMoreInput => {
+            return Ok(Some(parser.current_span()));
+        }
+        found => {
+            return Err(NextValueError::UnexpectedEvent {
+                expected: "value",
+                found,
+            })
+        }
+    };
+
+    // skip the nested values of the object or array
+    let mut depth = 1usize;
+    while depth > 0 {
+        match parser.next_event()? {
+            Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+            Some(JsonEvent::EndObject | JsonEvent::EndArray) => depth -= 1,
+            Some(JsonEvent::NeedMoreInput) | None => {
+                return Err(NextValueError::UnexpectedEvent {
+                    expected: "end of object or array",
+                    found: None,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(Some(Span::new(start, parser.current_span().end)))
+}
+
+/// An iterator over the elements of an array. Create it with
+/// [`LazyValue::elements()`]. Each element is only parsed far enough to
+/// find its end. After an error, the iterator ends.
+pub struct Elements<'a> {
+    raw: &'a [u8],
+    parser: JsonParser<SliceJsonFeeder<'a>>,
+    done: bool,
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Result<LazyValue<'a>, NextValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match next_value_span(&mut self.parser) {
+            Ok(Some(span)) => Some(Ok(LazyValue {
+                raw: &self.raw[span.start..span.end],
+            })),
+            r => {
+                self.done = true;
+                r.err().map(Err)
+            }
+        }
+    }
+}
+
+/// An iterator over the names and values of the members of an object.
+/// Create it with [`LazyValue::members()`]. Each value is only parsed far
+/// enough to find its end. After an error, the iterator ends.
+pub struct Members<'a> {
+    raw: &'a [u8],
+    parser: JsonParser<SliceJsonFeeder<'a>>,
+    done: bool,
+}
+
+impl<'a> Members<'a> {
+    fn next_member(&mut self) -> Result<Option<(Cow<'a, str>, LazyValue<'a>)>, NextValueError> {
+        match self.parser.next_event()? {
+            Some(JsonEvent::FieldName) => {}
+            Some(JsonEvent::EndObject) => return Ok(None),
+            found => {
+                return Err(NextValueError::UnexpectedEvent {
+                    expected: "field name or end of object",
+                    found,
+                })
+            }
+        }
+        let name = self.parser.current_str_cow()?;
+        match next_value_span(&mut self.parser)? {
+            Some(span) => Ok(Some((
+                name,
+                LazyValue {
+                    raw: &self.raw[span.start..span.end],
+                },
+            ))),
+            None => Err(NextValueError::UnexpectedEvent {
+                expected: "value",
+                found: None,
+            }),
+        }
+    }
+}
+
+impl<'a> Iterator for Members<'a> {
+    type Item = Result<(Cow<'a, str>, LazyValue<'a>), NextValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let r = self.next_member();
+        self.done = !matches!(r, Ok(Some(_)));
+        r.transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{dom::DomKind, parser::NextValueError, JsonNumber};
+
+    use super::LazyValue;
+
+    /// Test that values can be navigated to and read and that only the
+    /// parts of the text that are needed are parsed
+    #[test]
+    fn navigate() {
+        let json = br#" {"a": [1, -2.5, {"b": "x\u00e9"}], "c": true, "d": null, "e": [} "#;
+        let value = LazyValue::new(json);
+        assert_eq!(value.kind(), Some(DomKind::Object));
+
+        let a = value.get("a").unwrap().unwrap();
+        assert_eq!(a.raw(), br#"[1, -2.5, {"b": "x\u00e9"}]"#);
+        assert_eq!(
+            a.at(0).unwrap().unwrap().as_number().unwrap(),
+            JsonNumber::I64(1)
+        );
+        assert_eq!(
+            a.at(1).unwrap().unwrap().as_number().unwrap(),
+            JsonNumber::F64(-2.5)
+        );
+        assert!(a.at(3).unwrap().is_none());
+        assert_eq!(
+            value.pointer("/a/2/b").unwrap().unwrap().as_str().unwrap(),
+            "x\u{e9}"
+        );
+        assert_eq!(value.pointer("/a/01").unwrap(), None);
+        assert!(value.get("c").unwrap().unwrap().as_bool().unwrap());
+        assert!(value.get("d").unwrap().unwrap().is_null());
+        assert!(value.get("c").unwrap().unwrap().as_str().is_err());
+        assert!(a.members().is_err());
+
+        // the syntax error is only found once the invalid member is reached
+        assert!(value.get("e").is_err());
+        assert!(value.get("x").is_err());
+        let names = value
+            .members()
+            .unwrap()
+            .map(|m| m.map(|(k, _)| k))
+            .collect::<Vec<_>>();
+        assert_eq!(names.len(), 4);
+        assert!(names[3].is_err());
+    }
+
+    /// Test that a malformed pointer is reported as an error
+    #[test]
+    fn invalid_pointer() {
+        let value = LazyValue::new(br#"{"a": 1}"#);
+        assert!(matches!(
+            value.pointer("a"),
+            Err(NextValueError::InvalidPointer(p)) if p == "a"
+        ));
+        assert_eq!(value.pointer("").unwrap().unwrap().raw(), br#"{"a": 1}"#);
+    }
+}
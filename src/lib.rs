@@ -134,21 +134,33 @@
 //!
 //! ### Parsing into a Serde JSON Value
 //!
-//! For testing and compatibility reasons, Actson is able to parse a byte slice
-//! into a [Serde JSON](https://github.com/serde-rs/json) Value.
+//! For testing and compatibility reasons, Actson is able to deserialize a
+//! byte slice into any [`Deserialize`](::serde::Deserialize) type, including
+//! a [Serde JSON](https://github.com/serde-rs/json) Value.
 //!
 //! Heads up: You need to enable the `serde_json` feature for this.
 //!
 //! ```
 //! use actson::serde_json::from_slice;
+//! use serde_json::Value;
 //!
 //! let json = r#"{"name": "Elvis"}"#.as_bytes();
-//! let value = from_slice(json).unwrap();
+//! let value: Value = from_slice(json).unwrap();
 //!
 //! assert!(value.is_object());
 //! assert_eq!(value["name"], "Elvis");
 //! ```
 //!
+//! The same feature also lets you write any [`Serialize`](::serde::Serialize)
+//! type with the streaming [`JsonEmitter`](emitter::JsonEmitter) (see
+//! [`serde_json::to_writer()`]).
+//!
+//! Object members are deserialized in the order in which they appear in the
+//! JSON text. If you enable Serde JSON's `preserve_order` feature in your
+//! own `Cargo.toml`, objects in a `Value` therefore keep their key order.
+//! Enable Actson's `arbitrary_precision` feature to keep the exact literals
+//! of numbers in a `Value` instead of converting them to `f64`.
+//!
 //! However, if you find yourself doing this, you probably don't need the
 //! reactive features of Actson and your data seems to completely fit into
 //! memory. In this case, you're most likely better off using Serde JSON
@@ -209,16 +221,67 @@
 //!     JsonEvent::ValueTrue,
 //! ]);
 //! ```
+pub mod base64;
+pub mod cancel;
+pub mod canonical;
+pub mod csv;
+pub mod diff;
+pub mod dom;
+pub mod emitter;
+mod escape;
 pub mod event;
 pub mod feeder;
+pub mod flatten;
+pub mod hash;
+pub mod incremental;
+pub mod index;
+pub mod infer;
+pub mod intern;
+pub mod lazy;
+pub mod number;
 pub mod options;
 pub mod parser;
+pub mod path;
+pub mod pipeline;
+pub mod progress;
+pub mod reader;
+pub mod sample;
+mod scan;
+pub mod schema;
+pub mod sink;
+pub mod sniff;
+pub mod split;
+pub mod stats;
+pub mod storage;
+pub mod tape;
+pub mod transform;
+mod unescape;
+mod validate;
+pub mod value;
+pub mod writer;
 
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
+#[cfg(feature = "rayon")]
+pub mod rayon;
+
 #[cfg(feature = "serde_json")]
 pub mod serde_json;
 
-pub use event::JsonEvent;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+pub mod binary;
+
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+#[cfg(feature = "test-util")]
+pub mod testutil;
+
+pub use event::{BorrowedJsonEvent, JsonEvent, OwnedJsonEvent, Span};
+pub use number::JsonNumber;
 pub use parser::JsonParser;
+pub use validate::{validate, validate_all, validate_all_with_options, ValidationError};
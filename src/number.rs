@@ -0,0 +1,52 @@
+use std::num::ParseFloatError;
+
+/// A JSON number in its best-fitting representation as returned by
+/// [`JsonParser::current_number()`](crate::JsonParser::current_number())
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum JsonNumber<'a> {
+    /// An integer that fits into an [`i64`]
+    I64(i64),
+
+    /// A positive integer that is too large for an [`i64`] but fits into
+    /// a [`u64`]
+    U64(u64),
+
+    /// A floating point number
+    F64(f64),
+
+    /// A number that cannot be represented by any of the other variants
+    /// without losing information (e.g. an integer exceeding the range of
+    /// [`u64`] or a float exceeding the range of [`f64`]). Contains the
+    /// number exactly as it appeared in the JSON text.
+    Big(&'a str),
+}
+
+impl<'a> JsonNumber<'a> {
+    /// Convert a number as it appears in a JSON text to its best-fitting
+    /// representation
+    pub(crate) fn from_literal(literal: &'a str) -> Result<Self, ParseFloatError> {
+        let digits = literal.strip_prefix('-').unwrap_or(literal);
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(i) = btoi::btoi::<i64>(literal.as_bytes()) {
+                return Ok(JsonNumber::I64(i));
+            }
+            if let Ok(u) = btoi::btou::<u64>(literal.as_bytes()) {
+                return Ok(JsonNumber::U64(u));
+            }
+            return Ok(JsonNumber::Big(literal));
+        }
+
+        // numbers consist of ASCII characters only, so the literal can be
+        // parsed directly
+        let f: f64 = match fast_float2::parse(literal) {
+            Ok(f) => f,
+            // let the standard library produce a meaningful error
+            Err(_) => literal.parse()?,
+        };
+        if f.is_finite() {
+            Ok(JsonNumber::F64(f))
+        } else {
+            Ok(JsonNumber::Big(literal))
+        }
+    }
+}
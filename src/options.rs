@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 /// Options for [`JsonParser`](super::JsonParser). Use [`JsonParserOptionsBuilder`]
 /// to create instances of this struct.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -8,6 +10,105 @@ pub struct JsonParserOptions {
     /// `true` if streaming mode should be enabled, which means that the parser
     /// will be able to handle a stream of multiple JSON values
     pub(super) streaming: bool,
+
+    /// Specifies how integers that do not fit into the requested type
+    /// should be handled
+    pub(super) int_overflow_policy: IntOverflowPolicy,
+
+    /// `true` if the parser should only check the JSON text for validity
+    /// without collecting the values of strings and numbers
+    pub(super) validate_only: bool,
+
+    /// The maximum number of bytes the parser may consume
+    pub(super) byte_budget: Option<usize>,
+
+    /// The maximum time the parser may take, measured from the first call
+    /// of [`JsonParser::next_event()`](super::JsonParser::next_event())
+    pub(super) time_budget: Option<Duration>,
+
+    /// The maximum length of a string in bytes (before escape sequences are
+    /// decoded)
+    pub(super) max_string_len: Option<usize>,
+
+    /// The maximum length of a number in bytes
+    pub(super) max_number_len: Option<usize>,
+
+    /// The maximum number of events the parser may produce
+    pub(super) max_tokens: Option<usize>,
+
+    /// The maximum total length in bytes of all strings and numbers in one
+    /// top-level value
+    pub(super) max_total_value_len: Option<usize>,
+
+    /// `true` if the parser should reject objects with duplicate keys
+    pub(super) reject_duplicate_keys: bool,
+
+    /// `true` if integers with leading zeros (e.g. `0123`) should be accepted
+    pub(super) allow_leading_zeros: bool,
+
+    /// `true` if numbers with a leading plus sign (e.g. `+42`) should be
+    /// accepted
+    pub(super) allow_leading_plus: bool,
+
+    /// `true` if the parser should keep track of the field names and array
+    /// indices leading to the current value
+    pub(super) track_path: bool,
+
+    /// The maximum number of members per object
+    pub(super) max_object_len: Option<usize>,
+
+    /// The maximum number of elements per array
+    pub(super) max_array_len: Option<usize>,
+
+    /// `true` if the parser should stop after the first top-level value
+    /// without reading the bytes following it
+    pub(super) allow_trailing_data: bool,
+}
+
+/// A predefined set of [`JsonParserOptions`] (see
+/// [`JsonParserOptionsBuilder::with_profile()`])
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// The default options
+    #[default]
+    Default,
+
+    /// Options for parsing untrusted input. The maximum stack depth is 128,
+    /// strings may be at most 1 MiB long, a JSON text may consist of at
+    /// most 1,000,000 events, objects may have at most 10,000 members, and
+    /// objects with duplicate keys are rejected.
+    /// Streaming mode is disabled and integers that do not fit into the
+    /// requested type result in an error. A leading byte order mark is
+    /// rejected as it is not valid JSON.
+    Hardened,
+}
+
+/// Specifies how the [`JsonParser`](super::JsonParser) handles integers that
+/// do not fit into the type requested through
+/// [`JsonParser::current_int()`](super::JsonParser::current_int()).
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntOverflowPolicy {
+    /// Return an error if the integer does not fit into the requested type
+    #[default]
+    Error,
+
+    /// Clamp the integer to the minimum or maximum value of the requested
+    /// type
+    Saturate,
+
+    /// Report integers that do not even fit into an [`i128`] or [`u128`] as
+    /// [`JsonEvent::ValueFloat`](crate::JsonEvent::ValueFloat) instead of
+    /// [`JsonEvent::ValueInt`](crate::JsonEvent::ValueInt) so they can be
+    /// read with [`JsonParser::current_float()`](super::JsonParser::current_float()).
+    /// Integers that fit into a 128-bit type but not into the requested one
+    /// are treated like with [`IntOverflowPolicy::Error`].
+    Float,
+
+    /// Like [`IntOverflowPolicy::Float`] but report integers that do not
+    /// fit into an [`i64`] or [`u64`] as
+    /// [`JsonEvent::ValueFloat`](crate::JsonEvent::ValueFloat). This matches
+    /// the default behavior of `serde_json`.
+    Float64,
 }
 
 /// A builder for [`JsonParserOptions`]
@@ -36,6 +137,38 @@ impl Default for JsonParserOptions {
         Self {
             max_depth: 2048,
             streaming: false,
+            int_overflow_policy: IntOverflowPolicy::Error,
+            validate_only: false,
+            byte_budget: None,
+            time_budget: None,
+            max_string_len: None,
+            max_number_len: None,
+            max_tokens: None,
+            max_total_value_len: None,
+            reject_duplicate_keys: false,
+            allow_leading_zeros: false,
+            allow_leading_plus: false,
+            track_path: false,
+            max_object_len: None,
+            max_array_len: None,
+            allow_trailing_data: false,
+        }
+    }
+}
+
+impl From<Profile> for JsonParserOptions {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::Default => Self::default(),
+            Profile::Hardened => Self {
+                max_depth: 128,
+                max_string_len: Some(1024 * 1024),
+                max_number_len: Some(1024),
+                max_tokens: Some(1_000_000),
+                max_object_len: Some(10_000),
+                reject_duplicate_keys: true,
+                ..Self::default()
+            },
         }
     }
 }
@@ -51,9 +184,118 @@ impl JsonParserOptions {
     pub fn streaming(&self) -> bool {
         self.streaming
     }
+
+    /// Returns the policy for integers that do not fit into the requested type
+    pub fn int_overflow_policy(&self) -> IntOverflowPolicy {
+        self.int_overflow_policy
+    }
+
+    /// Returns `true` if the parser should only check the JSON text for
+    /// validity without collecting the values of strings and numbers
+    pub fn validate_only(&self) -> bool {
+        self.validate_only
+    }
+
+    /// Returns the maximum number of bytes the parser may consume
+    pub fn byte_budget(&self) -> Option<usize> {
+        self.byte_budget
+    }
+
+    /// Returns the maximum time the parser may take
+    pub fn time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+
+    /// Returns the maximum length of a string in bytes
+    pub fn max_string_len(&self) -> Option<usize> {
+        self.max_string_len
+    }
+
+    /// Returns the maximum length of a number in bytes
+    pub fn max_number_len(&self) -> Option<usize> {
+        self.max_number_len
+    }
+
+    /// Returns the maximum number of events the parser may produce
+    pub fn max_tokens(&self) -> Option<usize> {
+        self.max_tokens
+    }
+
+    /// Returns the maximum total length in bytes of all strings and numbers
+    /// in one top-level value
+    pub fn max_total_value_len(&self) -> Option<usize> {
+        self.max_total_value_len
+    }
+
+    /// Returns `true` if the parser should reject objects with duplicate
+    /// keys
+    pub fn reject_duplicate_keys(&self) -> bool {
+        self.reject_duplicate_keys
+    }
+
+    /// Returns `true` if integers with leading zeros should be accepted
+    pub fn allow_leading_zeros(&self) -> bool {
+        self.allow_leading_zeros
+    }
+
+    /// Returns `true` if numbers with a leading plus sign should be accepted
+    pub fn allow_leading_plus(&self) -> bool {
+        self.allow_leading_plus
+    }
+
+    /// Returns `true` if the parser should keep track of the field names and
+    /// array indices leading to the current value
+    pub fn track_path(&self) -> bool {
+        self.track_path
+    }
+
+    /// Returns the maximum number of members per object
+    pub fn max_object_len(&self) -> Option<usize> {
+        self.max_object_len
+    }
+
+    /// Returns the maximum number of elements per array
+    pub fn max_array_len(&self) -> Option<usize> {
+        self.max_array_len
+    }
+
+    /// Returns `true` if the parser should stop after the first top-level
+    /// value without reading the bytes following it
+    pub fn allow_trailing_data(&self) -> bool {
+        self.allow_trailing_data
+    }
 }
 
 impl JsonParserOptionsBuilder {
+    /// Replace all options with the ones from the given [`Profile`]. Options
+    /// set afterwards override the profile's values.
+    ///
+    /// ```rust
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::options::{JsonParserOptionsBuilder, Profile};
+    /// use actson::parser::ParserError;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let feeder = SliceJsonFeeder::new(br#"{"a": 1, "a": 2}"#);
+    /// let mut parser = JsonParser::new_with_options(
+    ///     feeder,
+    ///     JsonParserOptionsBuilder::default()
+    ///         .with_profile(Profile::Hardened)
+    ///         .with_max_depth(16)
+    ///         .build(),
+    /// );
+    ///
+    /// let mut result = parser.next_event();
+    /// while let Ok(Some(_)) = result {
+    ///     result = parser.next_event();
+    /// }
+    /// assert!(matches!(result, Err(ParserError::DuplicateKey)));
+    /// ```
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.options = profile.into();
+        self
+    }
+
     /// Set the maximum stack depth
     pub fn with_max_depth(mut self, max_depth: usize) -> Self {
         self.options.max_depth = max_depth;
@@ -119,6 +361,217 @@ impl JsonParserOptionsBuilder {
         self
     }
 
+    /// Specify how integers that do not fit into the requested type should
+    /// be handled (see [`IntOverflowPolicy`])
+    ///
+    /// ```rust
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::options::{IntOverflowPolicy, JsonParserOptionsBuilder};
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let feeder = SliceJsonFeeder::new(b"1000");
+    /// let mut parser = JsonParser::new_with_options(
+    ///     feeder,
+    ///     JsonParserOptionsBuilder::default()
+    ///         .with_int_overflow_policy(IntOverflowPolicy::Saturate)
+    ///         .build(),
+    /// );
+    ///
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    /// assert_eq!(parser.current_int::<u8>().unwrap(), 255);
+    /// ```
+    pub fn with_int_overflow_policy(mut self, policy: IntOverflowPolicy) -> Self {
+        self.options.int_overflow_policy = policy;
+        self
+    }
+
+    /// Enable validation-only mode. In this mode, the parser still produces
+    /// all events and checks the JSON text for syntactic validity, but it
+    /// does not collect the values of strings and numbers. This makes
+    /// validation faster. Accessors such as
+    /// [`JsonParser::current_str()`](super::JsonParser::current_str()) or
+    /// [`JsonParser::current_int()`](super::JsonParser::current_int())
+    /// must not be used in this mode as their results are unspecified.
+    ///
+    /// See also [`validate()`](crate::validate()).
+    pub fn with_validate_only(mut self, validate_only: bool) -> Self {
+        self.options.validate_only = validate_only;
+        self
+    }
+
+    /// Set the maximum number of bytes the parser may consume. If the JSON
+    /// text is longer, the parser returns
+    /// [`ParserError::Cancelled`](crate::parser::ParserError::Cancelled).
+    /// The budget is checked whenever the parser is asked for the next
+    /// event, so it may consume the rest of the current token first.
+    pub fn with_byte_budget(mut self, byte_budget: usize) -> Self {
+        self.options.byte_budget = Some(byte_budget);
+        self
+    }
+
+    /// Set the maximum time the parser may take, measured from the first
+    /// call of [`JsonParser::next_event()`](super::JsonParser::next_event()).
+    /// The time is checked periodically. Once it has elapsed, the parser
+    /// returns [`ParserError::Cancelled`](crate::parser::ParserError::Cancelled).
+    /// On targets without a clock (`wasm32-unknown-unknown`), the time
+    /// budget is ignored.
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.options.time_budget = Some(time_budget);
+        self
+    }
+
+    /// Set the maximum length of a string in bytes. The length is measured
+    /// before escape sequences are decoded. Longer strings result in
+    /// [`ParserError::StringTooLong`](crate::parser::ParserError::StringTooLong).
+    pub fn with_max_string_len(mut self, max_string_len: usize) -> Self {
+        self.options.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// Set the maximum length of a number in bytes (including its sign,
+    /// decimal point, and exponent). Longer numbers result in
+    /// [`ParserError::NumberTooLong`](crate::parser::ParserError::NumberTooLong)
+    /// as soon as the limit is exceeded, so huge digit runs are not
+    /// collected before they are rejected.
+    pub fn with_max_number_len(mut self, max_number_len: usize) -> Self {
+        self.options.max_number_len = Some(max_number_len);
+        self
+    }
+
+    /// Set the maximum number of events the parser may produce (not
+    /// counting [`JsonEvent::NeedMoreInput`](crate::JsonEvent::NeedMoreInput)).
+    /// Exceeding it results in
+    /// [`ParserError::TooManyTokens`](crate::parser::ParserError::TooManyTokens).
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.options.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the maximum total length in bytes of all field names, strings,
+    /// and numbers in one top-level value (i.e. one document in streaming
+    /// mode). Lengths are measured in the JSON text, including the quotes
+    /// of strings. Exceeding the limit results in
+    /// [`ParserError::ValuesTooLarge`](crate::parser::ParserError::ValuesTooLarge).
+    /// Unlike [`Self::with_max_string_len()`], this also protects against
+    /// documents consisting of many strings that are each below the limit.
+    pub fn with_max_total_value_len(mut self, max_total_value_len: usize) -> Self {
+        self.options.max_total_value_len = Some(max_total_value_len);
+        self
+    }
+
+    /// Reject objects with duplicate keys with
+    /// [`ParserError::DuplicateKey`](crate::parser::ParserError::DuplicateKey).
+    /// Keys are compared after escape sequences have been decoded. This
+    /// option has no effect in validation-only mode (see
+    /// [`Self::with_validate_only()`]).
+    pub fn with_reject_duplicate_keys(mut self, reject_duplicate_keys: bool) -> Self {
+        self.options.reject_duplicate_keys = reject_duplicate_keys;
+        self
+    }
+
+    /// Accept numbers with leading zeros such as `0123` or `-007.5`, which
+    /// are not valid JSON. They are reported as regular
+    /// [`JsonEvent::ValueInt`](crate::JsonEvent::ValueInt) or
+    /// [`JsonEvent::ValueFloat`](crate::JsonEvent::ValueFloat) events and
+    /// interpreted as decimal numbers.
+    pub fn with_allow_leading_zeros(mut self, allow_leading_zeros: bool) -> Self {
+        self.options.allow_leading_zeros = allow_leading_zeros;
+        self
+    }
+
+    /// Accept numbers with a leading plus sign such as `+42`, which are not
+    /// valid JSON. The plus sign is ignored and the number is reported as a
+    /// regular [`JsonEvent::ValueInt`](crate::JsonEvent::ValueInt) or
+    /// [`JsonEvent::ValueFloat`](crate::JsonEvent::ValueFloat) event.
+    ///
+    /// ```rust
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::options::JsonParserOptionsBuilder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let feeder = SliceJsonFeeder::new(b"[+42, 007]");
+    /// let mut parser = JsonParser::new_with_options(
+    ///     feeder,
+    ///     JsonParserOptionsBuilder::default()
+    ///         .with_allow_leading_plus(true)
+    ///         .with_allow_leading_zeros(true)
+    ///         .build(),
+    /// );
+    ///
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    /// assert_eq!(parser.current_int::<i32>().unwrap(), 42);
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    /// assert_eq!(parser.current_int::<i32>().unwrap(), 7);
+    /// ```
+    pub fn with_allow_leading_plus(mut self, allow_leading_plus: bool) -> Self {
+        self.options.allow_leading_plus = allow_leading_plus;
+        self
+    }
+
+    /// Keep track of the field names and array indices leading to the
+    /// current value so they can be retrieved with
+    /// [`JsonParser::current_parent_field()`](super::JsonParser::current_parent_field())
+    /// and [`JsonParser::current_array_index()`](super::JsonParser::current_array_index()).
+    /// This requires copying every field name, so it is disabled by default.
+    /// It has no effect in validation-only mode (see
+    /// [`Self::with_validate_only()`]).
+    pub fn with_track_path(mut self, track_path: bool) -> Self {
+        self.options.track_path = track_path;
+        self
+    }
+
+    /// Set the maximum number of members per object. Larger objects result in
+    /// [`ParserError::ContainerTooLarge`](crate::parser::ParserError::ContainerTooLarge).
+    /// Use this to protect code that builds maps from the parsed objects.
+    pub fn with_max_object_len(mut self, max_object_len: usize) -> Self {
+        self.options.max_object_len = Some(max_object_len);
+        self
+    }
+
+    /// Set the maximum number of elements per array. Larger arrays result in
+    /// [`ParserError::ContainerTooLarge`](crate::parser::ParserError::ContainerTooLarge).
+    pub fn with_max_array_len(mut self, max_array_len: usize) -> Self {
+        self.options.max_array_len = Some(max_array_len);
+        self
+    }
+
+    /// Stop parsing right after the first complete top-level value and
+    /// report the end of the JSON text instead of reading the bytes that
+    /// follow (which would otherwise result in a syntax error). Afterwards,
+    /// [`JsonParser::parsed_bytes()`](super::JsonParser::parsed_bytes())
+    /// returns exactly the number of bytes that belong to the value
+    /// (including leading white space), and the feeder still holds the rest
+    /// of the input. This is useful for protocols that put a JSON header in
+    /// front of other data. This option takes precedence over streaming
+    /// mode.
+    ///
+    /// A top-level number ends at the first byte that cannot be part of it.
+    /// This byte has to be read, so it is kept by the parser and can be
+    /// retrieved with [`JsonParser::remaining()`](super::JsonParser::remaining()).
+    ///
+    /// ```rust
+    /// use actson::feeder::{JsonFeeder, SliceJsonFeeder};
+    /// use actson::options::JsonParserOptionsBuilder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let input = b"{\"len\": 3}\x01\x02\x03";
+    /// let mut parser = JsonParser::new_with_options(
+    ///     SliceJsonFeeder::new(input),
+    ///     JsonParserOptionsBuilder::default()
+    ///         .with_allow_trailing_data(true)
+    ///         .build(),
+    /// );
+    ///
+    /// while parser.next_event().unwrap().is_some() {}
+    /// assert_eq!(parser.parsed_bytes(), 10);
+    /// assert_eq!(parser.feeder.peek_input(), b"\x01\x02\x03");
+    /// ```
+    pub fn with_allow_trailing_data(mut self, allow_trailing_data: bool) -> Self {
+        self.options.allow_trailing_data = allow_trailing_data;
+        self
+    }
+
     /// Create a new [`JsonParserOptions`] object
     pub fn build(self) -> JsonParserOptions {
         self.options
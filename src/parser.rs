@@ -1,16 +1,49 @@
 use std::{
-    collections::VecDeque,
+    borrow::Cow,
+    cell::OnceCell,
+    collections::HashSet,
+    fmt,
+    io::{self, Write},
+    iter::FusedIterator,
     num::ParseFloatError,
+    ops::{Deref, Range},
     str::{from_utf8, Utf8Error},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use crate::{feeder::JsonFeeder, options::JsonParserOptions, JsonEvent};
+use crate::{
+    base64::Base64Decoder,
+    cancel::CancellationToken,
+    feeder::{JsonFeeder, PushJsonFeeder, SliceJsonFeeder},
+    intern::StringInterner,
+    number::JsonNumber,
+    options::{IntOverflowPolicy, JsonParserOptions, JsonParserOptionsBuilder},
+    progress::{Progress, ProgressReporter},
+    scan,
+    storage::{HeapStorage, ParserStorage, PooledStorage, StoragePool},
+    unescape::{unescape, unescape_in_place},
+    BorrowedJsonEvent, JsonEvent, OwnedJsonEvent, Span,
+};
 use btoi::ParseIntegerError;
-use num_traits::{CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Zero};
+use num_traits::{
+    Bounded, CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Saturating, Unsigned, Zero,
+};
 use thiserror::Error;
 
 const __: i8 = -1; // the universal error code
 
+/// Append the given decimal digits to `value`. Return `None` on overflow.
+#[inline]
+fn accumulate_digits(value: u64, digits: &[u8]) -> Option<u64> {
+    digits.iter().try_fold(value, |v, d| {
+        v.checked_mul(10)?.checked_add((d - b'0') as u64)
+    })
+}
+
+/// The number of bytes after which the time budget is checked again
+const DEADLINE_CHECK_INTERVAL: usize = 4096;
+
 // Characters are mapped into these 31 character classes. This allows for
 // a significant reduction in the size of the state transition table.
 const C_SPACE: i8 = 0; // space
@@ -146,6 +179,37 @@ const STATE_TRANSITION_TABLE: [i8; 992] = [
 /*null   N3*/  __,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,__,OK,__,__,__,__,__,__,__,__,__,
 ];
 
+/// Marks bytes that are not allowed anywhere in a JSON text (i.e.
+/// non-whitespace control characters) in [`BYTE_TRANSITION_TABLE`]
+const ILLEGAL: i8 = -10;
+
+/// The number of states in [`STATE_TRANSITION_TABLE`]
+const NUM_STATES: usize = STATE_TRANSITION_TABLE.len() / 32;
+
+/// [`STATE_TRANSITION_TABLE`] and [`ASCII_CLASS`] combined into a single
+/// table that maps each state and each byte directly to the next state or
+/// action. This saves the class lookup and the branches for non-ASCII and
+/// illegal bytes on the hot path. Illegal bytes map to [`ILLEGAL`] in every
+/// state.
+static BYTE_TRANSITION_TABLE: [[i8; 256]; NUM_STATES] = {
+    let mut table = [[__; 256]; NUM_STATES];
+    let mut state = 0;
+    while state < NUM_STATES {
+        let mut b = 0;
+        while b < 256 {
+            let class = if b >= 128 { C_ETC } else { ASCII_CLASS[b] };
+            table[state][b] = if class <= __ {
+                ILLEGAL
+            } else {
+                STATE_TRANSITION_TABLE[(state << 5) + class as usize]
+            };
+            b += 1;
+        }
+        state += 1;
+    }
+    table
+};
+
 /// These modes can be pushed on the stack.
 const MODE_ARRAY: i8 = 0;
 const MODE_DONE: i8 = 1;
@@ -172,8 +236,93 @@ pub enum InvalidFloatValueError {
     Float(#[from] ParseFloatError),
 }
 
+/// An error that can happen when advancing to the next value and reading it
+/// in one call (e.g. with [`JsonParser::next_str()`])
+#[derive(Error, Debug)]
+pub enum NextValueError {
+    /// The JSON text could not be parsed
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    /// The parser needs more input before it can produce the next event.
+    /// Provide more input to the feeder and call the function again.
+    #[error("the parser needs more input")]
+    NeedMoreInput,
+
+    /// The next event does not match the expected type of value. `None`
+    /// means that the end of the JSON text has been reached.
+    #[error("expected {expected}, found {found:?}")]
+    UnexpectedEvent {
+        expected: &'static str,
+        found: Option<JsonEvent>,
+    },
+
+    /// The next field has another name than expected (see
+    /// [`JsonParser::expect_field()`])
+    #[error("expected field `{expected}', found `{found}'")]
+    UnexpectedField { expected: String, found: String },
+
+    /// A JSON Pointer is neither empty nor starts with `/` (see e.g.
+    /// [`JsonParser::skip_to_pointer()`] or
+    /// [`LazyValue::pointer()`](crate::lazy::LazyValue::pointer()))
+    #[error("invalid JSON Pointer `{0}'")]
+    InvalidPointer(String),
+
+    /// The string value could not be read
+    #[error(transparent)]
+    String(#[from] InvalidStringValueError),
+
+    /// The integer value could not be read
+    #[error(transparent)]
+    Int(#[from] InvalidIntValueError),
+
+    /// The float value could not be read
+    #[error(transparent)]
+    Float(#[from] InvalidFloatValueError),
+}
+
+/// An error returned by [`JsonParser::finish()`]
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FinishError {
+    /// The JSON text could not be parsed (e.g. because it ended
+    /// prematurely or is followed by non-whitespace bytes)
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    /// The JSON text has not ended yet. The parser produced the given event
+    /// instead.
+    #[error("expected end of JSON text, found {0:?}")]
+    UnexpectedEvent(JsonEvent),
+
+    /// The feeder is not done yet, so more input may follow. Call
+    /// [`PushJsonFeeder::done()`](crate::feeder::PushJsonFeeder::done())
+    /// (or the equivalent method of your feeder) first.
+    #[error("the feeder is not done")]
+    FeederNotDone,
+
+    /// The feeder provided more input after the end of the JSON text had
+    /// been reached
+    #[error("unexpected input after the end of the JSON text")]
+    TrailingInput,
+}
+
+/// An error returned by [`JsonParser::rewind()`]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewindError {
+    /// The save point has been created after the parser's current position
+    #[error("the save point is ahead of the parser")]
+    Ahead,
+
+    /// The feeder has already discarded input that was consumed after the
+    /// save point had been created
+    #[error("the input since the save point is no longer buffered")]
+    NotBuffered,
+}
+
 /// An error that can happen during parsing
 #[derive(Error, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParserError {
     /// The JSON text contains an illegal byte (e.g. a non-whitespace control
     /// character)
@@ -190,14 +339,145 @@ pub enum ParserError {
     /// too many times (i.e. after the end of a valid JSON text was reached).
     #[error("nothing more to parse")]
     NoMoreInput,
+
+    /// The JSON text is nested deeper than the configured maximum stack
+    /// depth or than the parser's [`ParserStorage`] allows
+    #[error("maximum stack depth exceeded")]
+    MaxDepthExceeded,
+
+    /// A string or number is too large for the parser's fixed-size value
+    /// buffer (see [`FixedStorage`](crate::storage::FixedStorage))
+    #[error("value buffer is full")]
+    ValueBufferFull,
+
+    /// Parsing has been cancelled through a [`CancellationToken`] or because
+    /// the configured byte or time budget has been exceeded
+    #[error("parsing has been cancelled")]
+    Cancelled,
+
+    /// A string is longer than the configured maximum length
+    #[error("string exceeds maximum length")]
+    StringTooLong,
+
+    /// A number is longer than the configured maximum length
+    #[error("number exceeds maximum length")]
+    NumberTooLong,
+
+    /// The JSON text consists of more events than configured
+    #[error("maximum number of tokens exceeded")]
+    TooManyTokens,
+
+    /// An object contains the same key more than once and duplicate keys
+    /// are rejected
+    #[error("duplicate key in object")]
+    DuplicateKey,
+
+    /// An object has more members or an array has more elements than
+    /// configured
+    #[error("object or array exceeds maximum number of members")]
+    ContainerTooLarge,
+
+    /// The strings and numbers in a document are longer in total than
+    /// configured
+    #[error("total length of values exceeds maximum")]
+    ValuesTooLarge,
+}
+
+/// Counters describing the work a [`JsonParser`] has done so far (see
+/// [`JsonParser::stats()`])
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParserStats {
+    /// The number of bytes consumed from the feeder
+    pub bytes_consumed: usize,
+
+    /// The number of events returned by [`JsonParser::next_event()`] (not
+    /// counting [`JsonEvent::NeedMoreInput`])
+    pub events_emitted: usize,
+
+    /// The maximum nesting depth of objects and arrays reached so far
+    pub max_depth_reached: usize,
+
+    /// The number of field names and string values parsed
+    pub strings_decoded: usize,
+
+    /// The number of integer and float values parsed
+    pub numbers_decoded: usize,
+}
+
+/// An open object or array tracked if
+/// [`JsonParserOptionsBuilder::with_track_path()`] is enabled
+#[derive(Clone, Debug)]
+enum PathFrame {
+    Array {
+        /// The number of elements seen so far
+        len: usize,
+
+        /// The array's index in its parent array
+        index: Option<usize>,
+    },
+
+    Object {
+        /// The most recent field name
+        key: String,
+
+        /// The object's index in its parent array
+        index: Option<usize>,
+    },
+}
+
+/// A copy of the state of a [`JsonParser`] that can be restored with
+/// [`JsonParser::rewind()`]. Created by [`JsonParser::save_point()`].
+#[derive(Clone, Debug)]
+pub struct SavePoint {
+    stack: Vec<i8>,
+    buffer: Vec<u8>,
+    current_is_borrowed: bool,
+    borrowed_range: Range<usize>,
+    current_is_number: bool,
+    int_valid: bool,
+    int_value: u64,
+    int_negative: bool,
+    state: i8,
+    current_has_escapes: bool,
+    event1: JsonEvent,
+    event2: JsonEvent,
+    span1: Span,
+    span2: Span,
+    current_span: Span,
+    current_event: JsonEvent,
+    reached_end: bool,
+    token_start: usize,
+    token_end: usize,
+    parsed_bytes: usize,
+    putback_character: Option<u8>,
+    stats: ParserStats,
+    total_value_len: usize,
+    object_keys: Vec<HashSet<Vec<u8>>>,
+    path: Vec<PathFrame>,
+    current_path_len: usize,
+    current_array_index: Option<usize>,
+    container_lens: Vec<usize>,
+}
+
+impl SavePoint {
+    /// Return the number of bytes the parser had consumed from its feeder
+    /// when the save point was created
+    fn fed_bytes(&self) -> usize {
+        self.parsed_bytes + self.putback_character.is_some() as usize
+    }
 }
 
 /// A non-blocking, event-based JSON parser.
-pub struct JsonParser<T> {
+///
+/// The parser keeps its stack and the contents of the current string or
+/// number in a [`ParserStorage`]. By default, this is a [`HeapStorage`].
+pub struct JsonParser<T, S = HeapStorage> {
     pub feeder: T,
 
-    /// The stack containing the current modes
-    stack: VecDeque<i8>,
+    /// The stack containing the current modes and the buffer that collects
+    /// all characters if the current state is ST (String), IN (Integer),
+    /// FR (Fraction) or the like
+    storage: S,
 
     /// The maximum number of modes on the stack
     depth: usize,
@@ -206,12 +486,54 @@ pub struct JsonParser<T> {
     /// handle a stream of multiple JSON values
     streaming: bool,
 
+    /// Specifies how integers that do not fit into the requested type
+    /// should be handled
+    int_overflow_policy: IntOverflowPolicy,
+
+    /// `true` if the parser should only check the JSON text for validity
+    /// without collecting the values of strings and numbers
+    validate_only: bool,
+
+    /// `true` if string contents should be collected in
+    /// the value buffer. `false` if they are read directly from the
+    /// feeder's input (see [`JsonFeeder::consumed_input()`]) or if
+    /// [`Self::validate_only`] is enabled.
+    buffer_strings: bool,
+
+    /// `true` if the current string has not been collected in
+    /// the value buffer but can be found in the feeder's input at
+    /// [`Self::borrowed_range`]
+    current_is_borrowed: bool,
+
+    /// The position of the current string in the feeder's input if
+    /// [`Self::current_is_borrowed`] is `true`. For numbers, only the start
+    /// is recorded. The end is computed from [`Self::token_end`].
+    borrowed_range: Range<usize>,
+
+    /// `true` if the current token is a number
+    current_is_number: bool,
+
+    /// `true` if the current token is an integer and [`Self::int_value`]
+    /// holds its absolute value (i.e. it did not overflow)
+    int_valid: bool,
+
+    /// The absolute value of the current integer, accumulated while its
+    /// digits are parsed
+    int_value: u64,
+
+    /// `true` if the current integer is negative
+    int_negative: bool,
+
     /// The current state
     state: i8,
 
-    /// Collects all characters if the current state is ST (String),
-    /// IN (Integer), FR (Fraction) or the like
-    current_buffer: Vec<u8>,
+    /// `true` if the string in the value buffer contains at least
+    /// one escape sequence
+    current_has_escapes: bool,
+
+    /// The unescaped contents of the value buffer. Only computed on
+    /// demand if the current string contains escape sequences.
+    unescaped_buffer: OnceCell<Vec<u8>>,
 
     /// The first event returned by [`Self::parse()`]
     event1: JsonEvent,
@@ -219,12 +541,120 @@ pub struct JsonParser<T> {
     /// The second event returned by [`Self::parse()`]
     event2: JsonEvent,
 
+    /// The position of [`Self::event1`] in the JSON text
+    span1: Span,
+
+    /// The position of [`Self::event2`] in the JSON text
+    span2: Span,
+
+    /// The position of the event most recently returned by
+    /// [`Self::next_event()`]
+    current_span: Span,
+
+    /// The event most recently returned by [`Self::next_event()`] or
+    /// [`JsonEvent::NeedMoreInput`] if there is none
+    current_event: JsonEvent,
+
+    /// `true` if [`Self::peek_event()`] has reached the end of the JSON
+    /// text but [`Self::next_event()`] has not returned `None` yet
+    reached_end: bool,
+
+    /// The position where the current string, number, or keyword starts
+    token_start: usize,
+
+    /// The position right after the last byte of the current string,
+    /// number, or keyword
+    token_end: usize,
+
     /// Tracks the number of bytes that have been processed
     parsed_bytes: usize,
 
     /// A character that has been put back to be parsed at the next call
     /// of [`Self::next_event()`]
     putback_character: Option<u8>,
+
+    /// Counters returned by [`Self::stats()`]
+    stats: ParserStats,
+
+    /// A token that can be used to cancel parsing
+    cancellation_token: Option<CancellationToken>,
+
+    /// Invokes the progress callback if one has been registered
+    progress: Option<ProgressReporter>,
+
+    /// The maximum number of bytes the parser may consume
+    byte_budget: Option<usize>,
+
+    /// The maximum time the parser may take
+    time_budget: Option<Duration>,
+
+    /// The point in time when the parser has to stop if a
+    /// [`Self::time_budget`] has been configured. Initialized at the first
+    /// call of [`Self::next_event()`].
+    deadline: Option<Instant>,
+
+    /// The value of [`Self::parsed_bytes`] at which the
+    /// [`Self::deadline`] should be checked next
+    next_deadline_check: usize,
+
+    /// The maximum length of a string in bytes
+    max_string_len: Option<usize>,
+
+    /// The maximum number of events the parser may produce
+    max_tokens: Option<usize>,
+
+    /// The maximum length of a number in bytes
+    max_number_len: Option<usize>,
+
+    /// The maximum total length of all strings and numbers in one top-level
+    /// value
+    max_total_value_len: Option<usize>,
+
+    /// The total length of all strings and numbers in the current top-level
+    /// value so far (not counting the one currently being parsed)
+    total_value_len: usize,
+
+    /// `true` if objects with duplicate keys should be rejected
+    reject_duplicate_keys: bool,
+
+    /// The keys seen so far in each of the currently open objects if
+    /// [`Self::reject_duplicate_keys`] is enabled
+    object_keys: Vec<HashSet<Vec<u8>>>,
+
+    /// `true` if integers with leading zeros should be accepted
+    allow_leading_zeros: bool,
+
+    /// `true` if numbers with a leading plus sign should be accepted
+    allow_leading_plus: bool,
+
+    /// `true` if the parser should keep track of the field names and array
+    /// indices leading to the current value
+    track_path: bool,
+
+    /// `true` if the parser should stop after the first top-level value
+    allow_trailing_data: bool,
+
+    /// The currently open objects and arrays if [`Self::track_path`] is
+    /// enabled
+    path: Vec<PathFrame>,
+
+    /// The number of entries in [`Self::path`] that contain the event most
+    /// recently returned by [`Self::next_event()`]
+    current_path_len: usize,
+
+    /// The index of the event most recently returned by
+    /// [`Self::next_event()`] in its parent array
+    current_array_index: Option<usize>,
+
+    /// The maximum number of members per object
+    max_object_len: Option<usize>,
+
+    /// The maximum number of elements per array
+    max_array_len: Option<usize>,
+
+    /// The number of members or elements of each currently open object or
+    /// array if [`Self::max_object_len`] or [`Self::max_array_len`] is set
+    container_lens: Vec<usize>,
 }
 
 impl<T> JsonParser<T>
@@ -233,75 +663,144 @@ where
 {
     /// Create a new JSON parser using the given [`JsonFeeder`]
     pub fn new(feeder: T) -> Self {
-        JsonParser {
-            feeder,
-            stack: VecDeque::from([MODE_DONE]),
-            depth: 2048,
-            streaming: false,
-            state: GO,
-            current_buffer: vec![],
-            event1: JsonEvent::NeedMoreInput,
-            event2: JsonEvent::NeedMoreInput,
-            parsed_bytes: 0,
-            putback_character: None,
-        }
+        Self::new_with_options(feeder, JsonParserOptions::default())
     }
 
     /// Create a new JSON parser using the given [`JsonFeeder`] and with a
     /// defined maximum stack depth
     #[deprecated(since = "1.1.0", note = "use `new_with_options` instead")]
     pub fn new_with_max_depth(feeder: T, max_depth: usize) -> Self {
-        JsonParser {
+        Self::new_with_options(
             feeder,
-            stack: VecDeque::from([MODE_DONE]),
-            depth: max_depth,
-            streaming: false,
-            state: GO,
-            current_buffer: vec![],
-            event1: JsonEvent::NeedMoreInput,
-            event2: JsonEvent::NeedMoreInput,
-            parsed_bytes: 0,
-            putback_character: None,
-        }
+            JsonParserOptionsBuilder::default()
+                .with_max_depth(max_depth)
+                .build(),
+        )
     }
 
     /// Create a new JSON parser using the given [`JsonFeeder`] and
     /// [`JsonParserOptions`]
     pub fn new_with_options(feeder: T, options: JsonParserOptions) -> Self {
+        Self::new_with_storage(feeder, HeapStorage::default(), options)
+    }
+}
+
+impl<T> JsonParser<T, PooledStorage>
+where
+    T: JsonFeeder,
+{
+    /// Create a new JSON parser using the given [`JsonFeeder`] and a
+    /// storage taken from the given pool, to which it is returned when the
+    /// parser is dropped. See [`StoragePool`].
+    pub fn new_in(feeder: T, pool: &StoragePool) -> Self {
+        Self::new_in_with_options(feeder, pool, JsonParserOptions::default())
+    }
+
+    /// Create a new JSON parser using the given [`JsonFeeder`], a storage
+    /// taken from the given pool, and the given [`JsonParserOptions`]
+    pub fn new_in_with_options(feeder: T, pool: &StoragePool, options: JsonParserOptions) -> Self {
+        Self::new_with_storage(feeder, pool.get(), options)
+    }
+}
+
+impl<T, S> JsonParser<T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    /// Create a new JSON parser using the given [`JsonFeeder`],
+    /// [`ParserStorage`], and [`JsonParserOptions`]. See the
+    /// [`storage`](crate::storage) module for an example.
+    pub fn new_with_storage(feeder: T, mut storage: S, options: JsonParserOptions) -> Self {
+        let buffer_strings =
+            !options.validate_only && (S::UNESCAPE_IN_PLACE || feeder.consumed_input().is_none());
+        storage.stack_push(MODE_DONE);
         JsonParser {
             feeder,
-            stack: VecDeque::from([MODE_DONE]),
+            storage,
             depth: options.max_depth,
             streaming: options.streaming,
+            int_overflow_policy: options.int_overflow_policy,
+            validate_only: options.validate_only,
+            buffer_strings,
+            current_is_borrowed: false,
+            borrowed_range: 0..0,
+            current_is_number: false,
+            int_valid: false,
+            int_value: 0,
+            int_negative: false,
             state: GO,
-            current_buffer: vec![],
+            current_has_escapes: false,
+            unescaped_buffer: OnceCell::new(),
             event1: JsonEvent::NeedMoreInput,
             event2: JsonEvent::NeedMoreInput,
+            span1: Span::default(),
+            span2: Span::default(),
+            current_span: Span::default(),
+            current_event: JsonEvent::NeedMoreInput,
+            reached_end: false,
+            token_start: 0,
+            token_end: 0,
             parsed_bytes: 0,
             putback_character: None,
+            stats: ParserStats::default(),
+            cancellation_token: None,
+            progress: None,
+            byte_budget: options.byte_budget,
+            time_budget: options.time_budget,
+            deadline: None,
+            next_deadline_check: 0,
+            max_string_len: options.max_string_len,
+            max_tokens: options.max_tokens,
+            max_number_len: options.max_number_len,
+            max_total_value_len: options.max_total_value_len,
+            total_value_len: 0,
+            reject_duplicate_keys: options.reject_duplicate_keys && !options.validate_only,
+            object_keys: Vec::new(),
+            allow_leading_zeros: options.allow_leading_zeros,
+            allow_leading_plus: options.allow_leading_plus,
+            track_path: options.track_path && !options.validate_only,
+            allow_trailing_data: options.allow_trailing_data,
+            path: Vec::new(),
+            current_path_len: 0,
+            current_array_index: None,
+            max_object_len: options.max_object_len,
+            max_array_len: options.max_array_len,
+            container_lens: Vec::new(),
         }
     }
 
-    /// Push to the stack. Return `false` if the maximum stack depth has been
-    /// exceeded.
-    fn push(&mut self, mode: i8) -> bool {
-        if self.stack.len() >= self.depth {
-            return false;
+    /// Push to the stack. Return an error if the maximum stack depth has
+    /// been exceeded.
+    fn push(&mut self, mode: i8) -> Result<(), ParserError> {
+        if self.storage.stack_len() >= self.depth || !self.storage.stack_push(mode) {
+            return Err(ParserError::MaxDepthExceeded);
         }
-        self.stack.push_back(mode);
-        true
+        // the bottom of the stack is always MODE_DONE
+        let depth = self.storage.stack_len() - 1;
+        self.stats.max_depth_reached = self.stats.max_depth_reached.max(depth);
+        Ok(())
     }
 
     /// Pop the stack, assuring that the current mode matches the expectation.
     /// Return `false` if there is underflow or if the modes mismatch.
     fn pop(&mut self, mode: i8) -> bool {
-        if self.stack.is_empty() || *self.stack.back().unwrap() != mode {
+        if self.storage.stack_last() != Some(mode) {
             return false;
         }
-        self.stack.pop_back();
+        self.storage.stack_pop();
         true
     }
 
+    /// Append bytes to the current string or number
+    #[inline]
+    fn buffer_extend(&mut self, bytes: &[u8]) -> Result<(), ParserError> {
+        if !self.storage.buffer_extend(bytes) {
+            return Err(ParserError::ValueBufferFull);
+        }
+        Ok(())
+    }
+
     /// Get the next input character either from [`Self::putback_character`] or
     /// from [`Self::feeder`]
     fn get_next_input(&mut self) -> Option<u8> {
@@ -321,30 +820,182 @@ where
         self.parsed_bytes -= 1;
     }
 
+    /// Register a token that can be used to cancel parsing from another
+    /// thread or task (see the [`cancel`](crate::cancel) module)
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation_token = Some(token);
+    }
+
+    /// Register a callback that is invoked every time the parser has
+    /// consumed at least `interval` more bytes and once more when it has
+    /// reached the end of the JSON text. `total_bytes` is the length of the
+    /// input if it is known and is passed on to the callback. See the
+    /// [`progress`](crate::progress) module.
+    pub fn set_progress_callback<F>(
+        &mut self,
+        interval: usize,
+        total_bytes: Option<usize>,
+        callback: F,
+    ) where
+        F: FnMut(Progress) + Send + 'static,
+    {
+        self.progress = Some(ProgressReporter::new(
+            interval,
+            total_bytes,
+            Box::new(callback),
+        ));
+    }
+
+    /// Check if parsing has been cancelled or if the byte or time budget
+    /// has been exceeded
+    fn check_cancelled(&mut self) -> Result<(), ParserError> {
+        if self
+            .cancellation_token
+            .as_ref()
+            .is_some_and(|t| t.is_cancelled())
+            || self.byte_budget.is_some_and(|b| self.parsed_bytes > b)
+        {
+            return Err(ParserError::Cancelled);
+        }
+
+        // there is no clock on wasm32-unknown-unknown, where
+        // `Instant::now()` panics
+        let has_clock = !cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+        if let Some(time_budget) = self.time_budget.filter(|_| has_clock) {
+            // getting the current time is comparatively expensive, so only
+            // check the deadline every few kilobytes
+            if self.parsed_bytes >= self.next_deadline_check {
+                let now = Instant::now();
+                let deadline = *self.deadline.get_or_insert(now + time_budget);
+                if now >= deadline {
+                    return Err(ParserError::Cancelled);
+                }
+                self.next_deadline_check = self.parsed_bytes + DEADLINE_CHECK_INTERVAL;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Call this method to proceed parsing the JSON text and to get the next
     /// event. The method returns [`Some(JsonEvent::NeedMoreInput)`](JsonEvent::NeedMoreInput)
     /// if it needs more input data from the feeder or `None` if the end of the
     /// JSON text has been reached.
     pub fn next_event(&mut self) -> Result<Option<JsonEvent>, ParserError> {
+        let r = match self.peek_event() {
+            Ok(Some(JsonEvent::NeedMoreInput)) => Some(JsonEvent::NeedMoreInput),
+            Ok(None) => {
+                self.reached_end = false;
+                if let Some(progress) = &mut self.progress {
+                    progress.finish(self.parsed_bytes);
+                }
+                None
+            }
+            Ok(Some(_)) => {
+                let r = self.event1;
+                self.event1 = self.event2;
+                self.event2 = JsonEvent::NeedMoreInput;
+                self.current_span = self.span1;
+                self.span1 = self.span2;
+                self.count_event(r)?;
+                if self.track_path {
+                    self.update_path(r);
+                }
+                return Ok(Some(r));
+            }
+            Err(e) => {
+                if matches!(e, ParserError::NoMoreInput) {
+                    self.current_span = Span::new(self.parsed_bytes, self.parsed_bytes);
+                }
+                return Err(e);
+            }
+        };
+        self.current_span = Span::new(self.parsed_bytes, self.parsed_bytes);
+        self.current_event = JsonEvent::NeedMoreInput;
+        Ok(r)
+    }
+
+    /// Get the next event without consuming it. The next call of this
+    /// method or of [`Self::next_event()`] returns the same event. Like
+    /// [`Self::next_event()`], the method returns
+    /// [`Some(JsonEvent::NeedMoreInput)`](JsonEvent::NeedMoreInput) if it
+    /// needs more input data from the feeder or `None` if the end of the JSON
+    /// text has been reached.
+    ///
+    /// To find the next event, the parser may have to parse the next string
+    /// or number, so the value accessors (e.g. [`Self::current_str()`]) may
+    /// already return the peeked event's value afterwards. Read the value of
+    /// the current event before peeking.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"{"name": "Elvis"}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+    /// assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::FieldName));
+    /// assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::FieldName));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+    /// assert_eq!(parser.current_str().unwrap(), "name");
+    /// ```
+    pub fn peek_event(&mut self) -> Result<Option<JsonEvent>, ParserError> {
+        if self.reached_end {
+            return Ok(None);
+        }
+        if self.allow_trailing_data
+            && self.state == OK
+            && self.event1 == JsonEvent::NeedMoreInput
+            && self.storage.stack_len() <= 1
+        {
+            // the first top-level value is complete and all of its events
+            // have been returned. Do not read any further.
+            if !self.pop(MODE_DONE) {
+                return Err(ParserError::NoMoreInput);
+            }
+            self.reached_end = true;
+            return Ok(None);
+        }
+        self.check_cancelled()?;
+        if let Some(progress) = &mut self.progress {
+            progress.update(self.parsed_bytes);
+        }
         while self.event1 == JsonEvent::NeedMoreInput {
+            if self.putback_character.is_none() {
+                self.consume_runs()?;
+            }
             if let Some(b) = self.get_next_input() {
                 self.parsed_bytes += 1;
-                if self.state == ST && (32..=127).contains(&b) && b != b'\\' && b != b'"' {
-                    // shortcut
-                    self.current_buffer.push(b);
+                if self.state == ST && b >= 32 && b != b'\\' && b != b'"' {
+                    // shortcut (UTF-8 validation is deferred until the
+                    // string is actually read)
+                    if self.buffer_strings {
+                        self.buffer_extend(&[b])?;
+                    }
                 } else {
                     self.parse(b)?;
                 }
+                if (ST..=U4).contains(&self.state) {
+                    // the string started after the opening quote at
+                    // `token_start`
+                    self.check_string_len(self.parsed_bytes - self.token_start - 1)?;
+                } else if (MI..=E3).contains(&self.state) {
+                    self.check_number_len(self.parsed_bytes - self.token_start)?;
+                }
             } else {
                 if self.feeder.is_done() {
                     if self.state != OK {
                         let r = self.state_to_event();
                         if r != JsonEvent::NeedMoreInput {
                             self.state = OK;
-                            return Ok(Some(r));
+                            self.event1 = r;
+                            self.span1 = self.token_span();
+                            break;
                         }
                     }
                     return if self.state == OK && self.pop(MODE_DONE) {
+                        self.reached_end = true;
                         Ok(None)
                     } else {
                         Err(ParserError::NoMoreInput)
@@ -353,42 +1004,280 @@ where
                 return Ok(Some(JsonEvent::NeedMoreInput));
             }
         }
+        Ok(Some(self.event1))
+    }
+
+    /// Update [`Self::path`] for an event returned by [`Self::next_event()`]
+    fn update_path(&mut self, e: JsonEvent) {
+        match e {
+            JsonEvent::FieldName => {
+                if let Some(PathFrame::Object { key, .. }) = self.path.last_mut() {
+                    // reuse the previous key's allocation
+                    let mut k = std::mem::take(key);
+                    k.clear();
+                    k.push_str(&String::from_utf8_lossy(self.current_unescaped()));
+                    if let Some(PathFrame::Object { key, .. }) = self.path.last_mut() {
+                        *key = k;
+                    }
+                }
+                // the field name is part of the object but its parent field
+                // is the one of the object
+                self.current_path_len = self.path.len().saturating_sub(1);
+                self.current_array_index = None;
+            }
+
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                self.current_array_index = match self.path.pop() {
+                    Some(PathFrame::Array { index, .. } | PathFrame::Object { index, .. }) => index,
+                    None => None,
+                };
+                self.current_path_len = self.path.len();
+            }
+
+            JsonEvent::NeedMoreInput => {}
+
+            _ => {
+                self.current_path_len = self.path.len();
+                self.current_array_index = match self.path.last_mut() {
+                    Some(PathFrame::Array { len, .. }) => {
+                        *len += 1;
+                        Some(*len - 1)
+                    }
+                    _ => None,
+                };
+                match e {
+                    JsonEvent::StartObject => self.path.push(PathFrame::Object {
+                        key: String::new(),
+                        index: self.current_array_index,
+                    }),
+                    JsonEvent::StartArray => self.path.push(PathFrame::Array {
+                        len: 0,
+                        index: self.current_array_index,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Get the name of the innermost field that contains the event most
+    /// recently returned by [`Self::next_event()`]. For a value inside an
+    /// object, this is the value's field name. For a value inside an array,
+    /// it is the field name of the array (or of the innermost object
+    /// containing it). For [`JsonEvent::FieldName`], it is the field that
+    /// contains the object. Returns `None` if there is no such field or if
+    /// [`JsonParserOptionsBuilder::with_track_path()`] is not enabled.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::options::JsonParserOptionsBuilder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"{"items": [10, 20, 30]}"#.as_bytes();
+    /// let mut parser = JsonParser::new_with_options(
+    ///     SliceJsonFeeder::new(json),
+    ///     JsonParserOptionsBuilder::default()
+    ///         .with_track_path(true)
+    ///         .build(),
+    /// );
+    ///
+    /// while let Some(e) = parser.next_event().unwrap() {
+    ///     if e == JsonEvent::ValueInt && parser.current_int::<i32>().unwrap() == 30 {
+    ///         assert_eq!(parser.current_parent_field(), Some("items"));
+    ///         assert_eq!(parser.current_array_index(), Some(2));
+    ///     }
+    /// }
+    /// ```
+    pub fn current_parent_field(&self) -> Option<&str> {
+        self.path[..self.current_path_len]
+            .iter()
+            .rev()
+            .find_map(|f| match f {
+                PathFrame::Object { key, .. } => Some(key.as_str()),
+                PathFrame::Array { .. } => None,
+            })
+    }
+
+    /// Get the index of the event most recently returned by
+    /// [`Self::next_event()`] in its parent array. For
+    /// [`JsonEvent::EndObject`] and [`JsonEvent::EndArray`], this is the
+    /// index of the object or array that has just ended. Returns `None` if
+    /// the event is not an element of an array or if
+    /// [`JsonParserOptionsBuilder::with_track_path()`] is not enabled.
+    pub fn current_array_index(&self) -> Option<usize> {
+        self.current_array_index
+    }
+
+    /// Update [`Self::stats`] for an event returned by [`Self::next_event()`]
+    /// and check if the maximum number of tokens has been exceeded
+    fn count_event(&mut self, e: JsonEvent) -> Result<(), ParserError> {
+        self.current_event = e;
+        self.stats.events_emitted += 1;
+        if self
+            .max_tokens
+            .is_some_and(|m| self.stats.events_emitted > m)
+        {
+            return Err(ParserError::TooManyTokens);
+        }
+        match e {
+            JsonEvent::FieldName | JsonEvent::ValueString => self.stats.strings_decoded += 1,
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                self.stats.numbers_decoded += 1;
+                if self
+                    .max_number_len
+                    .is_some_and(|m| self.current_span.len() > m)
+                {
+                    return Err(ParserError::NumberTooLong);
+                }
+            }
+            _ => {}
+        }
+        if let Some(max) = self.max_total_value_len {
+            if matches!(
+                e,
+                JsonEvent::FieldName
+                    | JsonEvent::ValueString
+                    | JsonEvent::ValueInt
+                    | JsonEvent::ValueFloat
+            ) {
+                self.total_value_len += self.current_span.len();
+                if self.total_value_len > max {
+                    return Err(ParserError::ValuesTooLarge);
+                }
+            }
+            if !matches!(
+                e,
+                JsonEvent::StartObject | JsonEvent::StartArray | JsonEvent::FieldName
+            ) && self.storage.stack_len() <= 1
+            {
+                // the top-level value is complete (or only its closing
+                // brackets are left)
+                self.total_value_len = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the length of the raw contents of the string that is currently
+    /// being parsed, so that oversized strings are rejected before they have
+    /// been buffered completely
+    fn check_string_len(&self, len: usize) -> Result<(), ParserError> {
+        if self.max_string_len.is_some_and(|m| len > m) {
+            return Err(ParserError::StringTooLong);
+        }
+        self.check_total_value_len(len)
+    }
+
+    /// Check the length of the number that is currently being parsed, so
+    /// that oversized numbers are rejected before they have been buffered
+    /// completely
+    fn check_number_len(&self, len: usize) -> Result<(), ParserError> {
+        if self.max_number_len.is_some_and(|m| len > m) {
+            return Err(ParserError::NumberTooLong);
+        }
+        self.check_total_value_len(len)
+    }
 
-        let r = self.event1;
-        self.event1 = self.event2;
-        self.event2 = JsonEvent::NeedMoreInput;
+    /// Check if the value that is currently being parsed would exceed the
+    /// total length of values allowed in the current document
+    fn check_total_value_len(&self, len: usize) -> Result<(), ParserError> {
+        if self
+            .max_total_value_len
+            .is_some_and(|m| self.total_value_len + len > m)
+        {
+            return Err(ParserError::ValuesTooLarge);
+        }
+        Ok(())
+    }
 
-        Ok(Some(r))
+    /// Consume a run of bytes from the feeder in bulk if the current state
+    /// allows it. Such runs can be white space between tokens, the contents
+    /// of strings that do not need special treatment, or the digits of
+    /// numbers. They do not change the state and do not produce events.
+    fn consume_runs(&mut self) -> Result<(), ParserError> {
+        let input = self.feeder.peek_input();
+        if input.is_empty() {
+            return Ok(());
+        }
+        let n = match self.state {
+            GO..=AR => scan::whitespace_run_len(input),
+            ST => {
+                let n = scan::string_run_len(input);
+                // the string started after the opening quote at `token_start`
+                self.check_string_len(self.parsed_bytes + n - self.token_start - 1)?;
+                if self.buffer_strings && !self.storage.buffer_extend(&input[..n]) {
+                    return Err(ParserError::ValueBufferFull);
+                }
+                n
+            }
+            IN | FR | E3 => {
+                let n = scan::digit_run_len(input);
+                self.check_number_len(self.parsed_bytes + n - self.token_start)?;
+                if self.validate_only {
+                    // nothing to collect
+                } else if !self.current_is_borrowed && !self.storage.buffer_extend(&input[..n]) {
+                    return Err(ParserError::ValueBufferFull);
+                }
+                if self.int_valid {
+                    match accumulate_digits(self.int_value, &input[..n]) {
+                        Some(v) => self.int_value = v,
+                        None => self.int_valid = false,
+                    }
+                }
+                self.token_end = self.parsed_bytes + n;
+                n
+            }
+            _ => 0,
+        };
+        if n > 0 {
+            self.feeder.consume_input(n);
+            self.parsed_bytes += n;
+        }
+        Ok(())
     }
 
     /// This function is called for each character (or partial character) in the
     /// JSON text. It will set [`self::event1`] and [`self::event2`] accordingly.
     /// As a precondition, these fields should have a value of [`JsonEvent::NeedMoreInput`].
     fn parse(&mut self, next_char: u8) -> Result<(), ParserError> {
-        // determine the character's class.
-        let next_class;
-        if next_char >= 128 {
-            next_class = C_ETC;
-        } else {
-            next_class = ASCII_CLASS[next_char as usize];
-            if next_class <= __ {
-                return Err(ParserError::IllegalInput(next_char));
-            }
+        // Get the next state from the state transition table.
+        let mut next_state = self.transition(self.state, next_char);
+        if self.allow_trailing_data
+            && matches!(self.state, ZE | IN | FR | E3)
+            && !(MI..=E3).contains(&next_state)
+            && self.storage.stack_len() == 1
+            && self.storage.stack_last() == Some(MODE_DONE)
+        {
+            // a top-level number ends at the first byte that does not belong
+            // to it. Put back this byte so it will not be consumed.
+            next_state = OK;
+            self.put_back(next_char);
+        }
+        if next_state == ILLEGAL {
+            return Err(ParserError::IllegalInput(next_char));
         }
 
-        // Get the next state from the state transition table.
-        let mut next_state =
-            STATE_TRANSITION_TABLE[((self.state as usize) << 5) + next_class as usize];
+        if self.max_array_len.is_some()
+            && matches!(self.state, AR | VA)
+            && !matches!(next_state, AR | VA | -7 | __)
+            && self.storage.stack_last() == Some(MODE_ARRAY)
+        {
+            // a new array element starts
+            self.count_member(self.max_array_len)?;
+        }
 
         // Try to recover if in streaming mode.
         if next_state == RC {
-            if self.streaming && self.stack.len() == 1 && *self.stack.back().unwrap() == MODE_DONE {
+            if self.streaming
+                && self.storage.stack_len() == 1
+                && self.storage.stack_last() == Some(MODE_DONE)
+            {
                 // Streaming is enabled and we're in a state where we can handle
                 // another JSON value.
                 if self.state == OK {
                     // The previous value has been converted to an event. Try
                     // again to get the next state but start from the GO state.
-                    next_state = STATE_TRANSITION_TABLE[((GO as usize) << 5) + next_class as usize];
+                    next_state = self.transition(GO, next_char);
                 } else {
                     // Switch to the OK state to convert the current value into
                     // an event. Put back the character so it will be parsed again.
@@ -403,22 +1292,73 @@ where
         }
 
         if next_state >= 0 {
-            if (ST..=E3).contains(&next_state) {
+            if next_state >= ST {
+                // the current byte belongs to a string, number, or keyword
+                if self.state < ST {
+                    self.token_start = self.parsed_bytes - 1;
+                }
+                self.token_end = self.parsed_bytes;
+            } else if next_state == OK && matches!(self.state, T3 | F4 | N3) {
+                // last byte of a keyword
+                self.token_end = self.parsed_bytes;
+            }
+
+            if (ST..=E3).contains(&next_state) && !self.validate_only {
                 // According to 'STATE_TRANSITION_TABLE', we don't need to check
                 // for "state <= E3". There is no way we can get here without
                 // 'state' being less than or equal to E3.
                 // if state >= ST && state <= E3 {
                 if self.state >= ST {
-                    self.current_buffer.push(next_char);
+                    if !self.current_is_borrowed {
+                        self.buffer_extend(&[next_char])?;
+                    }
+                    if next_state == ES {
+                        self.current_has_escapes = true;
+                    } else if self.int_valid {
+                        if matches!(next_state, ZE | IN) {
+                            match accumulate_digits(self.int_value, &[next_char]) {
+                                Some(v) => self.int_value = v,
+                                None => self.int_valid = false,
+                            }
+                        } else {
+                            // the number is not an integer
+                            self.int_valid = false;
+                        }
+                    }
                 } else {
-                    self.current_buffer.clear();
-                    if next_state != ST {
-                        self.current_buffer.push(next_char);
+                    self.storage.buffer_truncate(0);
+                    if self.current_has_escapes {
+                        self.current_has_escapes = false;
+                        self.unescaped_buffer.take();
+                    }
+                    self.current_is_number = next_state != ST;
+                    self.current_is_borrowed = !self.buffer_strings;
+                    if self.current_is_borrowed {
+                        let start = self.feeder.consumed_input().map_or(0, |i| i.len());
+                        self.borrowed_range = if self.current_is_number {
+                            // the number's first character has already been
+                            // consumed
+                            start - 1..start - 1
+                        } else {
+                            start..start
+                        };
+                    } else if self.current_is_number && next_char != b'+' {
+                        // a leading plus sign is only possible if
+                        // `allow_leading_plus` is enabled and is ignored
+                        self.buffer_extend(&[next_char])?;
                     }
+                    self.int_valid = self.current_is_number;
+                    self.int_negative = next_char == b'-';
+                    self.int_value = if next_char.is_ascii_digit() {
+                        (next_char - b'0') as u64
+                    } else {
+                        0
+                    };
                 }
             } else if next_state == OK {
                 // end of token identified, convert state to result
                 self.event1 = self.state_to_event();
+                self.span1 = self.token_span();
             }
 
             // Change the state.
@@ -431,6 +1371,51 @@ where
         Ok(())
     }
 
+    /// Look up the next state or action in the state transition table and
+    /// apply the lenient number options
+    #[inline]
+    fn transition(&self, state: i8, next_char: u8) -> i8 {
+        let next_state = BYTE_TRANSITION_TABLE[state as usize][next_char as usize];
+        if next_state == __ {
+            if self.allow_leading_plus && next_char == b'+' {
+                match state {
+                    GO | VA | AR => return MI,
+                    OK => return RC,
+                    _ => {}
+                }
+            } else if self.allow_leading_zeros && state == ZE && next_char.is_ascii_digit() {
+                return IN;
+            }
+        }
+        next_state
+    }
+
+    /// Increment the number of members of the innermost object or array and
+    /// check if it exceeds the given maximum
+    fn count_member(&mut self, max: Option<usize>) -> Result<(), ParserError> {
+        if let Some(len) = self.container_lens.last_mut() {
+            *len += 1;
+            if max.is_some_and(|m| *len > m) {
+                return Err(ParserError::ContainerTooLarge);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start counting the members of a new object or array
+    fn push_container_len(&mut self) {
+        if self.max_object_len.is_some() || self.max_array_len.is_some() {
+            self.container_lens.push(0);
+        }
+    }
+
+    /// Stop counting the members of the innermost object or array
+    fn pop_container_len(&mut self) {
+        if self.max_object_len.is_some() || self.max_array_len.is_some() {
+            self.container_lens.pop();
+        }
+    }
+
     /// Perform an action that changes the parser state
     fn perform_action(&mut self, action: i8) -> Result<(), ParserError> {
         match action {
@@ -439,8 +1424,13 @@ where
                 if !self.pop(MODE_KEY) {
                     return Err(ParserError::SyntaxError);
                 }
+                if self.reject_duplicate_keys {
+                    self.object_keys.pop();
+                }
+                self.pop_container_len();
                 self.state = OK;
                 self.event1 = JsonEvent::EndObject;
+                self.span1 = self.byte_span();
             }
 
             // }
@@ -448,11 +1438,20 @@ where
                 if !self.pop(MODE_OBJECT) {
                     return Err(ParserError::SyntaxError);
                 }
+                if self.reject_duplicate_keys {
+                    self.object_keys.pop();
+                }
+                self.pop_container_len();
                 match self.state_to_event() {
-                    JsonEvent::NeedMoreInput => self.event1 = JsonEvent::EndObject,
+                    JsonEvent::NeedMoreInput => {
+                        self.event1 = JsonEvent::EndObject;
+                        self.span1 = self.byte_span();
+                    }
                     e => {
                         self.event1 = e;
+                        self.span1 = self.token_span();
                         self.event2 = JsonEvent::EndObject;
+                        self.span2 = self.byte_span();
                     }
                 }
                 self.state = OK;
@@ -463,11 +1462,17 @@ where
                 if !self.pop(MODE_ARRAY) {
                     return Err(ParserError::SyntaxError);
                 }
+                self.pop_container_len();
                 match self.state_to_event() {
-                    JsonEvent::NeedMoreInput => self.event1 = JsonEvent::EndArray,
+                    JsonEvent::NeedMoreInput => {
+                        self.event1 = JsonEvent::EndArray;
+                        self.span1 = self.byte_span();
+                    }
                     e => {
                         self.event1 = e;
+                        self.span1 = self.token_span();
                         self.event2 = JsonEvent::EndArray;
+                        self.span2 = self.byte_span();
                     }
                 }
                 self.state = OK;
@@ -475,26 +1480,55 @@ where
 
             // {
             -6 => {
-                if !self.push(MODE_KEY) {
-                    return Err(ParserError::SyntaxError);
+                self.push(MODE_KEY)?;
+                if self.reject_duplicate_keys {
+                    self.object_keys.push(HashSet::new());
                 }
+                self.push_container_len();
                 self.state = OB;
                 self.event1 = JsonEvent::StartObject;
+                self.span1 = self.byte_span();
             }
 
             // [
             -5 => {
-                if !self.push(MODE_ARRAY) {
-                    return Err(ParserError::SyntaxError);
-                }
+                self.push(MODE_ARRAY)?;
+                self.push_container_len();
                 self.state = AR;
                 self.event1 = JsonEvent::StartArray;
+                self.span1 = self.byte_span();
             }
 
             // "
             -4 => {
-                if *self.stack.back().unwrap() == MODE_KEY {
-                    self.state = CO;
+                if self.current_is_borrowed {
+                    // the closing quote is the last consumed byte
+                    let end = self.feeder.consumed_input().map_or(0, |i| i.len() - 1);
+                    self.borrowed_range.end = end;
+                }
+                self.token_end = self.parsed_bytes;
+                self.span1 = self.token_span();
+                if self
+                    .max_string_len
+                    .is_some_and(|m| self.token_end - self.token_start - 2 > m)
+                {
+                    return Err(ParserError::StringTooLong);
+                }
+                if S::UNESCAPE_IN_PLACE && self.current_has_escapes {
+                    let len = unescape_in_place(self.storage.buffer_mut());
+                    self.storage.buffer_truncate(len);
+                    self.current_has_escapes = false;
+                }
+                if self.storage.stack_last() == Some(MODE_KEY) {
+                    if self.reject_duplicate_keys {
+                        let key = self.current_unescaped().to_vec();
+                        if let Some(keys) = self.object_keys.last_mut() {
+                            if !keys.insert(key) {
+                                return Err(ParserError::DuplicateKey);
+                            }
+                        }
+                    }
+                    self.state = CO;
                     self.event1 = JsonEvent::FieldName;
                 } else {
                     self.state = OK;
@@ -504,18 +1538,21 @@ where
 
             // ,
             -3 => {
-                match *self.stack.back().unwrap() {
+                match self.storage.stack_last().unwrap() {
                     MODE_OBJECT => {
                         // A comma causes a flip from object mode to key mode.
-                        if !self.pop(MODE_OBJECT) || !self.push(MODE_KEY) {
+                        if !self.pop(MODE_OBJECT) {
                             return Err(ParserError::SyntaxError);
                         }
+                        self.push(MODE_KEY)?;
                         self.event1 = self.state_to_event();
+                        self.span1 = self.token_span();
                         self.state = KE;
                     }
 
                     MODE_ARRAY => {
                         self.event1 = self.state_to_event();
+                        self.span1 = self.token_span();
                         self.state = VA;
                     }
 
@@ -528,9 +1565,11 @@ where
             // :
             -2 => {
                 // A colon causes a flip from key mode to object mode.
-                if !self.pop(MODE_KEY) || !self.push(MODE_OBJECT) {
+                if !self.pop(MODE_KEY) {
                     return Err(ParserError::SyntaxError);
                 }
+                self.push(MODE_OBJECT)?;
+                self.count_member(self.max_object_len)?;
                 self.state = VA;
             }
 
@@ -543,11 +1582,22 @@ where
         Ok(())
     }
 
+    /// Get the position of the current string, number, or keyword
+    fn token_span(&self) -> Span {
+        Span::new(self.token_start, self.token_end)
+    }
+
+    /// Get the position of the byte that has just been parsed
+    fn byte_span(&self) -> Span {
+        Span::new(self.parsed_bytes - 1, self.parsed_bytes)
+    }
+
     /// Converts the current parser state to a JSON event. Returns the JSON
     /// event or [`JsonEvent::NeedMoreInput`] if the current state does
     /// not produce a JSON event
     fn state_to_event(&self) -> JsonEvent {
         match self.state {
+            IN | ZE if !self.current_int_fits_policy() => JsonEvent::ValueFloat,
             IN | ZE => JsonEvent::ValueInt,
             FR..=E3 => JsonEvent::ValueFloat,
             T3 => JsonEvent::ValueTrue,
@@ -557,30 +1607,1540 @@ where
         }
     }
 
+    /// Check if the integer in the value buffer fits into the integer types
+    /// given by [`Self::int_overflow_policy`]. If it does not, it will be
+    /// reported as a float.
+    fn current_int_fits_policy(&self) -> bool {
+        match self.int_overflow_policy {
+            // i128::MAX has 39 digits
+            IntOverflowPolicy::Float => self.current_int_fits::<i128, u128>(39),
+            // i64::MAX has 19 digits
+            IntOverflowPolicy::Float64 => self.current_int_fits::<i64, u64>(19),
+            _ => true,
+        }
+    }
+
+    /// Check if the integer in the value buffer fits into either the signed
+    /// type `I` or the unsigned type `U`. Numbers with less than
+    /// `min_digits` digits always fit.
+    fn current_int_fits<I, U>(&self, min_digits: usize) -> bool
+    where
+        I: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul,
+        U: FromPrimitive + Zero + CheckedAdd + CheckedMul,
+    {
+        let buf = self.current_raw();
+        let digits = buf.strip_prefix(b"-").unwrap_or(buf);
+        if digits.len() < min_digits {
+            return true;
+        }
+        if digits.len() == buf.len() {
+            btoi::btou::<U>(digits).is_ok()
+        } else {
+            btoi::btoi::<I>(buf).is_ok()
+        }
+    }
+
     /// Get the value of the string that has just been parsed. Call this
     /// function after you've received [`JsonEvent::FieldName`](JsonEvent#variant.FieldName)
     /// or [`JsonEvent::ValueString`](JsonEvent#variant.ValueString).
+    ///
+    /// Escape sequences are decoded and the string's contents are validated
+    /// lazily the first time this function is called, so strings that are
+    /// never read do not have to pay for it.
     pub fn current_str(&self) -> Result<&str, InvalidStringValueError> {
-        Ok(from_utf8(&self.current_buffer)?)
+        if !self.current_has_escapes {
+            if let Some(s) = self.current_borrowed_str() {
+                return Ok(s);
+            }
+        }
+        Ok(from_utf8(self.current_unescaped())?)
+    }
+
+    /// Get the contents of the string that has just been parsed with escape
+    /// sequences decoded but without validating that they are UTF-8. This
+    /// allows binary or Latin-1 data that has been put into a JSON string
+    /// (which is not valid JSON) to be retrieved unchanged. Escape sequences
+    /// such as `\u00e9` are always decoded to UTF-8. Call this function
+    /// after you've received [`JsonEvent::FieldName`](JsonEvent#variant.FieldName)
+    /// or [`JsonEvent::ValueString`](JsonEvent#variant.ValueString).
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = b"[\"caf\xe9\\n\"]";
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueString));
+    /// assert!(parser.current_str().is_err());
+    /// assert_eq!(parser.current_bytes(), b"caf\xe9\n");
+    /// ```
+    pub fn current_bytes(&self) -> &[u8] {
+        self.current_unescaped()
+    }
+
+    /// Get the raw contents of the string that has just been parsed without
+    /// decoding escape sequences (e.g. `a\nb` instead of a string with a
+    /// line break). The surrounding quotes are not included. This is useful
+    /// for tools that re-emit the JSON text verbatim. Call this function
+    /// after you've received [`JsonEvent::FieldName`](JsonEvent#variant.FieldName)
+    /// or [`JsonEvent::ValueString`](JsonEvent#variant.ValueString). For
+    /// numbers, it returns the number as it appears in the JSON text.
+    ///
+    /// Note that a parser with a [`FixedStorage`](crate::storage::FixedStorage)
+    /// decodes escape sequences in place, so this function returns the same
+    /// as [`Self::current_str()`] in this case.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"["Bj\u00f6rn\n"]"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueString));
+    /// assert_eq!(parser.current_raw_str().unwrap(), r"Bj\u00f6rn\n");
+    /// assert_eq!(parser.current_str().unwrap(), "Bj\u{f6}rn\n");
+    /// ```
+    pub fn current_raw_str(&self) -> Result<&str, InvalidStringValueError> {
+        if let Some(s) = self.current_borrowed_str() {
+            return Ok(s);
+        }
+        Ok(from_utf8(self.current_raw())?)
+    }
+
+    /// Decode the base64-encoded string that has just been parsed and write
+    /// the decoded bytes to the given writer (see [`Base64Decoder`]). Escape
+    /// sequences (e.g. `\/`) are decoded first. Returns the writer.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"{"data": "AAEC\/w=="}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// while let Some(e) = parser.next_event().unwrap() {
+    ///     if e == JsonEvent::ValueString {
+    ///         let bytes = parser.current_base64_to(Vec::new()).unwrap();
+    ///         assert_eq!(bytes, vec![0, 1, 2, 255]);
+    ///     }
+    /// }
+    /// ```
+    pub fn current_base64_to<W: Write>(&self, writer: W) -> io::Result<W> {
+        let mut decoder = Base64Decoder::new(writer);
+        decoder.decode_chunk(self.current_unescaped())?;
+        decoder.finish()
+    }
+
+    /// Get the value of the string that has just been parsed as a shared
+    /// string. If the given [`StringInterner`] already contains a string
+    /// with the same raw contents, it is returned without decoding the
+    /// current string again. This is useful for object keys that repeat
+    /// frequently (e.g. in large arrays of records). See the
+    /// [`intern`](crate::intern) module for an example.
+    pub fn current_str_interned(
+        &self,
+        interner: &mut StringInterner,
+    ) -> Result<Arc<str>, InvalidStringValueError> {
+        interner.get_or_insert(self.current_raw(), || Ok(Arc::from(self.current_str()?)))
+    }
+
+    /// Get the raw (still escaped) contents of the current string or number
+    fn current_raw(&self) -> &[u8] {
+        if self.current_is_borrowed {
+            if let Some(input) = self.feeder.consumed_input() {
+                return self.borrowed_raw(input);
+            }
+        }
+        self.storage.buffer()
+    }
+
+    /// Get the raw contents of the current string or number from the given
+    /// input of the feeder if [`Self::current_is_borrowed`] is `true`
+    fn borrowed_raw<'b>(&self, input: &'b [u8]) -> &'b [u8] {
+        if self.current_is_number {
+            let start = self.borrowed_range.start;
+            let raw = &input[start..start + self.token_end - self.token_start];
+            // a leading plus sign is only possible if `allow_leading_plus`
+            // is enabled and is ignored
+            raw.strip_prefix(b"+").unwrap_or(raw)
+        } else {
+            &input[self.borrowed_range.clone()]
+        }
+    }
+
+    /// Get the raw contents of the current string or number from the
+    /// feeder's input without validating them again if the feeder
+    /// guarantees that its input is valid UTF-8 (see
+    /// [`JsonFeeder::consumed_str()`])
+    fn current_borrowed_str(&self) -> Option<&str> {
+        if !self.current_is_borrowed {
+            return None;
+        }
+        let raw = self.borrowed_raw(self.feeder.consumed_input()?);
+        let input = self.feeder.consumed_str()?;
+        let start = (raw.as_ptr() as usize).checked_sub(input.as_ptr() as usize)?;
+        input.get(start..start + raw.len())
+    }
+
+    /// Get the unescaped contents of the current string or number
+    fn current_unescaped(&self) -> &[u8] {
+        if !self.current_has_escapes {
+            return self.current_raw();
+        }
+        self.unescaped_buffer.get_or_init(|| {
+            let raw = self.current_raw();
+            let mut out = Vec::with_capacity(raw.len());
+            unescape(raw, &mut out);
+            out
+        })
     }
 
     /// Get the value of the integer that has just been parsed. Call this
     /// function after you've received [`JsonEvent::ValueInt`](JsonEvent#variant.ValueInt).
+    /// All primitive integer types including [`i128`] and [`u128`] are
+    /// supported. If the integer does not fit into the requested type, the
+    /// configured [`IntOverflowPolicy`] applies.
     pub fn current_int<I>(&self) -> Result<I, InvalidIntValueError>
     where
-        I: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul,
+        I: FromPrimitive + Zero + CheckedAdd + CheckedSub + CheckedMul + Saturating + Bounded,
+    {
+        if self.int_valid {
+            let i = if self.int_negative {
+                I::from_i128(-(self.int_value as i128))
+            } else {
+                I::from_u64(self.int_value)
+            };
+            if let Some(i) = i {
+                return Ok(i);
+            }
+        }
+
+        // the integer does not fit into the requested type or into a u64
+        if self.int_overflow_policy == IntOverflowPolicy::Saturate {
+            Ok(btoi::btoi_saturating(self.current_raw())?)
+        } else {
+            Ok(btoi::btoi(self.current_raw())?)
+        }
+    }
+
+    /// Get the value of the non-negative integer that has just been parsed.
+    /// Call this function after you've received
+    /// [`JsonEvent::ValueInt`](JsonEvent#variant.ValueInt). This is like
+    /// [`Self::current_int()`] but only supports unsigned types. Negative
+    /// integers result in an error (or 0 if the [`IntOverflowPolicy`] is
+    /// [`IntOverflowPolicy::Saturate`]).
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"[18446744073709551615, -1]"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    /// assert_eq!(parser.current_uint::<u64>().unwrap(), u64::MAX);
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    /// assert!(parser.current_uint::<u64>().is_err());
+    /// ```
+    pub fn current_uint<U>(&self) -> Result<U, InvalidIntValueError>
+    where
+        U: Unsigned + FromPrimitive + Zero + CheckedAdd + CheckedMul + Saturating + Bounded,
     {
-        Ok(btoi::btoi(&self.current_buffer)?)
+        // `-0` is the same as `0`
+        if self.int_valid && (!self.int_negative || self.int_value == 0) {
+            if let Some(u) = U::from_u64(self.int_value) {
+                return Ok(u);
+            }
+        }
+
+        let raw = self.current_raw();
+        if self.int_overflow_policy == IntOverflowPolicy::Saturate {
+            if raw.starts_with(b"-") {
+                return Ok(U::zero());
+            }
+            Ok(btoi::btou_saturating(raw)?)
+        } else {
+            Ok(btoi::btou(raw)?)
+        }
     }
 
     /// Get the value of the float that has just been parsed. Call this
     /// function after you've received [`JsonEvent::ValueFloat`](JsonEvent#variant.ValueFloat).
     pub fn current_float(&self) -> Result<f64, InvalidFloatValueError> {
-        Ok(self.current_str()?.parse()?)
+        // numbers never contain escape sequences and consist of ASCII
+        // characters only, so the raw digits can be parsed directly
+        match fast_float2::parse(self.current_raw()) {
+            Ok(f) => Ok(f),
+            // let the standard library produce a meaningful error
+            Err(_) => Ok(self.current_str()?.parse()?),
+        }
+    }
+
+    /// Get the value of the number that has just been parsed in its
+    /// best-fitting representation. Call this function after you've received
+    /// [`JsonEvent::ValueInt`](JsonEvent#variant.ValueInt) or
+    /// [`JsonEvent::ValueFloat`](JsonEvent#variant.ValueFloat).
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonNumber, JsonParser};
+    ///
+    /// let json = r#"[-1, 18446744073709551615, 2.5, 18446744073709551616]"#.as_bytes();
+    ///
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// let mut numbers = Vec::new();
+    /// while let Some(event) = parser.next_event().unwrap() {
+    ///     if matches!(event, JsonEvent::ValueInt | JsonEvent::ValueFloat) {
+    ///         numbers.push(format!("{:?}", parser.current_number().unwrap()));
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(numbers, vec![
+    ///     "I64(-1)",
+    ///     "U64(18446744073709551615)",
+    ///     "F64(2.5)",
+    ///     "Big(\"18446744073709551616\")",
+    /// ]);
+    /// ```
+    pub fn current_number(&self) -> Result<JsonNumber<'_>, InvalidFloatValueError> {
+        if self.int_valid {
+            if !self.int_negative {
+                return Ok(match i64::try_from(self.int_value) {
+                    Ok(i) => JsonNumber::I64(i),
+                    Err(_) => JsonNumber::U64(self.int_value),
+                });
+            }
+            if let Ok(i) = i64::try_from(-(self.int_value as i128)) {
+                return Ok(JsonNumber::I64(i));
+            }
+        }
+
+        Ok(JsonNumber::from_literal(self.current_raw_str()?)?)
+    }
+
+    /// Get the value of the boolean that has just been parsed. Call this
+    /// function after you've received [`JsonEvent::ValueTrue`] or
+    /// [`JsonEvent::ValueFalse`]. For all other events, the function returns
+    /// `None`. This allows code that extracts values generically to handle
+    /// both events in the same way.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"[true, false, null]"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let mut values = Vec::new();
+    /// while parser.next_event().unwrap().is_some() {
+    ///     values.push(parser.current_bool());
+    /// }
+    ///
+    /// assert_eq!(values, vec![None, Some(true), Some(false), None, None]);
+    /// ```
+    pub fn current_bool(&self) -> Option<bool> {
+        self.current_event.as_bool()
+    }
+
+    /// Describe the given event, which must be the event most recently
+    /// returned by [`Self::next_event()`], together with its value and its
+    /// position in the JSON text. The returned value implements
+    /// [`fmt::Display`] and is meant for logging and debugging. Long values
+    /// are truncated.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let mut lines = Vec::new();
+    /// while let Some(e) = parser.next_event().unwrap() {
+    ///     lines.push(parser.describe_event(e).to_string());
+    /// }
+    ///
+    /// assert_eq!(lines, vec![
+    ///     "StartObject at 0..1",
+    ///     "FieldName \"name\" at 1..7",
+    ///     "ValueString \"Elvis\" at 9..16",
+    ///     "FieldName \"age\" at 18..23",
+    ///     "ValueInt 42 at 25..27",
+    ///     "EndObject at 27..28",
+    /// ]);
+    /// ```
+    pub fn describe_event(&self, event: JsonEvent) -> EventDescription<'_> {
+        let value = match event {
+            JsonEvent::FieldName
+            | JsonEvent::ValueString
+            | JsonEvent::ValueInt
+            | JsonEvent::ValueFloat => Some(self.current_raw()),
+            _ => None,
+        };
+        EventDescription {
+            event,
+            value,
+            span: self.current_span,
+        }
+    }
+
+    /// Get the position of the event most recently returned by
+    /// [`Self::next_event()`] in the JSON text. For strings, the span
+    /// includes the quotes. For [`JsonEvent::NeedMoreInput`] and the end of
+    /// the JSON text, the span is empty.
+    pub fn current_span(&self) -> Span {
+        self.current_span
+    }
+
+    /// Release memory held by the parser's internal buffers beyond what
+    /// the current value needs. Buffers grow to the size of the largest
+    /// string or number (and the deepest nesting) seen so far and keep
+    /// their size afterwards. Call this between documents if a long-lived
+    /// parser has encountered an unusually large value. See also
+    /// [`Self::shrink_to()`].
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Release memory held by the parser's internal buffers beyond the
+    /// given capacity (in bytes for the value buffer and in entries for the
+    /// stacks) or beyond what the current value needs, whichever is larger.
+    /// This keeps enough memory for typical documents while returning the
+    /// excess allocated for unusually large ones.
+    ///
+    /// ```
+    /// use actson::feeder::PushJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let mut parser = JsonParser::new(PushJsonFeeder::new());
+    /// let long = format!("\"{}\"", "x".repeat(1000));
+    /// parser.feeder.push_bytes(long.as_bytes());
+    /// parser.feeder.done();
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueString));
+    /// assert_eq!(parser.current_str().unwrap().len(), 1000);
+    ///
+    /// // the current value stays available
+    /// parser.shrink_to(16);
+    /// assert_eq!(parser.current_str().unwrap().len(), 1000);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.storage.shrink_to(min_capacity);
+        if self
+            .unescaped_buffer
+            .get()
+            .is_some_and(|b| b.capacity() > min_capacity)
+        {
+            // the unescaped value is computed again on demand
+            self.unescaped_buffer.take();
+        }
+        for keys in &mut self.object_keys {
+            keys.shrink_to(min_capacity);
+        }
+        self.object_keys.shrink_to(min_capacity);
+        self.path.shrink_to(min_capacity);
+        self.container_lens.shrink_to(min_capacity);
+    }
+
+    /// Get counters describing the work the parser has done so far
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"{"a": [1, 2.5, "x"]}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// while parser.next_event().unwrap().is_some() {}
+    ///
+    /// let stats = parser.stats();
+    /// assert_eq!(stats.bytes_consumed, json.len());
+    /// assert_eq!(stats.events_emitted, 8);
+    /// assert_eq!(stats.max_depth_reached, 2);
+    /// assert_eq!(stats.strings_decoded, 2);
+    /// assert_eq!(stats.numbers_decoded, 2);
+    /// ```
+    pub fn stats(&self) -> ParserStats {
+        ParserStats {
+            bytes_consumed: self.parsed_bytes,
+            ..self.stats
+        }
+    }
+
+    /// Decode as many events as currently possible and append them together
+    /// with their positions in the JSON text to the given vector. Parsing
+    /// stops if the parser needs more input, in which case
+    /// [`JsonEvent::NeedMoreInput`] is appended as the last event, or if the
+    /// end of the JSON text has been reached. Returns the number of events
+    /// appended. If an error occurs, the events decoded up to this point
+    /// remain in the vector. Similar to [`Self::next_event()`], calling this
+    /// method again after the end of the JSON text has been reached results
+    /// in [`ParserError::NoMoreInput`].
+    ///
+    /// This amortizes the per-event overhead of [`Self::next_event()`] but
+    /// it is not possible to read the values of strings and numbers with
+    /// accessors like [`Self::current_str()`] anymore. Use the spans to
+    /// locate them in the input instead.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser, Span};
+    ///
+    /// let json = r#"{"name": "Elvis"}"#.as_bytes();
+    ///
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// let mut events = Vec::new();
+    /// parser.next_events(&mut events).unwrap();
+    ///
+    /// assert_eq!(events, vec![
+    ///     (JsonEvent::StartObject, Span::new(0, 1)),
+    ///     (JsonEvent::FieldName, Span::new(1, 7)),
+    ///     (JsonEvent::ValueString, Span::new(9, 16)),
+    ///     (JsonEvent::EndObject, Span::new(16, 17)),
+    /// ]);
+    /// ```
+    pub fn next_events(
+        &mut self,
+        events: &mut Vec<(JsonEvent, Span)>,
+    ) -> Result<usize, ParserError> {
+        let len = events.len();
+        while let Some(e) = self.next_event()? {
+            events.push((e, self.current_span));
+            if e == JsonEvent::NeedMoreInput {
+                break;
+            }
+        }
+        Ok(events.len() - len)
+    }
+
+    /// Advance to the next event and check that it is one of the expected
+    /// ones
+    fn next_expected(
+        &mut self,
+        expected: &'static str,
+        matches: impl Fn(JsonEvent) -> bool,
+    ) -> Result<JsonEvent, NextValueError> {
+        match self.next_event()? {
+            Some(JsonEvent::NeedMoreInput) => Err(NextValueError::NeedMoreInput),
+            Some(e) if matches(e) => Ok(e),
+            found => Err(NextValueError::UnexpectedEvent { expected, found }),
+        }
+    }
+
+    /// Advance to the next event, which must be
+    /// [`JsonEvent::ValueString`], and return the string's value. This is a
+    /// shortcut for [`Self::next_event()`] followed by
+    /// [`Self::current_str()`].
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::parser::NextValueError;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"["Elvis", 42, 2.5]"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    /// assert_eq!(parser.next_str().unwrap(), "Elvis");
+    /// assert_eq!(parser.next_i64().unwrap(), 42);
+    /// assert_eq!(parser.next_f64().unwrap(), 2.5);
+    /// assert!(matches!(
+    ///     parser.next_str(),
+    ///     Err(NextValueError::UnexpectedEvent { found: Some(JsonEvent::EndArray), .. })
+    /// ));
+    /// ```
+    ///
+    /// If the parser needs more input, the function returns
+    /// [`NextValueError::NeedMoreInput`]. In this case, no event has been
+    /// consumed and the function can be called again after more input has
+    /// been provided to the feeder.
+    pub fn next_str(&mut self) -> Result<&str, NextValueError> {
+        self.next_expected("string", |e| e == JsonEvent::ValueString)?;
+        Ok(self.current_str()?)
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::ValueInt`],
+    /// and return the integer's value (see [`Self::next_str()`])
+    pub fn next_i64(&mut self) -> Result<i64, NextValueError> {
+        self.next_expected("integer", |e| e == JsonEvent::ValueInt)?;
+        Ok(self.current_int()?)
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::ValueFloat`]
+    /// or [`JsonEvent::ValueInt`], and return the number's value as a float
+    /// (see [`Self::next_str()`])
+    pub fn next_f64(&mut self) -> Result<f64, NextValueError> {
+        self.next_expected("number", |e| {
+            matches!(e, JsonEvent::ValueFloat | JsonEvent::ValueInt)
+        })?;
+        Ok(self.current_float()?)
+    }
+
+    /// Advance to the next event, which must be
+    /// [`JsonEvent::StartObject`]. Like all `expect_*` functions, this
+    /// returns [`NextValueError::UnexpectedEvent`] describing the expected
+    /// and the actual event if they do not match and
+    /// [`NextValueError::NeedMoreInput`] if the parser needs more input (see
+    /// [`Self::next_str()`]).
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"{"name": "Elvis", "alive": false, "label": null}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// parser.expect_start_object().unwrap();
+    /// parser.expect_field("name").unwrap();
+    /// assert_eq!(parser.expect_string().unwrap(), "Elvis");
+    /// parser.expect_field("alive").unwrap();
+    /// assert!(!parser.expect_bool().unwrap());
+    /// parser.expect_field("label").unwrap();
+    /// parser.expect_null().unwrap();
+    /// parser.expect_end_object().unwrap();
+    /// ```
+    pub fn expect_start_object(&mut self) -> Result<(), NextValueError> {
+        self.next_expected("start of object", |e| e == JsonEvent::StartObject)?;
+        Ok(())
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::EndObject`]
+    /// (see [`Self::expect_start_object()`])
+    pub fn expect_end_object(&mut self) -> Result<(), NextValueError> {
+        self.next_expected("end of object", |e| e == JsonEvent::EndObject)?;
+        Ok(())
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::StartArray`]
+    /// (see [`Self::expect_start_object()`])
+    pub fn expect_start_array(&mut self) -> Result<(), NextValueError> {
+        self.next_expected("start of array", |e| e == JsonEvent::StartArray)?;
+        Ok(())
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::EndArray`]
+    /// (see [`Self::expect_start_object()`])
+    pub fn expect_end_array(&mut self) -> Result<(), NextValueError> {
+        self.next_expected("end of array", |e| e == JsonEvent::EndArray)?;
+        Ok(())
+    }
+
+    /// Advance to the next event, which must be a [`JsonEvent::FieldName`]
+    /// with the given name. Returns [`NextValueError::UnexpectedField`] if
+    /// the field has another name.
+    pub fn expect_field(&mut self, name: &str) -> Result<(), NextValueError> {
+        self.next_expected("field name", |e| e == JsonEvent::FieldName)?;
+        let found = self.current_str()?;
+        if found != name {
+            return Err(NextValueError::UnexpectedField {
+                expected: name.to_string(),
+                found: found.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Advance to the next event, which must be a
+    /// [`JsonEvent::ValueString`], and return the string's value. This is
+    /// the same as [`Self::next_str()`].
+    pub fn expect_string(&mut self) -> Result<&str, NextValueError> {
+        self.next_str()
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::ValueTrue`] or
+    /// [`JsonEvent::ValueFalse`], and return the boolean's value
+    pub fn expect_bool(&mut self) -> Result<bool, NextValueError> {
+        let e = self.next_expected("boolean", |e| {
+            matches!(e, JsonEvent::ValueTrue | JsonEvent::ValueFalse)
+        })?;
+        Ok(e == JsonEvent::ValueTrue)
+    }
+
+    /// Advance to the next event, which must be [`JsonEvent::ValueNull`]
+    pub fn expect_null(&mut self) -> Result<(), NextValueError> {
+        self.next_expected("null", |e| e == JsonEvent::ValueNull)?;
+        Ok(())
+    }
+
+    /// Advance to the next field of the current object. Returns a [`Field`]
+    /// that gives access to the field's name and to the parser, which is
+    /// positioned on the field's value. Returns `None` if the end of the
+    /// object has been reached and [`NextValueError::NeedMoreInput`] if the
+    /// parser needs more input (see [`Self::next_str()`]).
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let mut age = 0;
+    /// parser.expect_start_object().unwrap();
+    /// while let Some(field) = parser.next_field().unwrap() {
+    ///     match field.name() {
+    ///         "age" => age = field.value().next_i64().unwrap(),
+    ///         _ => field.value().skip_value().unwrap(),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(age, 42);
+    /// ```
+    pub fn next_field(&mut self) -> Result<Option<Field<'_, T, S>>, NextValueError> {
+        let e = self.next_expected("field name or end of object", |e| {
+            matches!(e, JsonEvent::FieldName | JsonEvent::EndObject)
+        })?;
+        if e == JsonEvent::EndObject {
+            return Ok(None);
+        }
+        // make sure the name is valid so Field::name() does not have to fail
+        self.current_str()?;
+        Ok(Some(Field { parser: self }))
+    }
+
+    /// Skip the next value including all nested values if it is an object
+    /// or an array. Returns [`NextValueError::NeedMoreInput`] if the parser
+    /// runs out of input. The value is then only partially skipped, so this
+    /// function is meant for feeders that provide all input at once such as
+    /// [`SliceJsonFeeder`].
+    pub fn skip_value(&mut self) -> Result<(), NextValueError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next_event()? {
+                Some(JsonEvent::NeedMoreInput) => return Err(NextValueError::NeedMoreInput),
+                Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+                Some(JsonEvent::EndObject | JsonEvent::EndArray) if depth > 0 => depth -= 1,
+                Some(JsonEvent::FieldName) => continue,
+                found @ (None | Some(JsonEvent::EndObject | JsonEvent::EndArray)) => {
+                    return Err(NextValueError::UnexpectedEvent {
+                        expected: "value",
+                        found,
+                    });
+                }
+                Some(_) => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Advance to the next event and return it together with its value.
+    /// This is a shortcut for [`Self::next_event()`] followed by
+    /// [`Self::current_str()`], [`Self::current_int()`], or
+    /// [`Self::current_float()`], depending on the event.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonParser, OwnedJsonEvent};
+    ///
+    /// let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let mut events = Vec::new();
+    /// while let Some(e) = parser.next_owned_event().unwrap() {
+    ///     events.push(e);
+    /// }
+    ///
+    /// assert_eq!(events, vec![
+    ///     OwnedJsonEvent::StartObject,
+    ///     OwnedJsonEvent::FieldName("name".to_string()),
+    ///     OwnedJsonEvent::ValueString("Elvis".to_string()),
+    ///     OwnedJsonEvent::FieldName("age".to_string()),
+    ///     OwnedJsonEvent::ValueInt(42),
+    ///     OwnedJsonEvent::EndObject,
+    /// ]);
+    /// ```
+    ///
+    /// Positive integers that do not fit into an [`i64`] are returned as
+    /// [`OwnedJsonEvent::ValueUint`]. Only integers that fit into neither an
+    /// [`i64`] nor a [`u64`] result in [`NextValueError::Int`]. Unlike with the other `next_*` functions,
+    /// [`JsonEvent::NeedMoreInput`] is returned as
+    /// [`OwnedJsonEvent::NeedMoreInput`] and not as an error.
+    pub fn next_owned_event(&mut self) -> Result<Option<OwnedJsonEvent>, NextValueError> {
+        Ok(self.advance()?.map(BorrowedJsonEvent::into_owned))
+    }
+
+    /// Advance to the next event and return it together with its value
+    /// borrowed from the parser. This is like [`Self::next_owned_event()`]
+    /// but does not allocate, which makes it the fastest way to read values
+    /// in a loop. The event borrows the parser, so it has to be dropped
+    /// before the next call.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{BorrowedJsonEvent, JsonParser};
+    ///
+    /// let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let mut total_len = 0;
+    /// while let Some(e) = parser.advance().unwrap() {
+    ///     match e {
+    ///         BorrowedJsonEvent::FieldName(s) | BorrowedJsonEvent::ValueString(s) => {
+    ///             total_len += s.len()
+    ///         }
+    ///         BorrowedJsonEvent::ValueInt(i) => assert_eq!(i, 42),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// assert_eq!(total_len, 12);
+    /// ```
+    ///
+    /// Like with [`Self::next_owned_event()`], [`JsonEvent::NeedMoreInput`]
+    /// is returned as [`BorrowedJsonEvent::NeedMoreInput`].
+    pub fn advance(&mut self) -> Result<Option<BorrowedJsonEvent<'_>>, NextValueError> {
+        let Some(e) = self.next_event()? else {
+            return Ok(None);
+        };
+        Ok(Some(match e {
+            JsonEvent::NeedMoreInput => BorrowedJsonEvent::NeedMoreInput,
+            JsonEvent::StartObject => BorrowedJsonEvent::StartObject,
+            JsonEvent::EndObject => BorrowedJsonEvent::EndObject,
+            JsonEvent::StartArray => BorrowedJsonEvent::StartArray,
+            JsonEvent::EndArray => BorrowedJsonEvent::EndArray,
+            JsonEvent::FieldName => BorrowedJsonEvent::FieldName(self.current_str()?),
+            JsonEvent::ValueString => BorrowedJsonEvent::ValueString(self.current_str()?),
+            JsonEvent::ValueInt if self.int_valid && !self.int_negative => {
+                match i64::try_from(self.int_value) {
+                    Ok(i) => BorrowedJsonEvent::ValueInt(i),
+                    Err(_) => BorrowedJsonEvent::ValueUint(self.int_value),
+                }
+            }
+            JsonEvent::ValueInt => BorrowedJsonEvent::ValueInt(self.current_int()?),
+            JsonEvent::ValueFloat => BorrowedJsonEvent::ValueFloat(self.current_float()?),
+            JsonEvent::ValueTrue => BorrowedJsonEvent::ValueTrue,
+            JsonEvent::ValueFalse => BorrowedJsonEvent::ValueFalse,
+            JsonEvent::ValueNull => BorrowedJsonEvent::ValueNull,
+        }))
+    }
+
+    /// Return a handle that yields only the events of the current subtree
+    /// and then reports the end of the JSON text, so that a nested decoder
+    /// can be handed a single value without being able to read beyond it.
+    ///
+    /// If the most recent event was [`JsonEvent::StartObject`] or
+    /// [`JsonEvent::StartArray`], the subtree consists of the rest of this
+    /// object or array up to and including its [`JsonEvent::EndObject`] or
+    /// [`JsonEvent::EndArray`]. Otherwise (e.g. after
+    /// [`JsonEvent::FieldName`]), it consists of the next value. The
+    /// handle dereferences to the parser, so values can be read with the
+    /// usual accessors.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::parser::Scoped;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// /// Sum up all integers of the current subtree
+    /// fn sum(mut scoped: Scoped<'_, SliceJsonFeeder<'_>>) -> i64 {
+    ///     let mut sum = 0;
+    ///     while let Some(e) = scoped.next_event().unwrap() {
+    ///         if e == JsonEvent::ValueInt {
+    ///             sum += scoped.current_int::<i64>().unwrap();
+    ///         }
+    ///     }
+    ///     sum
+    /// }
+    ///
+    /// let json = r#"{"a": [1, 2, {"b": 3}], "c": 4, "d": 5}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// parser.expect_start_object().unwrap();
+    /// parser.expect_field("a").unwrap();
+    /// assert_eq!(sum(parser.scoped()), 6);
+    /// parser.expect_field("c").unwrap();
+    /// assert_eq!(sum(parser.scoped()), 4);
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+    /// ```
+    pub fn scoped(&mut self) -> Scoped<'_, T, S> {
+        let in_container = self.current_event.is_container_start();
+        Scoped {
+            parser: self,
+            depth: usize::from(in_container),
+            pending: !in_container,
+            done: false,
+        }
+    }
+
+    /// Skip to the value at the given JSON Pointer (e.g. `/a/b/3`) relative
+    /// to the next value and return a handle that yields the events of the
+    /// target value (see [`Self::scoped()`]). Values that lie on the way are
+    /// skipped without materializing them.
+    ///
+    /// Once the handle has returned `None`, the parser is positioned right
+    /// after the target value, still inside its parents. Returns `Ok(None)`
+    /// if there is no value at the given pointer. The parser is then
+    /// positioned after the value the search started at. A pointer that is
+    /// neither empty nor starts with `/` results in
+    /// [`NextValueError::InvalidPointer`] without consuming any input. Like
+    /// [`Self::skip_value()`], this function returns
+    /// [`NextValueError::NeedMoreInput`] if the parser runs out of input and
+    /// is meant for feeders that provide all input at once.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"{"a": {"b": [1, 2, 3, "Elvis"]}, "c": 4}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let mut target = parser.skip_to_pointer("/a/b/3").unwrap().unwrap();
+    /// assert_eq!(target.next_event().unwrap(), Some(JsonEvent::ValueString));
+    /// assert_eq!(target.current_str().unwrap(), "Elvis");
+    /// assert_eq!(target.next_event().unwrap(), None);
+    ///
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::EndArray));
+    /// ```
+    pub fn skip_to_pointer(
+        &mut self,
+        pointer: &str,
+    ) -> Result<Option<Scoped<'_, T, S>>, NextValueError> {
+        let tokens = match pointer.strip_prefix('/') {
+            Some(rest) => rest.split('/').collect(),
+            None if pointer.is_empty() => Vec::new(),
+            None => return Err(NextValueError::InvalidPointer(pointer.to_string())),
+        };
+
+        // each token except the last one enters an object or array, so
+        // `depth` is the number of containers entered so far
+        for (depth, token) in tokens.into_iter().enumerate() {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            let found = match self.next_event()? {
+                Some(JsonEvent::NeedMoreInput) => return Err(NextValueError::NeedMoreInput),
+                Some(JsonEvent::StartObject) => self.skip_to_field(&token)?,
+                Some(JsonEvent::StartArray) => match token.parse() {
+                    Ok(i) if token == "0" || !token.starts_with('0') => self.skip_to_element(i)?,
+                    _ => {
+                        self.skip_open(1)?;
+                        false
+                    }
+                },
+                Some(e) if e.is_value() => false,
+                found => {
+                    return Err(NextValueError::UnexpectedEvent {
+                        expected: "value",
+                        found,
+                    });
+                }
+            };
+            if !found {
+                self.skip_open(depth)?;
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(Scoped {
+            parser: self,
+            depth: 0,
+            pending: true,
+            done: false,
+        }))
+    }
+
+    /// Skip the members of the current object until the field with the
+    /// given name. Returns `false` if the object does not contain such a
+    /// field. The parser is then positioned right after the end of the
+    /// object.
+    fn skip_to_field(&mut self, name: &str) -> Result<bool, NextValueError> {
+        loop {
+            match self.next_field()? {
+                Some(field) if field.name() == name => return Ok(true),
+                Some(field) => field.value().skip_value()?,
+                None => return Ok(false),
+            }
+        }
+    }
+
+    /// Skip the elements of the current array until the one with the given
+    /// index. Returns `false` if the array is shorter. The parser is then
+    /// positioned right after the end of the array.
+    fn skip_to_element(&mut self, index: usize) -> Result<bool, NextValueError> {
+        for i in 0..=index {
+            match self.peek_event()? {
+                Some(JsonEvent::NeedMoreInput) => return Err(NextValueError::NeedMoreInput),
+                Some(JsonEvent::EndArray) => {
+                    self.next_event()?;
+                    return Ok(false);
+                }
+                _ => {}
+            }
+            if i < index {
+                self.skip_value()?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Skip the rest of the given number of objects and arrays the parser
+    /// is currently in
+    fn skip_open(&mut self, mut depth: usize) -> Result<(), NextValueError> {
+        while depth > 0 {
+            match self.next_event()? {
+                Some(JsonEvent::NeedMoreInput) => return Err(NextValueError::NeedMoreInput),
+                Some(e) if e.is_container_start() => depth += 1,
+                Some(e) if e.is_container_end() => depth -= 1,
+                Some(_) => {}
+                None => {
+                    return Err(NextValueError::UnexpectedEvent {
+                        expected: "end of object or array",
+                        found: None,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return an iterator over the remaining events and their values (see
+    /// [`Self::next_owned_event()`]). The iterator ends after the end of the
+    /// JSON text has been reached or after it has returned an error.
+    ///
+    /// This is most useful with feeders that provide all input at once such
+    /// as [`SliceJsonFeeder`]. With other feeders, the iterator returns
+    /// [`OwnedJsonEvent::NeedMoreInput`] whenever the parser runs out of
+    /// input. More input can then be provided through [`Events::parser()`].
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonParser, OwnedJsonEvent};
+    ///
+    /// let json = r#"{"name": "Elvis", "age": 42, "city": "Memphis"}"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// let strings = parser
+    ///     .events()
+    ///     .filter_map(|e| match e {
+    ///         Ok(OwnedJsonEvent::ValueString(s)) => Some(s),
+    ///         _ => None,
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(strings, vec!["Elvis", "Memphis"]);
+    /// ```
+    pub fn events(&mut self) -> Events<'_, T, S> {
+        Events {
+            parser: self,
+            done: false,
+        }
     }
 
     /// Return the number of bytes parsed so far
     pub fn parsed_bytes(&self) -> usize {
         self.parsed_bytes
     }
+
+    /// Record the parser's current state, so that it can return to it
+    /// later with [`Self::rewind()`]. This enables limited backtracking,
+    /// e.g. to look for a discriminator field before deciding how to
+    /// interpret an object. The save point copies the parser's stack and
+    /// the current value but not the input.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = br#"{"x": 1, "y": 2, "type": "point"}"#;
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+    ///
+    /// // look for the type first
+    /// let sp = parser.save_point();
+    /// let mut kind = None;
+    /// while let Some(field) = parser.next_field().unwrap() {
+    ///     if field.name() == "type" {
+    ///         kind = Some(field.value().next_str().unwrap().to_string());
+    ///         break;
+    ///     }
+    ///     field.value().skip_value().unwrap();
+    /// }
+    /// assert_eq!(kind.as_deref(), Some("point"));
+    ///
+    /// // now read the object from the beginning
+    /// parser.rewind(&sp).unwrap();
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+    /// assert_eq!(parser.current_str().unwrap(), "x");
+    /// ```
+    pub fn save_point(&self) -> SavePoint {
+        SavePoint {
+            stack: self.storage.stack_to_vec(),
+            buffer: self.storage.buffer().to_vec(),
+            current_is_borrowed: self.current_is_borrowed,
+            borrowed_range: self.borrowed_range.clone(),
+            current_is_number: self.current_is_number,
+            int_valid: self.int_valid,
+            int_value: self.int_value,
+            int_negative: self.int_negative,
+            state: self.state,
+            current_has_escapes: self.current_has_escapes,
+            event1: self.event1,
+            event2: self.event2,
+            span1: self.span1,
+            span2: self.span2,
+            current_span: self.current_span,
+            current_event: self.current_event,
+            reached_end: self.reached_end,
+            token_start: self.token_start,
+            token_end: self.token_end,
+            parsed_bytes: self.parsed_bytes,
+            putback_character: self.putback_character,
+            stats: self.stats,
+            total_value_len: self.total_value_len,
+            object_keys: self.object_keys.clone(),
+            path: self.path.clone(),
+            current_path_len: self.current_path_len,
+            current_array_index: self.current_array_index,
+            container_lens: self.container_lens.clone(),
+        }
+    }
+
+    /// Return to the state recorded by [`Self::save_point()`]. The next
+    /// event will be the one that followed the save point, and the value of
+    /// the event most recently returned before the save point can be read
+    /// again. The save point stays valid and can be used again.
+    ///
+    /// This only works as long as the feeder still holds the input that has
+    /// been consumed since the save point (see
+    /// [`JsonFeeder::rewind_input()`]). For example,
+    /// [`SliceJsonFeeder`] and [`StrJsonFeeder`](crate::feeder::StrJsonFeeder)
+    /// always keep their input, while feeders that read from a stream only
+    /// keep their current buffer. Otherwise,
+    /// [`RewindError::NotBuffered`] is returned and the parser is left
+    /// unchanged. Save points are only meaningful for the parser that has
+    /// created them.
+    pub fn rewind(&mut self, save_point: &SavePoint) -> Result<(), RewindError> {
+        let fed_bytes = self.parsed_bytes + self.putback_character.is_some() as usize;
+        let n = fed_bytes
+            .checked_sub(save_point.fed_bytes())
+            .ok_or(RewindError::Ahead)?;
+        if !self.feeder.rewind_input(n) {
+            return Err(RewindError::NotBuffered);
+        }
+
+        while self.storage.stack_len() > 0 {
+            self.storage.stack_pop();
+        }
+        for &mode in &save_point.stack {
+            self.storage.stack_push(mode);
+        }
+        self.storage.buffer_truncate(0);
+        self.storage.buffer_extend(&save_point.buffer);
+        self.unescaped_buffer = OnceCell::new();
+
+        self.current_is_borrowed = save_point.current_is_borrowed;
+        self.borrowed_range = save_point.borrowed_range.clone();
+        self.current_is_number = save_point.current_is_number;
+        self.int_valid = save_point.int_valid;
+        self.int_value = save_point.int_value;
+        self.int_negative = save_point.int_negative;
+        self.state = save_point.state;
+        self.current_has_escapes = save_point.current_has_escapes;
+        self.event1 = save_point.event1;
+        self.event2 = save_point.event2;
+        self.span1 = save_point.span1;
+        self.span2 = save_point.span2;
+        self.current_span = save_point.current_span;
+        self.current_event = save_point.current_event;
+        self.reached_end = save_point.reached_end;
+        self.token_start = save_point.token_start;
+        self.token_end = save_point.token_end;
+        self.parsed_bytes = save_point.parsed_bytes;
+        self.putback_character = save_point.putback_character;
+        self.stats = save_point.stats;
+        self.total_value_len = save_point.total_value_len;
+        self.object_keys.clone_from(&save_point.object_keys);
+        self.path.clone_from(&save_point.path);
+        self.current_path_len = save_point.current_path_len;
+        self.current_array_index = save_point.current_array_index;
+        self.container_lens.clone_from(&save_point.container_lens);
+        Ok(())
+    }
+
+    /// Return the bytes that the parser has read from its feeder but not
+    /// parsed yet. Together with the input still held by the feeder, these
+    /// are the bytes following the most recent event. This is only ever
+    /// non-empty in streaming mode or if trailing data is allowed (see
+    /// [`JsonParserOptionsBuilder::with_allow_trailing_data()`]), where the
+    /// parser may have to read the byte after a number to find its end. Events
+    /// that have been peeked but not returned are not taken into account.
+    ///
+    /// ```
+    /// use actson::feeder::{JsonFeeder, SliceJsonFeeder};
+    /// use actson::options::JsonParserOptionsBuilder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let mut parser = JsonParser::new_with_options(
+    ///     SliceJsonFeeder::new(b"12[3]"),
+    ///     JsonParserOptionsBuilder::default().with_streaming(true).build(),
+    /// );
+    /// assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    /// assert_eq!(parser.remaining(), b"[");
+    ///
+    /// let (feeder, remaining) = parser.into_feeder();
+    /// assert_eq!(remaining, b"[");
+    /// assert_eq!(feeder.peek_input(), b"3]");
+    /// ```
+    pub fn remaining(&self) -> &[u8] {
+        self.putback_character.as_slice()
+    }
+
+    /// Consume the parser and return its feeder together with the bytes
+    /// the parser has read from it but not parsed yet (see
+    /// [`Self::remaining()`]). This is useful for protocols that send other
+    /// data after a JSON text on the same stream: stop calling
+    /// [`Self::next_event()`] after the last event of the JSON text and
+    /// continue reading from the returned bytes and the feeder.
+    pub fn into_feeder(self) -> (T, Vec<u8>) {
+        let remaining = self.remaining().to_vec();
+        (self.feeder, remaining)
+    }
+
+    /// Check that the end of the JSON text has been reached, that it is
+    /// followed only by whitespace, and that the feeder is done and
+    /// exhausted. Call this method after the last event has been processed
+    /// to make sure that the whole input has been consumed. Unlike calling
+    /// [`Self::next_event()`] again, this also works after `None` has
+    /// already been returned.
+    ///
+    /// ```
+    /// use actson::feeder::PushJsonFeeder;
+    /// use actson::parser::FinishError;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let mut parser = JsonParser::new(PushJsonFeeder::new());
+    /// parser.feeder.push_bytes(b"[1] ");
+    /// while parser.next_event().unwrap() != Some(JsonEvent::EndArray) {}
+    /// assert!(matches!(parser.finish(), Err(FinishError::FeederNotDone)));
+    ///
+    /// parser.feeder.done();
+    /// assert!(parser.finish().is_ok());
+    /// assert!(parser.finish().is_ok());
+    /// ```
+    pub fn finish(&mut self) -> Result<(), FinishError> {
+        if self.storage.stack_len() == 0 {
+            // the end has already been reached
+            if self.putback_character.is_some() || self.feeder.has_input() {
+                return Err(FinishError::TrailingInput);
+            }
+            return Ok(());
+        }
+        match self.next_event()? {
+            None => Ok(()),
+            Some(JsonEvent::NeedMoreInput) => Err(FinishError::FeederNotDone),
+            Some(e) => Err(FinishError::UnexpectedEvent(e)),
+        }
+    }
+}
+
+/// A field of a JSON object returned by [`JsonParser::next_field()`]
+pub struct Field<'p, T, S = HeapStorage> {
+    parser: &'p mut JsonParser<T, S>,
+}
+
+impl<'p, T, S> Field<'p, T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    /// Get the field's name
+    pub fn name(&self) -> &str {
+        // the name has already been validated in next_field()
+        self.parser.current_str().unwrap_or_default()
+    }
+
+    /// Get the parser, which is positioned on the field's value
+    pub fn value(self) -> &'p mut JsonParser<T, S> {
+        self.parser
+    }
+}
+
+/// A handle that yields the events of a subtree of the JSON text. See
+/// [`JsonParser::scoped()`].
+pub struct Scoped<'p, T, S = HeapStorage> {
+    parser: &'p mut JsonParser<T, S>,
+
+    /// The number of objects and arrays of the subtree that are open
+    depth: usize,
+
+    /// `true` if the subtree is a single value that has not started yet
+    pending: bool,
+
+    /// `true` if the end of the subtree has been reached
+    done: bool,
+}
+
+impl<T, S> Scoped<'_, T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    /// Get the next event of the subtree or `None` if the end of the subtree
+    /// has been reached. Like [`JsonParser::next_event()`], this returns
+    /// [`JsonEvent::NeedMoreInput`] if the parser needs more input (see
+    /// [`Self::feeder()`]).
+    ///
+    /// If the subtree is a single value, the next event is peeked before
+    /// the value starts (see [`JsonParser::peek_event()`]), so read the
+    /// value of the previous event before calling this method.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, ParserError> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.pending {
+            match self.parser.peek_event()? {
+                Some(JsonEvent::NeedMoreInput) => return self.parser.next_event(),
+                Some(e) if e.is_value() => self.pending = false,
+                _ => {
+                    // there is no value (e.g. at the end of an array)
+                    self.done = true;
+                    return Ok(None);
+                }
+            }
+        }
+
+        let e = self.parser.next_event()?;
+        match e {
+            Some(e) if e.is_container_start() => self.depth += 1,
+            Some(e) if e.is_container_end() => self.depth -= 1,
+            Some(JsonEvent::NeedMoreInput) => return Ok(e),
+            _ => {}
+        }
+        if self.depth == 0 {
+            self.done = true;
+        }
+        Ok(e)
+    }
+
+    /// Skip the rest of the subtree, so that the parser is positioned
+    /// right after it
+    pub fn skip_rest(&mut self) -> Result<(), NextValueError> {
+        loop {
+            match self.next_event()? {
+                Some(JsonEvent::NeedMoreInput) => return Err(NextValueError::NeedMoreInput),
+                Some(_) => {}
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Get the parser's feeder (e.g. to provide more input)
+    pub fn feeder(&mut self) -> &mut T {
+        &mut self.parser.feeder
+    }
+}
+
+impl<T, S> Deref for Scoped<'_, T, S> {
+    type Target = JsonParser<T, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.parser
+    }
+}
+
+/// The maximum number of characters of a value shown by
+/// [`EventDescription`]
+const DESCRIPTION_MAX_VALUE_LEN: usize = 40;
+
+/// A description of an event returned by [`JsonParser::describe_event()`]
+#[derive(Clone, Copy, Debug)]
+pub struct EventDescription<'p> {
+    event: JsonEvent,
+    value: Option<&'p [u8]>,
+    span: Span,
+}
+
+impl fmt::Display for EventDescription<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.event)?;
+        if let Some(value) = self.value.filter(|v| !v.is_empty()) {
+            // strings are shown with their escape sequences
+            let value = String::from_utf8_lossy(value);
+            let mut chars = value.chars();
+            let truncated = chars
+                .by_ref()
+                .take(DESCRIPTION_MAX_VALUE_LEN)
+                .collect::<String>();
+            let ellipsis = if chars.next().is_some() { "..." } else { "" };
+            if matches!(self.event, JsonEvent::FieldName | JsonEvent::ValueString) {
+                write!(f, " \"{truncated}{ellipsis}\"")?;
+            } else {
+                write!(f, " {truncated}{ellipsis}")?;
+            }
+        }
+        write!(f, " at {}..{}", self.span.start, self.span.end)
+    }
+}
+
+/// An iterator over the events produced by a chunk of input. See
+/// [`JsonParser::feed()`].
+pub struct Feed<'p, 'b, S = HeapStorage> {
+    parser: &'p mut JsonParser<PushJsonFeeder, S>,
+    input: &'b [u8],
+    done: bool,
+}
+
+impl<S> Iterator for Feed<'_, '_, S>
+where
+    S: ParserStorage,
+{
+    type Item = Result<OwnedJsonEvent, NextValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.parser.next_owned_event() {
+                Ok(Some(OwnedJsonEvent::NeedMoreInput)) => {
+                    if self.input.is_empty() {
+                        self.done = true;
+                    } else {
+                        let n = self.parser.feeder.push_bytes(self.input);
+                        self.input = &self.input[n..];
+                    }
+                }
+                Ok(Some(e)) => return Some(Ok(e)),
+                Ok(None) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<S> FusedIterator for Feed<'_, '_, S> where S: ParserStorage {}
+
+/// An iterator over the events of a [`JsonParser`] and their values. See
+/// [`JsonParser::events()`].
+pub struct Events<'p, T, S = HeapStorage> {
+    parser: &'p mut JsonParser<T, S>,
+    done: bool,
+}
+
+impl<T, S> Events<'_, T, S> {
+    /// Get the underlying parser (e.g. to provide more input to its feeder)
+    pub fn parser(&mut self) -> &mut JsonParser<T, S> {
+        self.parser
+    }
+}
+
+impl<T, S> Iterator for Events<'_, T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    type Item = Result<OwnedJsonEvent, NextValueError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let r = self.parser.next_owned_event().transpose();
+        self.done = !matches!(r, Some(Ok(_)));
+        r
+    }
+}
+
+impl<T, S> FusedIterator for Events<'_, T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+}
+
+impl<'a, S> JsonParser<SliceJsonFeeder<'a>, S>
+where
+    S: ParserStorage,
+{
+    /// Get the value of the string that has just been parsed without copying
+    /// it. Call this function after you've received
+    /// [`JsonEvent::FieldName`](JsonEvent#variant.FieldName) or
+    /// [`JsonEvent::ValueString`](JsonEvent#variant.ValueString).
+    ///
+    /// If the string does not contain escape sequences, the function returns
+    /// [`Cow::Borrowed`] pointing into the slice the parser reads from. The
+    /// returned value is not tied to the parser and can be kept after the
+    /// next call of [`JsonParser::next_event()`]. Otherwise, the string is
+    /// unescaped into a new [`String`].
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"["Elvis", "Presley\n"]"#.as_bytes();
+    ///
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    /// let mut values = Vec::new();
+    /// while let Some(event) = parser.next_event().unwrap() {
+    ///     if event == JsonEvent::ValueString {
+    ///         values.push(parser.current_str_cow().unwrap());
+    ///     }
+    /// }
+    ///
+    /// assert!(matches!(values[0], Cow::Borrowed("Elvis")));
+    /// assert!(matches!(&values[1], Cow::Owned(s) if s == "Presley\n"));
+    /// ```
+    pub fn current_str_cow(&self) -> Result<Cow<'a, str>, InvalidStringValueError> {
+        if self.current_is_borrowed && !self.current_has_escapes {
+            let raw = self.borrowed_raw(self.feeder.consumed_slice());
+            return Ok(Cow::Borrowed(from_utf8(raw)?));
+        }
+        Ok(Cow::Owned(self.current_str()?.to_string()))
+    }
+}
+
+impl<S> JsonParser<PushJsonFeeder, S>
+where
+    S: ParserStorage,
+{
+    /// Push the given chunk of input to the feeder and return an iterator
+    /// over the events that become available. The iterator alternates
+    /// between pushing and parsing on its own, so chunks of any size can be
+    /// fed regardless of the feeder's capacity. It ends when all bytes have
+    /// been pushed and the parser needs more input, when the end of the
+    /// JSON text has been reached, or after an error. Consume the iterator
+    /// completely before feeding the next chunk, because bytes that have
+    /// not been pushed yet are lost when it is dropped. Call
+    /// [`Self::feed_done()`] after the last chunk.
+    ///
+    /// ```
+    /// use actson::feeder::PushJsonFeeder;
+    /// use actson::{JsonParser, OwnedJsonEvent};
+    ///
+    /// let mut parser = JsonParser::new(PushJsonFeeder::new());
+    /// let mut events = Vec::new();
+    /// for chunk in [&b"[\"El"[..], b"vis\", 4", b"2]"] {
+    ///     for e in parser.feed(chunk) {
+    ///         events.push(e.unwrap());
+    ///     }
+    /// }
+    /// for e in parser.feed_done() {
+    ///     events.push(e.unwrap());
+    /// }
+    /// parser.finish().unwrap();
+    ///
+    /// assert_eq!(events, vec![
+    ///     OwnedJsonEvent::StartArray,
+    ///     OwnedJsonEvent::ValueString("Elvis".to_string()),
+    ///     OwnedJsonEvent::ValueInt(42),
+    ///     OwnedJsonEvent::EndArray,
+    /// ]);
+    /// ```
+    pub fn feed<'p, 'b>(&'p mut self, input: &'b [u8]) -> Feed<'p, 'b, S> {
+        Feed {
+            parser: self,
+            input,
+            done: false,
+        }
+    }
+
+    /// Indicate that the end of the JSON text has been reached (see
+    /// [`PushJsonFeeder::done()`]) and return an iterator over the
+    /// remaining events (see [`Self::feed()`])
+    pub fn feed_done(&mut self) -> Feed<'_, 'static, S> {
+        self.feeder.done();
+        self.feed(&[])
+    }
 }
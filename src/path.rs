@@ -0,0 +1,644 @@
+//! Extract values at specific locations from a JSON text.
+//!
+//! A [`PathFilter`] wraps a [`JsonParser`] and only returns the events of
+//! values whose location matches one of a set of [`PathPattern`]s. Patterns
+//! can be given as a subset of JSONPath (e.g. `$.items[*].id`) or as JSON
+//! Pointers in which `*` matches any field name or array index (e.g.
+//! `/items/*/id`). Field names are only decoded where a pattern may still
+//! match, so everything else is skipped without looking at its values.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::path::{PathFilter, PathPattern};
+//! use actson::{JsonEvent, JsonParser};
+//!
+//! let json = r#"{"items": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]}"#.as_bytes();
+//! let pattern = "$.items[*].id".parse::<PathPattern>().unwrap();
+//! let mut filter = PathFilter::new(JsonParser::new(SliceJsonFeeder::new(json)), [pattern]);
+//!
+//! let mut ids = Vec::new();
+//! while let Some(e) = filter.next_event().unwrap() {
+//!     if e == JsonEvent::ValueInt {
+//!         ids.push(filter.parser().current_int::<i32>().unwrap());
+//!     }
+//! }
+//!
+//! assert_eq!(ids, vec![1, 2]);
+//! ```
+//!
+//! [`Grep`] builds on [`PathFilter`] and reports each matching value
+//! together with its concrete location (as a JSON Pointer), its position in
+//! the JSON text, and optionally its decoded value. This answers questions
+//! like "where does field X appear in this huge dump?" without keeping more
+//! than the current match in memory.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::path::{Grep, PathPattern};
+//! use actson::value::JsonValue;
+//! use actson::JsonParser;
+//!
+//! let json = r#"{"users": [{"email": "a@x"}, {"name": "b"}, {"email": "c@x"}]}"#.as_bytes();
+//! let pattern = "$.users[*].email".parse::<PathPattern>().unwrap();
+//! let grep = Grep::new(JsonParser::new(SliceJsonFeeder::new(json)), [pattern], true);
+//!
+//! let matches = grep.collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(matches.len(), 2);
+//! assert_eq!(matches[1].pointer, "/users/2/email");
+//! assert_eq!(&json[matches[1].span.start..matches[1].span.end], br#""c@x""#);
+//! assert_eq!(matches[1].value, Some(JsonValue::String("c@x".to_string())));
+//! ```
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{FillJsonFeeder, JsonFeeder},
+    parser::{NextValueError, ParserError},
+    sink::{JsonEventSink, ParseWithError},
+    storage::{HeapStorage, ParserStorage},
+    value::{JsonValue, JsonValueBuilder},
+    JsonEvent, JsonParser, Span,
+};
+
+/// An error that can happen when parsing a [`PathPattern`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid path pattern at position {position}: {message}")]
+pub struct PathPatternError {
+    /// The byte offset in the pattern where the error was detected
+    pub position: usize,
+
+    /// A description of the error
+    pub message: &'static str,
+}
+
+/// One step of a [`PathPattern`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    /// A field with the given name
+    Key(String),
+
+    /// An array element with the given index
+    Index(usize),
+
+    /// A JSON Pointer reference token, which matches a field with the given
+    /// name or an array element if it is a valid index
+    Token(String),
+
+    /// Any field or array element
+    Wildcard,
+}
+
+/// The location of a value in its parent
+#[derive(Clone, Copy)]
+pub(crate) enum Location<'a> {
+    Key(Option<&'a str>),
+    Index(usize),
+}
+
+impl Segment {
+    fn matches(&self, loc: Location<'_>) -> bool {
+        match (self, loc) {
+            (Segment::Wildcard, _) => true,
+            (Segment::Key(k) | Segment::Token(k), Location::Key(Some(key))) => k == key,
+            (Segment::Index(i), Location::Index(index)) => *i == index,
+            (Segment::Token(t), Location::Index(index)) => {
+                (t == "0" || !t.starts_with('0')) && t.parse() == Ok(index)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the segment needs the name of a field to decide
+    /// whether it matches
+    fn needs_key(&self) -> bool {
+        matches!(self, Segment::Key(_) | Segment::Token(_))
+    }
+}
+
+/// A pattern describing locations in a JSON text. See the [module
+/// documentation](self).
+///
+/// The following JSONPath syntax is supported: `$` (the root), `.name` and
+/// `['name']` (a field), `[0]` (an array element), and `.*` and `[*]` (any
+/// field or array element). Patterns starting with `/` or empty patterns
+/// are parsed as JSON Pointers, in which `*` matches any field or array
+/// element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+impl PathPattern {
+    /// Parse a JSONPath expression or a JSON Pointer
+    pub fn parse(pattern: &str) -> Result<Self, PathPatternError> {
+        if pattern.is_empty() || pattern.starts_with('/') {
+            Ok(Self::parse_pointer(pattern))
+        } else {
+            Self::parse_json_path(pattern)
+        }
+    }
+
+    fn parse_pointer(pattern: &str) -> Self {
+        let segments = pattern
+            .split('/')
+            .skip(1)
+            .map(|t| match t {
+                "*" => Segment::Wildcard,
+                t => Segment::Token(t.replace("~1", "/").replace("~0", "~")),
+            })
+            .collect();
+        PathPattern { segments }
+    }
+
+    fn parse_json_path(pattern: &str) -> Result<Self, PathPatternError> {
+        let err = |position, message| Err(PathPatternError { position, message });
+
+        let bytes = pattern.as_bytes();
+        if bytes.first() != Some(&b'$') {
+            return err(0, "expected `$'");
+        }
+
+        let mut segments = Vec::new();
+        let mut i = 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'.' => {
+                    let start = i + 1;
+                    let end = bytes[start..]
+                        .iter()
+                        .position(|&b| b == b'.' || b == b'[')
+                        .map_or(bytes.len(), |p| start + p);
+                    match &pattern[start..end] {
+                        "" => return err(start, "expected field name"),
+                        "*" => segments.push(Segment::Wildcard),
+                        name => segments.push(Segment::Key(name.to_string())),
+                    }
+                    i = end;
+                }
+
+                b'[' => {
+                    let start = i + 1;
+                    let Some(end) = bytes[start..].iter().position(|&b| b == b']') else {
+                        return err(i, "missing `]'");
+                    };
+                    let end = start + end;
+                    let inner = &pattern[start..end];
+                    if inner == "*" {
+                        segments.push(Segment::Wildcard);
+                    } else if let Some(name) = inner
+                        .strip_prefix('\'')
+                        .and_then(|n| n.strip_suffix('\''))
+                        .or_else(|| inner.strip_prefix('"').and_then(|n| n.strip_suffix('"')))
+                    {
+                        segments.push(Segment::Key(name.to_string()));
+                    } else if let Ok(index) = inner.parse() {
+                        segments.push(Segment::Index(index));
+                    } else {
+                        return err(start, "expected index, quoted field name, or `*'");
+                    }
+                    i = end + 1;
+                }
+
+                _ => return err(i, "expected `.' or `['"),
+            }
+        }
+
+        Ok(PathPattern { segments })
+    }
+
+    /// Returns `true` if the pattern matches the value at the given path,
+    /// which consists of the locations of all its ancestors and itself
+    pub(crate) fn matches_path<'a>(
+        &self,
+        path: impl ExactSizeIterator<Item = Location<'a>>,
+    ) -> bool {
+        path.len() == self.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(path)
+                .all(|(s, loc)| s.matches(loc))
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = PathPatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// An object or array that is currently open
+#[derive(Debug)]
+struct Frame {
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The index of the next element if the container is an array
+    next_index: usize,
+
+    /// The most recent field name if the container is an object and a
+    /// pattern needs it
+    key: Option<String>,
+
+    /// The patterns that match the path leading to this container and that
+    /// may therefore match one of its descendants
+    viable: Vec<usize>,
+}
+
+/// Wraps a [`JsonParser`] and only returns the events of values matching
+/// one of a set of [`PathPattern`]s. See the [module documentation](self).
+pub struct PathFilter<T, S = HeapStorage> {
+    parser: JsonParser<T, S>,
+    patterns: Vec<PathPattern>,
+
+    /// The currently open objects and arrays
+    stack: Vec<Frame>,
+
+    /// The pattern matching the current value
+    current_match: Option<usize>,
+
+    /// The length of [`Self::stack`] inside the object or array that matches
+    /// [`Self::current_match`] or 0 if the current match is not a container
+    match_depth: usize,
+
+    /// `true` if the names of all fields should be recorded, not only those
+    /// needed to match the patterns (see [`Self::match_pointer()`])
+    record_keys: bool,
+}
+
+impl<T, S> PathFilter<T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    /// Create a new filter that reads events from the given parser and only
+    /// returns those of values matching one of the given patterns
+    pub fn new(parser: JsonParser<T, S>, patterns: impl IntoIterator<Item = PathPattern>) -> Self {
+        PathFilter {
+            parser,
+            patterns: patterns.into_iter().collect(),
+            stack: Vec::new(),
+            current_match: None,
+            match_depth: 0,
+            record_keys: false,
+        }
+    }
+
+    /// Get the underlying parser (e.g. to read the current value)
+    pub fn parser(&self) -> &JsonParser<T, S> {
+        &self.parser
+    }
+
+    /// Get the underlying parser mutably (e.g. to provide more input to its
+    /// feeder). Do not call [`JsonParser::next_event()`] on it directly.
+    pub fn parser_mut(&mut self) -> &mut JsonParser<T, S> {
+        &mut self.parser
+    }
+
+    /// Consume the filter and return the underlying parser
+    pub fn into_inner(self) -> JsonParser<T, S> {
+        self.parser
+    }
+
+    /// Get the index of the pattern matching the value the most recent
+    /// event belongs to. If a value matches multiple patterns, this is the
+    /// first one. Values nested in a match are not matched again.
+    pub fn current_match(&self) -> Option<usize> {
+        self.current_match
+    }
+
+    /// Return the next event belonging to a value that matches one of the
+    /// patterns. For objects and arrays, all nested events are returned too.
+    /// [`JsonEvent::NeedMoreInput`] is always returned.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, ParserError> {
+        if self.match_depth == 0 {
+            self.current_match = None;
+        }
+
+        loop {
+            let Some(e) = self.parser.next_event()? else {
+                return Ok(None);
+            };
+
+            if self.match_depth > 0 {
+                match e {
+                    JsonEvent::StartObject | JsonEvent::StartArray => self.push(e, Vec::new()),
+                    JsonEvent::EndObject | JsonEvent::EndArray => {
+                        self.stack.pop();
+                        if self.stack.len() < self.match_depth {
+                            self.match_depth = 0;
+                        }
+                    }
+                    _ => {}
+                }
+                return Ok(Some(e));
+            }
+
+            match e {
+                JsonEvent::NeedMoreInput => return Ok(Some(e)),
+
+                JsonEvent::FieldName => {
+                    let depth = self.stack.len() - 1;
+                    if let Some(frame) = self.stack.last_mut() {
+                        let needs_key = self.record_keys
+                            || frame
+                                .viable
+                                .iter()
+                                .any(|&p| self.patterns[p].segments[depth].needs_key());
+                        match (needs_key, self.parser.current_str()) {
+                            (true, Ok(name)) => {
+                                let key = frame.key.get_or_insert_with(String::new);
+                                key.clear();
+                                key.push_str(name);
+                            }
+                            _ => frame.key = None,
+                        }
+                    }
+                }
+
+                JsonEvent::EndObject | JsonEvent::EndArray => {
+                    self.stack.pop();
+                }
+
+                _ => {
+                    let (matched, viable) = self.match_value();
+                    let is_container = e.is_container_start();
+                    if is_container {
+                        self.push(e, viable);
+                    }
+                    if let Some(p) = matched {
+                        self.current_match = Some(p);
+                        if is_container {
+                            self.match_depth = self.stack.len();
+                        }
+                        return Ok(Some(e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Build a JSON Pointer to the value that has just started matching.
+    /// Requires [`Self::record_keys`].
+    fn match_pointer(&self) -> String {
+        let ancestors = if self.match_depth > 0 {
+            // the matching container is already on the stack
+            &self.stack[..self.match_depth - 1]
+        } else {
+            &self.stack[..]
+        };
+        let mut pointer = String::new();
+        for frame in ancestors {
+            pointer.push('/');
+            if frame.is_array {
+                pointer.push_str(&frame.next_index.saturating_sub(1).to_string());
+            } else if let Some(key) = &frame.key {
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            }
+        }
+        pointer
+    }
+
+    fn push(&mut self, e: JsonEvent, viable: Vec<usize>) {
+        self.stack.push(Frame {
+            is_array: e == JsonEvent::StartArray,
+            next_index: 0,
+            key: None,
+            viable,
+        });
+    }
+
+    /// Determine the first pattern matching the value that starts with the
+    /// current event and the patterns that may match one of its descendants
+    fn match_value(&mut self) -> (Option<usize>, Vec<usize>) {
+        let depth = self.stack.len();
+        let mut matched = None;
+        let mut viable = Vec::new();
+
+        let mut check = |p: usize, pattern: &PathPattern| {
+            if pattern.segments.len() == depth {
+                matched.get_or_insert(p);
+            } else {
+                viable.push(p);
+            }
+        };
+
+        match self.stack.last_mut() {
+            None => {
+                for (p, pattern) in self.patterns.iter().enumerate() {
+                    check(p, pattern);
+                }
+            }
+
+            Some(frame) => {
+                let loc = if frame.is_array {
+                    frame.next_index += 1;
+                    Location::Index(frame.next_index - 1)
+                } else {
+                    Location::Key(frame.key.as_deref())
+                };
+                for &p in &frame.viable {
+                    let pattern = &self.patterns[p];
+                    if pattern.segments[depth - 1].matches(loc) {
+                        check(p, pattern);
+                    }
+                }
+            }
+        }
+
+        (matched, viable)
+    }
+}
+
+/// A value found by [`Grep`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathMatch {
+    /// The index of the pattern the value matches
+    pub pattern: usize,
+
+    /// The location of the value as a JSON Pointer (e.g. `/items/0/id`)
+    pub pointer: String,
+
+    /// The position of the whole value in the JSON text
+    pub span: Span,
+
+    /// The decoded value if requested
+    pub value: Option<JsonValue>,
+}
+
+/// Finds all values matching one of a set of [`PathPattern`]s and reports
+/// their locations. See the [module documentation](self).
+pub struct Grep<T, S = HeapStorage> {
+    filter: PathFilter<T, S>,
+    decode_values: bool,
+    done: bool,
+}
+
+impl<T, S> Grep<T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Create a new instance that reads events from the given parser and
+    /// reports values matching one of the given patterns. If
+    /// `decode_values` is `true`, each match contains the decoded value.
+    /// Otherwise, values are skipped without being decoded.
+    pub fn new(
+        parser: JsonParser<T, S>,
+        patterns: impl IntoIterator<Item = PathPattern>,
+        decode_values: bool,
+    ) -> Self {
+        let mut filter = PathFilter::new(parser, patterns);
+        filter.record_keys = true;
+        Grep {
+            filter,
+            decode_values,
+            done: false,
+        }
+    }
+
+    /// Get the underlying parser
+    pub fn parser(&self) -> &JsonParser<T, S> {
+        self.filter.parser()
+    }
+
+    /// Consume this instance and return the underlying parser
+    pub fn into_inner(self) -> JsonParser<T, S> {
+        self.filter.into_inner()
+    }
+
+    /// Return the next matching value or `None` if the end of the JSON text
+    /// has been reached
+    pub fn next_match(&mut self) -> Result<Option<PathMatch>, ParseWithError<NextValueError>> {
+        let mut current: Option<PathMatch> = None;
+        let mut builder = self.decode_values.then(JsonValueBuilder::new);
+        loop {
+            let Some(e) = self.filter.next_event()? else {
+                return Ok(None);
+            };
+            if e == JsonEvent::NeedMoreInput {
+                self.filter.parser_mut().feeder.fill()?;
+                continue;
+            }
+
+            let span = self.filter.parser().current_span();
+            let m = current.get_or_insert_with(|| PathMatch {
+                pattern: self.filter.current_match().unwrap_or_default(),
+                pointer: self.filter.match_pointer(),
+                span,
+                value: None,
+            });
+            m.span.end = span.end;
+            if let Some(builder) = &mut builder {
+                builder
+                    .on_event(e, self.filter.parser())
+                    .map_err(ParseWithError::Sink)?;
+            }
+
+            if self.filter.match_depth == 0 {
+                // the value is complete
+                let mut m = current.take().unwrap_or_else(|| unreachable!());
+                m.value = builder.and_then(JsonValueBuilder::into_value);
+                return Ok(Some(m));
+            }
+        }
+    }
+}
+
+impl<T, S> Iterator for Grep<T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Item = Result<PathMatch, ParseWithError<NextValueError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let r = self.next_match().transpose();
+        self.done = !matches!(r, Some(Ok(_)));
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, JsonParser, Span};
+
+    use super::{Grep, PathPattern, Segment};
+
+    /// Test that nested matches, escaped keys, and multiple top-level
+    /// values are reported with their pointers and spans
+    #[test]
+    fn grep() {
+        let json = br#"{"a/b": [{"c": 1}, {"c": [2, {"c": 3}]}], "c~": 4} {"c": 5}"#;
+        let parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let patterns = ["/*/*/c", "/c~0", "$.c"].map(|p| p.parse::<PathPattern>().unwrap());
+        let matches = Grep::new(parser, patterns, false)
+            .map(|m| {
+                let m = m.unwrap();
+                assert_eq!(m.value, None);
+                (m.pattern, m.pointer, m.span)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                (0, "/a~1b/0/c".to_string(), Span::new(15, 16)),
+                (0, "/a~1b/1/c".to_string(), Span::new(25, 38)),
+                (1, "/c~0".to_string(), Span::new(48, 49)),
+                (2, "/c".to_string(), Span::new(57, 58)),
+            ]
+        );
+    }
+
+    /// Test that JSONPath expressions and JSON Pointers are parsed correctly
+    #[test]
+    fn parse() {
+        assert_eq!(PathPattern::parse("$").unwrap().segments, vec![]);
+        assert_eq!(
+            PathPattern::parse("$.items[*].id").unwrap().segments,
+            vec![
+                Segment::Key("items".to_string()),
+                Segment::Wildcard,
+                Segment::Key("id".to_string())
+            ]
+        );
+        assert_eq!(
+            PathPattern::parse("$['a.b'][2].*").unwrap().segments,
+            vec![
+                Segment::Key("a.b".to_string()),
+                Segment::Index(2),
+                Segment::Wildcard
+            ]
+        );
+        assert_eq!(PathPattern::parse("").unwrap().segments, vec![]);
+        assert_eq!(
+            PathPattern::parse("/a~1b/*/0").unwrap().segments,
+            vec![
+                Segment::Token("a/b".to_string()),
+                Segment::Wildcard,
+                Segment::Token("0".to_string())
+            ]
+        );
+    }
+
+    /// Test that invalid patterns are rejected
+    #[test]
+    fn invalid() {
+        for p in ["items", "$.", "$[", "$[x]", "$x", "$.a..b"] {
+            assert!(PathPattern::parse(p).is_err(), "{p}");
+        }
+        assert_eq!(PathPattern::parse("$[x]").unwrap_err().position, 2);
+    }
+}
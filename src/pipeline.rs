@@ -0,0 +1,213 @@
+//! Parse on a dedicated thread and receive events over a channel.
+//!
+//! [`spawn()`] moves a parser to a new thread, which reads the input,
+//! collects the events with their values into batches of
+//! [`OwnedJsonEvent`]s, and sends them over a bounded channel. Parsing
+//! overlaps with whatever the receiving thread does with the events, which
+//! pays off if processing the events takes about as long as parsing them.
+//! The parser thread blocks if the consumer falls behind by more than a
+//! given number of batches, so memory usage stays bounded.
+//!
+//! ```
+//! use actson::feeder::BufReaderJsonFeeder;
+//! use actson::pipeline::spawn;
+//! use actson::{JsonParser, OwnedJsonEvent};
+//! use std::io::{BufReader, Cursor};
+//!
+//! let json = br#"{"ids": [1, 2, 3]}"#.to_vec();
+//! let feeder = BufReaderJsonFeeder::new(BufReader::new(Cursor::new(json)));
+//! let batches = spawn(JsonParser::new(feeder), 2, 4);
+//!
+//! let mut sum = 0;
+//! for batch in batches {
+//!     for event in batch.unwrap() {
+//!         if let OwnedJsonEvent::ValueInt(i) = event {
+//!             sum += i;
+//!         }
+//!     }
+//! }
+//! assert_eq!(sum, 6);
+//! ```
+
+use std::{
+    sync::mpsc::{sync_channel, Receiver},
+    thread::{self, JoinHandle},
+};
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{FillError, FillJsonFeeder},
+    parser::NextValueError,
+    storage::ParserStorage,
+    JsonParser, OwnedJsonEvent,
+};
+
+/// An error that can happen in the parser thread
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    /// The JSON text is invalid or a value could not be converted
+    #[error(transparent)]
+    Value(#[from] NextValueError),
+
+    /// The feeder could not be filled
+    #[error(transparent)]
+    Fill(#[from] FillError),
+
+    /// The parser thread panicked (e.g. in the feeder)
+    #[error("parser thread panicked")]
+    Panicked,
+}
+
+/// Move the parser to a new thread and return an iterator over batches of
+/// its events. Each batch contains up to `batch_size` events (at least
+/// one). At most `max_pending_batches` batches are buffered before the
+/// parser thread waits for the consumer. See the [module
+/// documentation](self).
+///
+/// The iterator ends after the end of the JSON text has been reached or
+/// after it has returned an error. If it is dropped early, the parser
+/// thread stops after finishing its current batch.
+pub fn spawn<T, S>(
+    mut parser: JsonParser<T, S>,
+    batch_size: usize,
+    max_pending_batches: usize,
+) -> EventBatches
+where
+    T: FillJsonFeeder + Send + 'static,
+    S: ParserStorage + Send + 'static,
+{
+    let batch_size = batch_size.max(1);
+    let (sender, receiver) = sync_channel(max_pending_batches);
+
+    let handle = thread::spawn(move || {
+        let mut batch = Vec::with_capacity(batch_size);
+        loop {
+            let event = match parser.next_owned_event() {
+                Ok(Some(OwnedJsonEvent::NeedMoreInput)) => match parser.feeder.fill() {
+                    Ok(()) => continue,
+                    Err(e) => Err(e.into()),
+                },
+                Ok(Some(event)) => Ok(Some(event)),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e.into()),
+            };
+
+            match event {
+                Ok(Some(event)) => {
+                    batch.push(event);
+                    if batch.len() == batch_size {
+                        let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                        if sender.send(Ok(full)).is_err() {
+                            // the receiver has been dropped
+                            return;
+                        }
+                    }
+                }
+                Ok(None) => {
+                    if !batch.is_empty() {
+                        let _ = sender.send(Ok(batch));
+                    }
+                    return;
+                }
+                Err(e) => {
+                    if !batch.is_empty() && sender.send(Ok(batch)).is_err() {
+                        return;
+                    }
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            }
+        }
+    });
+
+    EventBatches {
+        receiver,
+        handle: Some(handle),
+    }
+}
+
+/// An iterator over batches of events received from a parser thread. See
+/// [`spawn()`].
+pub struct EventBatches {
+    receiver: Receiver<Result<Vec<OwnedJsonEvent>, PipelineError>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Iterator for EventBatches {
+    type Item = Result<Vec<OwnedJsonEvent>, PipelineError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.handle.as_ref()?;
+        match self.receiver.recv() {
+            Ok(Err(e)) => {
+                self.handle = None;
+                Some(Err(e))
+            }
+            Ok(batch) => Some(batch),
+            Err(_) => {
+                // the parser thread has finished
+                let panicked = self.handle.take().is_some_and(|h| h.join().is_err());
+                panicked.then_some(Err(PipelineError::Panicked))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufReader, Cursor};
+
+    use crate::{
+        feeder::BufReaderJsonFeeder,
+        parser::{NextValueError, ParserError},
+        JsonParser, OwnedJsonEvent,
+    };
+
+    use super::{spawn, PipelineError};
+
+    fn parser(json: &str) -> JsonParser<BufReaderJsonFeeder<Cursor<Vec<u8>>>> {
+        let reader = BufReader::with_capacity(4, Cursor::new(json.as_bytes().to_vec()));
+        JsonParser::new(BufReaderJsonFeeder::new(reader))
+    }
+
+    /// Test that events arrive in order and in batches of the given size
+    #[test]
+    fn batches() {
+        let batches = spawn(parser(r#"[1, "a", {"b": null}]"#), 3, 1)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![3, 3, 2]
+        );
+        assert_eq!(
+            batches.concat(),
+            vec![
+                OwnedJsonEvent::StartArray,
+                OwnedJsonEvent::ValueInt(1),
+                OwnedJsonEvent::ValueString("a".to_string()),
+                OwnedJsonEvent::StartObject,
+                OwnedJsonEvent::FieldName("b".to_string()),
+                OwnedJsonEvent::ValueNull,
+                OwnedJsonEvent::EndObject,
+                OwnedJsonEvent::EndArray,
+            ]
+        );
+    }
+
+    /// Test that the events before an error are delivered before the error
+    /// and that the iterator ends after it
+    #[test]
+    fn error() {
+        let mut batches = spawn(parser("[1, 2, }"), 10, 1);
+        assert_eq!(batches.next().unwrap().unwrap().len(), 3);
+        assert!(matches!(
+            batches.next(),
+            Some(Err(PipelineError::Value(NextValueError::Parser(
+                ParserError::SyntaxError
+            ))))
+        ));
+        assert!(batches.next().is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! Report the progress of long-running parses.
+//!
+//! Register a callback with
+//! [`JsonParser::set_progress_callback()`](crate::JsonParser::set_progress_callback())
+//! to be notified every time the parser has consumed a given number of
+//! bytes and once more when it has reached the end of the JSON text. If the
+//! total length of the input is known (e.g. the size of a file), it is
+//! passed to the callback, so it can render a progress bar.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::progress::Progress;
+//! use actson::JsonParser;
+//! use std::sync::{Arc, Mutex};
+//!
+//! let json = br#"{"values": [1, 2, 3, 4, 5, 6, 7, 8]}"#;
+//! let reports = Arc::new(Mutex::new(Vec::new()));
+//!
+//! let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+//! let r = Arc::clone(&reports);
+//! parser.set_progress_callback(16, Some(json.len()), move |p: Progress| {
+//!     r.lock().unwrap().push(p);
+//! });
+//! while parser.next_event().unwrap().is_some() {}
+//!
+//! let reports = reports.lock().unwrap();
+//! assert_eq!(reports.len(), 3);
+//! assert!(reports[0].bytes_consumed >= 16);
+//! assert_eq!(reports[2].bytes_consumed, json.len());
+//! assert_eq!(reports[2].fraction(), Some(1.0));
+//! assert!(reports[2].done);
+//! ```
+
+/// The progress of a parser passed to a callback registered with
+/// [`JsonParser::set_progress_callback()`](crate::JsonParser::set_progress_callback())
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of bytes consumed from the feeder so far
+    pub bytes_consumed: usize,
+
+    /// The total length of the input in bytes if it is known
+    pub total_bytes: Option<usize>,
+
+    /// `true` if the end of the JSON text has been reached
+    pub done: bool,
+}
+
+impl Progress {
+    /// Returns the fraction of the input consumed so far as a number
+    /// between 0 and 1, or `None` if the total length is not known
+    pub fn fraction(&self) -> Option<f64> {
+        self.total_bytes.map(|total| {
+            if total == 0 {
+                1.0
+            } else {
+                (self.bytes_consumed as f64 / total as f64).min(1.0)
+            }
+        })
+    }
+}
+
+/// Invokes a progress callback at a fixed interval of consumed bytes
+pub(crate) struct ProgressReporter {
+    /// The number of bytes between two reports
+    interval: usize,
+
+    /// The total length of the input if it is known
+    total_bytes: Option<usize>,
+
+    /// The number of consumed bytes at which the next report is due
+    next_report: usize,
+
+    callback: Box<dyn FnMut(Progress) + Send>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(
+        interval: usize,
+        total_bytes: Option<usize>,
+        callback: Box<dyn FnMut(Progress) + Send>,
+    ) -> Self {
+        let interval = interval.max(1);
+        ProgressReporter {
+            interval,
+            total_bytes,
+            next_report: interval,
+            callback,
+        }
+    }
+
+    /// Invoke the callback if at least [`Self::interval`] bytes have been
+    /// consumed since the last report
+    #[inline]
+    pub(crate) fn update(&mut self, bytes_consumed: usize) {
+        if bytes_consumed >= self.next_report {
+            self.report(bytes_consumed, false);
+            self.next_report = bytes_consumed - bytes_consumed % self.interval + self.interval;
+        }
+    }
+
+    /// Invoke the callback because the end of the JSON text has been reached
+    pub(crate) fn finish(&mut self, bytes_consumed: usize) {
+        self.report(bytes_consumed, true);
+    }
+
+    fn report(&mut self, bytes_consumed: usize, done: bool) {
+        (self.callback)(Progress {
+            bytes_consumed,
+            total_bytes: self.total_bytes,
+            done,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::{Progress, ProgressReporter};
+
+    /// Test that reports are due after each interval even if the parser
+    /// skips over several intervals at once
+    #[test]
+    fn interval() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let r = Arc::clone(&reports);
+        let mut reporter = ProgressReporter::new(
+            10,
+            None,
+            Box::new(move |p: Progress| r.lock().unwrap().push(p.bytes_consumed)),
+        );
+        for n in [0, 9, 10, 11, 19, 35, 39, 40] {
+            reporter.update(n);
+        }
+        reporter.finish(42);
+        assert_eq!(*reports.lock().unwrap(), [10, 35, 40, 42]);
+
+        let p = Progress {
+            bytes_consumed: 5,
+            total_bytes: Some(20),
+            done: false,
+        };
+        assert_eq!(p.fraction(), Some(0.25));
+    }
+}
@@ -0,0 +1,112 @@
+//! Process newline-delimited JSON (NDJSON, also known as JSON Lines) in
+//! parallel with [Rayon](https://docs.rs/rayon).
+//!
+//! The input is split on line boundaries and each line is parsed by its own
+//! [`JsonParser`] on Rayon's thread pool. The results are returned in the
+//! order of the lines in the input. Empty lines (or lines that contain only
+//! whitespace) are skipped.
+//!
+//! *Heads up:* The `rayon` feature has to be enabled for this.
+
+use ::rayon::prelude::*;
+
+use crate::{feeder::SliceJsonFeeder, JsonParser};
+
+/// Split the given input into non-empty lines in parallel
+fn par_lines(input: &[u8]) -> impl ParallelIterator<Item = &[u8]> {
+    input
+        .par_split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+}
+
+/// Call the given function with a parser for each non-empty line of the
+/// given NDJSON input in parallel and collect the results in the order of
+/// the lines.
+///
+/// ```
+/// use actson::rayon::par_map_ndjson;
+/// use actson::JsonEvent;
+///
+/// let ndjson = b"{\"a\": 1}\n[1, 2, 3]\n\n\"x\"\n";
+/// let counts = par_map_ndjson(ndjson, |mut parser| {
+///     let mut count = 0;
+///     while let Some(e) = parser.next_event()? {
+///         if e != JsonEvent::NeedMoreInput {
+///             count += 1;
+///         }
+///     }
+///     Ok::<_, actson::parser::NextValueError>(count)
+/// });
+/// assert_eq!(
+///     counts.into_iter().collect::<Result<Vec<_>, _>>().unwrap(),
+///     vec![4, 5, 1]
+/// );
+/// ```
+pub fn par_map_ndjson<'a, F, R>(input: &'a [u8], f: F) -> Vec<R>
+where
+    F: Fn(JsonParser<SliceJsonFeeder<'a>>) -> R + Sync + Send,
+    R: Send,
+{
+    par_lines(input)
+        .map(|line| f(JsonParser::new(SliceJsonFeeder::new(line))))
+        .collect()
+}
+
+/// Deserialize each non-empty line of the given NDJSON input into an
+/// instance of type `T` in parallel. The results are returned in the order
+/// of the lines, so a record that cannot be deserialized does not prevent
+/// the others from being returned.
+///
+/// *Heads up:* The `serde_json` feature has to be enabled for this.
+///
+/// ```
+/// use actson::rayon::par_from_ndjson;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Record<'a> {
+///     level: &'a str,
+/// }
+///
+/// let ndjson = b"{\"level\": \"info\"}\n{\"level\": \"warn\"}\n{\"level\"\n";
+/// let records = par_from_ndjson::<Record>(ndjson);
+/// assert_eq!(records.len(), 3);
+/// assert_eq!(records[0].as_ref().unwrap().level, "info");
+/// assert_eq!(records[1].as_ref().unwrap().level, "warn");
+/// assert!(records[2].is_err());
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn par_from_ndjson<'a, T>(
+    input: &'a [u8],
+) -> Vec<Result<T, crate::serde_json::DeserializeError>>
+where
+    T: serde::Deserialize<'a> + Send,
+{
+    par_lines(input)
+        .map(crate::serde_json::from_slice)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::JsonEvent;
+
+    use super::par_map_ndjson;
+
+    /// Test that the results are returned in the order of the lines
+    #[test]
+    fn order() {
+        let ndjson = (0..10_000)
+            .map(|i| format!("[{i}]\r\n"))
+            .collect::<String>();
+        let values = par_map_ndjson(ndjson.as_bytes(), |mut parser| {
+            while let Some(e) = parser.next_event().unwrap() {
+                if e == JsonEvent::ValueInt {
+                    return parser.current_int::<i64>().unwrap();
+                }
+            }
+            -1
+        });
+        assert_eq!(values, (0..10_000).collect::<Vec<_>>());
+    }
+}
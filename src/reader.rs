@@ -0,0 +1,423 @@
+//! A pull-style reader for JSON texts with typed accessors.
+//!
+//! [`JsonReader`] sits on top of a [`JsonParser`] and lets you consume the
+//! JSON text by calling a method for each expected token, similar to
+//! `JsonReader` in Gson. Each method checks that the next token is of the
+//! expected type, so hand-written decoders do not have to match events
+//! manually.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::reader::JsonReader;
+//! use actson::JsonParser;
+//!
+//! let json = r#"{"name": "Elvis", "tags": ["singer", "actor"], "age": 42}"#.as_bytes();
+//! let mut reader = JsonReader::new(JsonParser::new(SliceJsonFeeder::new(json)));
+//!
+//! let mut name = String::new();
+//! let mut tags = Vec::new();
+//! reader.begin_object().unwrap();
+//! while reader.has_next().unwrap() {
+//!     match reader.next_name().unwrap() {
+//!         "name" => name = reader.next_string().unwrap().to_string(),
+//!         "tags" => {
+//!             reader.begin_array().unwrap();
+//!             while reader.has_next().unwrap() {
+//!                 tags.push(reader.next_string().unwrap().to_string());
+//!             }
+//!             reader.end_array().unwrap();
+//!         }
+//!         _ => reader.skip_value().unwrap(),
+//!     }
+//! }
+//! reader.end_object().unwrap();
+//!
+//! assert_eq!(name, "Elvis");
+//! assert_eq!(tags, vec!["singer", "actor"]);
+//! ```
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{FillError, FillJsonFeeder},
+    parser::{InvalidFloatValueError, InvalidIntValueError, InvalidStringValueError, ParserError},
+    storage::{HeapStorage, ParserStorage},
+    JsonEvent, JsonParser,
+};
+
+/// An error that can happen while reading from a [`JsonReader`]
+#[derive(Error, Debug)]
+pub enum ReaderError {
+    /// The JSON text could not be parsed
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    /// The feeder could not provide more input
+    #[error(transparent)]
+    Fill(#[from] FillError),
+
+    /// The next token is not of the expected type. `None` means that the
+    /// end of the JSON text has been reached.
+    #[error("expected {expected}, found {found:?}")]
+    UnexpectedEvent {
+        expected: &'static str,
+        found: Option<JsonEvent>,
+    },
+
+    /// The string value could not be read
+    #[error(transparent)]
+    String(#[from] InvalidStringValueError),
+
+    /// The integer value could not be read
+    #[error(transparent)]
+    Int(#[from] InvalidIntValueError),
+
+    /// The float value could not be read
+    #[error(transparent)]
+    Float(#[from] InvalidFloatValueError),
+}
+
+/// A pull-style reader with typed accessors. See the [module
+/// documentation](self).
+pub struct JsonReader<T, S = HeapStorage> {
+    parser: JsonParser<T, S>,
+
+    /// An event that has been read from the parser by [`Self::peek()`] but
+    /// not consumed yet
+    peeked: Option<Option<JsonEvent>>,
+}
+
+impl<T, S> JsonReader<T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Create a new reader that reads from the given parser
+    pub fn new(parser: JsonParser<T, S>) -> Self {
+        JsonReader {
+            parser,
+            peeked: None,
+        }
+    }
+
+    /// Get the underlying parser
+    pub fn parser(&self) -> &JsonParser<T, S> {
+        &self.parser
+    }
+
+    /// Consume the reader and return the underlying parser
+    pub fn into_inner(self) -> JsonParser<T, S> {
+        self.parser
+    }
+
+    /// Return the type of the next token without consuming it. `None`
+    /// means that the end of the JSON text has been reached.
+    pub fn peek(&mut self) -> Result<Option<JsonEvent>, ReaderError> {
+        if let Some(e) = self.peeked {
+            return Ok(e);
+        }
+        let e = loop {
+            match self.parser.next_event()? {
+                Some(JsonEvent::NeedMoreInput) => self.parser.feeder.fill()?,
+                e => break e,
+            }
+        };
+        self.peeked = Some(e);
+        Ok(e)
+    }
+
+    /// Consume the next token
+    fn next(&mut self) -> Result<Option<JsonEvent>, ReaderError> {
+        let e = self.peek()?;
+        self.peeked = None;
+        Ok(e)
+    }
+
+    /// Consume the next token and check that it is the expected one
+    fn expect(&mut self, expected: &'static str, event: JsonEvent) -> Result<(), ReaderError> {
+        self.expect_any(expected, |e| e == event).map(|_| ())
+    }
+
+    /// Consume the next token and check that it matches
+    fn expect_any(
+        &mut self,
+        expected: &'static str,
+        matches: impl Fn(JsonEvent) -> bool,
+    ) -> Result<JsonEvent, ReaderError> {
+        match self.peek()? {
+            Some(e) if matches(e) => {
+                self.peeked = None;
+                Ok(e)
+            }
+            found => Err(ReaderError::UnexpectedEvent { expected, found }),
+        }
+    }
+
+    /// Returns `true` if the current object or array has more members
+    pub fn has_next(&mut self) -> Result<bool, ReaderError> {
+        Ok(!matches!(
+            self.peek()?,
+            None | Some(JsonEvent::EndObject | JsonEvent::EndArray)
+        ))
+    }
+
+    /// Consume the start of an object
+    pub fn begin_object(&mut self) -> Result<(), ReaderError> {
+        self.expect("start of object", JsonEvent::StartObject)
+    }
+
+    /// Consume the end of an object
+    pub fn end_object(&mut self) -> Result<(), ReaderError> {
+        self.expect("end of object", JsonEvent::EndObject)
+    }
+
+    /// Consume the start of an array
+    pub fn begin_array(&mut self) -> Result<(), ReaderError> {
+        self.expect("start of array", JsonEvent::StartArray)
+    }
+
+    /// Consume the end of an array
+    pub fn end_array(&mut self) -> Result<(), ReaderError> {
+        self.expect("end of array", JsonEvent::EndArray)
+    }
+
+    /// Consume a field name and return it
+    pub fn next_name(&mut self) -> Result<&str, ReaderError> {
+        self.expect("field name", JsonEvent::FieldName)?;
+        Ok(self.parser.current_str()?)
+    }
+
+    /// Consume a string value and return it
+    pub fn next_string(&mut self) -> Result<&str, ReaderError> {
+        self.expect("string", JsonEvent::ValueString)?;
+        Ok(self.parser.current_str()?)
+    }
+
+    /// Consume an integer value and return it
+    pub fn next_i64(&mut self) -> Result<i64, ReaderError> {
+        self.expect("integer", JsonEvent::ValueInt)?;
+        Ok(self.parser.current_int()?)
+    }
+
+    /// Consume a non-negative integer value and return it
+    pub fn next_u64(&mut self) -> Result<u64, ReaderError> {
+        self.expect("integer", JsonEvent::ValueInt)?;
+        Ok(self.parser.current_int()?)
+    }
+
+    /// Consume a number (integer or floating point) and return it as a float
+    pub fn next_f64(&mut self) -> Result<f64, ReaderError> {
+        self.expect_any("number", |e| {
+            matches!(e, JsonEvent::ValueInt | JsonEvent::ValueFloat)
+        })?;
+        Ok(self.parser.current_float()?)
+    }
+
+    /// Consume a boolean value and return it
+    pub fn next_bool(&mut self) -> Result<bool, ReaderError> {
+        let e = self.expect_any("boolean", |e| {
+            matches!(e, JsonEvent::ValueTrue | JsonEvent::ValueFalse)
+        })?;
+        Ok(e == JsonEvent::ValueTrue)
+    }
+
+    /// Consume a `null` value
+    pub fn next_null(&mut self) -> Result<(), ReaderError> {
+        self.expect("null", JsonEvent::ValueNull)
+    }
+
+    /// Read an array and call the given function for each element. The
+    /// function must consume exactly one value. The start and the end of
+    /// the array are consumed by this function.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::reader::{JsonReader, ReaderError};
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"{"name": "Elvis", "scores": [1, 2, 3]}"#.as_bytes();
+    /// let mut reader = JsonReader::new(JsonParser::new(SliceJsonFeeder::new(json)));
+    ///
+    /// let mut scores = Vec::new();
+    /// reader
+    ///     .read_object(|key, reader| match key {
+    ///         "scores" => reader.read_array(|reader| {
+    ///             scores.push(reader.next_i64()?);
+    ///             Ok::<_, ReaderError>(())
+    ///         }),
+    ///         _ => reader.skip_value(),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(scores, vec![1, 2, 3]);
+    /// ```
+    pub fn read_array<E>(&mut self, mut f: impl FnMut(&mut Self) -> Result<(), E>) -> Result<(), E>
+    where
+        E: From<ReaderError>,
+    {
+        self.begin_array()?;
+        while self.has_next()? {
+            f(self)?;
+        }
+        self.end_array()?;
+        Ok(())
+    }
+
+    /// Read an object and call the given function for each member with the
+    /// member's name. The function must consume exactly one value. The start
+    /// and the end of the object are consumed by this function. See
+    /// [`Self::read_array()`] for an example.
+    pub fn read_object<E>(
+        &mut self,
+        mut f: impl FnMut(&str, &mut Self) -> Result<(), E>,
+    ) -> Result<(), E>
+    where
+        E: From<ReaderError>,
+    {
+        self.begin_object()?;
+        let mut key = String::new();
+        while self.has_next()? {
+            key.clear();
+            key.push_str(self.next_name()?);
+            f(&key, self)?;
+        }
+        self.end_object()?;
+        Ok(())
+    }
+
+    /// Skip the next value including all nested values if it is an object
+    /// or an array. If the next token is a field name, the field name and
+    /// its value are skipped.
+    pub fn skip_value(&mut self) -> Result<(), ReaderError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next()? {
+                Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+                Some(JsonEvent::EndObject | JsonEvent::EndArray) if depth > 0 => depth -= 1,
+                Some(JsonEvent::FieldName) => continue,
+                found @ (None | Some(JsonEvent::EndObject | JsonEvent::EndArray)) => {
+                    return Err(ReaderError::UnexpectedEvent {
+                        expected: "value",
+                        found,
+                    });
+                }
+                Some(_) => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::SliceJsonFeeder, JsonEvent, JsonParser};
+
+    use super::{JsonReader, ReaderError};
+
+    fn reader(json: &[u8]) -> JsonReader<SliceJsonFeeder<'_>> {
+        JsonReader::new(JsonParser::new(SliceJsonFeeder::new(json)))
+    }
+
+    /// Test that values of all types can be read
+    #[test]
+    fn values() {
+        let mut r = reader(br#"["a", -1, 18446744073709551615, 1.5, 2, true, false, null]"#);
+        r.begin_array().unwrap();
+        assert_eq!(r.next_string().unwrap(), "a");
+        assert_eq!(r.next_i64().unwrap(), -1);
+        assert_eq!(r.next_u64().unwrap(), u64::MAX);
+        assert_eq!(r.next_f64().unwrap(), 1.5);
+        assert_eq!(r.next_f64().unwrap(), 2.0);
+        assert!(r.next_bool().unwrap());
+        assert!(!r.next_bool().unwrap());
+        r.next_null().unwrap();
+        assert!(!r.has_next().unwrap());
+        r.end_array().unwrap();
+        assert_eq!(r.peek().unwrap(), None);
+    }
+
+    /// Test that nested values and fields can be skipped
+    #[test]
+    fn skip() {
+        let mut r = reader(br#"{"a": {"b": [1, {"c": 2}]}, "d": 3, "e": [], "f": 4}"#);
+        r.begin_object().unwrap();
+        r.skip_value().unwrap();
+        assert_eq!(r.next_name().unwrap(), "d");
+        r.skip_value().unwrap();
+        assert_eq!(r.next_name().unwrap(), "e");
+        r.skip_value().unwrap();
+        assert_eq!(r.next_name().unwrap(), "f");
+        assert_eq!(r.next_i64().unwrap(), 4);
+        assert!(matches!(
+            r.skip_value(),
+            Err(ReaderError::UnexpectedEvent {
+                found: Some(JsonEvent::EndObject),
+                ..
+            })
+        ));
+    }
+
+    /// Test that nested arrays and objects can be read with closures
+    #[test]
+    fn closures() {
+        let mut r = reader(br#"[{"a": 1, "b": [2, 3]}, {"c": 4}]"#);
+        let mut values = Vec::new();
+        r.read_array(|r| {
+            r.read_object(|key, r| {
+                if key == "b" {
+                    r.read_array(|r| {
+                        values.push((key.to_string(), r.next_i64()?));
+                        Ok::<_, ReaderError>(())
+                    })
+                } else {
+                    values.push((key.to_string(), r.next_i64()?));
+                    Ok(())
+                }
+            })
+        })
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                ("a".to_string(), 1),
+                ("b".to_string(), 2),
+                ("b".to_string(), 3),
+                ("c".to_string(), 4)
+            ]
+        );
+
+        // errors of the closure are returned
+        let mut r = reader(b"[1, 2]");
+        let e = r.read_array(|_| {
+            Err(ReaderError::UnexpectedEvent {
+                expected: "nothing",
+                found: None,
+            })
+        });
+        assert!(matches!(
+            e,
+            Err(ReaderError::UnexpectedEvent {
+                expected: "nothing",
+                ..
+            })
+        ));
+    }
+
+    /// Test that unexpected tokens are reported and not consumed
+    #[test]
+    fn unexpected() {
+        let mut r = reader(br#"[1]"#);
+        assert!(matches!(
+            r.begin_object(),
+            Err(ReaderError::UnexpectedEvent {
+                expected: "start of object",
+                found: Some(JsonEvent::StartArray)
+            })
+        ));
+        r.begin_array().unwrap();
+        assert!(r.next_string().is_err());
+        assert_eq!(r.next_i64().unwrap(), 1);
+    }
+}
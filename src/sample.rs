@@ -0,0 +1,283 @@
+//! Preview the first elements of a huge top-level array.
+//!
+//! [`ArrayHead`] reads a JSON text that consists of a single array and
+//! returns the events or values of its first `n` elements. As soon as the
+//! `n`-th element is complete, it stops without reading any more input, so
+//! previewing an export of several gigabytes only costs as much as parsing
+//! its first elements. The events end with a [`JsonEvent::EndArray`] in any
+//! case, so they always describe a complete array and can, for example, be
+//! forwarded to an [emitter](crate::emitter) as they are.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sample::ArrayHead;
+//! use actson::value::JsonValue;
+//! use actson::JsonParser;
+//!
+//! let json = br#"[{"id": 1}, {"id": 2}, {"id": 3}, {"id": 4}]"#;
+//! let head = ArrayHead::new(JsonParser::new(SliceJsonFeeder::new(json)), 2);
+//!
+//! let values = head.collect::<Result<Vec<_>, _>>().unwrap();
+//! assert_eq!(values.len(), 2);
+//! assert_eq!(values[1].get("id"), Some(&JsonValue::Int(2)));
+//! ```
+//!
+//! If the whole JSON text is available as a slice, use
+//! [`ArraySplitter`](crate::split::ArraySplitter) together with
+//! [`Iterator::take()`] to get the raw bytes of the first elements instead.
+//! To deserialize them, use [`Iterator::take()`] on the iterator returned by
+//! [`array_iter()`](crate::serde_json::array_iter) (with the `serde_json`
+//! feature).
+
+use crate::{
+    feeder::{FillJsonFeeder, JsonFeeder},
+    parser::NextValueError,
+    sink::{JsonEventSink, ParseWithError},
+    storage::{HeapStorage, ParserStorage},
+    value::{JsonValue, JsonValueBuilder},
+    JsonEvent, JsonParser,
+};
+
+/// Reads the first elements of a top-level array. See the [module
+/// documentation](self).
+pub struct ArrayHead<T, S = HeapStorage> {
+    parser: JsonParser<T, S>,
+
+    /// The number of elements that have not been started yet
+    remaining: usize,
+
+    /// The nesting depth of the current event: 0 before the array, 1
+    /// between its elements, and more inside an element
+    depth: usize,
+
+    /// `true` if the array has ended or an error has occurred
+    done: bool,
+}
+
+impl<T, S> ArrayHead<T, S>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    /// Create a new instance that reads up to `n` elements of the array
+    /// from the given parser
+    pub fn new(parser: JsonParser<T, S>, n: usize) -> Self {
+        ArrayHead {
+            parser,
+            remaining: n,
+            depth: 0,
+            done: false,
+        }
+    }
+
+    /// Get the underlying parser (e.g. to get the current value or the
+    /// number of bytes consumed so far)
+    pub fn parser(&self) -> &JsonParser<T, S> {
+        &self.parser
+    }
+
+    /// Consume this instance and return the underlying parser
+    pub fn into_inner(self) -> JsonParser<T, S> {
+        self.parser
+    }
+
+    /// Return the next event of the array. After the last requested element,
+    /// the method returns [`JsonEvent::EndArray`] without consuming further
+    /// input, and `None` after that. Like [`JsonParser::next_event()`], the
+    /// method returns [`JsonEvent::NeedMoreInput`] if the feeder needs to be
+    /// filled.
+    ///
+    /// Returns [`NextValueError::UnexpectedEvent`] if the JSON text does not
+    /// start with an array.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, NextValueError> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.depth == 1 && self.remaining == 0 {
+            self.done = true;
+            return Ok(Some(JsonEvent::EndArray));
+        }
+
+        let event = match self.parser.next_event() {
+            Ok(Some(e)) => e,
+            Ok(None) => {
+                self.done = true;
+                return Ok(None);
+            }
+            Err(e) => {
+                self.done = true;
+                return Err(e.into());
+            }
+        };
+
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartArray if self.depth == 0 => self.depth = 1,
+            _ if self.depth == 0 => {
+                self.done = true;
+                return Err(NextValueError::UnexpectedEvent {
+                    expected: "start of array",
+                    found: Some(event),
+                });
+            }
+            JsonEvent::EndArray if self.depth == 1 => self.done = true,
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                if self.depth == 1 {
+                    self.remaining -= 1;
+                }
+                self.depth += 1;
+            }
+            JsonEvent::EndObject | JsonEvent::EndArray => self.depth -= 1,
+            _ if self.depth == 1 => self.remaining -= 1,
+            _ => {}
+        }
+        Ok(Some(event))
+    }
+}
+
+impl<T, S> ArrayHead<T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Return the value of the next element or `None` if all requested
+    /// elements have been returned or the array has ended. The feeder is
+    /// filled as needed.
+    pub fn next_value(&mut self) -> Result<Option<JsonValue>, ParseWithError<NextValueError>> {
+        let mut builder = JsonValueBuilder::new();
+        loop {
+            let event = match self.next_event() {
+                Ok(Some(e)) => e,
+                Ok(None) => return Ok(None),
+                Err(NextValueError::Parser(e)) => return Err(e.into()),
+                Err(e) => return Err(ParseWithError::Sink(e)),
+            };
+            match event {
+                JsonEvent::NeedMoreInput => {
+                    if let Err(e) = self.parser.feeder.fill() {
+                        self.done = true;
+                        return Err(e.into());
+                    }
+                }
+                JsonEvent::StartArray if self.depth == 1 => {}
+                JsonEvent::EndArray if self.done => return Ok(None),
+                _ => {
+                    builder
+                        .on_event(event, &self.parser)
+                        .map_err(ParseWithError::Sink)?;
+                    if self.depth == 1 {
+                        return Ok(builder.into_value());
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T, S> Iterator for ArrayHead<T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Item = Result<JsonValue, ParseWithError<NextValueError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = self.next_value().transpose();
+        if matches!(r, Some(Err(_))) {
+            self.done = true;
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, BufReader, Read};
+
+    use crate::{
+        feeder::{BufReaderJsonFeeder, SliceJsonFeeder},
+        parser::NextValueError,
+        sink::ParseWithError,
+        value::JsonValue,
+        JsonEvent, JsonParser,
+    };
+
+    use super::ArrayHead;
+
+    /// Test that the events of the first elements are followed by the end
+    /// of the array and that no more input is consumed after that
+    #[test]
+    fn events() {
+        let json = br#"[[1, {"a": 2}], 3, "b", 4, "#;
+        let mut head = ArrayHead::new(JsonParser::new(SliceJsonFeeder::new(json)), 3);
+        let mut events = Vec::new();
+        while let Some(e) = head.next_event().unwrap() {
+            events.push(e);
+        }
+        assert_eq!(
+            events,
+            vec![
+                JsonEvent::StartArray,
+                JsonEvent::StartArray,
+                JsonEvent::ValueInt,
+                JsonEvent::StartObject,
+                JsonEvent::FieldName,
+                JsonEvent::ValueInt,
+                JsonEvent::EndObject,
+                JsonEvent::EndArray,
+                JsonEvent::ValueInt,
+                JsonEvent::ValueString,
+                JsonEvent::EndArray,
+            ]
+        );
+        assert_eq!(head.parser().parsed_bytes(), 22);
+    }
+
+    /// An endless array `[1, 1, 1, ...`
+    struct Endless(usize);
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            for b in buf.iter_mut() {
+                *b = match self.0 {
+                    0 => b'[',
+                    i => b"1, "[(i - 1) % 3],
+                };
+                self.0 += 1;
+            }
+            Ok(buf.len())
+        }
+    }
+
+    /// Test that values are returned from an endless array and that the
+    /// iterator ends after the requested number of elements
+    #[test]
+    fn values() {
+        let feeder = BufReaderJsonFeeder::new(BufReader::with_capacity(16, Endless(0)));
+        let values = ArrayHead::new(JsonParser::new(feeder), 5)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![JsonValue::Int(1); 5]);
+    }
+
+    /// Test that shorter arrays are returned completely and that other
+    /// values are rejected
+    #[test]
+    fn short() {
+        let json = br#"[{"a": []}, null]"#;
+        let head = ArrayHead::new(JsonParser::new(SliceJsonFeeder::new(json)), 10);
+        let values = head.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1], JsonValue::Null);
+
+        let mut head = ArrayHead::new(JsonParser::new(SliceJsonFeeder::new(b"{}")), 10);
+        assert!(matches!(
+            head.next(),
+            Some(Err(ParseWithError::Sink(NextValueError::UnexpectedEvent {
+                found: Some(JsonEvent::StartObject),
+                ..
+            })))
+        ));
+        assert!(head.next().is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! Functions that determine the length of byte runs the parser can consume
+//! in bulk without running its state machine for every single byte. If the
+//! `simd` feature is enabled, SSE2/AVX2 (x86_64) or NEON (aarch64)
+//! instructions are used. Otherwise, or on other platforms, string runs are
+//! found with `memchr` and the other functions fall back to scalar
+//! implementations.
+
+/// Return `true` if the given byte can be part of a string without being
+/// handled by the state machine (i.e. it is neither a quote, nor a
+/// backslash, nor a control character)
+#[inline]
+fn is_plain_string_byte(b: u8) -> bool {
+    b >= 0x20 && b != b'"' && b != b'\\'
+}
+
+/// Return `true` if the given byte is insignificant white space
+#[inline]
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Return the number of leading bytes in `input` that can be part of a
+/// string without being handled by the state machine
+#[inline]
+pub(crate) fn string_run_len(input: &[u8]) -> usize {
+    #[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        simd::string_run_len(input)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    {
+        // find the next quote or backslash and then make sure there are no
+        // control characters in between
+        let end = memchr::memchr2(b'"', b'\\', input).unwrap_or(input.len());
+        scalar_run_len(&input[..end], is_plain_string_byte)
+    }
+}
+
+/// Return the number of leading white space bytes in `input`
+#[inline]
+pub(crate) fn whitespace_run_len(input: &[u8]) -> usize {
+    // white space runs are usually short (e.g. a single space or a newline
+    // followed by indentation), so check the first byte before doing more
+    // expensive work
+    if input.first().is_none_or(|b| !is_whitespace(*b)) {
+        return 0;
+    }
+    #[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        simd::whitespace_run_len(input)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    {
+        scalar_run_len(input, is_whitespace)
+    }
+}
+
+/// Return the number of leading ASCII digits in `input`
+#[inline]
+pub(crate) fn digit_run_len(input: &[u8]) -> usize {
+    #[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        simd::digit_run_len(input)
+    }
+    #[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+    {
+        scalar_run_len(input, |b| b.is_ascii_digit())
+    }
+}
+
+/// Return the number of leading bytes in `input` matching the given predicate
+#[inline]
+fn scalar_run_len(input: &[u8], pred: impl Fn(u8) -> bool) -> usize {
+    input.iter().position(|b| !pred(*b)).unwrap_or(input.len())
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[allow(unused_unsafe)]
+mod simd {
+    use super::{is_plain_string_byte, is_whitespace, scalar_run_len};
+
+    /// 16-byte vectors (SSE2 is always available on x86_64)
+    #[cfg(not(target_feature = "avx2"))]
+    mod ops {
+        pub(super) use std::arch::x86_64::{
+            __m128i as Vector, _mm_cmpeq_epi8 as cmpeq, _mm_loadu_si128 as loadu,
+            _mm_max_epu8 as max, _mm_movemask_epi8 as movemask, _mm_or_si128 as or,
+            _mm_set1_epi8 as splat, _mm_sub_epi8 as sub,
+        };
+        pub(super) const WIDTH: usize = 16;
+        pub(super) const MASK: u32 = 0xFFFF;
+    }
+
+    /// 32-byte vectors if the crate is compiled with AVX2 support (e.g.
+    /// with `-C target-cpu=native` on a CPU that supports it)
+    #[cfg(target_feature = "avx2")]
+    mod ops {
+        pub(super) use std::arch::x86_64::{
+            __m256i as Vector, _mm256_cmpeq_epi8 as cmpeq, _mm256_loadu_si256 as loadu,
+            _mm256_max_epu8 as max, _mm256_movemask_epi8 as movemask, _mm256_or_si256 as or,
+            _mm256_set1_epi8 as splat, _mm256_sub_epi8 as sub,
+        };
+        pub(super) const WIDTH: usize = 32;
+        pub(super) const MASK: u32 = u32::MAX;
+    }
+
+    use ops::*;
+
+    /// Process `input` in blocks and use `mask` to compute a bit mask for
+    /// each block in which a set bit marks a byte that ends the run
+    #[inline]
+    fn run_len(input: &[u8], mask: impl Fn(Vector) -> u32, pred: impl Fn(u8) -> bool) -> usize {
+        let mut i = 0;
+        while i + WIDTH <= input.len() {
+            // SAFETY: we checked that there are at least `WIDTH` bytes
+            // available at position `i`. Unaligned loads are allowed.
+            let v = unsafe { loadu(input.as_ptr().add(i) as *const Vector) };
+            let m = mask(v);
+            if m != 0 {
+                return i + m.trailing_zeros() as usize;
+            }
+            i += WIDTH;
+        }
+        i + scalar_run_len(&input[i..], pred)
+    }
+
+    pub(super) fn string_run_len(input: &[u8]) -> usize {
+        run_len(
+            input,
+            |v| unsafe {
+                let quote = cmpeq(v, splat(b'"' as i8));
+                let backslash = cmpeq(v, splat(b'\\' as i8));
+                // v <= 0x1F <=> max(v, 0x1F) == 0x1F (unsigned)
+                let control = cmpeq(max(v, splat(0x1F)), splat(0x1F));
+                movemask(or(or(quote, backslash), control)) as u32
+            },
+            is_plain_string_byte,
+        )
+    }
+
+    pub(super) fn whitespace_run_len(input: &[u8]) -> usize {
+        run_len(
+            input,
+            |v| unsafe {
+                let space = cmpeq(v, splat(b' ' as i8));
+                let tab = cmpeq(v, splat(b'\t' as i8));
+                let nl = cmpeq(v, splat(b'\n' as i8));
+                let cr = cmpeq(v, splat(b'\r' as i8));
+                !(movemask(or(or(space, tab), or(nl, cr))) as u32) & MASK
+            },
+            is_whitespace,
+        )
+    }
+
+    pub(super) fn digit_run_len(input: &[u8]) -> usize {
+        run_len(
+            input,
+            |v| unsafe {
+                // v - '0' <= 9 (unsigned) <=> v is a digit
+                let d = sub(v, splat(b'0' as i8));
+                let digit = cmpeq(max(d, splat(9)), splat(9));
+                !(movemask(digit) as u32) & MASK
+            },
+            |b| b.is_ascii_digit(),
+        )
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+#[allow(unused_unsafe)]
+mod simd {
+    use std::arch::aarch64::*;
+
+    use super::{is_plain_string_byte, is_whitespace, scalar_run_len};
+
+    const WIDTH: usize = 16;
+
+    /// Process `input` in blocks of 16 bytes and use `mask` to compute a
+    /// vector in which a non-zero lane marks a byte that ends the run
+    #[inline]
+    fn run_len(
+        input: &[u8],
+        mask: impl Fn(uint8x16_t) -> uint8x16_t,
+        pred: impl Fn(u8) -> bool + Copy,
+    ) -> usize {
+        let mut i = 0;
+        while i + WIDTH <= input.len() {
+            // SAFETY: we checked that there are at least 16 bytes available
+            // at position `i`. NEON is always available on aarch64.
+            let any = unsafe {
+                let m = mask(vld1q_u8(input.as_ptr().add(i)));
+                vmaxvq_u8(m) != 0
+            };
+            if any {
+                return i + scalar_run_len(&input[i..i + WIDTH], pred);
+            }
+            i += WIDTH;
+        }
+        i + scalar_run_len(&input[i..], pred)
+    }
+
+    pub(super) fn string_run_len(input: &[u8]) -> usize {
+        run_len(
+            input,
+            |v| unsafe {
+                let quote = vceqq_u8(v, vdupq_n_u8(b'"'));
+                let backslash = vceqq_u8(v, vdupq_n_u8(b'\\'));
+                let control = vcltq_u8(v, vdupq_n_u8(0x20));
+                vorrq_u8(vorrq_u8(quote, backslash), control)
+            },
+            is_plain_string_byte,
+        )
+    }
+
+    pub(super) fn whitespace_run_len(input: &[u8]) -> usize {
+        run_len(
+            input,
+            |v| unsafe {
+                let space = vceqq_u8(v, vdupq_n_u8(b' '));
+                let tab = vceqq_u8(v, vdupq_n_u8(b'\t'));
+                let nl = vceqq_u8(v, vdupq_n_u8(b'\n'));
+                let cr = vceqq_u8(v, vdupq_n_u8(b'\r'));
+                vmvnq_u8(vorrq_u8(vorrq_u8(space, tab), vorrq_u8(nl, cr)))
+            },
+            is_whitespace,
+        )
+    }
+
+    pub(super) fn digit_run_len(input: &[u8]) -> usize {
+        run_len(
+            input,
+            |v| unsafe {
+                let d = vsubq_u8(v, vdupq_n_u8(b'0'));
+                vcgtq_u8(d, vdupq_n_u8(9))
+            },
+            |b: u8| b.is_ascii_digit(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{digit_run_len, string_run_len, whitespace_run_len};
+
+    /// Test that string runs stop at quotes, backslashes, and control
+    /// characters, also if they are located beyond the first block
+    #[test]
+    fn string_runs() {
+        assert_eq!(string_run_len(b""), 0);
+        assert_eq!(string_run_len(b"\"abc"), 0);
+        assert_eq!(string_run_len(b"abc\""), 3);
+        assert_eq!(string_run_len(b"abc"), 3);
+        assert_eq!(string_run_len("Bj\u{0153}rn\\n".as_bytes()), 6);
+        for stop in [b'"', b'\\', b'\n', 0x00, 0x1F] {
+            for pos in 0..70 {
+                let mut input = vec![b'x'; 80];
+                input[40] = 0xC5;
+                input[pos] = stop;
+                assert_eq!(string_run_len(&input), pos);
+            }
+        }
+        assert_eq!(string_run_len(&[0x7F; 70]), 70);
+        assert_eq!(string_run_len(&[0xFF; 70]), 70);
+    }
+
+    /// Test that white space runs are detected correctly
+    #[test]
+    fn whitespace_runs() {
+        assert_eq!(whitespace_run_len(b""), 0);
+        assert_eq!(whitespace_run_len(b"a "), 0);
+        assert_eq!(whitespace_run_len(b" \t\r\n{"), 4);
+        for pos in 1..70 {
+            let mut input = vec![b' '; 80];
+            input[pos] = b'"';
+            assert_eq!(whitespace_run_len(&input), pos);
+        }
+        assert_eq!(whitespace_run_len(&[b'\n'; 70]), 70);
+    }
+
+    /// Test that digit runs are detected correctly
+    #[test]
+    fn digit_runs() {
+        assert_eq!(digit_run_len(b""), 0);
+        assert_eq!(digit_run_len(b"-1"), 0);
+        assert_eq!(digit_run_len(b"0123456789."), 10);
+        for pos in 0..70 {
+            let mut input = vec![b'7'; 80];
+            input[pos] = b'e';
+            assert_eq!(digit_run_len(&input), pos);
+        }
+        assert_eq!(digit_run_len(&[b'/'; 20]), 0);
+        assert_eq!(digit_run_len(&[b':'; 20]), 0);
+    }
+}
@@ -0,0 +1,723 @@
+//! Validate JSON texts against a JSON Schema while they are being parsed.
+//!
+//! A [`SchemaValidator`] is a [`JsonEventSink`] that checks the events of a
+//! parser against a [`Schema`] without building a document in memory. It
+//! records every violation together with the JSON Pointer and the byte
+//! offset of the offending value, so huge files can be validated in a single
+//! pass. Combine it with another sink through a [`Tee`](crate::sink::Tee)
+//! to validate a JSON text while processing it.
+//!
+//! The following subset of JSON Schema is supported: boolean schemas and
+//! the keywords `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `items`, `minItems`, `maxItems`, `minimum`, and
+//! `maximum`. Values of `enum` and `const` must be strings, numbers,
+//! booleans, or `null`. All other keywords are ignored.
+//!
+//! ```
+//! use actson::schema::{Schema, ViolationKind};
+//!
+//! let schema = Schema::parse(br#"{
+//!     "type": "object",
+//!     "required": ["id"],
+//!     "properties": {
+//!         "id": {"type": "integer"},
+//!         "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "maxItems": 2}
+//!     }
+//! }"#).unwrap();
+//!
+//! assert!(schema.validate(br#"{"id": 1, "tags": ["a"]}"#).unwrap().is_empty());
+//!
+//! let violations = schema.validate(br#"{"tags": ["a", "c"]}"#).unwrap();
+//! assert_eq!(violations.len(), 2);
+//! assert_eq!(violations[0].path, "/tags/1");
+//! assert_eq!(violations[0].offset, 15);
+//! assert_eq!(violations[0].kind, ViolationKind::NotInEnum);
+//! assert_eq!(violations[1].path, "");
+//! assert_eq!(violations[1].kind, ViolationKind::MissingProperty("id".to_string()));
+//! ```
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{JsonFeeder, SliceJsonFeeder},
+    parser::NextValueError,
+    sink::JsonEventSink,
+    storage::ParserStorage,
+    value::JsonValue,
+    JsonEvent, JsonNumber, JsonParser,
+};
+
+const TYPE_NULL: u8 = 1;
+const TYPE_BOOLEAN: u8 = 1 << 1;
+const TYPE_OBJECT: u8 = 1 << 2;
+const TYPE_ARRAY: u8 = 1 << 3;
+const TYPE_NUMBER: u8 = 1 << 4;
+const TYPE_STRING: u8 = 1 << 5;
+const TYPE_INTEGER: u8 = 1 << 6;
+
+/// An error that can happen when parsing a [`Schema`]
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    /// The schema is not a valid JSON text
+    #[error(transparent)]
+    Parse(#[from] NextValueError),
+
+    /// The schema is not a valid JSON Schema or uses an unsupported feature
+    #[error("invalid schema at `{path}': {message}")]
+    Invalid {
+        /// The JSON Pointer to the invalid part of the schema
+        path: String,
+
+        /// A description of the error
+        message: &'static str,
+    },
+}
+
+/// A JSON Schema. See the [module documentation](self) for the supported
+/// keywords.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    /// `false` if no value is valid (i.e. this is the `false` schema)
+    accept: bool,
+
+    /// The allowed types as a combination of the `TYPE_*` constants
+    types: Option<u8>,
+
+    /// The allowed values
+    enum_values: Option<Vec<JsonValue>>,
+
+    /// The schemas of the properties of an object
+    properties: Vec<(String, Schema)>,
+
+    /// The names of the properties an object must have
+    required: Vec<String>,
+
+    /// The schema of properties not listed in [`Self::properties`]
+    additional_properties: Option<Box<Schema>>,
+
+    /// The schema of the elements of an array
+    items: Option<Box<Schema>>,
+
+    /// The minimum number of elements of an array
+    min_items: Option<usize>,
+
+    /// The maximum number of elements of an array
+    max_items: Option<usize>,
+
+    /// The minimum value of a number
+    minimum: Option<f64>,
+
+    /// The maximum value of a number
+    maximum: Option<f64>,
+}
+
+impl Schema {
+    /// Parse a schema from a JSON text
+    pub fn parse(json: &[u8]) -> Result<Self, SchemaError> {
+        Self::from_value(&JsonValue::parse(json)?)
+    }
+
+    /// Create a schema from a parsed JSON value
+    pub fn from_value(value: &JsonValue) -> Result<Self, SchemaError> {
+        Self::compile(value, &mut String::new())
+    }
+
+    /// Create a schema that accepts any value (`true`) or no value at all
+    /// (`false`)
+    fn boolean(accept: bool) -> Self {
+        Schema {
+            accept,
+            types: None,
+            enum_values: None,
+            properties: Vec::new(),
+            required: Vec::new(),
+            additional_properties: None,
+            items: None,
+            min_items: None,
+            max_items: None,
+            minimum: None,
+            maximum: None,
+        }
+    }
+
+    fn compile(value: &JsonValue, path: &mut String) -> Result<Self, SchemaError> {
+        let invalid = |path: &str, message| SchemaError::Invalid {
+            path: path.to_string(),
+            message,
+        };
+
+        let o = match value {
+            JsonValue::Bool(b) => return Ok(Self::boolean(*b)),
+            JsonValue::Object(o) => o,
+            _ => return Err(invalid(path, "schema must be an object or a boolean")),
+        };
+
+        let mut schema = Self::boolean(true);
+        for (keyword, v) in o.iter() {
+            let len = path.len();
+            path.push('/');
+            path.push_str(&keyword.replace('~', "~0").replace('/', "~1"));
+            match keyword {
+                "type" => {
+                    let names = match v {
+                        JsonValue::String(s) => vec![s.as_str()],
+                        JsonValue::Array(a) => a
+                            .iter()
+                            .map(|n| n.as_str().ok_or_else(|| invalid(path, "expected a string")))
+                            .collect::<Result<_, _>>()?,
+                        _ => return Err(invalid(path, "expected a string or an array")),
+                    };
+                    let mut types = 0;
+                    for n in names {
+                        types |= match n {
+                            "null" => TYPE_NULL,
+                            "boolean" => TYPE_BOOLEAN,
+                            "object" => TYPE_OBJECT,
+                            "array" => TYPE_ARRAY,
+                            "number" => TYPE_NUMBER,
+                            "string" => TYPE_STRING,
+                            "integer" => TYPE_INTEGER,
+                            _ => return Err(invalid(path, "unknown type")),
+                        };
+                    }
+                    schema.types = Some(types);
+                }
+
+                "enum" | "const" => {
+                    let values = match (keyword, v) {
+                        ("enum", JsonValue::Array(a)) => a.clone(),
+                        ("enum", _) => return Err(invalid(path, "expected an array")),
+                        _ => vec![v.clone()],
+                    };
+                    if values
+                        .iter()
+                        .any(|v| matches!(v, JsonValue::Array(_) | JsonValue::Object(_)))
+                    {
+                        return Err(invalid(path, "objects and arrays are not supported"));
+                    }
+                    schema.enum_values = Some(values);
+                }
+
+                "properties" => {
+                    let Some(props) = v.as_object() else {
+                        return Err(invalid(path, "expected an object"));
+                    };
+                    for (name, p) in props.iter() {
+                        let len = path.len();
+                        path.push('/');
+                        path.push_str(&name.replace('~', "~0").replace('/', "~1"));
+                        schema
+                            .properties
+                            .push((name.to_string(), Self::compile(p, path)?));
+                        path.truncate(len);
+                    }
+                }
+
+                "required" => {
+                    schema.required = v
+                        .as_array()
+                        .ok_or_else(|| invalid(path, "expected an array"))?
+                        .iter()
+                        .map(|n| {
+                            n.as_str()
+                                .map(str::to_string)
+                                .ok_or_else(|| invalid(path, "expected a string"))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+
+                "additionalProperties" => {
+                    schema.additional_properties = Some(Box::new(Self::compile(v, path)?));
+                }
+
+                "items" => {
+                    if v.as_array().is_some() {
+                        return Err(invalid(path, "arrays of schemas are not supported"));
+                    }
+                    schema.items = Some(Box::new(Self::compile(v, path)?));
+                }
+
+                "minItems" | "maxItems" => {
+                    let n = v
+                        .as_u64()
+                        .and_then(|n| usize::try_from(n).ok())
+                        .ok_or_else(|| invalid(path, "expected a non-negative integer"))?;
+                    if keyword == "minItems" {
+                        schema.min_items = Some(n);
+                    } else {
+                        schema.max_items = Some(n);
+                    }
+                }
+
+                "minimum" | "maximum" => {
+                    let n = v
+                        .as_f64()
+                        .ok_or_else(|| invalid(path, "expected a number"))?;
+                    if keyword == "minimum" {
+                        schema.minimum = Some(n);
+                    } else {
+                        schema.maximum = Some(n);
+                    }
+                }
+
+                _ => {}
+            }
+            path.truncate(len);
+        }
+
+        Ok(schema)
+    }
+
+    /// Validate the given JSON text against this schema and return all
+    /// violations in document order
+    pub fn validate(&self, json: &[u8]) -> Result<Vec<Violation>, NextValueError> {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        let mut validator = SchemaValidator::new(self);
+        while let Some(e) = parser.next_event()? {
+            validator.on_event(e, &parser)?;
+        }
+        Ok(validator.into_violations())
+    }
+}
+
+/// The reason why a value does not match a [`Schema`]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ViolationKind {
+    /// The schema does not allow any value (i.e. it is the `false` schema)
+    #[error("value is not allowed")]
+    NotAllowed,
+
+    /// The type of the value is not allowed
+    #[error("value of type `{0}' is not allowed")]
+    InvalidType(&'static str),
+
+    /// The value is not one of the values listed in `enum` or `const`
+    #[error("value is not one of the allowed values")]
+    NotInEnum,
+
+    /// An object does not have a required property
+    #[error("missing required property `{0}'")]
+    MissingProperty(String),
+
+    /// An object has a property that is not allowed by
+    /// `additionalProperties`
+    #[error("property `{0}' is not allowed")]
+    AdditionalProperty(String),
+
+    /// An array has fewer elements than `minItems`
+    #[error("array has {len} elements but at least {min} are required")]
+    TooFewItems {
+        /// The minimum number of elements
+        min: usize,
+
+        /// The actual number of elements
+        len: usize,
+    },
+
+    /// An array has more elements than `maxItems`
+    #[error("array has {len} elements but at most {max} are allowed")]
+    TooManyItems {
+        /// The maximum number of elements
+        max: usize,
+
+        /// The actual number of elements
+        len: usize,
+    },
+
+    /// A number is less than `minimum`
+    #[error("value is less than the minimum of {0}")]
+    BelowMinimum(f64),
+
+    /// A number is greater than `maximum`
+    #[error("value is greater than the maximum of {0}")]
+    AboveMaximum(f64),
+}
+
+/// A value that does not match a [`Schema`]
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{kind} at `{path}' (offset {offset})")]
+pub struct Violation {
+    /// The JSON Pointer to the value (e.g. `/items/0/id`)
+    pub path: String,
+
+    /// The byte offset of the value in the JSON text. For violations that
+    /// concern an object or array as a whole (e.g. a missing property), this
+    /// is the offset of its opening bracket.
+    pub offset: usize,
+
+    /// The reason for the violation
+    pub kind: ViolationKind,
+}
+
+/// An object or array that is currently open
+struct Frame<'s> {
+    /// The schema of the container or `None` if it is not constrained
+    schema: Option<&'s Schema>,
+
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The number of elements if the container is an array
+    len: usize,
+
+    /// The most recent field name if the container is an object
+    key: String,
+
+    /// Whether each of the schema's required properties has been seen
+    seen: Vec<bool>,
+
+    /// The offset of the container in the JSON text
+    offset: usize,
+
+    /// The length of [`SchemaValidator::path`] before the container was
+    /// entered
+    path_len: usize,
+}
+
+/// Checks the events of a parser against a [`Schema`]. See the [module
+/// documentation](self).
+pub struct SchemaValidator<'s> {
+    schema: &'s Schema,
+    stack: Vec<Frame<'s>>,
+
+    /// The JSON Pointer to the innermost open container
+    path: String,
+
+    violations: Vec<Violation>,
+}
+
+impl<'s> SchemaValidator<'s> {
+    /// Create a validator for the given schema. In streaming mode, each
+    /// top-level value is validated against the schema.
+    pub fn new(schema: &'s Schema) -> Self {
+        SchemaValidator {
+            schema,
+            stack: Vec::new(),
+            path: String::new(),
+            violations: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if no violations have been found so far
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Get the violations found so far in document order
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Consume the validator and return the violations found
+    pub fn into_violations(self) -> Vec<Violation> {
+        self.violations
+    }
+
+    /// Get the JSON Pointer to the value that has just started
+    fn value_path(&self) -> String {
+        let mut path = self.path.clone();
+        if let Some(frame) = self.stack.last() {
+            path.push('/');
+            if frame.is_array {
+                path.push_str(&(frame.len - 1).to_string());
+            } else {
+                path.push_str(&frame.key.replace('~', "~0").replace('/', "~1"));
+            }
+        }
+        path
+    }
+
+    fn report(&mut self, path: String, offset: usize, kind: ViolationKind) {
+        self.violations.push(Violation { path, offset, kind });
+    }
+
+    /// Determine the schema of the value that has just started and count it
+    /// as a member or element of its parent. Report a violation if the
+    /// parent does not allow the value.
+    fn enter_value(&mut self, offset: usize) -> Option<&'s Schema> {
+        let Some(frame) = self.stack.last_mut() else {
+            return Some(self.schema);
+        };
+        let schema = frame.schema?;
+        if frame.is_array {
+            frame.len += 1;
+            return schema.items.as_deref();
+        }
+
+        if let Some(i) = schema.required.iter().position(|r| *r == frame.key) {
+            frame.seen[i] = true;
+        }
+        if let Some((_, s)) = schema.properties.iter().find(|(n, _)| *n == frame.key) {
+            return Some(s);
+        }
+        match schema.additional_properties.as_deref() {
+            Some(s) if !s.accept => {
+                let kind = ViolationKind::AdditionalProperty(frame.key.clone());
+                self.report(self.value_path(), offset, kind);
+                None
+            }
+            s => s,
+        }
+    }
+
+    /// Check a value against its schema
+    fn check_value<T, S>(
+        &mut self,
+        schema: &Schema,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), NextValueError>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        let offset = parser.current_span().start;
+        if !schema.accept {
+            self.report(self.value_path(), offset, ViolationKind::NotAllowed);
+            return Ok(());
+        }
+
+        let number = match event {
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => Some(match parser.current_number()? {
+                JsonNumber::I64(i) => i as f64,
+                JsonNumber::U64(u) => u as f64,
+                JsonNumber::F64(f) => f,
+                JsonNumber::Big(s) => s.parse().unwrap_or(f64::NAN),
+            }),
+            _ => None,
+        };
+
+        if let Some(types) = schema.types {
+            let (ty, name) = match event {
+                JsonEvent::StartObject => (TYPE_OBJECT, "object"),
+                JsonEvent::StartArray => (TYPE_ARRAY, "array"),
+                JsonEvent::ValueString => (TYPE_STRING, "string"),
+                JsonEvent::ValueTrue | JsonEvent::ValueFalse => (TYPE_BOOLEAN, "boolean"),
+                JsonEvent::ValueNull => (TYPE_NULL, "null"),
+                _ if number.is_some_and(|n| n.fract() == 0.0) => {
+                    (TYPE_NUMBER | TYPE_INTEGER, "integer")
+                }
+                _ => (TYPE_NUMBER, "number"),
+            };
+            if types & ty == 0 {
+                self.report(self.value_path(), offset, ViolationKind::InvalidType(name));
+            }
+        }
+
+        if let Some(values) = &schema.enum_values {
+            let found = match event {
+                JsonEvent::ValueString => {
+                    let s = parser.current_str()?;
+                    values.iter().any(|v| v.as_str() == Some(s))
+                }
+                JsonEvent::ValueTrue | JsonEvent::ValueFalse => {
+                    let b = event.as_bool();
+                    values.iter().any(|v| v.as_bool() == b)
+                }
+                JsonEvent::ValueNull => values.iter().any(JsonValue::is_null),
+                JsonEvent::ValueInt | JsonEvent::ValueFloat => values
+                    .iter()
+                    .any(|v| v.as_f64().is_some() && v.as_f64() == number),
+                _ => false,
+            };
+            if !found {
+                self.report(self.value_path(), offset, ViolationKind::NotInEnum);
+            }
+        }
+
+        if let Some(n) = number {
+            if let Some(min) = schema.minimum.filter(|min| n < *min) {
+                self.report(self.value_path(), offset, ViolationKind::BelowMinimum(min));
+            }
+            if let Some(max) = schema.maximum.filter(|max| n > *max) {
+                self.report(self.value_path(), offset, ViolationKind::AboveMaximum(max));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the properties or elements of the container that has just
+    /// ended
+    fn leave_container(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        if let Some(schema) = frame.schema.filter(|s| s.accept) {
+            if frame.is_array {
+                if let Some(min) = schema.min_items.filter(|min| frame.len < *min) {
+                    let kind = ViolationKind::TooFewItems {
+                        min,
+                        len: frame.len,
+                    };
+                    self.report(self.path.clone(), frame.offset, kind);
+                }
+                if let Some(max) = schema.max_items.filter(|max| frame.len > *max) {
+                    let kind = ViolationKind::TooManyItems {
+                        max,
+                        len: frame.len,
+                    };
+                    self.report(self.path.clone(), frame.offset, kind);
+                }
+            } else {
+                for (name, seen) in schema.required.iter().zip(&frame.seen) {
+                    if !seen {
+                        let kind = ViolationKind::MissingProperty(name.clone());
+                        self.report(self.path.clone(), frame.offset, kind);
+                    }
+                }
+            }
+        }
+        self.path.truncate(frame.path_len);
+    }
+}
+
+impl JsonEventSink for SchemaValidator<'_> {
+    type Error = NextValueError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => {}
+
+            JsonEvent::FieldName => {
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.key.clear();
+                    frame.key.push_str(parser.current_str()?);
+                }
+            }
+
+            JsonEvent::EndObject | JsonEvent::EndArray => self.leave_container(),
+
+            _ => {
+                let offset = parser.current_span().start;
+                let schema = self.enter_value(offset);
+                if let Some(schema) = schema {
+                    self.check_value(schema, event, parser)?;
+                }
+                if matches!(event, JsonEvent::StartObject | JsonEvent::StartArray) {
+                    let path_len = self.path.len();
+                    self.path = self.value_path();
+                    // properties and elements of a value that is not
+                    // allowed at all are not checked any further
+                    let schema = schema.filter(|s| s.accept);
+                    self.stack.push(Frame {
+                        schema,
+                        is_array: event == JsonEvent::StartArray,
+                        len: 0,
+                        key: String::new(),
+                        seen: vec![false; schema.map_or(0, |s| s.required.len())],
+                        offset,
+                        path_len,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Schema, SchemaError, ViolationKind};
+
+    fn kinds(schema: &Schema, json: &str) -> Vec<(String, ViolationKind)> {
+        schema
+            .validate(json.as_bytes())
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.path, v.kind))
+            .collect()
+    }
+
+    /// Test that all supported keywords are checked and that violations are
+    /// reported with their paths
+    #[test]
+    fn keywords() {
+        let schema = Schema::parse(
+            br#"{
+                "type": "object",
+                "required": ["id", "name"],
+                "additionalProperties": false,
+                "properties": {
+                    "id": {"type": "integer", "minimum": 1},
+                    "name": {"type": ["string", "null"]},
+                    "a/b": {"const": 2.0},
+                    "scores": {
+                        "type": "array",
+                        "minItems": 2,
+                        "maxItems": 3,
+                        "items": {"type": "number", "maximum": 10}
+                    },
+                    "never": false
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            kinds(
+                &schema,
+                r#"{"id": 1, "name": null, "a/b": 2, "scores": [1.5, 10]}"#
+            ),
+            vec![]
+        );
+        assert_eq!(
+            kinds(
+                &schema,
+                r#"{"id": 0.5, "a/b": "2", "scores": [11, {"x": 1}, 2, 3], "never": [1], "x": 1}"#
+            ),
+            vec![
+                ("/id".to_string(), ViolationKind::InvalidType("number")),
+                ("/id".to_string(), ViolationKind::BelowMinimum(1.0)),
+                ("/a~1b".to_string(), ViolationKind::NotInEnum),
+                ("/scores/0".to_string(), ViolationKind::AboveMaximum(10.0)),
+                (
+                    "/scores/1".to_string(),
+                    ViolationKind::InvalidType("object")
+                ),
+                (
+                    "/scores".to_string(),
+                    ViolationKind::TooManyItems { max: 3, len: 4 }
+                ),
+                ("/never".to_string(), ViolationKind::NotAllowed),
+                (
+                    "/x".to_string(),
+                    ViolationKind::AdditionalProperty("x".to_string())
+                ),
+                (
+                    "".to_string(),
+                    ViolationKind::MissingProperty("name".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            kinds(&schema, r#"[{"id": 1}]"#),
+            vec![("".to_string(), ViolationKind::InvalidType("array"))]
+        );
+    }
+
+    /// Test that unsupported or invalid schemas are rejected
+    #[test]
+    fn invalid() {
+        let err = |json: &str| match Schema::parse(json.as_bytes()) {
+            Err(SchemaError::Invalid { path, .. }) => path,
+            r => panic!("unexpected result: {r:?}"),
+        };
+        assert_eq!(err("1"), "");
+        assert_eq!(err(r#"{"type": "text"}"#), "/type");
+        assert_eq!(err(r#"{"enum": [[1]]}"#), "/enum");
+        assert_eq!(
+            err(r#"{"properties": {"a": {"items": [true]}}}"#),
+            "/properties/a/items"
+        );
+        assert_eq!(err(r#"{"minItems": -1}"#), "/minItems");
+    }
+}
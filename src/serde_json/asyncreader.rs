@@ -0,0 +1,341 @@
+use std::{
+    future::Future,
+    io,
+    panic::resume_unwind,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    sync::mpsc::{channel, Receiver, Sender},
+    task::{spawn, spawn_blocking, JoinHandle},
+};
+
+use crate::{
+    feeder::{FillError, FillJsonFeeder, JsonFeeder},
+    options::JsonParserOptionsBuilder,
+    JsonParser,
+};
+
+use super::{from_feeder, DeserializeError, Deserializer};
+
+/// The number of bytes to read from the reader at once
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// The number of chunks that may be read ahead of the deserializer
+const MAX_PENDING_CHUNKS: usize = 2;
+
+/// The number of values that may be deserialized ahead of the consumer of a
+/// [`ValueStream`]
+const MAX_PENDING_VALUES: usize = 16;
+
+/// A [`FillJsonFeeder`] that blocks until the next chunk of input has been
+/// received through a channel
+struct ChannelJsonFeeder {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl ChannelJsonFeeder {
+    fn new(receiver: Receiver<io::Result<Vec<u8>>>) -> Self {
+        ChannelJsonFeeder {
+            receiver,
+            chunk: Vec::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+}
+
+impl JsonFeeder for ChannelJsonFeeder {
+    fn has_input(&self) -> bool {
+        self.pos < self.chunk.len()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done && !self.has_input()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        let b = self.chunk.get(self.pos).copied();
+        if b.is_some() {
+            self.pos += 1;
+        }
+        b
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.chunk[self.pos..]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
+}
+
+impl FillJsonFeeder for ChannelJsonFeeder {
+    fn fill(&mut self) -> Result<(), FillError> {
+        if self.has_input() || self.done {
+            return Ok(());
+        }
+        match self.receiver.blocking_recv() {
+            Some(chunk) => {
+                self.chunk = chunk?;
+                self.pos = 0;
+            }
+            None => self.done = true,
+        }
+        Ok(())
+    }
+}
+
+/// Deserialize an instance of type `T` from an asynchronous reader (e.g. a
+/// socket). The input is parsed and deserialized incrementally while it is
+/// being read, so the complete JSON text never has to be kept in memory.
+///
+/// Serde's [`Deserialize`](serde::Deserialize) trait is synchronous, so the
+/// value is deserialized on Tokio's blocking thread pool, while the reader is
+/// polled by the calling task. Only a few kilobytes of input are read ahead.
+///
+/// ```
+/// use actson::serde_json::from_async_reader;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Artist {
+///     name: String,
+///     albums: Vec<u32>,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let json = r#"{"name": "Elvis", "albums": [1956, 1957]}"#.as_bytes();
+///     let artist: Artist = from_async_reader(json).await.unwrap();
+///     assert_eq!(artist.name, "Elvis");
+///     assert_eq!(artist.albums, vec![1956, 1957]);
+/// }
+/// ```
+pub async fn from_async_reader<R, T>(mut reader: R) -> Result<T, DeserializeError>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned + Send + 'static,
+{
+    let (sender, receiver) = channel(MAX_PENDING_CHUNKS);
+    let task = spawn_blocking(move || from_feeder(ChannelJsonFeeder::new(receiver)));
+
+    forward_chunks(&mut reader, sender).await;
+
+    match task.await {
+        Ok(r) => r,
+        Err(e) => resume_unwind(e.into_panic()),
+    }
+}
+
+/// Read chunks from the given reader and send them to a
+/// [`ChannelJsonFeeder`] until the end of the input has been reached, the
+/// reader has failed, or the feeder has been dropped
+async fn forward_chunks<R>(reader: &mut R, sender: Sender<io::Result<Vec<u8>>>)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let chunk = match reader.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => Ok(buf[..n].to_vec()),
+            Err(e) => Err(e),
+        };
+        let failed = chunk.is_err();
+        if sender.send(chunk).await.is_err() || failed {
+            // the deserializer has stopped early (e.g. because of a
+            // syntax error) or the reader has failed
+            break;
+        }
+    }
+}
+
+/// Deserialize a sequence of top-level values (e.g. concatenated JSON or
+/// newline-delimited JSON) from an asynchronous reader (e.g. a socket) into
+/// instances of type `T`. Returns a [`ValueStream`] that yields each value
+/// as soon as it is complete.
+///
+/// The reader is read by a new Tokio task and the values are deserialized
+/// on Tokio's blocking thread pool (see [`from_async_reader()`]), so this
+/// function must be called from within a Tokio runtime. Both tasks stop
+/// when the stream is dropped.
+///
+/// ```
+/// use actson::serde_json::parse_stream;
+/// use futures::StreamExt;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Record {
+///     id: u32,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let json = "{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}\n".as_bytes();
+///     let ids = parse_stream::<_, Record>(json)
+///         .map(|r| r.unwrap().id)
+///         .collect::<Vec<_>>()
+///         .await;
+///     assert_eq!(ids, vec![1, 2, 3]);
+/// }
+/// ```
+pub fn parse_stream<R, T>(mut reader: R) -> ValueStream<T>
+where
+    R: AsyncRead + Send + Unpin + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    let (chunk_sender, chunk_receiver) = channel(MAX_PENDING_CHUNKS);
+    let (value_sender, value_receiver) = channel(MAX_PENDING_VALUES);
+
+    let task = spawn_blocking(move || {
+        let parser = JsonParser::new_with_options(
+            ChannelJsonFeeder::new(chunk_receiver),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        for v in Deserializer::new(parser).into_iter::<T>() {
+            if value_sender.blocking_send(v).is_err() {
+                // the stream has been dropped
+                break;
+            }
+        }
+    });
+    spawn(async move { forward_chunks(&mut reader, chunk_sender).await });
+
+    ValueStream {
+        receiver: value_receiver,
+        task: Some(task),
+    }
+}
+
+/// A [`Stream`] of values deserialized from an asynchronous reader. Create
+/// it with [`parse_stream()`]. The stream ends after the end of the input
+/// has been reached or after it has returned an error.
+pub struct ValueStream<T> {
+    receiver: Receiver<Result<T, DeserializeError>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl<T> Stream for ValueStream<T> {
+    type Item = Result<T, DeserializeError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.receiver.poll_recv(cx) {
+            Poll::Ready(None) => {
+                // propagate a panic of the deserializer
+                if let Some(task) = &mut this.task {
+                    let Poll::Ready(r) = Pin::new(task).poll(cx) else {
+                        return Poll::Pending;
+                    };
+                    this.task = None;
+                    if let Err(e) = r {
+                        if e.is_panic() {
+                            resume_unwind(e.into_panic());
+                        }
+                    }
+                }
+                Poll::Ready(None)
+            }
+            r => r,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures::StreamExt;
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    use crate::{parser::ParserError, serde_json::DeserializeError};
+
+    use super::{from_async_reader, parse_stream};
+
+    /// An asynchronous reader that returns one byte per call and that is
+    /// pending every other call
+    struct Trickle {
+        input: &'static [u8],
+        pending: bool,
+    }
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            if let Some((first, rest)) = self.input.split_first() {
+                buf.put_slice(&[*first]);
+                self.input = rest;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Test that values are deserialized from input that arrives in small
+    /// chunks and that errors are reported
+    #[tokio::test]
+    async fn trickle() {
+        let json = br#"{"a": [1, 2.5, "\u00e9"], "b": {"c": null}}"#;
+        let value: Value = from_async_reader(Trickle {
+            input: json,
+            pending: false,
+        })
+        .await
+        .unwrap();
+        assert_eq!(value, serde_json::from_slice::<Value>(json).unwrap());
+
+        assert!(matches!(
+            from_async_reader::<_, Value>(Trickle {
+                input: b"[1, 2",
+                pending: false,
+            })
+            .await,
+            Err(DeserializeError::Parser(ParserError::NoMoreInput))
+        ));
+    }
+
+    /// Test that a sequence of values is streamed from input that arrives
+    /// in small chunks and that the stream ends after an error
+    #[tokio::test]
+    async fn stream() {
+        let values = parse_stream::<_, Value>(Trickle {
+            input: b"{\"a\": 1}\n[2, 3] \"four\" 5\n{",
+            pending: false,
+        })
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(values.len(), 5);
+        assert_eq!(
+            values[..4]
+                .iter()
+                .map(|v| v.as_ref().unwrap().clone())
+                .collect::<Vec<_>>(),
+            vec![json!({"a": 1}), json!([2, 3]), json!("four"), json!(5)]
+        );
+        assert!(values[4].is_err());
+    }
+}
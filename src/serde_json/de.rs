@@ -0,0 +1,1657 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+    io::{BufReader, Read},
+    str::from_utf8,
+};
+
+use memchr::memchr;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use thiserror::Error;
+
+use crate::{
+    feeder::{BufReaderJsonFeeder, FillError, FillJsonFeeder, SliceJsonFeeder},
+    parser::{InvalidFloatValueError, InvalidIntValueError, InvalidStringValueError, ParserError},
+    storage::{HeapStorage, ParserStorage},
+    JsonEvent, JsonNumber, JsonParser,
+};
+
+/// An error that can happen when deserializing a value with a
+/// [`Deserializer`]
+#[derive(Error, Debug)]
+pub enum DeserializeError {
+    /// The JSON text could not be parsed
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    /// The feeder could not provide more input
+    #[error(transparent)]
+    Fill(#[from] FillError),
+
+    /// A string value or field name could not be read
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+
+    /// An integer value could not be read
+    #[error(transparent)]
+    InvalidIntValue(#[from] InvalidIntValueError),
+
+    /// A float value could not be read
+    #[error(transparent)]
+    InvalidFloatValue(#[from] InvalidFloatValueError),
+
+    /// A number is too large to be represented as a finite [`f64`]
+    #[error("number out of range: {0}")]
+    NumberOutOfRange(String),
+
+    /// The JSON text does not have the expected structure (e.g. an array
+    /// has more elements than a tuple). `None` means that the end of the
+    /// JSON text has been reached.
+    #[error("expected {expected}, found {found:?}")]
+    UnexpectedEvent {
+        expected: &'static str,
+        found: Option<JsonEvent>,
+    },
+
+    /// An object contains the same key more than once and the
+    /// [`DuplicateKeyPolicy`] is [`DuplicateKeyPolicy::Error`]
+    #[error("duplicate key: {0}")]
+    DuplicateKey(String),
+
+    /// A JSON Pointer is neither empty nor starts with `/`
+    #[error("invalid JSON pointer: {0}")]
+    InvalidPointer(String),
+
+    /// A custom error reported by a [`Deserialize`](de::Deserialize)
+    /// implementation (e.g. because of a missing field or a value of the
+    /// wrong type)
+    #[error("{0}")]
+    Custom(String),
+
+    /// An error that happened inside an object or an array, annotated with
+    /// the JSON Pointer of the failing element and the byte offset of the
+    /// token at which it was detected. Only returned if
+    /// [`Deserializer::with_error_location()`] is enabled.
+    #[error("{source} at {pointer:?} (byte {offset})")]
+    Located {
+        pointer: String,
+        offset: usize,
+        source: Box<DeserializeError>,
+    },
+}
+
+impl DeserializeError {
+    /// Return the JSON Pointer of the element at which the error happened
+    /// if it is known
+    pub fn pointer(&self) -> Option<&str> {
+        match self {
+            DeserializeError::Located { pointer, .. } => Some(pointer),
+            _ => None,
+        }
+    }
+
+    /// Return the byte offset at which the error has been detected if it is
+    /// known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            DeserializeError::Located { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Return the error without the location it has been annotated with
+    pub fn into_inner(self) -> DeserializeError {
+        match self {
+            DeserializeError::Located { source, .. } => *source,
+            e => e,
+        }
+    }
+}
+
+impl de::Error for DeserializeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeserializeError::Custom(msg.to_string())
+    }
+}
+
+/// Specifies how a [`Deserializer`] handles objects that contain the same
+/// key more than once
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the value of the first occurrence and skip all later ones
+    KeepFirst,
+
+    /// Pass all occurrences on to the [`Deserialize`](de::Deserialize)
+    /// implementation. Maps keep the value of the last occurrence, while
+    /// structs report a duplicate field. This matches the behavior of
+    /// `serde_json`.
+    #[default]
+    KeepLast,
+
+    /// Return [`DeserializeError::DuplicateKey`]
+    Error,
+}
+
+/// Deserialize an instance of type `T` from a byte slice. Use
+/// [`value_from_slice()`](super::value_from_slice()) to parse deeply nested
+/// documents into a [`serde_json::Value`] without recursion.
+///
+/// ```
+/// use actson::serde_json::from_slice;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Artist {
+///     name: String,
+///     albums: Vec<u32>,
+///     label: Option<String>,
+/// }
+///
+/// let json = br#"{"name": "Elvis", "albums": [1956, 1957], "label": null}"#;
+/// let artist: Artist = from_slice(json).unwrap();
+/// assert_eq!(
+///     artist,
+///     Artist {
+///         name: "Elvis".to_string(),
+///         albums: vec![1956, 1957],
+///         label: None,
+///     }
+/// );
+/// ```
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T, DeserializeError>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(v)));
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize an instance of type `T` from a byte slice and handle
+/// duplicate object keys according to the given policy
+///
+/// ```
+/// use actson::serde_json::{from_slice_with_policy, DuplicateKeyPolicy};
+/// use serde_json::{json, Value};
+///
+/// let json = br#"{"role": "user", "role": "admin"}"#;
+/// let first: Value = from_slice_with_policy(json, DuplicateKeyPolicy::KeepFirst).unwrap();
+/// assert_eq!(first, json!({"role": "user"}));
+///
+/// assert!(from_slice_with_policy::<Value>(json, DuplicateKeyPolicy::Error).is_err());
+/// ```
+pub fn from_slice_with_policy<'a, T>(
+    v: &'a [u8],
+    policy: DuplicateKeyPolicy,
+) -> Result<T, DeserializeError>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(v)))
+        .with_duplicate_key_policy(policy);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize an instance of type `T` from the beginning of a byte slice
+/// and stop reading as soon as it is complete. Structs are complete as soon
+/// as all of their fields have been read (see
+/// [`Deserializer::with_early_exit()`]), so the rest of the slice does not
+/// have to be parsed. Returns the value and the number of bytes that have
+/// been read. This number may include a delimiter or whitespace following
+/// the last value that has been read.
+///
+/// ```
+/// use actson::serde_json::from_slice_partial;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Header {
+///     version: u32,
+///     kind: String,
+/// }
+///
+/// let json = br#"{"version": 2, "kind": "dump", "data": [1, 2, 3, 4, 5, 6]}"#;
+/// let (header, n): (Header, usize) = from_slice_partial(json).unwrap();
+/// assert_eq!(header.version, 2);
+/// assert_eq!(header.kind, "dump");
+/// assert!(n < 32);
+/// ```
+pub fn from_slice_partial<'a, T>(v: &'a [u8]) -> Result<(T, usize), DeserializeError>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de =
+        Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(v))).with_early_exit(true);
+    let value = T::deserialize(&mut de)?;
+    Ok((value, de.parser().parsed_bytes()))
+}
+
+/// Deserialize an instance of type `T` from an I/O stream. The stream is
+/// read in chunks and values are deserialized while it is being parsed, so
+/// the complete JSON text never has to be kept in memory.
+///
+/// ```
+/// use actson::serde_json::from_reader;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let reader = Cursor::new(br#"{"Elvis": 1935, "Priscilla": 1945}"#);
+/// let years: HashMap<String, u32> = from_reader(reader).unwrap();
+/// assert_eq!(years["Elvis"], 1935);
+/// ```
+pub fn from_reader<R, T>(reader: R) -> Result<T, DeserializeError>
+where
+    R: Read,
+    T: de::DeserializeOwned,
+{
+    from_feeder(BufReaderJsonFeeder::new(BufReader::new(reader)))
+}
+
+/// Deserialize an instance of type `T` from the JSON text provided by the
+/// given feeder. The feeder is filled whenever the parser needs more input.
+pub fn from_feeder<F, T>(feeder: F) -> Result<T, DeserializeError>
+where
+    F: FillJsonFeeder,
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::new(JsonParser::new(feeder));
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize a value from a byte slice using the given seed. This allows
+/// stateful deserialization, e.g. into arena-allocated values or with an
+/// interner.
+pub fn from_slice_seed<'a, D>(v: &'a [u8], seed: D) -> Result<D::Value, DeserializeError>
+where
+    D: DeserializeSeed<'a>,
+{
+    let mut de = Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(v)));
+    let value = seed.deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize a value from the JSON text provided by the given feeder
+/// using the given seed. This allows stateful deserialization, e.g. into
+/// arena-allocated values or with an interner.
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::serde_json::from_feeder_seed;
+/// use serde::de::{DeserializeSeed, Deserializer, SeqAccess, Visitor};
+/// use std::fmt;
+///
+/// /// Sums up all numbers in an array without collecting them
+/// struct Sum;
+///
+/// impl<'de> DeserializeSeed<'de> for Sum {
+///     type Value = u64;
+///
+///     fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<u64, D::Error> {
+///         d.deserialize_seq(self)
+///     }
+/// }
+///
+/// impl<'de> Visitor<'de> for Sum {
+///     type Value = u64;
+///
+///     fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         f.write_str("an array of numbers")
+///     }
+///
+///     fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<u64, A::Error> {
+///         let mut sum = 0;
+///         while let Some(n) = seq.next_element::<u64>()? {
+///             sum += n;
+///         }
+///         Ok(sum)
+///     }
+/// }
+///
+/// let sum = from_feeder_seed(SliceJsonFeeder::new(b"[1, 2, 3]"), Sum).unwrap();
+/// assert_eq!(sum, 6);
+/// ```
+pub fn from_feeder_seed<'de, F, D>(feeder: F, seed: D) -> Result<D::Value, DeserializeError>
+where
+    F: FillJsonFeeder,
+    D: DeserializeSeed<'de>,
+{
+    let mut de = Deserializer::new(JsonParser::new(feeder));
+    let value = seed.deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// A step from a container to one of its members on the path to the value
+/// that is currently being deserialized
+enum PathSegment {
+    /// The container has been entered but no member has been read yet or
+    /// all of its members have been read
+    None,
+
+    /// The value of an object member with the given key
+    Key(String),
+
+    /// The element of an array at the given index
+    Index(usize),
+}
+
+/// A Serde [`Deserializer`](de::Deserializer) that pulls events from a
+/// [`JsonParser`]. Values are deserialized while the JSON text is being
+/// parsed, so no intermediate representation is built. Whenever the parser
+/// needs more input, the deserializer fills the feeder.
+///
+/// The representation of values is the same as in Serde JSON (e.g. enums
+/// are externally tagged). Object members are visited in the order in which
+/// they appear in the JSON text, so insertion-ordered maps (e.g. a
+/// `serde_json::Value` with Serde JSON's `preserve_order` feature) keep the
+/// original key order.
+///
+/// If the deserializer reads from a byte slice (see [`Self::new_borrowed()`]),
+/// strings without escape sequences are borrowed from the slice, so types
+/// with `&'de str` or `&'de [u8]` fields can be deserialized without
+/// copying.
+pub struct Deserializer<'de, T, S = HeapStorage> {
+    parser: JsonParser<T, S>,
+
+    /// An event that has been read from the parser by [`Self::peek()`] but
+    /// not consumed yet
+    peeked: Option<Option<JsonEvent>>,
+
+    /// The complete JSON text if strings can be borrowed from it
+    input: Option<&'de [u8]>,
+
+    /// Specifies how objects with duplicate keys are handled
+    duplicate_key_policy: DuplicateKeyPolicy,
+
+    /// `true` if a top-level struct should be returned as soon as all of
+    /// its fields have been read
+    pub(super) early_exit: bool,
+
+    /// `true` if deserialization has stopped early and the rest of the JSON
+    /// text has not been read
+    stopped: bool,
+
+    /// The nesting depth of the value that is currently being deserialized
+    depth: usize,
+
+    /// `true` if errors should be annotated with their location
+    error_location: bool,
+
+    /// The path to the value that is currently being deserialized if
+    /// `error_location` is enabled
+    path: Vec<PathSegment>,
+}
+
+impl<'a, S> Deserializer<'a, SliceJsonFeeder<'a>, S>
+where
+    S: ParserStorage,
+{
+    /// Create a new deserializer that reads from the given parser and that
+    /// borrows strings without escape sequences from the parser's input
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::serde_json::Deserializer;
+    /// use actson::JsonParser;
+    /// use serde::Deserialize;
+    ///
+    /// let json = br#"["Elvis", "Bj\u00f6rn"]"#;
+    /// let mut de = Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(json)));
+    /// let (a, b): (&str, String) = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!(a, "Elvis");
+    /// assert_eq!(b, "Bj\u{f6}rn");
+    /// ```
+    pub fn new_borrowed(parser: JsonParser<SliceJsonFeeder<'a>, S>) -> Self {
+        let input = parser.feeder.slice();
+        Deserializer {
+            parser,
+            peeked: None,
+            input: Some(input),
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            early_exit: false,
+            stopped: false,
+            depth: 0,
+            error_location: false,
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<'de, T, S> Deserializer<'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Create a new deserializer that reads from the given parser
+    pub fn new(parser: JsonParser<T, S>) -> Self {
+        Deserializer {
+            parser,
+            peeked: None,
+            input: None,
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            early_exit: false,
+            stopped: false,
+            depth: 0,
+            error_location: false,
+            path: Vec::new(),
+        }
+    }
+
+    /// Specify how objects with duplicate keys should be handled (see
+    /// [`DuplicateKeyPolicy`])
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_key_policy = policy;
+        self
+    }
+
+    /// Stop reading as soon as a top-level struct has been fully populated,
+    /// i.e. as soon as all of its fields have been read. The rest of the
+    /// JSON text is neither parsed nor validated. Structs with fields that
+    /// are missing in the JSON text (e.g. optional fields) are read
+    /// completely. Also, [`Self::deserialize_at()`] does not parse the rest
+    /// of the JSON text after the value has been found.
+    pub fn with_early_exit(mut self, early_exit: bool) -> Self {
+        self.early_exit = early_exit;
+        self
+    }
+
+    /// Annotate errors that happen inside objects or arrays with the JSON
+    /// Pointer of the failing element and the byte offset at which they
+    /// have been detected (see [`DeserializeError::Located`]). Since the
+    /// input is not kept in memory, this is the only way to find out which
+    /// part of a streamed JSON text did not match the expected type.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::serde_json::Deserializer;
+    /// use actson::JsonParser;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Artist {
+    ///     name: String,
+    ///     albums: Vec<u32>,
+    /// }
+    ///
+    /// let json = br#"[{"name": "Elvis", "albums": [1956, "1957"]}]"#;
+    /// let mut de = Deserializer::new(JsonParser::new(SliceJsonFeeder::new(json)))
+    ///     .with_error_location(true);
+    /// let err = Vec::<Artist>::deserialize(&mut de).unwrap_err();
+    /// assert_eq!(err.pointer(), Some("/0/albums/1"));
+    /// assert_eq!(err.offset(), Some(36));
+    /// ```
+    pub fn with_error_location(mut self, error_location: bool) -> Self {
+        self.error_location = error_location;
+        self
+    }
+
+    /// Returns `true` if deserialization has stopped early (see
+    /// [`Self::with_early_exit()`]) and the rest of the JSON text has not
+    /// been read
+    pub fn stopped_early(&self) -> bool {
+        self.stopped
+    }
+
+    /// Get the underlying parser
+    pub fn parser(&self) -> &JsonParser<T, S> {
+        &self.parser
+    }
+
+    /// Consume the deserializer and return the underlying parser
+    pub fn into_inner(self) -> JsonParser<T, S> {
+        self.parser
+    }
+
+    /// Check that the end of the JSON text has been reached. Call this
+    /// after deserializing a value to make sure there is no trailing data.
+    pub fn end(&mut self) -> Result<(), DeserializeError> {
+        self.expect("end of input", |e| e.is_none()).map(|_| ())
+    }
+
+    /// Return the type of the next token without consuming it
+    pub(super) fn peek(&mut self) -> Result<Option<JsonEvent>, DeserializeError> {
+        if let Some(e) = self.peeked {
+            return Ok(e);
+        }
+        let e = loop {
+            match self.parser.next_event()? {
+                Some(JsonEvent::NeedMoreInput) => self.parser.feeder.fill()?,
+                e => break e,
+            }
+        };
+        self.peeked = Some(e);
+        Ok(e)
+    }
+
+    /// Consume the next token
+    pub(super) fn next(&mut self) -> Result<Option<JsonEvent>, DeserializeError> {
+        let e = self.peek()?;
+        self.peeked = None;
+        Ok(e)
+    }
+
+    /// Consume the next token and check that it matches
+    pub(super) fn expect(
+        &mut self,
+        expected: &'static str,
+        matches: impl Fn(Option<JsonEvent>) -> bool,
+    ) -> Result<Option<JsonEvent>, DeserializeError> {
+        match self.next()? {
+            e if matches(e) => Ok(e),
+            found => Err(DeserializeError::UnexpectedEvent { expected, found }),
+        }
+    }
+
+    /// Record that a container has been entered
+    fn enter(&mut self) {
+        if self.error_location {
+            self.path.push(PathSegment::None);
+        }
+    }
+
+    /// Record that the current container has been left
+    fn leave(&mut self) {
+        if self.error_location {
+            self.path.pop();
+        }
+    }
+
+    /// Record that the member with the field name that has just been
+    /// consumed is being deserialized
+    fn enter_key(&mut self) -> Result<(), DeserializeError> {
+        if self.error_location {
+            let key = self.parser.current_str()?;
+            match self.path.last_mut() {
+                Some(PathSegment::Key(k)) => {
+                    k.clear();
+                    k.push_str(key);
+                }
+                Some(segment) => *segment = PathSegment::Key(key.to_string()),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the innermost path segment
+    fn set_segment(&mut self, segment: PathSegment) {
+        if let Some(last) = self.path.last_mut() {
+            *last = segment;
+        }
+    }
+
+    /// Annotate the given error with the current path and offset unless
+    /// this is disabled or the error has already been annotated
+    fn locate(&self, e: DeserializeError) -> DeserializeError {
+        if !self.error_location || matches!(e, DeserializeError::Located { .. }) {
+            return e;
+        }
+        let mut pointer = String::new();
+        for segment in &self.path {
+            match segment {
+                PathSegment::None => {}
+                PathSegment::Key(k) => {
+                    pointer.push('/');
+                    pointer.push_str(&k.replace('~', "~0").replace('/', "~1"));
+                }
+                PathSegment::Index(i) => {
+                    let _ = write!(pointer, "/{i}");
+                }
+            }
+        }
+        DeserializeError::Located {
+            pointer,
+            offset: self.parser.current_span().start,
+            source: Box::new(e),
+        }
+    }
+
+    /// Skip the next value including all nested values if it is an object
+    /// or an array
+    pub(super) fn skip_value(&mut self) -> Result<(), DeserializeError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next()? {
+                Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+                Some(JsonEvent::EndObject | JsonEvent::EndArray) if depth > 0 => depth -= 1,
+                Some(JsonEvent::FieldName) => continue,
+                found @ (None | Some(JsonEvent::EndObject | JsonEvent::EndArray)) => {
+                    return Err(DeserializeError::UnexpectedEvent {
+                        expected: "value",
+                        found,
+                    });
+                }
+                Some(_) => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Return the string that has just been consumed if it contains no
+    /// escape sequences and can therefore be borrowed from the input
+    fn borrowed_str(&self) -> Result<Option<&'de str>, DeserializeError> {
+        let span = self.parser.current_span();
+        let Some(raw) = self
+            .input
+            .and_then(|input| input.get(span.start + 1..span.end.saturating_sub(1)))
+        else {
+            return Ok(None);
+        };
+        if memchr(b'\\', raw).is_some() {
+            return Ok(None);
+        }
+        Ok(Some(from_utf8(raw).map_err(InvalidStringValueError::from)?))
+    }
+
+    /// Create a deserializer for the field name that has just been consumed
+    fn key(&self) -> Result<MapKeyDeserializer<'_, 'de>, DeserializeError> {
+        Ok(MapKeyDeserializer {
+            key: self.parser.current_str()?,
+            borrowed: self.borrowed_str()?,
+        })
+    }
+
+    /// Visit the number that has just been consumed
+    fn visit_number<V>(&self, visitor: V) -> Result<V::Value, DeserializeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.parser.current_number()? {
+            JsonNumber::I64(i) => visitor.visit_i64(i),
+            JsonNumber::U64(u) => visitor.visit_u64(u),
+            JsonNumber::F64(f) => visitor.visit_f64(f),
+            JsonNumber::Big(s) => {
+                let f = self.parser.current_float()?;
+                if f.is_finite() {
+                    visitor.visit_f64(f)
+                } else {
+                    Err(DeserializeError::NumberOutOfRange(s.to_string()))
+                }
+            }
+        }
+    }
+}
+
+/// Deserializes numbers into primitive types. Unlike
+/// [`deserialize_any()`](de::Deserializer::deserialize_any), this never
+/// passes the literal of a number to the visitor, even if the
+/// `arbitrary_precision` feature is enabled.
+macro_rules! deserialize_number {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                if matches!(
+                    self.peek()?,
+                    Some(JsonEvent::ValueInt | JsonEvent::ValueFloat)
+                ) {
+                    self.next()?;
+                    return self.visit_number(visitor);
+                }
+                self.deserialize_any(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, T, S> de::Deserializer<'de> for &mut Deserializer<'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.next()? {
+            Some(JsonEvent::StartObject) => {
+                self.depth += 1;
+                self.enter();
+                let value = visitor.visit_map(MapAccess::new(&mut *self))?;
+                self.depth -= 1;
+                self.expect("end of object", |e| e == Some(JsonEvent::EndObject))?;
+                self.leave();
+                Ok(value)
+            }
+            Some(JsonEvent::StartArray) => {
+                self.depth += 1;
+                self.enter();
+                let value = visitor.visit_seq(SeqAccess {
+                    de: &mut *self,
+                    index: 0,
+                })?;
+                self.depth -= 1;
+                self.expect("end of array", |e| e == Some(JsonEvent::EndArray))?;
+                self.leave();
+                Ok(value)
+            }
+            Some(JsonEvent::ValueString) => match self.borrowed_str()? {
+                Some(s) => visitor.visit_borrowed_str(s),
+                None => visitor.visit_str(self.parser.current_str()?),
+            },
+            #[cfg(not(feature = "arbitrary_precision"))]
+            Some(JsonEvent::ValueInt | JsonEvent::ValueFloat) => self.visit_number(visitor),
+            #[cfg(feature = "arbitrary_precision")]
+            Some(JsonEvent::ValueInt | JsonEvent::ValueFloat) => {
+                match self.parser.current_number()? {
+                    JsonNumber::I64(i) => visitor.visit_i64(i),
+                    JsonNumber::U64(u) => visitor.visit_u64(u),
+                    JsonNumber::F64(_) | JsonNumber::Big(_) => {
+                        // pass the exact literal to Serde JSON's `Number`
+                        visitor.visit_map(NumberAccess {
+                            literal: Some(self.parser.current_str()?),
+                        })
+                    }
+                }
+            }
+            Some(JsonEvent::ValueTrue) => visitor.visit_bool(true),
+            Some(JsonEvent::ValueFalse) => visitor.visit_bool(false),
+            Some(JsonEvent::ValueNull) => visitor.visit_unit(),
+            found => Err(DeserializeError::UnexpectedEvent {
+                expected: "value",
+                found,
+            }),
+        }
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // integers outside the range of 64 bits would lose precision as floats
+        if self.peek()? == Some(JsonEvent::ValueInt) {
+            self.next()?;
+            return visitor.visit_i128(self.parser.current_int()?);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.peek()? == Some(JsonEvent::ValueInt) {
+            self.next()?;
+            return visitor.visit_u128(self.parser.current_int()?);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.peek()? == Some(JsonEvent::ValueNull) {
+            self.next()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.next()? {
+            Some(JsonEvent::ValueString) => {
+                let variant: &str = self.parser.current_str()?;
+                visitor.visit_enum(variant.into_deserializer())
+            }
+            Some(JsonEvent::StartObject) => {
+                self.enter();
+                let value = visitor.visit_enum(EnumAccess { de: &mut *self })?;
+                self.expect("end of object", |e| e == Some(JsonEvent::EndObject))?;
+                self.leave();
+                Ok(value)
+            }
+            found => Err(DeserializeError::UnexpectedEvent {
+                expected: "string or object",
+                found,
+            }),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if !self.early_exit || self.depth > 0 || self.peek()? != Some(JsonEvent::StartObject) {
+            return self.deserialize_any(visitor);
+        }
+        self.next()?;
+        self.depth += 1;
+        self.enter();
+        let mut access = MapAccess::new(&mut *self);
+        access.remaining = Some(fields.to_vec());
+        let value = visitor.visit_map(access)?;
+        self.depth -= 1;
+        if !self.stopped {
+            self.expect("end of object", |e| e == Some(JsonEvent::EndObject))?;
+        }
+        self.leave();
+        Ok(value)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // skip the value without decoding strings or numbers
+        self.skip_value()?;
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier
+    }
+}
+
+/// Gives a [`Visitor`] access to the members of an object
+struct MapAccess<'a, 'de, T, S> {
+    de: &'a mut Deserializer<'de, T, S>,
+
+    /// The keys seen so far or `None` if duplicate keys do not have to be
+    /// detected
+    seen: Option<HashSet<String>>,
+
+    /// The fields of a struct that have not been read yet or `None` if the
+    /// object should be read completely (see
+    /// [`Deserializer::with_early_exit()`])
+    remaining: Option<Vec<&'static str>>,
+}
+
+impl<'a, 'de, T, S> MapAccess<'a, 'de, T, S> {
+    fn new(de: &'a mut Deserializer<'de, T, S>) -> Self {
+        let seen = match de.duplicate_key_policy {
+            DuplicateKeyPolicy::KeepLast => None,
+            DuplicateKeyPolicy::KeepFirst | DuplicateKeyPolicy::Error => Some(HashSet::new()),
+        };
+        MapAccess {
+            de,
+            seen,
+            remaining: None,
+        }
+    }
+}
+
+impl<'de, T, S> de::MapAccess<'de> for MapAccess<'_, 'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        loop {
+            if self.remaining.as_ref().is_some_and(|r| r.is_empty()) {
+                self.de.stopped = true;
+                self.de.set_segment(PathSegment::None);
+                return Ok(None);
+            }
+            if self.de.peek().map_err(|e| self.de.locate(e))? != Some(JsonEvent::FieldName) {
+                self.de.set_segment(PathSegment::None);
+                return Ok(None);
+            }
+            self.de.next()?;
+            self.de.enter_key()?;
+            if let Some(remaining) = &mut self.remaining {
+                let key = self.de.parser.current_str()?;
+                remaining.retain(|f| *f != key);
+            }
+            if let Some(seen) = &mut self.seen {
+                let key = self.de.parser.current_str()?;
+                if seen.contains(key) {
+                    if self.de.duplicate_key_policy == DuplicateKeyPolicy::Error {
+                        return Err(DeserializeError::DuplicateKey(key.to_string()));
+                    }
+                    self.de.skip_value()?;
+                    continue;
+                }
+                seen.insert(key.to_string());
+            }
+            return seed
+                .deserialize(self.de.key()?)
+                .map(Some)
+                .map_err(|e| self.de.locate(e));
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+            .map_err(|e| self.de.locate(e))
+    }
+}
+
+/// Passes the literal of a number as a map with a single member to Serde
+/// JSON's `Number`, which keeps it as is if the `arbitrary_precision`
+/// feature is enabled
+#[cfg(feature = "arbitrary_precision")]
+struct NumberAccess<'a> {
+    literal: Option<&'a str>,
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl<'de> de::MapAccess<'de> for NumberAccess<'_> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.literal.is_none() {
+            return Ok(None);
+        }
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(super::NUMBER_TOKEN))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let literal = self.literal.take().unwrap_or_default();
+        seed.deserialize(literal.into_deserializer())
+    }
+}
+
+/// Gives a [`Visitor`] access to the elements of an array
+struct SeqAccess<'a, 'de, T, S> {
+    de: &'a mut Deserializer<'de, T, S>,
+
+    /// The index of the next element
+    index: usize,
+}
+
+impl<'de, T, S> de::SeqAccess<'de> for SeqAccess<'_, 'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Error = DeserializeError;
+
+    fn next_element_seed<E>(&mut self, seed: E) -> Result<Option<E::Value>, Self::Error>
+    where
+        E: DeserializeSeed<'de>,
+    {
+        if self.de.peek().map_err(|e| self.de.locate(e))? == Some(JsonEvent::EndArray) {
+            self.de.set_segment(PathSegment::None);
+            return Ok(None);
+        }
+        self.de.set_segment(PathSegment::Index(self.index));
+        self.index += 1;
+        seed.deserialize(&mut *self.de)
+            .map(Some)
+            .map_err(|e| self.de.locate(e))
+    }
+}
+
+/// Gives a [`Visitor`] access to an enum variant with content, which is
+/// represented as an object with a single member
+struct EnumAccess<'a, 'de, T, S> {
+    de: &'a mut Deserializer<'de, T, S>,
+}
+
+impl<'de, T, S> de::EnumAccess<'de> for EnumAccess<'_, 'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.de
+            .expect("variant name", |e| e == Some(JsonEvent::FieldName))?;
+        self.de.enter_key()?;
+        let variant = seed.deserialize(self.de.key()?)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, T, S> de::VariantAccess<'de> for EnumAccess<'_, 'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<V>(self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+/// Deserializes field names. Like in Serde JSON, keys can also be
+/// deserialized into numbers and booleans if they contain a valid
+/// representation of them (e.g. for a `HashMap<u32, T>`).
+struct MapKeyDeserializer<'a, 'de> {
+    key: &'a str,
+
+    /// The same key if it can be borrowed from the input
+    borrowed: Option<&'de str>,
+}
+
+impl MapKeyDeserializer<'_, '_> {
+    fn parse<V>(&self) -> Result<V, DeserializeError>
+    where
+        V: std::str::FromStr,
+        V::Err: Display,
+    {
+        self.key
+            .parse()
+            .map_err(|e| de::Error::custom(format_args!("invalid key {:?}: {e}", self.key)))
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKeyDeserializer<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.borrowed {
+            Some(key) => visitor.visit_borrowed_str(key),
+            None => visitor.visit_str(self.key),
+        }
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.key.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        borrow::Cow,
+        collections::{BTreeMap, HashMap},
+        io::{self, Read},
+    };
+
+    use serde::Deserialize;
+
+    use crate::{feeder::SliceJsonFeeder, parser::ParserError, JsonParser};
+
+    use super::{
+        from_feeder_seed, from_reader, from_slice, from_slice_partial, from_slice_seed,
+        from_slice_with_policy, DeserializeError, Deserializer, DuplicateKeyPolicy,
+    };
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Unit;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Newtype(u8);
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Tuple(u8, String);
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum E {
+        A,
+        B(i32),
+        C(i32, bool),
+        D { x: Option<f32> },
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(tag = "type")]
+    enum Tagged {
+        Circle { r: f64 },
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Record {
+        name: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        scores: HashMap<u32, f64>,
+        nested: Option<Box<Record>>,
+    }
+
+    /// Test that the values of all Serde data types can be deserialized
+    #[test]
+    fn data_types() {
+        assert_eq!(from_slice::<Unit>(b"null").unwrap(), Unit);
+        assert_eq!(from_slice::<Newtype>(b"7").unwrap(), Newtype(7));
+        assert_eq!(
+            from_slice::<Tuple>(br#"[1, "a"]"#).unwrap(),
+            Tuple(1, "a".to_string())
+        );
+        assert_eq!(
+            from_slice::<(i8, u64, f32, char, ())>(br#"[-1, 18446744073709551615, 1, "x", null]"#)
+                .unwrap(),
+            (-1, u64::MAX, 1.0, 'x', ())
+        );
+        assert_eq!(
+            from_slice::<Vec<E>>(br#"["A", {"B": 1}, {"C": [2, true]}, {"D": {"x": null}}]"#)
+                .unwrap(),
+            vec![E::A, E::B(1), E::C(2, true), E::D { x: None }]
+        );
+        // like in Serde JSON, internally tagged enums cannot contain floats
+        // if the `arbitrary_precision` feature is enabled
+        let json = br#"{"r": 0.5, "type": "Circle"}"#;
+        assert_eq!(
+            from_slice::<Tagged>(json).ok(),
+            serde_json::from_slice::<Tagged>(json).ok()
+        );
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert_eq!(
+            from_slice::<Tagged>(json).unwrap(),
+            Tagged::Circle { r: 0.5 }
+        );
+        assert_eq!(
+            from_slice::<BTreeMap<bool, (i128, u128)>>(
+                br#"{"true": [-170141183460469231731687303715884105728, 340282366920938463463374607431768211455]}"#
+            )
+            .unwrap(),
+            BTreeMap::from([(true, (i128::MIN, u128::MAX))])
+        );
+    }
+
+    /// Test that a struct with nested values and unknown fields can be
+    /// deserialized
+    #[test]
+    fn record() {
+        let json = br#"{
+            "name": "Elvis",
+            "unknown": [{"a": [1, 2]}, null],
+            "scores": {"1": 1.5, "2": 2},
+            "nested": {"name": "Priscilla", "scores": {}, "nested": null}
+        }"#;
+        assert_eq!(
+            from_slice::<Record>(json).unwrap(),
+            Record {
+                name: "Elvis".to_string(),
+                tags: vec![],
+                scores: HashMap::from([(1, 1.5), (2, 2.0)]),
+                nested: Some(Box::new(Record {
+                    name: "Priscilla".to_string(),
+                    tags: vec![],
+                    scores: HashMap::new(),
+                    nested: None,
+                })),
+            }
+        );
+    }
+
+    /// Test that invalid input and values of the wrong type are rejected
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            from_slice::<Record>(br#"{"name": 1}"#),
+            Err(DeserializeError::Custom(_))
+        ));
+        assert!(matches!(
+            from_slice::<Record>(br#"{"name": "Elvis"}"#),
+            Err(DeserializeError::Custom(_))
+        ));
+        assert!(matches!(
+            from_slice::<(u8, u8)>(b"[1, 2, 3]"),
+            Err(DeserializeError::UnexpectedEvent { .. })
+        ));
+        assert!(matches!(
+            from_slice::<u8>(b"256"),
+            Err(DeserializeError::Custom(_))
+        ));
+        assert!(matches!(
+            from_slice::<HashMap<u8, u8>>(br#"{"a": 1}"#),
+            Err(DeserializeError::Custom(_))
+        ));
+        assert!(matches!(
+            from_slice::<f64>(b"1e400"),
+            Err(DeserializeError::NumberOutOfRange(_))
+        ));
+        assert!(matches!(
+            from_slice::<Vec<u8>>(b"[1, 2"),
+            Err(DeserializeError::Parser(ParserError::NoMoreInput))
+        ));
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        bytes: &'a [u8],
+        #[serde(borrow)]
+        plain: Cow<'a, str>,
+        #[serde(borrow)]
+        escaped: Cow<'a, str>,
+        #[serde(borrow)]
+        map: BTreeMap<&'a str, u8>,
+    }
+
+    /// Test that strings without escape sequences are borrowed from the
+    /// input and that other strings are copied
+    #[test]
+    fn borrowed() {
+        let json =
+            br#"{"name": "Elvis", "bytes": "abc", "plain": "a", "escaped": "\u00e9", "map": {"k": 1}}"#;
+        let b = from_slice::<Borrowed>(json).unwrap();
+        assert_eq!(b.name, "Elvis");
+        assert_eq!(b.bytes, b"abc");
+        assert!(matches!(b.plain, Cow::Borrowed("a")));
+        assert!(matches!(b.escaped, Cow::Owned(s) if s == "\u{e9}"));
+        assert_eq!(b.map, BTreeMap::from([("k", 1)]));
+
+        let input = json.as_ptr_range();
+        assert!(input.contains(&b.name.as_ptr()));
+        assert!(input.contains(&b.bytes.as_ptr()));
+
+        assert!(matches!(
+            from_slice::<&str>(br#""a\nb""#),
+            Err(DeserializeError::Custom(_))
+        ));
+    }
+
+    /// A reader that returns one byte per call
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    /// Test that values can be deserialized from a reader that provides
+    /// its input in small chunks
+    #[test]
+    fn reader() {
+        let json =
+            br#"{"name": "Elvis", "scores": {"1": 1.5}, "tags": ["a\u00e9"], "nested": null}"#;
+        let record: Record = from_reader(Trickle(json)).unwrap();
+        assert_eq!(record.tags, vec!["a\u{e9}"]);
+        assert_eq!(record, from_slice::<Record>(json).unwrap());
+
+        assert!(matches!(
+            from_reader::<_, Record>(Trickle(br#"{"name": "#)),
+            Err(DeserializeError::Parser(ParserError::NoMoreInput))
+        ));
+    }
+
+    /// A seed that deserializes an array of strings into indices of an
+    /// interner, so that equal strings are only stored once
+    struct Interned<'a>(&'a mut Vec<String>);
+
+    impl<'de> serde::de::DeserializeSeed<'de> for Interned<'_> {
+        type Value = Vec<usize>;
+
+        fn deserialize<D>(self, d: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let strings = Vec::<String>::deserialize(d)?;
+            Ok(strings
+                .into_iter()
+                .map(|s| match self.0.iter().position(|i| *i == s) {
+                    Some(i) => i,
+                    None => {
+                        self.0.push(s);
+                        self.0.len() - 1
+                    }
+                })
+                .collect())
+        }
+    }
+
+    /// Test that seeds can keep state across calls and that trailing input
+    /// is rejected
+    #[test]
+    fn seed() {
+        let mut interner = Vec::new();
+        let a = from_slice_seed(br#"["x", "y", "x"]"#, Interned(&mut interner)).unwrap();
+        let b = from_feeder_seed(
+            crate::feeder::SliceJsonFeeder::new(br#"["y", "z"]"#),
+            Interned(&mut interner),
+        )
+        .unwrap();
+        assert_eq!(a, vec![0, 1, 0]);
+        assert_eq!(b, vec![1, 2]);
+        assert_eq!(interner, vec!["x", "y", "z"]);
+
+        assert!(matches!(
+            from_slice_seed(br#"["x"] 1"#, Interned(&mut interner)),
+            Err(DeserializeError::Parser(ParserError::SyntaxError))
+        ));
+    }
+
+    /// Test that duplicate keys are handled according to the policy, also
+    /// in nested objects and struct variants
+    #[test]
+    fn duplicate_keys() {
+        let json = br#"{"a": {"x": 1, "x": [2]}, "b": [{"y": 3, "y": 4}]}"#;
+        let first: serde_json::Value =
+            from_slice_with_policy(json, DuplicateKeyPolicy::KeepFirst).unwrap();
+        assert_eq!(first, serde_json::json!({"a": {"x": 1}, "b": [{"y": 3}]}));
+
+        let last: HashMap<String, serde_json::Value> =
+            from_slice_with_policy(json, DuplicateKeyPolicy::KeepLast).unwrap();
+        assert_eq!(last["b"], serde_json::json!([{"y": 4}]));
+
+        assert!(matches!(
+            from_slice_with_policy::<serde_json::Value>(json, DuplicateKeyPolicy::Error),
+            Err(DeserializeError::DuplicateKey(k)) if k == "x"
+        ));
+
+        let json = br#"{"D": {"x": 1.5, "x": 2.5}}"#;
+        assert_eq!(
+            from_slice_with_policy::<E>(json, DuplicateKeyPolicy::KeepFirst).unwrap(),
+            E::D { x: Some(1.5) }
+        );
+        assert!(matches!(
+            from_slice::<E>(json),
+            Err(DeserializeError::Custom(_))
+        ));
+    }
+
+    /// Collects the keys of an object in the order in which they are visited
+    struct Keys(Vec<String>);
+
+    impl<'de> Deserialize<'de> for Keys {
+        fn deserialize<D>(d: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct KeysVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for KeysVisitor {
+                type Value = Keys;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("an object")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Keys, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    let mut keys = Vec::new();
+                    while let Some((k, _)) = map.next_entry::<String, serde::de::IgnoredAny>()? {
+                        keys.push(k);
+                    }
+                    Ok(Keys(keys))
+                }
+            }
+
+            d.deserialize_map(KeysVisitor)
+        }
+    }
+
+    /// Test that object members are visited in document order, so that
+    /// insertion-ordered maps keep the original key order
+    #[test]
+    fn key_order() {
+        let json = br#"{"z": 1, "a": {"y": 2}, "m": [], "b": null}"#;
+        let Keys(keys) = from_slice(json).unwrap();
+        assert_eq!(keys, vec!["z", "a", "m", "b"]);
+    }
+
+    /// Test that the exact literals of numbers are kept in a
+    /// `serde_json::Value` and that they are written back unchanged
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn arbitrary_precision() {
+        let json = br#"[0.1, 1.10, -0.0, 1e400, 123456789012345678901234567890, 7]"#;
+        let value: serde_json::Value = from_slice(json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::from_slice::<serde_json::Value>(json).unwrap()
+        );
+        assert_eq!(
+            crate::serde_json::to_string(&value).unwrap(),
+            "[0.1,1.10,-0.0,1e400,123456789012345678901234567890,7]"
+        );
+
+        let floats: (f32, f64, u8) = from_slice(b"[0.5, 1.10, 7]").unwrap();
+        assert_eq!(floats, (0.5, 1.1, 7));
+    }
+
+    /// Test that ignored values are skipped without being decoded
+    #[test]
+    fn ignored_any() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct A {
+            a: u8,
+        }
+
+        let json = br#"{"b": [1e400, {"c": "\ud800"}], "a": 1, "d": -1e999}"#;
+        assert_eq!(from_slice::<A>(json).unwrap(), A { a: 1 });
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert!(from_slice::<serde_json::Value>(json).is_err());
+
+        let (a, _): (u8, serde::de::IgnoredAny) = from_slice(b"[1, {\"x\": [2]}]").unwrap();
+        assert_eq!(a, 1);
+        assert!(matches!(
+            from_slice::<A>(br#"{"b": [1, }"#),
+            Err(DeserializeError::Parser(ParserError::SyntaxError))
+        ));
+    }
+
+    /// Test that deserialization stops as soon as a top-level struct is
+    /// complete but that nested structs and other values are read
+    /// completely
+    #[test]
+    fn early_exit() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Inner {
+            x: u8,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Outer {
+            a: Inner,
+            b: Option<u8>,
+        }
+
+        let json = br#"{"b": 1, "c": [2], "a": {"x": 3, "y": 4}, "d": invalid"#;
+        let (outer, n) = from_slice_partial::<Outer>(json).unwrap();
+        assert_eq!(
+            outer,
+            Outer {
+                a: Inner { x: 3 },
+                b: Some(1)
+            }
+        );
+        assert_eq!(
+            &json[..n],
+            &br#"{"b": 1, "c": [2], "a": {"x": 3, "y": 4}"#[..]
+        );
+
+        // the optional field is missing, so the object is read completely
+        let json = br#"{"a": {"x": 3}}"#;
+        assert_eq!(from_slice_partial::<Outer>(json).unwrap().1, json.len());
+        assert!(from_slice_partial::<Outer>(br#"{"a": {"x": 3}, "c": }"#).is_err());
+
+        let (v, n) = from_slice_partial::<Vec<u8>>(b"[1, 2] [3]").unwrap();
+        assert_eq!(v, vec![1, 2]);
+        assert_eq!(n, 6);
+    }
+
+    /// Test that errors are annotated with the JSON Pointer and the offset
+    /// of the failing element
+    #[test]
+    fn error_location() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        #[serde(deny_unknown_fields)]
+        struct Track {
+            title: String,
+            length: u32,
+        }
+
+        fn error(json: &[u8]) -> DeserializeError {
+            let mut de = Deserializer::new(JsonParser::new(SliceJsonFeeder::new(json)))
+                .with_error_location(true);
+            HashMap::<String, Vec<Track>>::deserialize(&mut de).unwrap_err()
+        }
+
+        let e = error(br#"{"a/b": [{"title": "x", "length": 1}, {"title": "y", "length": -1}]}"#);
+        assert_eq!(e.pointer(), Some("/a~1b/1/length"));
+        assert_eq!(e.offset(), Some(63));
+        assert!(matches!(e.into_inner(), DeserializeError::Custom(_)));
+
+        // missing fields are reported for the object
+        let e = error(br#"{"a": [{"title": "x"}]}"#);
+        assert_eq!(e.pointer(), Some("/a/0"));
+        assert_eq!(e.offset(), Some(20));
+
+        let e = error(br#"{"a": [{"title": "x", "year": 1}]}"#);
+        assert_eq!(e.pointer(), Some("/a/0/year"));
+
+        let e = error(br#"{"a": [{"title": "x", "length": 1} {}]}"#);
+        assert_eq!(e.pointer(), Some("/a/0"));
+        assert!(matches!(
+            e.into_inner(),
+            DeserializeError::Parser(ParserError::SyntaxError)
+        ));
+
+        // top-level errors are not annotated
+        assert!(error(b"[]").pointer().is_none());
+
+        // the location is not tracked by default
+        assert!(matches!(
+            from_slice::<Vec<u8>>(b"[1, -1]"),
+            Err(DeserializeError::Custom(_))
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Display;
+
+use num_traits::PrimInt;
+use serde::{ser, Serialize};
+
+use crate::OwnedJsonEvent;
+
+use super::ser::{impl_serializer, Compound, SerializeError, Target};
+
+/// A Serde [`Serializer`](ser::Serializer) that turns values into a stream
+/// of [`OwnedJsonEvent`]s instead of JSON text and passes each event to a
+/// callback. This allows synthetic event streams to be produced (e.g. in
+/// tests) and Serde data to be injected into event-based pipelines.
+///
+/// The events are the same a [`JsonParser`](crate::JsonParser) would
+/// produce for the JSON text that [`to_string()`](super::to_string())
+/// writes. Integers that do not fit into an [`i64`] and floats that are NaN
+/// or infinite are rejected with [`SerializeError::NumberOutOfRange`].
+///
+/// ```
+/// use actson::emitter::JsonEmitter;
+/// use actson::serde_json::EventSerializer;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Artist {
+///     name: &'static str,
+/// }
+///
+/// // forward the events to an emitter
+/// let mut emitter = JsonEmitter::new(Vec::new());
+/// let mut serializer = EventSerializer::new(|e| emitter.emit_owned_event(&e).unwrap());
+/// [Artist { name: "Elvis" }].serialize(&mut serializer).unwrap();
+/// assert_eq!(emitter.finish().unwrap(), br#"[{"name":"Elvis"}]"#);
+/// ```
+pub struct EventSerializer<F> {
+    callback: F,
+}
+
+impl<F> EventSerializer<F>
+where
+    F: FnMut(OwnedJsonEvent),
+{
+    /// Create a new serializer that passes each event to the given callback
+    pub fn new(callback: F) -> Self {
+        EventSerializer { callback }
+    }
+
+    /// Consume the serializer and return the callback
+    pub fn into_inner(self) -> F {
+        self.callback
+    }
+
+    fn emit(&mut self, event: OwnedJsonEvent) -> Result<(), SerializeError> {
+        (self.callback)(event);
+        Ok(())
+    }
+}
+
+/// Serialize the given value into a list of events
+///
+/// ```
+/// use actson::serde_json::to_events;
+/// use actson::OwnedJsonEvent;
+///
+/// let events = to_events(&Some((1, "a"))).unwrap();
+/// assert_eq!(events, vec![
+///     OwnedJsonEvent::StartArray,
+///     OwnedJsonEvent::ValueInt(1),
+///     OwnedJsonEvent::ValueString("a".to_string()),
+///     OwnedJsonEvent::EndArray,
+/// ]);
+/// ```
+pub fn to_events<T>(value: &T) -> Result<Vec<OwnedJsonEvent>, SerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    let mut events = Vec::new();
+    value.serialize(&mut EventSerializer::new(|e| events.push(e)))?;
+    Ok(events)
+}
+
+impl<F> Target for EventSerializer<F>
+where
+    F: FnMut(OwnedJsonEvent),
+{
+    fn start_object(&mut self) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::StartObject)
+    }
+
+    fn end_object(&mut self) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::EndObject)
+    }
+
+    fn start_array(&mut self) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::StartArray)
+    }
+
+    fn end_array(&mut self) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::EndArray)
+    }
+
+    fn field_name(&mut self, name: &str) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::FieldName(name.to_string()))
+    }
+
+    fn value_str(&mut self, value: &str) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::ValueString(value.to_string()))
+    }
+
+    fn value_int<I>(&mut self, value: I) -> Result<(), SerializeError>
+    where
+        I: PrimInt + Display,
+    {
+        if let Some(i) = value.to_i64() {
+            return self.emit(OwnedJsonEvent::ValueInt(i));
+        }
+        let u = value
+            .to_u64()
+            .ok_or_else(|| SerializeError::NumberOutOfRange(value.to_string()))?;
+        self.emit(OwnedJsonEvent::ValueUint(u))
+    }
+
+    fn value_float(&mut self, value: f64) -> Result<(), SerializeError> {
+        if !value.is_finite() {
+            return Err(SerializeError::NumberOutOfRange(value.to_string()));
+        }
+        self.emit(OwnedJsonEvent::ValueFloat(value))
+    }
+
+    fn value_bool(&mut self, value: bool) -> Result<(), SerializeError> {
+        self.emit(if value {
+            OwnedJsonEvent::ValueTrue
+        } else {
+            OwnedJsonEvent::ValueFalse
+        })
+    }
+
+    fn value_null(&mut self) -> Result<(), SerializeError> {
+        self.emit(OwnedJsonEvent::ValueNull)
+    }
+
+    fn value_number(&mut self, literal: &str) -> Result<(), SerializeError> {
+        if let Ok(i) = literal.parse() {
+            return self.emit(OwnedJsonEvent::ValueInt(i));
+        }
+        if let Ok(u) = literal.parse() {
+            return self.emit(OwnedJsonEvent::ValueUint(u));
+        }
+        match literal.parse() {
+            Ok(f) => self.value_float(f),
+            Err(_) => Err(SerializeError::NumberOutOfRange(literal.to_string())),
+        }
+    }
+
+    fn serialize<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+}
+
+impl_serializer! {
+    [F: FnMut(OwnedJsonEvent)] EventSerializer<F>
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use crate::{feeder::SliceJsonFeeder, serde_json::SerializeError, JsonParser, OwnedJsonEvent};
+
+    use super::to_events;
+
+    #[derive(Serialize)]
+    enum E {
+        A,
+        B(i32),
+        C { x: Option<f32> },
+    }
+
+    #[derive(Serialize)]
+    struct Record {
+        name: &'static str,
+        bytes: &'static [u8],
+        enums: Vec<E>,
+        map: BTreeMap<u8, bool>,
+        unit: (),
+    }
+
+    /// Test that the events are the same a parser produces for the
+    /// serialized JSON text and that unrepresentable numbers are rejected
+    #[test]
+    fn like_parser() {
+        let record = Record {
+            name: "Elvis",
+            bytes: &[1, 2],
+            enums: vec![E::A, E::B(-1), E::C { x: Some(0.5) }, E::C { x: None }],
+            map: BTreeMap::from([(1, true), (2, false)]),
+            unit: (),
+        };
+        let json = serde_json::to_vec(&record).unwrap();
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(&json));
+        let mut expected = Vec::new();
+        while let Some(e) = parser.next_owned_event().unwrap() {
+            expected.push(e);
+        }
+        assert_eq!(to_events(&record).unwrap(), expected);
+
+        assert_eq!(
+            to_events(&u64::MAX).unwrap(),
+            vec![OwnedJsonEvent::ValueUint(u64::MAX)]
+        );
+        assert!(matches!(
+            to_events(&[u128::MAX]),
+            Err(SerializeError::NumberOutOfRange(_))
+        ));
+        assert!(matches!(
+            to_events(&f64::NAN),
+            Err(SerializeError::NumberOutOfRange(_))
+        ));
+    }
+}
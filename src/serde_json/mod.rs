@@ -1,132 +1,36 @@
-use serde_json::{Map, Number, Value};
-use thiserror::Error;
-
-use crate::feeder::{JsonFeeder, SliceJsonFeeder};
-use crate::parser::{
-    InvalidFloatValueError, InvalidIntValueError, InvalidStringValueError, ParserError,
+#[cfg(feature = "tokio")]
+mod asyncreader;
+mod de;
+mod events;
+mod pointer;
+mod ser;
+mod stream;
+mod transcode;
+mod value;
+
+#[cfg(feature = "tokio")]
+pub use asyncreader::{from_async_reader, parse_stream, ValueStream};
+pub use de::{
+    from_feeder, from_feeder_seed, from_reader, from_slice, from_slice_partial, from_slice_seed,
+    from_slice_with_policy, DeserializeError, Deserializer, DuplicateKeyPolicy,
 };
-use crate::{JsonEvent, JsonParser};
-
-/// An error that can happen when parsing JSON to a Serde [`Value`]
-#[derive(Error, Debug)]
-pub enum IntoSerdeValueError {
-    #[error("{0}")]
-    Parse(#[from] ParserError),
-
-    #[error("{0}")]
-    InvalidStringValue(#[from] InvalidStringValueError),
-
-    #[error("{0}")]
-    InvalidIntValue(#[from] InvalidIntValueError),
-
-    #[error("{0}")]
-    InvalidFloatValue(#[from] InvalidFloatValueError),
-
-    #[error("not a JSON number: {0}")]
-    IllegalJsonNumber(f64),
-}
-
-fn to_value<T>(event: &JsonEvent, parser: &JsonParser<T>) -> Result<Value, IntoSerdeValueError>
-where
-    T: JsonFeeder,
-{
-    Ok(match event {
-        JsonEvent::ValueString => Value::String(parser.current_str()?.to_string()),
-        JsonEvent::ValueInt => Value::Number(Number::from(parser.current_int::<i64>()?)),
-        JsonEvent::ValueFloat => {
-            let f = parser.current_float()?;
-            let n = Number::from_f64(f).ok_or(IntoSerdeValueError::IllegalJsonNumber(f))?;
-            Value::Number(n)
-        }
-        JsonEvent::ValueTrue => Value::Bool(true),
-        JsonEvent::ValueFalse => Value::Bool(false),
-        JsonEvent::ValueNull => Value::Null,
-        _ => unreachable!("this function will only be called for valid events"),
-    })
-}
-
-/// Parse a byte slice into a Serde JSON [Value]
-///
-/// ```
-/// use serde_json::json;
-/// use actson::serde_json::from_slice;
-///
-/// let json = r#"{"name": "Elvis"}"#.as_bytes();
-/// let expected = json!({
-///     "name": "Elvis"
-/// });
-/// let actual = from_slice(&json).unwrap();
-/// assert_eq!(expected, actual);
-/// ```
-pub fn from_slice(v: &[u8]) -> Result<Value, IntoSerdeValueError> {
-    let feeder = SliceJsonFeeder::new(v);
-    let mut parser = JsonParser::new(feeder);
-
-    let mut stack = vec![];
-    let mut result = None;
-    let mut current_key = None;
-
-    while let Some(event) = parser.next_event()? {
-        match event {
-            JsonEvent::NeedMoreInput => {}
-
-            JsonEvent::StartObject | JsonEvent::StartArray => {
-                let v = if event == JsonEvent::StartObject {
-                    Value::Object(Map::new())
-                } else {
-                    Value::Array(vec![])
-                };
-                stack.push((current_key, v));
-                current_key = None;
-            }
-
-            JsonEvent::EndObject | JsonEvent::EndArray => {
-                let v = stack.pop().unwrap();
-                if let Some((_, top)) = stack.last_mut() {
-                    if let Some(m) = top.as_object_mut() {
-                        m.insert(v.0.unwrap(), v.1);
-                    } else if let Some(a) = top.as_array_mut() {
-                        a.push(v.1);
-                    }
-                } else {
-                    result = Some(v.1);
-                }
-            }
-
-            JsonEvent::FieldName => current_key = Some(parser.current_str()?.to_string()),
-
-            JsonEvent::ValueString
-            | JsonEvent::ValueInt
-            | JsonEvent::ValueFloat
-            | JsonEvent::ValueTrue
-            | JsonEvent::ValueFalse
-            | JsonEvent::ValueNull => {
-                if let Some((_, top)) = stack.last_mut() {
-                    let v = to_value(&event, &parser)?;
-                    if let Some(m) = top.as_object_mut() {
-                        m.insert(current_key.unwrap(), v);
-                        current_key = None
-                    } else if let Some(a) = top.as_array_mut() {
-                        a.push(v);
-                    }
-                } else if result.is_none() {
-                    let v = to_value(&event, &parser)?;
-                    result = Some(v);
-                } else {
-                    return Err(IntoSerdeValueError::Parse(ParserError::SyntaxError));
-                }
-            }
-        }
-    }
-
-    result.ok_or(IntoSerdeValueError::Parse(ParserError::NoMoreInput))
-}
+pub use events::{to_events, EventSerializer};
+pub use pointer::{from_feeder_at, from_slice_at};
+pub use ser::{to_string, to_vec, to_writer, Compound, SerializeError};
+pub use stream::{array_iter, map_iter, ArrayIter, MapIter, StreamDeserializer};
+pub use transcode::{transcode, Transcoder};
+pub use value::{value_from_feeder, value_from_slice, ValueBuilder};
+
+/// The name Serde JSON uses to pass the literal of a number through Serde's
+/// data model if its `arbitrary_precision` feature is enabled
+#[cfg(feature = "arbitrary_precision")]
+const NUMBER_TOKEN: &str = "$serde_json::private::Number";
 
 #[cfg(test)]
 mod test {
     use crate::{
         parser::ParserError,
-        serde_json::{from_slice, IntoSerdeValueError},
+        serde_json::{from_slice, DeserializeError},
     };
     use serde_json::{from_slice as serde_from_slice, Value};
 
@@ -136,7 +40,7 @@ mod test {
         let json = r#""Elvis""#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -146,7 +50,7 @@ mod test {
         let json = r#"5"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -156,7 +60,7 @@ mod test {
         let json = r#"-5.0"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -166,7 +70,7 @@ mod test {
         let json = r#"{}"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -176,7 +80,7 @@ mod test {
         let json = r#"{"name": "Elvis"}"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -186,7 +90,7 @@ mod test {
         let json = r#"[]"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -196,7 +100,7 @@ mod test {
         let json = r#"["Elvis", "Max"]"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -206,7 +110,7 @@ mod test {
         let json = r#"["Elvis", 132, "Max", 80.67]"#.as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -231,7 +135,17 @@ mod test {
         .as_bytes();
         assert_eq!(
             serde_from_slice::<Value>(json).unwrap(),
-            from_slice(json).unwrap()
+            from_slice::<Value>(json).unwrap()
+        );
+    }
+
+    /// Test that escape sequences in keys and values are decoded
+    #[test]
+    fn escaped_strings() {
+        let json = r#"{"a\nb": "\"quoted\" \u00e9 \ud834\udd1e", "c": ["\\", "\/"]}"#.as_bytes();
+        assert_eq!(
+            serde_from_slice::<Value>(json).unwrap(),
+            from_slice::<Value>(json).unwrap()
         );
     }
 
@@ -240,8 +154,8 @@ mod test {
     fn premature_end_of_input() {
         let json = r#"{"name":"#.as_bytes();
         assert!(matches!(
-            from_slice(json),
-            Err(IntoSerdeValueError::Parse(ParserError::NoMoreInput))
+            from_slice::<Value>(json),
+            Err(DeserializeError::Parser(ParserError::NoMoreInput))
         ));
     }
 
@@ -250,8 +164,8 @@ mod test {
     fn syntax_error() {
         let json = r#"{"name"}"#.as_bytes();
         assert!(matches!(
-            from_slice(json),
-            Err(IntoSerdeValueError::Parse(ParserError::SyntaxError))
+            from_slice::<Value>(json),
+            Err(DeserializeError::Parser(ParserError::SyntaxError))
         ));
     }
 }
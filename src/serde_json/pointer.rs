@@ -0,0 +1,191 @@
+use serde::de;
+
+use crate::{
+    feeder::{FillJsonFeeder, SliceJsonFeeder},
+    storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+use super::{DeserializeError, Deserializer};
+
+/// Split a JSON Pointer (RFC 6901) into its decoded reference tokens
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, DeserializeError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(DeserializeError::InvalidPointer(pointer.to_string()));
+    };
+    Ok(rest
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Parse a reference token as an array index. Leading zeros are not allowed.
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || (token.len() > 1 && token.starts_with('0'))
+        || !token.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl<'de, T, S> Deserializer<'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Advance to the value the given reference tokens point to. Returns
+    /// `false` if there is no such value.
+    fn seek(&mut self, tokens: &[String]) -> Result<bool, DeserializeError> {
+        for token in tokens {
+            match self.next()? {
+                Some(JsonEvent::StartObject) => loop {
+                    match self.next()? {
+                        Some(JsonEvent::FieldName) => {
+                            if self.parser().current_str()? == token {
+                                break;
+                            }
+                            self.skip_value()?;
+                        }
+                        _ => return Ok(false),
+                    }
+                },
+
+                Some(JsonEvent::StartArray) => {
+                    let Some(index) = parse_index(token) else {
+                        return Ok(false);
+                    };
+                    for _ in 0..index {
+                        if self.peek()? == Some(JsonEvent::EndArray) {
+                            return Ok(false);
+                        }
+                        self.skip_value()?;
+                    }
+                    if self.peek()? == Some(JsonEvent::EndArray) {
+                        return Ok(false);
+                    }
+                }
+
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Consume all remaining events, so that syntax errors are detected
+    fn drain(&mut self) -> Result<(), DeserializeError> {
+        while self.next()?.is_some() {}
+        Ok(())
+    }
+
+    /// Deserialize the value the given JSON Pointer points to into an
+    /// instance of type `V`. Everything else is skipped without decoding
+    /// strings or numbers. Returns `None` if there is no such value. The
+    /// rest of the JSON text is still parsed to make sure it is valid,
+    /// unless early exit is enabled (see [`Self::with_early_exit()`]).
+    pub fn deserialize_at<V>(&mut self, pointer: &str) -> Result<Option<V>, DeserializeError>
+    where
+        V: de::Deserialize<'de>,
+    {
+        let tokens = parse_pointer(pointer)?;
+        let value = if self.seek(&tokens)? {
+            Some(V::deserialize(&mut *self)?)
+        } else {
+            None
+        };
+        if !self.early_exit {
+            self.drain()?;
+        }
+        Ok(value)
+    }
+}
+
+/// Deserialize the value at the given JSON Pointer (RFC 6901) in a byte
+/// slice into an instance of type `T`. Returns `None` if there is no such
+/// value. See [`Deserializer::deserialize_at()`].
+///
+/// ```
+/// use actson::serde_json::from_slice_at;
+///
+/// let json = br#"{"data": {"items": [10, 20, 30, 40]}, "meta": {"pages": 1}}"#;
+/// let item: Option<u32> = from_slice_at(json, "/data/items/3").unwrap();
+/// assert_eq!(item, Some(40));
+///
+/// let missing: Option<u32> = from_slice_at(json, "/data/items/4").unwrap();
+/// assert_eq!(missing, None);
+/// ```
+pub fn from_slice_at<'a, T>(v: &'a [u8], pointer: &str) -> Result<Option<T>, DeserializeError>
+where
+    T: de::Deserialize<'a>,
+{
+    Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(v))).deserialize_at(pointer)
+}
+
+/// Deserialize the value at the given JSON Pointer (RFC 6901) in the JSON
+/// text provided by the given feeder into an instance of type `T`. Returns
+/// `None` if there is no such value. See [`Deserializer::deserialize_at()`].
+pub fn from_feeder_at<F, T>(feeder: F, pointer: &str) -> Result<Option<T>, DeserializeError>
+where
+    F: FillJsonFeeder,
+    T: de::DeserializeOwned,
+{
+    Deserializer::new(JsonParser::new(feeder)).deserialize_at(pointer)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::Value;
+
+    use crate::{
+        feeder::{BufReaderJsonFeeder, SliceJsonFeeder},
+        parser::ParserError,
+        serde_json::{DeserializeError, Deserializer},
+        JsonParser,
+    };
+
+    use super::{from_feeder_at, from_slice_at};
+
+    /// Test that values are found at all kinds of pointers and that missing
+    /// values, invalid pointers, and invalid JSON texts are reported
+    #[test]
+    fn pointers() {
+        let json = br#"{"a": {"b/c": [1, {"~d": "x"}], "e": null}, "": [true], "f": "y"}"#;
+        assert_eq!(from_slice_at(json, "/a/b~1c/1/~0d").unwrap(), Some("x"));
+        assert_eq!(from_slice_at(json, "/a/b~1c/0").unwrap(), Some(1));
+        assert_eq!(from_slice_at(json, "//0").unwrap(), Some(true));
+        assert_eq!(from_slice_at(json, "/f").unwrap(), Some("y"));
+        assert_eq!(from_slice_at(json, "/a/e").unwrap(), Some(()));
+        assert_eq!(
+            from_slice_at::<Value>(json, "").unwrap(),
+            Some(serde_json::from_slice(json).unwrap())
+        );
+        assert_eq!(from_slice_at::<u8>(json, "/a/b~1c/2").unwrap(), None);
+        assert_eq!(from_slice_at::<u8>(json, "/a/b~1c/01").unwrap(), None);
+        assert_eq!(from_slice_at::<u8>(json, "/a/x").unwrap(), None);
+        assert_eq!(from_slice_at::<u8>(json, "/f/0").unwrap(), None);
+
+        assert!(matches!(
+            from_slice_at::<u8>(json, "a"),
+            Err(DeserializeError::InvalidPointer(_))
+        ));
+        assert!(matches!(
+            from_slice_at::<u8>(br#"{"a": 1, "b": "#, "/a"),
+            Err(DeserializeError::Parser(ParserError::NoMoreInput))
+        ));
+
+        let mut de =
+            Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(br#"{"a": 1, "b": "#)))
+                .with_early_exit(true);
+        assert_eq!(de.deserialize_at("/a").unwrap(), Some(1));
+
+        let feeder = BufReaderJsonFeeder::new(std::io::BufReader::with_capacity(3, &json[..]));
+        assert_eq!(
+            from_feeder_at::<_, String>(feeder, "/a/b~1c/1/~0d").unwrap(),
+            Some("x".to_string())
+        );
+    }
+}
@@ -0,0 +1,901 @@
+use std::{fmt::Display, io::Write};
+
+use serde::ser::{self, Impossible, Serialize};
+use thiserror::Error;
+
+use num_traits::PrimInt;
+
+use crate::emitter::{EmitterError, JsonEmitter};
+
+/// An error that can happen when serializing a value with a [`JsonEmitter`]
+#[derive(Error, Debug)]
+pub enum SerializeError {
+    /// The emitter failed to write the value
+    #[error(transparent)]
+    Emitter(#[from] EmitterError),
+
+    /// A number cannot be represented by an
+    /// [`OwnedJsonEvent`](crate::OwnedJsonEvent) (e.g. an integer that does
+    /// not fit into an [`i64`] or a float that is NaN or infinite)
+    #[error("number out of range: {0}")]
+    NumberOutOfRange(String),
+
+    /// A map key cannot be represented as a JSON string
+    #[error("key must be a string")]
+    KeyMustBeAString,
+
+    /// A custom error reported by a [`Serialize`] implementation
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl ser::Error for SerializeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerializeError::Custom(msg.to_string())
+    }
+}
+
+/// Serialize the given value as JSON into the given writer
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use actson::serde_json::to_writer;
+///
+/// let mut albums = BTreeMap::new();
+/// albums.insert("Elvis Presley", 1956);
+/// albums.insert("Elvis", 1956);
+///
+/// let mut out = Vec::new();
+/// to_writer(&mut out, &albums).unwrap();
+/// assert_eq!(out, br#"{"Elvis":1956,"Elvis Presley":1956}"#);
+/// ```
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), SerializeError>
+where
+    W: Write,
+    T: ?Sized + Serialize,
+{
+    let mut emitter = JsonEmitter::new(writer);
+    value.serialize(&mut emitter)?;
+    emitter.finish()?;
+    Ok(())
+}
+
+/// Serialize the given value as JSON into a byte vector
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, SerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = Vec::new();
+    to_writer(&mut out, value)?;
+    Ok(out)
+}
+
+/// Serialize the given value as a JSON string
+pub fn to_string<T>(value: &T) -> Result<String, SerializeError>
+where
+    T: ?Sized + Serialize,
+{
+    let out = to_vec(value)?;
+    Ok(String::from_utf8(out).expect("output is always valid UTF-8"))
+}
+
+/// Something a value can be serialized into: either a [`JsonEmitter`] that
+/// writes JSON text or an [`EventSerializer`] that produces events
+pub(crate) trait Target {
+    fn start_object(&mut self) -> Result<(), SerializeError>;
+    fn end_object(&mut self) -> Result<(), SerializeError>;
+    fn start_array(&mut self) -> Result<(), SerializeError>;
+    fn end_array(&mut self) -> Result<(), SerializeError>;
+    fn field_name(&mut self, name: &str) -> Result<(), SerializeError>;
+    fn value_str(&mut self, value: &str) -> Result<(), SerializeError>;
+    fn value_int<I>(&mut self, value: I) -> Result<(), SerializeError>
+    where
+        I: PrimInt + Display;
+    fn value_float(&mut self, value: f64) -> Result<(), SerializeError>;
+    fn value_bool(&mut self, value: bool) -> Result<(), SerializeError>;
+    fn value_null(&mut self) -> Result<(), SerializeError>;
+
+    /// Write the exact literal of a number (see `arbitrary_precision`)
+    #[cfg_attr(not(feature = "arbitrary_precision"), allow(dead_code))]
+    fn value_number(&mut self, literal: &str) -> Result<(), SerializeError>;
+
+    /// Serialize a nested value
+    fn serialize<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize;
+}
+
+impl<W> Target for JsonEmitter<W>
+where
+    W: Write,
+{
+    fn start_object(&mut self) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::start_object(self)?)
+    }
+
+    fn end_object(&mut self) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::end_object(self)?)
+    }
+
+    fn start_array(&mut self) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::start_array(self)?)
+    }
+
+    fn end_array(&mut self) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::end_array(self)?)
+    }
+
+    fn field_name(&mut self, name: &str) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::field_name(self, name)?)
+    }
+
+    fn value_str(&mut self, value: &str) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::value_str(self, value)?)
+    }
+
+    fn value_int<I>(&mut self, value: I) -> Result<(), SerializeError>
+    where
+        I: PrimInt + Display,
+    {
+        Ok(JsonEmitter::value_int(self, value)?)
+    }
+
+    fn value_float(&mut self, value: f64) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::value_float(self, value)?)
+    }
+
+    fn value_bool(&mut self, value: bool) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::value_bool(self, value)?)
+    }
+
+    fn value_null(&mut self) -> Result<(), SerializeError> {
+        Ok(JsonEmitter::value_null(self)?)
+    }
+
+    fn value_number(&mut self, literal: &str) -> Result<(), SerializeError> {
+        Ok(self.write_raw(literal)?)
+    }
+
+    fn serialize<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+}
+
+/// Implements [`ser::Serializer`] for a mutable reference to a [`Target`]
+macro_rules! impl_serializer {
+    ($(#[$meta:meta])* [$g:ident: $bound:path] $target:ty) => {
+        $(#[$meta])*
+        impl<'a, $g> ser::Serializer for &'a mut $target
+        where
+            $g: $bound,
+        {
+            type Ok = ();
+            type Error = SerializeError;
+
+            type SerializeSeq = Compound<'a, $target>;
+            type SerializeTuple = Compound<'a, $target>;
+            type SerializeTupleStruct = Compound<'a, $target>;
+            type SerializeTupleVariant = Compound<'a, $target>;
+            type SerializeMap = Compound<'a, $target>;
+            type SerializeStruct = Compound<'a, $target>;
+            type SerializeStructVariant = Compound<'a, $target>;
+
+            fn serialize_bool(self, v: bool) -> Result<(), SerializeError> {
+                Target::value_bool(self, v)
+            }
+
+            fn serialize_i8(self, v: i8) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_i16(self, v: i16) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_i32(self, v: i32) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_i64(self, v: i64) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_i128(self, v: i128) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_u8(self, v: u8) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_u16(self, v: u16) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_u32(self, v: u32) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_u64(self, v: u64) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_u128(self, v: u128) -> Result<(), SerializeError> {
+                Target::value_int(self, v)
+            }
+
+            fn serialize_f32(self, v: f32) -> Result<(), SerializeError> {
+                // go through the shortest decimal representation of the f32 so
+                // that e.g. 0.1f32 is written as 0.1 and not as 0.10000000149011612
+                let f = if v.is_finite() {
+                    v.to_string().parse().unwrap_or(v as f64)
+                } else {
+                    v as f64
+                };
+                Target::value_float(self, f)
+            }
+
+            fn serialize_f64(self, v: f64) -> Result<(), SerializeError> {
+                Target::value_float(self, v)
+            }
+
+            fn serialize_char(self, v: char) -> Result<(), SerializeError> {
+                Target::value_str(self, v.encode_utf8(&mut [0; 4]))
+            }
+
+            fn serialize_str(self, v: &str) -> Result<(), SerializeError> {
+                Target::value_str(self, v)
+            }
+
+            fn serialize_bytes(self, v: &[u8]) -> Result<(), SerializeError> {
+                Target::start_array(self)?;
+                for &b in v {
+                    Target::value_int(self, b)?;
+                }
+                Target::end_array(self)
+            }
+
+            fn serialize_none(self) -> Result<(), SerializeError> {
+                Target::value_null(self)
+            }
+
+            fn serialize_some<T>(self, value: &T) -> Result<(), SerializeError>
+            where
+                T: ?Sized + Serialize,
+            {
+                value.serialize(self)
+            }
+
+            fn serialize_unit(self) -> Result<(), SerializeError> {
+                Target::value_null(self)
+            }
+
+            fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerializeError> {
+                Target::value_null(self)
+            }
+
+            fn serialize_unit_variant(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                variant: &'static str,
+            ) -> Result<(), SerializeError> {
+                Target::value_str(self, variant)
+            }
+
+            fn serialize_newtype_struct<T>(
+                self,
+                _name: &'static str,
+                value: &T,
+            ) -> Result<(), SerializeError>
+            where
+                T: ?Sized + Serialize,
+            {
+                value.serialize(self)
+            }
+
+            fn serialize_newtype_variant<T>(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                variant: &'static str,
+                value: &T,
+            ) -> Result<(), SerializeError>
+            where
+                T: ?Sized + Serialize,
+            {
+                Target::start_object(self)?;
+                Target::field_name(self, variant)?;
+                value.serialize(&mut *self)?;
+                Target::end_object(self)
+            }
+
+            fn serialize_seq(
+                self,
+                _len: Option<usize>,
+            ) -> Result<Compound<'a, $target>, SerializeError> {
+                Target::start_array(self)?;
+                Ok(Compound::new(self, false))
+            }
+
+            fn serialize_tuple(self, len: usize) -> Result<Compound<'a, $target>, SerializeError> {
+                self.serialize_seq(Some(len))
+            }
+
+            fn serialize_tuple_struct(
+                self,
+                _name: &'static str,
+                len: usize,
+            ) -> Result<Compound<'a, $target>, SerializeError> {
+                self.serialize_seq(Some(len))
+            }
+
+            fn serialize_tuple_variant(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                variant: &'static str,
+                _len: usize,
+            ) -> Result<Compound<'a, $target>, SerializeError> {
+                Target::start_object(self)?;
+                Target::field_name(self, variant)?;
+                Target::start_array(self)?;
+                Ok(Compound::new(self, true))
+            }
+
+            fn serialize_map(
+                self,
+                _len: Option<usize>,
+            ) -> Result<Compound<'a, $target>, SerializeError> {
+                Target::start_object(self)?;
+                Ok(Compound::new(self, false))
+            }
+
+            fn serialize_struct(
+                self,
+                _name: &'static str,
+                len: usize,
+            ) -> Result<Compound<'a, $target>, SerializeError> {
+                #[cfg(feature = "arbitrary_precision")]
+                if _name == super::NUMBER_TOKEN {
+                    return Ok(Compound::new_number(self));
+                }
+                self.serialize_map(Some(len))
+            }
+
+            fn serialize_struct_variant(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                variant: &'static str,
+                _len: usize,
+            ) -> Result<Compound<'a, $target>, SerializeError> {
+                Target::start_object(self)?;
+                Target::field_name(self, variant)?;
+                Target::start_object(self)?;
+                Ok(Compound::new(self, true))
+            }
+        }
+    };
+}
+
+pub(super) use impl_serializer;
+
+impl_serializer! {
+    /// Serializes values at the current position of an emitter. This allows
+    /// [`Serialize`] types to be written into a larger document that is built
+    /// by hand, and it makes the emitter's options (e.g. pretty-printing or
+    /// sorted keys) apply to serialized values.
+    ///
+    /// The representation of values is the same as in Serde JSON (e.g. enums
+    /// are externally tagged and byte arrays become arrays of numbers), except
+    /// that floats that are NaN or infinite are rejected with
+    /// [`EmitterError::IllegalJsonNumber`] instead of being written as `null`.
+    ///
+    /// ```
+    /// use actson::emitter::JsonEmitter;
+    /// use serde::Serialize;
+    ///
+    /// let mut emitter = JsonEmitter::new(Vec::new());
+    /// emitter.start_object().unwrap();
+    /// emitter.field_name("data").unwrap();
+    /// vec![(1, "a"), (2, "b")].serialize(&mut emitter).unwrap();
+    /// emitter.end_object().unwrap();
+    /// assert_eq!(emitter.finish().unwrap(), br#"{"data":[[1,"a"],[2,"b"]]}"#);
+    /// ```
+    [W: Write] JsonEmitter<W>
+}
+
+/// Serializes the elements of a sequence or the members of a map or struct
+/// (see [`ser::Serializer`] implementations of [`JsonEmitter`] and
+/// [`EventSerializer`])
+pub struct Compound<'a, O> {
+    target: &'a mut O,
+
+    /// `true` if the container is wrapped in an object with the name of an
+    /// enum variant that needs to be closed too
+    variant: bool,
+
+    /// `true` if this is not a container but the literal of a number that
+    /// Serde JSON passes as a struct if its `arbitrary_precision` feature is
+    /// enabled
+    number: bool,
+}
+
+impl<'a, O> Compound<'a, O> {
+    pub(super) fn new(target: &'a mut O, variant: bool) -> Self {
+        Compound {
+            target,
+            variant,
+            number: false,
+        }
+    }
+
+    /// Create a compound that writes the literal of a number
+    #[cfg(feature = "arbitrary_precision")]
+    pub(super) fn new_number(target: &'a mut O) -> Self {
+        Compound {
+            target,
+            variant: false,
+            number: true,
+        }
+    }
+
+    fn end_array(self) -> Result<(), SerializeError>
+    where
+        O: Target,
+    {
+        self.target.end_array()?;
+        if self.variant {
+            self.target.end_object()?;
+        }
+        Ok(())
+    }
+
+    fn end_object(self) -> Result<(), SerializeError>
+    where
+        O: Target,
+    {
+        self.target.end_object()?;
+        if self.variant {
+            self.target.end_object()?;
+        }
+        Ok(())
+    }
+}
+
+impl<O> ser::SerializeSeq for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.end_array()
+    }
+}
+
+impl<O> ser::SerializeTuple for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.end_array()
+    }
+}
+
+impl<O> ser::SerializeTupleStruct for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.end_array()
+    }
+}
+
+impl<O> ser::SerializeTupleVariant for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.end_array()
+    }
+}
+
+impl<O> ser::SerializeMap for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = key.serialize(MapKeySerializer)?;
+        self.target.field_name(&key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.end_object()
+    }
+}
+
+impl<O> ser::SerializeStruct for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.number {
+            let literal = value.serialize(MapKeySerializer)?;
+            return self.target.value_number(&literal);
+        }
+        self.target.field_name(key)?;
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        if self.number {
+            return Ok(());
+        }
+        self.end_object()
+    }
+}
+
+impl<O> ser::SerializeStructVariant for Compound<'_, O>
+where
+    O: Target,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.target.field_name(key)?;
+        self.target.serialize(value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        self.end_object()
+    }
+}
+
+/// Converts map keys to strings. Like in Serde JSON, strings, characters,
+/// integers, booleans, and unit variants are allowed.
+struct MapKeySerializer;
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = SerializeError;
+
+    type SerializeSeq = Impossible<String, SerializeError>;
+    type SerializeTuple = Impossible<String, SerializeError>;
+    type SerializeTupleStruct = Impossible<String, SerializeError>;
+    type SerializeTupleVariant = Impossible<String, SerializeError>;
+    type SerializeMap = Impossible<String, SerializeError>;
+    type SerializeStruct = Impossible<String, SerializeError>;
+    type SerializeStructVariant = Impossible<String, SerializeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_none(self) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<String, SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_unit(self) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, SerializeError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, SerializeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(SerializeError::KeyMustBeAString)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+    use serde_json::json;
+
+    use crate::emitter::{EmitterError, EmitterOptionsBuilder, Indent, JsonEmitter};
+
+    use super::{to_string, SerializeError};
+
+    #[derive(Serialize)]
+    struct Unit;
+
+    #[derive(Serialize)]
+    struct Newtype(u8);
+
+    #[derive(Serialize)]
+    struct Tuple(u8, &'static str);
+
+    #[derive(Serialize)]
+    enum E {
+        A,
+        B(i32),
+        C(i32, bool),
+        D { x: Option<f32>, y: Option<f32> },
+    }
+
+    #[derive(Serialize)]
+    struct Record {
+        name: String,
+        tags: Vec<&'static str>,
+        bytes: &'static [u8],
+        unit: Unit,
+        newtype: Newtype,
+        tuple: Tuple,
+        enums: Vec<E>,
+        map: BTreeMap<i32, char>,
+    }
+
+    /// Test that values are serialized the same way as Serde JSON does it
+    #[test]
+    fn like_serde_json() {
+        let record = Record {
+            name: "Elvis \"The King\"".to_string(),
+            tags: vec!["a", "b"],
+            bytes: b"\x00\xff",
+            unit: Unit,
+            newtype: Newtype(1),
+            tuple: Tuple(2, "c"),
+            enums: vec![
+                E::A,
+                E::B(-1),
+                E::C(3, true),
+                E::D {
+                    x: Some(0.1),
+                    y: None,
+                },
+            ],
+            map: BTreeMap::from([(1, 'x'), (-2, '\u{1f600}')]),
+        };
+        assert_eq!(
+            to_string(&record).unwrap(),
+            serde_json::to_string(&record).unwrap()
+        );
+        assert_eq!(to_string(&json!(1e300)).unwrap(), "1e300");
+    }
+
+    /// Test that the emitter's options apply to serialized values and that
+    /// they can be embedded in a document built by hand
+    #[test]
+    fn nested() {
+        let mut emitter = JsonEmitter::new_with_options(
+            Vec::new(),
+            EmitterOptionsBuilder::default()
+                .with_indent(Indent::Spaces(1))
+                .with_sort_keys(true)
+                .build(),
+        );
+        emitter.start_array().unwrap();
+        json!({"b": [], "a": null}).serialize(&mut emitter).unwrap();
+        emitter.end_array().unwrap();
+        assert_eq!(
+            String::from_utf8(emitter.finish().unwrap()).unwrap(),
+            "[\n {\n  \"a\": null,\n  \"b\": []\n }\n]"
+        );
+    }
+
+    /// Test that invalid keys and floats are rejected
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            to_string(&BTreeMap::from([(vec![1], 1)])),
+            Err(SerializeError::KeyMustBeAString)
+        ));
+        assert!(matches!(
+            to_string(&f64::NAN),
+            Err(SerializeError::Emitter(EmitterError::IllegalJsonNumber(_)))
+        ));
+    }
+}
@@ -0,0 +1,426 @@
+use std::marker::PhantomData;
+
+use serde::de;
+
+use crate::{
+    feeder::FillJsonFeeder,
+    storage::{HeapStorage, ParserStorage},
+    JsonEvent, JsonParser,
+};
+
+use super::{DeserializeError, Deserializer};
+
+/// An iterator that deserializes a sequence of top-level values (e.g.
+/// concatenated JSON or newline-delimited JSON) into instances of type `V`.
+/// Create it with [`Deserializer::into_iter()`]. The parser must be in
+/// streaming mode (see
+/// [`JsonParserOptionsBuilder::with_streaming()`](crate::options::JsonParserOptionsBuilder::with_streaming)).
+///
+/// Each value is deserialized as soon as it is complete, so the iterator can
+/// process streams of arbitrary length. After an error, the iterator ends.
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::options::JsonParserOptionsBuilder;
+/// use actson::serde_json::Deserializer;
+/// use actson::JsonParser;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Record {
+///     id: u32,
+/// }
+///
+/// let json = b"{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}\n";
+/// let parser = JsonParser::new_with_options(
+///     SliceJsonFeeder::new(json),
+///     JsonParserOptionsBuilder::default().with_streaming(true).build(),
+/// );
+/// let ids = Deserializer::new_borrowed(parser)
+///     .into_iter::<Record>()
+///     .map(|r| r.map(|r| r.id))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(ids, vec![1, 2, 3]);
+/// ```
+pub struct StreamDeserializer<'de, T, S, V> {
+    de: Deserializer<'de, T, S>,
+    failed: bool,
+    output: PhantomData<V>,
+}
+
+impl<'de, T, S> Deserializer<'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Turn this deserializer into an iterator over all top-level values in
+    /// the JSON text (see [`StreamDeserializer`])
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<V>(self) -> StreamDeserializer<'de, T, S, V>
+    where
+        V: de::Deserialize<'de>,
+    {
+        StreamDeserializer {
+            de: self,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, S, V> StreamDeserializer<'de, T, S, V>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    V: de::Deserialize<'de>,
+{
+    /// Returns the number of bytes parsed so far. After a value has been
+    /// returned, this is the offset right after its end.
+    pub fn byte_offset(&self) -> usize {
+        self.de.parser().parsed_bytes()
+    }
+
+    /// Consume the iterator and return the underlying deserializer
+    pub fn into_inner(self) -> Deserializer<'de, T, S> {
+        self.de
+    }
+}
+
+impl<'de, T, S, V> Iterator for StreamDeserializer<'de, T, S, V>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    V: de::Deserialize<'de>,
+{
+    type Item = Result<V, DeserializeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let r = match self.de.peek() {
+            Ok(None) => return None,
+            Ok(Some(_)) => V::deserialize(&mut self.de),
+            Err(e) => Err(e),
+        };
+        self.failed = r.is_err();
+        Some(r)
+    }
+}
+
+/// An iterator that deserializes the elements of a top-level array one at a
+/// time into instances of type `V`. Create it with [`array_iter()`] or
+/// [`Deserializer::into_array_iter()`].
+///
+/// Only the current element is kept in memory, so arrays of arbitrary
+/// length can be processed. The iterator checks that the array is followed
+/// by the end of the JSON text. After an error, the iterator ends.
+pub struct ArrayIter<'de, T, S, V> {
+    de: Deserializer<'de, T, S>,
+    started: bool,
+    done: bool,
+    output: PhantomData<V>,
+}
+
+/// Iterate over the elements of the top-level array in the JSON text
+/// provided by the given feeder and deserialize each of them into an
+/// instance of type `T`
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::serde_json::array_iter;
+///
+/// let json = br#"[{"id": 1}, {"id": 2}]"#;
+/// let mut sum = 0;
+/// for element in array_iter::<_, serde_json::Value>(SliceJsonFeeder::new(json)) {
+///     sum += element.unwrap()["id"].as_u64().unwrap();
+/// }
+/// assert_eq!(sum, 3);
+/// ```
+pub fn array_iter<F, T>(feeder: F) -> ArrayIter<'static, F, HeapStorage, T>
+where
+    F: FillJsonFeeder,
+    T: de::DeserializeOwned,
+{
+    Deserializer::new(JsonParser::new(feeder)).into_array_iter()
+}
+
+impl<'de, T, S> Deserializer<'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Turn this deserializer into an iterator over the elements of the
+    /// top-level array in the JSON text (see [`ArrayIter`])
+    pub fn into_array_iter<V>(self) -> ArrayIter<'de, T, S, V>
+    where
+        V: de::Deserialize<'de>,
+    {
+        ArrayIter {
+            de: self,
+            started: false,
+            done: false,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, S, V> ArrayIter<'de, T, S, V>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    V: de::Deserialize<'de>,
+{
+    /// Get the next element or `None` if the end of the array has been
+    /// reached
+    fn next_element(&mut self) -> Result<Option<V>, DeserializeError> {
+        if !self.started {
+            self.started = true;
+            self.de
+                .expect("start of array", |e| e == Some(JsonEvent::StartArray))?;
+        }
+        if self.de.peek()? == Some(JsonEvent::EndArray) {
+            self.de.next()?;
+            self.de.end()?;
+            return Ok(None);
+        }
+        V::deserialize(&mut self.de).map(Some)
+    }
+}
+
+impl<'de, T, S, V> Iterator for ArrayIter<'de, T, S, V>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    V: de::Deserialize<'de>,
+{
+    type Item = Result<V, DeserializeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let r = self.next_element().transpose();
+        self.done = !matches!(r, Some(Ok(_)));
+        r
+    }
+}
+
+/// An iterator that deserializes the members of a top-level object one at
+/// a time into pairs of keys and instances of type `V`. Create it with
+/// [`map_iter()`] or [`Deserializer::into_map_iter()`].
+///
+/// Only the current member is kept in memory, so objects with an arbitrary
+/// number of members can be processed. The iterator checks that the object
+/// is followed by the end of the JSON text. After an error, the iterator
+/// ends.
+pub struct MapIter<'de, T, S, V> {
+    de: Deserializer<'de, T, S>,
+    started: bool,
+    done: bool,
+    output: PhantomData<V>,
+}
+
+/// Iterate over the members of the top-level object in the JSON text
+/// provided by the given feeder and deserialize each value into an instance
+/// of type `T`
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::serde_json::map_iter;
+///
+/// let json = br#"{"Elvis": [1956, 1957], "Priscilla": [], "Lisa": [2003]}"#;
+/// let mut counts = Vec::new();
+/// for member in map_iter::<_, Vec<u32>>(SliceJsonFeeder::new(json)) {
+///     let (name, albums) = member.unwrap();
+///     counts.push((name, albums.len()));
+/// }
+/// assert_eq!(counts, vec![
+///     ("Elvis".to_string(), 2),
+///     ("Priscilla".to_string(), 0),
+///     ("Lisa".to_string(), 1),
+/// ]);
+/// ```
+pub fn map_iter<F, T>(feeder: F) -> MapIter<'static, F, HeapStorage, T>
+where
+    F: FillJsonFeeder,
+    T: de::DeserializeOwned,
+{
+    Deserializer::new(JsonParser::new(feeder)).into_map_iter()
+}
+
+impl<'de, T, S> Deserializer<'de, T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Turn this deserializer into an iterator over the members of the
+    /// top-level object in the JSON text (see [`MapIter`])
+    pub fn into_map_iter<V>(self) -> MapIter<'de, T, S, V>
+    where
+        V: de::Deserialize<'de>,
+    {
+        MapIter {
+            de: self,
+            started: false,
+            done: false,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, S, V> MapIter<'de, T, S, V>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    V: de::Deserialize<'de>,
+{
+    /// Get the next member or `None` if the end of the object has been
+    /// reached
+    fn next_member(&mut self) -> Result<Option<(String, V)>, DeserializeError> {
+        if !self.started {
+            self.started = true;
+            self.de
+                .expect("start of object", |e| e == Some(JsonEvent::StartObject))?;
+        }
+        if self.de.next()? == Some(JsonEvent::EndObject) {
+            self.de.end()?;
+            return Ok(None);
+        }
+        let key = self.de.parser().current_str()?.to_string();
+        let value = V::deserialize(&mut self.de)?;
+        Ok(Some((key, value)))
+    }
+}
+
+impl<'de, T, S, V> Iterator for MapIter<'de, T, S, V>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    V: de::Deserialize<'de>,
+{
+    type Item = Result<(String, V), DeserializeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let r = self.next_member().transpose();
+        self.done = !matches!(r, Some(Ok(_)));
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use crate::{
+        feeder::{BufReaderJsonFeeder, SliceJsonFeeder},
+        options::JsonParserOptionsBuilder,
+        parser::ParserError,
+        serde_json::{array_iter, map_iter, DeserializeError, Deserializer},
+        JsonParser,
+    };
+
+    /// Test that values of different types are deserialized from a reader
+    /// and that the iterator ends after an error
+    #[test]
+    fn values() {
+        let json = br#"1 "two" [3] {"four": 4}"#;
+        let parser = JsonParser::new_with_options(
+            BufReaderJsonFeeder::new(std::io::BufReader::with_capacity(2, &json[..])),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let values = Deserializer::new(parser)
+            .into_iter::<Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![json!(1), json!("two"), json!([3]), json!({"four": 4})]
+        );
+
+        let parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(b"1 2 ] 3"),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut iter = Deserializer::new_borrowed(parser).into_iter::<u8>();
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_eq!(iter.next().unwrap().unwrap(), 2);
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DeserializeError::Parser(ParserError::SyntaxError)))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    /// Test that the elements of a top-level array are deserialized one at a
+    /// time and that errors end the iteration
+    #[test]
+    fn array() {
+        let json = br#"[[1, 2], [], [3]]"#;
+        let lens = array_iter::<_, Vec<u8>>(SliceJsonFeeder::new(json))
+            .map(|e| e.unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(lens, vec![2, 0, 1]);
+
+        assert_eq!(array_iter::<_, u8>(SliceJsonFeeder::new(b"[]")).count(), 0);
+
+        let mut iter = array_iter::<_, u8>(SliceJsonFeeder::new(b"[1, \"a\"]"));
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DeserializeError::Custom(_)))
+        ));
+        assert!(iter.next().is_none());
+
+        let mut iter = array_iter::<_, u8>(SliceJsonFeeder::new(b"{}"));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DeserializeError::UnexpectedEvent { .. }))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    /// Test that the members of a top-level object are deserialized one at
+    /// a time and that errors end the iteration
+    #[test]
+    fn map() {
+        let json = br#"{"a\n": [1, 2], "b": [], "a\n": [3]}"#;
+        let feeder = BufReaderJsonFeeder::new(std::io::BufReader::with_capacity(2, &json[..]));
+        let members = map_iter::<_, Vec<u8>>(feeder)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            members,
+            vec![
+                ("a\n".to_string(), vec![1, 2]),
+                ("b".to_string(), vec![]),
+                ("a\n".to_string(), vec![3])
+            ]
+        );
+
+        assert_eq!(map_iter::<_, u8>(SliceJsonFeeder::new(b"{}")).count(), 0);
+
+        let mut iter = map_iter::<_, u8>(SliceJsonFeeder::new(br#"{"a": 1} 2"#));
+        assert_eq!(iter.next().unwrap().unwrap(), ("a".to_string(), 1));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DeserializeError::Parser(ParserError::SyntaxError)))
+        ));
+        assert!(iter.next().is_none());
+
+        let mut iter = map_iter::<_, u8>(SliceJsonFeeder::new(b"[1]"));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DeserializeError::UnexpectedEvent { .. }))
+        ));
+        assert!(iter.next().is_none());
+    }
+}
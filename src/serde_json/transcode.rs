@@ -0,0 +1,281 @@
+use std::{cell::RefCell, fmt};
+
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess},
+    ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer},
+};
+
+/// Transcode a value from a Serde [`Deserializer`] directly into a Serde
+/// [`Serializer`] without building an intermediate representation. This
+/// allows JSON to be converted to other Serde formats (e.g. CBOR or
+/// MessagePack) in a single streaming pass.
+///
+/// The function is compatible with the `serde_transcode` crate, which can be
+/// used with [`Deserializer`](super::Deserializer) and the
+/// [`JsonEmitter`](crate::emitter::JsonEmitter) just as well.
+///
+/// Like with Serde JSON, floats are passed on as Serde JSON's internal
+/// number representation if the `arbitrary_precision` feature is enabled,
+/// which only JSON serializers understand.
+///
+/// ```
+/// use actson::emitter::{EmitterOptionsBuilder, Indent, JsonEmitter};
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::serde_json::{transcode, Deserializer};
+/// use actson::JsonParser;
+///
+/// let json = br#"{"name": "Elvis", "albums": [1956, 1957]}"#;
+/// let mut de = Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(json)));
+/// let mut emitter = JsonEmitter::new_with_options(
+///     Vec::new(),
+///     EmitterOptionsBuilder::default()
+///         .with_indent(Indent::Spaces(2))
+///         .build(),
+/// );
+/// transcode(&mut de, &mut emitter).unwrap();
+/// de.end().unwrap();
+/// assert_eq!(
+///     String::from_utf8(emitter.finish().unwrap()).unwrap(),
+///     "{\n  \"name\": \"Elvis\",\n  \"albums\": [\n    1956,\n    1957\n  ]\n}"
+/// );
+/// ```
+pub fn transcode<'de, D, S>(deserializer: D, serializer: S) -> Result<S::Ok, S::Error>
+where
+    D: Deserializer<'de>,
+    S: Serializer,
+{
+    Transcoder::new(deserializer).serialize(serializer)
+}
+
+/// A [`Serialize`] implementation that transcodes the value from a Serde
+/// [`Deserializer`] when it is serialized (see [`transcode()`]). It can be
+/// serialized only once.
+pub struct Transcoder<D>(RefCell<Option<D>>);
+
+impl<'de, D> Transcoder<D>
+where
+    D: Deserializer<'de>,
+{
+    /// Create a new transcoder that reads from the given deserializer
+    pub fn new(deserializer: D) -> Self {
+        Transcoder(RefCell::new(Some(deserializer)))
+    }
+}
+
+impl<'de, D> Serialize for Transcoder<D>
+where
+    D: Deserializer<'de>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let deserializer = self
+            .0
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| ser::Error::custom("transcoder can only be serialized once"))?;
+        deserializer
+            .deserialize_any(Visitor(serializer))
+            .map_err(ser::Error::custom)
+    }
+}
+
+/// Forwards each value the deserializer visits to the serializer
+struct Visitor<S>(S);
+
+impl<'de, S> de::Visitor<'de> for Visitor<S>
+where
+    S: Serializer,
+{
+    type Value = S::Ok;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<S::Ok, E> {
+        self.0.serialize_bool(v).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<S::Ok, E> {
+        self.0.serialize_i64(v).map_err(E::custom)
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<S::Ok, E> {
+        self.0.serialize_i128(v).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<S::Ok, E> {
+        self.0.serialize_u64(v).map_err(E::custom)
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<S::Ok, E> {
+        self.0.serialize_u128(v).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<S::Ok, E> {
+        self.0.serialize_f64(v).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<S::Ok, E> {
+        self.0.serialize_str(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<S::Ok, E> {
+        self.0.serialize_bytes(v).map_err(E::custom)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<S::Ok, E> {
+        self.0.serialize_none().map_err(E::custom)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<S::Ok, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0
+            .serialize_some(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<S::Ok, E> {
+        self.0.serialize_unit().map_err(E::custom)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<S::Ok, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0
+            .serialize_newtype_struct("Transcoder", &Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<S::Ok, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut s = self
+            .0
+            .serialize_seq(seq.size_hint())
+            .map_err(de::Error::custom)?;
+        while seq.next_element_seed(ElementSeed(&mut s))?.is_some() {}
+        s.end().map_err(de::Error::custom)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<S::Ok, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut m = self
+            .0
+            .serialize_map(map.size_hint())
+            .map_err(de::Error::custom)?;
+        while map.next_key_seed(KeySeed(&mut m))?.is_some() {
+            map.next_value_seed(ValueSeed(&mut m))?;
+        }
+        m.end().map_err(de::Error::custom)
+    }
+}
+
+/// Transcodes an element of a sequence
+struct ElementSeed<'a, S>(&'a mut S);
+
+impl<'de, S> DeserializeSeed<'de> for ElementSeed<'_, S>
+where
+    S: SerializeSeq,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0
+            .serialize_element(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Transcodes the key of a map entry
+struct KeySeed<'a, S>(&'a mut S);
+
+impl<'de, S> DeserializeSeed<'de> for KeySeed<'_, S>
+where
+    S: SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0
+            .serialize_key(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Transcodes the value of a map entry
+struct ValueSeed<'a, S>(&'a mut S);
+
+impl<'de, S> DeserializeSeed<'de> for ValueSeed<'_, S>
+where
+    S: SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0
+            .serialize_value(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::Value;
+
+    use crate::{
+        feeder::{BufReaderJsonFeeder, SliceJsonFeeder},
+        serde_json::Deserializer,
+        JsonParser,
+    };
+
+    use super::{transcode, Transcoder};
+
+    /// Test that JSON can be converted to CBOR and back without changes and
+    /// that syntax errors are reported
+    #[test]
+    fn cbor() {
+        let json = br#"{"a": [1, -2, "\u00e9", true, null], "b": {"c": {}, "d": []}}"#;
+        let feeder = BufReaderJsonFeeder::new(std::io::BufReader::with_capacity(3, &json[..]));
+        let mut de = Deserializer::new(JsonParser::new(feeder));
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&Transcoder::new(&mut de), &mut cbor).unwrap();
+        de.end().unwrap();
+        assert_eq!(
+            ciborium::from_reader::<Value, _>(&cbor[..]).unwrap(),
+            serde_json::from_slice::<Value>(json).unwrap()
+        );
+
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            let mut de =
+                Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(b"[2.5, 1e3]")));
+            let mut cbor = Vec::new();
+            ciborium::into_writer(&Transcoder::new(&mut de), &mut cbor).unwrap();
+            assert_eq!(
+                ciborium::from_reader::<Vec<f64>, _>(&cbor[..]).unwrap(),
+                vec![2.5, 1000.0]
+            );
+        }
+
+        let mut de = Deserializer::new_borrowed(JsonParser::new(SliceJsonFeeder::new(b"[1, }")));
+        assert!(transcode(&mut de, &mut crate::emitter::JsonEmitter::new(Vec::new())).is_err());
+    }
+}
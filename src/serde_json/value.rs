@@ -0,0 +1,307 @@
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    feeder::{FillJsonFeeder, JsonFeeder, SliceJsonFeeder},
+    sink::JsonEventSink,
+    storage::ParserStorage,
+    JsonEvent, JsonNumber, JsonParser,
+};
+
+use super::DeserializeError;
+
+/// A container that is currently being built
+enum Level {
+    Object(Map<String, Value>, Option<String>),
+    Array(Vec<Value>),
+}
+
+/// A [`JsonEventSink`] that builds a [`serde_json::Value`] from the events
+/// it receives. Values are represented like in Serde JSON.
+///
+/// The builder does not have to receive all events of a JSON text. Forward
+/// the events of a single value to it, for example, to materialize one
+/// object while streaming the rest of the JSON text. As soon as a complete
+/// value has been built, [`Self::is_done()`] returns `true` and the value
+/// can be retrieved with [`Self::take()`].
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::serde_json::ValueBuilder;
+/// use actson::sink::JsonEventSink;
+/// use actson::{JsonEvent, JsonParser};
+/// use serde_json::json;
+///
+/// let json = br#"{"items": [1, 2, 3], "meta": {"page": 1, "tags": ["a"]}}"#;
+/// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+/// let mut builder = ValueBuilder::new();
+/// let mut in_meta = false;
+/// while let Some(event) = parser.next_event().unwrap() {
+///     if event == JsonEvent::FieldName && parser.current_str().unwrap() == "meta" {
+///         in_meta = true;
+///     } else if in_meta {
+///         builder.on_event(event, &parser).unwrap();
+///         in_meta = !builder.is_done();
+///     }
+/// }
+/// assert_eq!(builder.take(), Some(json!({"page": 1, "tags": ["a"]})));
+/// ```
+#[derive(Default)]
+pub struct ValueBuilder {
+    /// The containers that are currently being built
+    stack: Vec<Level>,
+
+    /// The last complete value
+    value: Option<Value>,
+}
+
+impl ValueBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if a complete value has been built and not taken yet
+    pub fn is_done(&self) -> bool {
+        self.stack.is_empty() && self.value.is_some()
+    }
+
+    /// Take the last complete value out of the builder, so that it can
+    /// build another one. Returns `None` if the value is not complete yet.
+    pub fn take(&mut self) -> Option<Value> {
+        if self.stack.is_empty() {
+            self.value.take()
+        } else {
+            None
+        }
+    }
+
+    /// Consume the builder and return the last complete value
+    pub fn into_value(mut self) -> Option<Value> {
+        self.take()
+    }
+
+    /// Add a value to the current container or make it the complete value
+    /// if there is no container
+    fn push(&mut self, value: Value) {
+        match self.stack.last_mut() {
+            Some(Level::Object(map, key)) => {
+                map.insert(key.take().unwrap_or_default(), value);
+            }
+            Some(Level::Array(array)) => array.push(value),
+            None => self.value = Some(value),
+        }
+    }
+}
+
+/// Parse a byte slice into a [`serde_json::Value`].
+///
+/// Unlike [`from_slice::<Value>()`](super::from_slice()), which goes
+/// through Serde's recursive `Deserialize` implementation of [`Value`], this
+/// function builds the value with a [`ValueBuilder`] and an explicit stack.
+/// Documents that are nested as deeply as the parser's maximum depth allows
+/// can therefore not overflow the thread's stack while the value is being
+/// constructed.
+///
+/// ```
+/// use actson::serde_json::value_from_slice;
+/// use serde_json::json;
+///
+/// let json = format!("{}1{}", "[".repeat(1000), "]".repeat(1000));
+/// let mut value = &value_from_slice(json.as_bytes()).unwrap();
+/// for _ in 0..1000 {
+///     value = &value[0];
+/// }
+/// assert_eq!(value, &json!(1));
+/// ```
+pub fn value_from_slice(v: &[u8]) -> Result<Value, DeserializeError> {
+    value_from_feeder(SliceJsonFeeder::new(v))
+}
+
+/// Parse the JSON text provided by the given feeder into a
+/// [`serde_json::Value`] without recursion. See [`value_from_slice()`].
+pub fn value_from_feeder<F>(feeder: F) -> Result<Value, DeserializeError>
+where
+    F: FillJsonFeeder,
+{
+    let mut parser = JsonParser::new(feeder);
+    let mut builder = ValueBuilder::new();
+    while let Some(event) = parser.next_event()? {
+        if event == JsonEvent::NeedMoreInput {
+            parser.feeder.fill()?;
+        } else {
+            builder.on_event(event, &parser)?;
+        }
+    }
+    builder
+        .into_value()
+        .ok_or(DeserializeError::UnexpectedEvent {
+            expected: "value",
+            found: None,
+        })
+}
+
+/// Convert the number that has just been parsed to a [`Number`]
+fn current_number<T, S>(parser: &JsonParser<T, S>) -> Result<Number, DeserializeError>
+where
+    T: JsonFeeder,
+    S: ParserStorage,
+{
+    match parser.current_number()? {
+        JsonNumber::I64(i) => Ok(i.into()),
+        JsonNumber::U64(u) => Ok(u.into()),
+        #[cfg(feature = "arbitrary_precision")]
+        JsonNumber::F64(_) | JsonNumber::Big(_) => {
+            let literal = parser.current_str()?;
+            literal
+                .parse()
+                .map_err(|_| DeserializeError::NumberOutOfRange(literal.to_string()))
+        }
+        #[cfg(not(feature = "arbitrary_precision"))]
+        JsonNumber::F64(f) => {
+            Number::from_f64(f).ok_or_else(|| DeserializeError::NumberOutOfRange(f.to_string()))
+        }
+        #[cfg(not(feature = "arbitrary_precision"))]
+        JsonNumber::Big(s) => Number::from_f64(parser.current_float()?)
+            .ok_or_else(|| DeserializeError::NumberOutOfRange(s.to_string())),
+    }
+}
+
+impl JsonEventSink for ValueBuilder {
+    type Error = DeserializeError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartObject => self.stack.push(Level::Object(Map::new(), None)),
+            JsonEvent::StartArray => self.stack.push(Level::Array(Vec::new())),
+            JsonEvent::EndObject | JsonEvent::EndArray => match self.stack.pop() {
+                Some(Level::Object(map, _)) => self.push(Value::Object(map)),
+                Some(Level::Array(array)) => self.push(Value::Array(array)),
+                None => {
+                    return Err(DeserializeError::UnexpectedEvent {
+                        expected: "value",
+                        found: Some(event),
+                    })
+                }
+            },
+            JsonEvent::FieldName => match self.stack.last_mut() {
+                Some(Level::Object(_, key)) => *key = Some(parser.current_str()?.to_string()),
+                _ => {
+                    return Err(DeserializeError::UnexpectedEvent {
+                        expected: "value",
+                        found: Some(event),
+                    })
+                }
+            },
+            JsonEvent::ValueString => self.push(Value::String(parser.current_str()?.to_string())),
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let n = current_number(parser)?;
+                self.push(Value::Number(n));
+            }
+            JsonEvent::ValueTrue => self.push(Value::Bool(true)),
+            JsonEvent::ValueFalse => self.push(Value::Bool(false)),
+            JsonEvent::ValueNull => self.push(Value::Null),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use crate::{
+        feeder::SliceJsonFeeder,
+        options::JsonParserOptionsBuilder,
+        sink::{parse_with, parse_with_parser, JsonEventSink},
+        JsonParser,
+    };
+
+    use super::{value_from_slice, ValueBuilder};
+
+    /// Test that values of all types are built like in Serde JSON
+    #[test]
+    fn like_serde_json() {
+        let json = br#"{"a": [1, -2, 2.5, 18446744073709551616, "\u00e9"], "b": {"c": null},
+            "d": true, "e": false, "f": {}, "g": []}"#;
+        let mut builder = ValueBuilder::new();
+        parse_with(SliceJsonFeeder::new(json), &mut builder).unwrap();
+        assert!(builder.is_done());
+        assert_eq!(
+            builder.into_value().unwrap(),
+            serde_json::from_slice::<Value>(json).unwrap()
+        );
+
+        #[cfg(not(feature = "arbitrary_precision"))]
+        assert!(matches!(
+            parse_with(SliceJsonFeeder::new(b"[1e400]"), &mut ValueBuilder::new()),
+            Err(crate::sink::ParseWithError::Sink(
+                crate::serde_json::DeserializeError::NumberOutOfRange(_)
+            ))
+        ));
+    }
+
+    /// Test that the builder can build several values in a row and that
+    /// incomplete values cannot be taken
+    #[test]
+    fn take() {
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(br#"[1] {"a": 2} 3"#),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut builder = ValueBuilder::new();
+        let mut values = Vec::new();
+        while let Some(event) = parser.next_event().unwrap() {
+            builder.on_event(event, &parser).unwrap();
+            if !builder.is_done() {
+                assert_eq!(builder.take(), None);
+            } else {
+                values.push(builder.take().unwrap());
+            }
+        }
+        assert_eq!(values, vec![json!([1]), json!({"a": 2}), json!(3)]);
+
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(br#"{"a": [1, 2"#));
+        let mut builder = ValueBuilder::new();
+        assert!(parse_with_parser(&mut parser, &mut builder).is_err());
+        assert!(!builder.is_done());
+        assert_eq!(builder.into_value(), None);
+    }
+
+    /// Test that deeply nested values can be built on a small stack
+    #[test]
+    fn deep() {
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| {
+                let json = format!("{}{}", r#"{"a":["#.repeat(1000), "]}".repeat(1000));
+                let value = value_from_slice(json.as_bytes()).unwrap();
+                let mut v = &value;
+                let mut depth = 0;
+                while let Some(inner) = v.get("a").and_then(|a| a.get(0)) {
+                    v = inner;
+                    depth += 1;
+                }
+                assert_eq!(depth, 999);
+                assert_eq!(v, &json!({"a": []}));
+                // dropping a value is recursive in Serde JSON
+                std::mem::forget(value);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert!(value_from_slice(b"[1").is_err());
+        assert!(value_from_slice(b"[1] 2").is_err());
+    }
+}
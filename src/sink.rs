@@ -0,0 +1,453 @@
+//! Drive a [`JsonParser`] and forward its events to a [`JsonEventSink`].
+//!
+//! Implement [`JsonEventSink`] to process events and let [`parse_with()`]
+//! run the parser loop. The loop requests more input from the feeder
+//! whenever the parser needs it (see [`FillJsonFeeder`]), so the sink never
+//! sees [`JsonEvent::NeedMoreInput`].
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::parser::InvalidStringValueError;
+//! use actson::sink::{parse_with, JsonEventSink};
+//! use actson::storage::ParserStorage;
+//! use actson::feeder::JsonFeeder;
+//! use actson::{JsonEvent, JsonParser};
+//!
+//! #[derive(Default)]
+//! struct FieldNames(Vec<String>);
+//!
+//! impl JsonEventSink for FieldNames {
+//!     type Error = InvalidStringValueError;
+//!
+//!     fn on_event<T, S>(
+//!         &mut self,
+//!         event: JsonEvent,
+//!         parser: &JsonParser<T, S>,
+//!     ) -> Result<(), Self::Error>
+//!     where
+//!         T: JsonFeeder,
+//!         S: ParserStorage,
+//!     {
+//!         if event == JsonEvent::FieldName {
+//!             self.0.push(parser.current_str()?.to_string());
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+//! let mut names = FieldNames::default();
+//! parse_with(SliceJsonFeeder::new(json), &mut names).unwrap();
+//! assert_eq!(names.0, vec!["name", "age"]);
+//! ```
+
+use std::{convert::Infallible, ops::ControlFlow};
+
+use thiserror::Error;
+
+use crate::{
+    escape::escape_str,
+    feeder::{FillError, FillJsonFeeder, JsonFeeder, SliceJsonFeeder},
+    parser::ParserError,
+    storage::ParserStorage,
+    JsonEvent, JsonParser,
+};
+
+/// Receives the events of a [`JsonParser`]. See [`parse_with()`].
+pub trait JsonEventSink {
+    /// The error the sink returns if it cannot process an event
+    type Error;
+
+    /// Process the given event. Use the parser to read the event's value
+    /// (e.g. with [`JsonParser::current_str()`]).
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage;
+}
+
+impl<K> JsonEventSink for &mut K
+where
+    K: JsonEventSink,
+{
+    type Error = K::Error;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), K::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        (**self).on_event(event, parser)
+    }
+}
+
+/// A sink that forwards each event to two other sinks, so that a JSON text
+/// can be processed in several ways in a single pass. Nest tees to forward
+/// events to more than two sinks. Since `&mut K` is a sink too, the tee can
+/// also borrow sinks that are used again afterwards.
+///
+/// ```
+/// use actson::feeder::{JsonFeeder, SliceJsonFeeder};
+/// use actson::sink::{parse_with, JsonEventSink, Tee};
+/// use actson::storage::ParserStorage;
+/// use actson::{JsonEvent, JsonParser};
+///
+/// /// Counts events of a given type
+/// struct Count(JsonEvent, usize);
+///
+/// impl JsonEventSink for Count {
+///     type Error = std::convert::Infallible;
+///
+///     fn on_event<T, S>(&mut self, event: JsonEvent, _: &JsonParser<T, S>) -> Result<(), Self::Error>
+///     where
+///         T: JsonFeeder,
+///         S: ParserStorage,
+///     {
+///         if event == self.0 {
+///             self.1 += 1;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let json = r#"{"a": [1, 2], "b": {"c": 3}}"#.as_bytes();
+/// let mut fields = Count(JsonEvent::FieldName, 0);
+/// let mut ints = Count(JsonEvent::ValueInt, 0);
+/// parse_with(SliceJsonFeeder::new(json), &mut Tee::new(&mut fields, &mut ints)).unwrap();
+///
+/// assert_eq!(fields.1, 3);
+/// assert_eq!(ints.1, 3);
+/// ```
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Tee<A, B> {
+    /// Create a tee that forwards each event first to `first` and then to
+    /// `second`
+    pub fn new(first: A, second: B) -> Self {
+        Tee { first, second }
+    }
+
+    /// Get the first sink
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Get the second sink
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Consume the tee and return both sinks
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+/// An error returned by one of the sinks of a [`Tee`]
+#[derive(Error, Debug)]
+pub enum TeeError<A, B> {
+    /// The first sink could not process an event
+    #[error("first sink error: {0}")]
+    First(A),
+
+    /// The second sink could not process an event
+    #[error("second sink error: {0}")]
+    Second(B),
+}
+
+impl<A, B> JsonEventSink for Tee<A, B>
+where
+    A: JsonEventSink,
+    B: JsonEventSink,
+{
+    type Error = TeeError<A::Error, B::Error>;
+
+    /// Forward the event to both sinks. If the first sink returns an error,
+    /// the event is not forwarded to the second one.
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        self.first
+            .on_event(event, parser)
+            .map_err(TeeError::First)?;
+        self.second
+            .on_event(event, parser)
+            .map_err(TeeError::Second)
+    }
+}
+
+/// An error that can happen in [`parse_with()`]
+#[derive(Error, Debug)]
+pub enum ParseWithError<E> {
+    /// The JSON text could not be parsed
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    /// The feeder could not provide more input
+    #[error(transparent)]
+    Fill(#[from] FillError),
+
+    /// The sink could not process an event
+    #[error("sink error: {0}")]
+    Sink(E),
+}
+
+/// Parse the JSON text provided by the given feeder and forward all events
+/// to the given sink
+pub fn parse_with<T, K>(feeder: T, sink: &mut K) -> Result<(), ParseWithError<K::Error>>
+where
+    T: FillJsonFeeder,
+    K: JsonEventSink,
+{
+    parse_with_parser(&mut JsonParser::new(feeder), sink)
+}
+
+/// Like [`parse_with()`] but use an existing parser (e.g. one that has been
+/// created with custom options)
+pub fn parse_with_parser<T, S, K>(
+    parser: &mut JsonParser<T, S>,
+    sink: &mut K,
+) -> Result<(), ParseWithError<K::Error>>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+    K: JsonEventSink,
+{
+    while let Some(e) = parser.next_event()? {
+        if e == JsonEvent::NeedMoreInput {
+            parser.feeder.fill()?;
+        } else {
+            sink.on_event(e, parser).map_err(ParseWithError::Sink)?;
+        }
+    }
+    Ok(())
+}
+
+/// Forward the given event to the sink together with a value that does not
+/// come from the original parser. The value is read by a separate parser
+/// from `json`, which must be a JSON text that produces the event.
+pub(crate) fn forward_replaced<K>(
+    sink: &mut K,
+    event: JsonEvent,
+    json: &[u8],
+) -> Result<(), K::Error>
+where
+    K: JsonEventSink,
+{
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    while let Ok(Some(e)) = parser.next_event() {
+        if e == event {
+            return sink.on_event(e, &parser);
+        }
+    }
+    unreachable!("the replacement must produce the event")
+}
+
+/// Forward all events of the given JSON text to the sink (see
+/// [`forward_replaced()`])
+pub(crate) fn forward_json<K>(sink: &mut K, json: &[u8]) -> Result<(), K::Error>
+where
+    K: JsonEventSink,
+{
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    while let Ok(Some(e)) = parser.next_event() {
+        sink.on_event(e, &parser)?;
+    }
+    Ok(())
+}
+
+/// Forward a [`JsonEvent::FieldName`] or [`JsonEvent::ValueString`] with the
+/// given string to the sink (see [`forward_replaced()`]). `buf` is used to
+/// build the JSON text.
+pub(crate) fn forward_str<K>(
+    sink: &mut K,
+    event: JsonEvent,
+    s: &str,
+    buf: &mut Vec<u8>,
+) -> Result<(), K::Error>
+where
+    K: JsonEventSink,
+{
+    buf.clear();
+    if event == JsonEvent::FieldName {
+        buf.push(b'{');
+        escape_str(s, buf);
+        buf.push(b':');
+    } else {
+        escape_str(s, buf);
+    }
+    forward_replaced(sink, event, buf)
+}
+
+impl<T, S> JsonParser<T, S>
+where
+    T: FillJsonFeeder,
+    S: ParserStorage,
+{
+    /// Parse the JSON text and call the given function for each event. The
+    /// function receives the event and the parser, which can be used to read
+    /// the event's value. Parsing stops as soon as the function returns
+    /// [`ControlFlow::Break`], in which case the break value is returned.
+    /// The parser can be used to continue parsing afterwards. If the end of
+    /// the JSON text is reached, the function returns `None`.
+    ///
+    /// Like with [`parse_with()`], the function never receives
+    /// [`JsonEvent::NeedMoreInput`].
+    ///
+    /// ```
+    /// use std::ops::ControlFlow;
+    ///
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// let json = r#"[{"id": 1, "name": "Elvis"}, {"id": 2, "name": "Max"}]"#.as_bytes();
+    /// let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    ///
+    /// // find the first name
+    /// let name = parser.for_each_event(|event, parser| match event {
+    ///     JsonEvent::ValueString => ControlFlow::Break(parser.current_str().unwrap().to_string()),
+    ///     _ => ControlFlow::Continue(()),
+    /// });
+    ///
+    /// assert_eq!(name.unwrap().as_deref(), Some("Elvis"));
+    /// ```
+    pub fn for_each_event<B>(
+        &mut self,
+        mut f: impl FnMut(JsonEvent, &Self) -> ControlFlow<B>,
+    ) -> Result<Option<B>, ParseWithError<Infallible>> {
+        while let Some(e) = self.next_event()? {
+            if e == JsonEvent::NeedMoreInput {
+                self.feeder.fill()?;
+            } else if let ControlFlow::Break(b) = f(e, self) {
+                return Ok(Some(b));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ops::ControlFlow;
+
+    use crate::{
+        feeder::{JsonFeeder, SliceJsonFeeder},
+        parser::ParserError,
+        storage::ParserStorage,
+        JsonEvent, JsonParser,
+    };
+
+    use super::{parse_with, JsonEventSink, ParseWithError, Tee, TeeError};
+
+    /// Stops after the given number of events
+    struct Limit(usize);
+
+    impl JsonEventSink for Limit {
+        type Error = &'static str;
+
+        fn on_event<T, S>(&mut self, _: JsonEvent, _: &JsonParser<T, S>) -> Result<(), Self::Error>
+        where
+            T: JsonFeeder,
+            S: ParserStorage,
+        {
+            if self.0 == 0 {
+                return Err("limit reached");
+            }
+            self.0 -= 1;
+            Ok(())
+        }
+    }
+
+    /// Test that errors of the sink and the parser are returned
+    #[test]
+    fn errors() {
+        let json = b"[1, 2, 3]";
+        assert!(parse_with(SliceJsonFeeder::new(json), &mut Limit(5)).is_ok());
+        assert!(matches!(
+            parse_with(SliceJsonFeeder::new(json), &mut Limit(2)),
+            Err(ParseWithError::Sink("limit reached"))
+        ));
+        assert!(matches!(
+            parse_with(SliceJsonFeeder::new(b"[1, 2"), &mut Limit(5)),
+            Err(ParseWithError::Parser(ParserError::NoMoreInput))
+        ));
+    }
+
+    /// Test that a tee forwards events to both sinks and reports which one
+    /// failed
+    #[test]
+    fn tee() {
+        let json = b"[1, 2, 3]";
+        let mut tee = Tee::new(Limit(5), Limit(10));
+        parse_with(SliceJsonFeeder::new(json), &mut tee).unwrap();
+        let (a, b) = tee.into_inner();
+        assert_eq!((a.0, b.0), (0, 5));
+
+        assert!(matches!(
+            parse_with(
+                SliceJsonFeeder::new(json),
+                &mut Tee::new(Limit(5), Limit(2))
+            ),
+            Err(ParseWithError::Sink(TeeError::Second("limit reached")))
+        ));
+        assert!(matches!(
+            parse_with(
+                SliceJsonFeeder::new(json),
+                &mut Tee::new(Limit(2), Limit(5))
+            ),
+            Err(ParseWithError::Sink(TeeError::First("limit reached")))
+        ));
+    }
+
+    /// Test that parsing stops when the callback breaks and that it can be
+    /// continued afterwards
+    #[test]
+    fn for_each_event() {
+        let json = br#"[1, {"a": 2}, 3]"#;
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+
+        let mut events = 0;
+        let r = parser.for_each_event(|e, _| {
+            events += 1;
+            if e == JsonEvent::StartObject {
+                ControlFlow::Break(events)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(r.unwrap(), Some(3));
+
+        let mut ints = Vec::new();
+        let r = parser.for_each_event(|e, p| {
+            if e == JsonEvent::ValueInt {
+                ints.push(p.current_int::<i32>().unwrap());
+            }
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(r.unwrap(), None);
+        assert_eq!(ints, vec![2, 3]);
+
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(b"[1}"));
+        assert!(matches!(
+            parser.for_each_event(|_, _| ControlFlow::<()>::Continue(())),
+            Err(ParseWithError::Parser(ParserError::SyntaxError))
+        ));
+    }
+}
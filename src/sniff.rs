@@ -0,0 +1,430 @@
+//! Detect the variant of JSON input from its first bytes.
+//!
+//! Ingestion services often do not know in advance whether a client sends
+//! a single JSON document, several concatenated documents, newline-delimited
+//! JSON (NDJSON), or a JSON text sequence as defined by
+//! [RFC 7464](https://www.rfc-editor.org/rfc/rfc7464) (records introduced by
+//! the ASCII record separator `0x1E`). [`sniff()`] inspects a prefix of the
+//! input and returns its [`Format`] and [`Encoding`]. [`sniff_parser()`]
+//! does this for the bytes a feeder has buffered and returns a parser that
+//! is configured accordingly: streaming mode is enabled for everything
+//! except single documents, a UTF-8 byte order mark is skipped, and record
+//! separators are treated like white space.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sniff::{sniff_parser, Format};
+//! use actson::JsonEvent;
+//!
+//! let json = b"{\"id\": 1}\n{\"id\": 2}\n";
+//! let (sniffed, mut parser) = sniff_parser(SliceJsonFeeder::new(json)).unwrap();
+//! assert_eq!(sniffed.format, Format::Ndjson);
+//!
+//! let mut ids = 0;
+//! while let Some(e) = parser.next_event().unwrap() {
+//!     if e == JsonEvent::ValueInt {
+//!         ids += 1;
+//!     }
+//! }
+//! assert_eq!(ids, 2);
+//! ```
+//!
+//! The format is derived from what follows the first value. If the first
+//! value does not end within the inspected bytes or if nothing but white
+//! space follows it, the input is assumed to be a single document, so make
+//! sure the feeder has buffered enough input (e.g. a few kilobytes). UTF-16
+//! and UTF-32 are detected (with or without a byte order mark) but cannot be
+//! parsed.
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{FillError, FillJsonFeeder, JsonFeeder, SliceJsonFeeder},
+    options::{JsonParserOptions, JsonParserOptionsBuilder},
+    JsonEvent, JsonParser,
+};
+
+/// The record separator that introduces each record of a JSON text sequence
+const RS: u8 = 0x1e;
+
+/// The way JSON values are arranged in the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A single JSON document
+    Single,
+
+    /// Several JSON values that follow each other on the same line (e.g.
+    /// `{"a":1}{"a":2}`)
+    Concatenated,
+
+    /// One JSON value per line
+    Ndjson,
+
+    /// A JSON text sequence as defined by RFC 7464
+    JsonSeq,
+}
+
+/// The character encoding of the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+/// The result of [`sniff()`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sniffed {
+    /// The detected arrangement of values
+    pub format: Format,
+
+    /// The detected character encoding
+    pub encoding: Encoding,
+
+    /// The length of the byte order mark at the beginning of the input or
+    /// 0 if there is none
+    pub bom_len: usize,
+}
+
+impl Sniffed {
+    /// Return parser options suitable for the detected format. Streaming
+    /// mode is enabled for all formats except [`Format::Single`].
+    pub fn options(&self) -> JsonParserOptions {
+        JsonParserOptionsBuilder::default()
+            .with_streaming(self.format != Format::Single)
+            .build()
+    }
+}
+
+/// An error that can happen when creating a parser for sniffed input
+#[derive(Error, Debug)]
+pub enum SniffError {
+    /// The input is not encoded in UTF-8
+    #[error("unsupported encoding: {0:?}")]
+    UnsupportedEncoding(Encoding),
+
+    /// The feeder could not be filled
+    #[error(transparent)]
+    Fill(#[from] FillError),
+}
+
+/// Detect the encoding of the input from its first four bytes as described
+/// in RFC 4627 and return it together with the length of its byte order
+/// mark
+fn detect_encoding(prefix: &[u8]) -> (Encoding, usize) {
+    match prefix {
+        [0xef, 0xbb, 0xbf, ..] => (Encoding::Utf8, 3),
+        [0x00, 0x00, 0xfe, 0xff, ..] => (Encoding::Utf32Be, 4),
+        [0xff, 0xfe, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+        [0xfe, 0xff, ..] => (Encoding::Utf16Be, 2),
+        [0xff, 0xfe, ..] => (Encoding::Utf16Le, 2),
+        // JSON texts start with an ASCII character, so the position of
+        // the zero bytes reveals the encoding
+        [0x00, 0x00, 0x00, _, ..] => (Encoding::Utf32Be, 0),
+        [_, 0x00, 0x00, 0x00, ..] => (Encoding::Utf32Le, 0),
+        [0x00, _, ..] => (Encoding::Utf16Be, 0),
+        [_, 0x00, ..] => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Return the position right after the first value in `json` or `None`
+/// if it does not end within `json`
+fn first_value_end(json: &[u8]) -> Option<usize> {
+    let mut parser = JsonParser::new_with_options(
+        SliceJsonFeeder::new(json),
+        JsonParserOptionsBuilder::default()
+            .with_streaming(true)
+            .with_validate_only(true)
+            .build(),
+    );
+    let mut depth = 0usize;
+    while let Ok(Some(e)) = parser.next_event() {
+        match e {
+            JsonEvent::NeedMoreInput => return None,
+            JsonEvent::StartObject | JsonEvent::StartArray => depth += 1,
+            JsonEvent::EndObject | JsonEvent::EndArray => depth -= 1,
+            JsonEvent::FieldName => continue,
+            _ => {}
+        }
+        if depth == 0 {
+            let end = parser.current_span().end;
+            // a number at the end of the prefix might continue
+            let is_number = matches!(e, JsonEvent::ValueInt | JsonEvent::ValueFloat);
+            return (!is_number || end < json.len()).then_some(end);
+        }
+    }
+    None
+}
+
+/// Detect the format and encoding of the input from its first bytes. See
+/// the [module documentation](self).
+///
+/// ```
+/// use actson::sniff::{sniff, Encoding, Format};
+///
+/// assert_eq!(sniff(br#"{"a": [1, 2]}"#).format, Format::Single);
+/// assert_eq!(sniff(b"{\"a\": 1}\n{\"a\": 2}").format, Format::Ndjson);
+/// assert_eq!(sniff(br#"{"a": 1} {"a": 2}"#).format, Format::Concatenated);
+/// assert_eq!(sniff(b"\x1e{\"a\": 1}\n").format, Format::JsonSeq);
+/// assert_eq!(sniff(b"[\x001\x00]\x00").encoding, Encoding::Utf16Le);
+/// ```
+pub fn sniff(prefix: &[u8]) -> Sniffed {
+    let (encoding, bom_len) = detect_encoding(prefix);
+    let mut sniffed = Sniffed {
+        format: Format::Single,
+        encoding,
+        bom_len,
+    };
+    if encoding != Encoding::Utf8 {
+        return sniffed;
+    }
+
+    let json = &prefix[bom_len..];
+    let start = json
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(json.len());
+    if json.get(start) == Some(&RS) {
+        sniffed.format = Format::JsonSeq;
+        return sniffed;
+    }
+
+    if let Some(end) = first_value_end(json) {
+        let rest = &json[end..];
+        let gap = rest
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        if gap < rest.len() {
+            sniffed.format = if rest[..gap].contains(&b'\n') {
+                Format::Ndjson
+            } else {
+                Format::Concatenated
+            };
+        }
+    }
+    sniffed
+}
+
+/// A [`JsonFeeder`] that adapts the input described by a [`Sniffed`] for
+/// the parser. It skips the byte order mark and, for JSON text sequences,
+/// turns record separators into line feeds. Created by
+/// [`sniff_parser()`].
+pub struct SniffedFeeder<T> {
+    inner: T,
+    bom_len: usize,
+    json_seq: bool,
+}
+
+impl<T> SniffedFeeder<T>
+where
+    T: JsonFeeder,
+{
+    /// Wrap a feeder whose input has been sniffed. The feeder must not
+    /// have consumed any input yet.
+    pub fn new(mut inner: T, sniffed: &Sniffed) -> Result<Self, SniffError> {
+        if sniffed.encoding != Encoding::Utf8 {
+            return Err(SniffError::UnsupportedEncoding(sniffed.encoding));
+        }
+        for _ in 0..sniffed.bom_len {
+            inner.next_input();
+        }
+        Ok(SniffedFeeder {
+            inner,
+            bom_len: sniffed.bom_len,
+            json_seq: sniffed.format == Format::JsonSeq,
+        })
+    }
+
+    /// Consume this feeder and return the wrapped one
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> JsonFeeder for SniffedFeeder<T>
+where
+    T: JsonFeeder,
+{
+    fn has_input(&self) -> bool {
+        self.inner.has_input()
+    }
+
+    fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        match self.inner.next_input() {
+            Some(RS) if self.json_seq => Some(b'\n'),
+            b => b,
+        }
+    }
+
+    fn consumed_input(&self) -> Option<&[u8]> {
+        // positions reported by the parser do not include the byte order
+        // mark
+        self.inner.consumed_input()?.get(self.bom_len..)
+    }
+
+    fn consumed_str(&self) -> Option<&str> {
+        self.inner.consumed_str()?.get(self.bom_len..)
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        let input = self.inner.peek_input();
+        if !self.json_seq {
+            return input;
+        }
+        // record separators are returned one by one by next_input()
+        match memchr::memchr(RS, input) {
+            Some(i) => &input[..i],
+            None => input,
+        }
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.inner.consume_input(n)
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        self.inner.rewind_input(n)
+    }
+}
+
+impl<T> FillJsonFeeder for SniffedFeeder<T>
+where
+    T: FillJsonFeeder,
+{
+    fn fill(&mut self) -> Result<(), FillError> {
+        self.inner.fill()
+    }
+}
+
+/// Fill the given feeder if it has no input yet, sniff the bytes it has
+/// buffered (see [`JsonFeeder::peek_input()`]), and return the result
+/// together with a parser configured for the detected format. See the
+/// [module documentation](self).
+pub fn sniff_parser<T>(mut feeder: T) -> Result<(Sniffed, JsonParser<SniffedFeeder<T>>), SniffError>
+where
+    T: FillJsonFeeder,
+{
+    if !feeder.has_input() {
+        feeder.fill()?;
+    }
+    let sniffed = sniff(feeder.peek_input());
+    let feeder = SniffedFeeder::new(feeder, &sniffed)?;
+    Ok((
+        sniffed,
+        JsonParser::new_with_options(feeder, sniffed.options()),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::BufReader;
+
+    use crate::{
+        feeder::{BufReaderJsonFeeder, FillJsonFeeder, SliceJsonFeeder, StrJsonFeeder},
+        JsonEvent, OwnedJsonEvent, Span,
+    };
+
+    use super::{sniff, sniff_parser, Encoding, Format, SniffError};
+
+    /// Test that the format is detected from what follows the first value
+    #[test]
+    fn formats() {
+        for (json, format) in [
+            (&b""[..], Format::Single),
+            (b"  [1, {\"a\": 2}]  \n", Format::Single),
+            (b"12", Format::Single),
+            (b"12 13", Format::Concatenated),
+            (b"{}{}", Format::Concatenated),
+            (b"\"a\" \"b\"", Format::Concatenated),
+            (b"[1]\n[2]\n", Format::Ndjson),
+            (b"1\r\n2", Format::Ndjson),
+            (b"{\"a\": [1, 2, 3", Format::Single),
+            (b"[1]\n\n  ", Format::Single),
+            (b"{\"a\": x} {}", Format::Single),
+            (b"\n\x1e[1]\n\x1e[2]\n", Format::JsonSeq),
+            (b"\xef\xbb\xbf[1]\n[2]", Format::Ndjson),
+        ] {
+            assert_eq!(sniff(json).format, format, "{:?}", json);
+        }
+    }
+
+    /// Test that encodings and byte order marks are detected
+    #[test]
+    fn encodings() {
+        for (json, encoding, bom_len) in [
+            (&b"[1]"[..], Encoding::Utf8, 0),
+            (b"\xef\xbb\xbf[1]", Encoding::Utf8, 3),
+            (b"\xfe\xff\x00[", Encoding::Utf16Be, 2),
+            (b"\xff\xfe[\x00", Encoding::Utf16Le, 2),
+            (b"\x00\x00\xfe\xff", Encoding::Utf32Be, 4),
+            (b"\xff\xfe\x00\x00", Encoding::Utf32Le, 4),
+            (b"\x00[\x001", Encoding::Utf16Be, 0),
+            (b"[\x001\x00", Encoding::Utf16Le, 0),
+            (b"\x00\x00\x00[", Encoding::Utf32Be, 0),
+            (b"[\x00\x00\x00", Encoding::Utf32Le, 0),
+            (b"1", Encoding::Utf8, 0),
+        ] {
+            let sniffed = sniff(json);
+            assert_eq!((sniffed.encoding, sniffed.bom_len), (encoding, bom_len));
+        }
+        assert!(matches!(
+            sniff_parser(SliceJsonFeeder::new(b"\xff\xfe[\x00]\x00")),
+            Err(SniffError::UnsupportedEncoding(Encoding::Utf16Le))
+        ));
+    }
+
+    fn events(json: &[u8]) -> Vec<OwnedJsonEvent> {
+        let (_, mut parser) = sniff_parser(BufReaderJsonFeeder::new(BufReader::new(json))).unwrap();
+        let mut events = Vec::new();
+        while let Some(e) = parser.next_owned_event().unwrap() {
+            match e {
+                OwnedJsonEvent::NeedMoreInput => parser.feeder.fill().unwrap(),
+                e => events.push(e),
+            }
+        }
+        events
+    }
+
+    /// Test that the returned parser handles all formats
+    #[test]
+    fn parse() {
+        let expected = vec![
+            OwnedJsonEvent::StartArray,
+            OwnedJsonEvent::ValueInt(1),
+            OwnedJsonEvent::EndArray,
+            OwnedJsonEvent::ValueString("a".to_string()),
+        ];
+        assert_eq!(events(b"[1] \"a\""), expected);
+        assert_eq!(events(b"[1]\n\"a\"\n"), expected);
+        assert_eq!(events(b"\x1e[1]\n\x1e\"a\"\n"), expected);
+        assert_eq!(events(b"\x1e[1]\x1e\"a\""), expected);
+        assert_eq!(events(b"\xef\xbb\xbf[1]\n\"a\""), expected);
+        assert_eq!(events(b"\xef\xbb\xbf\"a\""), expected[3..]);
+
+        // a single document must not be followed by other values
+        let (_, mut parser) = sniff_parser(SliceJsonFeeder::new(b"[1] x")).unwrap();
+        let mut result = parser.next_event();
+        while let Ok(Some(_)) = result {
+            result = parser.next_event();
+        }
+        assert!(result.is_err());
+
+        // the byte order mark is not part of the parsed text
+        let json = "\u{feff}{\"name\": \"Elvis\"}";
+        let (sniffed, mut parser) = sniff_parser(StrJsonFeeder::new(json)).unwrap();
+        assert_eq!(sniffed.bom_len, 3);
+        assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+        assert_eq!(parser.current_span(), Span { start: 0, end: 1 });
+        assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+        assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueString));
+        assert_eq!(parser.current_str().unwrap(), "Elvis");
+    }
+}
@@ -0,0 +1,254 @@
+//! Split a top-level array into the raw bytes of its elements.
+//!
+//! [`ArraySplitter`] scans a JSON text that consists of a single array and
+//! returns each element as a slice of the input. Only brackets, braces,
+//! commas, and strings are tracked to find the boundaries between elements.
+//! The elements themselves are not parsed or validated, which makes
+//! splitting much faster than parsing. The slices can then be dispatched to
+//! worker threads, each of which parses its elements with its own
+//! [`JsonParser`](crate::JsonParser).
+//!
+//! ```
+//! use actson::split::ArraySplitter;
+//! use actson::value::JsonValue;
+//! use std::thread;
+//!
+//! let json = br#"[{"id": 1, "tags": ["a", "]"]}, {"id": 2}, 3]"#;
+//! let elements = ArraySplitter::new(json)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(elements[1], br#"{"id": 2}"#);
+//!
+//! let values = thread::scope(|s| {
+//!     let handles = elements
+//!         .iter()
+//!         .map(|e| s.spawn(|| JsonValue::parse(e).unwrap()))
+//!         .collect::<Vec<_>>();
+//!     handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+//! });
+//! assert_eq!(values[2], JsonValue::Int(3));
+//! ```
+
+use thiserror::Error;
+
+use crate::scan::{string_run_len, whitespace_run_len};
+
+/// An error that can happen when splitting an array
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitError {
+    /// The JSON text does not start with an array
+    #[error("expected `[' at offset {0}")]
+    ExpectedArray(usize),
+
+    /// The JSON text contains a byte that is not allowed at this position
+    /// (e.g. a comma without an element in front of it or data after the
+    /// end of the array)
+    #[error("unexpected byte at offset {0}")]
+    UnexpectedByte(usize),
+
+    /// The JSON text ended before the end of the array
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+}
+
+/// The position of the splitter in the JSON text
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the opening bracket
+    Start,
+
+    /// Before the first element
+    First,
+
+    /// After an element
+    Next,
+
+    /// After the closing bracket or an error
+    Done,
+}
+
+/// An iterator over the raw bytes of the elements of a top-level array. See
+/// the [module documentation](self). After an error, the iterator ends.
+pub struct ArraySplitter<'a> {
+    input: &'a [u8],
+    pos: usize,
+    state: State,
+}
+
+impl<'a> ArraySplitter<'a> {
+    /// Create a splitter for the given JSON text
+    pub fn new(input: &'a [u8]) -> Self {
+        ArraySplitter {
+            input,
+            pos: 0,
+            state: State::Start,
+        }
+    }
+
+    /// Returns the byte offset in the JSON text up to which it has been
+    /// scanned. After an element has been returned, this is the offset
+    /// right after its end.
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos += whitespace_run_len(&self.input[self.pos..]);
+    }
+
+    /// Find the end of the element starting at the current position
+    fn scan_element(&mut self) -> Result<&'a [u8], SplitError> {
+        let start = self.pos;
+        let mut end = start;
+        let mut depth = 0usize;
+        loop {
+            let Some(&b) = self.input.get(self.pos) else {
+                return Err(SplitError::UnexpectedEnd);
+            };
+            // the element is complete but is followed by whitespace and
+            // something other than a delimiter (e.g. `[1 2]`)
+            let delimiter = matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b',' | b']' | b'}');
+            if depth == 0 && start < end && end < self.pos && !delimiter {
+                return Err(SplitError::UnexpectedByte(self.pos));
+            }
+            match b {
+                b'"' => {
+                    self.skip_string()?;
+                    end = self.pos;
+                    continue;
+                }
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' if depth > 0 => depth -= 1,
+                b',' | b']' | b'}' if depth == 0 => break,
+                b' ' | b'\t' | b'\n' | b'\r' => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            self.pos += 1;
+            end = self.pos;
+        }
+        if start == end {
+            return Err(SplitError::UnexpectedByte(self.pos));
+        }
+        Ok(&self.input[start..end])
+    }
+
+    /// Skip the string starting at the current position
+    fn skip_string(&mut self) -> Result<(), SplitError> {
+        self.pos += 1;
+        loop {
+            self.pos += string_run_len(&self.input[self.pos..]);
+            match self.input.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b'\\') => self.pos += 2,
+                Some(_) => self.pos += 1,
+                None => return Err(SplitError::UnexpectedEnd),
+            }
+        }
+    }
+
+    fn next_element(&mut self) -> Result<Option<&'a [u8]>, SplitError> {
+        self.skip_whitespace();
+        match self.state {
+            State::Start => {
+                if self.input.get(self.pos) != Some(&b'[') {
+                    return Err(SplitError::ExpectedArray(self.pos));
+                }
+                self.pos += 1;
+                self.state = State::First;
+                self.skip_whitespace();
+                if self.input.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return self.end();
+                }
+            }
+            State::First => {}
+            State::Next => match self.input.get(self.pos) {
+                Some(b',') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                }
+                Some(b']') => {
+                    self.pos += 1;
+                    return self.end();
+                }
+                Some(_) => return Err(SplitError::UnexpectedByte(self.pos)),
+                None => return Err(SplitError::UnexpectedEnd),
+            },
+            State::Done => return Ok(None),
+        }
+        let element = self.scan_element()?;
+        self.state = State::Next;
+        Ok(Some(element))
+    }
+
+    /// Check that there is only whitespace after the end of the array
+    fn end(&mut self) -> Result<Option<&'a [u8]>, SplitError> {
+        self.state = State::Done;
+        self.skip_whitespace();
+        if self.pos < self.input.len() {
+            return Err(SplitError::UnexpectedByte(self.pos));
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for ArraySplitter<'a> {
+    type Item = Result<&'a [u8], SplitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = self.next_element();
+        if r.is_err() {
+            self.state = State::Done;
+        }
+        r.transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArraySplitter, SplitError};
+
+    fn split(json: &[u8]) -> Result<Vec<&[u8]>, SplitError> {
+        ArraySplitter::new(json).collect()
+    }
+
+    /// Test that elements of all types are split correctly
+    #[test]
+    fn elements() {
+        let json = br#" [ 1 ,"a,]\"}" , [[], {"b": [1, 2]}],{"c": "\\"},true,null , -2.5e3 ] "#;
+        assert_eq!(
+            split(json).unwrap(),
+            vec![
+                &b"1"[..],
+                br#""a,]\"}""#,
+                br#"[[], {"b": [1, 2]}]"#,
+                br#"{"c": "\\"}"#,
+                b"true",
+                b"null",
+                b"-2.5e3",
+            ]
+        );
+        assert_eq!(split(b"[]").unwrap(), Vec::<&[u8]>::new());
+        assert_eq!(split(b" [ ] ").unwrap(), Vec::<&[u8]>::new());
+    }
+
+    /// Test that texts that are not arrays or whose element boundaries are
+    /// invalid are rejected
+    #[test]
+    fn invalid() {
+        assert_eq!(split(b"{}"), Err(SplitError::ExpectedArray(0)));
+        assert_eq!(split(b""), Err(SplitError::ExpectedArray(0)));
+        assert_eq!(split(b"[1,,2]"), Err(SplitError::UnexpectedByte(3)));
+        assert_eq!(split(b"[1,]"), Err(SplitError::UnexpectedByte(3)));
+        assert_eq!(split(b"[1 2]"), Err(SplitError::UnexpectedByte(3)));
+        assert_eq!(split(b"[1] x"), Err(SplitError::UnexpectedByte(4)));
+        assert_eq!(split(b"[1, \"a"), Err(SplitError::UnexpectedEnd));
+        assert_eq!(split(b"[1, {"), Err(SplitError::UnexpectedEnd));
+    }
+}
@@ -0,0 +1,238 @@
+//! Summarize the structure of a JSON text in one pass.
+//!
+//! [`StatsCollector`] is a [`JsonEventSink`] that counts values by type,
+//! field names by frequency, and records the maximum nesting depth, string
+//! length, and container sizes. This is useful for profiling unknown
+//! datasets before writing code to extract data from them. Only the
+//! distinct field names are kept in memory.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sink::parse_with;
+//! use actson::stats::StatsCollector;
+//!
+//! let json = br#"[{"id": 1, "tags": ["a", "bc"]}, {"id": 2.5, "tags": []}, null]"#;
+//! let mut collector = StatsCollector::new();
+//! parse_with(SliceJsonFeeder::new(json), &mut collector).unwrap();
+//!
+//! let stats = collector.into_stats();
+//! assert_eq!(stats.types.objects, 2);
+//! assert_eq!(stats.types.arrays, 3);
+//! assert_eq!(stats.types.integers, 1);
+//! assert_eq!(stats.types.floats, 1);
+//! assert_eq!(stats.types.nulls, 1);
+//! assert_eq!(stats.field_names["tags"], 2);
+//! assert_eq!(stats.max_depth, 3);
+//! assert_eq!(stats.max_string_len, 2);
+//! assert_eq!(stats.max_array_len, 3);
+//! assert_eq!(stats.array_elements, 5);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    feeder::JsonFeeder, parser::InvalidStringValueError, sink::JsonEventSink,
+    storage::ParserStorage, JsonEvent, JsonParser,
+};
+
+/// The number of values of each type
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TypeCounts {
+    /// The number of objects
+    pub objects: usize,
+
+    /// The number of arrays
+    pub arrays: usize,
+
+    /// The number of strings (not counting field names)
+    pub strings: usize,
+
+    /// The number of integers
+    pub integers: usize,
+
+    /// The number of floats
+    pub floats: usize,
+
+    /// The number of `true` and `false` values
+    pub booleans: usize,
+
+    /// The number of `null` values
+    pub nulls: usize,
+}
+
+impl TypeCounts {
+    /// Returns the total number of values
+    pub fn total(&self) -> usize {
+        self.objects
+            + self.arrays
+            + self.strings
+            + self.integers
+            + self.floats
+            + self.booleans
+            + self.nulls
+    }
+}
+
+/// A summary of the structure of a JSON text. See the [module
+/// documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+    /// The number of values of each type
+    pub types: TypeCounts,
+
+    /// How often each field name occurs
+    pub field_names: HashMap<String, usize>,
+
+    /// The maximum nesting depth of objects and arrays (0 if the JSON text
+    /// only consists of scalars)
+    pub max_depth: usize,
+
+    /// The maximum length of a string value in bytes after unescaping
+    pub max_string_len: usize,
+
+    /// The maximum number of elements in an array
+    pub max_array_len: usize,
+
+    /// The maximum number of members in an object
+    pub max_object_len: usize,
+
+    /// The total number of elements in all arrays
+    pub array_elements: usize,
+
+    /// The total number of members in all objects
+    pub object_members: usize,
+}
+
+/// Collects [`DocumentStats`] from the events of a parser. See the [module
+/// documentation](self).
+#[derive(Default)]
+pub struct StatsCollector {
+    stats: DocumentStats,
+
+    /// Each open container: `true` if it is an array, and the number of its
+    /// entries so far
+    stack: Vec<(bool, usize)>,
+}
+
+impl StatsCollector {
+    /// Create a new collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the statistics collected so far
+    pub fn stats(&self) -> &DocumentStats {
+        &self.stats
+    }
+
+    /// Consume the collector and return the statistics
+    pub fn into_stats(self) -> DocumentStats {
+        self.stats
+    }
+}
+
+impl JsonEventSink for StatsCollector {
+    type Error = InvalidStringValueError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        let stats = &mut self.stats;
+        match event {
+            JsonEvent::NeedMoreInput => return Ok(()),
+
+            JsonEvent::FieldName => {
+                let name = parser.current_str()?;
+                match stats.field_names.get_mut(name) {
+                    Some(n) => *n += 1,
+                    None => {
+                        stats.field_names.insert(name.to_string(), 1);
+                    }
+                }
+                if let Some((_, n)) = self.stack.last_mut() {
+                    *n += 1;
+                }
+                stats.object_members += 1;
+                return Ok(());
+            }
+
+            JsonEvent::EndObject => {
+                let (_, n) = self.stack.pop().unwrap_or_default();
+                stats.max_object_len = stats.max_object_len.max(n);
+                return Ok(());
+            }
+
+            JsonEvent::EndArray => {
+                let (_, n) = self.stack.pop().unwrap_or_default();
+                stats.max_array_len = stats.max_array_len.max(n);
+                stats.array_elements += n;
+                return Ok(());
+            }
+
+            JsonEvent::StartObject => stats.types.objects += 1,
+            JsonEvent::StartArray => stats.types.arrays += 1,
+            JsonEvent::ValueString => {
+                stats.types.strings += 1;
+                stats.max_string_len = stats.max_string_len.max(parser.current_str()?.len());
+            }
+            JsonEvent::ValueInt => stats.types.integers += 1,
+            JsonEvent::ValueFloat => stats.types.floats += 1,
+            JsonEvent::ValueTrue | JsonEvent::ValueFalse => stats.types.booleans += 1,
+            JsonEvent::ValueNull => stats.types.nulls += 1,
+        }
+
+        // a value starts: count it as an element if the parent is an array
+        // (members of objects have already been counted by their names)
+        if let Some((true, n)) = self.stack.last_mut() {
+            *n += 1;
+        }
+        if event.is_container_start() {
+            self.stack.push((event == JsonEvent::StartArray, 0));
+            stats.max_depth = stats.max_depth.max(self.stack.len());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        feeder::SliceJsonFeeder, options::JsonParserOptionsBuilder, sink::parse_with_parser,
+        JsonParser,
+    };
+
+    use super::StatsCollector;
+
+    /// Test that object sizes are recorded and that multiple top-level
+    /// values are summarized together
+    #[test]
+    fn streaming() {
+        let json = br#"{"a": {}, "b": {"a": "\u00fc"}} 1 true {"c": [[]]}"#;
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut collector = StatsCollector::new();
+        parse_with_parser(&mut parser, &mut collector).unwrap();
+
+        let stats = collector.stats();
+        assert_eq!(stats.types.total(), 9);
+        assert_eq!(stats.types.booleans, 1);
+        assert_eq!(stats.field_names.len(), 3);
+        assert_eq!(stats.field_names["a"], 2);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.max_string_len, 2);
+        assert_eq!(stats.max_object_len, 2);
+        assert_eq!(stats.object_members, 4);
+        assert_eq!(stats.max_array_len, 1);
+        assert_eq!(stats.array_elements, 1);
+    }
+}
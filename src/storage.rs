@@ -0,0 +1,552 @@
+//! Storage for the [`JsonParser`](crate::JsonParser)'s internal state.
+//!
+//! By default, the parser uses [`HeapStorage`], which grows as needed. For
+//! targets that must not allocate memory, [`FixedStorage`] keeps the stack
+//! in a fixed-size array and collects values in a buffer provided by the
+//! caller. [`ScratchStorage`] does the same but collects values in a
+//! caller-owned [`Vec`] that grows as needed and keeps its memory between
+//! documents.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::options::JsonParserOptions;
+//! use actson::parser::ParserError;
+//! use actson::storage::FixedStorage;
+//! use actson::{JsonEvent, JsonParser};
+//!
+//! let json = r#"{"name": "Elvis", "friends": [["Max"]]}"#.as_bytes();
+//!
+//! let mut buf = [0u8; 16];
+//! let mut parser = JsonParser::new_with_storage(
+//!     SliceJsonFeeder::new(json),
+//!     FixedStorage::<3>::new(&mut buf),
+//!     JsonParserOptions::default(),
+//! );
+//!
+//! assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+//! assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+//! assert_eq!(parser.current_str().unwrap(), "name");
+//! assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueString));
+//! assert_eq!(parser.current_str().unwrap(), "Elvis");
+//! assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+//! assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+//!
+//! // the stack can only hold three modes
+//! assert!(matches!(parser.next_event(), Err(ParserError::MaxDepthExceeded)));
+//! ```
+//!
+//! Servers that create a short-lived parser per request can share a
+//! [`StoragePool`], so the parsers reuse each other's buffers instead of
+//! allocating new ones.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Storage for the parser's stack and for the contents of the string or
+/// number that is currently being parsed. This trait is sealed and cannot be
+/// implemented outside of this crate. Use [`HeapStorage`] or
+/// [`FixedStorage`].
+pub trait ParserStorage: private::Sealed {
+    /// `true` if escape sequences should be decoded in place as soon as a
+    /// string has been parsed completely instead of lazily into a separate
+    /// buffer
+    #[doc(hidden)]
+    const UNESCAPE_IN_PLACE: bool;
+
+    /// Return the number of modes on the stack
+    #[doc(hidden)]
+    fn stack_len(&self) -> usize;
+
+    /// Push a mode to the stack. Return `false` if the stack is full.
+    #[doc(hidden)]
+    fn stack_push(&mut self, mode: i8) -> bool;
+
+    /// Return the topmost mode on the stack
+    #[doc(hidden)]
+    fn stack_last(&self) -> Option<i8>;
+
+    /// Remove the topmost mode from the stack
+    #[doc(hidden)]
+    fn stack_pop(&mut self);
+
+    /// Return a copy of all modes on the stack from the bottom to the top
+    #[doc(hidden)]
+    fn stack_to_vec(&self) -> Vec<i8>;
+
+    /// Return the contents of the value buffer
+    #[doc(hidden)]
+    fn buffer(&self) -> &[u8];
+
+    /// Return the contents of the value buffer
+    #[doc(hidden)]
+    fn buffer_mut(&mut self) -> &mut [u8];
+
+    /// Append bytes to the value buffer. Return `false` if the buffer is full.
+    #[doc(hidden)]
+    fn buffer_extend(&mut self, bytes: &[u8]) -> bool;
+
+    /// Shorten the value buffer to the given length
+    #[doc(hidden)]
+    fn buffer_truncate(&mut self, len: usize);
+
+    /// Release memory of the stack and the value buffer beyond their
+    /// current length or the given capacity, whichever is larger
+    #[doc(hidden)]
+    fn shrink_to(&mut self, min_capacity: usize);
+}
+
+/// The default [`ParserStorage`]. The stack and the value buffer are
+/// allocated on the heap and grow as needed.
+#[derive(Debug, Default)]
+pub struct HeapStorage {
+    stack: VecDeque<i8>,
+    buffer: Vec<u8>,
+}
+
+impl HeapStorage {
+    /// Create a new storage whose value buffer can hold at least the given
+    /// number of bytes without reallocating. Use this if you know the
+    /// typical length of the strings and numbers in your JSON texts.
+    ///
+    /// ```
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::options::JsonParserOptions;
+    /// use actson::storage::HeapStorage;
+    /// use actson::JsonParser;
+    ///
+    /// let json = r#"{"name": "Elvis"}"#.as_bytes();
+    /// let mut parser = JsonParser::new_with_storage(
+    ///     SliceJsonFeeder::new(json),
+    ///     HeapStorage::with_capacity(4096),
+    ///     JsonParserOptions::default(),
+    /// );
+    /// while parser.next_event().unwrap().is_some() {}
+    /// ```
+    pub fn with_capacity(buffer_capacity: usize) -> Self {
+        HeapStorage {
+            stack: VecDeque::new(),
+            buffer: Vec::with_capacity(buffer_capacity),
+        }
+    }
+}
+
+impl private::Sealed for HeapStorage {}
+
+impl ParserStorage for HeapStorage {
+    const UNESCAPE_IN_PLACE: bool = false;
+
+    #[inline]
+    fn stack_len(&self) -> usize {
+        self.stack.len()
+    }
+
+    #[inline]
+    fn stack_push(&mut self, mode: i8) -> bool {
+        self.stack.push_back(mode);
+        true
+    }
+
+    #[inline]
+    fn stack_last(&self) -> Option<i8> {
+        self.stack.back().copied()
+    }
+
+    #[inline]
+    fn stack_pop(&mut self) {
+        self.stack.pop_back();
+    }
+
+    fn stack_to_vec(&self) -> Vec<i8> {
+        self.stack.iter().copied().collect()
+    }
+
+    #[inline]
+    fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    #[inline]
+    fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+
+    #[inline]
+    fn buffer_extend(&mut self, bytes: &[u8]) -> bool {
+        self.buffer.extend_from_slice(bytes);
+        true
+    }
+
+    #[inline]
+    fn buffer_truncate(&mut self, len: usize) {
+        self.buffer.truncate(len);
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.stack.shrink_to(min_capacity);
+        self.buffer.shrink_to(min_capacity);
+    }
+}
+
+/// A [`ParserStorage`] that does not allocate memory. The stack can hold at
+/// most `MAX_DEPTH` modes (including the one for the top level) and values
+/// are collected in a buffer provided by the caller. The parser returns
+/// [`ParserError::MaxDepthExceeded`](crate::parser::ParserError::MaxDepthExceeded)
+/// or [`ParserError::ValueBufferFull`](crate::parser::ParserError::ValueBufferFull)
+/// if either of them is exceeded.
+///
+/// Escape sequences in strings are decoded in place as soon as a string has
+/// been parsed, so the buffer must be large enough to hold the escaped form
+/// of the longest string or number in the JSON text.
+#[derive(Debug)]
+pub struct FixedStorage<'b, const MAX_DEPTH: usize> {
+    stack: [i8; MAX_DEPTH],
+    stack_len: usize,
+    buffer: &'b mut [u8],
+    buffer_len: usize,
+}
+
+impl<'b, const MAX_DEPTH: usize> FixedStorage<'b, MAX_DEPTH> {
+    /// Create a new storage that collects values in the given buffer
+    pub fn new(buffer: &'b mut [u8]) -> Self {
+        FixedStorage {
+            stack: [0; MAX_DEPTH],
+            stack_len: 0,
+            buffer,
+            buffer_len: 0,
+        }
+    }
+}
+
+impl<const MAX_DEPTH: usize> private::Sealed for FixedStorage<'_, MAX_DEPTH> {}
+
+impl<const MAX_DEPTH: usize> ParserStorage for FixedStorage<'_, MAX_DEPTH> {
+    const UNESCAPE_IN_PLACE: bool = true;
+
+    #[inline]
+    fn stack_len(&self) -> usize {
+        self.stack_len
+    }
+
+    #[inline]
+    fn stack_push(&mut self, mode: i8) -> bool {
+        if self.stack_len == MAX_DEPTH {
+            return false;
+        }
+        self.stack[self.stack_len] = mode;
+        self.stack_len += 1;
+        true
+    }
+
+    #[inline]
+    fn stack_last(&self) -> Option<i8> {
+        self.stack_len.checked_sub(1).map(|i| self.stack[i])
+    }
+
+    #[inline]
+    fn stack_pop(&mut self) {
+        self.stack_len = self.stack_len.saturating_sub(1);
+    }
+
+    fn stack_to_vec(&self) -> Vec<i8> {
+        self.stack[..self.stack_len].to_vec()
+    }
+
+    #[inline]
+    fn buffer(&self) -> &[u8] {
+        &self.buffer[..self.buffer_len]
+    }
+
+    #[inline]
+    fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer[..self.buffer_len]
+    }
+
+    #[inline]
+    fn buffer_extend(&mut self, bytes: &[u8]) -> bool {
+        let end = self.buffer_len + bytes.len();
+        if end > self.buffer.len() {
+            return false;
+        }
+        self.buffer[self.buffer_len..end].copy_from_slice(bytes);
+        self.buffer_len = end;
+        true
+    }
+
+    #[inline]
+    fn buffer_truncate(&mut self, len: usize) {
+        self.buffer_len = self.buffer_len.min(len);
+    }
+
+    fn shrink_to(&mut self, _min_capacity: usize) {
+        // the memory is owned by the caller
+    }
+}
+
+/// A [`ParserStorage`] that keeps the stack in a fixed-size array like
+/// [`FixedStorage`] but collects values in a [`Vec`] owned by the caller.
+/// The vector is cleared when the storage is created and grows as needed,
+/// and it keeps its capacity after the parser has been dropped. If the same
+/// vector is used for every document, parsing does not allocate memory once
+/// the vector has grown to the length of the longest string or number
+/// (unless options such as
+/// [`with_reject_duplicate_keys()`](crate::options::JsonParserOptionsBuilder::with_reject_duplicate_keys())
+/// are enabled).
+///
+/// Like with [`FixedStorage`], escape sequences are decoded in place.
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::options::JsonParserOptions;
+/// use actson::storage::ScratchStorage;
+/// use actson::{JsonEvent, JsonParser};
+///
+/// let mut scratch = Vec::with_capacity(64);
+/// for json in [r#"{"name": "Elvis"}"#, r#"{"name": "Max"}"#] {
+///     let mut parser = JsonParser::new_with_storage(
+///         SliceJsonFeeder::new(json.as_bytes()),
+///         ScratchStorage::<16>::new(&mut scratch),
+///         JsonParserOptions::default(),
+///     );
+///     while let Some(e) = parser.next_event().unwrap() {
+///         if e == JsonEvent::ValueString {
+///             assert!(!parser.current_str().unwrap().is_empty());
+///         }
+///     }
+/// }
+/// assert_eq!(scratch.capacity(), 64);
+/// ```
+#[derive(Debug)]
+pub struct ScratchStorage<'b, const MAX_DEPTH: usize> {
+    stack: [i8; MAX_DEPTH],
+    stack_len: usize,
+    buffer: &'b mut Vec<u8>,
+}
+
+impl<'b, const MAX_DEPTH: usize> ScratchStorage<'b, MAX_DEPTH> {
+    /// Create a new storage that collects values in the given vector. The
+    /// vector is cleared but keeps its capacity.
+    pub fn new(buffer: &'b mut Vec<u8>) -> Self {
+        buffer.clear();
+        ScratchStorage {
+            stack: [0; MAX_DEPTH],
+            stack_len: 0,
+            buffer,
+        }
+    }
+}
+
+impl<const MAX_DEPTH: usize> private::Sealed for ScratchStorage<'_, MAX_DEPTH> {}
+
+impl<const MAX_DEPTH: usize> ParserStorage for ScratchStorage<'_, MAX_DEPTH> {
+    const UNESCAPE_IN_PLACE: bool = true;
+
+    #[inline]
+    fn stack_len(&self) -> usize {
+        self.stack_len
+    }
+
+    #[inline]
+    fn stack_push(&mut self, mode: i8) -> bool {
+        if self.stack_len == MAX_DEPTH {
+            return false;
+        }
+        self.stack[self.stack_len] = mode;
+        self.stack_len += 1;
+        true
+    }
+
+    #[inline]
+    fn stack_last(&self) -> Option<i8> {
+        self.stack_len.checked_sub(1).map(|i| self.stack[i])
+    }
+
+    #[inline]
+    fn stack_pop(&mut self) {
+        self.stack_len = self.stack_len.saturating_sub(1);
+    }
+
+    fn stack_to_vec(&self) -> Vec<i8> {
+        self.stack[..self.stack_len].to_vec()
+    }
+
+    #[inline]
+    fn buffer(&self) -> &[u8] {
+        self.buffer
+    }
+
+    #[inline]
+    fn buffer_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    #[inline]
+    fn buffer_extend(&mut self, bytes: &[u8]) -> bool {
+        self.buffer.extend_from_slice(bytes);
+        true
+    }
+
+    #[inline]
+    fn buffer_truncate(&mut self, len: usize) {
+        self.buffer.truncate(len);
+    }
+
+    fn shrink_to(&mut self, _min_capacity: usize) {
+        // the memory is owned by the caller, who wants to reuse it
+    }
+}
+
+impl HeapStorage {
+    /// Remove all contents but keep the allocated memory
+    fn clear(&mut self) {
+        self.stack.clear();
+        self.buffer.clear();
+    }
+}
+
+/// The default maximum number of idle storages kept by a [`StoragePool`]
+pub const DEFAULT_MAX_IDLE: usize = 64;
+
+/// A pool of [`HeapStorage`]s shared by parsers. Parsers created with
+/// [`JsonParser::new_in()`](crate::JsonParser::new_in()) take a storage
+/// from the pool (or allocate a new one if the pool is empty) and return it
+/// when they are dropped, so the memory of their stack and value buffer is
+/// reused by the next parser. The pool can be cloned cheaply and shared
+/// between threads.
+///
+/// ```
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::storage::StoragePool;
+/// use actson::JsonParser;
+///
+/// let pool = StoragePool::default();
+/// for json in [r#"{"a": "x"}"#, r#"["y", 1]"#] {
+///     let mut parser = JsonParser::new_in(SliceJsonFeeder::new(json.as_bytes()), &pool);
+///     while parser.next_event().unwrap().is_some() {}
+/// }
+/// assert_eq!(pool.idle(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StoragePool {
+    idle: Arc<Mutex<Vec<HeapStorage>>>,
+    max_idle: usize,
+}
+
+impl StoragePool {
+    /// Create a pool that keeps at most `max_idle` unused storages.
+    /// Storages returned to a full pool are dropped.
+    pub fn new(max_idle: usize) -> Self {
+        StoragePool {
+            idle: Arc::new(Mutex::new(Vec::new())),
+            max_idle,
+        }
+    }
+
+    /// Take a storage from the pool or allocate a new one if the pool is
+    /// empty. The storage is returned to the pool when it is dropped.
+    pub fn get(&self) -> PooledStorage {
+        let storage = self
+            .idle
+            .lock()
+            .ok()
+            .and_then(|mut idle| idle.pop())
+            .unwrap_or_default();
+        PooledStorage {
+            storage,
+            pool: self.clone(),
+        }
+    }
+
+    /// Return the number of unused storages in the pool
+    pub fn idle(&self) -> usize {
+        self.idle.lock().map_or(0, |idle| idle.len())
+    }
+
+    fn put(&self, mut storage: HeapStorage) {
+        if let Ok(mut idle) = self.idle.lock() {
+            if idle.len() < self.max_idle {
+                storage.clear();
+                idle.push(storage);
+            }
+        }
+    }
+}
+
+impl Default for StoragePool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IDLE)
+    }
+}
+
+/// A [`HeapStorage`] taken from a [`StoragePool`], to which it is returned
+/// when it is dropped
+#[derive(Debug)]
+pub struct PooledStorage {
+    storage: HeapStorage,
+    pool: StoragePool,
+}
+
+impl Drop for PooledStorage {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.storage));
+    }
+}
+
+impl private::Sealed for PooledStorage {}
+
+impl ParserStorage for PooledStorage {
+    const UNESCAPE_IN_PLACE: bool = HeapStorage::UNESCAPE_IN_PLACE;
+
+    #[inline]
+    fn stack_len(&self) -> usize {
+        self.storage.stack_len()
+    }
+
+    #[inline]
+    fn stack_push(&mut self, mode: i8) -> bool {
+        self.storage.stack_push(mode)
+    }
+
+    #[inline]
+    fn stack_last(&self) -> Option<i8> {
+        self.storage.stack_last()
+    }
+
+    #[inline]
+    fn stack_pop(&mut self) {
+        self.storage.stack_pop()
+    }
+
+    fn stack_to_vec(&self) -> Vec<i8> {
+        self.storage.stack_to_vec()
+    }
+
+    #[inline]
+    fn buffer(&self) -> &[u8] {
+        self.storage.buffer()
+    }
+
+    #[inline]
+    fn buffer_mut(&mut self) -> &mut [u8] {
+        self.storage.buffer_mut()
+    }
+
+    #[inline]
+    fn buffer_extend(&mut self, bytes: &[u8]) -> bool {
+        self.storage.buffer_extend(bytes)
+    }
+
+    #[inline]
+    fn buffer_truncate(&mut self, len: usize) {
+        self.storage.buffer_truncate(len)
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.storage.shrink_to(min_capacity)
+    }
+}
@@ -0,0 +1,414 @@
+//! Record events in a compact binary format and replay them later.
+//!
+//! A [`TapeWriter`] is a [`JsonEventSink`] that writes each event together
+//! with its value and its [`Span`] to an [`io::Write`]. A [`TapeReader`]
+//! reads the events back and can replay them into another sink, so the
+//! result of parsing a JSON text can be cached or sent to another process
+//! without having to parse the original text again.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sink::parse_with;
+//! use actson::tape::{TapeReader, TapeWriter};
+//! use actson::{JsonEvent, Span};
+//!
+//! let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+//! let mut writer = TapeWriter::new(Vec::new()).unwrap();
+//! parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+//! let tape = writer.into_inner();
+//!
+//! let mut reader = TapeReader::new(tape.as_slice()).unwrap();
+//! assert_eq!(reader.next_event().unwrap(), Some(JsonEvent::StartObject));
+//! assert_eq!(reader.next_event().unwrap(), Some(JsonEvent::FieldName));
+//! assert_eq!(reader.current_str().unwrap(), "name");
+//! assert_eq!(reader.current_span(), Span::new(1, 7));
+//! ```
+//!
+//! ## Format
+//!
+//! A tape starts with the magic bytes `ACTT` and a version byte (currently
+//! 1). Each event is stored as a tag byte (the numeric value of the
+//! [`JsonEvent`]), followed by the start of its span and the span's length.
+//! Field names and string values are followed by the length of the decoded
+//! string and its UTF-8 bytes. Numbers are followed by their length and the
+//! number as it appears in the JSON text, so no precision is lost. All
+//! integers are encoded as unsigned LEB128 varints.
+
+use std::{
+    io::{self, Read, Write},
+    str::{from_utf8, Utf8Error},
+};
+
+use thiserror::Error;
+
+use crate::{
+    feeder::{JsonFeeder, SliceJsonFeeder},
+    parser::InvalidStringValueError,
+    sink::{forward_replaced, forward_str, JsonEventSink},
+    storage::ParserStorage,
+    JsonEvent, JsonParser, Span,
+};
+
+/// The bytes every tape starts with
+const MAGIC: &[u8; 4] = b"ACTT";
+
+/// The version of the tape format
+const VERSION: u8 = 1;
+
+/// The event tags in the order of their numeric values
+const EVENTS: [JsonEvent; 12] = [
+    JsonEvent::NeedMoreInput,
+    JsonEvent::StartObject,
+    JsonEvent::EndObject,
+    JsonEvent::StartArray,
+    JsonEvent::EndArray,
+    JsonEvent::FieldName,
+    JsonEvent::ValueString,
+    JsonEvent::ValueInt,
+    JsonEvent::ValueFloat,
+    JsonEvent::ValueTrue,
+    JsonEvent::ValueFalse,
+    JsonEvent::ValueNull,
+];
+
+/// Returns `true` if the given event is stored together with a value
+fn has_value(event: JsonEvent) -> bool {
+    matches!(
+        event,
+        JsonEvent::FieldName | JsonEvent::ValueString | JsonEvent::ValueInt | JsonEvent::ValueFloat
+    )
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut v: u64) -> io::Result<()> {
+    let mut buf = [0u8; 10];
+    let mut i = 0;
+    loop {
+        let b = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            buf[i] = b;
+            i += 1;
+            break;
+        }
+        buf[i] = b | 0x80;
+        i += 1;
+    }
+    writer.write_all(&buf[..i])
+}
+
+/// An error that can happen when writing a tape
+#[derive(Error, Debug)]
+pub enum TapeWriteError {
+    /// The tape could not be written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// A string in the JSON text is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+}
+
+/// Writes the events it receives to a tape. See the [module
+/// documentation](self).
+pub struct TapeWriter<W> {
+    writer: W,
+}
+
+impl<W> TapeWriter<W>
+where
+    W: Write,
+{
+    /// Create a new tape writer and write the tape's header
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        Ok(TapeWriter { writer })
+    }
+
+    /// Write an event with the given value and span. `value` is ignored for
+    /// events that do not have a value. For numbers, it must be the number
+    /// as it appears in the JSON text.
+    pub fn write_event(&mut self, event: JsonEvent, value: &[u8], span: Span) -> io::Result<()> {
+        self.writer.write_all(&[event as u8])?;
+        write_varint(&mut self.writer, span.start as u64)?;
+        write_varint(&mut self.writer, span.len() as u64)?;
+        if has_value(event) {
+            write_varint(&mut self.writer, value.len() as u64)?;
+            self.writer.write_all(value)?;
+        }
+        Ok(())
+    }
+
+    /// Consume the tape writer and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> JsonEventSink for TapeWriter<W>
+where
+    W: Write,
+{
+    type Error = TapeWriteError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        let value = match event {
+            JsonEvent::FieldName | JsonEvent::ValueString => parser.current_str()?,
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => parser.current_raw_str()?,
+            _ => "",
+        };
+        Ok(self.write_event(event, value.as_bytes(), parser.current_span())?)
+    }
+}
+
+/// An error that can happen when reading a tape
+#[derive(Error, Debug)]
+pub enum TapeReadError {
+    /// The tape could not be read
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The tape does not start with the expected header or has an
+    /// unsupported version
+    #[error("invalid tape header")]
+    InvalidHeader,
+
+    /// The tape contains an unknown event tag
+    #[error("invalid event tag: {0}")]
+    InvalidTag(u8),
+
+    /// A varint in the tape is too long
+    #[error("invalid varint")]
+    InvalidVarint,
+
+    /// The end of an event's span is out of range
+    #[error("invalid span")]
+    InvalidSpan,
+
+    /// The tape ends in the middle of an event
+    #[error("unexpected end of tape")]
+    UnexpectedEof,
+}
+
+/// An error that can happen in [`TapeReader::replay()`]
+#[derive(Error, Debug)]
+pub enum ReplayError<E> {
+    /// The tape could not be read
+    #[error(transparent)]
+    Tape(#[from] TapeReadError),
+
+    /// A value in the tape is not valid UTF-8
+    #[error(transparent)]
+    InvalidStringValue(#[from] Utf8Error),
+
+    /// The sink could not process an event
+    #[error("sink error: {0}")]
+    Sink(E),
+}
+
+/// Reads events from a tape. See the [module documentation](self).
+pub struct TapeReader<R> {
+    reader: R,
+
+    /// The value of the current event
+    value: Vec<u8>,
+
+    /// The span of the current event
+    span: Span,
+}
+
+impl<R> TapeReader<R>
+where
+    R: Read,
+{
+    /// Create a new tape reader and check the tape's header
+    pub fn new(mut reader: R) -> Result<Self, TapeReadError> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => TapeReadError::InvalidHeader,
+            _ => TapeReadError::Io(e),
+        })?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(TapeReadError::InvalidHeader);
+        }
+        Ok(TapeReader {
+            reader,
+            value: Vec::new(),
+            span: Span::default(),
+        })
+    }
+
+    /// Read one byte or return `None` at the end of the tape
+    fn read_byte(&mut self) -> Result<Option<u8>, TapeReadError> {
+        let mut b = [0u8];
+        loop {
+            match self.reader.read(&mut b) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(b[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn read_varint(&mut self) -> Result<usize, TapeReadError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.read_byte()?.ok_or(TapeReadError::UnexpectedEof)?;
+            v |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return usize::try_from(v).map_err(|_| TapeReadError::InvalidVarint);
+            }
+        }
+        Err(TapeReadError::InvalidVarint)
+    }
+
+    /// Read the next event from the tape or return `None` if the end of the
+    /// tape has been reached. Use [`Self::current_str()`] and
+    /// [`Self::current_span()`] to get the event's value and position.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, TapeReadError> {
+        let Some(tag) = self.read_byte()? else {
+            return Ok(None);
+        };
+        let event = *EVENTS
+            .get(tag as usize)
+            .ok_or(TapeReadError::InvalidTag(tag))?;
+
+        let start = self.read_varint()?;
+        let len = self.read_varint()?;
+        let end = start.checked_add(len).ok_or(TapeReadError::InvalidSpan)?;
+        self.span = Span::new(start, end);
+
+        self.value.clear();
+        if has_value(event) {
+            let len = self.read_varint()?;
+            (&mut self.reader)
+                .take(len as u64)
+                .read_to_end(&mut self.value)?;
+            if self.value.len() != len {
+                return Err(TapeReadError::UnexpectedEof);
+            }
+        }
+
+        Ok(Some(event))
+    }
+
+    /// Get the value of the current field name, string, or number. Numbers
+    /// are returned as they appeared in the original JSON text.
+    pub fn current_str(&self) -> Result<&str, Utf8Error> {
+        from_utf8(&self.value)
+    }
+
+    /// Get the position of the current event in the original JSON text
+    pub fn current_span(&self) -> Span {
+        self.span
+    }
+
+    /// Read all remaining events from the tape and forward them to the given
+    /// sink. The sink reads values from a parser as usual, but the parser's
+    /// [`JsonParser::current_span()`] does not refer to the original JSON
+    /// text. Use a [`TapeReader`] directly if you need the original spans.
+    pub fn replay<K>(&mut self, sink: &mut K) -> Result<(), ReplayError<K::Error>>
+    where
+        K: JsonEventSink,
+    {
+        let empty = JsonParser::new(SliceJsonFeeder::new(&[]));
+        let mut buf = Vec::new();
+        while let Some(event) = self.next_event()? {
+            let r = match event {
+                JsonEvent::FieldName | JsonEvent::ValueString => {
+                    let s = from_utf8(&self.value)?;
+                    forward_str(sink, event, s, &mut buf)
+                }
+                JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                    forward_replaced(sink, event, &self.value)
+                }
+                _ => sink.on_event(event, &empty),
+            };
+            r.map_err(ReplayError::Sink)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{JsonEvent, Span};
+
+    use super::{TapeReadError, TapeReader, TapeWriter};
+
+    /// Test that events and values survive a round trip
+    #[test]
+    fn round_trip() {
+        let mut writer = TapeWriter::new(Vec::new()).unwrap();
+        writer
+            .write_event(
+                JsonEvent::ValueString,
+                "x".repeat(200).as_bytes(),
+                Span::new(300, 502),
+            )
+            .unwrap();
+        writer
+            .write_event(JsonEvent::ValueNull, b"ignored", Span::new(504, 508))
+            .unwrap();
+        let tape = writer.into_inner();
+
+        let mut reader = TapeReader::new(tape.as_slice()).unwrap();
+        assert_eq!(reader.next_event().unwrap(), Some(JsonEvent::ValueString));
+        assert_eq!(reader.current_str().unwrap(), "x".repeat(200));
+        assert_eq!(reader.current_span(), Span::new(300, 502));
+        assert_eq!(reader.next_event().unwrap(), Some(JsonEvent::ValueNull));
+        assert_eq!(reader.current_str().unwrap(), "");
+        assert_eq!(reader.next_event().unwrap(), None);
+    }
+
+    /// Test that invalid tapes are rejected
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            TapeReader::new(&b"ACT"[..]),
+            Err(TapeReadError::InvalidHeader)
+        ));
+        assert!(matches!(
+            TapeReader::new(&b"ACTT\x02"[..]),
+            Err(TapeReadError::InvalidHeader)
+        ));
+
+        let mut reader = TapeReader::new(&b"ACTT\x01\x0c"[..]).unwrap();
+        assert!(matches!(
+            reader.next_event(),
+            Err(TapeReadError::InvalidTag(12))
+        ));
+
+        let mut reader = TapeReader::new(&b"ACTT\x01\x06\x00\x05\x03ab"[..]).unwrap();
+        assert!(matches!(
+            reader.next_event(),
+            Err(TapeReadError::UnexpectedEof)
+        ));
+    }
+
+    /// Test that a span whose end does not fit into a `usize` is rejected
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn span_overflow() {
+        let mut tape = b"ACTT\x01\x06".to_vec();
+        // start = usize::MAX
+        tape.extend_from_slice(&[0xff; 9]);
+        tape.push(0x01);
+        // len = 1
+        tape.push(0x01);
+        let mut reader = TapeReader::new(tape.as_slice()).unwrap();
+        assert!(matches!(
+            reader.next_event(),
+            Err(TapeReadError::InvalidSpan)
+        ));
+    }
+}
@@ -0,0 +1,394 @@
+//! Generate random JSON texts together with the events the parser returns
+//! for them.
+//!
+//! An [`EventGenerator`] produces [`GeneratedJson`] values: a valid JSON
+//! text and the exact sequence of [`OwnedJsonEvent`]s that
+//! [`JsonParser::next_owned_event()`](crate::JsonParser::next_owned_event())
+//! returns when it parses the text in one piece (i.e. without
+//! [`OwnedJsonEvent::NeedMoreInput`]). Use it to property-test sinks,
+//! emitters, and decoders against Actson's event grammar. The generated
+//! texts contain random white space, escape sequences, and numbers in
+//! different notations.
+//!
+//! The generator takes its randomness either from a seed or from a byte
+//! slice. The latter makes it easy to plug into fuzzers (e.g. `cargo fuzz`)
+//! and property testing frameworks, which can then shrink failing cases:
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn roundtrip(bytes in any::<Vec<u8>>()) {
+//!         let generated = EventGenerator::from_bytes(&bytes).generate();
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! An exhausted byte slice is treated as if it continued with zeros, which
+//! always leads to small values, so generation terminates for any input.
+//!
+//! Heads up: You need to enable the `test-util` feature for this.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::testutil::EventGenerator;
+//! use actson::JsonParser;
+//!
+//! for seed in 0..10 {
+//!     let generated = EventGenerator::from_seed(seed).generate();
+//!     let mut parser = JsonParser::new(SliceJsonFeeder::new(generated.json.as_bytes()));
+//!     let mut events = Vec::new();
+//!     while let Some(e) = parser.next_owned_event().unwrap() {
+//!         events.push(e);
+//!     }
+//!     assert_eq!(events, generated.events);
+//! }
+//! ```
+
+use crate::{
+    escape::{escape_str_with, EscapeOptions},
+    OwnedJsonEvent,
+};
+
+/// The default maximum depth of generated values
+pub const DEFAULT_MAX_DEPTH: usize = 6;
+
+/// The default maximum number of members or elements of generated objects
+/// and arrays
+pub const DEFAULT_MAX_LEN: usize = 5;
+
+/// Characters strings are made of. Includes characters that have to be
+/// escaped and characters outside the Basic Multilingual Plane.
+const CHARS: &[char] = &[
+    'a',
+    'b',
+    'z',
+    'A',
+    '0',
+    ' ',
+    '/',
+    '"',
+    '\\',
+    '\n',
+    '\t',
+    '\u{1}',
+    '\u{7f}',
+    '\u{e9}',
+    '\u{20ac}',
+    '\u{2028}',
+    '\u{ffff}',
+    '\u{1f600}',
+];
+
+/// White space that can appear between tokens
+const WHITESPACE: &[&str] = &["", "", "", " ", "\n", "\t", "\r\n  "];
+
+/// Where an [`EventGenerator`] takes its randomness from
+enum Source<'a> {
+    /// The state of a SplitMix64 generator
+    Seed(u64),
+
+    /// The remaining bytes of a slice
+    Bytes(&'a [u8]),
+}
+
+/// A JSON text and the events the parser returns for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedJson {
+    /// The JSON text
+    pub json: String,
+
+    /// The events of the JSON text in the order the parser returns them
+    pub events: Vec<OwnedJsonEvent>,
+}
+
+/// Generates random JSON texts and their events. See the [module
+/// documentation](self).
+pub struct EventGenerator<'a> {
+    source: Source<'a>,
+    max_depth: usize,
+    max_len: usize,
+    whitespace: bool,
+}
+
+impl EventGenerator<'static> {
+    /// Create a generator that derives its randomness from the given seed.
+    /// The same seed always produces the same JSON texts.
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new(Source::Seed(seed))
+    }
+}
+
+impl<'a> EventGenerator<'a> {
+    /// Create a generator that takes its randomness from the given bytes
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(Source::Bytes(bytes))
+    }
+
+    fn new(source: Source<'a>) -> Self {
+        EventGenerator {
+            source,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_len: DEFAULT_MAX_LEN,
+            whitespace: true,
+        }
+    }
+
+    /// Set the maximum depth of generated values. A depth of 0 only
+    /// generates scalar values.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the maximum number of members or elements of generated objects
+    /// and arrays
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Specify whether white space should be inserted between tokens
+    pub fn with_whitespace(mut self, whitespace: bool) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// Generate a JSON text consisting of a single value
+    pub fn generate(&mut self) -> GeneratedJson {
+        let mut out = GeneratedJson {
+            json: String::new(),
+            events: Vec::new(),
+        };
+        self.space(&mut out.json);
+        self.value(self.max_depth, &mut out);
+        self.space(&mut out.json);
+        out
+    }
+
+    /// Return the next 64 random bits
+    fn next_u64(&mut self) -> u64 {
+        match &mut self.source {
+            Source::Seed(state) => {
+                *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                let mut z = *state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^ (z >> 31)
+            }
+            Source::Bytes(bytes) => {
+                let n = bytes.len().min(8);
+                let mut buf = [0u8; 8];
+                buf[..n].copy_from_slice(&bytes[..n]);
+                *bytes = &bytes[n..];
+                u64::from_le_bytes(buf)
+            }
+        }
+    }
+
+    /// Return a random number in `0..n` (or 0 if `n` is 0)
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Append random white space
+    fn space(&mut self, json: &mut String) {
+        if self.whitespace {
+            json.push_str(WHITESPACE[self.below(WHITESPACE.len())]);
+        }
+    }
+
+    /// Generate a random string and append it to the JSON text with random
+    /// escaping
+    fn string(&mut self, json: &mut String) -> String {
+        let len = self.below(8);
+        let s = (0..len)
+            .map(|_| CHARS[self.below(CHARS.len())])
+            .collect::<String>();
+        let bits = self.next_u64();
+        let options = EscapeOptions {
+            ascii: bits & 1 != 0,
+            html: bits & 2 != 0,
+            slash: bits & 4 != 0,
+        };
+        let mut buf = Vec::new();
+        escape_str_with(&s, options, &mut buf);
+        json.push_str(std::str::from_utf8(&buf).expect("escaped strings are valid UTF-8"));
+        s
+    }
+
+    /// Generate a value that is nested at most `depth` levels deep
+    fn value(&mut self, depth: usize, out: &mut GeneratedJson) {
+        let kinds = if depth == 0 { 6 } else { 8 };
+        match self.below(kinds) {
+            0 => {
+                out.json.push_str("null");
+                out.events.push(OwnedJsonEvent::ValueNull);
+            }
+            1 => {
+                out.json.push_str("true");
+                out.events.push(OwnedJsonEvent::ValueTrue);
+            }
+            2 => {
+                out.json.push_str("false");
+                out.events.push(OwnedJsonEvent::ValueFalse);
+            }
+            3 => {
+                // mostly small numbers, sometimes the full range of an i64
+                // or a u64
+                let (literal, event) = match self.below(5) {
+                    0 => {
+                        let i = self.next_u64() as i64;
+                        (i.to_string(), OwnedJsonEvent::ValueInt(i))
+                    }
+                    1 => {
+                        let u = self.next_u64() | 1 << 63;
+                        (u.to_string(), OwnedJsonEvent::ValueUint(u))
+                    }
+                    _ => {
+                        let i = self.below(2001) as i64 - 1000;
+                        (i.to_string(), OwnedJsonEvent::ValueInt(i))
+                    }
+                };
+                out.json.push_str(&literal);
+                out.events.push(event);
+            }
+            4 => {
+                let f = match self.below(3) {
+                    0 => f64::from_bits(self.next_u64()),
+                    _ => (self.below(2001) as f64 - 1000.0) / 8.0,
+                };
+                let f = if f.is_finite() { f } else { 0.5 };
+                // `{:?}` always contains a `.` or an exponent, so the
+                // parser reads the number as a float
+                let literal = if self.below(2) == 0 {
+                    format!("{f:?}")
+                } else {
+                    format!("{f:e}")
+                };
+                out.json.push_str(&literal);
+                out.events.push(OwnedJsonEvent::ValueFloat(f));
+            }
+            5 => {
+                let s = self.string(&mut out.json);
+                out.events.push(OwnedJsonEvent::ValueString(s));
+            }
+            6 => {
+                out.json.push('[');
+                out.events.push(OwnedJsonEvent::StartArray);
+                let len = self.below(self.max_len + 1);
+                for i in 0..len {
+                    if i > 0 {
+                        out.json.push(',');
+                    }
+                    self.space(&mut out.json);
+                    self.value(depth - 1, out);
+                    self.space(&mut out.json);
+                }
+                if len == 0 {
+                    self.space(&mut out.json);
+                }
+                out.json.push(']');
+                out.events.push(OwnedJsonEvent::EndArray);
+            }
+            _ => {
+                out.json.push('{');
+                out.events.push(OwnedJsonEvent::StartObject);
+                let len = self.below(self.max_len + 1);
+                for i in 0..len {
+                    if i > 0 {
+                        out.json.push(',');
+                    }
+                    self.space(&mut out.json);
+                    let key = self.string(&mut out.json);
+                    out.events.push(OwnedJsonEvent::FieldName(key));
+                    self.space(&mut out.json);
+                    out.json.push(':');
+                    self.space(&mut out.json);
+                    self.value(depth - 1, out);
+                    self.space(&mut out.json);
+                }
+                if len == 0 {
+                    self.space(&mut out.json);
+                }
+                out.json.push('}');
+                out.events.push(OwnedJsonEvent::EndObject);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{feeder::PushJsonFeeder, JsonParser, OwnedJsonEvent};
+
+    use super::EventGenerator;
+
+    /// Parse a JSON text in chunks of the given size and return its events
+    fn events(json: &[u8], chunk_size: usize) -> Vec<OwnedJsonEvent> {
+        let mut parser = JsonParser::new(PushJsonFeeder::new());
+        let mut chunks = json.chunks(chunk_size);
+        let mut events = Vec::new();
+        while let Some(e) = parser.next_owned_event().unwrap() {
+            if e == OwnedJsonEvent::NeedMoreInput {
+                match chunks.next() {
+                    Some(c) => {
+                        parser.feeder.push_bytes(c);
+                    }
+                    None => parser.feeder.done(),
+                }
+            } else {
+                events.push(e);
+            }
+        }
+        events
+    }
+
+    /// Test that the generated events match the parser's events
+    #[test]
+    fn matches_parser() {
+        for seed in 0..500 {
+            let generated = EventGenerator::from_seed(seed).generate();
+            assert_eq!(
+                events(generated.json.as_bytes(), 3),
+                generated.events,
+                "{}",
+                generated.json
+            );
+        }
+    }
+
+    /// Test that any bytes produce a valid JSON text and that the options
+    /// are respected
+    #[test]
+    fn from_bytes() {
+        let generated = EventGenerator::from_bytes(&[]).generate();
+        assert_eq!(generated.json, "null");
+        assert_eq!(generated.events, vec![OwnedJsonEvent::ValueNull]);
+
+        let bytes = (0..=255u8).cycle().take(4096).collect::<Vec<_>>();
+        for start in 0..64 {
+            let generated = EventGenerator::from_bytes(&bytes[start..])
+                .with_max_depth(2)
+                .with_max_len(3)
+                .with_whitespace(false)
+                .generate();
+            assert_eq!(events(generated.json.as_bytes(), 1), generated.events);
+
+            let mut depth = 0;
+            let mut max_depth = 0;
+            for e in &generated.events {
+                match e {
+                    OwnedJsonEvent::StartObject | OwnedJsonEvent::StartArray => depth += 1,
+                    OwnedJsonEvent::EndObject | OwnedJsonEvent::EndArray => depth -= 1,
+                    _ => {}
+                }
+                max_depth = max_depth.max(depth);
+            }
+            assert!(max_depth <= 2);
+        }
+    }
+}
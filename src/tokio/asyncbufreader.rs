@@ -1,5 +1,10 @@
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
 use crate::feeder::{FillError, JsonFeeder};
-use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader};
 
 /// A [`JsonFeeder`] that reads from an asynchronous [`BufReader`].
 pub struct AsyncBufReaderJsonFeeder<T> {
@@ -29,6 +34,20 @@ where
         self.pos = 0;
         Ok(())
     }
+
+    /// Fill the feeder's internal buffer. This is the poll-based version of
+    /// [`Self::fill_buf()`] for use in manual [`Future`] or
+    /// [`Stream`](futures_core::Stream) implementations.
+    pub fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), FillError>> {
+        self.reader.consume(self.pos);
+        self.pos = 0;
+        // the buffer is empty while the read is pending but the feeder is
+        // not done yet
+        self.filled = false;
+        ready!(Pin::new(&mut self.reader).poll_fill_buf(cx))?;
+        self.filled = true;
+        Poll::Ready(Ok(()))
+    }
 }
 
 impl<T> JsonFeeder for AsyncBufReaderJsonFeeder<T>
@@ -53,4 +72,12 @@ where
             None
         }
     }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.reader.buffer()[self.pos..]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
 }
@@ -0,0 +1,138 @@
+use std::{
+    future::poll_fn,
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::Stream;
+
+use crate::feeder::{FillError, JsonFeeder};
+
+/// A [`JsonFeeder`] that reads from a [`Stream`] of [`Bytes`] frames as
+/// they arrive. Each frame is parsed without copying it.
+///
+/// This is the building block for parsing HTTP bodies in hyper or axum
+/// services without collecting them first: convert the body into a stream
+/// of data frames (e.g. with `http_body_util::BodyDataStream` or axum's
+/// `Body::into_data_stream()`) and pass it to this feeder. Empty frames are
+/// skipped. Errors of the stream are converted to [`FillError::Io`].
+///
+/// ```
+/// use bytes::Bytes;
+/// use futures::stream;
+///
+/// use actson::tokio::BytesStreamJsonFeeder;
+/// use actson::{JsonEvent, JsonParser};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let frames = stream::iter([
+///         Ok::<_, std::io::Error>(Bytes::from_static(br#"{"name": "El"#)),
+///         Ok(Bytes::from_static(br#"vis"}"#)),
+///     ]);
+///     let mut parser = JsonParser::new(BytesStreamJsonFeeder::new(frames));
+///
+///     let mut name = None;
+///     while let Some(e) = parser.next_event().unwrap() {
+///         match e {
+///             JsonEvent::NeedMoreInput => parser.feeder.fill_buf().await.unwrap(),
+///             JsonEvent::ValueString => name = Some(parser.current_str().unwrap().to_string()),
+///             _ => {}
+///         }
+///     }
+///     assert_eq!(name.as_deref(), Some("Elvis"));
+/// }
+/// ```
+pub struct BytesStreamJsonFeeder<St> {
+    stream: St,
+    frame: Bytes,
+    pos: usize,
+    done: bool,
+}
+
+impl<St, E> BytesStreamJsonFeeder<St>
+where
+    St: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Create a new feeder that reads frames from the given stream
+    pub fn new(stream: St) -> Self {
+        BytesStreamJsonFeeder {
+            stream,
+            frame: Bytes::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Wait for the next non-empty frame unless the current one has not
+    /// been consumed completely yet
+    pub async fn fill_buf(&mut self) -> Result<(), FillError> {
+        poll_fn(|cx| self.poll_fill_buf(cx)).await
+    }
+
+    /// Wait for the next non-empty frame. This is the poll-based version of
+    /// [`Self::fill_buf()`] for use in manual
+    /// [`Future`](std::future::Future) or [`Stream`] implementations.
+    pub fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), FillError>> {
+        if self.done || self.pos < self.frame.len() {
+            return Poll::Ready(Ok(()));
+        }
+        loop {
+            match ready!(Pin::new(&mut self.stream).poll_next(cx)) {
+                Some(Ok(frame)) if frame.is_empty() => {}
+                Some(Ok(frame)) => {
+                    self.frame = frame;
+                    self.pos = 0;
+                    return Poll::Ready(Ok(()));
+                }
+                Some(Err(e)) => return Poll::Ready(Err(io::Error::other(e).into())),
+                None => {
+                    self.done = true;
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+    }
+
+    /// Consume the feeder and return the underlying stream
+    pub fn into_inner(self) -> St {
+        self.stream
+    }
+}
+
+impl<St> JsonFeeder for BytesStreamJsonFeeder<St> {
+    fn has_input(&self) -> bool {
+        self.pos < self.frame.len()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done && !self.has_input()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        let r = self.frame.get(self.pos).copied();
+        if r.is_some() {
+            self.pos += 1;
+        }
+        r
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.frame[self.pos..]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        if n > self.pos {
+            return false;
+        }
+        self.pos -= n;
+        true
+    }
+}
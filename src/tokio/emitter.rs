@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use num_traits::PrimInt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    emitter::{EmitterError, EmitterOptions, Encoder},
+    feeder::JsonFeeder,
+    storage::ParserStorage,
+    JsonEvent, JsonParser, OwnedJsonEvent,
+};
+
+/// The asynchronous counterpart of [`JsonEmitter`](crate::emitter::JsonEmitter).
+/// Writes a JSON text to an [`AsyncWrite`].
+///
+/// Output is collected in a buffer. Whenever the buffer is full, the method
+/// that has filled it waits until the buffer has been written, so a slow
+/// writer slows down the producer of the JSON text. Call [`Self::finish()`]
+/// (or at least [`Self::flush()`]) when you are done.
+///
+/// ```
+/// use actson::tokio::AsyncJsonEmitter;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut emitter = AsyncJsonEmitter::new(Vec::new());
+///     emitter.start_array().await.unwrap();
+///     for i in 1..=3 {
+///         emitter.value_int(i).await.unwrap();
+///     }
+///     emitter.end_array().await.unwrap();
+///     assert_eq!(emitter.finish().await.unwrap(), b"[1,2,3]");
+/// }
+/// ```
+pub struct AsyncJsonEmitter<W> {
+    writer: W,
+    encoder: Encoder,
+
+    /// Output that has not been written to [`Self::writer`] yet
+    buf: Vec<u8>,
+}
+
+impl<W> AsyncJsonEmitter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Create a new emitter writing compact JSON to the given writer
+    pub fn new(writer: W) -> Self {
+        Self::new_with_options(writer, EmitterOptions::default())
+    }
+
+    /// Create a new emitter writing to the given writer with the given
+    /// options
+    pub fn new_with_options(writer: W, options: EmitterOptions) -> Self {
+        AsyncJsonEmitter {
+            writer,
+            encoder: Encoder::new(options),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Write the buffer to the underlying writer if it is full
+    async fn maybe_flush(&mut self) -> Result<(), EmitterError> {
+        let options = *self.encoder.options();
+        if options.flush_on_document_end() && self.encoder.is_top_level() && !self.buf.is_empty() {
+            return self.flush().await;
+        }
+        if self.buf.len() >= options.buffer_size() {
+            self.writer.write_all(&self.buf).await?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    /// Write the start of an object
+    pub async fn start_object(&mut self) -> Result<(), EmitterError> {
+        self.encoder.start_object(&mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write the end of the current object
+    pub async fn end_object(&mut self) -> Result<(), EmitterError> {
+        self.encoder.end_object(&mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write the start of an array
+    pub async fn start_array(&mut self) -> Result<(), EmitterError> {
+        self.encoder.start_array(&mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write the end of the current array
+    pub async fn end_array(&mut self) -> Result<(), EmitterError> {
+        self.encoder.end_array(&mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write the name of a field in the current object. Write the field's
+    /// value next.
+    pub async fn field_name(&mut self, name: &str) -> Result<(), EmitterError> {
+        self.encoder.field_name(name, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write a string value
+    pub async fn value_str(&mut self, value: &str) -> Result<(), EmitterError> {
+        self.encoder.value_str(value, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write an integer value
+    pub async fn value_int<I>(&mut self, value: I) -> Result<(), EmitterError>
+    where
+        I: PrimInt + Display,
+    {
+        self.encoder.value_int(value, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write a floating point value. Returns
+    /// [`EmitterError::IllegalJsonNumber`] if the value is NaN or infinite.
+    pub async fn value_float(&mut self, value: f64) -> Result<(), EmitterError> {
+        self.encoder.value_float(value, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write a boolean value
+    pub async fn value_bool(&mut self, value: bool) -> Result<(), EmitterError> {
+        self.encoder.value_bool(value, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write a `null` value
+    pub async fn value_null(&mut self) -> Result<(), EmitterError> {
+        self.encoder.value_null(&mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write an already serialized JSON value (e.g. a subtree copied from
+    /// another JSON text) verbatim in place of a value. The fragment is
+    /// neither parsed nor validated, so the caller must make sure it is a
+    /// single valid JSON value. It is also not reformatted, i.e. it is not
+    /// indented in pretty-printed output and keys in it are not sorted.
+    pub async fn write_raw(&mut self, json: &str) -> Result<(), EmitterError> {
+        self.encoder.write_raw(json, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write a comment before the next member or value (see
+    /// [`JsonEmitter::comment()`](crate::emitter::JsonEmitter::comment))
+    pub fn comment(&mut self, comment: &str) -> Result<(), EmitterError> {
+        self.encoder.comment(comment)
+    }
+
+    /// Write the given event of the given parser together with its value.
+    /// This is the asynchronous counterpart of the
+    /// [`JsonEventSink`](crate::sink::JsonEventSink) implementation of
+    /// [`JsonEmitter`](crate::emitter::JsonEmitter).
+    pub async fn emit_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), EmitterError>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        self.encoder.event(event, parser, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write the given event together with its value.
+    /// [`OwnedJsonEvent::NeedMoreInput`] is ignored.
+    pub async fn emit_owned_event(&mut self, event: &OwnedJsonEvent) -> Result<(), EmitterError> {
+        self.encoder.owned_event(event, &mut self.buf)?;
+        self.maybe_flush().await
+    }
+
+    /// Write all buffered output to the underlying writer and flush it
+    pub async fn flush(&mut self) -> Result<(), EmitterError> {
+        self.writer.write_all(&self.buf).await?;
+        self.buf.clear();
+        Ok(self.writer.flush().await?)
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub async fn finish(mut self) -> Result<W, EmitterError> {
+        self.encoder.finish(&mut self.buf);
+        self.flush().await?;
+        Ok(self.writer)
+    }
+}
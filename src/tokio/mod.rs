@@ -1,3 +1,11 @@
 mod asyncbufreader;
+mod bytestream;
+mod emitter;
+mod push;
+mod stream;
 
 pub use asyncbufreader::AsyncBufReaderJsonFeeder;
+pub use bytestream::BytesStreamJsonFeeder;
+pub use emitter::AsyncJsonEmitter;
+pub use push::{AsyncPushJsonFeeder, FeederSink};
+pub use stream::{JsonEventStream, JsonEventStreamError};
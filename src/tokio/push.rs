@@ -0,0 +1,267 @@
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+use bytes::Bytes;
+use futures_sink::Sink;
+
+use crate::feeder::{FillError, JsonFeeder};
+
+/// The default number of frames an [`AsyncPushJsonFeeder`] can buffer
+const DEFAULT_CAPACITY: usize = 16;
+
+/// The state shared between a [`FeederSink`] and an
+/// [`AsyncPushJsonFeeder`]
+struct Shared {
+    /// Frames that have been sent but not taken by the feeder yet
+    frames: VecDeque<Bytes>,
+
+    /// The maximum number of frames in `frames`
+    capacity: usize,
+
+    /// `true` if the sink has been closed or dropped
+    closed: bool,
+
+    /// `true` if the feeder has been dropped
+    feeder_dropped: bool,
+
+    /// The task waiting for a frame
+    feeder_waker: Option<Waker>,
+
+    /// The task waiting for room in the buffer
+    sink_waker: Option<Waker>,
+}
+
+/// An asynchronous push-based feeder that is split into two halves: a
+/// [`FeederSink`] that implements [`futures_sink::Sink<Bytes>`] and can be
+/// connected to stream and sink combinators (e.g.
+/// `stream.forward(sink)`), and the feeder itself, which is owned by the
+/// [`JsonParser`](crate::JsonParser). Frames are passed on without copying
+/// them.
+///
+/// The sink accepts frames as long as fewer than the feeder's capacity are
+/// buffered. Whenever the parser returns
+/// [`JsonEvent::NeedMoreInput`](crate::JsonEvent::NeedMoreInput), call
+/// [`Self::fill_buf()`] to wait for the next frame. Closing or dropping the
+/// sink marks the end of the JSON text.
+///
+/// ```
+/// use bytes::Bytes;
+/// use futures::{stream, StreamExt};
+///
+/// use actson::tokio::AsyncPushJsonFeeder;
+/// use actson::{JsonEvent, JsonParser};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (sink, feeder) = AsyncPushJsonFeeder::new();
+///
+///     let frames = stream::iter([
+///         Ok::<_, std::io::Error>(Bytes::from_static(br#"{"name": "El"#)),
+///         Ok(Bytes::from_static(br#"vis"}"#)),
+///     ]);
+///     let producer = tokio::spawn(frames.forward(sink));
+///
+///     let mut parser = JsonParser::new(feeder);
+///     let mut name = None;
+///     while let Some(e) = parser.next_event().unwrap() {
+///         match e {
+///             JsonEvent::NeedMoreInput => parser.feeder.fill_buf().await.unwrap(),
+///             JsonEvent::ValueString => name = Some(parser.current_str().unwrap().to_string()),
+///             _ => {}
+///         }
+///     }
+///     producer.await.unwrap().unwrap();
+///     assert_eq!(name.as_deref(), Some("Elvis"));
+/// }
+/// ```
+pub struct AsyncPushJsonFeeder {
+    shared: Arc<Mutex<Shared>>,
+    frame: Bytes,
+    pos: usize,
+    done: bool,
+}
+
+/// The half of an [`AsyncPushJsonFeeder`] that accepts input frames. It can
+/// be sent to another task. Sending fails with
+/// [`io::ErrorKind::BrokenPipe`] if the feeder has been dropped.
+pub struct FeederSink {
+    shared: Arc<Mutex<Shared>>,
+}
+
+/// Lock the shared state. A panic while the lock was held cannot leave the
+/// state inconsistent, so poisoning is ignored.
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl AsyncPushJsonFeeder {
+    /// Create a new pair of a sink and a feeder that can buffer a default
+    /// number of frames
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (FeederSink, AsyncPushJsonFeeder) {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new pair of a sink and a feeder that can buffer the given
+    /// number of frames (at least one)
+    pub fn with_capacity(capacity: usize) -> (FeederSink, AsyncPushJsonFeeder) {
+        let shared = Arc::new(Mutex::new(Shared {
+            frames: VecDeque::new(),
+            capacity: capacity.max(1),
+            closed: false,
+            feeder_dropped: false,
+            feeder_waker: None,
+            sink_waker: None,
+        }));
+        (
+            FeederSink {
+                shared: Arc::clone(&shared),
+            },
+            AsyncPushJsonFeeder {
+                shared,
+                frame: Bytes::new(),
+                pos: 0,
+                done: false,
+            },
+        )
+    }
+
+    /// Wait for the next frame unless the current one has not been consumed
+    /// completely yet
+    pub async fn fill_buf(&mut self) -> Result<(), FillError> {
+        poll_fn(|cx| self.poll_fill_buf(cx)).await
+    }
+
+    /// Wait for the next frame. This is the poll-based version of
+    /// [`Self::fill_buf()`] for use in manual
+    /// [`Future`](std::future::Future) or
+    /// [`Stream`](futures_core::Stream) implementations.
+    pub fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), FillError>> {
+        if self.done || self.pos < self.frame.len() {
+            return Poll::Ready(Ok(()));
+        }
+        let mut shared = lock(&self.shared);
+        match shared.frames.pop_front() {
+            Some(frame) => {
+                self.frame = frame;
+                self.pos = 0;
+                if let Some(waker) = shared.sink_waker.take() {
+                    waker.wake();
+                }
+            }
+            None if shared.closed => self.done = true,
+            None => {
+                shared.feeder_waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for AsyncPushJsonFeeder {
+    fn drop(&mut self) {
+        let mut shared = lock(&self.shared);
+        shared.feeder_dropped = true;
+        if let Some(waker) = shared.sink_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl JsonFeeder for AsyncPushJsonFeeder {
+    fn has_input(&self) -> bool {
+        self.pos < self.frame.len()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done && !self.has_input()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        let r = self.frame.get(self.pos).copied();
+        if r.is_some() {
+            self.pos += 1;
+        }
+        r
+    }
+
+    fn peek_input(&self) -> &[u8] {
+        &self.frame[self.pos..]
+    }
+
+    fn consume_input(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn rewind_input(&mut self, n: usize) -> bool {
+        if n > self.pos {
+            return false;
+        }
+        self.pos -= n;
+        true
+    }
+}
+
+impl FeederSink {
+    /// Mark the end of the JSON text and wake up the feeder
+    fn close(&self) {
+        let mut shared = lock(&self.shared);
+        shared.closed = true;
+        if let Some(waker) = shared.feeder_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Sink<Bytes> for FeederSink {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut shared = lock(&self.shared);
+        if shared.feeder_dropped {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if shared.frames.len() < shared.capacity {
+            return Poll::Ready(Ok(()));
+        }
+        shared.sink_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), Self::Error> {
+        let mut shared = lock(&self.shared);
+        if shared.feeder_dropped {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        if !item.is_empty() {
+            shared.frames.push_back(item);
+            if let Some(waker) = shared.feeder_waker.take() {
+                waker.wake();
+            }
+        }
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // frames are handed over as soon as they are sent
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for FeederSink {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
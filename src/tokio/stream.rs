@@ -0,0 +1,198 @@
+use std::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::{FusedStream, Stream};
+use thiserror::Error;
+use tokio::io::AsyncRead;
+
+use crate::{
+    feeder::FillError,
+    parser::NextValueError,
+    storage::{HeapStorage, ParserStorage},
+    JsonEvent, JsonParser, OwnedJsonEvent,
+};
+
+use super::AsyncBufReaderJsonFeeder;
+
+/// An error that can happen while reading events from a [`JsonEventStream`]
+/// or with [`JsonParser::poll_next_event()`]
+#[derive(Error, Debug)]
+pub enum JsonEventStreamError {
+    /// The JSON text could not be parsed or a value could not be read
+    #[error(transparent)]
+    Parser(#[from] NextValueError),
+
+    /// The underlying reader could not be read
+    #[error(transparent)]
+    Fill(#[from] FillError),
+}
+
+impl<T, S> JsonParser<AsyncBufReaderJsonFeeder<T>, S>
+where
+    T: AsyncRead + Unpin,
+    S: ParserStorage,
+{
+    /// Poll for the next event. If the parser needs more input, the feeder's
+    /// buffer is filled (see [`AsyncBufReaderJsonFeeder::poll_fill_buf()`]),
+    /// so this method never returns [`JsonEvent::NeedMoreInput`]. Instead,
+    /// it returns [`Poll::Pending`] and wakes the given context when more
+    /// input is available. This is the building block for hand-written
+    /// [`Future`](std::future::Future) or [`Stream`] implementations that
+    /// cannot use `async fn`. Values can be read with the parser's
+    /// accessors as usual.
+    ///
+    /// ```
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::task::{Context, Poll};
+    ///
+    /// use tokio::io::{AsyncRead, BufReader};
+    ///
+    /// use actson::tokio::{AsyncBufReaderJsonFeeder, JsonEventStreamError};
+    /// use actson::{JsonEvent, JsonParser};
+    ///
+    /// /// A future that sums up all integers of a JSON text
+    /// struct Sum<T> {
+    ///     parser: JsonParser<AsyncBufReaderJsonFeeder<T>>,
+    ///     sum: i64,
+    /// }
+    ///
+    /// impl<T: AsyncRead + Unpin> Future for Sum<T> {
+    ///     type Output = Result<i64, JsonEventStreamError>;
+    ///
+    ///     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    ///         let this = self.get_mut();
+    ///         loop {
+    ///             match this.parser.poll_next_event(cx) {
+    ///                 Poll::Ready(Ok(Some(JsonEvent::ValueInt))) => {
+    ///                     this.sum += this.parser.current_int::<i64>().unwrap();
+    ///                 }
+    ///                 Poll::Ready(Ok(Some(_))) => {}
+    ///                 Poll::Ready(Ok(None)) => return Poll::Ready(Ok(this.sum)),
+    ///                 Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+    ///                 Poll::Pending => return Poll::Pending,
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let json = r#"[1, {"a": 2}, [3, 4]]"#.as_bytes();
+    ///     let feeder = AsyncBufReaderJsonFeeder::new(BufReader::new(json));
+    ///     let sum = Sum { parser: JsonParser::new(feeder), sum: 0 };
+    ///     assert_eq!(sum.await.unwrap(), 10);
+    /// }
+    /// ```
+    pub fn poll_next_event(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<JsonEvent>, JsonEventStreamError>> {
+        loop {
+            match self.next_event() {
+                Ok(Some(JsonEvent::NeedMoreInput)) => ready!(self.feeder.poll_fill_buf(cx))?,
+                r => return Poll::Ready(r.map_err(|e| NextValueError::from(e).into())),
+            }
+        }
+    }
+}
+
+/// A [`Stream`] of the events of a [`JsonParser`] reading from an
+/// [`AsyncBufReaderJsonFeeder`]. The stream fills the feeder's buffer
+/// whenever the parser needs more input, so it never returns
+/// [`OwnedJsonEvent::NeedMoreInput`]. It ends after the end of the JSON text
+/// has been reached or after it has returned an error.
+///
+/// ```
+/// use futures::StreamExt;
+/// use tokio::io::BufReader;
+///
+/// use actson::tokio::{AsyncBufReaderJsonFeeder, JsonEventStream};
+/// use actson::{JsonParser, OwnedJsonEvent};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let json = r#"{"name": "Elvis", "age": 42}"#.as_bytes();
+///     let feeder = AsyncBufReaderJsonFeeder::new(BufReader::new(json));
+///     let stream = JsonEventStream::new(JsonParser::new(feeder));
+///
+///     let names = stream
+///         .filter_map(|e| async move {
+///             match e.unwrap() {
+///                 OwnedJsonEvent::FieldName(n) => Some(n),
+///                 _ => None,
+///             }
+///         })
+///         .collect::<Vec<_>>()
+///         .await;
+///
+///     assert_eq!(names, vec!["name", "age"]);
+/// }
+/// ```
+pub struct JsonEventStream<T, S = HeapStorage> {
+    parser: JsonParser<AsyncBufReaderJsonFeeder<T>, S>,
+    done: bool,
+}
+
+impl<T, S> JsonEventStream<T, S> {
+    /// Create a new stream that reads events from the given parser
+    pub fn new(parser: JsonParser<AsyncBufReaderJsonFeeder<T>, S>) -> Self {
+        JsonEventStream {
+            parser,
+            done: false,
+        }
+    }
+
+    /// Get the underlying parser
+    pub fn parser(&self) -> &JsonParser<AsyncBufReaderJsonFeeder<T>, S> {
+        &self.parser
+    }
+
+    /// Consume the stream and return the underlying parser
+    pub fn into_inner(self) -> JsonParser<AsyncBufReaderJsonFeeder<T>, S> {
+        self.parser
+    }
+}
+
+impl<T, S> Stream for JsonEventStream<T, S>
+where
+    T: AsyncRead + Unpin,
+    S: ParserStorage + Unpin,
+{
+    type Item = Result<OwnedJsonEvent, JsonEventStreamError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        loop {
+            let r = match this.parser.next_owned_event() {
+                Ok(Some(OwnedJsonEvent::NeedMoreInput)) => {
+                    match this.parser.feeder.poll_fill_buf(cx) {
+                        Poll::Ready(Ok(())) => continue,
+                        Poll::Ready(Err(e)) => Some(Err(e.into())),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                Ok(Some(e)) => return Poll::Ready(Some(Ok(e))),
+                Ok(None) => None,
+                Err(e) => Some(Err(e.into())),
+            };
+            this.done = true;
+            return Poll::Ready(r);
+        }
+    }
+}
+
+impl<T, S> FusedStream for JsonEventStream<T, S>
+where
+    T: AsyncRead + Unpin,
+    S: ParserStorage + Unpin,
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
@@ -0,0 +1,972 @@
+//! Filter and rewrite events on their way to a [`JsonEventSink`].
+//!
+//! Each adapter in this module wraps another sink and implements
+//! [`JsonEventSink`] itself, so adapters can be nested to build a pipeline:
+//!
+//! - [`DropFields`] removes fields (or array elements) selected by name or
+//!   by [`PathPattern`]
+//! - [`RenameKeys`] renames fields
+//! - [`Redact`] replaces selected values with a string
+//! - [`LimitDepth`] replaces objects and arrays nested too deeply with empty
+//!   ones
+//! - [`MergePatch`] applies a JSON Merge Patch (RFC 7386)
+//!
+//! ```
+//! use actson::feeder::{JsonFeeder, SliceJsonFeeder};
+//! use actson::sink::{parse_with, JsonEventSink};
+//! use actson::storage::ParserStorage;
+//! use actson::transform::{DropFields, FieldSelector, Redact, RenameKeys};
+//! use actson::{JsonEvent, JsonParser};
+//!
+//! /// Collects the values of all field names and strings
+//! #[derive(Default)]
+//! struct Strings(Vec<String>);
+//!
+//! impl JsonEventSink for Strings {
+//!     type Error = std::convert::Infallible;
+//!
+//!     fn on_event<T, S>(&mut self, event: JsonEvent, parser: &JsonParser<T, S>) -> Result<(), Self::Error>
+//!     where
+//!         T: JsonFeeder,
+//!         S: ParserStorage,
+//!     {
+//!         if matches!(event, JsonEvent::FieldName | JsonEvent::ValueString) {
+//!             self.0.push(parser.current_str().unwrap().to_string());
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let json = r#"{"user": "elvis", "password": "secret", "token": "abc"}"#.as_bytes();
+//! let mut sink = DropFields::new(
+//!     RenameKeys::new(
+//!         Redact::new(Strings::default(), [FieldSelector::name("token")], "***"),
+//!         [("user", "name")],
+//!     ),
+//!     [FieldSelector::name("password")],
+//! );
+//! parse_with(SliceJsonFeeder::new(json), &mut sink).unwrap();
+//!
+//! let strings = sink.into_inner().into_inner().into_inner().0;
+//! assert_eq!(strings, vec!["name", "elvis", "token", "***"]);
+//! ```
+//!
+//! Events with replaced values (renamed field names, redacted or patched
+//! values) are forwarded together with a separate parser that holds the new
+//! value, so [`JsonParser::current_span()`] is not meaningful for them.
+
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    escape::escape_str,
+    feeder::JsonFeeder,
+    path::{Location, PathPattern, PathPatternError},
+    sink::{forward_json, forward_replaced, forward_str, JsonEventSink},
+    storage::ParserStorage,
+    value::JsonValue,
+    JsonEvent, JsonParser,
+};
+
+/// Selects the values an adapter applies to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldSelector {
+    /// The value of any field with the given name, regardless of where the
+    /// object containing it is located
+    Name(String),
+
+    /// Values whose location matches the given pattern
+    Path(PathPattern),
+}
+
+impl FieldSelector {
+    /// Select the value of any field with the given name
+    pub fn name(name: impl Into<String>) -> Self {
+        FieldSelector::Name(name.into())
+    }
+
+    /// Parse a selector from a string as found in configuration files.
+    /// Strings starting with `$` or `/` are parsed as a [`PathPattern`].
+    /// Strings containing `.` are parsed as a dotted path relative to the
+    /// root in which `*` matches any field or array element (e.g.
+    /// `*.token` selects `token` in any object directly inside the root).
+    /// All other strings select fields with that name anywhere in the JSON
+    /// text (e.g. `password`).
+    ///
+    /// ```
+    /// use actson::emitter::JsonEmitter;
+    /// use actson::feeder::SliceJsonFeeder;
+    /// use actson::sink::parse_with;
+    /// use actson::transform::{FieldSelector, Redact};
+    ///
+    /// let selectors = ["password", "*.token"]
+    ///     .into_iter()
+    ///     .map(FieldSelector::parse)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    ///
+    /// let json = br#"{"user": {"password": "x", "token": "y"}, "token": "z"}"#;
+    /// let mut sink = Redact::new(JsonEmitter::new(Vec::new()), selectors, "***");
+    /// parse_with(SliceJsonFeeder::new(json), &mut sink).unwrap();
+    ///
+    /// let out = sink.into_inner().finish().unwrap();
+    /// assert_eq!(out, br#"{"user":{"password":"***","token":"***"},"token":"z"}"#);
+    /// ```
+    pub fn parse(selector: &str) -> Result<Self, PathPatternError> {
+        if selector.starts_with('$') || selector.starts_with('/') {
+            Ok(FieldSelector::Path(PathPattern::parse(selector)?))
+        } else if selector.contains('.') {
+            Ok(FieldSelector::Path(PathPattern::parse(&format!(
+                "$.{selector}"
+            ))?))
+        } else {
+            Ok(FieldSelector::name(selector))
+        }
+    }
+}
+
+impl FromStr for FieldSelector {
+    type Err = PathPatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<PathPattern> for FieldSelector {
+    fn from(pattern: PathPattern) -> Self {
+        FieldSelector::Path(pattern)
+    }
+}
+
+/// An object or array that is currently open
+struct Frame {
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The number of elements seen so far if the container is an array
+    len: usize,
+
+    /// The most recent field name if the container is an object
+    key: Option<String>,
+}
+
+/// Keeps track of the location of the current value
+#[derive(Default)]
+struct PathTracker {
+    stack: Vec<Frame>,
+}
+
+impl PathTracker {
+    /// Record the name of the field that has just been parsed
+    fn field_name<T, S>(&mut self, parser: &JsonParser<T, S>)
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if let Some(frame) = self.stack.last_mut() {
+            match parser.current_str() {
+                Ok(name) => {
+                    let key = frame.key.get_or_insert_with(String::new);
+                    key.clear();
+                    key.push_str(name);
+                }
+                Err(_) => frame.key = None,
+            }
+        }
+    }
+
+    /// Advance to the next element if the current container is an array.
+    /// Call this when a value starts.
+    fn start_value(&mut self) {
+        if let Some(frame) = self.stack.last_mut() {
+            if frame.is_array {
+                frame.len += 1;
+            }
+        }
+    }
+
+    /// Enter the object or array that has just started
+    fn push(&mut self, event: JsonEvent) {
+        self.stack.push(Frame {
+            is_array: event == JsonEvent::StartArray,
+            len: 0,
+            key: None,
+        });
+    }
+
+    /// Leave the object or array that has just ended
+    fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Returns `true` if the current value is a field of an object
+    fn in_object(&self) -> bool {
+        self.stack.last().is_some_and(|f| !f.is_array)
+    }
+
+    /// Returns `true` if the current value (or the value of the field that
+    /// has just been parsed) is selected by one of the given selectors
+    fn matches(&self, selectors: &[FieldSelector]) -> bool {
+        selectors.iter().any(|s| match s {
+            FieldSelector::Name(name) => self
+                .stack
+                .last()
+                .is_some_and(|f| !f.is_array && f.key.as_ref() == Some(name)),
+            FieldSelector::Path(pattern) => pattern.matches_path(self.stack.iter().map(|f| {
+                if f.is_array {
+                    Location::Index(f.len - 1)
+                } else {
+                    Location::Key(f.key.as_deref())
+                }
+            })),
+        })
+    }
+}
+
+/// Counts the open objects and arrays of a value that is being skipped
+#[derive(Default)]
+struct Skip {
+    /// `true` if the next value should be skipped
+    pending: bool,
+
+    /// The number of open objects and arrays inside the skipped value
+    depth: usize,
+}
+
+impl Skip {
+    /// Skip the value that starts with the given event
+    fn start(&mut self, event: JsonEvent) {
+        if event.is_container_start() {
+            self.depth = 1;
+        }
+    }
+
+    /// Returns `true` if the given event belongs to a skipped value
+    fn skip(&mut self, event: JsonEvent) -> bool {
+        if event == JsonEvent::NeedMoreInput {
+            false
+        } else if self.pending {
+            self.pending = false;
+            self.start(event);
+            true
+        } else if self.depth > 0 {
+            if event.is_container_start() {
+                self.depth += 1;
+            } else if event.is_container_end() {
+                self.depth -= 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Removes selected fields and array elements before forwarding events to
+/// another sink. See the [module documentation](self).
+pub struct DropFields<K> {
+    inner: K,
+    selectors: Vec<FieldSelector>,
+    tracker: PathTracker,
+    skip: Skip,
+}
+
+impl<K> DropFields<K> {
+    /// Create an adapter that forwards all events to `inner` except those of
+    /// the values selected by the given selectors. For fields, the field
+    /// name is removed too.
+    pub fn new(inner: K, selectors: impl IntoIterator<Item = FieldSelector>) -> Self {
+        DropFields {
+            inner,
+            selectors: selectors.into_iter().collect(),
+            tracker: PathTracker::default(),
+            skip: Skip::default(),
+        }
+    }
+
+    /// Get the wrapped sink
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Consume the adapter and return the wrapped sink
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+impl<K> JsonEventSink for DropFields<K>
+where
+    K: JsonEventSink,
+{
+    type Error = K::Error;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), K::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if self.skip.skip(event) {
+            return Ok(());
+        }
+
+        match event {
+            JsonEvent::NeedMoreInput => {}
+
+            JsonEvent::FieldName => {
+                self.tracker.field_name(parser);
+                if self.tracker.matches(&self.selectors) {
+                    self.skip.pending = true;
+                    return Ok(());
+                }
+            }
+
+            JsonEvent::EndObject | JsonEvent::EndArray => self.tracker.pop(),
+
+            _ => {
+                self.tracker.start_value();
+                // fields have already been checked when their names were parsed
+                if !self.tracker.in_object() && self.tracker.matches(&self.selectors) {
+                    self.skip.start(event);
+                    return Ok(());
+                }
+                if event.is_container_start() {
+                    self.tracker.push(event);
+                }
+            }
+        }
+
+        self.inner.on_event(event, parser)
+    }
+}
+
+/// Renames fields before forwarding events to another sink. See the
+/// [module documentation](self).
+pub struct RenameKeys<K> {
+    inner: K,
+    names: HashMap<String, String>,
+    buf: Vec<u8>,
+}
+
+impl<K> RenameKeys<K> {
+    /// Create an adapter that forwards all events to `inner` but renames
+    /// fields according to the given pairs of old and new names. Fields are
+    /// renamed regardless of where the object containing them is located.
+    pub fn new<O, N>(inner: K, names: impl IntoIterator<Item = (O, N)>) -> Self
+    where
+        O: Into<String>,
+        N: Into<String>,
+    {
+        RenameKeys {
+            inner,
+            names: names
+                .into_iter()
+                .map(|(o, n)| (o.into(), n.into()))
+                .collect(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get the wrapped sink
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Consume the adapter and return the wrapped sink
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+impl<K> JsonEventSink for RenameKeys<K>
+where
+    K: JsonEventSink,
+{
+    type Error = K::Error;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), K::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if event == JsonEvent::FieldName {
+            if let Some(new_name) = parser.current_str().ok().and_then(|n| self.names.get(n)) {
+                return forward_str(&mut self.inner, event, new_name, &mut self.buf);
+            }
+        }
+        self.inner.on_event(event, parser)
+    }
+}
+
+/// Replaces selected values with a string before forwarding events to
+/// another sink. See the [module documentation](self).
+pub struct Redact<K> {
+    inner: K,
+    selectors: Vec<FieldSelector>,
+    replacement: Vec<u8>,
+    tracker: PathTracker,
+    skip: Skip,
+}
+
+impl<K> Redact<K> {
+    /// Create an adapter that forwards all events to `inner` but replaces
+    /// the values selected by the given selectors with the given string.
+    /// Objects and arrays are replaced as a whole.
+    pub fn new(
+        inner: K,
+        selectors: impl IntoIterator<Item = FieldSelector>,
+        replacement: &str,
+    ) -> Self {
+        let mut buf = Vec::new();
+        escape_str(replacement, &mut buf);
+        Redact {
+            inner,
+            selectors: selectors.into_iter().collect(),
+            replacement: buf,
+            tracker: PathTracker::default(),
+            skip: Skip::default(),
+        }
+    }
+
+    /// Get the wrapped sink
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Consume the adapter and return the wrapped sink
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+impl<K> JsonEventSink for Redact<K>
+where
+    K: JsonEventSink,
+{
+    type Error = K::Error;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), K::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if self.skip.skip(event) {
+            return Ok(());
+        }
+
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::FieldName => self.tracker.field_name(parser),
+            JsonEvent::EndObject | JsonEvent::EndArray => self.tracker.pop(),
+            _ => {
+                self.tracker.start_value();
+                if self.tracker.matches(&self.selectors) {
+                    self.skip.start(event);
+                    return forward_replaced(
+                        &mut self.inner,
+                        JsonEvent::ValueString,
+                        &self.replacement,
+                    );
+                }
+                if event.is_container_start() {
+                    self.tracker.push(event);
+                }
+            }
+        }
+
+        self.inner.on_event(event, parser)
+    }
+}
+
+/// Replaces objects and arrays nested deeper than a given limit with empty
+/// ones before forwarding events to another sink. See the [module
+/// documentation](self).
+pub struct LimitDepth<K> {
+    inner: K,
+    max_depth: usize,
+    depth: usize,
+    skip: Skip,
+}
+
+impl<K> LimitDepth<K> {
+    /// Create an adapter that forwards all events to `inner` but only keeps
+    /// the contents of the outermost `max_depth` levels of objects and
+    /// arrays. Deeper objects and arrays are replaced with empty ones. With
+    /// a limit of 0, only empty objects and arrays remain.
+    pub fn new(inner: K, max_depth: usize) -> Self {
+        LimitDepth {
+            inner,
+            max_depth,
+            depth: 0,
+            skip: Skip::default(),
+        }
+    }
+
+    /// Get the wrapped sink
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Consume the adapter and return the wrapped sink
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+impl<K> JsonEventSink for LimitDepth<K>
+where
+    K: JsonEventSink,
+{
+    type Error = K::Error;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), K::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if self.skip.skip(event) {
+            return Ok(());
+        }
+
+        match event {
+            JsonEvent::StartObject | JsonEvent::StartArray if self.depth == self.max_depth => {
+                self.skip.start(event);
+                self.inner.on_event(event, parser)?;
+                let end = if event == JsonEvent::StartObject {
+                    JsonEvent::EndObject
+                } else {
+                    JsonEvent::EndArray
+                };
+                return self.inner.on_event(end, parser);
+            }
+            JsonEvent::StartObject | JsonEvent::StartArray => self.depth += 1,
+            JsonEvent::EndObject | JsonEvent::EndArray => self.depth -= 1,
+            _ => {}
+        }
+
+        self.inner.on_event(event, parser)
+    }
+}
+
+/// What a merge patch does with a value
+enum PatchOp {
+    /// Remove the field
+    Remove,
+
+    /// Merge the members of the object at the given index in
+    /// [`MergePatch::objects`] into the value if it is an object. Otherwise,
+    /// replace the value with the given JSON text.
+    Merge(usize, Vec<u8>),
+
+    /// Replace the value with the given JSON text
+    Replace(Vec<u8>),
+}
+
+/// An object in a merge patch
+struct PatchObject {
+    members: Vec<(String, PatchOp)>,
+}
+
+/// An object of the document that is currently open
+struct PatchFrame {
+    /// The index of the patch object in [`MergePatch::objects`] that applies
+    /// to this object or `None` if it is not patched (or an array)
+    object: Option<usize>,
+
+    /// Whether each member of the patch object has been applied
+    applied: Vec<bool>,
+
+    /// The index of the patch member that applies to the value of the field
+    /// that has just been parsed
+    pending: Option<usize>,
+}
+
+/// Applies a JSON Merge Patch (RFC 7386) before forwarding events to
+/// another sink. Only the patch is kept in memory, so documents of any size
+/// can be patched while they are being parsed. See the [module
+/// documentation](self).
+///
+/// ```
+/// use actson::emitter::{transcode_with, JsonEmitter};
+/// use actson::feeder::SliceJsonFeeder;
+/// use actson::transform::MergePatch;
+/// use actson::value::JsonValue;
+///
+/// let json = br#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}}"#;
+/// let patch = JsonValue::parse(br#"{"title": "Hello!", "author": {"familyName": null}, "tags": ["x"]}"#).unwrap();
+/// let sink = MergePatch::new(JsonEmitter::new(Vec::new()), &patch);
+/// let sink = transcode_with(SliceJsonFeeder::new(json), sink).unwrap();
+/// let out = sink.into_inner().finish().unwrap();
+/// assert_eq!(out, br#"{"title":"Hello!","author":{"givenName":"John"},"tags":["x"]}"#);
+/// ```
+///
+/// Members added by the patch are appended to the end of their object.
+pub struct MergePatch<K> {
+    inner: K,
+
+    /// The operation applied to each top-level value
+    root: PatchOp,
+
+    /// All objects of the patch
+    objects: Vec<PatchObject>,
+
+    stack: Vec<PatchFrame>,
+    skip: Skip,
+    buf: Vec<u8>,
+}
+
+impl<K> MergePatch<K> {
+    /// Create an adapter that forwards all events to `inner` but applies
+    /// the given merge patch to each top-level value
+    pub fn new(inner: K, patch: &JsonValue) -> Self {
+        let mut objects = Vec::new();
+        let root = match patch {
+            JsonValue::Null => PatchOp::Replace(b"null".to_vec()),
+            _ => compile_patch(patch, &mut objects),
+        };
+        MergePatch {
+            inner,
+            root,
+            objects,
+            stack: Vec::new(),
+            skip: Skip::default(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get the wrapped sink
+    pub fn inner(&self) -> &K {
+        &self.inner
+    }
+
+    /// Consume the adapter and return the wrapped sink
+    pub fn into_inner(self) -> K {
+        self.inner
+    }
+}
+
+/// Convert a patch value to the operation it stands for and collect its
+/// objects
+fn compile_patch(patch: &JsonValue, objects: &mut Vec<PatchObject>) -> PatchOp {
+    match patch {
+        JsonValue::Null => PatchOp::Remove,
+        JsonValue::Object(o) => {
+            let i = objects.len();
+            objects.push(PatchObject {
+                members: Vec::new(),
+            });
+            let members = o
+                .iter()
+                .map(|(name, v)| (name.to_string(), compile_patch(v, objects)))
+                .collect();
+            objects[i].members = members;
+            PatchOp::Merge(i, without_nulls(patch).to_string().into_bytes())
+        }
+        _ => PatchOp::Replace(patch.to_string().into_bytes()),
+    }
+}
+
+/// Apply a patch to an empty object, which removes all members that are
+/// `null` from the patch's objects
+fn without_nulls(patch: &JsonValue) -> JsonValue {
+    match patch {
+        JsonValue::Object(o) => JsonValue::Object(
+            o.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(name, v)| (name, without_nulls(v)))
+                .collect(),
+        ),
+        _ => patch.clone(),
+    }
+}
+
+impl<K> JsonEventSink for MergePatch<K>
+where
+    K: JsonEventSink,
+{
+    type Error = K::Error;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), K::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        if self.skip.skip(event) {
+            return Ok(());
+        }
+
+        match event {
+            JsonEvent::NeedMoreInput => {}
+
+            JsonEvent::FieldName => {
+                let Some(frame) = self.stack.last_mut() else {
+                    return self.inner.on_event(event, parser);
+                };
+                frame.pending = None;
+                let Some(object) = frame.object else {
+                    return self.inner.on_event(event, parser);
+                };
+                let name = parser.current_str().ok();
+                let members = &self.objects[object].members;
+                if let Some(i) = members.iter().position(|(n, _)| Some(n.as_str()) == name) {
+                    frame.applied[i] = true;
+                    if matches!(members[i].1, PatchOp::Remove) {
+                        self.skip.pending = true;
+                        return Ok(());
+                    }
+                    frame.pending = Some(i);
+                }
+            }
+
+            JsonEvent::EndObject => {
+                if let Some(frame) = self.stack.pop() {
+                    if let Some(object) = frame.object {
+                        let members = &self.objects[object].members;
+                        for ((name, op), applied) in members.iter().zip(frame.applied) {
+                            let json = match op {
+                                PatchOp::Merge(_, json) | PatchOp::Replace(json) if !applied => {
+                                    json
+                                }
+                                _ => continue,
+                            };
+                            forward_str(
+                                &mut self.inner,
+                                JsonEvent::FieldName,
+                                name,
+                                &mut self.buf,
+                            )?;
+                            forward_json(&mut self.inner, json)?;
+                        }
+                    }
+                }
+            }
+
+            JsonEvent::EndArray => {
+                self.stack.pop();
+            }
+
+            _ => {
+                let op = match self.stack.last_mut() {
+                    None => Some(&self.root),
+                    Some(frame) => frame
+                        .pending
+                        .take()
+                        .zip(frame.object)
+                        .map(|(i, object)| &self.objects[object].members[i].1),
+                };
+                let object = match op {
+                    Some(PatchOp::Merge(object, _)) if event == JsonEvent::StartObject => {
+                        Some(*object)
+                    }
+                    Some(PatchOp::Merge(_, json) | PatchOp::Replace(json)) => {
+                        self.skip.start(event);
+                        return forward_json(&mut self.inner, json);
+                    }
+                    _ => None,
+                };
+                if event.is_container_start() {
+                    self.stack.push(PatchFrame {
+                        object,
+                        applied: vec![false; object.map_or(0, |o| self.objects[o].members.len())],
+                        pending: None,
+                    });
+                }
+            }
+        }
+
+        self.inner.on_event(event, parser)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::Infallible;
+
+    use crate::{
+        emitter::JsonEmitter,
+        feeder::{JsonFeeder, SliceJsonFeeder},
+        sink::{parse_with, JsonEventSink},
+        storage::ParserStorage,
+        value::JsonValue,
+        JsonEvent, JsonParser,
+    };
+
+    use super::{DropFields, FieldSelector, LimitDepth, MergePatch, Redact};
+
+    /// Collects descriptions of all events without their spans
+    #[derive(Default)]
+    struct Collect(Vec<String>);
+
+    impl JsonEventSink for Collect {
+        type Error = Infallible;
+
+        fn on_event<T, S>(
+            &mut self,
+            event: JsonEvent,
+            parser: &JsonParser<T, S>,
+        ) -> Result<(), Infallible>
+        where
+            T: JsonFeeder,
+            S: ParserStorage,
+        {
+            let d = parser.describe_event(event).to_string();
+            self.0.push(d.split(" at ").next().unwrap().to_string());
+            Ok(())
+        }
+    }
+
+    fn path(p: &str) -> FieldSelector {
+        FieldSelector::Path(p.parse().unwrap())
+    }
+
+    /// Test that fields and array elements can be dropped by path
+    #[test]
+    fn drop_by_path() {
+        let json = br#"{"a": [1, {"b": 2}, 3], "b": 4}"#;
+        let mut sink = DropFields::new(Collect::default(), [path("$.a[1]"), path("$.b")]);
+        parse_with(SliceJsonFeeder::new(json), &mut sink).unwrap();
+        assert_eq!(
+            sink.into_inner().0,
+            vec![
+                "StartObject",
+                "FieldName \"a\"",
+                "StartArray",
+                "ValueInt 1",
+                "ValueInt 3",
+                "EndArray",
+                "EndObject"
+            ]
+        );
+    }
+
+    /// Test that nested objects and arrays are redacted as a whole
+    #[test]
+    fn redact_container() {
+        let json = br#"[{"secret": {"x": [1]}}, {"secret": null}]"#;
+        let mut sink = Redact::new(Collect::default(), [FieldSelector::name("secret")], "-");
+        parse_with(SliceJsonFeeder::new(json), &mut sink).unwrap();
+        assert_eq!(
+            sink.into_inner().0,
+            vec![
+                "StartArray",
+                "StartObject",
+                "FieldName \"secret\"",
+                "ValueString \"-\"",
+                "EndObject",
+                "StartObject",
+                "FieldName \"secret\"",
+                "ValueString \"-\"",
+                "EndObject",
+                "EndArray"
+            ]
+        );
+    }
+
+    /// Test that selectors are parsed from names, dotted paths, JSONPath
+    /// expressions, and JSON Pointers
+    #[test]
+    fn parse_selector() {
+        assert_eq!(
+            "password".parse::<FieldSelector>().unwrap(),
+            FieldSelector::name("password")
+        );
+        assert_eq!(
+            "*.token".parse::<FieldSelector>().unwrap(),
+            path("$.*.token")
+        );
+        assert_eq!("a.b".parse::<FieldSelector>().unwrap(), path("$.a.b"));
+        assert_eq!("$.a[0]".parse::<FieldSelector>().unwrap(), path("$.a[0]"));
+        assert_eq!("/a/0".parse::<FieldSelector>().unwrap(), path("/a/0"));
+        assert!("a..b".parse::<FieldSelector>().is_err());
+    }
+
+    /// Test that deeply nested objects and arrays are replaced with empty ones
+    #[test]
+    fn limit_depth() {
+        let json = br#"{"a": {"b": [1]}, "c": [2, []]}"#;
+        let mut sink = LimitDepth::new(Collect::default(), 1);
+        parse_with(SliceJsonFeeder::new(json), &mut sink).unwrap();
+        assert_eq!(
+            sink.into_inner().0,
+            vec![
+                "StartObject",
+                "FieldName \"a\"",
+                "StartObject",
+                "EndObject",
+                "FieldName \"c\"",
+                "StartArray",
+                "EndArray",
+                "EndObject"
+            ]
+        );
+    }
+
+    /// Test the examples from Appendix A of RFC 7386
+    #[test]
+    fn merge_patch() {
+        let merge = |doc: &str, patch: &str| {
+            let patch = JsonValue::parse(patch.as_bytes()).unwrap();
+            let mut sink = MergePatch::new(JsonEmitter::new(Vec::new()), &patch);
+            parse_with(SliceJsonFeeder::new(doc.as_bytes()), &mut sink).unwrap();
+            String::from_utf8(sink.into_inner().finish().unwrap()).unwrap()
+        };
+
+        assert_eq!(merge(r#"{"a":"b"}"#, r#"{"a":"c"}"#), r#"{"a":"c"}"#);
+        assert_eq!(
+            merge(r#"{"a":"b"}"#, r#"{"b":"c"}"#),
+            r#"{"a":"b","b":"c"}"#
+        );
+        assert_eq!(merge(r#"{"a":"b"}"#, r#"{"a":null}"#), r#"{}"#);
+        assert_eq!(
+            merge(r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#),
+            r#"{"b":"c"}"#
+        );
+        assert_eq!(merge(r#"{"a":["b"]}"#, r#"{"a":"c"}"#), r#"{"a":"c"}"#);
+        assert_eq!(merge(r#"{"a":"c"}"#, r#"{"a":["b"]}"#), r#"{"a":["b"]}"#);
+        assert_eq!(
+            merge(r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#),
+            r#"{"a":{"b":"d"}}"#
+        );
+        assert_eq!(
+            merge(r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#),
+            r#"{"a":[1]}"#
+        );
+        assert_eq!(merge(r#"["a","b"]"#, r#"["c","d"]"#), r#"["c","d"]"#);
+        assert_eq!(merge(r#"{"a":"b"}"#, r#"["c"]"#), r#"["c"]"#);
+        assert_eq!(merge(r#"{"a":"foo"}"#, "null"), "null");
+        assert_eq!(merge(r#"{"a":"foo"}"#, r#""bar""#), r#""bar""#);
+        assert_eq!(merge(r#"{"e":null}"#, r#"{"a":1}"#), r#"{"e":null,"a":1}"#);
+        assert_eq!(merge(r#"[1,2]"#, r#"{"a":"b","c":null}"#), r#"{"a":"b"}"#);
+        assert_eq!(
+            merge(r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#),
+            r#"{"a":{"bb":{}}}"#
+        );
+        assert_eq!(
+            merge(
+                r#"{"a":{"x":[{"y":1}]},"b":2}"#,
+                r#"{"a":{"z":1},"b":null}"#
+            ),
+            r#"{"a":{"x":[{"y":1}],"z":1}}"#
+        );
+    }
+}
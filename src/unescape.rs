@@ -0,0 +1,154 @@
+/// Decode the escape sequences in the given string token and append the
+/// result to `out`. The token must not contain the surrounding quotes and
+/// must have been accepted by the parser's state machine, which guarantees
+/// that every backslash starts a complete and well-formed escape sequence.
+///
+/// Unpaired surrogates (e.g. `\uD800` not followed by a low surrogate) cannot
+/// be represented in UTF-8 and are replaced with U+FFFD REPLACEMENT CHARACTER.
+pub(crate) fn unescape(raw: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < raw.len() {
+        // copy everything up to the next escape sequence
+        let start = i;
+        while i < raw.len() && raw[i] != b'\\' {
+            i += 1;
+        }
+        out.extend_from_slice(&raw[start..i]);
+        if i == raw.len() {
+            break;
+        }
+
+        let (decoded, len, consumed) = decode_escape(&raw[i..]);
+        out.extend_from_slice(&decoded[..len]);
+        i += consumed;
+    }
+}
+
+/// Decode the escape sequences in the given string token in place (see
+/// [`unescape()`]) and return the length of the decoded string. This is
+/// possible because a decoded escape sequence is never longer than the
+/// escape sequence itself.
+pub(crate) fn unescape_in_place(buf: &mut [u8]) -> usize {
+    let mut r = 0;
+    let mut w = 0;
+    while r < buf.len() {
+        if buf[r] != b'\\' {
+            buf[w] = buf[r];
+            r += 1;
+            w += 1;
+        } else {
+            let (decoded, len, consumed) = decode_escape(&buf[r..]);
+            buf[w..w + len].copy_from_slice(&decoded[..len]);
+            r += consumed;
+            w += len;
+        }
+    }
+    w
+}
+
+/// Decode the escape sequence at the beginning of `raw`. Return the decoded
+/// bytes, the number of decoded bytes, and the number of bytes consumed
+/// from `raw`.
+fn decode_escape(raw: &[u8]) -> ([u8; 4], usize, usize) {
+    let mut decoded = [0; 4];
+    let c = raw[1];
+    let b = match c {
+        b'b' => 0x08,
+        b'f' => 0x0c,
+        b'n' => b'\n',
+        b'r' => b'\r',
+        b't' => b'\t',
+        b'u' => {
+            let mut consumed = 6;
+            let mut cp = hex4(&raw[2..6]);
+            if (0xD800..0xDC00).contains(&cp) {
+                // high surrogate: try to combine it with a following low
+                // surrogate
+                let low = if raw.len() >= 12 && raw[6] == b'\\' && raw[7] == b'u' {
+                    Some(hex4(&raw[8..12]))
+                } else {
+                    None
+                };
+                match low {
+                    Some(low) if (0xDC00..0xE000).contains(&low) => {
+                        cp = 0x10000 + ((cp - 0xD800) << 10) + (low - 0xDC00);
+                        consumed = 12;
+                    }
+                    _ => cp = 0xFFFD,
+                }
+            } else if (0xDC00..0xE000).contains(&cp) {
+                cp = 0xFFFD;
+            }
+            let ch = char::from_u32(cp).unwrap_or(char::REPLACEMENT_CHARACTER);
+            let len = ch.encode_utf8(&mut decoded).len();
+            return (decoded, len, consumed);
+        }
+        // '"', '\\', '/'
+        c => c,
+    };
+    decoded[0] = b;
+    (decoded, 1, 2)
+}
+
+/// Convert four hexadecimal digits to a number
+fn hex4(digits: &[u8]) -> u32 {
+    digits.iter().fold(0, |acc, &d| {
+        let v = match d {
+            b'0'..=b'9' => d - b'0',
+            b'a'..=b'f' => d - b'a' + 10,
+            _ => d - b'A' + 10,
+        };
+        (acc << 4) | v as u32
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{unescape, unescape_in_place};
+
+    fn unescape_str(raw: &str) -> String {
+        let mut out = Vec::new();
+        unescape(raw.as_bytes(), &mut out);
+
+        // unescaping in place must yield the same result
+        let mut buf = raw.as_bytes().to_vec();
+        let len = unescape_in_place(&mut buf);
+        assert_eq!(out, &buf[..len]);
+
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Test that strings without escape sequences are copied verbatim
+    #[test]
+    fn no_escapes() {
+        assert_eq!(unescape_str(""), "");
+        assert_eq!(unescape_str("Elvis"), "Elvis");
+        assert_eq!(unescape_str("Bj\u{0153}rn"), "Bj\u{0153}rn");
+    }
+
+    /// Test that simple escape sequences are decoded
+    #[test]
+    fn simple_escapes() {
+        assert_eq!(unescape_str(r#"\"\\\/\b\f\n\r\t"#), "\"\\/\u{8}\u{c}\n\r\t");
+        assert_eq!(unescape_str(r#"a\nb"#), "a\nb");
+    }
+
+    /// Test that unicode escape sequences including surrogate pairs are
+    /// decoded
+    #[test]
+    fn unicode_escapes() {
+        assert_eq!(unescape_str(r#"A\u00e9\u4567"#), "A\u{e9}\u{4567}");
+        assert_eq!(unescape_str(r#"\uD834\uDD1E"#), "\u{1D11E}");
+        assert_eq!(unescape_str(r#"\ud834\udd1ex"#), "\u{1D11E}x");
+    }
+
+    /// Test that unpaired surrogates are replaced
+    #[test]
+    fn unpaired_surrogates() {
+        assert_eq!(unescape_str(r#"\uD834"#), "\u{FFFD}");
+        assert_eq!(unescape_str(r#"\uD834x"#), "\u{FFFD}x");
+        assert_eq!(unescape_str(r#"\uDD1E"#), "\u{FFFD}");
+        assert_eq!(unescape_str(r#"\uD834\n"#), "\u{FFFD}\n");
+        assert_eq!(unescape_str(r#"\uD834\u0041"#), "\u{FFFD}A");
+    }
+}
@@ -0,0 +1,341 @@
+use thiserror::Error;
+
+use crate::feeder::{JsonFeeder, SliceJsonFeeder};
+use crate::options::{JsonParserOptions, JsonParserOptionsBuilder};
+use crate::parser::ParserError;
+use crate::{JsonEvent, JsonParser, Span};
+
+/// Check if the given byte slice contains a single valid JSON text. This
+/// function runs the parser in validation-only mode (see
+/// [`JsonParserOptionsBuilder::with_validate_only()`]), so it does not pay
+/// the cost of collecting string and number values. Strings that are not
+/// valid UTF-8 are reported as [`ParserError::IllegalInput`] with the first
+/// invalid byte.
+///
+/// ```
+/// use actson::validate;
+///
+/// assert!(validate(br#"{"name": "Elvis"}"#).is_ok());
+/// assert!(validate(br#"{"name": }"#).is_err());
+/// assert!(validate(b"\"\xff\"").is_err());
+/// ```
+pub fn validate(json: &[u8]) -> Result<(), ParserError> {
+    let feeder = SliceJsonFeeder::new(json);
+    let mut parser = JsonParser::new_with_options(
+        feeder,
+        JsonParserOptionsBuilder::default()
+            .with_validate_only(true)
+            .build(),
+    );
+    while let Some(e) = parser.next_event()? {
+        if matches!(e, JsonEvent::FieldName | JsonEvent::ValueString) {
+            if let Some((_, b)) = invalid_utf8(json, parser.current_span()) {
+                return Err(ParserError::IllegalInput(b));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the contents of the string at the given span (including the
+/// quotes) are valid UTF-8. Returns the offset and the value of the first
+/// invalid byte otherwise.
+fn invalid_utf8(json: &[u8], span: Span) -> Option<(usize, u8)> {
+    let contents = &json[span.start + 1..span.end - 1];
+    std::str::from_utf8(contents).err().map(|e| {
+        let offset = span.start + 1 + e.valid_up_to();
+        (offset, json[offset])
+    })
+}
+
+/// An error found by [`validate_all()`]
+#[derive(Error, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("{error} (at byte {offset})")]
+pub struct ValidationError {
+    /// The offset of the byte at which the error was detected. For
+    /// [`ParserError::NoMoreInput`], this is the length of the JSON text.
+    pub offset: usize,
+
+    /// The error
+    pub error: ParserError,
+}
+
+/// Feeds a synthetic prefix, which restores the nesting of the parser at
+/// the point where parsing resumes after an error, followed by the rest of
+/// the JSON text
+struct ResumeFeeder<'a> {
+    prefix: SliceJsonFeeder<'a>,
+    rest: SliceJsonFeeder<'a>,
+}
+
+impl JsonFeeder for ResumeFeeder<'_> {
+    fn has_input(&self) -> bool {
+        self.prefix.has_input() || self.rest.has_input()
+    }
+
+    fn is_done(&self) -> bool {
+        !self.has_input()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        self.prefix.next_input().or_else(|| self.rest.next_input())
+    }
+}
+
+/// Check the given byte slice like [`validate()`] but do not stop at the
+/// first syntax error. Instead, skip to the next comma or closing bracket
+/// on the same nesting level and continue from there, so that all errors
+/// can be reported in one pass (e.g. by an editor or a linter). Recovery is
+/// a heuristic: errors following the first one may be consequences of it.
+/// Strings that are not valid UTF-8 are reported like in [`validate()`]
+/// without ending the validation. Errors other than syntax errors (e.g. an
+/// exceeded limit) end the validation. Returns an empty vector if the JSON
+/// text is valid.
+///
+/// ```
+/// use actson::validate_all;
+///
+/// let errors = validate_all(br#"{"a": [1, 2 x], "b": tru, "c": {}}"#);
+/// assert_eq!(errors.iter().map(|e| e.offset).collect::<Vec<_>>(), vec![12, 24]);
+/// assert!(validate_all(br#"{"a": [1, 2]}"#).is_empty());
+/// ```
+pub fn validate_all(json: &[u8]) -> Vec<ValidationError> {
+    validate_all_with_options(
+        json,
+        JsonParserOptionsBuilder::default()
+            .with_validate_only(true)
+            .build(),
+    )
+}
+
+/// Like [`validate_all()`] but use the given parser options (e.g. to
+/// validate a sequence of values in streaming mode, in which case parsing
+/// resumes at the next line after an error at the top level)
+pub fn validate_all_with_options(json: &[u8], options: JsonParserOptions) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    // `true` for each open array and `false` for each open object
+    let mut stack: Vec<bool> = Vec::new();
+
+    let mut prefix = Vec::new();
+    let mut start = 0;
+    loop {
+        let mut parser = JsonParser::new_with_options(
+            ResumeFeeder {
+                prefix: SliceJsonFeeder::new(&prefix),
+                rest: SliceJsonFeeder::new(&json[start..]),
+            },
+            options,
+        );
+
+        let error = loop {
+            match parser.next_event() {
+                Ok(Some(e)) => {
+                    let span = parser.current_span();
+                    if span.start < prefix.len() {
+                        continue;
+                    }
+                    match e {
+                        JsonEvent::FieldName | JsonEvent::ValueString => {
+                            let span = Span::new(
+                                start + span.start - prefix.len(),
+                                start + span.end - prefix.len(),
+                            );
+                            if let Some((offset, b)) = invalid_utf8(json, span) {
+                                errors.push(ValidationError {
+                                    offset,
+                                    error: ParserError::IllegalInput(b),
+                                });
+                            }
+                        }
+                        JsonEvent::StartArray => stack.push(true),
+                        JsonEvent::StartObject => stack.push(false),
+                        JsonEvent::EndArray | JsonEvent::EndObject => {
+                            stack.pop();
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(None) => return errors,
+                Err(e) => break e,
+            }
+        };
+
+        if matches!(error, ParserError::NoMoreInput) {
+            errors.push(ValidationError {
+                offset: json.len(),
+                error,
+            });
+            return errors;
+        }
+        let offset = (start + parser.parsed_bytes())
+            .saturating_sub(prefix.len() + 1)
+            .min(json.len());
+        errors.push(ValidationError { offset, error });
+        if !matches!(
+            error,
+            ParserError::SyntaxError | ParserError::IllegalInput(_)
+        ) {
+            return errors;
+        }
+
+        let (in_string, _) = string_state(json, start, offset);
+        let closing = matches!(json.get(offset), Some(b']' | b'}'));
+        if !in_string && closing && stack.pop().is_some() {
+            // assume that a mismatched or misplaced closing bracket closes
+            // the innermost container
+            start = offset + 1;
+        } else if stack.is_empty() {
+            // skip to the next line if there can be more values
+            let next_line = json[offset..].iter().position(|&b| b == b'\n');
+            match next_line {
+                Some(n) if options.streaming() => start = offset + n + 1,
+                _ => return errors,
+            }
+        } else {
+            // skip to the next comma or closing bracket on the same level
+            let (in_string, pos) = string_state(json, start, offset + 1);
+            let Some(sync) = find_sync_point(json, pos, in_string) else {
+                errors.push(ValidationError {
+                    offset: json.len(),
+                    error: ParserError::NoMoreInput,
+                });
+                return errors;
+            };
+            start = sync;
+        }
+        prefix = resume_prefix(&stack);
+    }
+}
+
+/// Create a JSON text that opens the given containers and puts a value
+/// into the innermost one (or a top-level value if there are no
+/// containers), so that parsing can continue with a comma, a closing
+/// bracket, or the end of the JSON text
+fn resume_prefix(stack: &[bool]) -> Vec<u8> {
+    let mut prefix = Vec::new();
+    for &is_array in stack {
+        prefix.extend_from_slice(if is_array { b"[" } else { br#"{"":"# });
+    }
+    prefix.extend_from_slice(b"0 ");
+    prefix
+}
+
+/// Scan from `start`, which must be outside of a string, to `end` and
+/// determine if `end` is inside a string. Returns the state and the
+/// position where scanning stopped, which is after `end` if `end` is an
+/// escaped character.
+fn string_state(json: &[u8], start: usize, end: usize) -> (bool, usize) {
+    let mut in_string = false;
+    let mut pos = start;
+    while pos < end {
+        match json[pos] {
+            b'\\' if in_string => pos += 1,
+            b'"' => in_string = !in_string,
+            _ => {}
+        }
+        pos += 1;
+    }
+    (in_string || pos > end, pos)
+}
+
+/// Find the position of the next comma or closing bracket at or after
+/// `pos` (with the given string state) that is not nested in another container or string
+fn find_sync_point(json: &[u8], mut pos: usize, mut in_string: bool) -> Option<usize> {
+    let mut depth = 0usize;
+    while pos < json.len() {
+        match json[pos] {
+            b'\\' if in_string => pos += 1,
+            b'"' => in_string = !in_string,
+            _ if in_string => {}
+            b'[' | b'{' => depth += 1,
+            b',' | b']' | b'}' if depth == 0 => return Some(pos),
+            b']' | b'}' => depth -= 1,
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::options::JsonParserOptionsBuilder;
+    use crate::parser::ParserError;
+    use crate::{validate, validate_all, validate_all_with_options};
+
+    /// Test that valid JSON texts are accepted
+    #[test]
+    fn valid() {
+        assert!(validate(b"{}").is_ok());
+        assert!(validate(br#"["Elvis", 132, "Max", 80.67, true, null]"#).is_ok());
+        assert!(validate(br#"{"a": {"b": [1, 2, {"c": "A\n"}]}}"#).is_ok());
+    }
+
+    /// Test that invalid JSON texts are rejected
+    #[test]
+    fn invalid() {
+        assert!(matches!(validate(b"{key}"), Err(ParserError::SyntaxError)));
+        assert!(matches!(validate(b"[1, 2"), Err(ParserError::NoMoreInput)));
+        assert!(matches!(validate(b""), Err(ParserError::NoMoreInput)));
+        assert!(matches!(validate(b"1 2"), Err(ParserError::SyntaxError)));
+    }
+
+    /// Test that parsing resumes after errors in nested containers, that
+    /// mismatched brackets close the inner containers, and that unclosed
+    /// containers are reported at the end
+    #[test]
+    fn all_errors() {
+        let offsets = |json: &[u8]| {
+            validate_all(json)
+                .into_iter()
+                .map(|e| (e.offset, matches!(e.error, ParserError::NoMoreInput)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            offsets(br#"[1, {"a": 1], 2, [,], "\x"]"#),
+            vec![(11, false), (18, false), (24, false)]
+        );
+        assert_eq!(
+            offsets(br#"[{"a" 1}, {"b": "}", 3}, [4"#),
+            vec![(6, false), (21, false), (27, true)]
+        );
+        assert_eq!(offsets(b"[1, 2"), vec![(5, true)]);
+        assert_eq!(offsets(b"1 2 3"), vec![(2, false)]);
+
+        let errors = validate_all_with_options(
+            b"{\"a\": 1}\n{\"a\" 2}\n[3]\n{]\n[true]",
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        assert_eq!(
+            errors.iter().map(|e| e.offset).collect::<Vec<_>>(),
+            vec![14, 22]
+        );
+    }
+
+    /// Test that strings that are not valid UTF-8 are rejected by both
+    /// entry points
+    #[test]
+    fn invalid_utf8() {
+        assert!(validate("[\"Grüße\", {\"€\": \"🎸\"}]".as_bytes()).is_ok());
+        assert!(matches!(
+            validate(b"\"\xff\""),
+            Err(ParserError::IllegalInput(0xff))
+        ));
+        assert!(matches!(
+            validate(b"{\"a\xc3\": 1}"),
+            Err(ParserError::IllegalInput(0xc3))
+        ));
+
+        let errors = validate_all(b"[\"\xff\", {\"ab\xe2\x82\": 1}, \"ok\"]");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].offset, 2);
+        assert!(matches!(errors[0].error, ParserError::IllegalInput(0xff)));
+        assert_eq!(errors[1].offset, 10);
+        assert!(matches!(errors[1].error, ParserError::IllegalInput(0xe2)));
+        assert!(validate_all("[\"Grüße\"]".as_bytes()).is_empty());
+    }
+}
@@ -0,0 +1,621 @@
+//! An owned JSON value that keeps the order of object members.
+//!
+//! [`JsonValue`] is a small document type built directly from parser events
+//! with a [`JsonValueBuilder`]. It does not need Serde JSON, keeps object
+//! members in the order in which they appear in the JSON text, and
+//! distinguishes integers, floats, and numbers that cannot be represented by
+//! either of them without losing information.
+//!
+//! ```
+//! use actson::value::JsonValue;
+//!
+//! let value = JsonValue::parse(br#"{"b": 1, "a": [2.5, 18446744073709551616]}"#).unwrap();
+//! assert_eq!(value.get("b"), Some(&JsonValue::Int(1)));
+//! assert_eq!(value.pointer("/a/0"), Some(&JsonValue::Float(2.5)));
+//! assert_eq!(
+//!     value.pointer("/a/1"),
+//!     Some(&JsonValue::RawNumber("18446744073709551616".to_string()))
+//! );
+//! assert_eq!(value.to_string(), r#"{"b":1,"a":[2.5,18446744073709551616]}"#);
+//! ```
+
+use std::{collections::HashMap, fmt, io::Write};
+
+use crate::{
+    emitter::{EmitterError, JsonEmitter},
+    feeder::{JsonFeeder, SliceJsonFeeder},
+    parser::NextValueError,
+    sink::JsonEventSink,
+    storage::ParserStorage,
+    JsonEvent, JsonNumber, JsonParser,
+};
+
+/// An owned JSON value. See the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum JsonValue {
+    /// `null`
+    #[default]
+    Null,
+
+    /// `true` or `false`
+    Bool(bool),
+
+    /// An integer that fits into an [`i64`]
+    Int(i64),
+
+    /// A positive integer that is too large for an [`i64`] but fits into a
+    /// [`u64`]
+    UInt(u64),
+
+    /// A floating point number
+    Float(f64),
+
+    /// A number that cannot be represented by any of the other variants
+    /// without losing information, exactly as it appeared in the JSON text
+    RawNumber(String),
+
+    /// A string
+    String(String),
+
+    /// An array
+    Array(Vec<JsonValue>),
+
+    /// An object
+    Object(JsonObject),
+}
+
+impl JsonValue {
+    /// Parse the given JSON text into a value
+    pub fn parse(json: &[u8]) -> Result<Self, NextValueError> {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        let mut builder = JsonValueBuilder::new();
+        while let Some(e) = parser.next_event()? {
+            builder.on_event(e, &parser)?;
+        }
+        builder.into_value().ok_or(NextValueError::UnexpectedEvent {
+            expected: "value",
+            found: None,
+        })
+    }
+
+    /// Get the value of the field with the given name if this is an object
+    pub fn get(&self, name: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(o) => o.get(name),
+            _ => None,
+        }
+    }
+
+    /// Get the element at the given index if this is an array
+    pub fn at(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(a) => a.get(index),
+            _ => None,
+        }
+    }
+
+    /// Navigate to the value at the given JSON Pointer (e.g. `/a/0/b`)
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |v, token| {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                match v {
+                    JsonValue::Object(o) => o.get(&token),
+                    JsonValue::Array(a) if token == "0" || !token.starts_with('0') => {
+                        a.get(token.parse::<usize>().ok()?)
+                    }
+                    _ => None,
+                }
+            })
+    }
+
+    /// Get the string if this is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the number if this is an integer that fits into an [`i64`]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            JsonValue::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Get the number if this is a non-negative integer that fits into a
+    /// [`u64`]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            JsonValue::Int(i) => i.try_into().ok(),
+            JsonValue::UInt(u) => Some(u),
+            _ => None,
+        }
+    }
+
+    /// Get the number as an [`f64`] if this is a number. Large numbers may
+    /// lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Int(i) => Some(*i as f64),
+            JsonValue::UInt(u) => Some(*u as f64),
+            JsonValue::Float(f) => Some(*f),
+            JsonValue::RawNumber(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value if this is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            JsonValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Get the elements if this is an array
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Get the members if this is an object
+    pub fn as_object(&self) -> Option<&JsonObject> {
+        match self {
+            JsonValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is `null`
+    pub fn is_null(&self) -> bool {
+        *self == JsonValue::Null
+    }
+
+    /// Write the value with the given emitter
+    pub fn emit<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        match self {
+            JsonValue::Null => emitter.value_null(),
+            JsonValue::Bool(b) => emitter.value_bool(*b),
+            JsonValue::Int(i) => emitter.value_int(*i),
+            JsonValue::UInt(u) => emitter.value_int(*u),
+            JsonValue::Float(f) => emitter.value_float(*f),
+            JsonValue::RawNumber(s) => emitter.write_raw(s),
+            JsonValue::String(s) => emitter.value_str(s),
+            JsonValue::Array(a) => {
+                emitter.start_array()?;
+                for v in a {
+                    v.emit(emitter)?;
+                }
+                emitter.end_array()
+            }
+            JsonValue::Object(o) => {
+                emitter.start_object()?;
+                for (k, v) in o {
+                    emitter.field_name(k)?;
+                    v.emit(emitter)?;
+                }
+                emitter.end_object()
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsonValue {
+    /// Write the value as compact JSON
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut emitter = JsonEmitter::new(Vec::new());
+        self.emit(&mut emitter).map_err(|_| fmt::Error)?;
+        let out = emitter.finish().map_err(|_| fmt::Error)?;
+        f.write_str(std::str::from_utf8(&out).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(i: i64) -> Self {
+        JsonValue::Int(i)
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(u: u64) -> Self {
+        match i64::try_from(u) {
+            Ok(i) => JsonValue::Int(i),
+            Err(_) => JsonValue::UInt(u),
+        }
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(f: f64) -> Self {
+        JsonValue::Float(f)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(a: Vec<JsonValue>) -> Self {
+        JsonValue::Array(a)
+    }
+}
+
+impl From<JsonObject> for JsonValue {
+    fn from(o: JsonObject) -> Self {
+        JsonValue::Object(o)
+    }
+}
+
+/// The number of members above which a [`JsonObject`] indexes its members
+/// by name. Smaller objects are searched linearly, which is faster than
+/// hashing.
+const INDEX_MIN_LEN: usize = 16;
+
+/// The members of a JSON object in the order in which they have been
+/// inserted. Names of small objects are looked up in linear time, which is
+/// faster than hashing for the small objects typically found in JSON texts.
+/// Larger objects keep an index, so that members are inserted and looked
+/// up in constant time on average.
+#[derive(Clone, Default)]
+pub struct JsonObject {
+    members: Vec<(String, JsonValue)>,
+
+    /// The positions of the members in [`Self::members`] by name if the
+    /// object has more than [`INDEX_MIN_LEN`] members
+    index: Option<HashMap<String, usize>>,
+}
+
+impl JsonObject {
+    /// Create an empty object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of members
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `true` if the object has no members
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Get the position of the member with the given name
+    fn position(&self, name: &str) -> Option<usize> {
+        match &self.index {
+            Some(index) => index.get(name).copied(),
+            None => self.members.iter().position(|(k, _)| k == name),
+        }
+    }
+
+    /// Get the value of the member with the given name
+    pub fn get(&self, name: &str) -> Option<&JsonValue> {
+        self.position(name).map(|i| &self.members[i].1)
+    }
+
+    /// Get a mutable reference to the value of the member with the given
+    /// name
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut JsonValue> {
+        self.position(name).map(|i| &mut self.members[i].1)
+    }
+
+    /// Returns `true` if the object has a member with the given name
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Add a member to the end of the object. If the object already has a
+    /// member with the given name, its value is replaced in place and the
+    /// old value is returned.
+    pub fn insert(&mut self, name: impl Into<String>, value: JsonValue) -> Option<JsonValue> {
+        let name = name.into();
+        if let Some(i) = self.position(&name) {
+            return Some(std::mem::replace(&mut self.members[i].1, value));
+        }
+        if let Some(index) = &mut self.index {
+            index.insert(name.clone(), self.members.len());
+        }
+        self.members.push((name, value));
+        if self.index.is_none() && self.members.len() > INDEX_MIN_LEN {
+            self.index = Some(
+                self.members
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (k, _))| (k.clone(), i))
+                    .collect(),
+            );
+        }
+        None
+    }
+
+    /// Remove the member with the given name and return its value. The
+    /// order of the other members is kept.
+    pub fn remove(&mut self, name: &str) -> Option<JsonValue> {
+        let i = self.position(name)?;
+        if let Some(index) = &mut self.index {
+            index.remove(name);
+            for p in index.values_mut() {
+                if *p > i {
+                    *p -= 1;
+                }
+            }
+        }
+        Some(self.members.remove(i).1)
+    }
+
+    /// Iterate over the names and values of the members in order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &JsonValue)> {
+        self.members.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Iterate over the names of the members in order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|(k, _)| k.as_str())
+    }
+}
+
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.members == other.members
+    }
+}
+
+impl fmt::Debug for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonObject")
+            .field("members", &self.members)
+            .finish()
+    }
+}
+
+impl<K> FromIterator<(K, JsonValue)> for JsonObject
+where
+    K: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, JsonValue)>>(iter: I) -> Self {
+        let mut o = JsonObject::new();
+        for (k, v) in iter {
+            o.insert(k, v);
+        }
+        o
+    }
+}
+
+impl IntoIterator for JsonObject {
+    type Item = (String, JsonValue);
+    type IntoIter = std::vec::IntoIter<(String, JsonValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.members.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonObject {
+    type Item = &'a (String, JsonValue);
+    type IntoIter = std::slice::Iter<'a, (String, JsonValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.members.iter()
+    }
+}
+
+/// A container that is currently being built
+enum Level {
+    Object(JsonObject, Option<String>),
+    Array(Vec<JsonValue>),
+}
+
+/// A [`JsonEventSink`] that builds a [`JsonValue`] from the events it
+/// receives. It can be fed the events of a single value while the rest of
+/// a JSON text is streamed. As soon as a complete value has been built,
+/// [`Self::is_done()`] returns `true` and the value can be retrieved with
+/// [`Self::take()`].
+#[derive(Default)]
+pub struct JsonValueBuilder {
+    /// The containers that are currently being built
+    stack: Vec<Level>,
+
+    /// The last complete value
+    value: Option<JsonValue>,
+}
+
+impl JsonValueBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if a complete value has been built and not taken yet
+    pub fn is_done(&self) -> bool {
+        self.stack.is_empty() && self.value.is_some()
+    }
+
+    /// Take the last complete value out of the builder, so that it can
+    /// build another one. Returns `None` if the value is not complete yet.
+    pub fn take(&mut self) -> Option<JsonValue> {
+        if self.stack.is_empty() {
+            self.value.take()
+        } else {
+            None
+        }
+    }
+
+    /// Consume the builder and return the last complete value
+    pub fn into_value(mut self) -> Option<JsonValue> {
+        self.take()
+    }
+
+    /// Add a value to the current container or make it the complete value
+    /// if there is no container
+    fn push(&mut self, value: JsonValue) {
+        match self.stack.last_mut() {
+            Some(Level::Object(o, key)) => {
+                o.insert(key.take().unwrap_or_default(), value);
+            }
+            Some(Level::Array(a)) => a.push(value),
+            None => self.value = Some(value),
+        }
+    }
+}
+
+impl JsonEventSink for JsonValueBuilder {
+    type Error = NextValueError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        let unexpected = || NextValueError::UnexpectedEvent {
+            expected: "value",
+            found: Some(event),
+        };
+        match event {
+            JsonEvent::NeedMoreInput => {}
+            JsonEvent::StartObject => self.stack.push(Level::Object(JsonObject::new(), None)),
+            JsonEvent::StartArray => self.stack.push(Level::Array(Vec::new())),
+            JsonEvent::EndObject | JsonEvent::EndArray => match self.stack.pop() {
+                Some(Level::Object(o, _)) => self.push(JsonValue::Object(o)),
+                Some(Level::Array(a)) => self.push(JsonValue::Array(a)),
+                None => return Err(unexpected()),
+            },
+            JsonEvent::FieldName => match self.stack.last_mut() {
+                Some(Level::Object(_, key)) => *key = Some(parser.current_str()?.to_string()),
+                _ => return Err(unexpected()),
+            },
+            JsonEvent::ValueString => {
+                self.push(JsonValue::String(parser.current_str()?.to_string()))
+            }
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                let v = match parser.current_number()? {
+                    JsonNumber::I64(i) => JsonValue::Int(i),
+                    JsonNumber::U64(u) => JsonValue::UInt(u),
+                    JsonNumber::F64(f) => JsonValue::Float(f),
+                    JsonNumber::Big(s) => JsonValue::RawNumber(s.to_string()),
+                };
+                self.push(v);
+            }
+            JsonEvent::ValueTrue => self.push(JsonValue::Bool(true)),
+            JsonEvent::ValueFalse => self.push(JsonValue::Bool(false)),
+            JsonEvent::ValueNull => self.push(JsonValue::Null),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{JsonObject, JsonValue};
+
+    /// Test that values of all types are built, that object members keep
+    /// their order, and that values are written back unchanged
+    #[test]
+    fn roundtrip() {
+        let json =
+            r#"{"z":[1,-2,18446744073709551615,2.5,1e400,"é",true,false,null],"a":{},"m":[]}"#;
+        let value = JsonValue::parse(json.as_bytes()).unwrap();
+        let o = value.as_object().unwrap();
+        assert_eq!(o.keys().collect::<Vec<_>>(), vec!["z", "a", "m"]);
+        assert_eq!(
+            value.get("z").unwrap().as_array().unwrap(),
+            &vec![
+                JsonValue::Int(1),
+                JsonValue::Int(-2),
+                JsonValue::UInt(18446744073709551615),
+                JsonValue::Float(2.5),
+                JsonValue::RawNumber("1e400".to_string()),
+                JsonValue::from("\u{e9}"),
+                JsonValue::Bool(true),
+                JsonValue::Bool(false),
+                JsonValue::Null,
+            ]
+        );
+        assert_eq!(
+            value.to_string(),
+            r#"{"z":[1,-2,18446744073709551615,2.5,1e400,"é",true,false,null],"a":{},"m":[]}"#
+        );
+
+        assert!(JsonValue::parse(b"[1,").is_err());
+        assert!(JsonValue::parse(b"").is_err());
+    }
+
+    /// Test that members can be inserted, replaced, and removed without
+    /// changing the order of the others
+    #[test]
+    fn object() {
+        let mut o = [("b", JsonValue::Int(1)), ("a", JsonValue::Int(2))]
+            .into_iter()
+            .collect::<JsonObject>();
+        assert_eq!(o.insert("b", JsonValue::Null), Some(JsonValue::Int(1)));
+        o.insert("c", JsonValue::Int(3));
+        assert_eq!(o.remove("a"), Some(JsonValue::Int(2)));
+        assert_eq!(JsonValue::from(o).to_string(), r#"{"b":null,"c":3}"#);
+    }
+
+    /// Test that large objects are built in linear time and that their
+    /// index stays consistent when members are replaced and removed
+    #[test]
+    fn large_object() {
+        let n = 200_000;
+        let json = format!(
+            "{{{}}}",
+            (0..n)
+                .map(|i| format!("\"k{i}\":{i}"))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let value = JsonValue::parse(json.as_bytes()).unwrap();
+        let o = value.as_object().unwrap();
+        assert_eq!(o.len(), n);
+        for i in (0..n).step_by(997) {
+            assert_eq!(o.get(&format!("k{i}")), Some(&JsonValue::Int(i as i64)));
+        }
+        assert_eq!(o.get("k"), None);
+
+        let mut o = (0..20)
+            .map(|i| (format!("k{i}"), JsonValue::Int(i)))
+            .collect::<JsonObject>();
+        assert_eq!(o.insert("k3", JsonValue::Null), Some(JsonValue::Int(3)));
+        assert_eq!(o.remove("k5"), Some(JsonValue::Int(5)));
+        assert_eq!(o.remove("k5"), None);
+        assert_eq!(o.get("k3"), Some(&JsonValue::Null));
+        assert_eq!(o.get("k19"), Some(&JsonValue::Int(19)));
+        assert_eq!(o.keys().nth(5), Some("k6"));
+        o.insert("k5", JsonValue::Int(-5));
+        assert_eq!(o.keys().last(), Some("k5"));
+        assert_eq!(o.get("k5"), Some(&JsonValue::Int(-5)));
+    }
+}
@@ -0,0 +1,356 @@
+//! A push-style writer for JSON texts with typed methods.
+//!
+//! [`JsonWriter`] is the counterpart of [`JsonReader`](crate::reader::JsonReader)
+//! and sits on top of a [`JsonEmitter`]. Its methods are similar to the ones
+//! of `JsonWriter` in Gson and can be chained, which makes hand-written
+//! encoders short and readable.
+//!
+//! ```
+//! use actson::writer::JsonWriter;
+//!
+//! let mut writer = JsonWriter::new(Vec::new());
+//! writer.begin_object().unwrap();
+//! writer.name("name").unwrap().value("Elvis").unwrap();
+//! writer.name("age").unwrap().value(42).unwrap();
+//! writer.name("tags").unwrap().begin_array().unwrap();
+//! for tag in ["singer", "actor"] {
+//!     writer.value(tag).unwrap();
+//! }
+//! writer.end_array().unwrap();
+//! writer.name("spouse").unwrap().value(None::<&str>).unwrap();
+//! writer.end_object().unwrap();
+//!
+//! assert_eq!(
+//!     writer.finish().unwrap(),
+//!     br#"{"name":"Elvis","age":42,"tags":["singer","actor"],"spouse":null}"#
+//! );
+//! ```
+
+use std::io::Write;
+
+use crate::emitter::{EmitterError, EmitterOptions, JsonEmitter};
+
+/// A value that can be written with [`JsonWriter::value()`]
+pub trait WriteValue {
+    /// Write this value with the given emitter
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write;
+}
+
+impl<T> WriteValue for &T
+where
+    T: WriteValue + ?Sized,
+{
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        (**self).write_value(emitter)
+    }
+}
+
+impl<T> WriteValue for Option<T>
+where
+    T: WriteValue,
+{
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        match self {
+            Some(v) => v.write_value(emitter),
+            None => emitter.value_null(),
+        }
+    }
+}
+
+impl WriteValue for str {
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        emitter.value_str(self)
+    }
+}
+
+impl WriteValue for String {
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        emitter.value_str(self)
+    }
+}
+
+impl WriteValue for bool {
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        emitter.value_bool(*self)
+    }
+}
+
+impl WriteValue for f64 {
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        emitter.value_float(*self)
+    }
+}
+
+impl WriteValue for f32 {
+    fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+    where
+        W: Write,
+    {
+        emitter.value_float(*self as f64)
+    }
+}
+
+macro_rules! impl_write_value_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl WriteValue for $t {
+                fn write_value<W>(&self, emitter: &mut JsonEmitter<W>) -> Result<(), EmitterError>
+                where
+                    W: Write,
+                {
+                    emitter.value_int(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_write_value_for_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// A push-style writer with typed methods. See the [module
+/// documentation](self).
+pub struct JsonWriter<W> {
+    emitter: JsonEmitter<W>,
+}
+
+impl<W> JsonWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer that writes compact JSON to the given writer
+    pub fn new(writer: W) -> Self {
+        Self::from_emitter(JsonEmitter::new(writer))
+    }
+
+    /// Create a new writer with the given options
+    pub fn new_with_options(writer: W, options: EmitterOptions) -> Self {
+        Self::from_emitter(JsonEmitter::new_with_options(writer, options))
+    }
+
+    /// Create a new writer that writes to the given emitter
+    pub fn from_emitter(emitter: JsonEmitter<W>) -> Self {
+        JsonWriter { emitter }
+    }
+
+    /// Get the underlying emitter
+    pub fn emitter(&mut self) -> &mut JsonEmitter<W> {
+        &mut self.emitter
+    }
+
+    /// Consume the writer and return the underlying emitter
+    pub fn into_inner(self) -> JsonEmitter<W> {
+        self.emitter
+    }
+
+    /// Write the start of an object
+    pub fn begin_object(&mut self) -> Result<&mut Self, EmitterError> {
+        self.emitter.start_object()?;
+        Ok(self)
+    }
+
+    /// Write the end of the current object
+    pub fn end_object(&mut self) -> Result<&mut Self, EmitterError> {
+        self.emitter.end_object()?;
+        Ok(self)
+    }
+
+    /// Write the start of an array
+    pub fn begin_array(&mut self) -> Result<&mut Self, EmitterError> {
+        self.emitter.start_array()?;
+        Ok(self)
+    }
+
+    /// Write the end of the current array
+    pub fn end_array(&mut self) -> Result<&mut Self, EmitterError> {
+        self.emitter.end_array()?;
+        Ok(self)
+    }
+
+    /// Write the name of a field in the current object. Write the field's
+    /// value next.
+    pub fn name(&mut self, name: &str) -> Result<&mut Self, EmitterError> {
+        self.emitter.field_name(name)?;
+        Ok(self)
+    }
+
+    /// Write a string, number, boolean, or optional value (`None` is
+    /// written as `null`)
+    pub fn value(&mut self, value: impl WriteValue) -> Result<&mut Self, EmitterError> {
+        value.write_value(&mut self.emitter)?;
+        Ok(self)
+    }
+
+    /// Write a `null` value
+    pub fn null_value(&mut self) -> Result<&mut Self, EmitterError> {
+        self.emitter.value_null()?;
+        Ok(self)
+    }
+
+    /// Write an already serialized JSON value verbatim (see
+    /// [`JsonEmitter::write_raw()`])
+    pub fn json_value(&mut self, json: &str) -> Result<&mut Self, EmitterError> {
+        self.emitter.write_raw(json)?;
+        Ok(self)
+    }
+
+    /// Serialize the given value at the current position (see
+    /// [`JsonEmitter::serialize_value()`])
+    ///
+    /// ```
+    /// use actson::writer::JsonWriter;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut writer = JsonWriter::new(Vec::new());
+    /// writer.begin_object().unwrap();
+    /// writer
+    ///     .name("counts")
+    ///     .unwrap()
+    ///     .serialize_value(&BTreeMap::from([("a", 1), ("b", 2)]))
+    ///     .unwrap();
+    /// writer.end_object().unwrap();
+    /// assert_eq!(writer.finish().unwrap(), br#"{"counts":{"a":1,"b":2}}"#);
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn serialize_value<T>(
+        &mut self,
+        value: &T,
+    ) -> Result<&mut Self, crate::serde_json::SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.emitter.serialize_value(value)?;
+        Ok(self)
+    }
+
+    /// Write an array whose elements are written by the given function.
+    /// The start and the end of the array are written by this function.
+    ///
+    /// ```
+    /// use actson::emitter::EmitterError;
+    /// use actson::writer::JsonWriter;
+    ///
+    /// let mut writer = JsonWriter::new(Vec::new());
+    /// writer
+    ///     .write_object(|w| {
+    ///         w.name("scores")?.write_array(|w| {
+    ///             for s in [1, 2, 3] {
+    ///                 w.value(s)?;
+    ///             }
+    ///             Ok::<_, EmitterError>(())
+    ///         })?;
+    ///         Ok::<_, EmitterError>(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(writer.finish().unwrap(), br#"{"scores":[1,2,3]}"#);
+    /// ```
+    pub fn write_array<E>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<&mut Self, E>
+    where
+        E: From<EmitterError>,
+    {
+        self.begin_array()?;
+        f(self)?;
+        Ok(self.end_array()?)
+    }
+
+    /// Write an object whose members are written by the given function.
+    /// The start and the end of the object are written by this function.
+    /// See [`Self::write_array()`] for an example.
+    pub fn write_object<E>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), E>,
+    ) -> Result<&mut Self, E>
+    where
+        E: From<EmitterError>,
+    {
+        self.begin_object()?;
+        f(self)?;
+        Ok(self.end_object()?)
+    }
+
+    /// Write all buffered output to the underlying writer and flush it
+    pub fn flush(&mut self) -> Result<(), EmitterError> {
+        self.emitter.flush()
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub fn finish(self) -> Result<W, EmitterError> {
+        self.emitter.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::emitter::EmitterError;
+
+    use super::JsonWriter;
+
+    /// Test that values of all types can be written
+    #[test]
+    fn values() {
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_array()
+            .unwrap()
+            .value("a")
+            .unwrap()
+            .value(String::from("b"))
+            .unwrap()
+            .value(-1i8)
+            .unwrap()
+            .value(u64::MAX)
+            .unwrap()
+            .value(1.5)
+            .unwrap()
+            .value(0.5f32)
+            .unwrap()
+            .value(true)
+            .unwrap()
+            .value(Some(2))
+            .unwrap()
+            .value(None::<bool>)
+            .unwrap()
+            .null_value()
+            .unwrap()
+            .json_value("{}")
+            .unwrap()
+            .end_array()
+            .unwrap();
+        assert_eq!(
+            w.finish().unwrap(),
+            br#"["a","b",-1,18446744073709551615,1.5,0.5,true,2,null,null,{}]"#
+        );
+    }
+
+    /// Test that values in the wrong place are rejected
+    #[test]
+    fn unexpected() {
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_object().unwrap();
+        assert!(matches!(
+            w.value(1),
+            Err(EmitterError::UnexpectedToken("value"))
+        ));
+    }
+}
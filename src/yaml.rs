@@ -0,0 +1,350 @@
+//! Convert JSON texts to YAML.
+//!
+//! [`YamlWriter`] is a [`JsonEventSink`] that renders the events it
+//! receives as a YAML document in block style. The output is written while
+//! the JSON text is being parsed, so only the current path is kept in
+//! memory and large configuration files or dumps can be converted in a
+//! single pass.
+//!
+//! Strings are written as plain scalars if that does not change their
+//! meaning (e.g. `yes` or `1.0` are quoted) and as double-quoted scalars
+//! otherwise. Numbers are written exactly as they appear in the JSON text.
+//! Empty objects and arrays are written in flow style (`{}` and `[]`). If
+//! the parser is in streaming mode, each top-level value becomes a separate
+//! document.
+//!
+//! ```
+//! use actson::feeder::SliceJsonFeeder;
+//! use actson::sink::parse_with;
+//! use actson::yaml::YamlWriter;
+//!
+//! let json = br#"{"name": "Elvis", "albums": [{"year": 1956, "tags": []}], "alive": null}"#;
+//! let mut writer = YamlWriter::new(Vec::new());
+//! parse_with(SliceJsonFeeder::new(json), &mut writer).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(writer.finish().unwrap()).unwrap(),
+//!     "name: Elvis\n\
+//!      albums:\n  \
+//!        - year: 1956\n    \
+//!          tags: []\n\
+//!      alive: null\n"
+//! );
+//! ```
+
+use std::io::Write;
+
+use thiserror::Error;
+
+use crate::{
+    emitter::DEFAULT_BUFFER_SIZE, escape::escape_str, feeder::JsonFeeder,
+    parser::InvalidStringValueError, sink::JsonEventSink, storage::ParserStorage, JsonEvent,
+    JsonParser,
+};
+
+/// An error that can happen when writing YAML
+#[derive(Error, Debug)]
+pub enum YamlError {
+    /// The output could not be written
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// A string received from the parser is invalid
+    #[error(transparent)]
+    InvalidStringValue(#[from] InvalidStringValueError),
+}
+
+/// An object or array that is currently open
+struct Frame {
+    /// `true` if the container is an array
+    is_array: bool,
+
+    /// The number of spaces before each of the container's items
+    indent: usize,
+
+    /// `true` if the first item continues the current line (e.g. after
+    /// `- `) instead of starting a new one
+    inline_first: bool,
+
+    /// `true` if the container is the value of an object member
+    after_key: bool,
+
+    /// The number of items written so far
+    count: usize,
+}
+
+/// Writes the events it receives as YAML. See the [module
+/// documentation](self).
+///
+/// The output is buffered. Call [`Self::finish()`] when you are done.
+pub struct YamlWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+
+    /// The containers that are currently open
+    stack: Vec<Frame>,
+
+    /// `true` if a field name has been written and its value is expected
+    after_key: bool,
+
+    /// The number of top-level values written so far
+    documents: usize,
+}
+
+impl<W> YamlWriter<W>
+where
+    W: Write,
+{
+    /// Create a new writer
+    pub fn new(writer: W) -> Self {
+        YamlWriter {
+            writer,
+            buf: Vec::new(),
+            stack: Vec::new(),
+            after_key: false,
+            documents: 0,
+        }
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Flush all buffered output and return the underlying writer
+    pub fn finish(mut self) -> Result<W, YamlError> {
+        self.writer.write_all(&self.buf)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Start a new item in the current container, so that a field name or
+    /// `- ` can be written
+    fn begin_item(&mut self) {
+        let Some(frame) = self.stack.last_mut() else {
+            return;
+        };
+        if frame.count > 0 || !frame.inline_first {
+            if frame.count == 0 {
+                // end the line of the container's key
+                self.buf.push(b'\n');
+            }
+            self.buf.resize(self.buf.len() + frame.indent, b' ');
+        }
+        frame.count += 1;
+    }
+
+    /// Prepare writing a value and return `true` if it is the value of an
+    /// object member
+    fn begin_value(&mut self) -> bool {
+        if self.stack.is_empty() {
+            if self.documents > 0 {
+                self.buf.extend_from_slice(b"---\n");
+            }
+            self.documents += 1;
+        }
+        if self.stack.last().is_some_and(|f| f.is_array) {
+            self.begin_item();
+            self.buf.extend_from_slice(b"- ");
+        }
+        std::mem::take(&mut self.after_key)
+    }
+
+    /// Write a complete scalar value
+    fn scalar(&mut self, value: &[u8]) {
+        if self.begin_value() {
+            self.buf.push(b' ');
+        }
+        self.buf.extend_from_slice(value);
+        self.buf.push(b'\n');
+    }
+
+    /// Write a string as a plain scalar if possible or as a double-quoted
+    /// scalar otherwise
+    fn write_str(&mut self, s: &str) {
+        if is_plain(s) {
+            self.buf.extend_from_slice(s.as_bytes());
+        } else {
+            // YAML's double-quoted scalars support all of JSON's escape
+            // sequences
+            escape_str(s, &mut self.buf);
+        }
+    }
+}
+
+/// Returns `true` if the given string can be written as a plain scalar
+/// without being mistaken for another type or for YAML syntax
+fn is_plain(s: &str) -> bool {
+    let Some(first) = s.chars().next() else {
+        return false;
+    };
+    if first.is_ascii_digit()
+        || "-+.?:,[]{}#&*!|>'\"%@`~ ".contains(first)
+        || s.ends_with([' ', ':'])
+        || s.contains(": ")
+        || s.contains(" #")
+        || s.chars().any(|c| c.is_control() || c == '\u{feff}')
+    {
+        return false;
+    }
+    // values that YAML 1.1 or 1.2 would read as booleans or null
+    !matches!(
+        s.to_ascii_lowercase().as_str(),
+        "null" | "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n"
+    )
+}
+
+impl<W> JsonEventSink for YamlWriter<W>
+where
+    W: Write,
+{
+    type Error = YamlError;
+
+    fn on_event<T, S>(
+        &mut self,
+        event: JsonEvent,
+        parser: &JsonParser<T, S>,
+    ) -> Result<(), Self::Error>
+    where
+        T: JsonFeeder,
+        S: ParserStorage,
+    {
+        match event {
+            JsonEvent::NeedMoreInput => return Ok(()),
+
+            JsonEvent::StartObject | JsonEvent::StartArray => {
+                let after_key = self.begin_value();
+                let (indent, inline_first) = match self.stack.last() {
+                    Some(parent) => (parent.indent + 2, parent.is_array),
+                    None => (0, true),
+                };
+                self.stack.push(Frame {
+                    is_array: event == JsonEvent::StartArray,
+                    indent,
+                    inline_first,
+                    after_key,
+                    count: 0,
+                });
+            }
+
+            JsonEvent::EndObject | JsonEvent::EndArray => {
+                if let Some(frame) = self.stack.pop() {
+                    if frame.count == 0 {
+                        if frame.after_key {
+                            self.buf.push(b' ');
+                        }
+                        let empty: &[u8] = if frame.is_array { b"[]\n" } else { b"{}\n" };
+                        self.buf.extend_from_slice(empty);
+                    }
+                }
+            }
+
+            JsonEvent::FieldName => {
+                self.begin_item();
+                self.write_str(parser.current_str()?);
+                self.buf.push(b':');
+                self.after_key = true;
+            }
+
+            JsonEvent::ValueString => {
+                if self.begin_value() {
+                    self.buf.push(b' ');
+                }
+                self.write_str(parser.current_str()?);
+                self.buf.push(b'\n');
+            }
+
+            JsonEvent::ValueInt | JsonEvent::ValueFloat => {
+                self.scalar(parser.current_raw_str()?.as_bytes())
+            }
+            JsonEvent::ValueTrue => self.scalar(b"true"),
+            JsonEvent::ValueFalse => self.scalar(b"false"),
+            JsonEvent::ValueNull => self.scalar(b"null"),
+        }
+
+        if self.buf.len() >= DEFAULT_BUFFER_SIZE {
+            self.writer.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        feeder::SliceJsonFeeder,
+        options::JsonParserOptionsBuilder,
+        sink::{parse_with, parse_with_parser},
+        JsonParser,
+    };
+
+    use super::{is_plain, YamlWriter};
+
+    fn yaml(json: &str) -> String {
+        let mut writer = YamlWriter::new(Vec::new());
+        parse_with(SliceJsonFeeder::new(json.as_bytes()), &mut writer).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    /// Test that nested containers are indented correctly
+    #[test]
+    fn nested() {
+        assert_eq!(
+            yaml(r#"{"a": [[1, 2], [], {"b": {"c": true}, "d": {}}], "e": {"f": [null]}}"#),
+            "a:\n  \
+               - - 1\n    \
+                 - 2\n  \
+               - []\n  \
+               - b:\n      \
+                   c: true\n    \
+                 d: {}\n\
+             e:\n  \
+               f:\n    \
+                 - null\n"
+        );
+        assert_eq!(yaml("[]"), "[]\n");
+        assert_eq!(yaml("[{}]"), "- {}\n");
+        assert_eq!(yaml("[[[]]]"), "- - []\n");
+        assert_eq!(yaml(r#""x""#), "x\n");
+    }
+
+    /// Test that strings are quoted if necessary
+    #[test]
+    fn strings() {
+        for s in ["Elvis", "a b", "a-b", "x:y", "\u{e9}t\u{e9}", "a#b"] {
+            assert!(is_plain(s), "{s}");
+        }
+        for s in [
+            "", " a", "a ", "1", "1.5", "-1", ".5", "yes", "No", "null", "~", "- a", "a: b",
+            "a #b", "a:", "[a]", "{a}", "*a", "&a", "!a", "'a'", "\"a\"", "a\nb", "#a", "@a",
+        ] {
+            assert!(!is_plain(s), "{s}");
+        }
+        assert_eq!(
+            yaml(r#"{"": "a\nb", "yes": "1.0", "k: v": ["\"q\"", "\u0007"]}"#),
+            "\"\": \"a\\nb\"\n\
+             \"yes\": \"1.0\"\n\
+             \"k: v\":\n  \
+               - \"\\\"q\\\"\"\n  \
+               - \"\\u0007\"\n"
+        );
+    }
+
+    /// Test that top-level values in streaming mode become separate
+    /// documents
+    #[test]
+    fn documents() {
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(br#"{"a": 1} [2] 3"#),
+            JsonParserOptionsBuilder::default()
+                .with_streaming(true)
+                .build(),
+        );
+        let mut writer = YamlWriter::new(Vec::new());
+        parse_with_parser(&mut parser, &mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "a: 1\n---\n- 2\n---\n3\n"
+        );
+    }
+}
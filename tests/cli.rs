@@ -0,0 +1,64 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Run the `actson` binary with the given arguments and input. Return
+/// whether it succeeded and what it wrote to stdout and stderr.
+fn actson(args: &[&str], input: &str) -> (bool, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_actson"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Test that valid input is accepted and invalid input is reported with
+/// its position
+#[test]
+fn validate() {
+    assert!(actson(&["validate"], r#"{"a": [1, 2]}"#).0);
+    let (ok, _, err) = actson(&["validate"], r#"{"a": [1, 2}"#);
+    assert!(!ok);
+    assert!(err.contains("syntax error"), "{err}");
+    assert!(err.contains("at byte 12"), "{err}");
+}
+
+/// Test pretty-printing and minifying
+#[test]
+fn format() {
+    let json = "{ \"a\" : [ 1, { \"b\": null } ] }";
+    assert_eq!(
+        actson(&["pretty", "--indent", "1"], json).1,
+        "{\n \"a\": [\n  1,\n  {\n   \"b\": null\n  }\n ]\n}\n"
+    );
+    assert_eq!(actson(&["minify"], json).1, "{\"a\":[1,{\"b\":null}]}\n");
+}
+
+/// Test converting between arrays and NDJSON
+#[test]
+fn ndjson() {
+    let (ok, out, _) = actson(&["to-ndjson"], r#"[{"a": [1]}, 2, "x"]"#);
+    assert!(ok);
+    assert_eq!(out, "{\"a\":[1]}\n2\n\"x\"\n");
+    assert_eq!(actson(&["from-ndjson"], &out).1, "[{\"a\":[1]},2,\"x\"]\n");
+    assert_eq!(actson(&["to-ndjson"], "[]").1, "");
+
+    let (ok, _, err) = actson(&["to-ndjson"], r#"{"a": 1}"#);
+    assert!(!ok);
+    assert_eq!(err, "error: expected an array (at byte 0)\n");
+}
@@ -2,8 +2,9 @@ use serde_json::Value;
 use std::fs::File;
 use std::io::{BufReader, Read};
 
-use crate::prettyprinter::PrettyPrinter;
+use actson::emitter::PrettyPrinter;
 use actson::feeder::{BufReaderJsonFeeder, JsonFeeder};
+use actson::sink::{parse_with, JsonEventSink};
 use actson::{JsonEvent, JsonParser};
 
 /// Test if [`BufReaderJsonFeeder`] can fully consume a file
@@ -78,3 +79,21 @@ fn parse_from_file() {
     let am: Value = serde_json::from_str(actual).unwrap();
     assert_eq!(em, am);
 }
+
+/// Test if [`parse_with()`] can drive a [`BufReaderJsonFeeder`] that has to
+/// be refilled many times
+#[test]
+fn parse_with_file() {
+    let expected = std::fs::read_to_string("tests/fixtures/pass1.txt").unwrap();
+
+    let file = File::open("tests/fixtures/pass1.txt").unwrap();
+    let reader = BufReader::with_capacity(32, file);
+
+    let feeder = BufReaderJsonFeeder::new(reader);
+    let mut prettyprinter = PrettyPrinter::new();
+    parse_with(feeder, &mut prettyprinter).unwrap();
+
+    let em: Value = serde_json::from_str(&expected).unwrap();
+    let am: Value = serde_json::from_str(prettyprinter.get_result()).unwrap();
+    assert_eq!(em, am);
+}
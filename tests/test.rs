@@ -1,14 +1,25 @@
 mod feeder;
-mod prettyprinter;
 mod tokio;
 
+use std::borrow::Cow;
 use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
 
-use actson::feeder::PushJsonFeeder;
-use actson::options::JsonParserOptionsBuilder;
-use actson::parser::ParserError;
-use actson::{JsonEvent, JsonParser};
-use prettyprinter::PrettyPrinter;
+use actson::cancel::CancellationToken;
+use actson::emitter::{minify, PrettyPrinter};
+use actson::feeder::{
+    BufReaderJsonFeeder, JsonFeeder, PushJsonFeeder, SliceJsonFeeder, StrJsonFeeder, TailJsonFeeder,
+};
+use actson::intern::StringInterner;
+use actson::options::{IntOverflowPolicy, JsonParserOptions, JsonParserOptionsBuilder, Profile};
+use actson::parser::{FinishError, NextValueError, ParserError, RewindError};
+use actson::path::{PathFilter, PathPattern};
+use actson::sink::{parse_with, JsonEventSink};
+use actson::storage::{FixedStorage, ScratchStorage};
+use actson::tape::{TapeReader, TapeWriter};
+use actson::transform::{DropFields, FieldSelector, LimitDepth, Redact, RenameKeys};
+use actson::{BorrowedJsonEvent, JsonEvent, JsonParser, OwnedJsonEvent, Span};
 use serde_json::Value;
 
 /// Parse a JSON string and return a new JSON string generated by
@@ -179,6 +190,282 @@ fn too_many_next_event() {
     assert!(matches!(parser.next_event(), Err(ParserError::NoMoreInput)));
 }
 
+/// Test that a parser reading from a string produces the same values as
+/// one reading from bytes
+#[test]
+fn str_feeder() {
+    let json = r#"{"näme": "Björn", "city": "Köln", "x": -1.5e3, "emoji": "😀", "esc": "Björn\n"}"#;
+    let mut expected = JsonParser::new(SliceJsonFeeder::new(json.as_bytes()));
+    let mut parser = JsonParser::new(StrJsonFeeder::new(json));
+    while let Some(e) = parser.next_event().unwrap() {
+        assert_eq!(expected.next_event().unwrap(), Some(e));
+        if matches!(
+            e,
+            JsonEvent::FieldName | JsonEvent::ValueString | JsonEvent::ValueFloat
+        ) {
+            assert_eq!(
+                parser.current_str().unwrap(),
+                expected.current_str().unwrap()
+            );
+            assert_eq!(
+                parser.current_raw_str().unwrap(),
+                expected.current_raw_str().unwrap()
+            );
+        }
+    }
+    assert_eq!(expected.next_event().unwrap(), None);
+}
+
+/// Test that chunks larger than the feeder's capacity can be fed and that
+/// feeding stops after an error
+#[test]
+fn feed() {
+    let json = std::fs::read("tests/fixtures/pass1.txt").unwrap();
+    let mut expected = JsonParser::new(SliceJsonFeeder::new(&json));
+    let mut parser = JsonParser::new(PushJsonFeeder::with_capacity(8));
+    let mut n = 0;
+    for chunk in json.chunks(100) {
+        for e in parser.feed(chunk) {
+            assert_eq!(Some(e.unwrap()), expected.next_owned_event().unwrap());
+            n += 1;
+        }
+    }
+    assert!(matches!(parser.finish(), Err(FinishError::FeederNotDone)));
+    for e in parser.feed_done() {
+        assert_eq!(Some(e.unwrap()), expected.next_owned_event().unwrap());
+        n += 1;
+    }
+    assert!(n > 100);
+    assert_eq!(expected.next_owned_event().unwrap(), None);
+    parser.finish().unwrap();
+
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let events = parser.feed(b"[1, 2} 3").collect::<Vec<_>>();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(
+        events[2],
+        Err(NextValueError::Parser(ParserError::SyntaxError))
+    ));
+}
+
+/// Test that scoped parsers stop at the end of the current object or array
+/// or after the next value, and that the parser continues after the subtree
+#[test]
+fn scoped() {
+    let json = br#"[{"a": [1, {"b": 2}], "c": true}, "x", [], 3]"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+
+    let mut scoped = parser.scoped();
+    let mut events = Vec::new();
+    while let Some(e) = scoped.next_event().unwrap() {
+        events.push(e);
+    }
+    assert_eq!(events.len(), 7);
+    assert_eq!(events[6], JsonEvent::EndArray);
+    assert_eq!(scoped.next_event().unwrap(), None);
+
+    // rest of the object
+    let mut scoped = parser.scoped();
+    scoped.skip_rest().unwrap();
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+    assert_eq!(parser.current_str().unwrap(), "c");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueTrue));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::EndObject));
+
+    let mut scoped = parser.scoped();
+    assert_eq!(scoped.next_event().unwrap(), Some(JsonEvent::ValueString));
+    assert_eq!(scoped.current_str().unwrap(), "x");
+    assert_eq!(scoped.next_event().unwrap(), None);
+
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    let mut scoped = parser.scoped();
+    assert_eq!(scoped.next_event().unwrap(), Some(JsonEvent::EndArray));
+    assert_eq!(scoped.next_event().unwrap(), None);
+
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    let mut scoped = parser.scoped();
+    assert_eq!(scoped.next_event().unwrap(), None);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::EndArray));
+}
+
+/// Test that skip_to_pointer() finds values in objects and arrays, returns
+/// `None` for missing values, and leaves the parser in the right position
+#[test]
+fn skip_to_pointer() {
+    let json = br#"[{"a": [1, {"b/c": 2}], "d~": true}, "x", [], 3]"#;
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    let mut target = parser.skip_to_pointer("/0/a").unwrap().unwrap();
+    let mut events = Vec::new();
+    while let Some(e) = target.next_event().unwrap() {
+        events.push(e);
+    }
+    assert_eq!(events.len(), 7);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+    assert_eq!(parser.current_str().unwrap(), "d~");
+
+    for (pointer, event) in [
+        ("/0/a/1/b~1c", JsonEvent::ValueInt),
+        ("/0/d~0", JsonEvent::ValueTrue),
+        ("/1", JsonEvent::ValueString),
+        ("/2", JsonEvent::StartArray),
+        ("", JsonEvent::StartArray),
+    ] {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        let mut target = parser.skip_to_pointer(pointer).unwrap().unwrap();
+        assert_eq!(target.next_event().unwrap(), Some(event), "{pointer}");
+    }
+
+    for pointer in [
+        "/4", "/0/b", "/0/a/2", "/0/a/01", "/0/a/x", "/1/a", "/2/0", "/-",
+    ] {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        assert!(
+            parser.skip_to_pointer(pointer).unwrap().is_none(),
+            "{pointer}"
+        );
+        assert_eq!(parser.next_event().unwrap(), None, "{pointer}");
+    }
+
+    // malformed pointers are rejected without consuming any input
+    for pointer in ["a", "a/b", "0"] {
+        let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+        assert!(
+            matches!(
+                parser.skip_to_pointer(pointer),
+                Err(NextValueError::InvalidPointer(p)) if p == pointer
+            ),
+            "{pointer}"
+        );
+        assert_eq!(parser.parsed_bytes(), 0);
+        assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    }
+
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    parser.feeder.push_bytes(br#"{"a": [1, "#);
+    assert!(matches!(
+        parser.skip_to_pointer("/a/2"),
+        Err(NextValueError::NeedMoreInput)
+    ));
+}
+
+/// Test that binary data following a JSON text can be recovered from the
+/// feeder
+#[test]
+fn into_feeder() {
+    let mut feeder = PushJsonFeeder::new();
+    feeder.push_bytes(b"{\"a\": [1]}\x00\xff\x01");
+    let mut parser = JsonParser::new(feeder);
+    let mut depth = 0;
+    loop {
+        match parser.next_event().unwrap() {
+            Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+            Some(JsonEvent::EndObject | JsonEvent::EndArray) => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            break;
+        }
+    }
+    assert!(parser.remaining().is_empty());
+    let (mut feeder, remaining) = parser.into_feeder();
+    let rest = remaining
+        .into_iter()
+        .chain(std::iter::from_fn(|| feeder.next_input()))
+        .collect::<Vec<_>>();
+    assert_eq!(rest, b"\x00\xff\x01");
+}
+
+/// Test that the parser stops after the first top-level value if trailing
+/// data is allowed and that it reports the length of the value
+#[test]
+fn allow_trailing_data() {
+    let parse = |json: &[u8]| {
+        let mut parser = JsonParser::new_with_options(
+            SliceJsonFeeder::new(json),
+            JsonParserOptionsBuilder::default()
+                .with_allow_trailing_data(true)
+                .build(),
+        );
+        let mut events = Vec::new();
+        while let Some(e) = parser.next_event().unwrap() {
+            events.push(e);
+        }
+        assert!(matches!(parser.next_event(), Err(ParserError::NoMoreInput)));
+        let remaining = parser.remaining().to_vec();
+        let rest = parser.feeder.peek_input().to_vec();
+        (events, parser.parsed_bytes(), remaining, rest)
+    };
+
+    let (events, n, remaining, rest) = parse(b" [1, {\"a\": null}] {} x\x00");
+    assert_eq!(events.len(), 7);
+    assert_eq!(n, 17);
+    assert!(remaining.is_empty());
+    assert_eq!(rest, b" {} x\x00");
+
+    let (events, n, remaining, rest) = parse(b"\"abc\"\"def\"");
+    assert_eq!(events, vec![JsonEvent::ValueString]);
+    assert_eq!(n, 5);
+    assert!(remaining.is_empty());
+    assert_eq!(rest, b"\"def\"");
+
+    let (events, n, remaining, rest) = parse(b"-12.5e3\x00\x01");
+    assert_eq!(events, vec![JsonEvent::ValueFloat]);
+    assert_eq!(n, 7);
+    assert_eq!(remaining, b"\x00");
+    assert_eq!(rest, b"\x01");
+
+    let (events, n, remaining, rest) = parse(b"42 ");
+    assert_eq!(events, vec![JsonEvent::ValueInt]);
+    assert_eq!(n, 2);
+    assert_eq!(remaining, b" ");
+    assert!(rest.is_empty());
+
+    let (events, n, _, _) = parse(b"true");
+    assert_eq!(events, vec![JsonEvent::ValueTrue]);
+    assert_eq!(n, 4);
+
+    let (events, n, _, _) = parse(b"42");
+    assert_eq!(events, vec![JsonEvent::ValueInt]);
+    assert_eq!(n, 2);
+}
+
+#[test]
+fn finish() {
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(b"{} \n"));
+    assert!(parser.finish().is_err());
+    while parser.next_event().unwrap().is_some() {}
+    assert!(parser.finish().is_ok());
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(b"{} x"));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::EndObject));
+    assert!(matches!(
+        parser.finish(),
+        Err(FinishError::Parser(ParserError::SyntaxError))
+    ));
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(b"[1"));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert!(matches!(
+        parser.finish(),
+        Err(FinishError::UnexpectedEvent(JsonEvent::ValueInt))
+    ));
+    assert!(matches!(
+        parser.finish(),
+        Err(FinishError::Parser(ParserError::NoMoreInput))
+    ));
+
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    parser.feeder.push_bytes(b"1");
+    parser.feeder.done();
+    assert!(parser.finish().is_err());
+    assert!(parser.finish().is_ok());
+}
+
 #[test]
 fn illegal_character() {
     let json = "{\"key\":\x02}";
@@ -332,8 +619,8 @@ fn test_suite_pass() {
         if name.to_str().unwrap().starts_with('y') {
             let json = fs::read_to_string(f.path()).unwrap();
             if name == "y_number_minus_zero.json" || name == "y_number_negative_zero.json" {
-                // -0 equals 0
-                assert_eq!("[\n  0\n]", &parse(&json));
+                // numbers are printed as they appear in the JSON text
+                assert_eq!("[\n  -0\n]", &parse(&json));
             } else {
                 assert_json_eq(&json, &parse(&json));
             }
@@ -366,7 +653,7 @@ fn streaming_numbers() {
     let feeder = PushJsonFeeder::new();
     let r = parse_with_parser(json, &mut JsonParser::new_with_options(feeder, options));
 
-    assert_eq!("12345", r);
+    assert_eq!("1\n2\n3\n4\n5", r);
 }
 
 /// Test if multiple top-level strings can be parsed in streaming mode
@@ -380,7 +667,7 @@ fn streaming_strings() {
     let feeder = PushJsonFeeder::new();
     let r = parse_with_parser(json, &mut JsonParser::new_with_options(feeder, options));
 
-    assert_eq!(r#""1""2""3""4""5""#, r);
+    assert_eq!("\"1\"\n\"2\"\n\"3\"\n\"4\"\n\"5\"", r);
 }
 
 /// Test if multiple top-level arrays can be parsed in streaming mode
@@ -394,7 +681,7 @@ fn streaming_arrays() {
     let feeder = PushJsonFeeder::new();
     let r = parse_with_parser(json, &mut JsonParser::new_with_options(feeder, options));
 
-    assert_eq!("[\n  1\n][\n  2\n][\n  3\n][\n  4\n][\n  5\n]", r);
+    assert_eq!("[\n  1\n]\n[\n  2\n]\n[\n  3\n]\n[\n  4\n]\n[\n  5\n]", r);
 }
 
 /// Test if multiple top-level objects can be parsed in streaming mode
@@ -411,13 +698,17 @@ fn streaming_objects() {
     assert_eq!(
         r#"{
   "a": 1
-}{
+}
+{
   "b": 2
-}{
+}
+{
   "c": 3
-}{
+}
+{
   "d": 4
-}{
+}
+{
   "e": 5
 }"#,
         r
@@ -437,15 +728,1585 @@ fn streaming_complex() {
     let r = parse_with_parser(json, &mut JsonParser::new_with_options(feeder, options));
 
     assert_eq!(
-        r#"1.02"3"{
+        r#"1.0
+2
+"3"
+{
   "a": 4
-}50.00006[
+}
+5
+6e-5
+[
   "b"
-][
+]
+[
   "c"
-]7{
+]
+7
+{
   "d": "e"
-}true8falsenull"#,
+}
+true
+8
+false
+null"#,
         r
     );
 }
+
+/// Parse a single top-level value with the given options and return the
+/// parser positioned at the value's event
+fn parse_single_value(json: &str, options: JsonParserOptions) -> JsonParser<SliceJsonFeeder<'_>> {
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json.as_bytes()), options);
+    let e = parser.next_event().unwrap();
+    assert!(matches!(
+        e,
+        Some(JsonEvent::ValueInt) | Some(JsonEvent::ValueFloat)
+    ));
+    parser
+}
+
+/// Test that integers can be read as 128-bit types
+#[test]
+fn int_128() {
+    let parser = parse_single_value(
+        "-170141183460469231731687303715884105728",
+        JsonParserOptions::default(),
+    );
+    assert_eq!(parser.current_int::<i128>().unwrap(), i128::MIN);
+    assert!(parser.current_int::<i64>().is_err());
+
+    let parser = parse_single_value(
+        "340282366920938463463374607431768211455",
+        JsonParserOptions::default(),
+    );
+    assert_eq!(parser.current_int::<u128>().unwrap(), u128::MAX);
+}
+
+/// Test that integers are clamped to the bounds of the requested type if
+/// the overflow policy is set to [`IntOverflowPolicy::Saturate`]
+#[test]
+fn int_overflow_saturate() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_int_overflow_policy(IntOverflowPolicy::Saturate)
+        .build();
+    let parser = parse_single_value("12345678901234567890123", options);
+    assert_eq!(parser.current_int::<i64>().unwrap(), i64::MAX);
+    assert_eq!(parser.current_int::<u8>().unwrap(), u8::MAX);
+    let parser = parse_single_value("-12345678901234567890123", options);
+    assert_eq!(parser.current_int::<i32>().unwrap(), i32::MIN);
+    assert_eq!(parser.current_int::<u32>().unwrap(), 0);
+}
+
+/// Test that integers exceeding the 128-bit range are reported as floats if
+/// the overflow policy is set to [`IntOverflowPolicy::Float`]
+#[test]
+fn int_overflow_float() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_int_overflow_policy(IntOverflowPolicy::Float)
+        .build();
+
+    let mut parser = JsonParser::new_with_options(
+        SliceJsonFeeder::new(b"[340282366920938463463374607431768211456, 5]"),
+        options,
+    );
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueFloat));
+    assert_eq!(parser.current_float().unwrap(), 3.402823669209385e38);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.current_int::<i64>().unwrap(), 5);
+
+    let parser = parse_single_value("-170141183460469231731687303715884105729", options);
+    assert!(parser.current_int::<i128>().is_err());
+    assert_eq!(parser.current_float().unwrap(), -1.7014118346046923e38);
+
+    // without the policy, the number is still an integer
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(
+        b"340282366920938463463374607431768211456",
+    ));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert!(parser.current_int::<u128>().is_err());
+}
+
+/// Test that integers that do not fit into an i64 or u64 are reported as
+/// floats with [`IntOverflowPolicy::Float64`]
+#[test]
+fn int_overflow_float64() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_int_overflow_policy(IntOverflowPolicy::Float64)
+        .build();
+
+    let parser = parse_single_value("18446744073709551615", options);
+    assert_eq!(parser.current_int::<u64>().unwrap(), u64::MAX);
+    let parser = parse_single_value("-9223372036854775808", options);
+    assert_eq!(parser.current_int::<i64>().unwrap(), i64::MIN);
+
+    let mut parser = JsonParser::new_with_options(
+        SliceJsonFeeder::new(b"[18446744073709551616, -9223372036854775809, 1]"),
+        options,
+    );
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueFloat));
+    assert_eq!(parser.current_float().unwrap(), 1.8446744073709552e19);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueFloat));
+    assert_eq!(parser.current_float().unwrap(), -9.223372036854776e18);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.current_int::<i64>().unwrap(), 1);
+}
+
+/// Test that [`JsonParser::current_uint()`] reads non-negative integers
+/// and applies the [`IntOverflowPolicy`] to negative ones
+#[test]
+fn current_uint() {
+    let options = JsonParserOptions::default();
+    let parser = parse_single_value("18446744073709551615", options);
+    assert_eq!(parser.current_uint::<u64>().unwrap(), u64::MAX);
+    assert!(parser.current_uint::<u32>().is_err());
+    let parser = parse_single_value("340282366920938463463374607431768211455", options);
+    assert_eq!(parser.current_uint::<u128>().unwrap(), u128::MAX);
+    let parser = parse_single_value("-1", options);
+    assert!(parser.current_uint::<u64>().is_err());
+    let parser = parse_single_value("-0", options);
+    assert_eq!(parser.current_uint::<u64>().unwrap(), 0);
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_int_overflow_policy(IntOverflowPolicy::Saturate)
+        .build();
+    let parser = parse_single_value("-1", options);
+    assert_eq!(parser.current_uint::<u64>().unwrap(), 0);
+    let parser = parse_single_value("300", options);
+    assert_eq!(parser.current_uint::<u8>().unwrap(), u8::MAX);
+}
+
+/// Test that [`JsonParser::current_number()`] returns the best-fitting
+/// representation of a number
+#[test]
+fn current_number() {
+    let json = r#"[0, -9223372036854775808, 9223372036854775808, -9223372036854775809,
+        1.5e3, -0.0, 1e400]"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json.as_bytes()));
+    let mut numbers = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        if matches!(e, JsonEvent::ValueInt | JsonEvent::ValueFloat) {
+            numbers.push(format!("{:?}", parser.current_number().unwrap()));
+        }
+    }
+    assert_eq!(
+        numbers,
+        vec![
+            "I64(0)",
+            "I64(-9223372036854775808)",
+            "U64(9223372036854775808)",
+            "Big(\"-9223372036854775809\")",
+            "F64(1500.0)",
+            "F64(-0.0)",
+            "Big(\"1e400\")",
+        ]
+    );
+}
+
+/// Test that escape sequences are decoded and that the same string can be
+/// read multiple times
+#[test]
+fn unescape_strings() {
+    let json = r#"{"k\"ey": "a\tbé", "plain": "\\"}"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json.as_bytes()));
+    let mut strings = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        if matches!(e, JsonEvent::FieldName | JsonEvent::ValueString) {
+            assert_eq!(parser.current_str().unwrap(), parser.current_str().unwrap());
+            strings.push(parser.current_str().unwrap().to_string());
+        }
+    }
+    assert_eq!(strings, vec!["k\"ey", "a\tb\u{e9}", "plain", "\\"]);
+}
+
+/// Test that strings read directly from a [`SliceJsonFeeder`] are borrowed
+/// from the input if they do not contain escape sequences
+#[test]
+fn slice_borrowed_strings() {
+    let json = r#""a"{"b\"":"c"} 12 "de""#;
+    let options = JsonParserOptionsBuilder::default()
+        .with_streaming(true)
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json.as_bytes()), options);
+    let mut strings = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::FieldName | JsonEvent::ValueString => {
+                let s = parser.current_str_cow().unwrap();
+                assert_eq!(s, parser.current_str().unwrap());
+                assert_eq!(matches!(s, Cow::Borrowed(_)), s != "b\"");
+                strings.push(s);
+            }
+            JsonEvent::ValueInt => assert_eq!(parser.current_int::<i32>().unwrap(), 12),
+            _ => {}
+        }
+    }
+    assert_eq!(strings, vec!["a", "b\"", "c", "de"]);
+}
+
+/// Test that [`JsonParser::next_events()`] stops when the parser needs more
+/// input and reports correct spans across pushes
+#[test]
+fn next_events_with_spans() {
+    let json = br#"[1, true,"ab", {"c": -2.5e1}]"#;
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut events = Vec::new();
+    let mut i = 0;
+    loop {
+        parser.next_events(&mut events).unwrap();
+        if events.last().map(|e| e.0) != Some(JsonEvent::NeedMoreInput) {
+            break;
+        }
+        events.pop();
+        // feed one byte at a time
+        if i < json.len() {
+            parser.feeder.push_byte(json[i]).unwrap();
+            i += 1;
+        } else {
+            parser.feeder.done();
+        }
+    }
+
+    let actual = events
+        .iter()
+        .map(|(e, s)| (*e, std::str::from_utf8(&json[s.start..s.end]).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        vec![
+            (JsonEvent::StartArray, "["),
+            (JsonEvent::ValueInt, "1"),
+            (JsonEvent::ValueTrue, "true"),
+            (JsonEvent::ValueString, "\"ab\""),
+            (JsonEvent::StartObject, "{"),
+            (JsonEvent::FieldName, "\"c\""),
+            (JsonEvent::ValueFloat, "-2.5e1"),
+            (JsonEvent::EndObject, "}"),
+            (JsonEvent::EndArray, "]"),
+        ]
+    );
+}
+
+/// Test that spans are reported correctly for top-level values and in
+/// streaming mode
+#[test]
+fn spans_streaming() {
+    let json = br#"12 null"x"[]3"#;
+    let options = JsonParserOptionsBuilder::default()
+        .with_streaming(true)
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    let mut spans = Vec::new();
+    while parser.next_event().unwrap().is_some() {
+        let s = parser.current_span();
+        spans.push(std::str::from_utf8(&json[s.start..s.end]).unwrap());
+    }
+    assert_eq!(spans, vec!["12", "null", "\"x\"", "[", "]", "3"]);
+}
+
+/// Test that long runs of white space, string contents, and digits are
+/// parsed correctly even if they are split across multiple pushes
+#[test]
+fn long_runs() {
+    let json = format!(
+        "{{{ws}\"{s}\"{ws}:{ws}[{ws}\"{s}\\n{s}\",{ws}{d},{ws}-{d}.{d}e-{e}{ws}]{ws}}}",
+        ws = " \t\r\n".repeat(50),
+        s = "x\u{0153}".repeat(100),
+        d = "1".repeat(18),
+        e = "0".repeat(40) + "1",
+    );
+    assert_json_eq(&json, &parse(&json));
+}
+
+/// Test that a parser with a [`FixedStorage`] decodes escape sequences and
+/// reports when its value buffer or stack is exhausted
+#[test]
+fn fixed_storage() {
+    let json = br#"{"a\u0062":["\"\\n\"", 12345]}"#;
+    let mut buf = [0u8; 8];
+    let mut parser = JsonParser::new_with_storage(
+        SliceJsonFeeder::new(json),
+        FixedStorage::<4>::new(&mut buf),
+        JsonParserOptions::default(),
+    );
+    let mut values = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::FieldName | JsonEvent::ValueString => {
+                values.push(parser.current_str().unwrap().to_string())
+            }
+            JsonEvent::ValueInt => values.push(parser.current_int::<i32>().unwrap().to_string()),
+            _ => {}
+        }
+    }
+    assert_eq!(values, vec!["ab", "\"\\n\"", "12345"]);
+
+    let mut buf = [0u8; 4];
+    let mut parser = JsonParser::new_with_storage(
+        SliceJsonFeeder::new(br#"["abcde"]"#),
+        FixedStorage::<4>::new(&mut buf),
+        JsonParserOptions::default(),
+    );
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert!(matches!(
+        parser.next_event(),
+        Err(ParserError::ValueBufferFull)
+    ));
+
+    let mut buf = [0u8; 4];
+    let mut parser = JsonParser::new_with_storage(
+        SliceJsonFeeder::new(b"[[[[1]]]]"),
+        FixedStorage::<4>::new(&mut buf),
+        JsonParserOptions::default(),
+    );
+    let mut result = parser.next_event();
+    while let Ok(Some(_)) = result {
+        result = parser.next_event();
+    }
+    assert!(matches!(result, Err(ParserError::MaxDepthExceeded)));
+}
+
+/// Test that a parser with a [`ScratchStorage`] decodes escape sequences and
+/// reuses the caller's vector for subsequent documents
+#[test]
+fn scratch_storage() {
+    fn parse(json: &[u8], scratch: &mut Vec<u8>) -> Vec<String> {
+        let mut parser = JsonParser::new_with_storage(
+            SliceJsonFeeder::new(json),
+            ScratchStorage::<4>::new(scratch),
+            JsonParserOptions::default(),
+        );
+        let mut values = Vec::new();
+        while let Some(e) = parser.next_event().unwrap() {
+            match e {
+                JsonEvent::FieldName | JsonEvent::ValueString => {
+                    values.push(parser.current_str().unwrap().to_string())
+                }
+                JsonEvent::ValueInt => {
+                    values.push(parser.current_int::<i32>().unwrap().to_string())
+                }
+                _ => {}
+            }
+        }
+        values
+    }
+
+    let mut scratch = Vec::new();
+
+    assert_eq!(
+        parse(br#"{"a\u0062":["\"\\n\"", 12345]}"#, &mut scratch),
+        vec!["ab", "\"\\n\"", "12345"]
+    );
+    assert_eq!(parse(br#"["xyz"]"#, &mut scratch), vec!["xyz"]);
+    let capacity = scratch.capacity();
+    let ptr = scratch.as_ptr();
+    assert_eq!(
+        parse(br#"{"b": ["c", 1]}"#, &mut scratch),
+        vec!["b", "c", "1"]
+    );
+    assert_eq!(scratch.capacity(), capacity);
+    assert_eq!(scratch.as_ptr(), ptr);
+
+    let mut parser = JsonParser::new_with_storage(
+        SliceJsonFeeder::new(b"[[[[1]]]]"),
+        ScratchStorage::<4>::new(&mut scratch),
+        JsonParserOptions::default(),
+    );
+    let mut result = parser.next_event();
+    while let Ok(Some(_)) = result {
+        result = parser.next_event();
+    }
+    assert!(matches!(result, Err(ParserError::MaxDepthExceeded)));
+}
+
+/// Test that statistics do not count [`JsonEvent::NeedMoreInput`] and keep
+/// track of the maximum depth
+#[test]
+fn stats() {
+    let json = br#"[[{"a":[]}], [true, -1]]"#;
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut i = 0;
+    while let Some(e) = parser.next_event().unwrap() {
+        if e == JsonEvent::NeedMoreInput {
+            if i < json.len() {
+                parser.feeder.push_byte(json[i]).unwrap();
+                i += 1;
+            } else {
+                parser.feeder.done();
+            }
+        }
+    }
+    let stats = parser.stats();
+    assert_eq!(stats.bytes_consumed, json.len());
+    assert_eq!(stats.events_emitted, 13);
+    assert_eq!(stats.max_depth_reached, 4);
+    assert_eq!(stats.strings_decoded, 1);
+    assert_eq!(stats.numbers_decoded, 1);
+}
+
+/// Test that the parser stops if its byte or time budget is exceeded
+#[test]
+fn budgets() {
+    let json = br#"[1, 2, 3, 4, 5, 6, 7, 8, 9]"#;
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_byte_budget(10)
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    let mut result = parser.next_event();
+    while let Ok(Some(_)) = result {
+        result = parser.next_event();
+    }
+    assert!(matches!(result, Err(ParserError::Cancelled)));
+    assert!(parser.parsed_bytes() < json.len());
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_time_budget(Duration::ZERO)
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    assert!(matches!(parser.next_event(), Err(ParserError::Cancelled)));
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_byte_budget(json.len())
+        .with_time_budget(Duration::from_secs(3600))
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    while parser.next_event().unwrap().is_some() {}
+}
+
+/// Parse the given JSON text until the end or until an error occurs
+fn parse_result(json: &[u8], options: JsonParserOptions) -> Result<(), ParserError> {
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    while parser.next_event()?.is_some() {}
+    Ok(())
+}
+
+/// A feeder that returns its input byte by byte and does not support
+/// [`JsonFeeder::peek_input()`], so the parser cannot consume runs of bytes
+/// in bulk
+struct ByteJsonFeeder<I> {
+    input: I,
+    next: Option<u8>,
+}
+
+impl<I: Iterator<Item = u8>> ByteJsonFeeder<I> {
+    fn new(mut input: I) -> Self {
+        let next = input.next();
+        ByteJsonFeeder { input, next }
+    }
+}
+
+impl<I: Iterator<Item = u8>> JsonFeeder for ByteJsonFeeder<I> {
+    fn has_input(&self) -> bool {
+        self.next.is_some()
+    }
+
+    fn is_done(&self) -> bool {
+        self.next.is_none()
+    }
+
+    fn next_input(&mut self) -> Option<u8> {
+        let r = self.next;
+        if r.is_some() {
+            self.next = self.input.next();
+        }
+        r
+    }
+}
+
+/// Parse the given input byte by byte and return the first error and the
+/// number of bytes consumed until then
+fn parse_bytewise<I>(input: I, options: JsonParserOptions) -> Result<(), (ParserError, usize)>
+where
+    I: Iterator<Item = u8>,
+{
+    let mut parser = JsonParser::new_with_options(ByteJsonFeeder::new(input), options);
+    loop {
+        match parser.next_event() {
+            Ok(Some(_)) => {}
+            Ok(None) => return Ok(()),
+            Err(e) => return Err((e, parser.parsed_bytes())),
+        }
+    }
+}
+
+/// Test the limits of the hardened profile
+#[test]
+fn hardened_profile() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_profile(Profile::Hardened)
+        .build();
+    assert_eq!(options.max_depth(), 128);
+
+    // keys may be repeated in different objects
+    assert!(parse_result(br#"{"a":{"a":1},"b":[{"a":2},{"a":3}]}"#, options).is_ok());
+
+    // keys are compared after decoding escape sequences
+    assert!(matches!(
+        parse_result(br#"{"a":1,"b":{},"\u0061":2}"#, options),
+        Err(ParserError::DuplicateKey)
+    ));
+
+    assert!(matches!(
+        parse_result(&[0xEF, 0xBB, 0xBF, b'1'], options),
+        Err(ParserError::SyntaxError)
+    ));
+
+    let json = format!("[\"{}\"]", "x".repeat(1024 * 1024 + 1));
+    assert!(matches!(
+        parse_result(json.as_bytes(), options),
+        Err(ParserError::StringTooLong)
+    ));
+
+    let json = format!("[{}0]", "0,".repeat(1_000_000));
+    assert!(matches!(
+        parse_result(json.as_bytes(), options),
+        Err(ParserError::TooManyTokens)
+    ));
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_string_len(3)
+        .with_max_tokens(4)
+        .build();
+    assert!(parse_result(br#"["abc", "\n"]"#, options).is_ok());
+    assert!(matches!(
+        parse_result(br#"["abc\n"]"#, options),
+        Err(ParserError::StringTooLong)
+    ));
+    assert!(matches!(
+        parse_result(br#"[1,2,3]"#, options),
+        Err(ParserError::TooManyTokens)
+    ));
+}
+
+/// Test that the string length limit is enforced while a string is being
+/// read, even if the feeder only provides one byte at a time
+#[test]
+fn max_string_len_bytewise() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_string_len(3)
+        .build();
+    assert!(parse_bytewise(br#"["abc", "\n"]"#.iter().copied(), options).is_ok());
+    assert!(matches!(
+        parse_bytewise(br#"["ab\n"]"#.iter().copied(), options),
+        Err((ParserError::StringTooLong, _))
+    ));
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_profile(Profile::Hardened)
+        .build();
+    for c in [b'x', b'\\'] {
+        let json = b"[\""
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(c, 5_000_000));
+        let Err((e, parsed)) = parse_bytewise(json, options) else {
+            panic!("string should be rejected");
+        };
+        assert!(matches!(e, ParserError::StringTooLong));
+        assert!(parsed <= 1024 * 1024 + 3, "{parsed} bytes have been read");
+    }
+}
+
+/// Test that numbers with leading zeros or a leading plus sign are only
+/// accepted if the respective options are enabled
+#[test]
+fn lenient_numbers() {
+    let json = br#"[+1, 0123, -007.5, +0.5e+1, 00]"#;
+    assert!(matches!(
+        parse_result(json, JsonParserOptions::default()),
+        Err(ParserError::SyntaxError)
+    ));
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_allow_leading_plus(true)
+        .with_allow_leading_zeros(true)
+        .with_streaming(true)
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    let mut values = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::ValueInt => values.push(parser.current_int::<i64>().unwrap() as f64),
+            JsonEvent::ValueFloat => values.push(parser.current_float().unwrap()),
+            _ => {}
+        }
+    }
+    assert_eq!(values, vec![1.0, 123.0, -7.5, 5.0, 0.0]);
+
+    // streaming mode and spans
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(b"+1 +2"), options);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.current_span(), Span::new(0, 2));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.current_int::<i32>().unwrap(), 2);
+
+    for json in ["+", "+-1", "-+1", "[+]", "{\"a\":++1}"] {
+        assert!(
+            parse_result(json.as_bytes(), options).is_err(),
+            "{json} should be rejected"
+        );
+    }
+}
+
+/// Test that interned strings are shared and that the interner respects its
+/// maximum length
+#[test]
+fn interned_strings() {
+    let json = br#"[{"id": "a", "name": 1}, {"id": "b", "n\u0061me": 2}, {"id": "c", "new": 3}]"#;
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut interner = StringInterner::with_max_len(3);
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::NeedMoreInput => i += parser.feeder.push_bytes(&json[i..]),
+            JsonEvent::FieldName => keys.push(parser.current_str_interned(&mut interner).unwrap()),
+            _ => {}
+        }
+        if i == json.len() {
+            parser.feeder.done();
+        }
+    }
+
+    assert_eq!(
+        keys.iter().map(|k| &**k).collect::<Vec<_>>(),
+        vec!["id", "name", "id", "name", "id", "new"]
+    );
+    assert!(Arc::ptr_eq(&keys[0], &keys[2]));
+    assert!(Arc::ptr_eq(&keys[0], &keys[4]));
+
+    // keys with different escape sequences are cached separately
+    assert!(!Arc::ptr_eq(&keys[1], &keys[3]));
+
+    // the interner is full
+    assert_eq!(interner.len(), 3);
+}
+
+/// Test that raw strings keep their escape sequences regardless of the feeder
+#[test]
+fn raw_strings() {
+    let json = r#"{"k\"ey": ["é\t", -1.5e3]}"#;
+    let expected = vec![r#"k\"ey"#, r"é\t", "-1.5e3"];
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json.as_bytes()));
+    let mut raw = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        if matches!(
+            e,
+            JsonEvent::FieldName | JsonEvent::ValueString | JsonEvent::ValueFloat
+        ) {
+            raw.push(parser.current_raw_str().unwrap().to_string());
+        }
+    }
+    assert_eq!(raw, expected);
+
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut raw = Vec::new();
+    let mut i = 0;
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::NeedMoreInput => {
+                if i < json.len() {
+                    parser.feeder.push_byte(json.as_bytes()[i]).unwrap();
+                    i += 1;
+                } else {
+                    parser.feeder.done();
+                }
+            }
+            JsonEvent::FieldName | JsonEvent::ValueString | JsonEvent::ValueFloat => {
+                raw.push(parser.current_raw_str().unwrap().to_string())
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(raw, expected);
+}
+
+/// Test that the parent field name and the array index are tracked for all
+/// kinds of events
+#[test]
+fn parent_field_and_array_index() {
+    let json = br#"{"a": 1, "items": [{"b": [true]}, 2, []], "c": {"d": null}}"#;
+    let options = JsonParserOptionsBuilder::default()
+        .with_track_path(true)
+        .build();
+    let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+    let mut actual = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        actual.push((
+            e,
+            parser.current_parent_field().map(|s| s.to_string()),
+            parser.current_array_index(),
+        ));
+    }
+
+    let f = |s: &str| Some(s.to_string());
+    assert_eq!(
+        actual,
+        vec![
+            (JsonEvent::StartObject, None, None),
+            (JsonEvent::FieldName, None, None),
+            (JsonEvent::ValueInt, f("a"), None),
+            (JsonEvent::FieldName, None, None),
+            (JsonEvent::StartArray, f("items"), None),
+            (JsonEvent::StartObject, f("items"), Some(0)),
+            (JsonEvent::FieldName, f("items"), None),
+            (JsonEvent::StartArray, f("b"), None),
+            (JsonEvent::ValueTrue, f("b"), Some(0)),
+            (JsonEvent::EndArray, f("b"), None),
+            (JsonEvent::EndObject, f("items"), Some(0)),
+            (JsonEvent::ValueInt, f("items"), Some(1)),
+            (JsonEvent::StartArray, f("items"), Some(2)),
+            (JsonEvent::EndArray, f("items"), Some(2)),
+            (JsonEvent::EndArray, f("items"), None),
+            (JsonEvent::FieldName, None, None),
+            (JsonEvent::StartObject, f("c"), None),
+            (JsonEvent::FieldName, f("c"), None),
+            (JsonEvent::ValueNull, f("d"), None),
+            (JsonEvent::EndObject, f("c"), None),
+            (JsonEvent::EndObject, None, None),
+        ]
+    );
+
+    // nothing is tracked by default
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    while parser.next_event().unwrap().is_some() {
+        assert_eq!(parser.current_parent_field(), None);
+        assert_eq!(parser.current_array_index(), None);
+    }
+}
+
+/// Test that the number of members per object and elements per array can
+/// be limited
+#[test]
+fn container_len_limits() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_object_len(2)
+        .with_max_array_len(3)
+        .build();
+
+    assert!(parse_result(br#"{"a": [1, [], {"x": 1, "y": 2}], "b": {}}"#, options).is_ok());
+    assert!(parse_result(br#"[[1, 2, 3], ["a", "b", "c"], [{}, [], null]]"#, options).is_ok());
+
+    for json in [
+        r#"{"a": 1, "b": 2, "c": 3}"#,
+        r#"[1, 2, 3, 4]"#,
+        r#"[[], {}, "x", true]"#,
+        r#"{"a": [{"b": 1, "c": [0, 0, 0, 0]}]}"#,
+    ] {
+        assert!(
+            matches!(
+                parse_result(json.as_bytes(), options),
+                Err(ParserError::ContainerTooLarge)
+            ),
+            "{json} should be rejected"
+        );
+    }
+
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_array_len(0)
+        .build();
+    assert!(parse_result(b"[ ]", options).is_ok());
+    assert!(matches!(
+        parse_result(b"[ 1 ]", options),
+        Err(ParserError::ContainerTooLarge)
+    ));
+}
+
+/// Test that long numbers are rejected, even before they have been read
+/// completely
+#[test]
+fn max_number_len() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_number_len(6)
+        .build();
+    assert!(parse_result(b"[123456, -1.5e9, -12345]", options).is_ok());
+    for json in [
+        "1234567",
+        "[-123456]",
+        "[1.5e100]",
+        "[1.23456]",
+        "{\"a\": 1e+1234}",
+    ] {
+        assert!(
+            matches!(
+                parse_result(json.as_bytes(), options),
+                Err(ParserError::NumberTooLong)
+            ),
+            "{json} should be rejected"
+        );
+    }
+
+    let mut parser = JsonParser::new_with_options(PushJsonFeeder::new(), options);
+    parser.feeder.push_bytes(b"[1234567");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert!(matches!(
+        parser.next_event(),
+        Err(ParserError::NumberTooLong)
+    ));
+
+    // the same applies if the feeder only provides one byte at a time
+    assert!(parse_bytewise(b"[123456, -1.5e9]".iter().copied(), options).is_ok());
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_number_len(10)
+        .build();
+    for prefix in [&b"["[..], b"[-", b"[1.", b"[1e"] {
+        let json = prefix
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(b'1', 5_000_000));
+        let Err((e, parsed)) = parse_bytewise(json, options) else {
+            panic!("number should be rejected");
+        };
+        assert!(matches!(e, ParserError::NumberTooLong));
+        assert!(parsed <= 12, "{parsed} bytes have been read");
+    }
+}
+
+/// Test that the total length of all values in a document is limited and
+/// that the limit applies to each document separately in streaming mode
+#[test]
+fn max_total_value_len() {
+    let options = JsonParserOptionsBuilder::default()
+        .with_max_total_value_len(16)
+        .with_streaming(true)
+        .build();
+    assert!(parse_result(br#"{"ab": "cd", "e": 1234}"#, options).is_ok());
+    assert!(parse_result(
+        br#"["abcdefgh", "ij"] ["abcdefgh", "ij"] 1234567890"#,
+        options
+    )
+    .is_ok());
+    for json in [
+        r#"{"ab": "cd", "e": 123456}"#,
+        r#"["abcde", "fghij", "klm"]"#,
+        r#"[100, 200, 300, 400, 500, 600]"#,
+        r#""abcdefghijklmnopqrstuvwxyz""#,
+    ] {
+        assert!(
+            matches!(
+                parse_result(json.as_bytes(), options),
+                Err(ParserError::ValuesTooLarge)
+            ),
+            "{json} should be rejected"
+        );
+    }
+
+    // long strings are rejected before they have been read completely
+    let mut parser = JsonParser::new_with_options(PushJsonFeeder::new(), options);
+    parser.feeder.push_bytes(br#"["abc", "defghijklmnop"#);
+    let mut result = parser.next_event();
+    while let Ok(Some(JsonEvent::StartArray | JsonEvent::ValueString)) = result {
+        result = parser.next_event();
+    }
+    assert!(matches!(result, Err(ParserError::ValuesTooLarge)));
+
+    // the same applies if the feeder only provides one byte at a time
+    assert!(parse_bytewise(br#"{"ab": "cd", "e": 1234}"#.iter().copied(), options).is_ok());
+    for prefix in [&b"[\"ab\", \""[..], b"[\"ab\", 1"] {
+        let json = prefix
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(b'1', 5_000_000));
+        let Err((e, parsed)) = parse_bytewise(json, options) else {
+            panic!("value should be rejected");
+        };
+        assert!(matches!(e, ParserError::ValuesTooLarge));
+        assert!(parsed <= 32, "{parsed} bytes have been read");
+    }
+}
+
+/// Test that floats are parsed exactly like the standard library does
+#[test]
+fn float_parsing() {
+    for json in [
+        "0.0",
+        "-0.0",
+        "1.5",
+        "-2.25e-3",
+        "3E+10",
+        "0.1",
+        "123456789012345678901234567890.5",
+        "2.2250738585072011e-308",
+        "4.9e-324",
+        "1e-400",
+        "1.7976931348623157e308",
+        "1e400",
+        "-1e400",
+        "9007199254740993.0",
+    ] {
+        let parser = parse_single_value(json, JsonParserOptions::default());
+        assert_eq!(
+            parser.current_float().unwrap(),
+            json.parse::<f64>().unwrap(),
+            "{json}"
+        );
+    }
+}
+
+/// Test that integers are read correctly from both borrowed and buffered
+/// input, including values at and beyond the limits of 64-bit types
+#[test]
+fn int_fast_path() {
+    let json = br#"[0, -0, 7, -42, 9223372036854775807, -9223372036854775808,
+        18446744073709551615, 18446744073709551616, -9223372036854775809, 1.5, 2e3, 123]"#;
+
+    fn check<T: JsonFeeder>(parser: &JsonParser<T>, values: &mut Vec<String>, e: JsonEvent) {
+        match e {
+            JsonEvent::ValueInt => values.push(format!(
+                "{}/{:?}/{:?}/{:?}",
+                parser.current_int::<i128>().unwrap(),
+                parser.current_int::<i64>().ok(),
+                parser.current_int::<u64>().ok(),
+                parser.current_int::<u8>().ok(),
+            )),
+            JsonEvent::ValueFloat => values.push(parser.current_float().unwrap().to_string()),
+            _ => {}
+        }
+    }
+
+    let expected = vec![
+        "0/Some(0)/Some(0)/Some(0)",
+        "0/Some(0)/Some(0)/Some(0)",
+        "7/Some(7)/Some(7)/Some(7)",
+        "-42/Some(-42)/None/None",
+        "9223372036854775807/Some(9223372036854775807)/Some(9223372036854775807)/None",
+        "-9223372036854775808/Some(-9223372036854775808)/None/None",
+        "18446744073709551615/None/Some(18446744073709551615)/None",
+        "18446744073709551616/None/None/None",
+        "-9223372036854775809/None/None/None",
+        "1.5",
+        "2000",
+        "123/Some(123)/Some(123)/Some(123)",
+    ];
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    let mut values = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        check(&parser, &mut values, e);
+    }
+    assert_eq!(values, expected);
+
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut values = Vec::new();
+    let mut i = 0;
+    while let Some(e) = parser.next_event().unwrap() {
+        if e == JsonEvent::NeedMoreInput {
+            // feed three bytes at a time to split numbers
+            if i < json.len() {
+                i += parser.feeder.push_bytes(&json[i..json.len().min(i + 3)]);
+            } else {
+                parser.feeder.done();
+            }
+        }
+        check(&parser, &mut values, e);
+    }
+    assert_eq!(values, expected);
+}
+
+/// Test that the fused accessors can be retried after more input has been
+/// provided
+#[test]
+fn fused_accessors() {
+    let json = br#"{"name": "Elvis", "age": 42, "height": 1.82, "weight": 75}"#;
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut i = 0;
+
+    // call the given function and feed the parser byte by byte until it
+    // does not need more input anymore
+    macro_rules! feed {
+        ($e:expr, $need_more_input:pat) => {
+            loop {
+                match $e {
+                    $need_more_input => {
+                        parser.feeder.push_byte(json[i]).unwrap();
+                        i += 1;
+                    }
+                    r => break r,
+                }
+            }
+        };
+    }
+
+    macro_rules! next {
+        ($f:ident) => {
+            feed!(parser.$f(), Err(NextValueError::NeedMoreInput)).map(|v| v.to_string())
+        };
+    }
+
+    macro_rules! field {
+        () => {
+            feed!(parser.next_event(), Ok(Some(JsonEvent::NeedMoreInput)))
+        };
+    }
+
+    assert!(matches!(field!(), Ok(Some(JsonEvent::StartObject))));
+    assert!(matches!(field!(), Ok(Some(JsonEvent::FieldName))));
+    assert_eq!(next!(next_str).unwrap(), "Elvis");
+    assert!(matches!(field!(), Ok(Some(JsonEvent::FieldName))));
+    assert_eq!(next!(next_i64).unwrap(), "42");
+    assert!(matches!(field!(), Ok(Some(JsonEvent::FieldName))));
+    assert_eq!(next!(next_f64).unwrap(), "1.82");
+    assert!(matches!(field!(), Ok(Some(JsonEvent::FieldName))));
+    assert!(matches!(
+        next!(next_str),
+        Err(NextValueError::UnexpectedEvent {
+            expected: "string",
+            found: Some(JsonEvent::ValueInt)
+        })
+    ));
+}
+
+/// Test that owned events can be sent to another thread
+#[test]
+fn owned_events() {
+    let json = br#"[{"a": "b\n"}, -1, 2.5, true, false, null]"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = std::thread::spawn(move || rx.into_iter().collect::<Vec<OwnedJsonEvent>>());
+    while let Some(e) = parser.next_owned_event().unwrap() {
+        tx.send(e).unwrap();
+    }
+    drop(tx);
+
+    let events = handle.join().unwrap();
+    assert_eq!(
+        events,
+        vec![
+            OwnedJsonEvent::StartArray,
+            OwnedJsonEvent::StartObject,
+            OwnedJsonEvent::FieldName("a".to_string()),
+            OwnedJsonEvent::ValueString("b\n".to_string()),
+            OwnedJsonEvent::EndObject,
+            OwnedJsonEvent::ValueInt(-1),
+            OwnedJsonEvent::ValueFloat(2.5),
+            OwnedJsonEvent::ValueTrue,
+            OwnedJsonEvent::ValueFalse,
+            OwnedJsonEvent::ValueNull,
+            OwnedJsonEvent::EndArray,
+        ]
+    );
+    assert_eq!(events[2].event(), JsonEvent::FieldName);
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(
+        b"[9223372036854775807, 18446744073709551615, 18446744073709551616]",
+    ));
+    assert_eq!(
+        parser.next_owned_event().unwrap(),
+        Some(OwnedJsonEvent::StartArray)
+    );
+    assert_eq!(
+        parser.next_owned_event().unwrap(),
+        Some(OwnedJsonEvent::ValueInt(i64::MAX))
+    );
+    let e = parser.next_owned_event().unwrap().unwrap();
+    assert_eq!(e, OwnedJsonEvent::ValueUint(u64::MAX));
+    assert_eq!(e.event(), JsonEvent::ValueInt);
+    assert!(matches!(
+        parser.next_owned_event(),
+        Err(NextValueError::Int(_))
+    ));
+}
+
+/// Test that the parser can be rewound to save points created before any
+/// event and that it then returns the same events again
+#[test]
+fn save_point_rewind() {
+    fn check(json: &[u8], options: JsonParserOptions) {
+        let mut parser = JsonParser::new_with_options(SliceJsonFeeder::new(json), options);
+        let mut save_points = vec![parser.save_point()];
+        let mut events = Vec::new();
+        while let Some(e) = parser.next_owned_event().unwrap() {
+            events.push((e, parser.current_span()));
+            save_points.push(parser.save_point());
+        }
+
+        for (i, sp) in save_points.iter().enumerate() {
+            parser.rewind(sp).unwrap();
+            if i > 0 {
+                // the value of the most recent event can be read again
+                assert_eq!(parser.current_span(), events[i - 1].1);
+            }
+            let mut rest = Vec::new();
+            while let Some(e) = parser.next_owned_event().unwrap() {
+                rest.push((e, parser.current_span()));
+            }
+            assert_eq!(rest, events[i..]);
+        }
+    }
+
+    let json = br#"{"a\u0062": ["c\n", -12, 0.5e1, {"d": [true, false]}], "e": null, "f": 7}"#;
+    check(json, JsonParserOptions::default());
+    check(
+        json,
+        JsonParserOptionsBuilder::default()
+            .with_track_path(true)
+            .with_reject_duplicate_keys(true)
+            .build(),
+    );
+    check(
+        b"1 2 [3] \"x\" 45",
+        JsonParserOptionsBuilder::default()
+            .with_streaming(true)
+            .build(),
+    );
+
+    let mut parser = JsonParser::new(StrJsonFeeder::new(r#"[{"t": "a"}, 2]"#));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartObject));
+    let sp1 = parser.save_point();
+    while parser.next_event().unwrap() != Some(JsonEvent::EndObject) {}
+    assert_eq!(parser.next_i64().unwrap(), 2);
+    let sp2 = parser.save_point();
+    parser.rewind(&sp1).unwrap();
+    assert_eq!(parser.rewind(&sp2), Err(RewindError::Ahead));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::FieldName));
+    assert_eq!(parser.current_str().unwrap(), "t");
+
+    // the feeder discards its buffer when it reads more input
+    let json: &[u8] = br#"["abcdefgh", 1]"#;
+    let mut parser = JsonParser::new(BufReaderJsonFeeder::new(std::io::BufReader::with_capacity(
+        4, json,
+    )));
+    let mut sp = None;
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::NeedMoreInput => parser.feeder.fill_buf().unwrap(),
+            JsonEvent::StartArray => sp = Some(parser.save_point()),
+            JsonEvent::ValueString => break,
+            _ => {}
+        }
+    }
+    assert_eq!(parser.rewind(&sp.unwrap()), Err(RewindError::NotBuffered));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::NeedMoreInput));
+}
+
+/// Test that borrowed events carry the same values as owned events
+#[test]
+fn borrowed_events() {
+    let json = br#"{"a\u0062": ["c", 1, 0.5]}"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+
+    let mut events = Vec::new();
+    while let Some(e) = parser.advance().unwrap() {
+        if let BorrowedJsonEvent::FieldName(s) = e {
+            assert_eq!(s, "ab");
+        }
+        assert_eq!(e.into_owned().event(), e.event());
+        events.push(e.into_owned());
+    }
+    assert_eq!(
+        events,
+        vec![
+            OwnedJsonEvent::StartObject,
+            OwnedJsonEvent::FieldName("ab".to_string()),
+            OwnedJsonEvent::StartArray,
+            OwnedJsonEvent::ValueString("c".to_string()),
+            OwnedJsonEvent::ValueInt(1),
+            OwnedJsonEvent::ValueFloat(0.5),
+            OwnedJsonEvent::EndArray,
+            OwnedJsonEvent::EndObject,
+        ]
+    );
+}
+
+/// Test that events, spans, and errors can be serialized and deserialized
+#[cfg(feature = "serde")]
+#[test]
+fn serde_events() {
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(br#"{"a": [1.5, null] x"#));
+    let mut events = Vec::new();
+    let error = loop {
+        match parser.next_owned_event() {
+            Ok(Some(e)) => events.push((e, parser.current_span())),
+            Ok(None) => unreachable!(),
+            Err(NextValueError::Parser(e)) => break e,
+            Err(e) => panic!("{e}"),
+        }
+    };
+
+    let json = serde_json::to_string(&(&events, error)).unwrap();
+    let (events2, error2): (Vec<(OwnedJsonEvent, Span)>, ParserError) =
+        serde_json::from_str(&json).unwrap();
+    assert_eq!(events2, events);
+    assert!(matches!(error2, ParserError::SyntaxError));
+    assert_eq!(
+        serde_json::to_value(&events[1]).unwrap(),
+        serde_json::json!([{"FieldName": "a"}, {"start": 1, "end": 4}])
+    );
+}
+
+/// Test that the event iterator composes with iterator adapters and stops
+/// after an error
+#[test]
+fn events_iterator() {
+    let json = br#"[1, 2, 3, 4]"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+    let sum: i64 = parser
+        .events()
+        .map(|e| match e.unwrap() {
+            OwnedJsonEvent::ValueInt(i) => i,
+            _ => 0,
+        })
+        .sum();
+    assert_eq!(sum, 10);
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(b"[1, 2}"));
+    let events = parser.events().collect::<Vec<_>>();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(
+        events[2],
+        Err(NextValueError::Parser(ParserError::SyntaxError))
+    ));
+
+    // provide more input through the iterator
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    let mut events = parser.events();
+    let mut values = Vec::new();
+    while let Some(e) = events.next() {
+        match e.unwrap() {
+            OwnedJsonEvent::NeedMoreInput => {
+                let feeder = &mut events.parser().feeder;
+                feeder.push_bytes(b"[true]");
+                feeder.done();
+            }
+            e => values.push(e),
+        }
+    }
+    assert_eq!(
+        values,
+        vec![
+            OwnedJsonEvent::StartArray,
+            OwnedJsonEvent::ValueTrue,
+            OwnedJsonEvent::EndArray
+        ]
+    );
+}
+
+/// Test that the expect helpers report what was expected and what was found
+#[test]
+fn expect_helpers() {
+    let json = br#"{"id": 1, "tags": ["a"]}"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+
+    assert!(matches!(
+        parser.expect_start_array(),
+        Err(NextValueError::UnexpectedEvent {
+            expected: "start of array",
+            found: Some(JsonEvent::StartObject)
+        })
+    ));
+    match parser.expect_field("name") {
+        Err(e @ NextValueError::UnexpectedField { .. }) => {
+            assert_eq!(e.to_string(), "expected field `name', found `id'");
+        }
+        r => panic!("unexpected result: {r:?}"),
+    }
+    assert_eq!(parser.next_i64().unwrap(), 1);
+    parser.expect_field("tags").unwrap();
+    parser.expect_start_array().unwrap();
+    assert_eq!(parser.expect_string().unwrap(), "a");
+    parser.expect_end_array().unwrap();
+    assert!(matches!(
+        parser.expect_null(),
+        Err(NextValueError::UnexpectedEvent {
+            found: Some(JsonEvent::EndObject),
+            ..
+        })
+    ));
+}
+
+/// Test that fields of an object can be iterated with their values
+#[test]
+fn next_field() {
+    let json = br#"{"name": "Elvis", "albums": [{"title": "Elvis"}], "age": 42}"#;
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(json));
+
+    let mut names = Vec::new();
+    parser.expect_start_object().unwrap();
+    while let Some(field) = parser.next_field().unwrap() {
+        names.push(field.name().to_string());
+        match field.name() {
+            "age" => assert_eq!(field.value().next_i64().unwrap(), 42),
+            _ => field.value().skip_value().unwrap(),
+        }
+    }
+    assert_eq!(names, vec!["name", "albums", "age"]);
+    assert_eq!(parser.next_event().unwrap(), None);
+
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(b"[1]"));
+    parser.expect_start_array().unwrap();
+    assert!(matches!(
+        parser.next_field(),
+        Err(NextValueError::UnexpectedEvent {
+            found: Some(JsonEvent::ValueInt),
+            ..
+        })
+    ));
+}
+
+/// Test that the boolean value is available for both boolean events and is
+/// reset when the parser needs more input
+#[test]
+fn current_bool() {
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    parser.feeder.push_bytes(b"[true, fal");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.current_bool(), None);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueTrue));
+    assert_eq!(parser.current_bool(), Some(true));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::NeedMoreInput));
+    assert_eq!(parser.current_bool(), None);
+    parser.feeder.push_bytes(b"se]");
+    parser.feeder.done();
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueFalse));
+    assert_eq!(parser.current_bool(), Some(false));
+    assert_eq!(JsonEvent::ValueNull.as_bool(), None);
+}
+
+/// Test that long values are truncated in event descriptions and that
+/// escape sequences are kept
+#[test]
+fn describe_event() {
+    let json = format!(r#"["{}", "a\nb", true]"#, "x".repeat(50));
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    parser.feeder.push_bytes(json.as_bytes());
+    parser.feeder.done();
+
+    let mut lines = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        lines.push(parser.describe_event(e).to_string());
+    }
+    assert_eq!(
+        lines,
+        vec![
+            "StartArray at 0..1".to_string(),
+            format!("ValueString \"{}...\" at 1..53", "x".repeat(40)),
+            r#"ValueString "a\nb" at 55..61"#.to_string(),
+            "ValueTrue at 63..67".to_string(),
+            "EndArray at 67..68".to_string(),
+        ]
+    );
+}
+
+/// Test that peeking does not consume events, also at the end of the JSON
+/// text and when the parser needs more input
+#[test]
+fn peek_event() {
+    let mut parser = JsonParser::new(PushJsonFeeder::new());
+    parser.feeder.push_bytes(b"[1, ");
+    assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::StartArray));
+    assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.current_int::<i32>().unwrap(), 1);
+    assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::NeedMoreInput));
+    parser.feeder.push_bytes(b"\"a\"]");
+    parser.feeder.done();
+    assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::ValueString));
+    assert_eq!(parser.current_str().unwrap(), "a");
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueString));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::EndArray));
+    assert_eq!(parser.peek_event().unwrap(), None);
+    assert_eq!(parser.peek_event().unwrap(), None);
+    assert_eq!(parser.next_event().unwrap(), None);
+    assert!(matches!(parser.next_event(), Err(ParserError::NoMoreInput)));
+
+    // a number at the end of the input is only complete when the feeder is
+    // done
+    let mut parser = JsonParser::new(SliceJsonFeeder::new(b"42"));
+    assert_eq!(parser.peek_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::ValueInt));
+    assert_eq!(parser.current_span(), Span::new(0, 2));
+    assert_eq!(parser.next_event().unwrap(), None);
+}
+
+/// Test that a path filter only returns events of matching values
+#[test]
+fn path_filter() {
+    let json = br#"{
+        "meta": {"id": 0, "items": [{"id": -1}]},
+        "items": [
+            {"id": 1, "tags": ["a", "b"]},
+            {"name": "x", "id": {"nested": 2}},
+            {"id": 3}
+        ],
+        "0": "zero"
+    }"#;
+
+    fn collect(json: &[u8], patterns: &[&str]) -> Vec<(usize, String)> {
+        let patterns = patterns.iter().map(|p| p.parse::<PathPattern>().unwrap());
+        let mut filter = PathFilter::new(JsonParser::new(SliceJsonFeeder::new(json)), patterns);
+        let mut result = Vec::new();
+        while let Some(e) = filter.next_event().unwrap() {
+            let p = filter.current_match().unwrap();
+            result.push((p, filter.parser().describe_event(e).to_string()));
+        }
+        result
+            .into_iter()
+            .map(|(p, s)| (p, s.split(" at ").next().unwrap().to_string()))
+            .collect()
+    }
+
+    assert_eq!(
+        collect(json, &["$.items[*].id"]),
+        vec![
+            (0, "ValueInt 1".to_string()),
+            (0, "StartObject".to_string()),
+            (0, "FieldName \"nested\"".to_string()),
+            (0, "ValueInt 2".to_string()),
+            (0, "EndObject".to_string()),
+            (0, "ValueInt 3".to_string()),
+        ]
+    );
+
+    assert_eq!(
+        collect(json, &["/items/0/tags/1", "/0", "$.items[2]"]),
+        vec![
+            (0, "ValueString \"b\"".to_string()),
+            (2, "StartObject".to_string()),
+            (2, "FieldName \"id\"".to_string()),
+            (2, "ValueInt 3".to_string()),
+            (2, "EndObject".to_string()),
+            (1, "ValueString \"zero\"".to_string()),
+        ]
+    );
+
+    assert_eq!(collect(b"[1, 2]", &["$"]).len(), 4);
+    assert!(collect(json, &["$.missing[*]"]).is_empty());
+}
+
+/// Test that transform adapters can be nested and feed another sink
+#[test]
+fn transform_pipeline() {
+    let json = br#"{
+        "user": {"name": "Elvis", "password": "secret", "roles": [["admin"]]},
+        "tokens": ["a", "b"],
+        "password": "top-level"
+    }"#;
+
+    let mut sink = DropFields::new(
+        RenameKeys::new(
+            Redact::new(
+                LimitDepth::new(PrettyPrinter::new(), 2),
+                ["$.tokens[*]".parse::<PathPattern>().unwrap().into()],
+                "***",
+            ),
+            [("name", "first_name")],
+        ),
+        [FieldSelector::name("password")],
+    );
+    parse_with(SliceJsonFeeder::new(json), &mut sink).unwrap();
+
+    let printer = sink.into_inner().into_inner().into_inner().into_inner();
+    assert_json_eq(
+        r#"{"user": {"first_name": "Elvis", "roles": []}, "tokens": ["***", "***"]}"#,
+        printer.get_result(),
+    );
+}
+
+/// Test that replaying a tape produces the same output as parsing the
+/// original JSON text
+#[test]
+fn tape_replay() {
+    let json = fs::read_to_string("tests/fixtures/pass1.txt").unwrap();
+
+    let mut writer = TapeWriter::new(Vec::new()).unwrap();
+    parse_with(SliceJsonFeeder::new(json.as_bytes()), &mut writer).unwrap();
+    let tape = writer.into_inner();
+
+    let mut prettyprinter = PrettyPrinter::new();
+    TapeReader::new(tape.as_slice())
+        .unwrap()
+        .replay(&mut prettyprinter)
+        .unwrap();
+    assert_json_eq(&json, prettyprinter.get_result());
+}
+
+/// Test that minifying removes all whitespace outside of strings and keeps
+/// the JSON text's value
+#[test]
+fn minify_file() {
+    let json = fs::read_to_string("tests/fixtures/pass1.txt").unwrap();
+    let file = fs::File::open("tests/fixtures/pass1.txt").unwrap();
+    let feeder = BufReaderJsonFeeder::new(std::io::BufReader::with_capacity(16, file));
+    let minified = String::from_utf8(minify(feeder, Vec::new()).unwrap()).unwrap();
+
+    assert_json_eq(&json, &minified);
+    assert!(minified.len() < json.len());
+    assert!(minified.starts_with("[\"JSON Test Pattern pass1\",{\"object with 1 member\""));
+}
+
+/// A reader over a buffer that another thread appends to. Returns 0 at the
+/// current end of the buffer like a file that is still being written.
+struct GrowingReader {
+    data: Arc<std::sync::Mutex<Vec<u8>>>,
+    pos: usize,
+}
+
+impl std::io::Read for GrowingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let data = self.data.lock().unwrap();
+        let n = buf.len().min(data.len() - self.pos);
+        buf[..n].copy_from_slice(&data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Test that a tail feeder waits for appended input and only reports the
+/// end of the input after it has been stopped and everything has been read
+#[test]
+fn tail_follow() {
+    let data = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let token = CancellationToken::new();
+
+    let writer = {
+        let data = data.clone();
+        let token = token.clone();
+        std::thread::spawn(move || {
+            for i in 0..20 {
+                let line = format!("{{\"seq\": {i}}}\n");
+                // split lines, so the parser sees incomplete values
+                let (a, b) = line.as_bytes().split_at(5);
+                data.lock().unwrap().extend_from_slice(a);
+                std::thread::sleep(Duration::from_millis(2));
+                data.lock().unwrap().extend_from_slice(b);
+            }
+            token.cancel();
+        })
+    };
+
+    let feeder = TailJsonFeeder::with_capacity(GrowingReader { data, pos: 0 }, 4)
+        .with_poll_interval(Duration::from_millis(1))
+        .with_stop_token(token);
+    let mut parser = JsonParser::new_with_options(
+        feeder,
+        JsonParserOptionsBuilder::default()
+            .with_streaming(true)
+            .build(),
+    );
+    let mut seqs = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        match e {
+            JsonEvent::NeedMoreInput => parser.feeder.fill_buf().unwrap(),
+            JsonEvent::ValueInt => seqs.push(parser.current_int::<i64>().unwrap()),
+            _ => {}
+        }
+    }
+    writer.join().unwrap();
+    assert_eq!(seqs, (0..20).collect::<Vec<_>>());
+
+    // an idle timeout ends empty input
+    let feeder = TailJsonFeeder::new(std::io::empty())
+        .with_poll_interval(Duration::from_millis(1))
+        .with_idle_timeout(Some(Duration::from_millis(10)));
+    let mut parser = JsonParser::new(feeder);
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::NeedMoreInput));
+    parser.feeder.fill_buf().unwrap();
+    assert!(parser.feeder.is_done());
+}
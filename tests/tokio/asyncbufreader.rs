@@ -2,8 +2,9 @@ use serde_json::Value;
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, BufReader};
 
-use crate::prettyprinter::PrettyPrinter;
+use actson::emitter::PrettyPrinter;
 use actson::feeder::JsonFeeder;
+use actson::sink::JsonEventSink;
 use actson::tokio::AsyncBufReaderJsonFeeder;
 use actson::{JsonEvent, JsonParser};
 
@@ -0,0 +1,55 @@
+use std::io;
+
+use bytes::Bytes;
+use futures::stream;
+use serde_json::Value;
+
+use actson::emitter::PrettyPrinter;
+use actson::feeder::FillError;
+use actson::sink::JsonEventSink;
+use actson::tokio::BytesStreamJsonFeeder;
+use actson::{JsonEvent, JsonParser};
+
+/// Test that a JSON text split into frames at arbitrary positions (including
+/// empty frames) can be parsed
+#[tokio::test]
+async fn parse_frames() {
+    let json = std::fs::read_to_string("tests/fixtures/pass1.txt").unwrap();
+    let frames = json
+        .as_bytes()
+        .chunks(7)
+        .flat_map(|c| [Bytes::new(), Bytes::copy_from_slice(c)])
+        .map(Ok::<_, io::Error>)
+        .collect::<Vec<_>>();
+
+    let mut parser = JsonParser::new(BytesStreamJsonFeeder::new(stream::iter(frames)));
+    let mut printer = PrettyPrinter::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        if e == JsonEvent::NeedMoreInput {
+            parser.feeder.fill_buf().await.unwrap();
+        } else {
+            printer.on_event(e, &parser).unwrap();
+        }
+    }
+
+    let expected: Value = serde_json::from_str(&json).unwrap();
+    let actual: Value = serde_json::from_str(printer.get_result()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+/// Test that errors of the stream are passed on
+#[tokio::test]
+async fn stream_error() {
+    let frames = stream::iter([
+        Ok(Bytes::from_static(b"[1, ")),
+        Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")),
+    ]);
+    let mut parser = JsonParser::new(BytesStreamJsonFeeder::new(frames));
+    assert_eq!(parser.next_event().unwrap(), Some(JsonEvent::NeedMoreInput));
+    parser.feeder.fill_buf().await.unwrap();
+    while parser.next_event().unwrap() != Some(JsonEvent::NeedMoreInput) {}
+    assert!(matches!(
+        parser.feeder.fill_buf().await,
+        Err(FillError::Io(e)) if e.kind() == io::ErrorKind::Other
+    ));
+}
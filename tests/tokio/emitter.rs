@@ -0,0 +1,33 @@
+use serde_json::Value;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, BufReader};
+
+use actson::tokio::{AsyncBufReaderJsonFeeder, AsyncJsonEmitter};
+use actson::{JsonEvent, JsonParser};
+
+/// Test if [`AsyncJsonEmitter`] re-emits all events of a JSON file
+#[tokio::test]
+async fn emit_from_file() {
+    let expected: Value = {
+        let mut buf = Vec::new();
+        let mut file = File::open("tests/fixtures/pass1.txt").await.unwrap();
+        file.read_to_end(&mut buf).await.unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    };
+
+    let file = File::open("tests/fixtures/pass1.txt").await.unwrap();
+    let reader = BufReader::with_capacity(32, file);
+    let mut parser = JsonParser::new(AsyncBufReaderJsonFeeder::new(reader));
+
+    let mut emitter = AsyncJsonEmitter::new(Vec::new());
+    while let Some(e) = parser.next_event().unwrap() {
+        if e == JsonEvent::NeedMoreInput {
+            parser.feeder.fill_buf().await.unwrap();
+        } else {
+            emitter.emit_event(e, &parser).await.unwrap();
+        }
+    }
+
+    let json = emitter.finish().await.unwrap();
+    assert_eq!(expected, serde_json::from_slice::<Value>(&json).unwrap());
+}
@@ -1 +1,5 @@
 mod asyncbufreader;
+mod bytestream;
+mod emitter;
+mod push;
+mod stream;
@@ -0,0 +1,74 @@
+use std::io;
+
+use bytes::Bytes;
+use futures::{stream, SinkExt, StreamExt};
+use serde_json::Value;
+
+use actson::emitter::PrettyPrinter;
+use actson::sink::JsonEventSink;
+use actson::tokio::AsyncPushJsonFeeder;
+use actson::{JsonEvent, JsonParser};
+
+/// Test that a stream of frames can be forwarded to the sink while the JSON
+/// text is being parsed, even if the feeder can only buffer one frame
+#[tokio::test]
+async fn forward() {
+    let json = std::fs::read_to_string("tests/fixtures/pass1.txt").unwrap();
+    let frames = json
+        .as_bytes()
+        .chunks(7)
+        .flat_map(|c| [Bytes::new(), Bytes::copy_from_slice(c)])
+        .map(Ok::<_, io::Error>)
+        .collect::<Vec<_>>();
+
+    let (sink, feeder) = AsyncPushJsonFeeder::with_capacity(1);
+    let producer = tokio::spawn(stream::iter(frames).forward(sink));
+
+    let mut parser = JsonParser::new(feeder);
+    let mut printer = PrettyPrinter::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        if e == JsonEvent::NeedMoreInput {
+            parser.feeder.fill_buf().await.unwrap();
+        } else {
+            printer.on_event(e, &parser).unwrap();
+        }
+    }
+    producer.await.unwrap().unwrap();
+
+    let expected: Value = serde_json::from_str(&json).unwrap();
+    let actual: Value = serde_json::from_str(printer.get_result()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+/// Test that dropping the sink ends the JSON text and that sending fails
+/// once the feeder has been dropped
+#[tokio::test]
+async fn drop_halves() {
+    let (mut sink, feeder) = AsyncPushJsonFeeder::new();
+    sink.send(Bytes::from_static(b"[1, 2]")).await.unwrap();
+    drop(sink);
+
+    let mut parser = JsonParser::new(feeder);
+    let mut events = Vec::new();
+    while let Some(e) = parser.next_event().unwrap() {
+        if e == JsonEvent::NeedMoreInput {
+            parser.feeder.fill_buf().await.unwrap();
+        } else {
+            events.push(e);
+        }
+    }
+    assert_eq!(
+        events,
+        [
+            JsonEvent::StartArray,
+            JsonEvent::ValueInt,
+            JsonEvent::ValueInt,
+            JsonEvent::EndArray
+        ]
+    );
+
+    let (mut sink, feeder) = AsyncPushJsonFeeder::new();
+    drop(feeder);
+    let err = sink.send(Bytes::from_static(b"1")).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
@@ -0,0 +1,96 @@
+use futures::StreamExt;
+use serde_json::Value;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, BufReader};
+
+use actson::feeder::SliceJsonFeeder;
+use actson::parser::{NextValueError, ParserError};
+use actson::tokio::{AsyncBufReaderJsonFeeder, JsonEventStream, JsonEventStreamError};
+use actson::{JsonParser, OwnedJsonEvent};
+
+/// Test if [`JsonEventStream`] yields all events of a JSON file
+#[tokio::test]
+async fn stream_from_file() {
+    let expected: Value = {
+        let mut buf = Vec::new();
+        let mut file = File::open("tests/fixtures/pass1.txt").await.unwrap();
+        file.read_to_end(&mut buf).await.unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    };
+
+    let file = File::open("tests/fixtures/pass1.txt").await.unwrap();
+    let reader = BufReader::with_capacity(32, file);
+    let stream = JsonEventStream::new(JsonParser::new(AsyncBufReaderJsonFeeder::new(reader)));
+
+    let events = stream.map(|e| e.unwrap()).collect::<Vec<_>>().await;
+    assert!(!events.contains(&OwnedJsonEvent::NeedMoreInput));
+
+    let strings = events
+        .iter()
+        .filter(|e| matches!(e, OwnedJsonEvent::ValueString(_)))
+        .count();
+    fn count_strings(v: &Value) -> usize {
+        match v {
+            Value::String(_) => 1,
+            Value::Array(a) => a.iter().map(count_strings).sum(),
+            Value::Object(o) => o.values().map(count_strings).sum(),
+            _ => 0,
+        }
+    }
+    assert_eq!(strings, count_strings(&expected));
+}
+
+/// Test if [`JsonEventStream`] ends after an error
+#[tokio::test]
+async fn stream_error() {
+    let reader = BufReader::new(&b"[1, }"[..]);
+    let mut stream = JsonEventStream::new(JsonParser::new(AsyncBufReaderJsonFeeder::new(reader)));
+
+    assert_eq!(
+        stream.next().await.unwrap().unwrap(),
+        OwnedJsonEvent::StartArray
+    );
+    assert_eq!(
+        stream.next().await.unwrap().unwrap(),
+        OwnedJsonEvent::ValueInt(1)
+    );
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(JsonEventStreamError::Parser(NextValueError::Parser(
+            ParserError::SyntaxError
+        ))))
+    ));
+    assert!(stream.next().await.is_none());
+}
+
+/// Test that [`JsonParser::poll_next_event()`] fills the feeder on its own
+/// and returns the same events as [`JsonParser::next_event()`]
+#[tokio::test]
+async fn poll_next_event() {
+    let json = std::fs::read("tests/fixtures/pass1.txt").unwrap();
+    let mut expected = JsonParser::new(SliceJsonFeeder::new(&json));
+
+    let file = File::open("tests/fixtures/pass1.txt").await.unwrap();
+    let reader = BufReader::with_capacity(32, file);
+    let mut parser = JsonParser::new(AsyncBufReaderJsonFeeder::new(reader));
+    while let Some(e) = std::future::poll_fn(|cx| parser.poll_next_event(cx))
+        .await
+        .unwrap()
+    {
+        assert_eq!(Some(e), expected.next_event().unwrap());
+    }
+    assert_eq!(expected.next_event().unwrap(), None);
+
+    let reader = BufReader::new(&b"[1}"[..]);
+    let mut parser = JsonParser::new(AsyncBufReaderJsonFeeder::new(reader));
+    let r = loop {
+        match std::future::poll_fn(|cx| parser.poll_next_event(cx)).await {
+            Ok(_) => {}
+            Err(e) => break e,
+        }
+    };
+    assert!(matches!(
+        r,
+        JsonEventStreamError::Parser(NextValueError::Parser(ParserError::SyntaxError))
+    ));
+}